@@ -18,21 +18,39 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 
-// use crate::Float;
+use crate::contaminants::{SpaceContaminants, DEFAULT_OUTDOOR_CO2};
+use crate::heat_recovery::HeatRecovery;
+use crate::humidity::SpaceHumidity;
+use crate::natural_ventilation::NaturalVentilation;
+use crate::network::AirFlowNetwork;
 use crate::resolvers::*;
+use crate::Float;
 use calendar::Date;
 use communication::{ErrorHandling, MetaOptions, SimulationModel};
 use model::{
     Infiltration, Model, SimulationState, SimulationStateElement, SimulationStateHeader,
-    SiteDetails,
+    SiteDetails, HVAC,
 };
 use std::borrow::Borrow;
+use std::sync::Arc;
 use weather::{CurrentWeather, WeatherTrait};
 
 pub type Resolver = Box<dyn Fn(&CurrentWeather, &mut SimulationState) -> Result<(), String>>;
 
 pub struct AirFlowModel {
     infiltration_calcs: Vec<Resolver>,
+
+    /// The CO2 and contaminant balance of each space
+    contaminants: Vec<SpaceContaminants>,
+
+    /// The moisture balance of each space
+    humidity: Vec<SpaceHumidity>,
+
+    /// The CO2 concentration outdoors, in ppm
+    outdoor_co2: Float,
+
+    /// The timestep, in seconds
+    dt: Float,
 }
 
 impl ErrorHandling for AirFlowModel {
@@ -59,7 +77,7 @@ impl SimulationModel for AirFlowModel {
         _options: (),
         model: M,
         state: &mut SimulationStateHeader,
-        _n: usize,
+        n: usize,
     ) -> Result<Self, String> {
         let mut infiltration_calcs = Vec::with_capacity(model.borrow().spaces.len());
 
@@ -95,6 +113,30 @@ impl SimulationModel for AirFlowModel {
                     Infiltration::EffectiveAirLeakageArea { area } => {
                         effective_air_leakage_resolver(space, model.borrow(), *area)?
                     }
+                    // Solved by the AirFlowNetwork, below
+                    Infiltration::AirFlowNetwork => Box::new(
+                        move |_current_weather: &CurrentWeather,
+                              _state: &mut SimulationState|
+                              -> Result<(), String> { Ok(()) },
+                    ),
+                };
+                // Scale the infiltration by its schedule, if any
+                let infiltration_fn = if let Ok(sched_name) = space.infiltration_schedule() {
+                    let schedule = model.borrow().get_schedule(sched_name)?;
+                    let space_clone = Arc::clone(space);
+                    Box::new(
+                        move |current_weather: &CurrentWeather,
+                              state: &mut SimulationState|
+                              -> Result<(), String> {
+                            infiltration_fn(current_weather, state)?;
+                            let v = space_clone.infiltration_volume(state).unwrap_or(0.0);
+                            let factor = schedule.value(current_weather.date)?;
+                            space_clone.set_infiltration_volume(state, v * factor)?;
+                            Ok(())
+                        },
+                    )
+                } else {
+                    infiltration_fn
                 };
                 infiltration_calcs.push(infiltration_fn);
             } else {
@@ -107,7 +149,67 @@ impl SimulationModel for AirFlowModel {
             }
         }
 
-        Ok(AirFlowModel { infiltration_calcs })
+        if let Some(network) = AirFlowNetwork::new(model.borrow(), &site_details, state)? {
+            infiltration_calcs.push(Box::new(
+                move |current_weather: &CurrentWeather,
+                      state: &mut SimulationState|
+                      -> Result<(), String> {
+                    network.solve(current_weather, state)
+                },
+            ));
+        }
+
+        // Operable windows in spaces that are not in the network
+        for vent in NaturalVentilation::new(model.borrow(), &site_details, state)? {
+            infiltration_calcs.push(Box::new(
+                move |current_weather: &CurrentWeather,
+                      state: &mut SimulationState|
+                      -> Result<(), String> {
+                    vent.resolve(current_weather, state)
+                },
+            ));
+        }
+
+        // Mechanical ventilation... after the network and the windows, so it
+        // can be mixed with the flows they calculate
+        for hvac in model.borrow().hvacs.iter() {
+            if let HVAC::HeatRecoveryVentilator(ventilator) = hvac {
+                let hrv = HeatRecovery::new(ventilator, model.borrow(), state)?;
+                infiltration_calcs.push(Box::new(
+                    move |current_weather: &CurrentWeather,
+                          state: &mut SimulationState|
+                          -> Result<(), String> {
+                        hrv.resolve(current_weather, state)
+                    },
+                ));
+            }
+        }
+
+        // Contaminants
+        let outdoor_co2 =
+            site_details.outdoor_co2_concentration_or(Self::module_name(), DEFAULT_OUTDOOR_CO2);
+        let mut contaminants = Vec::with_capacity(model.borrow().spaces.len());
+        for (i, space) in model.borrow().spaces.iter().enumerate() {
+            if let Some(c) = SpaceContaminants::new(space, i, model.borrow(), state, outdoor_co2)? {
+                contaminants.push(c);
+            }
+        }
+
+        // Humidity
+        let mut humidity = Vec::with_capacity(model.borrow().spaces.len());
+        for (i, space) in model.borrow().spaces.iter().enumerate() {
+            if let Some(h) = SpaceHumidity::new(space, i, model.borrow(), state)? {
+                humidity.push(h);
+            }
+        }
+
+        Ok(AirFlowModel {
+            infiltration_calcs,
+            contaminants,
+            humidity,
+            outdoor_co2,
+            dt: 60. * 60. / n as Float,
+        })
     }
 
     /// Advances one main_timestep through time. That is,
@@ -127,6 +229,14 @@ impl SimulationModel for AirFlowModel {
             func(&current_weather, state)?;
         }
 
+        // Then, the contaminants carried by those flows
+        for c in self.contaminants.iter() {
+            c.march(date, self.dt, self.outdoor_co2, state)?;
+        }
+        for h in self.humidity.iter() {
+            h.march(date, self.dt, &current_weather, state)?;
+        }
+
         Ok(())
     }
 }
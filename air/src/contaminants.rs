@@ -0,0 +1,223 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::Float;
+use calendar::Date;
+use model::{
+    Model, Schedule, SimulationState, SimulationStateElement, SimulationStateHeader, Space,
+};
+use std::sync::Arc;
+
+/// The CO2 exhaled by a person doing light office work, in m3/s (ASHRAE Fundamentals)
+const CO2_GENERATION_PER_PERSON: Float = 5.2e-6;
+
+/// The CO2 concentration of the outdoor air, when not given in the `SiteDetails`
+pub(crate) const DEFAULT_OUTDOOR_CO2: Float = 400.;
+
+/// Calculates the concentration of CO2 and generic contaminants in a [`Space`],
+/// through a mass balance that considers the contaminants generated inside
+/// (i.e., people and other sources) and the air entering the space through
+/// infiltration and ventilation.
+///
+/// The air entering the space is assumed to carry the outdoor concentration.
+pub(crate) struct SpaceContaminants {
+    space: Arc<Space>,
+
+    /// The volume of the space
+    volume: Float,
+
+    /// The schedule with the number of people in the space
+    occupancy: Option<Arc<Schedule>>,
+
+    /// The generic contaminant generation rate, if any
+    generic_generation: Option<Float>,
+}
+
+impl SpaceContaminants {
+    /// Builds a new [`SpaceContaminants`], pushing the concentrations into the state.
+    ///
+    /// Returns `None` if the space has no volume.
+    pub fn new(
+        space: &Arc<Space>,
+        space_index: usize,
+        model: &Model,
+        state: &mut SimulationStateHeader,
+        outdoor_co2: Float,
+    ) -> Result<Option<Self>, String> {
+        let volume = match space.volume() {
+            Ok(v) => *v,
+            Err(_) => return Ok(None),
+        };
+
+        let occupancy = match space.occupancy_schedule() {
+            Ok(name) => Some(model.get_schedule(name)?),
+            Err(_) => None,
+        };
+
+        let co2_index = state.push(
+            SimulationStateElement::SpaceCO2Concentration(space_index),
+            outdoor_co2,
+        )?;
+        space.set_co2_concentration_index(co2_index)?;
+
+        let generic_generation = space.generic_contaminant_generation().ok().copied();
+        if generic_generation.is_some() {
+            let index = state.push(
+                SimulationStateElement::SpaceGenericContaminantConcentration(space_index),
+                0.,
+            )?;
+            space.set_generic_contaminant_concentration_index(index)?;
+        }
+
+        Ok(Some(Self {
+            space: Arc::clone(space),
+            volume,
+            occupancy,
+            generic_generation,
+        }))
+    }
+
+    /// Advances the concentrations `dt` seconds, through an implicit
+    /// Euler step of the mass balance:
+    ///
+    /// ```math
+    /// V \frac{dC}{dt} = Q (C_{out} - C) + 10^6 G
+    /// ```
+    pub fn march(
+        &self,
+        date: Date,
+        dt: Float,
+        outdoor_co2: Float,
+        state: &mut SimulationState,
+    ) -> Result<(), String> {
+        let q = self.space.infiltration_volume(state).unwrap_or(0.)
+            + self.space.ventilation_volume(state).unwrap_or(0.);
+
+        let step = |c: Float, c_out: Float, generation: Float| -> Float {
+            (self.volume * c + dt * (q * c_out + 1e6 * generation)) / (self.volume + dt * q)
+        };
+
+        // CO2
+        let people = match &self.occupancy {
+            Some(s) => s.value(date)?,
+            None => 0.,
+        };
+        let c = self
+            .space
+            .co2_concentration(state)
+            .ok_or("Space has no CO2 concentration state")?;
+        let c = step(c, outdoor_co2, people * CO2_GENERATION_PER_PERSON);
+        self.space.set_co2_concentration(state, c)?;
+
+        // Generic
+        if let Some(generation) = self.generic_generation {
+            let c = self
+                .space
+                .generic_contaminant_concentration(state)
+                .ok_or("Space has no generic contaminant concentration state")?;
+            let c = step(c, 0., generation);
+            self.space.set_generic_contaminant_concentration(state, c)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use model::{Infiltration, ScheduleProfile};
+
+    fn get_model(people: Float) -> Result<(Model, SimulationStateHeader), String> {
+        let mut model = Model::default();
+        let mut header = SimulationStateHeader::new();
+        model.add_schedule(Schedule::new(
+            "people",
+            ScheduleProfile::Constant { value: people },
+        ));
+
+        let mut space = Space::new("the space");
+        space
+            .set_volume(50.)
+            .set_infiltration(Infiltration::Constant { flow: 0.01 })
+            .set_occupancy_schedule("people")
+            .set_generic_contaminant_generation(1e-6);
+        let i = header.push(SimulationStateElement::SpaceInfiltrationVolume(0), 0.01)?;
+        space.set_infiltration_volume_index(i)?;
+        model.add_space(space);
+        Ok((model, header))
+    }
+
+    #[test]
+    fn test_steady_state() -> Result<(), String> {
+        let people = 2.;
+        let (model, mut header) = get_model(people)?;
+        let space = &model.spaces[0];
+        let contaminants = SpaceContaminants::new(space, 0, &model, &mut header, 400.)?
+            .ok_or("Expecting contaminants")?;
+        let mut state = header.take_values().ok_or("Could not take values")?;
+        assert_eq!(space.co2_concentration(&state), Some(400.));
+
+        let date = Date {
+            month: 1,
+            day: 1,
+            hour: 12.,
+            year: None,
+        };
+        for _ in 0..1000 {
+            contaminants.march(date, 600., 400., &mut state)?;
+        }
+
+        // At steady state, Q (C - C_out) = G
+        let q = 0.01;
+        let expected = 400. + 1e6 * people * CO2_GENERATION_PER_PERSON / q;
+        let found = space.co2_concentration(&state).ok_or("No CO2")?;
+        assert!(
+            (expected - found).abs() < 1e-3,
+            "expected {}, found {}",
+            expected,
+            found
+        );
+
+        let expected = 1e6 * 1e-6 / q;
+        let found = space
+            .generic_contaminant_concentration(&state)
+            .ok_or("No contaminant")?;
+        assert!(
+            (expected - found).abs() < 1e-3,
+            "expected {}, found {}",
+            expected,
+            found
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_volume() -> Result<(), String> {
+        let model = Model::default();
+        let mut header = SimulationStateHeader::new();
+        let space = Arc::new(Space::new("the space"));
+        let contaminants = SpaceContaminants::new(&space, 0, &model, &mut header, 400.)?;
+        assert!(contaminants.is_none());
+        assert_eq!(header.len(), 0);
+        Ok(())
+    }
+}
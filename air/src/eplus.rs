@@ -150,6 +150,7 @@ mod tests {
             month: 1,
             day: 1,
             hour: 1.,
+            year: None,
         };
 
         let design_rate = 1.;
@@ -173,6 +174,7 @@ mod tests {
             month: 1,
             day: 1,
             hour: 1.,
+            year: None,
         };
 
         let design_rate = 1.;
@@ -208,6 +210,7 @@ mod tests {
             month: 1,
             day: 1,
             hour: 1.,
+            year: None,
         };
 
         let design_rate = 1.;
@@ -231,6 +234,7 @@ mod tests {
             month: 1,
             day: 1,
             hour: 1.,
+            year: None,
         };
 
         let design_rate = 1.;
@@ -254,6 +258,7 @@ mod tests {
             month: 1,
             day: 1,
             hour: 1.,
+            year: None,
         };
 
         let design_rate = 1.;
@@ -293,6 +298,7 @@ mod tests {
             month: 1,
             day: 1,
             hour: 1.,
+            year: None,
         };
 
         let current_weather = weather.get_weather_data(date);
@@ -0,0 +1,183 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::Float;
+use model::hvac::HeatRecoveryVentilator;
+use model::{Model, SimulationState, SimulationStateElement, SimulationStateHeader, Space};
+use std::sync::Arc;
+use weather::CurrentWeather;
+
+/// The sensible effectiveness of a [`HeatRecoveryVentilator`] when none is given
+const DEFAULT_SENSIBLE_EFFECTIVENESS: Float = 0.7;
+
+/// The module name. For debugging purposes
+const MODULE_NAME: &str = "Heat recovery";
+
+/// Calculates the air supplied by a [`HeatRecoveryVentilator`] into its
+/// target [`Space`], which is reported as ventilation.
+pub(crate) struct HeatRecovery {
+    ventilator: Arc<HeatRecoveryVentilator>,
+
+    space: Arc<Space>,
+
+    /// The maximum air flow
+    air_flow: Float,
+
+    /// The fraction of the temperature difference that is recovered
+    sensible_effectiveness: Float,
+
+    /// Whether the ventilation of the space is also calculated by
+    /// something else (e.g., the [`AirFlowNetwork`](crate::network::AirFlowNetwork)),
+    /// in which case the air supplied is mixed with the ventilation
+    /// that is already there.
+    mix: bool,
+}
+
+impl HeatRecovery {
+    /// Creates a new [`HeatRecovery`], pushing the ventilation elements
+    /// of the target space into the state if needed.
+    pub fn new(
+        ventilator: &Arc<HeatRecoveryVentilator>,
+        model: &Model,
+        state: &mut SimulationStateHeader,
+    ) -> Result<Self, String> {
+        let space_name = ventilator.target_space()?;
+        let space_index = model
+            .spaces
+            .iter()
+            .position(|s| s.name() == space_name)
+            .ok_or_else(|| {
+                format!(
+                    "HeatRecoveryVentilator '{}' is supposed to be in a space called '{}'... but it was not found",
+                    ventilator.name(),
+                    space_name
+                )
+            })?;
+        let space = Arc::clone(&model.spaces[space_index]);
+
+        let mix = space.ventilation_volume_index().is_some();
+        if !mix {
+            let i = state.push(
+                SimulationStateElement::SpaceVentilationVolume(space_index),
+                0.,
+            )?;
+            space.set_ventilation_volume_index(i)?;
+            let i = state.push(
+                SimulationStateElement::SpaceVentilationTemperature(space_index),
+                0.,
+            )?;
+            space.set_ventilation_temperature_index(i)?;
+        }
+
+        Ok(Self {
+            ventilator: Arc::clone(ventilator),
+            space,
+            air_flow: *ventilator.air_flow()?,
+            sensible_effectiveness: ventilator
+                .sensible_effectiveness_or(MODULE_NAME, DEFAULT_SENSIBLE_EFFECTIVENESS),
+            mix,
+        })
+    }
+
+    /// Calculates the volume and temperature of the air supplied
+    pub fn resolve(
+        &self,
+        weather: &CurrentWeather,
+        state: &mut SimulationState,
+    ) -> Result<(), String> {
+        let fraction = self
+            .ventilator
+            .air_flow_fraction(state)
+            .ok_or("HeatRecoveryVentilator has no air flow fraction")?;
+        let flow = self.air_flow * fraction.clamp(0., 1.);
+
+        // If the space temperature is not there, there is nothing to recover
+        let t_out = weather.dry_bulb_temperature;
+        let t_space = self.space.dry_bulb_temperature(state).unwrap_or(t_out);
+        let t_supply = t_out + self.sensible_effectiveness * (t_space - t_out);
+
+        let (volume, temperature) = if self.mix {
+            let v = self.space.ventilation_volume(state).unwrap_or(0.);
+            let t = self
+                .space
+                .ventilation_temperature(state)
+                .unwrap_or(t_supply);
+            let total = v + flow;
+            if total > 0. {
+                (total, (v * t + flow * t_supply) / total)
+            } else {
+                (0., t_supply)
+            }
+        } else {
+            (flow, t_supply)
+        };
+        self.space.set_ventilation_volume(state, volume)?;
+        self.space.set_ventilation_temperature(state, temperature)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn test_resolve() -> Result<(), String> {
+        let mut model = Model::default();
+        let mut ventilator = HeatRecoveryVentilator::new("HRV");
+        ventilator
+            .set_target_space("Bedroom")
+            .set_air_flow(0.02)
+            .set_sensible_effectiveness(0.8);
+        let ventilator = match model.add_hvac(ventilator.wrap())? {
+            model::HVAC::HeatRecoveryVentilator(v) => v,
+            _ => unreachable!(),
+        };
+        let mut header = model.take_state().ok_or("Could not take state")?;
+
+        let space = Space::new("Bedroom");
+        let i = header.push(SimulationStateElement::SpaceDryBulbTemperature(0), 20.)?;
+        space.set_dry_bulb_temperature_index(i)?;
+        model.add_space(space);
+
+        let hrv = HeatRecovery::new(&ventilator, &model, &mut header)?;
+        let mut state = header.take_values().ok_or("Could not take values")?;
+
+        let weather = CurrentWeather {
+            dry_bulb_temperature: -5.,
+            ..CurrentWeather::default()
+        };
+        hrv.resolve(&weather, &mut state)?;
+        let space = &model.spaces[0];
+        assert_eq!(space.ventilation_volume(&state), Some(0.02));
+        let t = space
+            .ventilation_temperature(&state)
+            .ok_or("No temperature")?;
+        assert!((t - 15.).abs() < 1e-5, "t = {}", t);
+
+        // Half the flow
+        ventilator.set_air_flow_fraction(&mut state, 0.5)?;
+        hrv.resolve(&weather, &mut state)?;
+        let v = space.ventilation_volume(&state).ok_or("No volume")?;
+        assert!((v - 0.01).abs() < 1e-7, "v = {}", v);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,318 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::network::air_density;
+use crate::Float;
+use calendar::Date;
+use model::hvac::{HeatRecoveryVentilator, IdealHeaterCooler};
+use model::{
+    ElectricEquipment, Model, Schedule, SimulationState, SimulationStateElement,
+    SimulationStateHeader, Space, HVAC,
+};
+use std::sync::Arc;
+use weather::CurrentWeather;
+
+/// The latent heat of vaporization of water, in J/kg
+const HEAT_OF_VAPORIZATION: Float = 2.45e6;
+
+/// The moisture released by a seated person doing light work (i.e., 45W of
+/// latent heat, according to ASHRAE Fundamentals), in kg/s
+const MOISTURE_GENERATION_PER_PERSON: Float = 45. / HEAT_OF_VAPORIZATION;
+
+/// The humidity ratio of the spaces at the beginning of the simulation, in
+/// kg of water per kg of dry air (i.e., roughly 20C and 55% relative humidity)
+const INITIAL_HUMIDITY_RATIO: Float = 0.008;
+
+pub use weather::psychrometrics::{
+    humidity_ratio, relative_humidity, saturation_pressure, STANDARD_PRESSURE,
+};
+
+/// Calculates the humidity ratio of the air in a [`Space`], through a
+/// moisture balance that considers the moisture released by people and
+/// [`ElectricEquipment`], the moisture added or removed by the
+/// [`IdealHeaterCooler`]s controlling its humidity, and the air entering the
+/// space through infiltration and ventilation.
+///
+/// The air supplied by [`HeatRecoveryVentilator`]s with a `latent_effectiveness`
+/// brings back part of the moisture of the air they extract.
+///
+/// The air entering the space is assumed to carry the outdoor humidity ratio.
+pub(crate) struct SpaceHumidity {
+    space: Arc<Space>,
+
+    /// The volume of the space
+    volume: Float,
+
+    /// The schedule with the number of people in the space
+    occupancy: Option<Arc<Schedule>>,
+
+    /// The equipment in the space, and the fraction of their
+    /// power that is released as moisture
+    equipment: Vec<(Arc<ElectricEquipment>, Float)>,
+
+    /// The HVACs humidifying or dehumidifying the space
+    hvacs: Vec<Arc<IdealHeaterCooler>>,
+
+    /// The ventilators recovering moisture, and their latent effectiveness
+    ventilators: Vec<(Arc<HeatRecoveryVentilator>, Float)>,
+}
+
+impl SpaceHumidity {
+    /// Builds a new [`SpaceHumidity`], pushing the humidity ratio into the state.
+    ///
+    /// Returns `None` if the space has no volume.
+    pub fn new(
+        space: &Arc<Space>,
+        space_index: usize,
+        model: &Model,
+        state: &mut SimulationStateHeader,
+    ) -> Result<Option<Self>, String> {
+        let volume = match space.volume() {
+            Ok(v) => *v,
+            Err(_) => return Ok(None),
+        };
+
+        let occupancy = match space.occupancy_schedule() {
+            Ok(name) => Some(model.get_schedule(name)?),
+            Err(_) => None,
+        };
+
+        let equipment = model
+            .electric_equipment
+            .iter()
+            .filter_map(|e| match (e.target_space(), e.fraction_latent()) {
+                (Ok(target), Ok(fraction)) if target == space.name() => {
+                    Some((Arc::clone(e), *fraction))
+                }
+                _ => None,
+            })
+            .collect();
+
+        let hvacs = model
+            .hvacs
+            .iter()
+            .filter_map(|h| match h {
+                HVAC::IdealHeaterCooler(h) if h.controls_humidity() => match h.target_space() {
+                    Ok(target) if target == space.name() => Some(Arc::clone(h)),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect();
+
+        let ventilators = model
+            .hvacs
+            .iter()
+            .filter_map(|h| match h {
+                HVAC::HeatRecoveryVentilator(h) => {
+                    match (h.target_space(), h.latent_effectiveness()) {
+                        (Ok(target), Ok(e)) if target == space.name() => Some((Arc::clone(h), *e)),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            })
+            .collect();
+
+        let index = state.push(
+            SimulationStateElement::SpaceHumidityRatio(space_index),
+            INITIAL_HUMIDITY_RATIO,
+        )?;
+        space.set_humidity_ratio_index(index)?;
+
+        Ok(Some(Self {
+            space: Arc::clone(space),
+            volume,
+            occupancy,
+            equipment,
+            hvacs,
+            ventilators,
+        }))
+    }
+
+    /// Advances the humidity ratio `dt` seconds, through an implicit
+    /// Euler step of the moisture balance:
+    ///
+    /// ```math
+    /// \rho V \frac{dW}{dt} = \dot{m} (W_{out} - W) + G
+    /// ```
+    pub fn march(
+        &self,
+        date: Date,
+        dt: Float,
+        weather: &CurrentWeather,
+        state: &mut SimulationState,
+    ) -> Result<(), String> {
+        let t_out = weather.dry_bulb_temperature;
+        let w_out = weather.humidity_ratio();
+
+        // Mass of air coming in
+        let mut m_in = 0.0;
+        if let Some(v) = self.space.infiltration_volume(state) {
+            let t = self.space.infiltration_temperature(state).unwrap_or(t_out);
+            m_in += v * air_density(t);
+        }
+        if let Some(v) = self.space.ventilation_volume(state) {
+            let t = self.space.ventilation_temperature(state).unwrap_or(t_out);
+            m_in += v * air_density(t);
+        }
+
+        // Part of the air supplied by ventilators brings back the moisture
+        // of the air they extract, so it does not count as exchange
+        for (v, effectiveness) in self.ventilators.iter() {
+            let fraction = v.air_flow_fraction(state).unwrap_or(0.).clamp(0., 1.);
+            let flow = v.air_flow_or("", 0.) * fraction;
+            m_in -= effectiveness * flow * air_density(t_out);
+        }
+        let m_in = m_in.max(0.);
+
+        // Moisture generated
+        let people = match &self.occupancy {
+            Some(s) => s.value(date)?,
+            None => 0.,
+        };
+        let mut generation = people * MOISTURE_GENERATION_PER_PERSON;
+        for (e, fraction) in self.equipment.iter() {
+            let power = e.power_consumption(state).unwrap_or(0.);
+            generation += power * fraction / HEAT_OF_VAPORIZATION;
+        }
+        for h in self.hvacs.iter() {
+            let latent = h.latent_heating_cooling_consumption(state).unwrap_or(0.);
+            generation += latent / HEAT_OF_VAPORIZATION;
+        }
+
+        let t_in = self.space.dry_bulb_temperature(state).unwrap_or(20.);
+        let mass = air_density(t_in) * self.volume;
+        let w = self
+            .space
+            .humidity_ratio(state)
+            .ok_or("Space has no humidity ratio state")?;
+        let w = (mass * w + dt * (m_in * w_out + generation)) / (mass + dt * m_in);
+        // dehumidifying cannot go below zero
+        let w = w.max(0.);
+        self.space.set_humidity_ratio(state, w)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use model::{Infiltration, ScheduleProfile};
+
+    #[test]
+    fn test_humidity_ratio() {
+        // From the psychrometric chart: 20C and 50% RH is about 7.3g/kg
+        let w = humidity_ratio(20., 0.5, STANDARD_PRESSURE);
+        assert!((w - 0.00726).abs() < 1e-4, "w = {}", w);
+
+        // Saturation pressure of water at 0C is about 611.2 Pa
+        let p = saturation_pressure(0.);
+        assert!((p - 611.2).abs() < 1., "p = {}", p);
+
+        // Back and forth
+        let rh = relative_humidity(20., w, STANDARD_PRESSURE);
+        assert!((rh - 0.5).abs() < 1e-5, "rh = {}", rh);
+    }
+
+    #[test]
+    fn test_steady_state() -> Result<(), String> {
+        let people = 3.;
+        let flow = 0.02;
+        let power = 1000.;
+        let fraction_latent = 0.5;
+
+        let mut model = Model::default();
+        model.add_schedule(Schedule::new(
+            "people",
+            ScheduleProfile::Constant { value: people },
+        ));
+
+        // Operational elements go first
+        let mut equipment = ElectricEquipment::new("kettle");
+        equipment
+            .set_target_space("the space")
+            .set_fraction_latent(fraction_latent);
+        let equipment = model.add_electric_equipment(equipment)?;
+        let mut header = model.take_state().ok_or("No state in model")?;
+
+        let mut space = Space::new("the space");
+        space
+            .set_volume(50.)
+            .set_infiltration(Infiltration::Constant { flow })
+            .set_occupancy_schedule("people");
+        let i = header.push(SimulationStateElement::SpaceInfiltrationVolume(0), flow)?;
+        space.set_infiltration_volume_index(i)?;
+        let i = header.push(SimulationStateElement::SpaceInfiltrationTemperature(0), 10.)?;
+        space.set_infiltration_temperature_index(i)?;
+        model.add_space(space);
+
+        let space = &model.spaces[0];
+        let humidity =
+            SpaceHumidity::new(space, 0, &model, &mut header)?.ok_or("Expecting humidity")?;
+        let mut state = header.take_values().ok_or("Could not take values")?;
+        assert_eq!(space.humidity_ratio(&state), Some(INITIAL_HUMIDITY_RATIO));
+        equipment.set_power_consumption(&mut state, power)?;
+
+        let weather = CurrentWeather {
+            dry_bulb_temperature: 10.,
+            relative_humidity: 0.8,
+            pressure: STANDARD_PRESSURE,
+            ..CurrentWeather::default()
+        };
+        let date = Date {
+            month: 1,
+            day: 1,
+            hour: 12.,
+            year: None,
+        };
+        for _ in 0..1000 {
+            humidity.march(date, 600., &weather, &mut state)?;
+        }
+
+        // At steady state, m (W - W_out) = G
+        let m = flow * air_density(10.);
+        let w_out = humidity_ratio(10., 0.8, STANDARD_PRESSURE);
+        let g = people * MOISTURE_GENERATION_PER_PERSON
+            + power * fraction_latent / HEAT_OF_VAPORIZATION;
+        let expected = w_out + g / m;
+        let found = space.humidity_ratio(&state).ok_or("No humidity ratio")?;
+        assert!(
+            (expected - found).abs() < 1e-6,
+            "expected {}, found {}",
+            expected,
+            found
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_volume() -> Result<(), String> {
+        let model = Model::default();
+        let mut header = SimulationStateHeader::new();
+        let space = Arc::new(Space::new("the space"));
+        let humidity = SpaceHumidity::new(&space, 0, &model, &mut header)?;
+        assert!(humidity.is_none());
+        assert_eq!(header.len(), 0);
+        Ok(())
+    }
+}
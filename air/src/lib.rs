@@ -21,8 +21,12 @@ SOFTWARE.
 //! A simple AirFlow model for SIMPLE Building Simulation
 //!
 //! It uses the equations available in EnergyPlus' Engineering Reference
-//! for calculating the infiltration rates. No ventiation is calculated
-//! yet.
+//! for calculating the infiltration rates. Alternatively, infiltration and
+//! inter-zone flows can be calculated by means of a multi-zone air flow
+//! network. Operable windows in spaces outside of such a network are
+//! treated as single-sided openings. The CO2, contaminant and moisture
+//! balances of the spaces are also solved, as well as the air supplied by
+//! mechanical ventilation with heat recovery.
 
 /// The kind of Floating point number used in the
 /// library... the `"float"` feature means it becomes `f32`
@@ -34,5 +38,14 @@ pub type Float = f32;
 pub type Float = f64;
 
 pub mod air_model;
+mod contaminants;
 mod eplus;
+mod heat_recovery;
+
+/// Moisture balance of the spaces, and psychrometric functions
+pub mod humidity;
+mod natural_ventilation;
+
+/// A multi-zone air flow network, driven by wind and buoyancy
+pub mod network;
 mod resolvers;
@@ -0,0 +1,327 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::network::DEFAULT_DISCHARGE_COEFFICIENT;
+use crate::Float;
+use model::{
+    Boundary, Fenestration, FenestrationType, Infiltration, Model, SimulationState,
+    SimulationStateElement, SimulationStateHeader, SiteDetails, Space, SurfaceTrait,
+};
+use std::sync::Arc;
+use weather::CurrentWeather;
+
+/// The module name. For debugging purposes
+const MODULE_NAME: &str = "Natural ventilation";
+
+/// Acceleration of gravity, in m/s2
+const G: Float = 9.81;
+
+/// An operable [`Fenestration`] that connects a [`Space`] with the outdoors
+struct SingleOpening {
+    fenestration: Arc<Fenestration>,
+
+    /// The area of the fenestration when fully open, in m2
+    area: Float,
+
+    /// The vertical extent of the fenestration, in m
+    height: Float,
+
+    /// The discharge coefficient of the opening
+    discharge_coefficient: Float,
+
+    /// The factor that multiplies the wind speed at the height of the opening
+    wind_speed_modifier: Float,
+
+    /// Openings of category `Opening` are always fully open
+    always_open: bool,
+}
+
+impl SingleOpening {
+    /// Calculates the volumetric air flow (in m3/s) that comes in through
+    /// this opening, following the single-sided ventilation correlations
+    /// in BS 5925 (also used in CIBSE AM10).
+    ///
+    /// The wind- and buoyancy-driven flows are
+    ///
+    /// ```math
+    /// Q_{wind} = 0.025 A U
+    /// ```
+    ///
+    /// ```math
+    /// Q_{stack} = \frac{C_d A}{3} \sqrt{\frac{g H |\Delta T|}{\bar{T}}}
+    /// ```
+    ///
+    /// where $`A`$ is the open area, $`U`$ is the wind speed at the height
+    /// of the opening, $`H`$ is the height of the opening and $`\bar{T}`$
+    /// is the mean of the indoor and outdoor temperatures (in K). The larger
+    /// of the two is used.
+    fn flow(&self, wind_speed: Float, t_in: Float, t_out: Float, state: &SimulationState) -> Float {
+        let fraction = if self.always_open {
+            1.
+        } else {
+            self.fenestration
+                .open_fraction(state)
+                .unwrap_or(0.)
+                .clamp(0., 1.)
+        };
+        let area = self.area * fraction;
+        if area <= 0. {
+            return 0.;
+        }
+
+        let u = wind_speed * self.wind_speed_modifier;
+        let wind = 0.025 * area * u;
+
+        let t_mean = (t_in + t_out) / 2. + 273.15;
+        let stack = self.discharge_coefficient * area / 3.
+            * (G * self.height * (t_in - t_out).abs() / t_mean).sqrt();
+
+        wind.max(stack)
+    }
+}
+
+/// Calculates the air exchanged through the operable [`Fenestration`]s
+/// that connect a [`Space`] with the outdoors, reported as ventilation.
+///
+/// Spaces with an [`Infiltration::AirFlowNetwork`] are not considered, as
+/// the network already accounts for their openings.
+pub(crate) struct NaturalVentilation {
+    space: Arc<Space>,
+
+    openings: Vec<SingleOpening>,
+}
+
+impl NaturalVentilation {
+    /// Creates a [`NaturalVentilation`] for each [`Space`] that has operable
+    /// [`Fenestration`]s leading outdoors, pushing its ventilation elements
+    /// into the state.
+    pub fn new(
+        model: &Model,
+        site_details: &SiteDetails,
+        state: &mut SimulationStateHeader,
+    ) -> Result<Vec<Self>, String> {
+        let mut ret = Vec::new();
+        for (space_index, space) in model.spaces.iter().enumerate() {
+            if let Ok(Infiltration::AirFlowNetwork) = space.infiltration() {
+                continue;
+            }
+            let faces_space = |b: &Boundary| -> bool {
+                matches!(b, Boundary::Space { space: s } if s == space.name())
+            };
+
+            let mut openings = Vec::new();
+            for f in model.fenestrations.iter() {
+                let always_open = f.category == FenestrationType::Opening;
+                if !always_open && !f.is_operable() {
+                    continue;
+                }
+                let connects = (faces_space(&f.front_boundary)
+                    && matches!(f.back_boundary, Boundary::Outdoor))
+                    || (faces_space(&f.back_boundary)
+                        && matches!(f.front_boundary, Boundary::Outdoor));
+                if !connects {
+                    continue;
+                }
+
+                let bbox = f.vertices.outer().bbox()?;
+                let centroid = f.vertices.outer_centroid();
+                openings.push(SingleOpening {
+                    fenestration: Arc::clone(f),
+                    area: f.area(),
+                    height: bbox.max.z - bbox.min.z,
+                    discharge_coefficient: f
+                        .discharge_coefficient_or(MODULE_NAME, DEFAULT_DISCHARGE_COEFFICIENT),
+                    wind_speed_modifier: site_details.wind_speed_modifier(centroid.z),
+                    always_open,
+                });
+            }
+            if openings.is_empty() {
+                continue;
+            }
+
+            let i = state.push(
+                SimulationStateElement::SpaceVentilationVolume(space_index),
+                0.,
+            )?;
+            space.set_ventilation_volume_index(i)?;
+            let i = state.push(
+                SimulationStateElement::SpaceVentilationTemperature(space_index),
+                0.,
+            )?;
+            space.set_ventilation_temperature_index(i)?;
+
+            ret.push(Self {
+                space: Arc::clone(space),
+                openings,
+            });
+        }
+        Ok(ret)
+    }
+
+    /// Calculates the volume of outdoor air coming in through the openings
+    pub fn resolve(
+        &self,
+        weather: &CurrentWeather,
+        state: &mut SimulationState,
+    ) -> Result<(), String> {
+        let t_out = weather.dry_bulb_temperature;
+        let t_in = self.space.dry_bulb_temperature(state).unwrap_or(t_out);
+
+        let volume: Float = self
+            .openings
+            .iter()
+            .map(|o| o.flow(weather.wind_speed, t_in, t_out, state))
+            .sum();
+
+        self.space.set_ventilation_volume(state, volume)?;
+        self.space.set_ventilation_temperature(state, t_out)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use geometry::{Loop3D, Point3D, Polygon3D};
+    use model::{Construction, FenestrationPosition};
+
+    /// A 1m wide and 1.5m high window, from z=1 to z=2.5
+    fn get_model(
+        operable: bool,
+        infiltration: Option<Infiltration>,
+    ) -> Result<(Model, SimulationStateHeader), String> {
+        let mut model = Model::default();
+        model.add_construction(Construction::new("the construction"));
+
+        let mut the_loop = Loop3D::new();
+        the_loop.push(Point3D::new(0., 0., 1.))?;
+        the_loop.push(Point3D::new(1., 0., 1.))?;
+        the_loop.push(Point3D::new(1., 0., 2.5))?;
+        the_loop.push(Point3D::new(0., 0., 2.5))?;
+        the_loop.close()?;
+        let mut window = Fenestration::new(
+            "the window",
+            Polygon3D::new(the_loop)?,
+            "the construction",
+            FenestrationType::Window,
+            Boundary::Space {
+                space: "the space".into(),
+            },
+            Boundary::Outdoor,
+        );
+        if operable {
+            window.set_operation(FenestrationPosition::Continuous {
+                max: None,
+                min: None,
+            });
+        }
+        model.add_fenestration(window)?;
+
+        let mut header = model.take_state().ok_or("Could not take state")?;
+        let mut space = Space::new("the space");
+        if let Some(infiltration) = infiltration {
+            space.set_infiltration(infiltration);
+        }
+        let i = header.push(SimulationStateElement::SpaceDryBulbTemperature(0), 20.)?;
+        space.set_dry_bulb_temperature_index(i)?;
+        model.add_space(space);
+
+        Ok((model, header))
+    }
+
+    fn get_weather(temperature: Float, wind_speed: Float) -> CurrentWeather {
+        CurrentWeather {
+            dry_bulb_temperature: temperature,
+            wind_speed,
+            ..CurrentWeather::default()
+        }
+    }
+
+    #[test]
+    fn test_fixed_window() -> Result<(), String> {
+        let (model, mut header) = get_model(false, None)?;
+        let vents = NaturalVentilation::new(&model, &SiteDetails::default(), &mut header)?;
+        assert!(vents.is_empty());
+        assert!(model.spaces[0].ventilation_volume_index().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_network_spaces_are_skipped() -> Result<(), String> {
+        let (model, mut header) = get_model(true, Some(Infiltration::AirFlowNetwork))?;
+        let vents = NaturalVentilation::new(&model, &SiteDetails::default(), &mut header)?;
+        assert!(vents.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_wind_driven() -> Result<(), String> {
+        let (model, mut header) = get_model(true, None)?;
+        let details = SiteDetails::default();
+        let vents = NaturalVentilation::new(&model, &details, &mut header)?;
+        assert_eq!(vents.len(), 1);
+        let mut state = header.take_values().ok_or("Could not take values")?;
+        let space = &model.spaces[0];
+        let window = &model.fenestrations[0];
+
+        // Closed window... nothing comes in
+        let wind_speed = 4.;
+        let weather = get_weather(20., wind_speed);
+        vents[0].resolve(&weather, &mut state)?;
+        assert_eq!(space.ventilation_volume(&state), Some(0.));
+
+        // Half open, no temperature difference
+        window.set_open_fraction(&mut state, 0.5)?;
+        vents[0].resolve(&weather, &mut state)?;
+        let u = wind_speed * details.wind_speed_modifier(1.75);
+        let exp = 0.025 * 0.75 * u;
+        let v = space.ventilation_volume(&state).ok_or("No volume")?;
+        assert!((v - exp).abs() < 1e-7, "v = {}, exp = {}", v, exp);
+        assert_eq!(space.ventilation_temperature(&state), Some(20.));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stack_driven() -> Result<(), String> {
+        let (model, mut header) = get_model(true, None)?;
+        let vents = NaturalVentilation::new(&model, &SiteDetails::default(), &mut header)?;
+        let mut state = header.take_values().ok_or("Could not take values")?;
+        let space = &model.spaces[0];
+        model.fenestrations[0].set_open_fraction(&mut state, 1.)?;
+
+        let t_out = 10.;
+        vents[0].resolve(&get_weather(t_out, 0.), &mut state)?;
+        let t_mean = 15. + 273.15;
+        let exp = DEFAULT_DISCHARGE_COEFFICIENT * 1.5 / 3. * (G * 1.5 * 10. / t_mean).sqrt();
+        let v = space.ventilation_volume(&state).ok_or("No volume")?;
+        assert!((v - exp).abs() < 1e-7, "v = {}, exp = {}", v, exp);
+        assert_eq!(space.ventilation_temperature(&state), Some(t_out));
+
+        // It always brings air in, also in summer
+        vents[0].resolve(&get_weather(30., 0.), &mut state)?;
+        let v = space.ventilation_volume(&state).ok_or("No volume")?;
+        let t_mean = 25. + 273.15;
+        let exp = DEFAULT_DISCHARGE_COEFFICIENT * 1.5 / 3. * (G * 1.5 * 10. / t_mean).sqrt();
+        assert!((v - exp).abs() < 1e-7, "v = {}, exp = {}", v, exp);
+
+        Ok(())
+    }
+}
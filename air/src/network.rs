@@ -0,0 +1,644 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::Float;
+use matrix::Matrix;
+use model::{
+    Boundary, Crack, Fenestration, FenestrationType, Infiltration, Model, SimulationState,
+    SimulationStateElement, SimulationStateHeader, SiteDetails, Space, SurfaceTrait,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use weather::CurrentWeather;
+
+const MODULE_NAME: &str = "Air-flow network";
+
+/// Gravity acceleration, in m/s2
+const G: Float = 9.81;
+
+/// Below this pressure difference (in Pa), flows are linearized in order
+/// to avoid the infinite derivative of the power law at zero.
+const LINEAR_PRESSURE_DIFFERENCE: Float = 1e-4;
+
+/// The mass balance of each space (in kg/s) is considered solved when it is below this value
+const MASS_TOLERANCE: Float = 1e-7;
+
+/// The maximum number of Newton-Raphson iterations
+const MAX_ITERATIONS: usize = 100;
+
+/// Default exponent of a [`Crack`] with no `flow_exponent`
+const DEFAULT_FLOW_EXPONENT: Float = 0.65;
+
+/// Default discharge coefficient of an opening
+pub(crate) const DEFAULT_DISCHARGE_COEFFICIENT: Float = 0.6;
+
+/// Density of dry air (in kg/m3) at a certain temperature (in C), at sea level
+pub(crate) fn air_density(temperature: Float) -> Float {
+    101325. / (287.058 * (temperature + 273.15))
+}
+
+/// Calculates the mass flow of a power law $`\dot{m} = C \Delta P^n`$
+/// and its derivative with respect to $`\Delta P`$
+fn power_law(c: Float, n: Float, delta_p: Float) -> (Float, Float) {
+    if c <= 0. {
+        return (0., 0.);
+    }
+    let abs_delta_p = delta_p.abs();
+    if abs_delta_p < LINEAR_PRESSURE_DIFFERENCE {
+        let k = c * LINEAR_PRESSURE_DIFFERENCE.powf(n - 1.);
+        (k * delta_p, k)
+    } else {
+        let m = c * abs_delta_p.powf(n);
+        (m * delta_p.signum(), n * m / abs_delta_p)
+    }
+}
+
+/// The node to which a [`FlowPath`] leads.
+enum Node {
+    /// A space in the network
+    Space(usize),
+    /// The outdoor environment.
+    Outdoor {
+        /// The azimuth of the outward normal of the path, in degrees. It is
+        /// `None` for (mostly) horizontal surfaces.
+        azimuth: Option<Float>,
+        /// The factor that multiplies the wind speed at the height of the path
+        wind_speed_modifier: Float,
+    },
+}
+
+/// The element through which the air flows
+enum FlowElement {
+    Crack {
+        coefficient: Float,
+        exponent: Float,
+    },
+    Opening {
+        fenestration: Arc<Fenestration>,
+        area: Float,
+        discharge_coefficient: Float,
+        always_open: bool,
+    },
+}
+
+impl FlowElement {
+    fn from_crack(crack: &Crack) -> Self {
+        Self::Crack {
+            coefficient: crack.flow_coefficient,
+            exponent: crack.flow_exponent_or(MODULE_NAME, DEFAULT_FLOW_EXPONENT),
+        }
+    }
+
+    /// Calculates the mass flow and its derivative, for a certain pressure difference
+    /// and air density
+    fn flow(&self, delta_p: Float, density: Float, state: &SimulationState) -> (Float, Float) {
+        match self {
+            Self::Crack {
+                coefficient,
+                exponent,
+            } => power_law(*coefficient, *exponent, delta_p),
+            Self::Opening {
+                fenestration,
+                area,
+                discharge_coefficient,
+                always_open,
+            } => {
+                let fraction = if *always_open {
+                    1.
+                } else {
+                    fenestration.open_fraction(state).unwrap_or(0.)
+                };
+                let c = discharge_coefficient * area * fraction * (2. * density).sqrt();
+                power_law(c, 0.5, delta_p)
+            }
+        }
+    }
+}
+
+/// A link between a space and another space or the outdoors.
+///
+/// Flows are positive when they go from `from` to `to`
+struct FlowPath {
+    from: usize,
+    to: Node,
+    height: Float,
+    element: FlowElement,
+}
+
+/// A multi-zone air flow network
+pub struct AirFlowNetwork {
+    /// The spaces in the network
+    spaces: Vec<Arc<Space>>,
+
+    /// The links between the spaces and the outdoors
+    paths: Vec<FlowPath>,
+
+    /// The site details, for calculating wind pressures
+    site_details: SiteDetails,
+}
+
+impl AirFlowNetwork {
+    /// Builds a new [`AirFlowNetwork`] from a [`Model`]. Returns `None` if no
+    /// [`Space`] has an [`Infiltration::AirFlowNetwork`].
+    ///
+    /// It pushes the ventilation elements of the spaces in the network
+    /// into the state, as these report the air coming from other spaces.
+    pub fn new(
+        model: &Model,
+        site_details: &SiteDetails,
+        state: &mut SimulationStateHeader,
+    ) -> Result<Option<Self>, String> {
+        let mut spaces = Vec::new();
+        let mut indices: HashMap<String, usize> = HashMap::new();
+        for (i, space) in model.spaces.iter().enumerate() {
+            if let Ok(Infiltration::AirFlowNetwork) = space.infiltration() {
+                indices.insert(space.name().clone(), spaces.len());
+                let vol_index =
+                    state.push(SimulationStateElement::SpaceVentilationVolume(i), 0.)?;
+                space.set_ventilation_volume_index(vol_index)?;
+                let temp_index =
+                    state.push(SimulationStateElement::SpaceVentilationTemperature(i), 0.)?;
+                space.set_ventilation_temperature_index(temp_index)?;
+                spaces.push(Arc::clone(space));
+            }
+        }
+        if spaces.is_empty() {
+            return Ok(None);
+        }
+
+        let mut ret = Self {
+            spaces,
+            paths: Vec::new(),
+            site_details: site_details.clone(),
+        };
+
+        for s in model.surfaces.iter() {
+            if let Ok(crack) = s.crack() {
+                ret.add_path(&indices, &**s, FlowElement::from_crack(crack));
+            }
+        }
+        for f in model.fenestrations.iter() {
+            if let Ok(crack) = f.crack() {
+                ret.add_path(&indices, &**f, FlowElement::from_crack(crack));
+            }
+            let opening = FlowElement::Opening {
+                fenestration: Arc::clone(f),
+                area: f.area(),
+                discharge_coefficient: f
+                    .discharge_coefficient_or(MODULE_NAME, DEFAULT_DISCHARGE_COEFFICIENT),
+                always_open: f.category == FenestrationType::Opening,
+            };
+            ret.add_path(&indices, &**f, opening);
+        }
+
+        Ok(Some(ret))
+    }
+
+    /// Adds a path through a surface, if it connects the network.
+    fn add_path<T: SurfaceTrait>(
+        &mut self,
+        indices: &HashMap<String, usize>,
+        surface: &T,
+        element: FlowElement,
+    ) {
+        let node = |b: &Boundary| -> Option<Option<usize>> {
+            match b {
+                Boundary::Outdoor => Some(None),
+                Boundary::Space { space } => indices.get(space).map(|i| Some(*i)),
+                _ => None,
+            }
+        };
+        let front = node(surface.front_boundary());
+        let back = node(surface.back_boundary());
+        let mut normal = surface.normal();
+
+        let (from, to) = match (back, front) {
+            (Some(Some(a)), Some(Some(b))) => (a, Some(b)),
+            (Some(Some(a)), Some(None)) => (a, None),
+            (Some(None), Some(Some(a))) => {
+                // The outside is behind the surface
+                normal = -normal;
+                (a, None)
+            }
+            _ => return,
+        };
+
+        let height = surface.vertices().outer_centroid().z;
+        let to = match to {
+            Some(b) => Node::Space(b),
+            None => {
+                let horizontal = (normal.x * normal.x + normal.y * normal.y).sqrt();
+                let azimuth = if horizontal < 0.1 {
+                    None
+                } else {
+                    // Clockwise from North (i.e., Y axis)
+                    Some(normal.x.atan2(normal.y).to_degrees())
+                };
+                Node::Outdoor {
+                    azimuth,
+                    wind_speed_modifier: self.site_details.wind_speed_modifier(height),
+                }
+            }
+        };
+
+        self.paths.push(FlowPath {
+            from,
+            to,
+            height,
+            element,
+        })
+    }
+
+    /// Calculates the mass flow (and its derivative) through each path
+    fn flows(
+        &self,
+        pressures: &[Float],
+        densities: &[Float],
+        outdoor_pressures: &[Float],
+        outdoor_density: Float,
+        state: &SimulationState,
+    ) -> Vec<(Float, Float)> {
+        self.paths
+            .iter()
+            .zip(outdoor_pressures.iter())
+            .map(|(path, p_out)| {
+                let rho_from = densities[path.from];
+                let p_from = pressures[path.from] - rho_from * G * path.height;
+                let (p_to, rho_to) = match path.to {
+                    Node::Space(j) => (pressures[j] - densities[j] * G * path.height, densities[j]),
+                    Node::Outdoor { .. } => (*p_out, outdoor_density),
+                };
+                path.element
+                    .flow(p_from - p_to, (rho_from + rho_to) / 2., state)
+            })
+            .collect()
+    }
+
+    /// Calculates the net mass flow leaving each space
+    fn residuals(&self, flows: &[(Float, Float)]) -> Vec<Float> {
+        let mut ret = vec![0.; self.spaces.len()];
+        for (path, (m, _)) in self.paths.iter().zip(flows.iter()) {
+            ret[path.from] += m;
+            if let Node::Space(j) = path.to {
+                ret[j] -= m;
+            }
+        }
+        ret
+    }
+
+    /// Solves the network and stores the infiltration and
+    /// ventilation (i.e., air coming from other spaces) of each space
+    /// in the `state`
+    pub fn solve(
+        &self,
+        weather: &CurrentWeather,
+        state: &mut SimulationState,
+    ) -> Result<(), String> {
+        let n = self.spaces.len();
+        let t_out = weather.dry_bulb_temperature;
+        let outdoor_density = air_density(t_out);
+        let mut temperatures = Vec::with_capacity(n);
+        for space in self.spaces.iter() {
+            let t = space
+                .dry_bulb_temperature(state)
+                .ok_or_else(|| format!("Space '{}' has no dry bulb temperature", space.name()))?;
+            temperatures.push(t);
+        }
+        let densities: Vec<Float> = temperatures.iter().map(|t| air_density(*t)).collect();
+
+        // Pressure outside of each path, driven by wind and stack
+        let outdoor_pressures: Vec<Float> = self
+            .paths
+            .iter()
+            .map(|path| match path.to {
+                Node::Outdoor {
+                    azimuth,
+                    wind_speed_modifier,
+                } => {
+                    let cp = match azimuth {
+                        Some(azimuth) => self.site_details.wind_pressure_coefficient(
+                            weather.wind_direction.to_degrees() - azimuth,
+                        ),
+                        None => self.site_details.wind_pressure_coefficient(90.),
+                    };
+                    let v = weather.wind_speed * wind_speed_modifier;
+                    0.5 * outdoor_density * cp * v * v - outdoor_density * G * path.height
+                }
+                Node::Space(_) => 0.0,
+            })
+            .collect();
+
+        // Newton-Raphson
+        let mut pressures = vec![0.0; n];
+        let mut flows = self.flows(
+            &pressures,
+            &densities,
+            &outdoor_pressures,
+            outdoor_density,
+            state,
+        );
+        let mut residuals = self.residuals(&flows);
+        let norm = |r: &[Float]| r.iter().fold(0.0, |acc: Float, x| acc.max(x.abs()));
+
+        for _ in 0..MAX_ITERATIONS {
+            let err = norm(&residuals);
+            if err < MASS_TOLERANCE {
+                break;
+            }
+
+            let mut jacobian = Matrix::new(0.0, n, n);
+            for i in 0..n {
+                // Avoids singular matrices in spaces that are not connected
+                jacobian.add_to_element(i, i, 1e-9)?;
+            }
+            for (path, (_, d)) in self.paths.iter().zip(flows.iter()) {
+                let a = path.from;
+                jacobian.add_to_element(a, a, *d)?;
+                if let Node::Space(b) = path.to {
+                    jacobian.add_to_element(a, b, -d)?;
+                    jacobian.add_to_element(b, a, -d)?;
+                    jacobian.add_to_element(b, b, *d)?;
+                }
+            }
+            let mut delta = Matrix::new(0.0, n, 1);
+            for (i, r) in residuals.iter().enumerate() {
+                delta.set(i, 0, *r)?;
+            }
+            // Falls back to a pivoted LU factorization if elimination fails
+            let delta = jacobian.solve(&delta)?;
+
+            // Simple line search... halve the step until the residuals decrease
+            let mut step = 1.0;
+            loop {
+                let mut trial = pressures.clone();
+                for (i, p) in trial.iter_mut().enumerate() {
+                    *p -= step * delta.get(i, 0)?;
+                }
+                let trial_flows = self.flows(
+                    &trial,
+                    &densities,
+                    &outdoor_pressures,
+                    outdoor_density,
+                    state,
+                );
+                let trial_residuals = self.residuals(&trial_flows);
+                if norm(&trial_residuals) < err || step < 1e-3 {
+                    pressures = trial;
+                    flows = trial_flows;
+                    residuals = trial_residuals;
+                    break;
+                }
+                step /= 2.;
+            }
+        }
+
+        // Report
+        let mut infiltration_mass = vec![0.0; n];
+        let mut ventilation_mass = vec![0.0; n];
+        let mut ventilation_heat = vec![0.0; n];
+        for (path, (m, _)) in self.paths.iter().zip(flows.iter()) {
+            let a = path.from;
+            match path.to {
+                Node::Outdoor { .. } => {
+                    if *m < 0. {
+                        infiltration_mass[a] -= m;
+                    }
+                }
+                Node::Space(b) => {
+                    if *m > 0. {
+                        ventilation_mass[b] += m;
+                        ventilation_heat[b] += m * temperatures[a];
+                    } else {
+                        ventilation_mass[a] -= m;
+                        ventilation_heat[a] -= m * temperatures[b];
+                    }
+                }
+            }
+        }
+
+        for (i, space) in self.spaces.iter().enumerate() {
+            space.set_infiltration_temperature(state, t_out)?;
+            space.set_infiltration_volume(state, infiltration_mass[i] / outdoor_density)?;
+
+            let t_vent = if ventilation_mass[i] > 0. {
+                ventilation_heat[i] / ventilation_mass[i]
+            } else {
+                temperatures[i]
+            };
+            space.set_ventilation_temperature(state, t_vent)?;
+            space.set_ventilation_volume(state, ventilation_mass[i] / air_density(t_vent))?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use calendar::Date;
+    use geometry::{Loop3D, Point3D, Polygon3D};
+    use model::{Construction, Surface};
+
+    /// A square crack of side 0.2, facing North or South, at a certain height
+    fn add_crack(
+        model: &mut Model,
+        name: &str,
+        facing_north: bool,
+        z: Float,
+        coefficient: Float,
+    ) -> Result<(), String> {
+        let (y, x0, x1) = if facing_north {
+            (4., 2.1, 1.9)
+        } else {
+            (0., 1.9, 2.1)
+        };
+        let mut the_loop = Loop3D::new();
+        the_loop.push(Point3D::new(x0, y, z - 0.1))?;
+        the_loop.push(Point3D::new(x1, y, z - 0.1))?;
+        the_loop.push(Point3D::new(x1, y, z + 0.1))?;
+        the_loop.push(Point3D::new(x0, y, z + 0.1))?;
+        the_loop.close()?;
+
+        let mut surface = Surface::new(
+            name,
+            Polygon3D::new(the_loop)?,
+            "the construction",
+            Boundary::Outdoor,
+            Boundary::Space {
+                space: "the space".into(),
+            },
+        );
+        surface.set_crack(Crack::new(coefficient));
+        model.add_surface(surface)?;
+        Ok(())
+    }
+
+    fn get_model() -> Result<(Model, SimulationStateHeader), String> {
+        let mut model = Model::default();
+        let mut state = SimulationStateHeader::new();
+        model.add_construction(Construction::new("the construction"));
+
+        let mut space = Space::new("the space");
+        space.set_infiltration(Infiltration::AirFlowNetwork);
+        let i = state.push(SimulationStateElement::SpaceDryBulbTemperature(0), 20.)?;
+        space.set_dry_bulb_temperature_index(i)?;
+        let i = state.push(SimulationStateElement::SpaceInfiltrationVolume(0), 0.)?;
+        space.set_infiltration_volume_index(i)?;
+        let i = state.push(SimulationStateElement::SpaceInfiltrationTemperature(0), 0.)?;
+        space.set_infiltration_temperature_index(i)?;
+        model.add_space(space);
+
+        Ok((model, state))
+    }
+
+    /// Wind direction is in degrees
+    fn get_weather(temperature: Float, wind_speed: Float, wind_direction: Float) -> CurrentWeather {
+        CurrentWeather {
+            date: Date {
+                month: 1,
+                day: 1,
+                hour: 12.,
+                year: None,
+            },
+            dry_bulb_temperature: temperature,
+            wind_speed,
+            wind_direction: wind_direction.to_radians(),
+            ..CurrentWeather::default()
+        }
+    }
+
+    #[test]
+    fn test_power_law() {
+        let (c, n) = (0.01, 0.65);
+
+        // Antisymmetric
+        let (m, d) = power_law(c, n, 4.);
+        let (m2, d2) = power_law(c, n, -4.);
+        assert!((m + m2).abs() < 1e-12);
+        assert!((d - d2).abs() < 1e-12);
+        assert!((m - c * (4. as Float).powf(n)).abs() < 1e-12);
+
+        // Continuous at the linearization limit
+        let (lin, _) = power_law(c, n, LINEAR_PRESSURE_DIFFERENCE * 0.999999);
+        let (pow, _) = power_law(c, n, LINEAR_PRESSURE_DIFFERENCE);
+        assert!((lin - pow).abs() < 1e-9);
+
+        // No flow
+        assert_eq!(power_law(0., n, 10.), (0., 0.));
+    }
+
+    #[test]
+    fn test_no_network() -> Result<(), String> {
+        let mut model = Model::default();
+        let mut state = SimulationStateHeader::new();
+        model.add_space(Space::new("the space"));
+        let network = AirFlowNetwork::new(&model, &SiteDetails::default(), &mut state)?;
+        assert!(network.is_none());
+        assert_eq!(state.len(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_wind_driven() -> Result<(), String> {
+        let (mut model, mut header) = get_model()?;
+        let c = 0.01;
+        add_crack(&mut model, "south crack", false, 1.5, c)?;
+        add_crack(&mut model, "north crack", true, 1.5, c)?;
+
+        let details = SiteDetails::default();
+        let network =
+            AirFlowNetwork::new(&model, &details, &mut header)?.ok_or("Expecting a network")?;
+        let mut state = header.take_values().ok_or("Could not take values")?;
+
+        // Wind from the South, same temperature inside and outside.
+        let wind_speed = 5.;
+        let weather = get_weather(20., wind_speed, 180.);
+        network.solve(&weather, &mut state)?;
+
+        // Symmetric cracks... the space pressure lies in the middle
+        let rho = air_density(20.);
+        let v = wind_speed * details.wind_speed_modifier(1.5);
+        let delta_cp =
+            details.wind_pressure_coefficient(0.) - details.wind_pressure_coefficient(180.);
+        let delta_p = 0.5 * rho * delta_cp * v * v / 2.;
+        let expected = c * delta_p.powf(DEFAULT_FLOW_EXPONENT) / rho;
+
+        let space = &model.spaces[0];
+        let found = space.infiltration_volume(&state).ok_or("No infiltration")?;
+        assert!(
+            (found - expected).abs() < 1e-6,
+            "expected {}, found {}",
+            expected,
+            found
+        );
+        assert_eq!(space.infiltration_temperature(&state), Some(20.));
+        assert_eq!(space.ventilation_volume(&state), Some(0.));
+
+        // No wind, no temperature difference... no flow
+        let weather = get_weather(20., 0., 180.);
+        network.solve(&weather, &mut state)?;
+        let found = space.infiltration_volume(&state).ok_or("No infiltration")?;
+        assert!(found.abs() < 1e-6, "found {}", found);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stack_driven() -> Result<(), String> {
+        let (mut model, mut header) = get_model()?;
+        let c = 0.01;
+        add_crack(&mut model, "low crack", false, 0.5, c)?;
+        add_crack(&mut model, "high crack", false, 2.5, c)?;
+
+        let network = AirFlowNetwork::new(&model, &SiteDetails::default(), &mut header)?
+            .ok_or("Expecting a network")?;
+        let mut state = header.take_values().ok_or("Could not take values")?;
+
+        // No wind, cold outside
+        let weather = get_weather(0., 0., 0.);
+        network.solve(&weather, &mut state)?;
+        let space = &model.spaces[0];
+        let cold = space.infiltration_volume(&state).ok_or("No infiltration")?;
+        assert!(cold > 0.);
+
+        // Colder outside, more infiltration
+        let weather = get_weather(-10., 0., 0.);
+        network.solve(&weather, &mut state)?;
+        let colder = space.infiltration_volume(&state).ok_or("No infiltration")?;
+        assert!(colder > cold);
+
+        // Check the neutral pressure plane is in the middle: same mass flow
+        // through both cracks
+        let t_in = 20.;
+        let rho_in = air_density(t_in);
+        let rho_out = air_density(-10.);
+        let delta_p = (rho_out - rho_in) * G * 1.;
+        let expected = c * delta_p.powf(DEFAULT_FLOW_EXPONENT) / rho_out;
+        assert!(
+            (colder - expected).abs() / expected < 0.05,
+            "expected {}, found {}",
+            expected,
+            colder
+        );
+
+        Ok(())
+    }
+}
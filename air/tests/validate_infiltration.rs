@@ -81,12 +81,14 @@ fn sim(inf: Infiltration, dir: &str) -> Result<(Vec<Float>, Vec<Float>), String>
         day: 1,
         month: 1,
         hour: 0.0,
+        year: None,
     };
 
     let end = Date {
         day: 31,
         month: 12,
         hour: 23.99999,
+        year: None,
     };
 
     let dt = 60. * 60. / 4.0; // 15 minutes
@@ -77,6 +77,7 @@ fn main() -> Result<(), String> {
         max_depth: inputs.max_depth,
         n_ambient_samples: inputs.n_ambient_samples,
         reinhart: ReinhartSky::new(inputs.mf),
+        cache_dir: None,
     };
 
     let rays = vec![
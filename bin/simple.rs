@@ -20,23 +20,26 @@ SOFTWARE.
 
 use clap::Parser;
 use simple::control_trait::SimpleControl;
+use simple::model_summary::{lint, ModelSummary};
 use simple::run_simulation::*;
 use simple::void_control::VoidControl;
-use simple::OccupantBehaviour;
-use simple::{Model, SimulationStateHeader};
+use simple::{DaylightDimming, OccupantBehaviour};
+use simple::{
+    MetaOptions, Model, MultiphysicsModel, SimpleError, SimulationModel, SimulationStateHeader,
+};
 
 fn run_sim<C>(
     model: &Model,
     state_header: &mut SimulationStateHeader,
     options: &SimOptions,
     controller: C,
-) -> Result<(), String>
+) -> Result<(), SimpleError>
 where
     C: SimpleControl,
 {
     match &options.output {
         Some(v) => {
-            let out = std::fs::File::create(v).map_err(|e| format!("{}", e))?;
+            let out = std::fs::File::create(v)?;
             run(model, state_header, options, out, controller)
         }
         None => run(
@@ -53,7 +56,7 @@ fn choose_controller(
     model: Model,
     state_header: &mut SimulationStateHeader,
     options: &SimOptions,
-) -> Result<(), String> {
+) -> Result<(), SimpleError> {
     match &options.control_file {
         None => {
             let controller = VoidControl {};
@@ -64,11 +67,15 @@ fn choose_controller(
                 let controller = OccupantBehaviour::new(&model)?;
                 run_sim::<OccupantBehaviour>(&model, state_header, options, controller)
             }
+            "daylight" => {
+                let controller = DaylightDimming::new(&model)?;
+                run_sim::<DaylightDimming>(&model, state_header, options, controller)
+            }
             _ => {
                 if let Some(control_file) = &options.control_file {
                     match &options.output {
                         Some(v) => {
-                            let out = std::fs::File::create(v).map_err(|e| format!("{}", e))?;
+                            let out = std::fs::File::create(v)?;
                             run_rhai(model, state_header, options, control_file, out)
                         }
                         None => run_rhai(
@@ -92,6 +99,35 @@ fn abort(msg: &str) -> ! {
     std::process::exit(1);
 }
 
+fn load_model(filename: &str) -> (Model, SimulationStateHeader) {
+    let loaded = if filename.ends_with(".spl") {
+        Model::from_file(filename)
+    } else if filename.ends_with(".json") {
+        Model::from_json_file(filename)
+    } else {
+        let e = format!(
+            "Unkown kind of file '{}'... expecting .json or .spl",
+            filename
+        );
+        abort(&e);
+    };
+    match loaded {
+        Ok(o) => o,
+        Err(e) => abort(&e.to_string()),
+    }
+}
+
+/// Validates the model beyond parsing it, and summarizes it
+fn check(model: &Model, state_header: &mut SimulationStateHeader) -> Result<ModelSummary, String> {
+    let problems = lint(model);
+    if !problems.is_empty() {
+        return Err(problems.join("\n"));
+    }
+    // Building the physics model checks constructions, geometry, etc.
+    MultiphysicsModel::new(&MetaOptions::default(), (), model, state_header, 1)?;
+    ModelSummary::new(model)
+}
+
 fn main() {
     // cargo instruments --release --template Allocations --package simple --bin simple -- -i tests/cold_apartment/cold.spl -w tests/wellington.epw -n 1 -o check.csv
     // cargo instruments --release --template 'CPU Profiler' --package simple --bin simple -- -i tests/cold_apartment/cold.spl -w tests/wellington.epw -n 1 -o check.csv
@@ -108,29 +144,16 @@ fn main() {
     //     // check:true,
     //     .. SimOptions::default()
     // };
-    let filename = options.input_file.to_string();
-
-    let (model, mut state_header) = if filename.ends_with(".spl") {
-        match Model::from_file(filename) {
-            Ok(o) => o,
-            Err(e) => {
-                abort(&e);
-            }
-        }
-    } else if filename.ends_with(".json") {
-        match Model::from_json_file(filename) {
-            Ok(o) => o,
-            Err(e) => {
-                abort(&e);
-            }
+    if let Some(SimCommand::Check { input_file }) = &options.command {
+        let (model, mut state_header) = load_model(input_file);
+        match check(&model, &mut state_header) {
+            Ok(summary) => print!("{}", summary),
+            Err(e) => abort(&e),
         }
-    } else {
-        let e = format!(
-            "Unkown kind of file '{}'... expecting .json or .spl",
-            filename
-        );
-        abort(&e);
-    };
+        std::process::exit(0);
+    }
+
+    let (model, mut state_header) = load_model(&options.input_file);
 
     if let Some(outfile) = options.translate {
         if outfile.ends_with(".json") {
@@ -19,7 +19,7 @@ SOFTWARE.
 */
 
 use clap::Parser;
-use rendering::{RayTracer, Scene, Wavelengths};
+use rendering::{Integrator, RayTracer, Scene, Wavelengths};
 
 use geometry::{Point3D, Vector3D};
 use rendering::camera::{Film, Pinhole, View};
@@ -85,7 +85,8 @@ struct Inputs {
     pub input: String,
 
     #[clap(short, long)]
-    /// The output of the final image (in rgbe format)
+    /// The output of the final image (in OpenEXR format if it ends
+    /// with `.exr`; in rgbe format otherwise)
     pub output: String,
 
     /* Ray-tracer data */
@@ -104,6 +105,16 @@ struct Inputs {
     #[clap(short = 'a', long = "ambient_samples", default_value_t = 70)]
     pub n_ambient_samples: usize,
 
+    /// The number of photons emitted for estimating caustics (i.e., light
+    /// reflected by mirrors or transmitted through glass and light pipes).
+    /// If zero, only path tracing is used
+    #[clap(long = "photons", default_value_t = 0)]
+    pub n_photons: usize,
+
+    /// The radius within which photons are gathered
+    #[clap(long = "photon_radius", default_value_t = 0.05)]
+    pub photon_radius: Float,
+
     /* Film */
     /// The Horizontal resolution of the final image
     #[clap(short = 'x', long, default_value_t = 512)]
@@ -172,9 +183,22 @@ fn main() -> Result<(), String> {
         n_ambient_samples: inputs.n_ambient_samples,
         n_shadow_samples: inputs.n_shadow_samples,
         max_depth: inputs.max_depth,
+        integrator: if inputs.n_photons > 0 {
+            Integrator::PhotonMapping {
+                n_photons: inputs.n_photons,
+                radius: inputs.photon_radius,
+            }
+        } else {
+            Integrator::PathTracing
+        },
     };
 
     let buffer = integrator.render(&scene, &camera);
 
-    buffer.save_hdre(std::path::Path::new(&inputs.output))
+    let output = std::path::Path::new(&inputs.output);
+    if inputs.output.ends_with(".exr") {
+        buffer.save_exr(output)
+    } else {
+        buffer.save_hdre_with_view(output, &view)
+    }
 }
@@ -27,15 +27,22 @@ use std::ops::Sub;
 use chrono::NaiveDate;
 
 #[cfg(feature = "serde")]
-use serde::{Deserialize, Serialize};
+use serde::Serialize;
 
 /// An extremely simple Date object. We don't
 /// need anything else, I think.
-/// It does not consider years at all!
 /// Days and Months are counted from 1
-/// (e.g. January is 1, not 0)
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(Copy, Clone, PartialEq, Debug, Default)]
+/// (e.g. January is 1, not 0).
+///
+/// Years are optional: dates without a `year` are year-agnostic
+/// (i.e., they represent the same moment of every year), which is
+/// what typical weather files and schedules need. Dates with a
+/// `year` allow simulating multi-year datasets continuously.
+///
+/// Dates can also be parsed from and displayed in ISO 8601 format
+/// (see [`iso8601`](crate::iso8601)).
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Copy, Clone, Debug, Default)]
 pub struct Date {
     /// Months of the year, from 1 to 12
     pub month: u8,
@@ -45,6 +52,10 @@ pub struct Date {
 
     /// Hour of the day, from 0.0 to 23.999999
     pub hour: Float,
+
+    /// The year, if known
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "Option::is_none"))]
+    pub year: Option<i32>,
 }
 
 #[cfg(feature = "chrono")]
@@ -59,7 +70,12 @@ impl std::convert::From<NaiveDateTime> for Date {
         let minute = item.minute() as Float;
         let seconds = item.second() as Float;
         let hour = hour_int + minute / 60. + seconds / 3600.;
-        Self { month, day, hour }
+        Self {
+            month,
+            day,
+            hour,
+            year: Some(item.year()),
+        }
     }
 }
 
@@ -79,6 +95,9 @@ impl fmt::Display for Date {
 
         // ignore seconds
 
+        if let Some(year) = self.year {
+            write!(f, "{:04}/", year)?;
+        }
         write!(
             f,
             "{:02}/{:02} - {}:{:02}",
@@ -91,7 +110,17 @@ impl Sub for Date {
     type Output = Float;
 
     fn sub(self, other: Self) -> Float {
-        self.day_of_year() - other.day_of_year()
+        let days = match (self.year, other.year) {
+            (Some(a), Some(b)) => days_between_years(b, a),
+            _ => 0.,
+        };
+        self.day_of_year() - other.day_of_year() + days
+    }
+}
+
+impl PartialEq for Date {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
     }
 }
 
@@ -104,7 +133,14 @@ impl PartialOrd for Date {
 }
 
 impl Ord for Date {
+    /// Dates are compared by year only if both of them have one;
+    /// otherwise, they are compared as if they were in the same year.
     fn cmp(&self, other: &Self) -> Ordering {
+        if let (Some(a), Some(b)) = (self.year, other.year) {
+            if a != b {
+                return a.cmp(&b);
+            }
+        }
         match self.month.cmp(&other.month) {
             Ordering::Less => Ordering::Less,
             Ordering::Greater => Ordering::Greater,
@@ -129,10 +165,43 @@ impl Ord for Date {
     }
 }
 
+/// Checks whether a year is a leap year, according to the Gregorian calendar
+///
+/// ```
+/// use calendar::is_leap_year;
+///
+/// assert!(is_leap_year(2020));
+/// assert!(!is_leap_year(2021));
+/// assert!(!is_leap_year(1900));
+/// assert!(is_leap_year(2000));
+/// ```
+pub fn is_leap_year(year: i32) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+/// The number of days in a year
+fn days_in_year(year: i32) -> Float {
+    if is_leap_year(year) {
+        366.
+    } else {
+        365.
+    }
+}
+
+/// The number of days between January 1 of `from` and
+/// January 1 of `to` (negative if `to` is earlier)
+pub(crate) fn days_between_years(from: i32, to: i32) -> Float {
+    if from <= to {
+        (from..to).map(days_in_year).sum()
+    } else {
+        -days_between_years(to, from)
+    }
+}
+
 impl Date {
     /// Transforms a Date into a `chrono` `NaiveDateTime`.
     ///
-    /// Because `Date` does not have a year, we need to pass it as a parameter.        
+    /// The `year` is used when the `Date` does not have one.
     #[cfg(feature = "chrono")]
     pub fn into_naive_datetime(self, year: i32) -> Result<NaiveDateTime, String> {
         let hour = self.hour.floor();
@@ -150,6 +219,7 @@ impl Date {
 
         sec %= 60;
 
+        let year = self.year.unwrap_or(year);
         let r = NaiveDate::from_ymd_opt(year, self.month as u32, self.day as u32)
             .ok_or("Could not build chronos::Date")?;
         let hour = hour.round() as u32;
@@ -164,17 +234,66 @@ impl Date {
         let n_self = self.day_of_year();
         let mut n_other = other.day_of_year();
 
-        // If we celebrate new years between both dates
-        if n_other < n_self {
+        if let (Some(a), Some(b)) = (self.year, other.year) {
+            n_other += days_between_years(a, b);
+        } else if n_other < n_self {
+            // If we celebrate new years between both dates
             n_other += 365.
         }
 
         // Interpolate between the two
         let full_n = n_self + x * (n_other - n_self);
-        Date::from_day_of_year(full_n % 365.)
+        Date::from_year_and_day(self.year, full_n)
     }
 
-    /// Transforms a day of the year into a date
+    /// Transforms a day of a certain year into a date. Unlike
+    /// [`from_day_of_year`](Self::from_day_of_year), this considers leap
+    /// years, and `n` can be negative or larger than the number of days in
+    /// the year, in which case the date moves to the previous or following
+    /// years. If the `year` is unknown, `n` wraps around a 365-day year.
+    ///
+    /// ```
+    /// use calendar::Date;
+    ///
+    /// let d = Date::from_year_and_day(Some(2020), 59.5);
+    /// assert_eq!((d.month, d.day), (2, 29));
+    ///
+    /// let d = Date::from_year_and_day(Some(2020), 366.);
+    /// assert_eq!((d.year, d.month, d.day), (Some(2021), 1, 1));
+    /// ```
+    pub fn from_year_and_day(mut year: Option<i32>, mut n: Float) -> Self {
+        match year.as_mut() {
+            None => n = n.rem_euclid(365.),
+            Some(y) => {
+                while n < 0. {
+                    *y -= 1;
+                    n += days_in_year(*y);
+                }
+                while n >= days_in_year(*y) {
+                    n -= days_in_year(*y);
+                    *y += 1;
+                }
+            }
+        }
+
+        if year.map(is_leap_year).unwrap_or(false) && n >= 59. {
+            if n < 60. {
+                return Date {
+                    month: 2,
+                    day: 29,
+                    hour: (n - 59.) * 24.,
+                    year,
+                };
+            }
+            n -= 1.;
+        }
+        let mut date = Date::from_day_of_year(n);
+        date.year = year;
+        date
+    }
+
+    /// Transforms a day of the year (from 0 to 365, in a non-leap year)
+    /// into a date without a year
     pub fn from_day_of_year(n: Float) -> Self {
         if !(0. ..365.).contains(&n) {
             panic!("Impossible day of the year '{}' when building a date", n);
@@ -207,6 +326,7 @@ impl Date {
                     month: i as u8,
                     day: 1 + day as u8,
                     hour: hour * 24.0,
+                    year: None,
                 };
             }
         }
@@ -222,11 +342,16 @@ impl Date {
             month: 12,
             day: 1 + day as u8,
             hour: hour * 24.0,
+            year: None,
         }
     }
 
     /// Retrieves the day of the year corresponding
-    /// to the date (includes the decimals for the hour)
+    /// to the date (includes the decimals for the hour).
+    ///
+    /// In leap years (which can only be known if the date has a `year`),
+    /// the days after February 28 are one day later than in other years.
+    /// Dates without a year are always in a 365-day year.
     pub fn day_of_year(&self) -> Float {
         const CUMULATED_DAYS_BEFORE_MONTH: [Float; 12] = [
             0.,   // Jan
@@ -243,8 +368,37 @@ impl Date {
             334., // Dec
         ];
 
+        let leap_day = match self.year {
+            Some(year) if is_leap_year(year) && self.month > 2 => 1.,
+            _ => 0.,
+        };
+
         CUMULATED_DAYS_BEFORE_MONTH[self.month as usize - 1] + self.day as Float + self.hour / 24.0
             - 1.0
+            + leap_day
+    }
+
+    /// The number of days in the month of the date. February has 29
+    /// days in leap years, if the date has a `year`.
+    pub fn days_in_month(&self) -> u8 {
+        const N_DAYS_PER_MONTH: [u8; 12] = [
+            31, // Jan
+            28, // Feb
+            31, // March
+            30, // Apr
+            31, // May
+            30, // Jun
+            31, // Jul
+            31, // Aug
+            30, // Sept
+            31, // Oct
+            30, // Nov
+            31, // Dec
+        ];
+        match self.year {
+            Some(year) if self.month == 2 && is_leap_year(year) => 29,
+            _ => N_DAYS_PER_MONTH[self.month as usize - 1],
+        }
     }
 
     /// Adds a certain number of seconds to a date
@@ -267,32 +421,27 @@ impl Date {
 
     /// Adds a certain number of days to a date.
     pub fn add_days(&mut self, n_days: usize) {
-        const N_DAYS_PER_MONTH: [u8; 12] = [
-            31, // Jan
-            28, // Feb
-            31, // March
-            30, // Apr
-            31, // May
-            30, // Jun
-            31, // Jul
-            31, // Aug
-            30, // Sept
-            31, // Oct
-            30, // Nov
-            31, // Dec
-        ];
+        // Whole years only matter if we know the year
+        let n_days = if self.year.is_some() {
+            n_days
+        } else {
+            n_days % 365
+        };
 
         // Lets recursively consume n_days month by month.
-        let n_days = n_days % 365;
         if n_days > 0 {
             let would_be_day = self.day as usize + n_days; // Now we can add more than a year
-            let n_days_this_month = N_DAYS_PER_MONTH[self.month as usize - 1];
+                                                           // A February 29 without a year is treated as the last day of February
+            let n_days_this_month = self.days_in_month().max(self.day);
 
-            if would_be_day as u8 > n_days_this_month {
+            if would_be_day > n_days_this_month as usize {
                 // Add one month, considering that this might be a change in year
                 self.month += 1;
                 if self.month == 13 {
                     self.month = 1;
+                    if let Some(year) = self.year.as_mut() {
+                        *year += 1;
+                    }
                 }
 
                 // reset the day
@@ -343,6 +492,7 @@ mod tests {
             month: 1,
             day: 1,
             hour: 0.0,
+            year: None,
         };
 
         assert!((start - start.interpolate(start, 0.0)).abs() < 1e-5);
@@ -354,6 +504,7 @@ mod tests {
             month: 1,
             day: 2,
             hour: 23.999999999,
+            year: None,
         };
 
         assert!(start.same_day(start.interpolate(end, 0.0)));
@@ -373,12 +524,14 @@ mod tests {
             month: 12,
             day: 31,
             hour: 0.0,
+            year: None,
         };
 
         let end = Date {
             month: 1,
             day: 1,
             hour: 23.9999999999999,
+            year: None,
         };
 
         assert!(start.same_day(start.interpolate(end, 0.0)));
@@ -393,12 +546,14 @@ mod tests {
             month: 1,
             day: 31,
             hour: 0.0,
+            year: None,
         };
 
         let end = Date {
             month: 2,
             day: 1,
             hour: 23.9999999999999,
+            year: None,
         };
 
         assert!(start.same_day(start.interpolate(end, 0.0)));
@@ -416,6 +571,7 @@ mod tests {
             month: 12,
             day: 31,
             hour: 12.0,
+            year: None,
         };
         assert!((date - Date::from_day_of_year(364.5)).abs() < 0.01);
 
@@ -423,6 +579,7 @@ mod tests {
             day: 1,
             month: 1,
             hour: 0.0,
+            year: None,
         };
 
         assert_eq!(0., date.day_of_year());
@@ -432,6 +589,7 @@ mod tests {
             day: 1,
             month: 1,
             hour: 12.0,
+            year: None,
         };
 
         assert_eq!(0.5, date.day_of_year());
@@ -441,6 +599,7 @@ mod tests {
             day: 4,
             month: 8,
             hour: 0.0,
+            year: None,
         };
 
         assert_eq!(215., date.day_of_year());
@@ -450,6 +609,7 @@ mod tests {
             day: 4,
             month: 8,
             hour: 0.3 * 24.,
+            year: None,
         };
 
         assert_eq!(215.3, date.day_of_year());
@@ -459,6 +619,7 @@ mod tests {
             day: 24,
             month: 3,
             hour: 0.0,
+            year: None,
         };
 
         assert_eq!(82., date.day_of_year());
@@ -468,6 +629,7 @@ mod tests {
             day: 24,
             month: 3,
             hour: 0.9 * 24.,
+            year: None,
         };
 
         assert_eq!(82.9, date.day_of_year());
@@ -492,6 +654,7 @@ mod tests {
             month: 2,
             day: 3,
             hour: 1.,
+            year: None,
         };
         assert!(!(d > d));
         assert!(!(d < d));
@@ -505,11 +668,13 @@ mod tests {
             month: 1,
             day: 1,
             hour: 0.,
+            year: None,
         };
         let after = Date {
             month: 2,
             day: 1,
             hour: 0.,
+            year: None,
         };
 
         test_compare(before, after);
@@ -522,11 +687,13 @@ mod tests {
             month: 2,
             day: 12,
             hour: 0.,
+            year: None,
         };
         let after = Date {
             month: 2,
             day: 31,
             hour: 0.,
+            year: None,
         };
 
         test_compare(before, after);
@@ -539,11 +706,13 @@ mod tests {
             month: 2,
             day: 12,
             hour: 12.4,
+            year: None,
         };
         let after = Date {
             month: 2,
             day: 12,
             hour: 21.,
+            year: None,
         };
 
         test_compare(before, after);
@@ -556,11 +725,13 @@ mod tests {
             month: 2,
             day: 12,
             hour: 12.4,
+            year: None,
         };
         let after = Date {
             month: 9,
             day: 1,
             hour: 0.,
+            year: None,
         };
 
         test_compare(before, after);
@@ -572,11 +743,13 @@ mod tests {
             month: 2,
             day: 12,
             hour: 12.4,
+            year: None,
         };
         let after = Date {
             month: 2,
             day: 12,
             hour: 12.4,
+            year: None,
         };
 
         before.add_days(0);
@@ -588,6 +761,7 @@ mod tests {
             month: 2,
             day: 15,
             hour: 12.4,
+            year: None,
         };
         assert!(before == after);
     }
@@ -598,11 +772,13 @@ mod tests {
             month: 1,
             day: 25,
             hour: 12.4,
+            year: None,
         };
         let after = Date {
             month: 2,
             day: 4,
             hour: 12.4,
+            year: None,
         };
 
         // Add ten days
@@ -614,11 +790,13 @@ mod tests {
             month: 1,
             day: 25,
             hour: 12.4,
+            year: None,
         };
         let after = Date {
             month: 3,
             day: 1,
             hour: 12.4,
+            year: None,
         };
 
         // Add 35 days
@@ -632,11 +810,13 @@ mod tests {
             month: 12,
             day: 25,
             hour: 12.4,
+            year: None,
         };
         let after = Date {
             month: 1,
             day: 4,
             hour: 12.4,
+            year: None,
         };
 
         // Add ten days
@@ -648,11 +828,13 @@ mod tests {
             month: 12,
             day: 25,
             hour: 12.4,
+            year: None,
         };
         let after = Date {
             month: 2,
             day: 2,
             hour: 12.4,
+            year: None,
         };
 
         // Add 39 days
@@ -664,11 +846,13 @@ mod tests {
             month: 12,
             day: 25,
             hour: 12.4,
+            year: None,
         };
         let after = Date {
             month: 12,
             day: 25,
             hour: 12.4,
+            year: None,
         };
         before.add_days(365);
         assert!(before == after);
@@ -678,11 +862,13 @@ mod tests {
             month: 12,
             day: 25,
             hour: 12.4,
+            year: None,
         };
         let after = Date {
             month: 2,
             day: 2,
             hour: 12.4,
+            year: None,
         };
         before.add_days(365 + 39);
         assert!(before == after);
@@ -694,11 +880,13 @@ mod tests {
             month: 12,
             day: 25,
             hour: 12.4,
+            year: None,
         };
         let after = Date {
             month: 12,
             day: 25,
             hour: 13.6,
+            year: None,
         };
 
         // Add 1.2 hours
@@ -714,6 +902,7 @@ mod tests {
             month: 12,
             day: 25,
             hour: 12.4,
+            year: None,
         };
 
         // Add 1.2 hours, but in seconds
@@ -730,11 +919,13 @@ mod tests {
             month: 12,
             day: 25,
             hour: 12.4,
+            year: None,
         };
         let after = Date {
             month: 1,
             day: 4,
             hour: 12.4,
+            year: None,
         };
 
         // Add ten days, in hours
@@ -746,11 +937,13 @@ mod tests {
             month: 12,
             day: 25,
             hour: 12.4,
+            year: None,
         };
         let after = Date {
             month: 2,
             day: 2,
             hour: 12.4,
+            year: None,
         };
 
         // Add 39 days
@@ -765,31 +958,37 @@ mod tests {
                 month: 1,
                 day: 1,
                 hour: 1.23,
+                year: None,
             },
             Date {
                 month: 1,
                 day: 2,
                 hour: 2.23,
+                year: None,
             },
             Date {
                 month: 2,
                 day: 3,
                 hour: 1.23,
+                year: None,
             },
             Date {
                 month: 5,
                 day: 4,
                 hour: 7.23,
+                year: None,
             },
             Date {
                 month: 7,
                 day: 5,
                 hour: 1.23,
+                year: None,
             },
             Date {
                 month: 12,
                 day: 6,
                 hour: 2.23,
+                year: None,
             },
         ];
 
@@ -798,31 +997,37 @@ mod tests {
                 month: 5,
                 day: 4,
                 hour: 7.23,
+                year: None,
             },
             Date {
                 month: 12,
                 day: 6,
                 hour: 2.23,
+                year: None,
             },
             Date {
                 month: 1,
                 day: 2,
                 hour: 2.23,
+                year: None,
             },
             Date {
                 month: 2,
                 day: 3,
                 hour: 1.23,
+                year: None,
             },
             Date {
                 month: 7,
                 day: 5,
                 hour: 1.23,
+                year: None,
             },
             Date {
                 month: 1,
                 day: 1,
                 hour: 1.23,
+                year: None,
             },
         ];
 
@@ -838,31 +1043,37 @@ mod tests {
                 month: 1,
                 day: 1,
                 hour: 1.23,
+                year: None,
             },
             Date {
                 month: 1,
                 day: 2,
                 hour: 2.23,
+                year: None,
             },
             Date {
                 month: 2,
                 day: 3,
                 hour: 1.23,
+                year: None,
             },
             Date {
                 month: 5,
                 day: 4,
                 hour: 7.23,
+                year: None,
             },
             Date {
                 month: 7,
                 day: 5,
                 hour: 1.23,
+                year: None,
             },
             Date {
                 month: 12,
                 day: 6,
                 hour: 2.23,
+                year: None,
             },
         ];
 
@@ -870,6 +1081,7 @@ mod tests {
             month: 2,
             day: 3,
             hour: 1.23,
+            year: None,
         }) {
             assert_eq!(i, 2)
         }
@@ -878,6 +1090,7 @@ mod tests {
             month: 2,
             day: 3,
             hour: 1.0,
+            year: None,
         }) {
             assert_eq!(i, 2)
         }
@@ -886,6 +1099,7 @@ mod tests {
             month: 12,
             day: 13,
             hour: 1.0,
+            year: None,
         }) {
             assert_eq!(i, 6)
         }
@@ -953,11 +1167,13 @@ mod tests {
             month: 12,
             day: 25,
             hour: 0.0,
+            year: None,
         };
         let end = Date {
             month: 12,
             day: 31,
             hour: 23.999722222222225,
+            year: None,
         };
         dbg!(end - start);
         let factory = Period::new(start, end, 3600.);
@@ -968,6 +1184,102 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_years() {
+        let mut d = Date {
+            month: 12,
+            day: 31,
+            hour: 23.,
+            year: Some(2019),
+        };
+        let before = d;
+
+        // Years advance when going through New Year...
+        d.add_hours(2.);
+        assert_eq!((d.year, d.month, d.day), (Some(2020), 1, 1));
+        assert!(d > before);
+        assert!((d - before - 2. / 24.).abs() < 1e-9);
+        assert_eq!(d.to_string(), "2020/01/01 - 1:00");
+
+        // ... and when adding whole years (2020 is a leap year)
+        d.add_days(366 + 365);
+        assert_eq!((d.year, d.month, d.day), (Some(2022), 1, 1));
+
+        // Dates without a year are compared as in the same year
+        let yearless = Date { year: None, ..d };
+        assert_eq!(yearless, d);
+        assert!(before > yearless);
+
+        // Interpolation goes through New Year
+        let mid = before.interpolate(
+            Date {
+                month: 1,
+                day: 1,
+                hour: 1.,
+                year: Some(2020),
+            },
+            0.75,
+        );
+        assert_eq!((mid.year, mid.month, mid.day), (Some(2020), 1, 1));
+        assert!((mid.hour - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_leap_years() {
+        use crate::Period;
+
+        let date = |year, month, day, hour| Date {
+            month,
+            day,
+            hour,
+            year,
+        };
+
+        // February 29 exists in leap years...
+        let mut d = date(Some(2020), 2, 28, 12.);
+        d.add_hours(24.);
+        assert_eq!((d.month, d.day), (2, 29));
+        assert_eq!(d.days_in_month(), 29);
+        d.add_hours(24.);
+        assert_eq!((d.month, d.day), (3, 1));
+
+        // ... but not in other years, or in dates without a year
+        for year in [Some(2021), None] {
+            let mut d = date(year, 2, 28, 12.);
+            d.add_hours(24.);
+            assert_eq!((d.month, d.day), (3, 1));
+        }
+        let mut d = date(None, 2, 29, 12.);
+        d.add_days(1);
+        assert_eq!((d.month, d.day), (3, 1));
+
+        // Days of the year are consistent
+        assert_eq!(date(Some(2020), 3, 1, 0.).day_of_year(), 60.);
+        assert_eq!(date(Some(2021), 3, 1, 0.).day_of_year(), 59.);
+        assert_eq!(date(None, 3, 1, 0.).day_of_year(), 59.);
+        assert_eq!(date(Some(2020), 12, 31, 0.).day_of_year(), 365.);
+        assert_eq!(date(Some(2020), 3, 1, 0.) - date(Some(2020), 2, 28, 0.), 2.);
+        assert_eq!(
+            date(Some(2021), 1, 1, 0.) - date(Some(2020), 1, 1, 0.),
+            366.
+        );
+        for n in [0., 58.5, 59.5, 60.5, 365.5] {
+            let d = Date::from_year_and_day(Some(2020), n);
+            assert!((d.day_of_year() - n).abs() < 1e-9, "n = {}", n);
+        }
+        let d = Date::from_year_and_day(Some(2020), -1.);
+        assert_eq!((d.year, d.month, d.day), (Some(2019), 12, 31));
+
+        let d = date(Some(2020), 2, 28, 12.).interpolate(date(Some(2020), 3, 1, 12.), 0.5);
+        assert_eq!((d.month, d.day), (2, 29));
+        assert!((d.hour - 12.).abs() < 1e-6);
+
+        // A whole leap year
+        let start = date(Some(2020), 1, 1, 0.);
+        let end = date(Some(2020), 12, 31, 23.5);
+        assert_eq!(Period::new(start, end, 3600.).count(), 8784 - 1);
+    }
+
     #[test]
     #[cfg(feature = "chrono")]
     fn test_into_naive_datetime() -> Result<(), String> {
@@ -977,6 +1289,7 @@ mod tests {
             month: 1,
             day: 1,
             hour: 23.9999,
+            year: None,
         };
 
         let year = 2025;
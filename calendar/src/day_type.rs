@@ -0,0 +1,198 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use crate::date::{days_between_years, Date};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A reference year starting on a Monday, for calculating the days of the week
+const REFERENCE_YEAR: i32 = 2001;
+
+/// The days of the week
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Weekday {
+    /// Monday
+    Monday,
+    /// Tuesday
+    Tuesday,
+    /// Wednesday
+    Wednesday,
+    /// Thursday
+    Thursday,
+    /// Friday
+    Friday,
+    /// Saturday
+    Saturday,
+    /// Sunday
+    Sunday,
+}
+
+impl Weekday {
+    /// All the days of the week, from Monday to Sunday
+    pub const ALL: [Weekday; 7] = [
+        Weekday::Monday,
+        Weekday::Tuesday,
+        Weekday::Wednesday,
+        Weekday::Thursday,
+        Weekday::Friday,
+        Weekday::Saturday,
+        Weekday::Sunday,
+    ];
+
+    /// The index of the day in the week, from 0 (Monday) to 6 (Sunday)
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+
+    /// Checks whether this is a Saturday or a Sunday
+    pub fn is_weekend(&self) -> bool {
+        matches!(self, Weekday::Saturday | Weekday::Sunday)
+    }
+}
+
+/// The kind of day, which usually determines the occupancy of a building
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DayType {
+    /// From Monday to Friday
+    Weekday,
+
+    /// Saturdays and Sundays
+    Weekend,
+
+    /// A public holiday, regardless of the day of the week
+    Holiday,
+}
+
+impl Date {
+    /// The day of the week of the date.
+    ///
+    /// > Note: Dates without a `year` are assumed to be in a year starting on a Monday.
+    ///
+    /// ```
+    /// use calendar::{Date, Weekday};
+    ///
+    /// let date = Date { month: 10, day: 16, hour: 12., year: Some(2026) };
+    /// assert_eq!(date.weekday(), Weekday::Friday);
+    ///
+    /// let date = Date { month: 1, day: 1, hour: 12., year: None };
+    /// assert_eq!(date.weekday(), Weekday::Monday);
+    /// ```
+    pub fn weekday(&self) -> Weekday {
+        let mut days = self.day_of_year().floor();
+        if let Some(year) = self.year {
+            days += days_between_years(REFERENCE_YEAR, year);
+        }
+        Weekday::ALL[(days as i64).rem_euclid(7) as usize]
+    }
+
+    /// Checks whether the date falls on one of the `holidays`. Holidays
+    /// without a `year` happen every year, and those with one only happen
+    /// in that year (if the date has a year). Hours are ignored.
+    pub fn is_holiday(&self, holidays: &[Date]) -> bool {
+        holidays.iter().any(|h| {
+            h.month == self.month
+                && h.day == self.day
+                && match (h.year, self.year) {
+                    (Some(a), Some(b)) => a == b,
+                    _ => true,
+                }
+        })
+    }
+
+    /// The [`DayType`] of the date, given a list of `holidays`
+    /// (see [`is_holiday`](Self::is_holiday))
+    ///
+    /// ```
+    /// use calendar::{Date, DayType};
+    ///
+    /// let christmas = Date { month: 12, day: 25, hour: 0., year: None };
+    /// let holidays = vec![christmas];
+    ///
+    /// let date = Date { month: 12, day: 25, hour: 10., year: Some(2024) };
+    /// assert_eq!(date.day_type(&holidays), DayType::Holiday);
+    ///
+    /// let date = Date { month: 12, day: 28, hour: 10., year: Some(2024) };
+    /// assert_eq!(date.day_type(&holidays), DayType::Weekend);
+    ///
+    /// let date = Date { month: 12, day: 27, hour: 10., year: Some(2024) };
+    /// assert_eq!(date.day_type(&holidays), DayType::Weekday);
+    /// ```
+    pub fn day_type(&self, holidays: &[Date]) -> DayType {
+        if self.is_holiday(holidays) {
+            DayType::Holiday
+        } else if self.weekday().is_weekend() {
+            DayType::Weekend
+        } else {
+            DayType::Weekday
+        }
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn test_weekday() {
+        let date = |year, month, day| Date {
+            month,
+            day,
+            hour: 23.5,
+            year,
+        };
+        assert_eq!(date(Some(2001), 1, 1).weekday(), Weekday::Monday);
+        assert_eq!(date(Some(2000), 12, 31).weekday(), Weekday::Sunday);
+        assert_eq!(date(Some(1969), 7, 20).weekday(), Weekday::Sunday);
+        assert_eq!(date(Some(2024), 2, 29).weekday(), Weekday::Thursday);
+        assert_eq!(date(Some(2024), 3, 1).weekday(), Weekday::Friday);
+        assert_eq!(date(Some(2100), 1, 1).weekday(), Weekday::Friday);
+
+        // Years without a year start on Monday
+        assert_eq!(date(None, 1, 7).weekday(), Weekday::Sunday);
+        assert_eq!(date(None, 12, 31).weekday(), Weekday::Monday);
+    }
+
+    #[test]
+    fn test_holidays() {
+        let date = |year, month, day| Date {
+            month,
+            day,
+            hour: 0.,
+            year,
+        };
+        let holidays = vec![date(None, 1, 1), date(Some(2024), 4, 25)];
+        assert!(date(Some(2030), 1, 1).is_holiday(&holidays));
+        assert!(date(Some(2024), 4, 25).is_holiday(&holidays));
+        assert!(!date(Some(2025), 4, 25).is_holiday(&holidays));
+        assert!(date(None, 4, 25).is_holiday(&holidays));
+        assert!(!date(None, 4, 26).is_holiday(&holidays));
+
+        // Jan 6 of a year without a year is a Saturday
+        assert_eq!(date(None, 1, 6).day_type(&holidays), DayType::Weekend);
+        assert_eq!(date(None, 1, 1).day_type(&holidays), DayType::Holiday);
+        assert_eq!(date(None, 1, 2).day_type(&holidays), DayType::Weekday);
+    }
+}
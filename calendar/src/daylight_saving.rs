@@ -0,0 +1,125 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use crate::date::Date;
+use crate::period::Period;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// The hour (in standard time) at which clocks change
+const CHANGE_HOUR: crate::Float = 2.;
+
+/// A daylight saving time rule: the period of the year in which clocks are
+/// one hour ahead of standard time. Clocks go forward at 2:00 (standard time)
+/// on the `start` day, and back at 2:00 (clock time) on the `end` day.
+///
+/// Simulations run in standard (i.e., solar-consistent) time, so this
+/// is used for reporting results in the local clock time.
+///
+/// ```
+/// use calendar::{Date, DaylightSaving};
+///
+/// // Southern hemisphere daylight saving time goes through New Year
+/// let dst = DaylightSaving {
+///     start: Date { month: 9, day: 24, hour: 0., year: None },
+///     end: Date { month: 4, day: 2, hour: 0., year: None },
+/// };
+///
+/// let summer = Date { month: 1, day: 10, hour: 12., year: None };
+/// assert!(dst.contains(summer));
+/// assert_eq!(dst.to_clock_time(summer).hour, 13.);
+///
+/// let winter = Date { month: 7, day: 10, hour: 12., year: None };
+/// assert_eq!(dst.to_clock_time(winter).hour, 12.);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DaylightSaving {
+    /// The day in which daylight saving time starts (the hour is ignored)
+    pub start: Date,
+
+    /// The day in which daylight saving time ends (the hour is ignored)
+    pub end: Date,
+}
+
+impl DaylightSaving {
+    /// Checks whether a date (in standard time) is within daylight saving time
+    pub fn contains(&self, date: Date) -> bool {
+        let at_change = |d: Date| Date {
+            hour: CHANGE_HOUR,
+            year: None,
+            ..d
+        };
+        // Clocks go back at 2:00 clock time (i.e., 1:00 standard time)
+        let mut end = at_change(self.end);
+        end.hour -= 1.;
+        Period::new(at_change(self.start), end, 3600.).contains(Date { year: None, ..date })
+    }
+
+    /// Transforms a date in standard time into local clock time
+    pub fn to_clock_time(&self, date: Date) -> Date {
+        let mut date = date;
+        if self.contains(date) {
+            date.add_hours(1.);
+        }
+        date
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn test_northern_hemisphere() {
+        let date = |month, day, hour| Date {
+            month,
+            day,
+            hour,
+            year: None,
+        };
+        let dst = DaylightSaving {
+            start: date(3, 10, 0.),
+            end: date(11, 3, 0.),
+        };
+
+        assert!(!dst.contains(date(3, 10, 1.5)));
+        assert!(dst.contains(date(3, 10, 2.)));
+        assert!(dst.contains(date(7, 1, 12.)));
+        assert!(dst.contains(date(11, 3, 0.5)));
+        assert!(!dst.contains(date(11, 3, 1.5)));
+        assert!(!dst.contains(date(12, 25, 12.)));
+
+        // The clock goes to the next day
+        let d = dst.to_clock_time(date(7, 1, 23.5));
+        assert_eq!((d.month, d.day, d.hour), (7, 2, 0.5));
+
+        // Years are kept
+        let d = dst.to_clock_time(Date {
+            year: Some(2024),
+            ..date(7, 1, 10.)
+        });
+        assert_eq!((d.year, d.hour), (Some(2024), 11.));
+    }
+}
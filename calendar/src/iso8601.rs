@@ -0,0 +1,299 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! ISO 8601 is unambiguous and understood by most external tools.
+//! Dates with a `year` are written as `YYYY-MM-DDThh:mm:ss`, and dates
+//! without one use the truncated representation `--MM-DDThh:mm:ss`.
+//!
+//! ```
+//! use calendar::Date;
+//!
+//! let date = Date::parse_iso8601("2024-02-29T13:30").unwrap();
+//! assert_eq!(date.year, Some(2024));
+//! assert_eq!(date.hour, 13.5);
+//! assert_eq!(date.iso8601().to_string(), "2024-02-29T13:30:00");
+//!
+//! let date = Date { month: 7, day: 4, hour: 9.25, year: None };
+//! assert_eq!(date.iso8601().to_string(), "--07-04T09:15:00");
+//! ```
+//!
+//! # Interaction with Serde
+//!
+//! When the `serde` feature is enabled, [`Date`]s can be deserialized
+//! from ISO 8601 strings (as well as from objects with `month`, `day`,
+//! `hour` and `year`). They are serialized as objects, unless the
+//! field is annotated with `#[serde(with = "calendar::iso8601")]`.
+
+use crate::{Date, Float};
+use std::fmt;
+
+#[cfg(feature = "serde")]
+use serde::{de, Deserialize, Deserializer, Serializer};
+
+/// Displays a [`Date`] in ISO 8601 format. Created by [`Date::iso8601`].
+#[derive(Clone, Copy, Debug)]
+pub struct Iso8601(pub Date);
+
+impl fmt::Display for Iso8601 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let date = self.0;
+        let seconds = (date.hour * 3600.).round() as u32;
+        match date.year {
+            Some(year) => write!(f, "{:04}-", year)?,
+            None => write!(f, "--")?,
+        }
+        write!(
+            f,
+            "{:02}-{:02}T{:02}:{:02}:{:02}",
+            date.month,
+            date.day,
+            seconds / 3600,
+            (seconds % 3600) / 60,
+            seconds % 60
+        )
+    }
+}
+
+impl Date {
+    /// Returns an object that displays the date in ISO 8601 format
+    /// (e.g., `2024-01-15T13:30:00`, or `--01-15T13:30:00` if the
+    /// date has no year).
+    pub fn iso8601(&self) -> Iso8601 {
+        Iso8601(*self)
+    }
+
+    /// Parses a date in ISO 8601 format. The date can be given as
+    /// `YYYY-MM-DD` or—for dates without a year—as `--MM-DD`, optionally
+    /// followed by a time (separated by a `T` or a space) as `hh:mm` or
+    /// `hh:mm:ss`. A trailing `Z` is accepted, but other time zones are
+    /// not. Dates without a time are at midnight.
+    pub fn parse_iso8601(s: &str) -> Result<Self, String> {
+        let err = || format!("Invalid ISO 8601 date '{}'", s);
+        let s = s.trim();
+        let trimmed = s.strip_suffix('Z').unwrap_or(s);
+        let (date, time) = match trimmed.split_once(['T', ' ']) {
+            Some((d, t)) => (d, Some(t)),
+            None => (trimmed, None),
+        };
+
+        // Date
+        let (year, month_day) = match date.strip_prefix("--") {
+            Some(md) => (None, md),
+            None => {
+                let (y, md) = date.split_once('-').ok_or_else(err)?;
+                if y.len() != 4 {
+                    return Err(err());
+                }
+                (Some(y.parse::<i32>().map_err(|_| err())?), md)
+            }
+        };
+        let (month, day) = month_day.split_once('-').ok_or_else(err)?;
+        let number = |v: &str| -> Result<u8, String> {
+            if v.len() != 2 {
+                return Err(err());
+            }
+            v.parse().map_err(|_| err())
+        };
+        let mut date = Date {
+            month: number(month)?,
+            day: number(day)?,
+            hour: 0.,
+            year,
+        };
+        if !(1..=12).contains(&date.month) || date.day == 0 || date.day > date.days_in_month() {
+            return Err(err());
+        }
+
+        // Time
+        if let Some(time) = time {
+            let mut parts = time.split(':');
+            let hour = number(parts.next().ok_or_else(err)?)? as Float;
+            let minute = match parts.next() {
+                Some(m) => number(m)? as Float,
+                None => 0.,
+            };
+            let second: Float = match parts.next() {
+                Some(v) if v.len() >= 2 => v.parse().map_err(|_| err())?,
+                Some(_) => return Err(err()),
+                None => 0.,
+            };
+            if parts.next().is_some() || minute >= 60. || second >= 60. {
+                return Err(err());
+            }
+            date.hour = hour + minute / 60. + second / 3600.;
+            if date.hour == 24. {
+                // The end of the day
+                date.hour = 0.;
+                date.add_days(1);
+            } else if date.hour > 24. {
+                return Err(err());
+            }
+        }
+        Ok(date)
+    }
+}
+
+/// Serializes a [`Date`] as an ISO 8601 string. Meant to be used
+/// as `#[serde(with = "calendar::iso8601")]`
+#[cfg(feature = "serde")]
+pub fn serialize<S: Serializer>(date: &Date, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(&date.iso8601())
+}
+
+/// Deserializes a [`Date`] from an ISO 8601 string or from an object.
+/// Meant to be used as `#[serde(with = "calendar::iso8601")]`
+#[cfg(feature = "serde")]
+pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Date, D::Error> {
+    Date::deserialize(deserializer)
+}
+
+/// The fields of a [`Date`] written as an object
+#[cfg(feature = "serde")]
+#[derive(Deserialize)]
+struct DateFields {
+    month: u8,
+    day: u8,
+    hour: Float,
+    #[serde(default)]
+    year: Option<i32>,
+}
+
+#[cfg(feature = "serde")]
+struct DateVisitor;
+
+#[cfg(feature = "serde")]
+impl<'de> de::Visitor<'de> for DateVisitor {
+    type Value = Date;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("an ISO 8601 date, or an object with a month, a day and an hour")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Date, E> {
+        Date::parse_iso8601(v).map_err(E::custom)
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Date, A::Error> {
+        let fields = DateFields::deserialize(de::value::MapAccessDeserializer::new(map))?;
+        Ok(Date {
+            month: fields.month,
+            day: fields.day,
+            hour: fields.hour,
+            year: fields.year,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(DateVisitor)
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn test_parse() -> Result<(), String> {
+        let d = Date::parse_iso8601("2021-11-28T21:00:09Z")?;
+        assert_eq!((d.year, d.month, d.day), (Some(2021), 11, 28));
+        assert!((d.hour - 21.0025).abs() < 1e-9);
+
+        let d = Date::parse_iso8601("--03-05 07:45")?;
+        assert_eq!((d.year, d.month, d.day), (None, 3, 5));
+        assert!((d.hour - 7.75).abs() < 1e-9);
+
+        let d = Date::parse_iso8601("2020-12-31")?;
+        assert_eq!((d.month, d.day, d.hour), (12, 31, 0.));
+
+        // End of the day
+        let d = Date::parse_iso8601("2020-12-31T24:00")?;
+        assert_eq!((d.year, d.month, d.day, d.hour), (Some(2021), 1, 1, 0.));
+
+        for bad in [
+            "",
+            "12/31",
+            "2020-13-01",
+            "2021-02-29",
+            "--02-29",
+            "2020-1-5",
+            "2020-01-05T25:00",
+            "2020-01-05T10:60",
+            "2020-01-05T10:00+02:00",
+        ] {
+            assert!(Date::parse_iso8601(bad).is_err(), "'{}' was parsed", bad);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_display() -> Result<(), String> {
+        let d = Date {
+            month: 1,
+            day: 2,
+            hour: 3.5,
+            year: Some(987),
+        };
+        assert_eq!(d.iso8601().to_string(), "0987-01-02T03:30:00");
+
+        // EPW-like hours are rounded to the second
+        let d = Date {
+            month: 1,
+            day: 2,
+            hour: 0.999999,
+            year: None,
+        };
+        assert_eq!(d.iso8601().to_string(), "--01-02T01:00:00");
+
+        // Round trip
+        for s in ["2024-02-29T13:14:15", "--12-31T23:59:00"] {
+            assert_eq!(Date::parse_iso8601(s)?.iso8601().to_string(), s);
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() -> Result<(), String> {
+        let d: Date = serde_json::from_str("\"2024-06-01T12:00\"").map_err(|e| e.to_string())?;
+        assert_eq!((d.year, d.month, d.day, d.hour), (Some(2024), 6, 1, 12.));
+
+        let d: Date = serde_json::from_str(r#"{"month": 9, "day": 4, "hour": 21}"#)
+            .map_err(|e| e.to_string())?;
+        assert_eq!((d.year, d.month, d.day, d.hour), (None, 9, 4, 21.));
+
+        assert!(serde_json::from_str::<Date>("\"2024-06-31\"").is_err());
+
+        #[derive(serde::Serialize)]
+        struct Wrapper {
+            #[serde(with = "crate::iso8601")]
+            date: Date,
+        }
+        let json = serde_json::to_string(&Wrapper { date: d }).map_err(|e| e.to_string())?;
+        assert_eq!(json, r#"{"date":"--09-04T21:00:00"}"#);
+        Ok(())
+    }
+}
@@ -21,10 +21,11 @@ SOFTWARE.
 #![deny(missing_docs)]
 
 //! This is a library containing nn extremely simple Date object. The
-//! purpose is to help perform Building Performance calculations, so it only
-//! contains month, day and hour (in decimals). **It does not consider years at all**,
-//! days and Months are counted from 1 (e.g. January is 1, not 0).
-//! We don't need anything else, I think.
+//! purpose is to help perform Building Performance calculations, so it
+//! contains month, day and hour (in decimals) and, optionally, a year. Days
+//! and Months are counted from 1 (e.g. January is 1, not 0).
+//! Dates without a year are year-agnostic, and years are only considered
+//! when comparing two dates that have them.
 //!
 //! # Interaction with Serde
 //!
@@ -39,6 +40,10 @@ SOFTWARE.
 //! assert_eq!(d.month, 9);
 //! assert_eq!(d.day, 4);
 //! assert!((d.hour - 21.).abs() < 1e-5);
+//!
+//! // Dates can also be written in ISO 8601 format
+//! let d : Date = serde_json::from_str(r#""2024-09-04T21:00""#).expect("Something bad happened!");
+//! assert_eq!(d.year, Some(2024));
 //! ```
 //!
 //! # Interaction with Chrono
@@ -71,6 +76,12 @@ type Float = f32;
 type Float = f64;
 
 mod date;
-pub use crate::date::Date;
+pub use crate::date::{is_leap_year, Date};
 mod period;
 pub use crate::period::Period;
+mod day_type;
+pub use crate::day_type::{DayType, Weekday};
+mod daylight_saving;
+/// Parsing and formatting of dates in ISO 8601 format
+pub mod iso8601;
+pub use crate::daylight_saving::DaylightSaving;
@@ -77,31 +77,37 @@ impl Period {
     /// use calendar::{Date, Period};
     ///
     /// let start = Date{
-    ///     month: 1, day: 2, hour: 1.23
+    ///     month: 1, day: 2, hour: 1.23,
+    ///     year: None,
     /// };
     ///
     /// let end = Date{
-    ///     month: 1, day: 3, hour: 1.23
+    ///     month: 1, day: 3, hour: 1.23,
+    ///     year: None,
     /// };
     /// let period = Period::new(start,end, 3600.);
     ///
     /// assert!(period.contains(Date{
-    ///     month: 1, day: 2, hour: 5.0
+    ///     month: 1, day: 2, hour: 5.0,
+    ///     year: None,
     /// }));
     ///
     /// // What about a period that loops through the end of the year?
     ///
     /// let start = Date{
-    ///     month: 12, day: 2, hour: 1.23
+    ///     month: 12, day: 2, hour: 1.23,
+    ///     year: None,
     /// };
     ///
     /// let end = Date{
-    ///     month: 1, day: 3, hour: 1.23
+    ///     month: 1, day: 3, hour: 1.23,
+    ///     year: None,
     /// };
     /// let period = Period::new(start,end, 3600.);
     ///
     /// assert!(period.contains(Date{
-    ///     month: 12, day: 5, hour: 5.0
+    ///     month: 12, day: 5, hour: 5.0,
+    ///     year: None,
     /// }));
     ///
     ///
@@ -154,11 +160,13 @@ mod tests {
             month: 1,
             day: 1,
             hour: 12.,
+            year: None,
         };
         let end = Date {
             month: 1,
             day: 2,
             hour: 12.,
+            year: None,
         };
         assert!(!Period::new(start, end, 0.1).goes_through_new_year);
 
@@ -166,11 +174,13 @@ mod tests {
             month: 12,
             day: 1,
             hour: 12.,
+            year: None,
         };
         let end = Date {
             month: 1,
             day: 2,
             hour: 12.,
+            year: None,
         };
         assert!(Period::new(start, end, 0.1).goes_through_new_year);
 
@@ -178,11 +188,13 @@ mod tests {
             month: 1,
             day: 1,
             hour: 12.,
+            year: None,
         };
         let end = Date {
             month: 1,
             day: 1,
             hour: 12.,
+            year: None,
         };
         assert!(!Period::new(start, end, 0.1).goes_through_new_year);
     }
@@ -194,11 +206,13 @@ mod tests {
                 month: 1,
                 day: 1,
                 hour: 0.0,
+                year: None,
             };
             let end = Date {
                 month: 12,
                 day: 31,
                 hour: 23.9,
+                year: None,
             };
             let dt = 60. * 60. / (n as Float);
             let dates = Period::new(start, end, dt);
@@ -222,17 +236,40 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_iterate_several_years() {
+        let start = Date {
+            month: 1,
+            day: 1,
+            hour: 0.0,
+            year: Some(2018),
+        };
+        let end = Date {
+            month: 12,
+            day: 31,
+            hour: 23.5,
+            year: Some(2020),
+        };
+        let period = Period::new(start, end, 3600.);
+        assert!(!period.goes_through_new_year);
+        // 2020 is a leap year
+        assert_eq!(period.count(), 2 * 8760 + 8784 - 1);
+        assert_eq!(period.last().and_then(|d| d.year), Some(2020));
+    }
+
     #[test]
     fn test_iterate_through_newyears() {
         let start = Date {
             month: 12,
             day: 31,
             hour: 0.0,
+            year: None,
         };
         let end = Date {
             month: 1,
             day: 2,
             hour: 23.99999,
+            year: None,
         };
 
         let f = Period::new(start, end, 1100.);
@@ -254,16 +291,19 @@ mod tests {
                 month: 1,
                 day: 1,
                 hour: 1.0,
+                year: None,
             },
             start: Date {
                 month: 12,
                 day: 25,
                 hour: 1.0,
+                year: None,
             },
             current: Date {
                 month: 12,
                 day: 25,
                 hour: 1.0,
+                year: None,
             },
             dt: 3600.0,
             goes_through_new_year: true,
@@ -0,0 +1,383 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::Float;
+use calendar::Date;
+use model::{Model, Schedule, SimulationState, Space};
+use std::collections::HashMap;
+use std::sync::Arc;
+use weather::CurrentWeather;
+
+/// The weight of the running mean of the previous day when calculating
+/// the running mean outdoor temperature of the next one
+const RUNNING_MEAN_ALPHA: Float = 0.8;
+
+/// The adaptive comfort standards that can be evaluated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdaptiveStandard {
+    /// ASHRAE Standard 55, with the limits for 80% acceptability.
+    ///
+    /// The comfort temperature is $`T_c = 0.31 T_{rm} + 17.8`$, and the
+    /// acceptable operative temperatures are within $`\pm 3.5 K`$ from it.
+    /// It is applicable for running mean outdoor temperatures between
+    /// $`10^oC`$ and $`33.5^oC`$.
+    Ashrae55,
+
+    /// EN 16798-1, with the limits for Category II (i.e., normal expectation).
+    ///
+    /// The comfort temperature is $`T_c = 0.33 T_{rm} + 18.8`$, and the
+    /// acceptable operative temperatures are between $`T_c - 4 K`$ and
+    /// $`T_c + 3 K`$. It is applicable for running mean outdoor temperatures
+    /// between $`10^oC`$ and $`30^oC`$.
+    En16798,
+}
+
+impl AdaptiveStandard {
+    /// All the standards
+    pub const ALL: [AdaptiveStandard; 2] = [AdaptiveStandard::Ashrae55, AdaptiveStandard::En16798];
+
+    /// The name of the standard
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ashrae55 => "ASHRAE-55",
+            Self::En16798 => "EN-16798",
+        }
+    }
+
+    /// Calculates the lower and upper limits of the acceptable operative
+    /// temperature (in C), for a certain running mean outdoor temperature.
+    /// Returns `None` if the standard is not applicable for such a temperature.
+    pub fn limits(&self, running_mean: Float) -> Option<(Float, Float)> {
+        let (min, max, a, b, below, above) = match self {
+            Self::Ashrae55 => (10., 33.5, 0.31, 17.8, 3.5, 3.5),
+            Self::En16798 => (10., 30., 0.33, 18.8, 4., 3.),
+        };
+        if !(min..=max).contains(&running_mean) {
+            return None;
+        }
+        let comfort = a * running_mean + b;
+        Some((comfort - below, comfort + above))
+    }
+}
+
+/// Calculates the exponentially weighted running mean outdoor temperature of
+/// each day, based on the daily mean outdoor temperatures.
+///
+/// ```math
+/// T_{rm,d} = (1 - \alpha) T_{ed,d-1} + \alpha T_{rm,d-1}
+/// ```
+///
+/// where $`\alpha = 0.8`$. Weather files are assumed to represent a cycle, so
+/// the days at the end are used for warming up the running mean of the first
+/// ones.
+pub fn running_mean_outdoor_temperatures(daily_means: &[Float]) -> Vec<Float> {
+    let n = daily_means.len();
+    if n == 0 {
+        return Vec::new();
+    }
+
+    // Warm up
+    let mut running_mean = daily_means[n - 1];
+    for t in daily_means.iter() {
+        running_mean = (1. - RUNNING_MEAN_ALPHA) * t + RUNNING_MEAN_ALPHA * running_mean;
+    }
+
+    let mut ret = Vec::with_capacity(n);
+    let mut yesterday = daily_means[n - 1];
+    for t in daily_means.iter() {
+        running_mean = (1. - RUNNING_MEAN_ALPHA) * yesterday + RUNNING_MEAN_ALPHA * running_mean;
+        ret.push(running_mean);
+        yesterday = *t;
+    }
+    ret
+}
+
+/// The statistics of a [`Space`] under an [`AdaptiveStandard`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AdaptiveComfortStatistics {
+    /// The number of (occupied) hours in which the standard was applicable
+    pub evaluated_hours: Float,
+
+    /// The number of hours with an operative temperature over the upper limit
+    pub hours_above: Float,
+
+    /// The number of hours with an operative temperature under the lower limit
+    pub hours_below: Float,
+
+    /// The integral of the difference between the operative temperature and the
+    /// upper limit, when the former exceeds the latter, in K h
+    pub degree_hours_above: Float,
+}
+
+impl AdaptiveComfortStatistics {
+    /// Accounts for `hours` hours at a certain operative temperature
+    fn record(&mut self, hours: Float, operative_temperature: Float, limits: (Float, Float)) {
+        let (lower, upper) = limits;
+        self.evaluated_hours += hours;
+        if operative_temperature > upper {
+            self.hours_above += hours;
+            self.degree_hours_above += hours * (operative_temperature - upper);
+        } else if operative_temperature < lower {
+            self.hours_below += hours;
+        }
+    }
+}
+
+/// Evaluates the adaptive comfort in each [`Space`] of a [`Model`]
+/// throughout a simulation, accumulating per-space overheating statistics.
+///
+/// The operative temperature is the average of the air temperature and the
+/// mean radiant temperature of the space. If the space has an occupancy
+/// schedule, only the hours in which it is occupied are evaluated.
+pub struct AdaptiveComfort {
+    /// The running mean outdoor temperature of each day, indexed by month and day
+    running_means: HashMap<(u8, u8), Float>,
+
+    /// The spaces and their occupancy schedule, if any
+    spaces: Vec<(Arc<Space>, Option<Arc<Schedule>>)>,
+
+    /// The statistics for each space and for each standard in [`AdaptiveStandard::ALL`]
+    statistics: Vec<[AdaptiveComfortStatistics; 2]>,
+}
+
+impl AdaptiveComfort {
+    /// Creates a new [`AdaptiveComfort`] for a [`Model`], calculating the
+    /// running mean outdoor temperatures from the weather data.
+    pub fn new(model: &Model, weather_data: &[CurrentWeather]) -> Result<Self, String> {
+        // Daily means, in chronological order
+        let mut days: Vec<(u8, u8)> = Vec::new();
+        let mut sums: Vec<(Float, usize)> = Vec::new();
+        for w in weather_data.iter() {
+            let key = (w.date.month, w.date.day);
+            if days.last() != Some(&key) {
+                days.push(key);
+                sums.push((0., 0));
+            }
+            let last = sums.len() - 1;
+            sums[last].0 += w.dry_bulb_temperature;
+            sums[last].1 += 1;
+        }
+        let daily_means: Vec<Float> = sums.iter().map(|(s, n)| s / *n as Float).collect();
+        let running_means = days
+            .into_iter()
+            .zip(running_mean_outdoor_temperatures(&daily_means))
+            .collect();
+
+        let mut spaces = Vec::with_capacity(model.spaces.len());
+        for space in model.spaces.iter() {
+            let schedule = match space.occupancy_schedule() {
+                Ok(name) => Some(model.get_schedule(name)?),
+                Err(_) => None,
+            };
+            spaces.push((Arc::clone(space), schedule));
+        }
+
+        Ok(Self {
+            running_means,
+            statistics: vec![Default::default(); spaces.len()],
+            spaces,
+        })
+    }
+
+    /// Accounts for a timestep of `dt` seconds, ending at `date`
+    pub fn record(&mut self, date: Date, dt: Float, state: &SimulationState) -> Result<(), String> {
+        let running_mean = match self.running_means.get(&(date.month, date.day)) {
+            Some(v) => *v,
+            None => return Ok(()),
+        };
+        let hours = dt / 3600.;
+        for ((space, schedule), stats) in self.spaces.iter().zip(self.statistics.iter_mut()) {
+            if let Some(schedule) = schedule {
+                if schedule.value(date)? <= 0. {
+                    continue;
+                }
+            }
+            let air_temperature = match space.dry_bulb_temperature(state) {
+                Some(v) => v,
+                None => continue,
+            };
+            let mrt = space
+                .mean_radiant_temperature(state)
+                .unwrap_or(air_temperature);
+            let operative_temperature = (air_temperature + mrt) / 2.;
+            for (standard, stats) in AdaptiveStandard::ALL.iter().zip(stats.iter_mut()) {
+                if let Some(limits) = standard.limits(running_mean) {
+                    stats.record(hours, operative_temperature, limits);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Gets the statistics of the space at index `space_index` under a
+    /// certain standard
+    pub fn statistics(
+        &self,
+        space_index: usize,
+        standard: AdaptiveStandard,
+    ) -> Option<AdaptiveComfortStatistics> {
+        let i = AdaptiveStandard::ALL.iter().position(|s| *s == standard)?;
+        self.statistics.get(space_index).map(|s| s[i])
+    }
+
+    /// Writes the statistics of all the spaces, in CSV format
+    pub fn write_report<T: std::io::Write>(&self, mut out: T) -> std::io::Result<()> {
+        writeln!(
+            out,
+            "Space,Standard,Evaluated hours,Hours above,Hours below,Degree-hours above"
+        )?;
+        for ((space, _), stats) in self.spaces.iter().zip(self.statistics.iter()) {
+            for (standard, s) in AdaptiveStandard::ALL.iter().zip(stats.iter()) {
+                writeln!(
+                    out,
+                    "{},{},{:.1},{:.1},{:.1},{:.1}",
+                    space.name(),
+                    standard.as_str(),
+                    s.evaluated_hours,
+                    s.hours_above,
+                    s.hours_below,
+                    s.degree_hours_above
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use model::SimulationStateElement;
+
+    #[test]
+    fn test_limits() {
+        let (lower, upper) = AdaptiveStandard::Ashrae55.limits(20.).unwrap();
+        assert!((lower - 20.5).abs() < 1e-9, "lower = {}", lower);
+        assert!((upper - 27.5).abs() < 1e-9, "upper = {}", upper);
+
+        let (lower, upper) = AdaptiveStandard::En16798.limits(20.).unwrap();
+        assert!((lower - 21.4).abs() < 1e-9, "lower = {}", lower);
+        assert!((upper - 28.4).abs() < 1e-9, "upper = {}", upper);
+
+        // Out of range
+        assert!(AdaptiveStandard::Ashrae55.limits(5.).is_none());
+        assert!(AdaptiveStandard::Ashrae55.limits(32.).is_some());
+        assert!(AdaptiveStandard::En16798.limits(32.).is_none());
+    }
+
+    #[test]
+    fn test_running_mean() {
+        // Constant temperature... constant running mean
+        let found = running_mean_outdoor_temperatures(&[15.; 10]);
+        assert!(found.iter().all(|t| (t - 15.).abs() < 1e-9));
+
+        // A warm day affects the following ones
+        let mut daily = vec![10.; 100];
+        daily[10] = 20.;
+        let found = running_mean_outdoor_temperatures(&daily);
+        assert!((found[10] - 10.).abs() < 1e-6, "found = {}", found[10]);
+        assert!((found[11] - 12.).abs() < 1e-6, "found = {}", found[11]);
+        assert!((found[12] - 11.6).abs() < 1e-6, "found = {}", found[12]);
+
+        assert!(running_mean_outdoor_temperatures(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_record() -> Result<(), String> {
+        let mut model = Model::default();
+        let space = model.add_space(Space::new("the space"));
+        let mut header = model.take_state().ok_or("Could not take state")?;
+        let i = header.push(SimulationStateElement::SpaceDryBulbTemperature(0), 30.)?;
+        space.set_dry_bulb_temperature_index(i)?;
+        let i = header.push(SimulationStateElement::SpaceMeanRadiantTemperature(0), 28.)?;
+        space.set_mean_radiant_temperature_index(i)?;
+        let mut state = header.take_values().ok_or("Could not take values")?;
+
+        // Two days at 20C
+        let mut weather_data = Vec::new();
+        for day in 1..=2 {
+            for hour in 0..24 {
+                weather_data.push(CurrentWeather {
+                    date: Date {
+                        month: 1,
+                        day,
+                        hour: hour as Float + 0.5,
+                        year: None,
+                    },
+                    dry_bulb_temperature: 20.,
+                    ..CurrentWeather::default()
+                });
+            }
+        }
+        let mut adaptive = AdaptiveComfort::new(&model, &weather_data)?;
+
+        // Operative temperature is 29C... too hot for both
+        let date = Date {
+            month: 1,
+            day: 1,
+            hour: 12.,
+            year: None,
+        };
+        adaptive.record(date, 1800., &state)?;
+        adaptive.record(date, 1800., &state)?;
+
+        // Not in the weather file... nothing happens
+        let other_date = Date {
+            month: 2,
+            day: 1,
+            hour: 12.,
+            year: None,
+        };
+        adaptive.record(other_date, 3600., &state)?;
+
+        let ashrae = adaptive
+            .statistics(0, AdaptiveStandard::Ashrae55)
+            .ok_or("No statistics")?;
+        assert!((ashrae.evaluated_hours - 1.).abs() < 1e-9);
+        assert!((ashrae.hours_above - 1.).abs() < 1e-9);
+        assert!((ashrae.degree_hours_above - 1.5).abs() < 1e-9);
+
+        let en = adaptive
+            .statistics(0, AdaptiveStandard::En16798)
+            .ok_or("No statistics")?;
+        assert!((en.hours_above - 1.).abs() < 1e-9);
+        assert!((en.degree_hours_above - 0.6).abs() < 1e-9);
+
+        // Cold
+        space.set_dry_bulb_temperature(&mut state, 18.)?;
+        space.set_mean_radiant_temperature(&mut state, 18.)?;
+        adaptive.record(date, 3600., &state)?;
+        let en = adaptive
+            .statistics(0, AdaptiveStandard::En16798)
+            .ok_or("No statistics")?;
+        assert!((en.evaluated_hours - 2.).abs() < 1e-9);
+        assert!((en.hours_below - 1.).abs() < 1e-9);
+
+        let mut report: Vec<u8> = Vec::new();
+        adaptive
+            .write_report(&mut report)
+            .map_err(|e| e.to_string())?;
+        let report = String::from_utf8(report).map_err(|e| e.to_string())?;
+        assert_eq!(report.lines().count(), 3);
+        assert!(report.contains("the space,ASHRAE-55,2.0,1.0,1.0,1.5"));
+
+        Ok(())
+    }
+}
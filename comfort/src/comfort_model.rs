@@ -0,0 +1,274 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use crate::pmv::{predicted_mean_vote, predicted_percentage_dissatisfied, ComfortInputs};
+use crate::Float;
+use calendar::Date;
+use communication::{ErrorHandling, MetaOptions, SimulationModel};
+use model::{Model, SimulationState, SimulationStateElement, SimulationStateHeader, Space};
+use std::borrow::Borrow;
+use std::sync::Arc;
+use weather::psychrometrics::{saturation_pressure, vapour_pressure, STANDARD_PRESSURE};
+use weather::WeatherTrait;
+
+/// The name of the module
+const MODULE_NAME: &str = "Comfort model";
+
+/// The insulation of the clothing of the occupants at the
+/// beginning of the simulation, in clo
+const DEFAULT_CLOTHING: Float = 1.0;
+
+/// The metabolic rate of the occupants at the beginning of the
+/// simulation, in met (i.e., sedentary activity)
+const DEFAULT_METABOLIC_RATE: Float = 1.2;
+
+/// The relative air velocity around the occupants, in m/s
+const DEFAULT_AIR_SPEED: Float = 0.1;
+
+/// The relative humidity (from 0 to 1) assumed in spaces whose
+/// humidity is not being calculated
+const DEFAULT_RELATIVE_HUMIDITY: Float = 0.5;
+
+/// The memory used by this module during simulation
+pub type ComfortModelMemory = ();
+
+/// The main model
+pub struct ComfortModel {
+    /// The spaces in the model
+    spaces: Vec<Arc<Space>>,
+}
+
+impl ErrorHandling for ComfortModel {
+    fn module_name() -> &'static str {
+        MODULE_NAME
+    }
+}
+
+impl SimulationModel for ComfortModel {
+    type OutputType = Self;
+    type OptionType = ();
+    type AllocType = ComfortModelMemory;
+
+    fn allocate_memory(&self, _state: &SimulationState) -> Result<Self::AllocType, String> {
+        Ok(())
+    }
+
+    /// Creates a new [`ComfortModel`].
+    ///
+    /// > Note: The clothing and metabolic rate of the occupants are operational
+    /// > elements of the state, so this model needs to be created before any other
+    /// > module pushes physical elements into it.
+    fn new<M: Borrow<Model>>(
+        _meta_options: &MetaOptions,
+        _options: (),
+        model: M,
+        state: &mut SimulationStateHeader,
+        _n: usize,
+    ) -> Result<Self::OutputType, String> {
+        let model = model.borrow();
+
+        // Operational elements first
+        for (i, space) in model.spaces.iter().enumerate() {
+            if space.clothing_index().is_none() {
+                let i = state.push(SimulationStateElement::SpaceClothing(i), DEFAULT_CLOTHING)?;
+                space.set_clothing_index(i)?;
+            }
+            if space.metabolic_rate_index().is_none() {
+                let i = state.push(
+                    SimulationStateElement::SpaceMetabolicRate(i),
+                    DEFAULT_METABOLIC_RATE,
+                )?;
+                space.set_metabolic_rate_index(i)?;
+            }
+        }
+
+        let mut spaces = Vec::with_capacity(model.spaces.len());
+        for (i, space) in model.spaces.iter().enumerate() {
+            let index = state.push(SimulationStateElement::SpacePredictedMeanVote(i), 0.)?;
+            space.set_predicted_mean_vote_index(index)?;
+            let index = state.push(
+                SimulationStateElement::SpacePredictedPercentageDissatisfied(i),
+                0.,
+            )?;
+            space.set_predicted_percentage_dissatisfied_index(index)?;
+            spaces.push(Arc::clone(space));
+        }
+
+        Ok(Self { spaces })
+    }
+
+    fn march<W: WeatherTrait, M: Borrow<Model>>(
+        &self,
+        _date: Date,
+        _weather: &W,
+        _model: M,
+        state: &mut SimulationState,
+        _alloc: &mut ComfortModelMemory,
+    ) -> Result<(), String> {
+        for space in self.spaces.iter() {
+            let air_temperature = space.dry_bulb_temperature(state).ok_or(format!(
+                "Could not get the dry bulb temperature of Space '{}'",
+                space.name()
+            ))?;
+            let mean_radiant_temperature = space
+                .mean_radiant_temperature(state)
+                .unwrap_or(air_temperature);
+            let vapour_pressure = match space.humidity_ratio(state) {
+                Some(w) => vapour_pressure(w, STANDARD_PRESSURE),
+                None => DEFAULT_RELATIVE_HUMIDITY * saturation_pressure(air_temperature),
+            };
+
+            let inputs = ComfortInputs {
+                air_temperature,
+                mean_radiant_temperature,
+                air_speed: DEFAULT_AIR_SPEED,
+                vapour_pressure,
+                metabolic_rate: space
+                    .metabolic_rate(state)
+                    .unwrap_or(DEFAULT_METABOLIC_RATE),
+                clothing: space.clothing(state).unwrap_or(DEFAULT_CLOTHING),
+            };
+            let pmv = predicted_mean_vote(&inputs)
+                .map_err(|e| format!("Space '{}': {}", space.name(), e))?;
+
+            space.set_predicted_mean_vote(state, pmv)?;
+            space.set_predicted_percentage_dissatisfied(
+                state,
+                predicted_percentage_dissatisfied(pmv),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use weather::SyntheticWeather;
+
+    fn get_model() -> Result<(Model, SimulationStateHeader), String> {
+        let mut model = Model::default();
+        model.add_space(Space::new("the space"));
+        let state = model.take_state().ok_or("Could not take state")?;
+        Ok((model, state))
+    }
+
+    fn march(
+        model: &Model,
+        mut header: SimulationStateHeader,
+        air_temperature: Float,
+        mean_radiant_temperature: Option<Float>,
+    ) -> Result<(ComfortModel, SimulationState), String> {
+        let comfort = ComfortModel::new(&MetaOptions::default(), (), model, &mut header, 1)?;
+
+        // These are usually pushed by the Thermal model
+        let space = &model.spaces[0];
+        let i = header.push(
+            SimulationStateElement::SpaceDryBulbTemperature(0),
+            air_temperature,
+        )?;
+        space.set_dry_bulb_temperature_index(i)?;
+        if let Some(mrt) = mean_radiant_temperature {
+            let i = header.push(SimulationStateElement::SpaceMeanRadiantTemperature(0), mrt)?;
+            space.set_mean_radiant_temperature_index(i)?;
+        }
+
+        let mut state = header.take_values().ok_or("Could not take values")?;
+        let date = Date {
+            month: 1,
+            day: 1,
+            hour: 12.,
+            year: None,
+        };
+        comfort.march(
+            date,
+            &SyntheticWeather::default(),
+            model,
+            &mut state,
+            &mut (),
+        )?;
+        Ok((comfort, state))
+    }
+
+    #[test]
+    fn test_no_mean_radiant_temperature() -> Result<(), String> {
+        // Without a thermal model, the air temperature is used instead
+        let (model, header) = get_model()?;
+        let (_, with_air) = march(&model, header, 22., None)?;
+        let (model, header) = get_model()?;
+        let (_, with_mrt) = march(&model, header, 22., Some(22.))?;
+        let space = &model.spaces[0];
+        assert_eq!(
+            space.predicted_mean_vote(&with_air),
+            space.predicted_mean_vote(&with_mrt)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_pmv() -> Result<(), String> {
+        let (model, header) = get_model()?;
+        let (_, state) = march(&model, header, 24., Some(24.))?;
+        let space = &model.spaces[0];
+        let exp = predicted_mean_vote(&ComfortInputs {
+            air_temperature: 24.,
+            mean_radiant_temperature: 24.,
+            air_speed: DEFAULT_AIR_SPEED,
+            vapour_pressure: DEFAULT_RELATIVE_HUMIDITY * saturation_pressure(24.),
+            metabolic_rate: DEFAULT_METABOLIC_RATE,
+            clothing: DEFAULT_CLOTHING,
+        })?;
+        let pmv = space.predicted_mean_vote(&state).ok_or("No PMV")?;
+        assert!((pmv - exp).abs() < 1e-9, "pmv = {}, exp = {}", pmv, exp);
+        // Warm-ish, with winter clothes
+        assert!(pmv > 0.);
+        let ppd = space
+            .predicted_percentage_dissatisfied(&state)
+            .ok_or("No PPD")?;
+        assert!((ppd - predicted_percentage_dissatisfied(pmv)).abs() < 1e-9);
+        Ok(())
+    }
+
+    #[test]
+    fn test_clothing() -> Result<(), String> {
+        let (model, header) = get_model()?;
+        let (comfort, mut state) = march(&model, header, 24., Some(24.))?;
+        let space = &model.spaces[0];
+        let warm = space.predicted_mean_vote(&state).ok_or("No PMV")?;
+
+        // Lighter clothes feel cooler
+        space.set_clothing(&mut state, 0.5)?;
+        let date = Date {
+            month: 1,
+            day: 1,
+            hour: 12.,
+            year: None,
+        };
+        comfort.march(
+            date,
+            &SyntheticWeather::default(),
+            &model,
+            &mut state,
+            &mut (),
+        )?;
+        let cool = space.predicted_mean_vote(&state).ok_or("No PMV")?;
+        assert!(cool < warm, "cool = {}, warm = {}", cool, warm);
+        Ok(())
+    }
+}
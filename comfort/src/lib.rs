@@ -0,0 +1,56 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+#![deny(missing_docs)]
+
+//! A simple thermal comfort model for SIMPLE Building Simulation
+//!
+//! It calculates Fanger's Predicted Mean Vote (PMV) and Predicted Percentage
+//! of Dissatisfied (PPD) of the occupants of each space, following ISO 7730.
+//! The air temperature and humidity are calculated by the `heat` and `air`
+//! modules (so this module needs to march after them), while the clothing and
+//! metabolic rate of the occupants can be set by the control algorithms.
+//!
+//! It also evaluates the adaptive comfort models of ASHRAE-55 and EN-16798,
+//! accumulating overheating statistics of each space throughout a simulation.
+
+/// The kind of Floating point number used in the
+/// library... the `"float"` feature means it becomes `f32`
+/// and `f64` is used otherwise.
+#[cfg(feature = "float")]
+pub type Float = f32;
+
+/// The kind of Floating point number used in the
+/// library... the `"float"` feature means it becomes `f32`
+/// and `f64` is used otherwise.
+#[cfg(not(feature = "float"))]
+pub type Float = f64;
+
+/// The main export of this module: A Simulation Model for
+/// calculating the thermal comfort in the spaces of the model.
+pub mod comfort_model;
+pub use comfort_model::ComfortModel;
+
+/// Fanger's PMV/PPD model
+pub mod pmv;
+
+/// Adaptive comfort evaluation, following ASHRAE-55 and EN-16798
+pub mod adaptive;
+pub use adaptive::{AdaptiveComfort, AdaptiveStandard};
@@ -0,0 +1,186 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::Float;
+
+/// The maximum number of iterations when calculating the
+/// temperature of the clothing
+const MAX_ITERATIONS: usize = 150;
+
+/// The temperature of the clothing is considered solved when two
+/// iterations (in hundreds of K) are closer than this value
+const TOLERANCE: Float = 1.5e-4;
+
+/// The inputs of Fanger's comfort model
+#[derive(Debug, Clone, Copy)]
+pub struct ComfortInputs {
+    /// The air temperature, in C
+    pub air_temperature: Float,
+
+    /// The mean radiant temperature, in C
+    pub mean_radiant_temperature: Float,
+
+    /// The relative air velocity, in m/s
+    pub air_speed: Float,
+
+    /// The partial pressure of water vapour in the air, in Pa
+    pub vapour_pressure: Float,
+
+    /// The metabolic rate, in met
+    pub metabolic_rate: Float,
+
+    /// The insulation of the clothing, in clo
+    pub clothing: Float,
+}
+
+/// Calculates Fanger's Predicted Mean Vote (PMV), following the
+/// algorithm in Annex D of ISO 7730. No external work is considered.
+pub fn predicted_mean_vote(inputs: &ComfortInputs) -> Result<Float, String> {
+    let ta = inputs.air_temperature;
+    let tr = inputs.mean_radiant_temperature;
+    let pa = inputs.vapour_pressure;
+
+    // Thermal insulation of the clothing, in m2K/W
+    let icl = 0.155 * inputs.clothing;
+    // Metabolic rate, in W/m2
+    let m = inputs.metabolic_rate * 58.15;
+    // Clothing area factor
+    let fcl = if icl <= 0.078 {
+        1. + 1.29 * icl
+    } else {
+        1.05 + 0.645 * icl
+    };
+    // Heat transfer coefficient by forced convection
+    let hcf = 12.1 * inputs.air_speed.max(0.).sqrt();
+    let taa = ta + 273.;
+    let tra = tr + 273.;
+
+    // Iterate for the surface temperature of the clothing
+    let tcla = taa + (35.5 - ta) / (3.5 * icl + 0.1);
+    let p1 = icl * fcl;
+    let p2 = p1 * 3.96;
+    let p3 = p1 * 100.;
+    let p4 = p1 * taa;
+    let p5 = 308.7 - 0.028 * m + p2 * (tra / 100.).powi(4);
+    let mut xn = tcla / 100.;
+    let mut xf = tcla / 50.;
+    let mut hc = hcf;
+    let mut n = 0;
+    while (xn - xf).abs() > TOLERANCE {
+        xf = (xf + xn) / 2.;
+        let hcn = 2.38 * (100. * xf - taa).abs().powf(0.25);
+        hc = hcf.max(hcn);
+        xn = (p5 + p4 * hc - p2 * xf.powi(4)) / (100. + p3 * hc);
+        n += 1;
+        if n > MAX_ITERATIONS {
+            return Err(format!(
+                "The temperature of the clothing did not converge after {} iterations",
+                MAX_ITERATIONS
+            ));
+        }
+    }
+    let tcl = 100. * xn - 273.;
+
+    // Heat losses
+    // ... skin diffusion
+    let hl1 = 3.05e-3 * (5733. - 6.99 * m - pa);
+    // ... sweating
+    let hl2 = if m > 58.15 { 0.42 * (m - 58.15) } else { 0. };
+    // ... latent respiration
+    let hl3 = 1.7e-5 * m * (5867. - pa);
+    // ... dry respiration
+    let hl4 = 0.0014 * m * (34. - ta);
+    // ... radiation
+    let hl5 = 3.96 * fcl * (xn.powi(4) - (tra / 100.).powi(4));
+    // ... convection
+    let hl6 = fcl * hc * (tcl - ta);
+
+    let ts = 0.303 * (-0.036 * m).exp() + 0.028;
+    Ok(ts * (m - hl1 - hl2 - hl3 - hl4 - hl5 - hl6))
+}
+
+/// Calculates the Predicted Percentage of Dissatisfied (PPD), in %, that
+/// corresponds to a certain Predicted Mean Vote
+pub fn predicted_percentage_dissatisfied(pmv: Float) -> Float {
+    100. - 95. * (-0.03353 * pmv.powi(4) - 0.2179 * pmv.powi(2)).exp()
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use air::humidity::saturation_pressure;
+
+    /// Builds the inputs of the examples in Table D.1 of ISO 7730
+    fn inputs(
+        air_temperature: Float,
+        mean_radiant_temperature: Float,
+        air_speed: Float,
+        relative_humidity: Float,
+        clothing: Float,
+    ) -> ComfortInputs {
+        ComfortInputs {
+            air_temperature,
+            mean_radiant_temperature,
+            air_speed,
+            vapour_pressure: relative_humidity * saturation_pressure(air_temperature),
+            metabolic_rate: 1.2,
+            clothing,
+        }
+    }
+
+    fn check(inputs: ComfortInputs, exp_pmv: Float, exp_ppd: Float) -> Result<(), String> {
+        let pmv = predicted_mean_vote(&inputs)?;
+        let ppd = predicted_percentage_dissatisfied(pmv);
+        assert!(
+            (pmv - exp_pmv).abs() < 0.02,
+            "pmv = {}, exp = {}",
+            pmv,
+            exp_pmv
+        );
+        assert!(
+            (ppd - exp_ppd).abs() < 1.,
+            "ppd = {}, exp = {}",
+            ppd,
+            exp_ppd
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_iso_7730() -> Result<(), String> {
+        check(inputs(22., 22., 0.1, 0.6, 0.5), -0.75, 17.)?;
+        check(inputs(27., 27., 0.1, 0.6, 0.5), 0.77, 17.)?;
+        check(inputs(27., 27., 0.3, 0.6, 0.5), 0.44, 9.)?;
+        check(inputs(23.5, 25.5, 0.1, 0.6, 0.5), -0.01, 5.)?;
+        check(inputs(19., 19., 0.1, 0.4, 1.0), -0.60, 13.)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_ppd() {
+        // Neutral... 5% are always dissatisfied
+        assert!((predicted_percentage_dissatisfied(0.) - 5.).abs() < 1e-9);
+        // Symmetric
+        let a = predicted_percentage_dissatisfied(1.3);
+        let b = predicted_percentage_dissatisfied(-1.3);
+        assert!((a - b).abs() < 1e-9);
+        assert!(a > 30.);
+    }
+}
@@ -48,6 +48,22 @@ pub struct MetaOptions {
 
     /// The elevation of the site, in meters
     pub elevation: Float,
+
+    /// The daylight saving time rule used for reporting the results
+    /// in local clock time. If `None`, results are reported in
+    /// standard time (i.e., the time in which the simulation runs).
+    pub daylight_saving: Option<DaylightSaving>,
+}
+
+impl MetaOptions {
+    /// Transforms a date of the simulation (which runs in standard time)
+    /// into the date in which it should be reported
+    pub fn report_date(&self, date: Date) -> Date {
+        match &self.daylight_saving {
+            Some(dst) => dst.to_clock_time(date),
+            None => date,
+        }
+    }
 }
 
 /// Helps communicating issues to user
@@ -69,7 +85,7 @@ pub trait ErrorHandling {
     }
 }
 
-use calendar::Date;
+use calendar::{Date, DaylightSaving};
 use model::{Model, SimulationState, SimulationStateHeader};
 use std::borrow::Borrow;
 use weather::WeatherTrait;
@@ -93,7 +109,7 @@ pub trait SimulationModel: ErrorHandling {
     /// # Arguments;
     /// * A model model
     /// * A model state model (data important to the Sim Engine will be added)
-    /// * The number of timesteps per hour for the main simulation... internally, each simulation model will choose its own sub-subdivition (e.g. half or one third of what is asked for)
+    /// * The number of timesteps per hour for this model, which may differ from the one of the main simulation (see [`SubStepping`])... internally, each simulation model will choose its own sub-subdivition (e.g. half or one third of what is asked for)
     fn new<M: Borrow<Model>>(
         meta_option: &MetaOptions,
         options: Self::OptionType,
@@ -102,7 +118,8 @@ pub trait SimulationModel: ErrorHandling {
         n: usize,
     ) -> Result<Self::OutputType, String>;
 
-    /// Marchs forward in the simulation.
+    /// Marchs forward in the simulation by one of its timesteps (i.e., `1/n`
+    /// hours, where `n` is the number of timesteps per hour given to `new`).
     /// # Arguments
     /// * The model
     /// * The model state (will be modified)
@@ -122,3 +139,72 @@ pub trait SimulationModel: ErrorHandling {
     /// during the simulation
     fn allocate_memory(&self, state: &SimulationState) -> Result<Self::AllocType, String>;
 }
+
+/// How a [`SimulationModel`] whose timestep differs from the main timestep
+/// of a simulation is kept synchronized with it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubStepping {
+    /// The model is marched this many times within each main timestep
+    /// (e.g., for fast dynamics)
+    Substeps(usize),
+
+    /// The model is marched once every this many main timesteps (e.g.,
+    /// for slowly changing results). In between, the values it calculated
+    /// remain in the state.
+    Every(usize),
+}
+
+impl SubStepping {
+    /// Negotiates how a model that should be marched `model_n` times per
+    /// hour is synchronized with a simulation with `n` timesteps per hour.
+    /// One must be a multiple of the other, so that both are synchronized
+    /// at the end of every coarser timestep.
+    pub fn negotiate(n: usize, model_n: usize) -> Result<Self, String> {
+        if n == 0 || model_n == 0 {
+            return Err("The number of timesteps per hour must be larger than 0".to_string());
+        }
+        if model_n >= n && model_n % n == 0 {
+            Ok(Self::Substeps(model_n / n))
+        } else if n % model_n == 0 {
+            Ok(Self::Every(n / model_n))
+        } else {
+            Err(format!(
+                "Cannot synchronize a module with {} timesteps per hour with a simulation with {}... one of them must be a multiple of the other",
+                model_n, n
+            ))
+        }
+    }
+
+    /// The number of timesteps per hour of the model, given the number
+    /// of timesteps per hour `n` of the simulation
+    pub fn timesteps_per_hour(&self, n: usize) -> usize {
+        match self {
+            Self::Substeps(k) => n * k,
+            Self::Every(k) => n / k,
+        }
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn test_negotiate() -> Result<(), String> {
+        assert_eq!(SubStepping::negotiate(4, 4)?, SubStepping::Substeps(1));
+        assert_eq!(SubStepping::negotiate(4, 60)?, SubStepping::Substeps(15));
+        assert_eq!(SubStepping::negotiate(4, 1)?, SubStepping::Every(4));
+        assert_eq!(SubStepping::negotiate(6, 2)?, SubStepping::Every(3));
+        assert!(SubStepping::negotiate(4, 6).is_err());
+        assert!(SubStepping::negotiate(4, 3).is_err());
+        assert!(SubStepping::negotiate(4, 0).is_err());
+
+        assert_eq!(SubStepping::Substeps(15).timesteps_per_hour(4), 60);
+        assert_eq!(SubStepping::Every(4).timesteps_per_hour(4), 1);
+        Ok(())
+    }
+}
@@ -47,7 +47,13 @@ fn object_location(typename: String) -> Option<&'static str> {
         ("Building", "buildings"),
         ("Fenestration", "fenestrations"),
         ("HVAC", "hvacs"),
+        ("Thermostat", "thermostats"),
+        ("Photovoltaic", "photovoltaics"),
+        ("SolarCollector", "solar_collectors"),
+        ("WaterTank", "water_tanks"),
+        ("SensorGrid", "sensor_grids"),
         ("Luminaire", "luminaires"),
+        ("ElectricEquipment", "electric_equipment"),
         ("SiteDetails", "site_details"),
         ("Object", "objects"),
     ]);
@@ -63,7 +69,17 @@ fn object_has_api(typename: String) -> bool {
     let typename_bytes = typename.as_bytes();
     matches!(
         typename_bytes,
-        b"Space" | b"Surface" | b"Fenestration" | b"HVAC" | b"Luminaire"
+        b"Space"
+            | b"Surface"
+            | b"Fenestration"
+            | b"HVAC"
+            | b"Luminaire"
+            | b"ElectricEquipment"
+            | b"Thermostat"
+            | b"Photovoltaic"
+            | b"SolarCollector"
+            | b"WaterTank"
+            | b"SensorGrid"
     )
 }
 
@@ -146,6 +162,9 @@ pub fn derive_input_output(input: TokenStream) -> TokenStream {
         .gen_state_getters_setters()
         .expect("Could not generate setters getters");
 
+    // Clones with their own state indexes
+    let deep_clone = obj.gen_deep_clone().expect("Could not generate deep_clone");
+
     // docs
     let docs = obj.gen_docs();
 
@@ -179,6 +198,8 @@ pub fn derive_input_output(input: TokenStream) -> TokenStream {
 
             #state_getters_setters
 
+            #deep_clone
+
 
 
         }
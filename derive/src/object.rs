@@ -60,6 +60,13 @@ impl Object {
         }
     }
 
+    pub fn gen_deep_clone(&self) -> Result<TokenStream2, String> {
+        match self {
+            Self::StructObject(s) => s.gen_deep_clone(),
+            Self::Enum(_s) => Ok(quote!()), // don't have state
+        }
+    }
+
     pub fn gen_display(&self) -> TokenStream2 {
         match self {
             Self::StructObject(s) => s.gen_display(),
@@ -187,12 +187,12 @@ impl StructObject {
 
     fn collect_required_fields(&self) -> TokenStream2 {
         let mut req_field_names = quote!();
-        self.fields.iter().enumerate().for_each(|(i, f)| match f {
+        self.fields.iter().for_each(|f| match f {
             Field::Option(_) | Field::State(_) | Field::Vec(_) => {}
             Field::String(_) => {
                 let data = f.data();
                 let f_ident = data.ident;
-                if i == 0 {
+                if req_field_names.is_empty() {
                     req_field_names = quote!(#f_ident: S);
                 } else {
                     req_field_names = quote!(#req_field_names, #f_ident : S);
@@ -202,7 +202,7 @@ impl StructObject {
                 let data = f.data();
                 let f_ident = data.ident;
                 let f_type = data.ty;
-                if i == 0 {
+                if req_field_names.is_empty() {
                     req_field_names = quote!(#f_ident: #f_type);
                 } else {
                     req_field_names = quote!(#req_field_names, #f_ident : #f_type);
@@ -481,6 +481,38 @@ impl StructObject {
         ))
     }
 
+    pub fn gen_deep_clone(&self) -> Result<TokenStream2, String> {
+        let mut fields: TokenStream2 = quote!();
+        for f in self.fields.iter() {
+            if let Field::State(_d) = f {
+                let f_ident = f.data().ident.clone().ok_or("No identity")?;
+                let cant_lock_err = format!(
+                    "Field '{}' in {} cannot be locked for reading.",
+                    self.ident, f_ident
+                );
+                fields = quote!(
+                    #fields
+                    let index = *self.#f_ident.lock().map_err(|_| #cant_lock_err.to_string())?;
+                    ret.#f_ident = std::sync::Arc::new(std::sync::Mutex::new(index));
+                );
+            }
+        }
+        if fields.is_empty() {
+            return Ok(quote!());
+        }
+
+        let object_name = &self.ident;
+        let doc = format!(" Clones this [`{}`], but—unlike `clone()`, which shares them—giving the copy its own indexes within the [`SimulationState`], starting with the same values. This allows building a second physics model on the copy.", object_name);
+        Ok(quote!(
+            #[doc = #doc]
+            pub fn deep_clone(&self) -> Result<Self, String> {
+                let mut ret = self.clone();
+                #fields
+                Ok(ret)
+            }
+        ))
+    }
+
     fn get_api_getters_setters_docs(&self) -> Result<(TokenStream2, TokenStream2, String), String> {
         let object_name = self.ident.clone();
         let mut field_getters = quote!();
@@ -0,0 +1,269 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use crate::Float;
+use calendar::Date;
+use communication::{ErrorHandling, MetaOptions, SimulationModel};
+use model::{Model, SimulationState, SimulationStateElement, SimulationStateHeader};
+use polynomial::Polynomial;
+use std::borrow::Borrow;
+use weather::WeatherTrait;
+
+/// The name of the module
+const MODULE_NAME: &str = "Electrical model";
+
+/// The default efficiency of a `Photovoltaic` panel at rated conditions
+const DEFAULT_RATED_EFFICIENCY: Float = 0.17;
+
+/// The default efficiency of the inverter of a `Photovoltaic` panel
+const DEFAULT_INVERTER_EFFICIENCY: Float = 0.96;
+
+/// The solar irradiance at rated conditions, in W/m2
+const RATED_IRRADIANCE: Float = 1000.;
+
+/// The memory used by this module during simulation
+pub type ElectricalModelMemory = ();
+
+/// The information needed for calculating the power generated by
+/// a `Photovoltaic` panel
+struct PhotovoltaicGenerator {
+    /// The area covered by photovoltaic cells, in m2
+    area: Float,
+
+    /// The efficiency of the panel at rated conditions
+    rated_efficiency: Float,
+
+    /// The polynomial of the irradiance (relative to the rated
+    /// irradiance) that multiplies the `rated_efficiency`
+    efficiency_curve: Polynomial,
+
+    /// The efficiency of the inverter
+    inverter_efficiency: Float,
+}
+
+impl PhotovoltaicGenerator {
+    /// Calculates the power generated (after the inverter) when the
+    /// panel receives a certain `irradiance`, in W/m2
+    fn power(&self, irradiance: Float) -> Float {
+        if irradiance <= 0.0 {
+            return 0.0;
+        }
+        let efficiency =
+            self.rated_efficiency * self.efficiency_curve.eval(irradiance / RATED_IRRADIANCE);
+        let power = irradiance * self.area * efficiency * self.inverter_efficiency;
+        power.max(0.0)
+    }
+}
+
+/// Builds a [`Polynomial`] out of a set of coefficients. If no coefficients
+/// are given, the polynomial is the constant 1.
+fn curve(coefficients: Result<&Vec<Float>, String>) -> Result<Polynomial, String> {
+    let mut ret = Polynomial::new();
+    match coefficients {
+        Ok(coefficients) => {
+            if coefficients.len() > ret.coefficients.len() {
+                return Err(format!(
+                    "Efficiency curves can have up to {} coefficients... found {}",
+                    ret.coefficients.len(),
+                    coefficients.len()
+                ));
+            }
+            coefficients.iter().for_each(|c| ret.push(*c));
+        }
+        Err(_) => ret.push(1.),
+    }
+    Ok(ret)
+}
+
+/// The main model
+pub struct ElectricalModel {
+    /// The generators corresponding to each `Photovoltaic` in the model
+    photovoltaics: Vec<PhotovoltaicGenerator>,
+}
+
+impl ErrorHandling for ElectricalModel {
+    fn module_name() -> &'static str {
+        MODULE_NAME
+    }
+}
+
+impl SimulationModel for ElectricalModel {
+    type OutputType = Self;
+    type OptionType = ();
+    type AllocType = ElectricalModelMemory;
+
+    fn allocate_memory(&self, _state: &SimulationState) -> Result<Self::AllocType, String> {
+        Ok(())
+    }
+
+    fn new<M: Borrow<Model>>(
+        _meta_options: &MetaOptions,
+        _options: (),
+        model: M,
+        state: &mut SimulationStateHeader,
+        _n: usize,
+    ) -> Result<Self::OutputType, String> {
+        let model = model.borrow();
+
+        let mut photovoltaics = Vec::with_capacity(model.photovoltaics.len());
+        for (i, pv) in model.photovoltaics.iter().enumerate() {
+            if pv.generated_power_index().is_none() {
+                let i = state.push(SimulationStateElement::PhotovoltaicGeneratedPower(i), 0.0)?;
+                pv.set_generated_power_index(i)?;
+            }
+
+            photovoltaics.push(PhotovoltaicGenerator {
+                area: pv.area(model)?,
+                rated_efficiency: pv.rated_efficiency_or(MODULE_NAME, DEFAULT_RATED_EFFICIENCY),
+                efficiency_curve: curve(pv.efficiency_curve())?,
+                inverter_efficiency: pv
+                    .inverter_efficiency_or(MODULE_NAME, DEFAULT_INVERTER_EFFICIENCY),
+            });
+        }
+
+        Ok(Self { photovoltaics })
+    }
+
+    fn march<W: WeatherTrait, M: Borrow<Model>>(
+        &self,
+        _date: Date,
+        _weather: &W,
+        model: M,
+        state: &mut SimulationState,
+        _alloc: &mut ElectricalModelMemory,
+    ) -> Result<(), String> {
+        let model = model.borrow();
+        for (pv, generator) in model.photovoltaics.iter().zip(self.photovoltaics.iter()) {
+            let irradiance = pv.incident_solar_irradiance(state).ok_or(format!(
+                "Could not get the incident solar irradiance of Photovoltaic '{}'",
+                pv.name()
+            ))?;
+            pv.set_generated_power(state, generator.power(irradiance))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use geometry::{Loop3D, Point3D, Polygon3D};
+    use model::Photovoltaic;
+    use schedule::ScheduleConstant;
+    use weather::SyntheticWeather;
+
+    fn get_model(pv: Photovoltaic) -> Result<(Model, SimulationStateHeader), String> {
+        let mut model = Model::default();
+        let mut state = SimulationStateHeader::new();
+        let pv = model.add_photovoltaic(pv)?;
+        // This is usually done by the Solar model
+        let i = state.push(SimulationStateElement::PhotovoltaicSolarIrradiance(0), 0.0)?;
+        pv.set_incident_solar_irradiance_index(i)?;
+        Ok((model, state))
+    }
+
+    fn panel(name: &str) -> Result<Photovoltaic, String> {
+        let mut the_loop = Loop3D::new();
+        the_loop.push(Point3D::new(0., 0., 0.))?;
+        the_loop.push(Point3D::new(2., 0., 0.))?;
+        the_loop.push(Point3D::new(2., 5., 0.))?;
+        the_loop.push(Point3D::new(0., 5., 0.))?;
+        the_loop.close()?;
+        let mut pv = Photovoltaic::new(name);
+        pv.set_vertices(Polygon3D::new(the_loop)?);
+        Ok(pv)
+    }
+
+    fn march(
+        model: &Model,
+        state_header: SimulationStateHeader,
+        irradiance: Float,
+    ) -> Result<Float, String> {
+        let meta_options = MetaOptions::default();
+        let mut state_header = state_header;
+        let electrical = ElectricalModel::new(&meta_options, (), model, &mut state_header, 1)?;
+        let mut state = state_header.take_values().ok_or("Could not take values")?;
+        model.photovoltaics[0].set_incident_solar_irradiance(&mut state, irradiance)?;
+
+        let mut weather = SyntheticWeather::default();
+        weather.dry_bulb_temperature = Box::new(ScheduleConstant::new(20.));
+        let date = Date {
+            month: 1,
+            day: 1,
+            hour: 12.,
+            year: None,
+        };
+        electrical.march(date, &weather, model, &mut state, &mut ())?;
+
+        model.photovoltaics[0]
+            .generated_power(&state)
+            .ok_or("No generated power".to_string())
+    }
+
+    #[test]
+    fn test_defaults() -> Result<(), String> {
+        let (model, state) = get_model(panel("the panel")?)?;
+        let found = march(&model, state, 800.)?;
+        let exp = 800. * 10. * DEFAULT_RATED_EFFICIENCY * DEFAULT_INVERTER_EFFICIENCY;
+        assert!(
+            (found - exp).abs() < 1e-6,
+            "found = {}, exp = {}",
+            found,
+            exp
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_sun() -> Result<(), String> {
+        let (model, state) = get_model(panel("the panel")?)?;
+        let found = march(&model, state, 0.)?;
+        assert!(found.abs() < 1e-9, "found = {}", found);
+        Ok(())
+    }
+
+    #[test]
+    fn test_curve() -> Result<(), String> {
+        let mut pv = panel("the panel")?;
+        pv.set_area_fraction(0.5)
+            .set_rated_efficiency(0.2)
+            .set_efficiency_curve(vec![0.9, 0.1])
+            .set_inverter_efficiency(0.9);
+        let (model, state) = get_model(pv)?;
+        let found = march(&model, state, 500.)?;
+        let exp = 500. * 5. * 0.2 * (0.9 + 0.1 * 0.5) * 0.9;
+        assert!(
+            (found - exp).abs() < 1e-6,
+            "found = {}, exp = {}",
+            found,
+            exp
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_too_many_coefficients() -> Result<(), String> {
+        let mut pv = panel("the panel")?;
+        pv.set_efficiency_curve(vec![1.; 20]);
+        let (model, mut state) = get_model(pv)?;
+        let meta_options = MetaOptions::default();
+        assert!(ElectricalModel::new(&meta_options, (), &model, &mut state, 1).is_err());
+        Ok(())
+    }
+}
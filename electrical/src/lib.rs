@@ -0,0 +1,46 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+#![deny(missing_docs)]
+
+//! A simple Electrical model for SIMPLE Building Simulation
+//!
+//! It calculates the electricity generated by the `Photovoltaic` panels
+//! in the model, based on the solar irradiance they receive (which
+//! is calculated by the `light` module, so this module needs to march
+//! after it). The generated power can then be reported alongside the heating,
+//! cooling and lighting consumption.
+
+/// The kind of Floating point number used in the
+/// library... the `"float"` feature means it becomes `f32`
+/// and `f64` is used otherwise.
+#[cfg(feature = "float")]
+pub type Float = f32;
+
+/// The kind of Floating point number used in the
+/// library... the `"float"` feature means it becomes `f32`
+/// and `f64` is used otherwise.
+#[cfg(not(feature = "float"))]
+pub type Float = f64;
+
+/// The main export of this module: A Simulation Model for
+/// calculating the electricity generated in the model.
+pub mod electrical_model;
+pub use electrical_model::ElectricalModel;
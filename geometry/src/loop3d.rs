@@ -27,7 +27,7 @@ use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
 
 use crate::{BBox3D, Float};
-use crate::{Point3D, Segment3D, Vector3D};
+use crate::{Point3D, Segment3D, Transform, Vector3D};
 
 /// A set of [`Point3D`] in sequence, forming a closed loop.
 /// It has some particularities.
@@ -323,6 +323,45 @@ impl Loop3D {
         self.vertices = self.vertices.iter().rev().copied().collect();
     }
 
+    /// Applies a [`Transform`] to all the vertices of the [`Loop3D`], updating
+    /// its normal, area and perimeter.
+    ///
+    /// If the [`Transform`] changes the hand-ness of the coordinate system
+    /// (e.g., a mirror), the order of the vertices is reversed so that the normal
+    /// keeps pointing towards the (transformed) side it was pointing at.
+    ///
+    /// # Example
+    /// ```
+    /// use geometry::{Loop3D, Point3D, Transform, Vector3D};
+    ///
+    /// let mut l = Loop3D::new();
+    /// l.push(Point3D::new(0., 0., 0.)).unwrap();
+    /// l.push(Point3D::new(1., 0., 0.)).unwrap();
+    /// l.push(Point3D::new(1., 1., 0.)).unwrap();
+    /// l.close().unwrap();
+    ///
+    /// l.transform(&Transform::scale(2., 2., 2.)).unwrap();
+    /// assert!((l.area().unwrap() - 2.).abs() < 1e-9);
+    ///
+    /// l.transform(&Transform::rotate_x(90.)).unwrap();
+    /// assert!(l.normal().compare(Vector3D::new(0., -1., 0.)));
+    /// ```
+    pub fn transform(&mut self, t: &Transform) -> Result<(), String> {
+        for v in self.vertices.iter_mut() {
+            *v = t.transform_pt(*v);
+        }
+        if t.changes_hands() {
+            self.vertices.reverse();
+        }
+        self.normal = Vector3D::new(0., 0., 0.);
+        self.set_normal()?;
+        if self.closed {
+            self.set_area()?;
+            self.set_perimeter()?;
+        }
+        Ok(())
+    }
+
     /// Returns a clone of the [`Loop3D`] but reversed (vertices in the
     /// opposite order, and the normal [`Vector3D`] pointing on the
     /// opposite direction)
@@ -25,7 +25,7 @@ SOFTWARE.
 use serde::{Deserialize, Deserializer, Serialize};
 
 use crate::Float;
-use crate::{Loop3D, Point3D, Segment3D, Vector3D};
+use crate::{Loop3D, Point3D, Segment3D, Transform, Vector3D};
 
 /// A 3-dimensional Polygon which can contain holes.
 ///
@@ -120,6 +120,19 @@ impl Polygon3D {
         self.normal = self.outer.normal();
     }
 
+    /// Applies a [`Transform`] to all the [`Loop3D`] in the [`Polygon3D`],
+    /// updating its normal and area (see [`Loop3D::transform`])
+    pub fn transform(&mut self, t: &Transform) -> Result<(), String> {
+        self.outer.transform(t)?;
+        self.area = self.outer.area()?;
+        for i in self.inner.iter_mut() {
+            i.transform(t)?;
+            self.area -= i.area()?;
+        }
+        self.normal = self.outer.normal();
+        Ok(())
+    }
+
     /// Returns a clone of this [`Polygon3D`], reversed.
     pub fn get_reversed(&self) -> Self {
         let mut ret = self.clone();
@@ -847,6 +860,44 @@ mod testing {
         Ok(())
     }
 
+    #[test]
+    fn test_transform() -> Result<(), String> {
+        let mut outer = Loop3D::new();
+        outer.push(Point3D::new(0., 0., 0.))?;
+        outer.push(Point3D::new(2., 0., 0.))?;
+        outer.push(Point3D::new(2., 2., 0.))?;
+        outer.push(Point3D::new(0., 2., 0.))?;
+        outer.close()?;
+        let mut p = Polygon3D::new(outer)?;
+
+        let mut inner = Loop3D::new();
+        inner.push(Point3D::new(0.5, 0.5, 0.))?;
+        inner.push(Point3D::new(1.5, 0.5, 0.))?;
+        inner.push(Point3D::new(1.5, 1.5, 0.))?;
+        inner.push(Point3D::new(0.5, 1.5, 0.))?;
+        inner.close()?;
+        p.cut_hole(inner)?;
+        assert!((p.area() - 3.).abs() < 1e-9);
+
+        // Stand it up, facing South
+        p.transform(&Transform::rotate_x(90.))?;
+        assert!(p.normal().compare(Vector3D::new(0., -1., 0.)));
+        assert!(p.inner(0)?.normal().compare(Vector3D::new(0., -1., 0.)));
+        assert!((p.area() - 3.).abs() < 1e-9);
+
+        // Mirror it... it faces North now
+        p.transform(&Transform::mirror(Vector3D::new(0., 1., 0.)))?;
+        assert!(p.normal().compare(Vector3D::new(0., 1., 0.)));
+        assert!(p.outer().vertices().iter().all(|v| v.y.abs() < 1e-9));
+
+        // Scaling changes the area
+        p.transform(&Transform::scale(2., 1., 1.))?;
+        assert!((p.area() - 6.).abs() < 1e-9);
+        assert!(p.normal().compare(Vector3D::new(0., 1., 0.)));
+
+        Ok(())
+    }
+
     #[test]
     fn test_get_reversed() -> Result<(), String> {
         let mut outer = Loop3D::with_capacity(4);
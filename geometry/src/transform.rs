@@ -289,6 +289,23 @@ impl Transform {
         ret
     }
 
+    /// Creates a new [`Transform`] that mirrors (i.e., reflects) points across
+    /// the plane that contains the origin and is perpendicular to `normal`
+    pub fn mirror(normal: Vector3D) -> Self {
+        let n = normal.get_normalized();
+        let n = [n.x, n.y, n.z];
+        let mut ret = Self::new();
+        for (row, n_row) in n.iter().enumerate() {
+            for (col, n_col) in n.iter().enumerate() {
+                // A reflection is its own inverse
+                let v = -2. * n_row * n_col;
+                ret.elements[elem!(row, col)] += v;
+                ret.inv_elements[elem!(row, col)] += v;
+            }
+        }
+        ret
+    }
+
     /// Checks whether a [`Transform`] modifies the hand-ness of the coordinate system
     pub fn changes_hands(&self) -> bool {
         let m = &self.elements;
@@ -842,6 +859,36 @@ mod testing {
         }
         Ok(())
     }
+    #[test]
+    fn test_mirror_transform() -> Result<(), String> {
+        let t = Transform::mirror(Vector3D::new(2., 0., 0.));
+        assert!(t.changes_hands());
+        compare_pts(
+            t.transform_pt(Point3D::new(1., 2., 3.)),
+            Point3D::new(-1., 2., 3.),
+        )?;
+        compare_pts(
+            t.inv_transform_pt(Point3D::new(1., 2., 3.)),
+            Point3D::new(-1., 2., 3.),
+        )?;
+        compare_vecs(
+            t.transform_vec(Vector3D::new(1., 1., 0.)),
+            Vector3D::new(-1., 1., 0.),
+        )?;
+
+        // Across the plane x = y
+        let t = Transform::mirror(Vector3D::new(1., -1., 0.));
+        compare_pts(
+            t.transform_pt(Point3D::new(1., 0., 5.)),
+            Point3D::new(0., 1., 5.),
+        )?;
+        compare_pts(
+            t.transform_pt(Point3D::new(2., 2., 0.)),
+            Point3D::new(2., 2., 0.),
+        )?;
+        Ok(())
+    }
+
     #[test]
     fn test_rotate_x_transform() -> Result<(), String> {
         /* POINTS */
@@ -49,47 +49,138 @@ pub struct Cavity {
     pub angle: Float,
 }
 
+/// The temperatures (in $`C`$) at the front and back of a cavity under
+/// the standard conditions of EN-673 (i.e., a mean temperature of $`10^oC`$
+/// and a difference of $`15K`$), used when a single U-value is needed
+pub const REFERENCE_TEMPERATURES: (Float, Float) = (2.5, 17.5);
+
 impl Cavity {
-    /// Calculates the `U-value`—including convective and radiative heat transfer—of a
-    /// cavity, so that $`U_{cavity}\times \Delta T = q`$
+    /// Calculates the convective heat transfer coefficient (in $`W/m^2K`$)
+    /// of the gas in the cavity, given the temperatures (in $`C`$) of the
+    /// surfaces at its front and back, according to section 5.3.3 of ISO15099/2003.
     ///
-    /// ```math
-    /// U_{cavity} = \frac{4*{T_m}^3 * \Sigma  \epsilon_1 \epsilon_2}{1-(1-\epsilon_1)(1-\epsilon_2)} + h_{conv}
-    /// ```
-    pub fn u_value(&self, t_front: Float, t_back: Float) -> Float {
+    /// The tilt of the cavity and the direction of the heat flow define which
+    /// correlation is used for the Nusselt number. Pure conduction is
+    /// the lower limit (i.e., $`Nu \geq 1`$).
+    pub fn convection_coefficient(&self, t_front: Float, t_back: Float) -> Float {
         let conv =
             self.gas
                 .cavity_convection(self.height, self.thickness, self.angle, t_front, t_back);
         let tm = (t_back + t_front) / 2. + 273.15;
+        let conduction = self.gas.thermal_conductivity(tm) / self.thickness;
+        conv.max(conduction)
+    }
+
+    /// Calculates the radiative heat transfer coefficient (in $`W/m^2K`$) between
+    /// the two (infrared-opaque) surfaces enclosing the cavity, given their
+    /// temperatures (in $`C`$), so that $`h_r (T_{front} - T_{back})`$ is the
+    /// net radiative exchange.
+    ///
+    /// ```math
+    /// h_r = \frac{\sigma ({T_f}^2+{T_b}^2)(T_f + T_b)}{1/\epsilon_1 + 1/\epsilon_2 - 1}
+    /// ```
+    ///
+    /// Which is the exact (i.e., not linearised) version of the
+    /// radiative balance of ISO15099/2003 for two parallel surfaces.
+    pub fn radiation_coefficient(&self, t_front: Float, t_back: Float) -> Float {
+        let tf = t_front + 273.15;
+        let tb = t_back + 273.15;
+        SIGMA * (tf * tf + tb * tb) * (tf + tb) / (1. / self.ein + 1. / self.eout - 1.)
+    }
 
-        let rad = 4. * tm.powi(3) * SIGMA * self.ein * self.eout
-            / (1. - (1. - self.ein) * (1. - self.eout));
+    /// Calculates the `U-value`—including convective and radiative heat transfer—of a
+    /// cavity, so that $`U_{cavity}\times \Delta T = q`$
+    ///
+    /// ```math
+    /// U_{cavity} = h_r + h_{conv}
+    /// ```
+    ///
+    /// See [`Cavity::convection_coefficient`] and [`Cavity::radiation_coefficient`].
+    pub fn u_value(&self, t_front: Float, t_back: Float) -> Float {
+        self.radiation_coefficient(t_front, t_back) + self.convection_coefficient(t_front, t_back)
+    }
 
-        rad + conv
+    /// Calculates the `U-value` of the cavity under the [`REFERENCE_TEMPERATURES`]
+    pub fn reference_u_value(&self) -> Float {
+        let (t_front, t_back) = REFERENCE_TEMPERATURES;
+        self.u_value(t_front, t_back)
     }
 }
 
+/***********/
+/* TESTING */
+/***********/
+
 #[cfg(test)]
 mod testing {
     use super::*;
-    // use model::Substance;
-
-    #[test]
-    fn test_u_value() {
-        let gap_thickness = 0.0127;
 
-        let gap = Cavity {
-            thickness: gap_thickness,
+    fn double_clear_gap(thickness: Float) -> Cavity {
+        Cavity {
+            thickness,
             height: 1.,
             gas: crate::gas::AIR,
             eout: 0.84,
             ein: 0.84,
             angle: crate::PI / 2.,
-        };
+        }
+    }
+
+    #[test]
+    fn test_u_value() {
+        let gap_thickness = 0.0127;
+        let gap = double_clear_gap(gap_thickness);
         let t_out = 259.116115 - 273.15;
         let t_in = 279.323983 - 273.15;
         let u = gap.u_value(t_out, t_in);
+        // Equivalent conductivity of the gap reported by LBNL's Tarcog
         let exp_u = 0.069446 / gap_thickness;
-        dbg!(u, exp_u);
+        assert!(
+            (u - exp_u).abs() / exp_u < 0.05,
+            "u = {u} | exp_u = {exp_u}"
+        );
+
+        // Symmetric... a vertical cavity does not care about the direction of heat
+        let u2 = gap.u_value(t_in, t_out);
+        assert!((u - u2).abs() < 1e-9);
+
+        // Conduction is the minimum
+        let tm = (t_in + t_out) / 2. + 273.15;
+        let conduction = crate::gas::AIR.thermal_conductivity(tm) / gap_thickness;
+        assert!(gap.convection_coefficient(t_out, t_in) >= conduction);
+    }
+
+    #[test]
+    fn test_tilt() {
+        // Horizontal cavity
+        let mut gap = double_clear_gap(0.02);
+        gap.angle = 0.;
+        // Heated from below (i.e., the back is warmer)
+        let up = gap.convection_coefficient(0., 20.);
+        // Heated from above
+        let down = gap.convection_coefficient(20., 0.);
+        assert!(up > down, "up = {up} | down = {down}");
+    }
+
+    #[test]
+    fn test_double_clear_window() {
+        // Center-of-glass U-value of 3mm clear / 12.7mm air / 3mm clear under
+        // NFRC 100 winter conditions, as reported by LBNL's WINDOW: 2.73 W/m2K
+        let gap = double_clear_gap(0.0127);
+        let (t_out, t_in) = (-18., 21.);
+        // Combined film coefficients
+        let (h_out, h_in) = (26., 7.7);
+        let r_glass = 0.003 / 1.;
+
+        let (mut t1, mut t2) = (-10., 10.);
+        let mut u = 0.;
+        for _ in 0..50 {
+            let r = 1. / h_out + 2. * r_glass + 1. / gap.u_value(t1, t2) + 1. / h_in;
+            u = 1. / r;
+            let q = u * (t_in - t_out);
+            t1 = t_out + q * (1. / h_out + r_glass);
+            t2 = t_in - q * (1. / h_in + r_glass);
+        }
+        assert!((u - 2.73).abs() / 2.73 < 0.02, "u = {u}");
     }
 }
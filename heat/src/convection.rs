@@ -19,8 +19,13 @@ SOFTWARE.
 */
 
 use crate::Float;
+use model::ConvectionModel;
 const MIN_H: Float = 0.1;
 
+/// The roughness coefficient $`R_f`$ of the forced convection, for each
+/// roughness index
+const ROUGHNESS_COEFFICIENTS: [Float; 6] = [2.17, 1.67, 1.52, 1.13, 1.11, 1.];
+
 /// Represents a border condition of between a Surface
 /// and a Zone or the exterior
 #[derive(Debug, Clone, Copy)]
@@ -155,9 +160,7 @@ impl ConvectionParams {
         perimeter: Float,
         windward: bool,
     ) -> Float {
-        const COEFFICIENTS: [Float; 6] = [2.17, 1.67, 1.52, 1.13, 1.11, 1.];
-
-        let rf = COEFFICIENTS[self.roughness_index];
+        let rf = ROUGHNESS_COEFFICIENTS[self.roughness_index];
 
         let wf = if windward { 1.0 } else { 0.5 };
 
@@ -167,17 +170,244 @@ impl ConvectionParams {
 
         forced + natural // this will never be less than MIN_HS because natural is already limited
     }
+
+    /// Calculates the exterior convection coefficient according to the DOE-2
+    /// model, based on the description given in EnergyPlus' Engineering Reference.
+    ///
+    /// # The math
+    ///
+    /// The convection coefficient of a smooth (i.e., glass) surface is
+    /// calculated by combining the natural convection $`h_n`$ (calculated
+    /// as in the TARP model) with a forced component that depends on the
+    /// wind speed $`V_z`$:
+    ///
+    /// ```math
+    /// h_{c,glass} = \sqrt{h_n^2 + \left(a V_z^b\right)^2}
+    /// ```
+    ///
+    /// where $`a = 3.26`$ and $`b = 0.89`$ for surfaces facing the wind,
+    /// and $`a = 3.55`$ and $`b = 0.617`$ for others. Then, the coefficient
+    /// is corrected by the roughness of the surface (using the same $`R_f`$ as
+    /// in the TARP model):
+    ///
+    /// ```math
+    /// h_c = h_n + R_f \left( h_{c,glass} - h_n \right)
+    /// ```
+    pub fn get_doe2_convection_coefficient(&self, windward: bool) -> Float {
+        let (a, b) = if windward {
+            (3.26, 0.89)
+        } else {
+            (3.55, 0.617)
+        };
+        let rf = ROUGHNESS_COEFFICIENTS[self.roughness_index.min(5)];
+
+        let natural = self.get_tarp_natural_convection_coefficient();
+        let forced = a * self.air_speed.powf(b);
+        let glass = (natural * natural + forced * forced).sqrt();
+
+        natural + rf * (glass - natural)
+    }
+
+    /// Calculates the interior convection coefficient according to EnergyPlus'
+    /// `Simple` model, which uses fixed values depending on the orientation of
+    /// the surface and on the direction of the heat flow.
+    ///
+    /// | Surface | Heat flow | $`h`$ |
+    /// |---------|-----------|-------|
+    /// | Vertical (i.e., within 22.5 degrees of vertical) | Any | 3.076 |
+    /// | Horizontal (i.e., within 22.5 degrees of horizontal) | Enhanced | 4.040 |
+    /// | Horizontal | Reduced | 0.948 |
+    /// | Tilted | Enhanced | 3.870 |
+    /// | Tilted | Reduced | 2.281 |
+    ///
+    /// The convection is enhanced and reduced in the same cases in which
+    /// the TARP model uses its corresponding correlations.
+    pub fn get_simple_natural_convection_coefficient(&self) -> Float {
+        // cos(67.5 degrees) and cos(22.5 degrees)
+        const VERTICAL: Float = 0.382_683_4;
+        const HORIZONTAL: Float = 0.923_879_5;
+
+        let delta_t = self.air_temperature - self.surface_temperature;
+        let abs_cos = self.cos_surface_tilt.abs();
+        let enhanced = delta_t * self.cos_surface_tilt > 0.;
+
+        if abs_cos < VERTICAL {
+            3.076
+        } else if abs_cos > HORIZONTAL {
+            if enhanced {
+                4.040
+            } else {
+                0.948
+            }
+        } else if enhanced {
+            3.870
+        } else {
+            2.281
+        }
+    }
+
+    /// Calculates the exterior combined (i.e., convective and radiant) heat
+    /// transfer coefficient according to EnergyPlus' `SimpleCombined` model,
+    /// which depends on the roughness of the surface and on the wind speed $`V_z`$:
+    ///
+    /// ```math
+    /// h = D + E V_z + F V_z^2
+    /// ```
+    ///
+    /// | Roughness index | $`D`$ | $`E`$ | $`F`$ |
+    /// |-----------------|-------|-------|-------|
+    /// | 1. Very Rough   | 11.58 | 5.894 | 0.0   |
+    /// | 2. Rough        | 12.49 | 4.065 | 0.028 |
+    /// | 3. Medium Rough | 10.79 | 4.192 | 0.0   |
+    /// | 4. Medium Smooth| 8.23  | 4.0   | -0.057|
+    /// | 5. Smooth       | 10.22 | 3.1   | 0.0   |
+    /// | 6. Very Smooth  | 8.23  | 3.33  | -0.036|
+    pub fn get_simple_combined_convection_coefficient(&self) -> Float {
+        const COEFFICIENTS: [(Float, Float, Float); 6] = [
+            (11.58, 5.894, 0.0),
+            (12.49, 4.065, 0.028),
+            (10.79, 4.192, 0.0),
+            (8.23, 4.0, -0.057),
+            (10.22, 3.1, 0.0),
+            (8.23, 3.33, -0.036),
+        ];
+        let (d, e, f) = COEFFICIENTS[self.roughness_index.min(5)];
+        let v = self.air_speed;
+        let h = d + e * v + f * v * v;
+        if h < MIN_H {
+            MIN_H
+        } else {
+            h
+        }
+    }
+
+    /// Calculates the convection coefficient of a side of a surface that
+    /// does not face the outdoors, using a certain [`ConvectionModel`]
+    pub fn get_interior_convection_coefficient(&self, model: &ConvectionModel) -> Float {
+        match model {
+            ConvectionModel::Tarp | ConvectionModel::Doe2 => {
+                self.get_tarp_natural_convection_coefficient()
+            }
+            ConvectionModel::SimpleCombined => self.get_simple_natural_convection_coefficient(),
+            ConvectionModel::Fixed { coefficient } => *coefficient,
+        }
+    }
+
+    /// Calculates the convection coefficient of a side of a surface that
+    /// faces the outdoors, using a certain [`ConvectionModel`]
+    pub fn get_exterior_convection_coefficient(
+        &self,
+        model: &ConvectionModel,
+        area: Float,
+        perimeter: Float,
+        windward: bool,
+    ) -> Float {
+        match model {
+            ConvectionModel::Tarp => {
+                self.get_tarp_convection_coefficient(area, perimeter, windward)
+            }
+            ConvectionModel::Doe2 => self.get_doe2_convection_coefficient(windward),
+            ConvectionModel::SimpleCombined => self.get_simple_combined_convection_coefficient(),
+            ConvectionModel::Fixed { coefficient } => *coefficient,
+        }
+    }
 }
 
-// #[cfg(test)]
-// mod testing {
-//     #[test]
-//     fn test_indoor_coefficient(){
-//         assert!(false)
-//     }
-
-//     #[test]
-//     fn test_outdoor_coefficient(){
-//         assert!(false)
-//     }
-// }
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use validate::assert_close;
+
+    fn params(air_speed: Float, delta_t: Float, cos_surface_tilt: Float) -> ConvectionParams {
+        ConvectionParams {
+            air_temperature: 20. + delta_t,
+            air_speed,
+            rad_temperature: 20.,
+            surface_temperature: 20.,
+            roughness_index: 5,
+            cos_surface_tilt,
+        }
+    }
+
+    #[test]
+    fn test_doe2() {
+        // Without wind, only natural convection remains
+        let p = params(0., 10., 0.);
+        assert_close!(
+            p.get_doe2_convection_coefficient(true),
+            p.get_tarp_natural_convection_coefficient()
+        );
+
+        // Very smooth surfaces (Rf = 1) behave like glass
+        let p = params(4., 10., 0.);
+        let natural = p.get_tarp_natural_convection_coefficient();
+        let forced = 3.26 * (4. as Float).powf(0.89);
+        let expected = (natural * natural + forced * forced).sqrt();
+        assert_close!(p.get_doe2_convection_coefficient(true), expected, 1e-9);
+        assert!(p.get_doe2_convection_coefficient(false) < expected);
+    }
+
+    #[test]
+    fn test_simple() {
+        // Vertical
+        assert_close!(
+            params(0., 5., 0.).get_simple_natural_convection_coefficient(),
+            3.076
+        );
+        // Horizontal, enhanced and reduced
+        assert_close!(
+            params(0., 5., 1.).get_simple_natural_convection_coefficient(),
+            4.040
+        );
+        assert_close!(
+            params(0., -5., 1.).get_simple_natural_convection_coefficient(),
+            0.948
+        );
+        // Tilted
+        assert_close!(
+            params(0., 5., 0.7).get_simple_natural_convection_coefficient(),
+            3.870
+        );
+        assert_close!(
+            params(0., 5., -0.7).get_simple_natural_convection_coefficient(),
+            2.281
+        );
+
+        // Very smooth
+        assert_close!(
+            params(2., 0., 0.).get_simple_combined_convection_coefficient(),
+            8.23 + 3.33 * 2. - 0.036 * 4.
+        );
+    }
+
+    #[test]
+    fn test_models() {
+        let p = params(3., 5., 0.);
+        let fixed = ConvectionModel::Fixed { coefficient: 7. };
+        assert_close!(p.get_interior_convection_coefficient(&fixed), 7.);
+        assert_close!(
+            p.get_exterior_convection_coefficient(&fixed, 1., 4., true),
+            7.
+        );
+        assert_close!(
+            p.get_interior_convection_coefficient(&ConvectionModel::Tarp),
+            p.get_tarp_natural_convection_coefficient()
+        );
+        assert_close!(
+            p.get_exterior_convection_coefficient(&ConvectionModel::Tarp, 1., 4., true),
+            p.get_tarp_convection_coefficient(1., 4., true)
+        );
+        assert_close!(
+            p.get_exterior_convection_coefficient(&ConvectionModel::Doe2, 1., 4., true),
+            p.get_doe2_convection_coefficient(true)
+        );
+        assert_close!(
+            p.get_exterior_convection_coefficient(&ConvectionModel::SimpleCombined, 1., 4., true),
+            p.get_simple_combined_convection_coefficient()
+        );
+    }
+}
@@ -19,9 +19,21 @@ SOFTWARE.
 */
 
 pub(crate) const MAX_RS: Float = 0.05;
+
+/// The maximum thickness of the elements (in m), unless
+/// stated otherwise in the model's [`ThermalOptions`](model::ThermalOptions)
+pub(crate) const DEFAULT_MAX_DX: Float = 0.05;
+
+/// The minimum timestep (in seconds), unless stated otherwise
+/// in the model's [`ThermalOptions`](model::ThermalOptions)
+pub(crate) const DEFAULT_MIN_DT: Float = 60.;
+
+/// The maximum number of timestep subdivisions, unless stated
+/// otherwise in the model's [`ThermalOptions`](model::ThermalOptions)
+pub(crate) const DEFAULT_MAX_SUBDIVISIONS: usize = 13;
 use crate::convection::ConvectionParams;
 use crate::Float;
-use crate::{cavity::Cavity, surface::ChunkMemory};
+use crate::{cavity::Cavity, phase_change::PhaseChange, surface::ChunkMemory};
 use matrix::matrix::Matrix;
 use model::{Construction, Model, Substance};
 use std::sync::Arc;
@@ -79,6 +91,11 @@ pub struct Discretization {
 
     /// The number of elements on each layer
     pub n_elements: Vec<usize>,
+
+    /// The nodes whose heat capacity depends on their temperature: the index
+    /// of the node, the mass of Phase Change Material lumped into it (in kg/m2)
+    /// and its [`PhaseChange`]
+    pub phase_change: Vec<(usize, Float, PhaseChange)>,
 }
 
 impl Discretization {
@@ -172,6 +189,7 @@ impl Discretization {
         // n_nodes = n_nodes.max(construction.materials.len() + 1); // At least one per layer... but Zero means  "no_mass wall"
 
         let mut segments: Vec<(Float, UValue)> = vec![(0.0, UValue::default()); n_nodes];
+        let mut phase_change = Vec::new();
 
         let mut n_segment = 0;
         for (n_layer, n) in n_elements.iter().enumerate() {
@@ -196,6 +214,21 @@ impl Discretization {
                 }
             };
 
+            // The mass of each segment (in kg/m2) and the latent heat
+            // storage of Phase Change Materials. No-mass layers have none.
+            let pcm = match &substance {
+                Substance::Normal(s) if n > 0 => match s.phase_change() {
+                    Ok(spec) => {
+                        let dx = material.thickness / n as Float;
+                        let rho = s.density()?;
+                        let cp = s.specific_heat_capacity()?;
+                        Some((rho * dx, PhaseChange::new(spec, *cp)?))
+                    }
+                    Err(_) => None,
+                },
+                _ => None,
+            };
+
             if n == 0 {
                 n = 1;
             }
@@ -206,6 +239,10 @@ impl Discretization {
                         // Add mass to this and next nodes (if it is NoMass, it is Zero)
                         segments[n_segment].0 += mass / 2.;
                         segments[n_segment + 1].0 += mass / 2.;
+                        if let Some((pcm_mass, pc)) = &pcm {
+                            phase_change.push((n_segment, pcm_mass / 2., pc.clone()));
+                            phase_change.push((n_segment + 1, pcm_mass / 2., pc.clone()));
+                        }
 
                         // Add resistance
                         let dx = material.thickness / n as Float;
@@ -286,10 +323,32 @@ impl Discretization {
             segments,
             tstep_subdivision,
             n_elements,
+            phase_change,
         })
     }
 
-    /// Calculates the R value of the whole system
+    /// Retrieves the index of the node at the interface between the layer
+    /// `layer` (starting from 0 at the front) and the next one. If `layer` is
+    /// the last layer, this is the back node.
+    pub fn interface_node(&self, layer: usize) -> Result<usize, String> {
+        if layer >= self.n_elements.len() {
+            return Err(format!(
+                "Trying to get the interface after layer {}... but there are only {} layers",
+                layer,
+                self.n_elements.len()
+            ));
+        }
+        let node = self
+            .n_elements
+            .iter()
+            .take(layer + 1)
+            .map(|n| if *n == 0 { 1 } else { *n })
+            .sum();
+        Ok(node)
+    }
+
+    /// Calculates the R value of the whole system. Cavities are
+    /// evaluated under the [`REFERENCE_TEMPERATURES`](crate::cavity::REFERENCE_TEMPERATURES)
     ///
     /// # Panics
     /// Panics if the calculated R value is Zero (i.e., if there are no
@@ -299,7 +358,7 @@ impl Discretization {
 
         for (_, u_value) in &self.segments {
             r += match u_value {
-                UValue::Cavity(_c) => todo!(), //c.u_value(t_front, t_back),
+                UValue::Cavity(c) => 1. / c.reference_u_value(),
                 UValue::Solid(v) => 1. / v,
                 UValue::Back => 0.0,
                 UValue::None => unreachable!(),
@@ -403,11 +462,14 @@ impl Discretization {
     }
 
     /// Returns `Option<(nsegments, n_time_subd)>`. Returns `None` if the substance
-    /// is a Gas or if the element is so thin that the `min_dx` cannot be satisfied.
+    /// is a Gas or if the element is so thin that the `min_dx` cannot be satisfied
+    /// without dividing the timestep into more than `max_subdivisions`.
     fn nsegments(
         model_dt: Float,
         max_dx: Float,
         min_dt: Float,
+        max_elements: Option<usize>,
+        max_subdivisions: usize,
         thickness: Float,
         substance: &Substance,
     ) -> Option<(usize, usize)> {
@@ -427,7 +489,10 @@ impl Discretization {
 
         // Let's start by using the maximum number of segments possible
         // We know that this is at least 1, because thickness >= max_dx
-        let n_segments = (thickness / max_dx).ceil();
+        let mut n_segments = (thickness / max_dx).ceil();
+        if let Some(max) = max_elements {
+            n_segments = n_segments.min(max.max(1) as Float);
+        }
         let dx = thickness / n_segments;
 
         // find the n that satisfies this condition
@@ -446,7 +511,7 @@ impl Discretization {
                 return Some((n_segments as usize, n));
             }
             // Let's not stretch this too much.
-            if n > 12 {
+            if n >= max_subdivisions {
                 return None;
             }
             n += 1;
@@ -460,7 +525,8 @@ impl Discretization {
     /// This function recursively increases the model's timestep subdivisions (`n`) in order to reduce $`\Delta t`$ to numbers
     /// that respect the restrictions of (1) stability, (2) $`\Delta x_{max}`$, and (3) $`\Delta t_{min}`$. In other words,
     /// it searches (by testing $`\Delta t_{model}/1`$, $`\Delta t_{model}/2`$, $`\Delta t_{model}/3`$, ... $`\Delta t_{model}/n`$)
-    /// for the minimum `n` that respects this restrictions
+    /// for the minimum `n` that respects this restrictions. The number of elements per layer and the
+    /// maximum `n` can be limited through the model's [`ThermalOptions`](model::ThermalOptions).
     pub fn discretize_construction(
         construction: &Arc<Construction>,
         model: &Model,
@@ -472,6 +538,17 @@ impl Discretization {
         let mut n_elements: Vec<usize> = Vec::with_capacity(n_layers);
         let mut n = 0;
 
+        let (max_elements, max_subdivisions) = match &model.thermal_options {
+            Some(options) => (
+                options.max_elements_per_layer().ok().copied(),
+                options
+                    .max_timestep_subdivisions()
+                    .copied()
+                    .unwrap_or(DEFAULT_MAX_SUBDIVISIONS),
+            ),
+            None => (None, DEFAULT_MAX_SUBDIVISIONS),
+        };
+
         for n_layer in 0..n_layers {
             let mat_name = &construction.materials[n_layer];
             let material = model.get_material(mat_name)?;
@@ -479,9 +556,15 @@ impl Discretization {
             let substance = model.get_substance(sub_name)?;
             let thickness = material.thickness;
 
-            if let Some((elements, nsubs)) =
-                Discretization::nsegments(model_dt, max_dx, min_dt, thickness, &substance)
-            {
+            if let Some((elements, nsubs)) = Discretization::nsegments(
+                model_dt,
+                max_dx,
+                min_dt,
+                max_elements,
+                max_subdivisions,
+                thickness,
+                &substance,
+            ) {
                 n_elements.push(elements);
                 if nsubs > n {
                     n = nsubs;
@@ -828,6 +911,47 @@ mod testing {
         Ok(())
     }
 
+    #[test]
+    fn build_phase_change() -> Result<(), String> {
+        let density = 800.;
+        let cp = 2000.;
+        let thickness = 0.02;
+
+        let mut model = Model::default();
+        let mut phase_change = model::substance::normal::PhaseChange::new(21., 25.);
+        phase_change.enthalpy = vec![0., 0., 1000.];
+        let mut s = Normal::new("PCM");
+        s.set_thermal_conductivity(0.2)
+            .set_density(density)
+            .set_specific_heat_capacity(cp)
+            .set_phase_change(phase_change);
+        model.add_substance(s.wrap());
+        model.add_material(Material::new("PCM board", "PCM", thickness));
+        let mut c = Construction::new("the construction");
+        c.materials.push("PCM board".into());
+        let construction = model.add_construction(c);
+
+        // Two elements, so two halves in the middle node
+        let d = Discretization::build(&construction, &model, 1, vec![2], 1., 0.)?;
+        assert_eq!(d.segments.len(), 3);
+        assert_eq!(d.phase_change.len(), 4);
+        let nodes: Vec<usize> = d.phase_change.iter().map(|(i, ..)| *i).collect();
+        assert_eq!(nodes, vec![0, 1, 1, 2]);
+        let pcm_mass: Float = d.phase_change.iter().map(|(_, m, _)| m).sum();
+        assert!((pcm_mass - density * thickness).abs() < 1e-9);
+
+        // cp = 2000 T within the range
+        let (_, m, pc) = &d.phase_change[0];
+        assert!((m * pc.latent_heat_capacity(22.) - m * (44000. - cp)).abs() < 1e-6);
+        assert!(pc.latent_heat_capacity(30.).abs() < 1e-9);
+
+        // No-mass layers do not store latent heat
+        let d = Discretization::build(&construction, &model, 1, vec![0], 1., 0.)?;
+        assert!(d.phase_change.is_empty());
+
+        Ok(())
+    }
+
     #[test]
     fn test_build_normal_no_mass() -> Result<(), String> {
         let thermal_cond = 1.;
@@ -1125,6 +1249,7 @@ mod testing {
             segments,
             tstep_subdivision: 1,
             n_elements: vec![n],
+            phase_change: Vec::new(),
         };
 
         let front_env = ConvectionParams {
@@ -1389,6 +1514,7 @@ mod testing {
             segments,
             tstep_subdivision: 1,
             n_elements: vec![n],
+            phase_change: Vec::new(),
         };
 
         let front_env = ConvectionParams {
@@ -1477,6 +1603,7 @@ mod testing {
             tstep_subdivision: 1,
             segments: vec![(1., UValue::None)],
             n_elements: vec![1], // Does not matter for this test
+            phase_change: Vec::new(),
         };
 
         let (mass_chunks, nomass_chunks) = d.get_chunks();
@@ -1489,6 +1616,7 @@ mod testing {
             tstep_subdivision: 1,
             segments: vec![(0., UValue::None)],
             n_elements: vec![1], // Does not matter for this test
+            phase_change: Vec::new(),
         };
 
         let (mass_chunks, nomass_chunks) = d.get_chunks();
@@ -1501,6 +1629,7 @@ mod testing {
             tstep_subdivision: 1,
             segments: vec![(1., UValue::None); 10],
             n_elements: vec![1], // Does not matter for this test
+            phase_change: Vec::new(),
         };
 
         let (mass_chunks, nomass_chunks) = d.get_chunks();
@@ -1513,6 +1642,7 @@ mod testing {
             tstep_subdivision: 1,
             segments: vec![(0., UValue::None); 10],
             n_elements: vec![1], // Does not matter for this test
+            phase_change: Vec::new(),
         };
 
         let (mass_chunks, nomass_chunks) = d.get_chunks();
@@ -1531,6 +1661,7 @@ mod testing {
                 (0., UValue::None),
             ],
             n_elements: vec![0, 1, 1, 0, 0], // Does not matter for this test
+            phase_change: Vec::new(),
         };
 
         let (mass_chunks, nomass_chunks) = d.get_chunks();
@@ -1550,6 +1681,7 @@ mod testing {
                 (0., UValue::None),
             ],
             n_elements: vec![1, 1, 1, 0, 0], // Does not matter for this test
+            phase_change: Vec::new(),
         };
 
         let (mass_chunks, nomass_chunks) = d.get_chunks();
@@ -1559,6 +1691,25 @@ mod testing {
         assert_eq!(nomass_chunks, vec![(3, 5)]);
     }
 
+    #[test]
+    fn test_interface_node() -> Result<(), String> {
+        // A massive layer with 3 elements, a no-mass one, and
+        // a massive one with 2 elements... so, 3 + 1 + 2 + 1 = 7 nodes
+        let d = Discretization {
+            tstep_subdivision: 1,
+            segments: vec![(1., UValue::None); 7],
+            n_elements: vec![3, 0, 2],
+            phase_change: Vec::new(),
+        };
+
+        assert_eq!(d.interface_node(0)?, 3);
+        assert_eq!(d.interface_node(1)?, 4);
+        // the last interface is the back node
+        assert_eq!(d.interface_node(2)?, 6);
+        assert!(d.interface_node(3).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_discretize_construction() {
         let mut model = Model::default();
@@ -1583,4 +1734,45 @@ mod testing {
                 .unwrap();
         dbg!(r);
     }
+
+    #[test]
+    fn test_discretize_construction_options() -> Result<(), String> {
+        let mut model = Model::default();
+
+        let mut substance = Normal::new("concrete");
+        substance
+            .set_density(2400.)
+            .set_thermal_conductivity(1.63)
+            .set_specific_heat_capacity(900.);
+        let substance = model.add_substance(Substance::Normal(Arc::new(substance)));
+        let material = model.add_material(Material::new("wall", substance.name(), 0.2));
+        let mut construction = Construction::new("the construction");
+        construction.materials.push(material.name().clone());
+        let construction = model.add_construction(construction);
+
+        // Default: thin elements require subdividing the timestep
+        let (subdivisions, n_elements) =
+            Discretization::discretize_construction(&construction, &model, 900., 0.04, 60.)?;
+        assert_eq!(n_elements, vec![5]);
+        assert_eq!(subdivisions, 4);
+
+        // Fewer, thicker elements are stable with the model timestep
+        let mut options = model::ThermalOptions::new();
+        options.set_max_elements_per_layer(2);
+        model.thermal_options = Some(options);
+        let (subdivisions, n_elements) =
+            Discretization::discretize_construction(&construction, &model, 900., 0.04, 60.)?;
+        assert_eq!(n_elements, vec![2]);
+        assert_eq!(subdivisions, 1);
+
+        // Not enough subdivisions allowed: the layer has no mass
+        let mut options = model::ThermalOptions::new();
+        options.set_max_timestep_subdivisions(3);
+        model.thermal_options = Some(options);
+        let (_, n_elements) =
+            Discretization::discretize_construction(&construction, &model, 900., 0.04, 60.)?;
+        assert_eq!(n_elements, vec![0]);
+
+        Ok(())
+    }
 }
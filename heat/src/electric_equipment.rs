@@ -0,0 +1,51 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use model::{ElectricEquipment, Model};
+use std::sync::Arc;
+
+/// An ElectricEquipment element from the point of view of the thermal
+/// model.
+pub struct ThermalElectricEquipment {
+    /// The parent ElectricEquipment
+    pub(crate) parent: ElectricEquipment,
+
+    /// The space where the equipment is located
+    pub(crate) target_space_index: usize,
+}
+
+impl ThermalElectricEquipment {
+    /// Builds a new [`ThermalElectricEquipment`] from an [`ElectricEquipment`] and its location
+    pub fn from(eq: &Arc<ElectricEquipment>, model: &Model) -> Result<Self, String> {
+        let parent = (**eq).clone();
+        for (i, s) in model.spaces.iter().enumerate() {
+            if s.name() == parent.target_space()? {
+                return Ok(Self {
+                    parent,
+                    target_space_index: i,
+                });
+            }
+        }
+        Err(format!(
+            "ElectricEquipment is supposed to be in a space called '{}'... but it was not found",
+            parent.target_space()?
+        ))
+    }
+}
@@ -0,0 +1,245 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::{Float, PI};
+use calendar::Date;
+use model::{GroundCoupling, GroundTemperature, SiteDetails};
+use weather::WeatherTrait;
+
+/// Calculates the undisturbed temperature of the ground in contact with surfaces
+/// that have a `Ground` boundary, based on the `ground_temperature` of the
+/// [`SiteDetails`].
+///
+/// If none is given, the shallowest ground temperature in the weather is used.
+pub(crate) fn ground_temperature<W: WeatherTrait>(
+    site_details: Option<&SiteDetails>,
+    weather: &W,
+    date: Date,
+) -> Result<Float, String> {
+    match site_details.and_then(|d| d.ground_temperature().ok()) {
+        Some(GroundTemperature::WeatherFile { depth }) => {
+            weather.get_ground_temperature(date, *depth).ok_or_else(|| {
+                "The GroundTemperature is supposed to come from the weather file... but the weather has no ground temperature information".to_string()
+            })
+        }
+        Some(GroundTemperature::Kusuda {
+            mean_temperature,
+            amplitude,
+            coldest_day,
+            depth,
+            soil_diffusivity,
+        }) => {
+            if *soil_diffusivity <= 0. {
+                return Err(format!(
+                    "The soil_diffusivity of a Kusuda GroundTemperature must be positive... found {}",
+                    soil_diffusivity
+                ));
+            }
+            Ok(kusuda(
+                *mean_temperature,
+                *amplitude,
+                *coldest_day,
+                *depth,
+                *soil_diffusivity,
+                date.day_of_year(),
+            ))
+        }
+        None => weather.get_ground_temperature(date, 0.).ok_or_else(|| {
+            "There are surfaces with Ground boundaries, but no ground temperature could be found. Set the 'ground_temperature' of the SiteDetails or use a weather file with ground temperature information".to_string()
+        }),
+    }
+}
+
+/// The Kusuda and Achenbach (1965) model for the ground temperature at a `depth`
+/// (in m) and a day of the year `t`. The `soil_diffusivity` is given in m2/s.
+fn kusuda(
+    mean_temperature: Float,
+    amplitude: Float,
+    coldest_day: Float,
+    depth: Float,
+    soil_diffusivity: Float,
+    t: Float,
+) -> Float {
+    // The model is written in days
+    let alpha = soil_diffusivity * 24. * 60. * 60.;
+    let damping = (-depth * (PI / (365. * alpha)).sqrt()).exp();
+    let delay = depth / 2. * (365. / (PI * alpha)).sqrt();
+    mean_temperature - amplitude * damping * (2. * PI / 365. * (t - coldest_day - delay)).cos()
+}
+
+/// Calculates the surface coefficient (in W/m2K) connecting surfaces with
+/// a `Ground` boundary to the ground temperature, based on the `ground_coupling`
+/// of the [`SiteDetails`].
+///
+/// Returns `None` when the coupling is `Convective`, meaning that the coefficient
+/// needs to be calculated as natural convection at each timestep.
+pub(crate) fn ground_coupling_coefficient(
+    site_details: Option<&SiteDetails>,
+) -> Result<Option<Float>, String> {
+    match site_details.and_then(|d| d.ground_coupling().ok()) {
+        None | Some(GroundCoupling::Convective) => Ok(None),
+        Some(GroundCoupling::SoilLayer {
+            thickness,
+            conductivity,
+        }) => {
+            if *thickness <= 0. || *conductivity <= 0. {
+                return Err(format!(
+                    "The thickness and conductivity of a SoilLayer GroundCoupling must be positive... found {} and {}",
+                    thickness, conductivity
+                ));
+            }
+            Ok(Some(conductivity / thickness))
+        }
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use validate::assert_close;
+    use weather::{EPWGroundTemperature, Weather};
+
+    const SOIL_DIFFUSIVITY: Float = 5e-7;
+
+    fn kusuda_details(depth: Float) -> SiteDetails {
+        let mut details = SiteDetails::default();
+        details.set_ground_temperature(GroundTemperature::Kusuda {
+            mean_temperature: 12.,
+            amplitude: 8.,
+            coldest_day: 20.,
+            depth,
+            soil_diffusivity: SOIL_DIFFUSIVITY,
+        });
+        details
+    }
+
+    #[test]
+    fn test_kusuda() -> Result<(), String> {
+        let weather = Weather::default();
+        let coldest = Date {
+            month: 1,
+            day: 21,
+            hour: 0.,
+            year: None,
+        };
+
+        // At the surface, the coldest day is mean - amplitude
+        let details = kusuda_details(0.);
+        let t = ground_temperature(Some(&details), &weather, coldest)?;
+        assert_close!(t, 4.);
+
+        // ... and half a year later it is the hottest
+        let t = kusuda(12., 8., 20., 0., SOIL_DIFFUSIVITY, 20. + 365. / 2.);
+        assert_close!(t, 20.);
+
+        // Deeper, the amplitude is dampened
+        let details = kusuda_details(2.);
+        let t = ground_temperature(Some(&details), &weather, coldest)?;
+        assert!(t > 4. && t < 20.);
+
+        // Very deep, it is the mean temperature
+        let details = kusuda_details(50.);
+        let t = ground_temperature(Some(&details), &weather, coldest)?;
+        assert_close!(t, 12.);
+
+        // Bad diffusivity
+        let mut details = SiteDetails::default();
+        details.set_ground_temperature(GroundTemperature::Kusuda {
+            mean_temperature: 12.,
+            amplitude: 8.,
+            coldest_day: 20.,
+            depth: 1.,
+            soil_diffusivity: 0.,
+        });
+        assert!(ground_temperature(Some(&details), &weather, coldest).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_weather_file() -> Result<(), String> {
+        let date = Date {
+            month: 7,
+            day: 1,
+            hour: 0.,
+            year: None,
+        };
+
+        // No information in the weather
+        let mut weather = Weather::default();
+        assert!(ground_temperature(None, &weather, date).is_err());
+
+        weather.ground_temperature = vec![
+            EPWGroundTemperature {
+                depth: 0.5,
+                average_monthly_temperature: [18.; 12],
+                ..EPWGroundTemperature::default()
+            },
+            EPWGroundTemperature {
+                depth: 4.,
+                average_monthly_temperature: [14.; 12],
+                ..EPWGroundTemperature::default()
+            },
+        ];
+
+        // Defaults to the shallowest
+        assert_close!(ground_temperature(None, &weather, date)?, 18.);
+        assert_close!(
+            ground_temperature(Some(&SiteDetails::default()), &weather, date)?,
+            18.
+        );
+
+        // At a certain depth
+        let mut details = SiteDetails::default();
+        details.set_ground_temperature(GroundTemperature::WeatherFile { depth: 4. });
+        assert_close!(ground_temperature(Some(&details), &weather, date)?, 14.);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ground_coupling_coefficient() -> Result<(), String> {
+        assert!(ground_coupling_coefficient(None)?.is_none());
+
+        let mut details = SiteDetails::default();
+        assert!(ground_coupling_coefficient(Some(&details))?.is_none());
+
+        details.set_ground_coupling(GroundCoupling::Convective);
+        assert!(ground_coupling_coefficient(Some(&details))?.is_none());
+
+        details.set_ground_coupling(GroundCoupling::SoilLayer {
+            thickness: 0.5,
+            conductivity: 1.5,
+        });
+        let hs = ground_coupling_coefficient(Some(&details))?.ok_or("Expecting a coefficient")?;
+        assert_close!(hs, 3.);
+
+        details.set_ground_coupling(GroundCoupling::SoilLayer {
+            thickness: 0.,
+            conductivity: 1.5,
+        });
+        assert!(ground_coupling_coefficient(Some(&details)).is_err());
+        Ok(())
+    }
+}
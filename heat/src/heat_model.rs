@@ -17,7 +17,8 @@ LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
 OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
-use crate::discretization::Discretization;
+use crate::discretization::{Discretization, DEFAULT_MAX_DX, DEFAULT_MIN_DT};
+use crate::radiant_exchange::add_radiant_exchange;
 use crate::Float;
 use calendar::Date;
 use model::{FenestrationType, SurfaceTrait};
@@ -28,15 +29,18 @@ use weather::WeatherTrait;
 
 use crate::surface::{SurfaceMemory, ThermalFenestration, ThermalSurface, ThermalSurfaceData};
 
+use crate::electric_equipment::ThermalElectricEquipment;
+use crate::ground::{ground_coupling_coefficient, ground_temperature};
 use crate::heating_cooling::ThermalHVAC;
+use crate::hot_water::ThermalWaterTank;
 use crate::luminaire::ThermalLuminaire;
 
 use crate::zone::ThermalZone;
-use model::{Boundary, Model, SimulationState, SimulationStateHeader};
+use model::{Boundary, Model, SimulationState, SimulationStateHeader, HVAC};
 use std::borrow::Borrow;
 
-// #[cfg(feature = "parallel")]
-// use rayon::prelude::*;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 /// The module name. For debugging purposes
 pub(crate) const MODULE_NAME: &str = "Thermal model";
@@ -66,6 +70,12 @@ pub struct ThermalModel {
     /// Luminaires
     pub luminaires: Vec<ThermalLuminaire>,
 
+    /// Plug loads
+    pub electric_equipment: Vec<ThermalElectricEquipment>,
+
+    /// Domestic hot water tanks, and the solar collectors heating them
+    pub water_tanks: Vec<ThermalWaterTank>,
+
     // / contains all the HVACs
     // pub hvacs: Vec<Float>,
     /// The number of steps that this model needs
@@ -77,9 +87,10 @@ pub struct ThermalModel {
     pub dt: Float,
 }
 
-fn get_boundary_temperature(
+pub(crate) fn get_boundary_temperature(
     b: &Boundary,
     t_out: Float,
+    t_ground: Option<Float>,
     model: &Model,
     state: &SimulationState,
 ) -> Result<Float, String> {
@@ -93,13 +104,23 @@ fn get_boundary_temperature(
                 .dry_bulb_temperature(state)
                 .ok_or_else(|| "Space at the back of surface has no temperature!".into())
         }
-        Boundary::Ground => unimplemented!(),
+        Boundary::Ground => t_ground.ok_or_else(|| {
+            "Surface leads to the Ground, but there is no ground temperature!".into()
+        }),
         Boundary::AmbientTemperature { temperature } => Ok(*temperature),
         Boundary::Outdoor => Ok(t_out),
     }
 }
 
-// #[cfg(not(feature = "parallel"))]
+/// Marches all the `surfaces` through a timestep, and writes the results
+/// in the `state`.
+///
+/// Surfaces only read the state while marching (i.e., the temperature of
+/// the spaces and of other surfaces are those at the beginning of the
+/// timestep, and the spaces are coupled through their air temperature,
+/// which is updated afterwards), so they are independent from each other
+/// and—with the `parallel` feature—they are marched in parallel. The
+/// results are then written sequentially.
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn iterate_surfaces<T: SurfaceTrait + Send + Sync>(
     surfaces: &[ThermalSurfaceData<T>],
@@ -107,14 +128,15 @@ pub(crate) fn iterate_surfaces<T: SurfaceTrait + Send + Sync>(
     wind_direction: Float,
     wind_speed: Float,
     t_out: Float,
+    t_ground: Option<Float>,
     dt: Float,
     model: &Model,
     state: &mut SimulationState,
 ) -> Result<(), String> {
-    // #[cfg(not(feature = "parallel"))]
+    #[cfg(not(feature = "parallel"))]
     let surface_iter = surfaces.iter().zip(alloc.iter_mut());
-    // #[cfg(feature = "parallel")]
-    // let surface_iter = (*surfaces).into_par_iter().zip(alloc.par_iter_mut());
+    #[cfg(feature = "parallel")]
+    let surface_iter = surfaces.par_iter().zip(alloc.par_iter_mut());
 
     // Collect boundary temperatures
     let boundary_temps: Vec<(Float, Float)> = surfaces
@@ -122,19 +144,22 @@ pub(crate) fn iterate_surfaces<T: SurfaceTrait + Send + Sync>(
         .map(|s| {
             let t_front = match &s.front_boundary {
                 Boundary::Adiabatic => s.parent.back_temperature(state),
-                _ => get_boundary_temperature(&s.front_boundary, t_out, model, state).unwrap(),
+                _ => get_boundary_temperature(&s.front_boundary, t_out, t_ground, model, state)
+                    .unwrap(),
             };
 
             let t_back = match &s.back_boundary {
                 Boundary::Adiabatic => t_front,
-                _ => get_boundary_temperature(&s.back_boundary, t_out, model, state).unwrap(),
+                _ => get_boundary_temperature(&s.back_boundary, t_out, t_ground, model, state)
+                    .unwrap(),
             };
 
             (t_front, t_back)
         })
         .collect();
 
-    // Perform calculations in parallel
+    // Perform calculations (in parallel, if possible)
+    let shared_state: &SimulationState = state;
     let results: Vec<Result<(), String>> = surface_iter
         .enumerate()
         .map(|(index, d)| -> Result<(), String> {
@@ -144,7 +169,7 @@ pub(crate) fn iterate_surfaces<T: SurfaceTrait + Send + Sync>(
 
             // Update temperatures
             thermal_surface.march(
-                state,
+                shared_state,
                 t_front,
                 t_back,
                 wind_direction,
@@ -283,12 +308,28 @@ impl SimulationModel for ThermalModel {
 
         // choose the smallest timestep in all constructions
 
-        let max_dx = 0.05; // m
-        let min_dt = 60.0; // seconds
+        let (max_dx, min_dt) = match &model.thermal_options {
+            Some(options) => (
+                options
+                    .max_element_thickness()
+                    .copied()
+                    .unwrap_or(DEFAULT_MAX_DX),
+                options.min_timestep().copied().unwrap_or(DEFAULT_MIN_DT),
+            ),
+            None => (DEFAULT_MAX_DX, DEFAULT_MIN_DT),
+        };
+        if max_dx <= 0. || min_dt <= 0. {
+            return Err(format!(
+                "The maximum element thickness ({}) and minimum timestep ({}) in ThermalOptions must be positive",
+                max_dx, min_dt
+            ));
+        }
 
         let mut dt_subdivisions: usize = 1;
         let main_dt = 60. * 60. / n as Float;
 
+        let ground_hs = ground_coupling_coefficient(model.site_details.as_ref())?;
+
         // Store the dts and n_nodes somwehere. Take note of the largest
         // number of subditivions required
         let mut surfaces = Vec::with_capacity(model.surfaces.len());
@@ -325,10 +366,41 @@ impl SimulationModel for ThermalModel {
             // Match surface and zones
             tsurf.set_front_boundary(surf.front_boundary.clone(), model);
             tsurf.set_back_boundary(surf.back_boundary.clone(), model);
+            tsurf.set_ground_coupling(ground_hs);
+            tsurf.thermal_bridges_coefficient =
+                surf.thermal_bridges_heat_transfer_coefficient(model)?;
+            tsurf.set_convection_models(
+                surf.front_convection_model().ok().copied(),
+                surf.back_convection_model().ok().copied(),
+                model.thermal_options.as_ref(),
+            );
 
             surfaces.push(tsurf);
         }
 
+        // Radiant systems inject heat into the surfaces
+        for hvac in model.hvacs.iter() {
+            if let HVAC::RadiantSurfaceSystem(system) = hvac {
+                let target = system.target_surface()?;
+                let index = model
+                    .surfaces
+                    .iter()
+                    .position(|s| s.name() == target)
+                    .ok_or_else(|| {
+                        format!(
+                            "RadiantSurfaceSystem '{}' is supposed to be in a surface called '{}'... but it was not found",
+                            system.name(),
+                            target
+                        )
+                    })?;
+                let layer = system.source_layer_or(MODULE_NAME, 0);
+                let node = surfaces[index].discretization.interface_node(layer)?;
+                surfaces[index]
+                    .heat_sources
+                    .push((node, std::sync::Arc::clone(system)));
+            }
+        }
+
         let mut fenestrations = Vec::with_capacity(model.fenestrations.len());
         for (i, surf) in model.fenestrations.iter().enumerate() {
             if let FenestrationType::Opening = surf.category {
@@ -339,7 +411,8 @@ impl SimulationModel for ThermalModel {
             let normal = surf.vertices.normal();
             let cos_tilt = normal * Vector3D::new(0., 0., 1.);
             let angle = cos_tilt.acos();
-            let area = surf.area();
+            // The frame (if any) is not part of the simulated glazing
+            let area = surf.glazed_area()?;
             let perimeter = surf.vertices.outer().perimeter()?;
             let centroid = surf.vertices.outer().centroid()?;
 
@@ -368,17 +441,44 @@ impl SimulationModel for ThermalModel {
             // Match surface and zones
             tsurf.set_front_boundary(surf.front_boundary.clone(), model);
             tsurf.set_back_boundary(surf.back_boundary.clone(), model);
+            tsurf.set_ground_coupling(ground_hs);
+            tsurf.thermal_bridges_coefficient = surf.frame_heat_transfer_coefficient()?;
+            tsurf.set_convection_models(
+                surf.front_convection_model().ok().copied(),
+                surf.back_convection_model().ok().copied(),
+                model.thermal_options.as_ref(),
+            );
 
             fenestrations.push(tsurf);
         }
 
+        // Long-wave radiant exchange between the surfaces around each zone
+        let n_rays = model
+            .thermal_options
+            .as_ref()
+            .and_then(|o| o.n_view_factor_rays().ok().copied());
+        if let Some(n_rays) = n_rays {
+            add_radiant_exchange(
+                model,
+                zones.len(),
+                &mut surfaces,
+                &mut fenestrations,
+                n_rays,
+            )?;
+        }
+
+        // The surfaces around each zone define its mean radiant temperature
+        for (i, zone) in zones.iter_mut().enumerate() {
+            zone.calc_view_factors(i, &surfaces, &fenestrations)?;
+        }
+
         dt_subdivisions *= 2; // this is needed mostly for the no-mass walls and windows
         let dt = 60. * 60. / (n as Float * dt_subdivisions as Float);
 
         // This is the model's dt now. When marching
         let mut hvacs: Vec<ThermalHVAC> = Vec::with_capacity(model.hvacs.len());
-        for hvac in model.hvacs.iter() {
-            let h = ThermalHVAC::from(hvac, model)?;
+        for (i, hvac) in model.hvacs.iter().enumerate() {
+            let h = ThermalHVAC::from(hvac, i, model, state)?;
             hvacs.push(h)
         }
 
@@ -388,10 +488,33 @@ impl SimulationModel for ThermalModel {
             luminaires.push(l)
         }
 
+        let mut electric_equipment: Vec<ThermalElectricEquipment> =
+            Vec::with_capacity(model.electric_equipment.len());
+        for eq in model.electric_equipment.iter() {
+            let e = ThermalElectricEquipment::from(eq, model)?;
+            electric_equipment.push(e)
+        }
+
+        for collector in model.solar_collectors.iter() {
+            if model.get_water_tank(&collector.water_tank).is_err() {
+                return Err(format!(
+                    "SolarCollector '{}' heats a WaterTank called '{}'... but it was not found",
+                    collector.name(),
+                    collector.water_tank
+                ));
+            }
+        }
+        let mut water_tanks: Vec<ThermalWaterTank> = Vec::with_capacity(model.water_tanks.len());
+        for (i, tank) in model.water_tanks.iter().enumerate() {
+            water_tanks.push(ThermalWaterTank::from(tank, i, model, state)?);
+        }
+
         Ok(ThermalModel {
             zones,
             surfaces,
             luminaires,
+            electric_equipment,
+            water_tanks,
             fenestrations,
             dt_subdivisions,
             hvacs,
@@ -420,6 +543,15 @@ impl SimulationModel for ThermalModel {
             let wind_speed = current_weather.wind_speed;
 
             let t_out = current_weather.dry_bulb_temperature;
+            let t_ground = if self.has_ground_boundaries() {
+                Some(ground_temperature(
+                    model.site_details.as_ref(),
+                    weather,
+                    date,
+                )?)
+            } else {
+                None
+            };
 
             // Gather spaces temperatures
             let t_current = self.get_current_zones_temperatures(state);
@@ -430,6 +562,7 @@ impl SimulationModel for ThermalModel {
                 wind_direction,
                 wind_speed,
                 t_out,
+                t_ground,
                 self.dt,
                 model,
                 state,
@@ -441,14 +574,25 @@ impl SimulationModel for ThermalModel {
                 wind_direction,
                 wind_speed,
                 t_out,
+                t_ground,
                 self.dt,
                 model,
                 state,
             )?;
 
+            /* DOMESTIC HOT WATER */
+            for tank in self.water_tanks.iter() {
+                tank.march(model, t_out, self.dt, state)?;
+            }
+
+            /* ELECTRICITY CONSUMED BY HVACS */
+            for hvac in self.hvacs.iter() {
+                hvac.update_electricity_consumption(t_out, state)?;
+            }
+
             /* UPDATE ZONES' TEMPERATURE */
             // This is done analytically.
-            let (a, b, c) = self.calculate_zones_abc(model, state)?;
+            let (a, b, c) = self.calculate_zones_abc(model, t_out, t_ground, state)?;
 
             let future_temperatures =
                 self.estimate_zones_future_temperatures(&t_current, &a, &b, &c, self.dt);
@@ -462,11 +606,35 @@ impl SimulationModel for ThermalModel {
             }
         } // End of 'in each sub-timestep-subdivision'
 
+        /* UPDATE ZONES' MEAN RADIANT TEMPERATURE */
+        for zone in self.zones.iter() {
+            let space = &zone.reference_space;
+            if let Some(t) = zone
+                .mean_radiant_temperature(state)
+                .or_else(|| space.dry_bulb_temperature(state))
+            {
+                space.set_mean_radiant_temperature(state, t)?;
+            }
+        }
+
         Ok(())
     }
 }
 
 impl ThermalModel {
+    /// Checks whether any of the surfaces or fenestrations
+    /// is in contact with the `Ground`
+    fn has_ground_boundaries(&self) -> bool {
+        let is_ground = |b: &Boundary| matches!(b, Boundary::Ground);
+        self.surfaces
+            .iter()
+            .any(|s| is_ground(&s.front_boundary) || is_ground(&s.back_boundary))
+            || self
+                .fenestrations
+                .iter()
+                .any(|s| is_ground(&s.front_boundary) || is_ground(&s.back_boundary))
+    }
+
     /// Retrieves the dt_subdivisions (i.e. the
     /// number of substimesteps per timestep of this
     /// model)
@@ -525,10 +693,15 @@ impl ThermalModel {
     /// ```math
     /// \frac{\displaystyle\int_{0}^t{T(t)dt}}{t} = \frac{A}{B}+\frac{C_{zone}\left(T_{current}-\frac{A}{B}\right)}{Bt}\left(1-e^{-\frac{Bt}{C_{zone}}} \right)
     /// ```
+    ///
+    /// $`A`$, $`B`$ and $`C_{zone}`$ are multiplied by the `multiplier` of
+    /// the space, so they add up all of its copies.
     #[allow(clippy::type_complexity)]
     fn calculate_zones_abc(
         &self,
         model: &Model,
+        t_out: Float,
+        t_ground: Option<Float>,
         state: &SimulationState,
     ) -> Result<(Vec<Float>, Vec<Float>, Vec<Float>), String> {
         let nzones = self.zones.len();
@@ -554,6 +727,29 @@ impl ThermalModel {
                 .expect("Luminaire has no Power Consumption state");
             a[index] += consumption;
         }
+        // Electric equipment... the radiant fraction is not yet distributed
+        // among the surfaces, so it all goes into the air. The latent fraction
+        // goes into the humidity of the air.
+        for eq in self.electric_equipment.iter() {
+            let index = eq.target_space_index;
+            let consumption = eq
+                .parent
+                .power_consumption(state)
+                .expect("ElectricEquipment has no Power Consumption state");
+            let latent = eq.parent.fraction_latent_or(MODULE_NAME, 0.);
+            a[index] += consumption * (1. - latent);
+        }
+        // Heat lost by the hot water tanks located in the spaces
+        for tank in self.water_tanks.iter() {
+            if let Some(index) = tank.target_space_index {
+                let t_water = tank
+                    .parent
+                    .water_temperature(state)
+                    .expect("WaterTank has no water temperature");
+                a[index] += tank.heat_loss_coefficient * t_water;
+                b[index] += tank.heat_loss_coefficient;
+            }
+        }
 
         let air = crate::gas::AIR;
         // Other
@@ -637,9 +833,84 @@ impl ThermalModel {
         iterate_surfaces(&self.surfaces, state, &mut a, &mut b)?;
         iterate_surfaces(&self.fenestrations, state, &mut a, &mut b)?;
 
+        /* THERMAL BRIDGES */
+        // A steady conductive path between the environments at each side
+        // (e.g., linear thermal bridges, or the frames of the windows)
+        fn iterate_thermal_bridges<T: SurfaceTrait + Send + Sync>(
+            surfaces: &[ThermalSurfaceData<T>],
+            t_out: Float,
+            t_ground: Option<Float>,
+            model: &Model,
+            state: &SimulationState,
+            a: &mut [Float],
+            b: &mut [Float],
+        ) -> Result<(), String> {
+            for surface in surfaces {
+                let ua = surface.thermal_bridges_coefficient;
+                if ua == 0.0
+                    || matches!(surface.front_boundary, Boundary::Adiabatic)
+                    || matches!(surface.back_boundary, Boundary::Adiabatic)
+                {
+                    continue;
+                }
+                if let Some(z_index) = surface.front_space_index {
+                    let temp = get_boundary_temperature(
+                        &surface.back_boundary,
+                        t_out,
+                        t_ground,
+                        model,
+                        state,
+                    )?;
+                    a[z_index] += ua * temp;
+                    b[z_index] += ua;
+                }
+                if let Some(z_index) = surface.back_space_index {
+                    let temp = get_boundary_temperature(
+                        &surface.front_boundary,
+                        t_out,
+                        t_ground,
+                        model,
+                        state,
+                    )?;
+                    a[z_index] += ua * temp;
+                    b[z_index] += ua;
+                }
+            }
+            Ok(())
+        }
+
+        iterate_thermal_bridges(
+            &self.surfaces,
+            t_out,
+            t_ground,
+            model,
+            state,
+            &mut a,
+            &mut b,
+        )?;
+        iterate_thermal_bridges(
+            &self.fenestrations,
+            t_out,
+            t_ground,
+            model,
+            state,
+            &mut a,
+            &mut b,
+        )?;
+
         /* AIR MIXTURE WITH OTHER ZONES */
         // unimplemented();
 
+        /* SPACE MULTIPLIERS */
+        // A zone stands for all the copies of its space, so the loads,
+        // conductances and capacitance of all of them are added up. This
+        // does not change the temperature of the zone.
+        for (i, zone) in self.zones.iter().enumerate() {
+            a[i] *= zone.multiplier;
+            b[i] *= zone.multiplier;
+            c[i] *= zone.multiplier;
+        }
+
         // RETURN
         Ok((a, b, c))
     }
@@ -738,6 +1009,7 @@ mod testing {
         longitude: 0.,
         standard_meridian: 0.,
         elevation: 0.0,
+        daylight_saving: None,
     };
 
     #[test]
@@ -761,7 +1033,9 @@ mod testing {
         // MAP THE STATE
 
         // Test
-        let (a, b, c) = thermal_model.calculate_zones_abc(&model, &state).unwrap();
+        let (a, b, c) = thermal_model
+            .calculate_zones_abc(&model, 10., None, &state)
+            .unwrap();
         assert_eq!(a.len(), 1);
         assert_eq!(c.len(), 1);
         assert_eq!(b.len(), 1);
@@ -777,4 +1051,135 @@ mod testing {
 
         Ok(())
     }
+
+    #[test]
+    fn test_space_multiplier() -> Result<(), String> {
+        let options = SingleZoneTestBuildingOptions {
+            zone_volume: 40.,
+            surface_height: 2.,
+            surface_width: 2.,
+            construction: vec![TestMat::Polyurethane(0.02)],
+            heating_power: 500.,
+            lighting_power: 100.,
+            emissivity: 0.0,
+            ..Default::default()
+        };
+
+        // The coefficients and future temperature of the zone
+        let abc = |multiplier: usize| -> Result<(Float, Float, Float, Float), String> {
+            let (mut model, mut state_header) = get_single_zone_test_building(&options)?;
+            let mut space = (*model.spaces[0]).clone();
+            space.set_multiplier(multiplier);
+            model.spaces[0] = std::sync::Arc::new(space);
+
+            let thermal_model = ThermalModel::new(&META_OPTIONS, (), &model, &mut state_header, 1)?;
+            let mut state = state_header.take_values().ok_or("Could not take state")?;
+            model.luminaires[0].set_power_consumption(&mut state, 100.)?;
+            if let HVAC::ElectricHeater(heater) = &model.hvacs[0] {
+                heater.set_heating_cooling_consumption(&mut state, 500.)?;
+            }
+
+            let (a, b, c) = thermal_model.calculate_zones_abc(&model, 5., None, &state)?;
+            let t_current = thermal_model.get_current_zones_temperatures(&state);
+            let t = thermal_model.estimate_zones_future_temperatures(&t_current, &a, &b, &c, 300.);
+            Ok((a[0], b[0], c[0], t[0]))
+        };
+
+        let (a1, b1, c1, t1) = abc(1)?;
+        let (a3, b3, c3, t3) = abc(3)?;
+        assert!(a1 > 600.);
+        assert_eq!(a3, 3. * a1);
+        assert_eq!(b3, 3. * b1);
+        assert_eq!(c3, 3. * c1);
+        assert!((t3 - t1).abs() < 1e-9);
+        Ok(())
+    }
+
+    #[test]
+    fn test_mean_radiant_temperature() -> Result<(), String> {
+        let (model, mut state_header) =
+            get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+                zone_volume: 40.,
+                surface_height: 2.,
+                surface_width: 2.,
+                construction: vec![TestMat::Polyurethane(0.02)],
+                ..Default::default()
+            })?;
+
+        let thermal_model = ThermalModel::new(&META_OPTIONS, (), &model, &mut state_header, 1)?;
+        let mut state = state_header.take_values().ok_or("Could not take state")?;
+        let zone = thermal_model.get_thermal_zone(0)?;
+        assert!(!zone.view_factors.is_empty());
+        let total: Float = zone.view_factors.iter().map(|(_, f)| f).sum();
+        assert!((total - 1.).abs() < 1e-9, "total = {}", total);
+
+        // All surfaces at the same temperature
+        for (i, _) in zone.view_factors.iter() {
+            state[*i] = 17.;
+        }
+        let mrt = zone
+            .mean_radiant_temperature(&state)
+            .ok_or("No mean radiant temperature")?;
+        assert!((mrt - 17.).abs() < 1e-9, "mrt = {}", mrt);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_thermal_bridges() -> Result<(), String> {
+        let (model, mut state_header) =
+            get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+                zone_volume: 40.,
+                surface_height: 2.,
+                surface_width: 2.,
+                construction: vec![TestMat::Polyurethane(0.02)],
+                emissivity: 0.0,
+                ..Default::default()
+            })?;
+
+        let n: usize = 1;
+        let mut thermal_model = ThermalModel::new(&META_OPTIONS, (), &model, &mut state_header, n)?;
+        let state = state_header.take_values().ok_or("Could not take state")?;
+        assert_eq!(thermal_model.surfaces[0].thermal_bridges_coefficient, 0.0);
+
+        let t_out = 10.;
+        let (a, b, ..) = thermal_model.calculate_zones_abc(&model, t_out, None, &state)?;
+
+        // The surface leads outdoors, so the space gets connected to the outside
+        let ua = 2.;
+        thermal_model.surfaces[0].thermal_bridges_coefficient = ua;
+        let (a_bridge, b_bridge, ..) =
+            thermal_model.calculate_zones_abc(&model, t_out, None, &state)?;
+        assert!((a_bridge[0] - a[0] - ua * t_out).abs() < 1e-6);
+        assert!((b_bridge[0] - b[0] - ua).abs() < 1e-6);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_window_frame() -> Result<(), String> {
+        let (mut model, mut state_header) =
+            get_single_zone_test_building(&SingleZoneTestBuildingOptions {
+                zone_volume: 40.,
+                surface_height: 2.,
+                surface_width: 2.,
+                window_height: 1.,
+                window_width: 1.,
+                construction: vec![TestMat::Polyurethane(0.02)],
+                ..Default::default()
+            })?;
+        let mut window = (*model.fenestrations[0]).clone();
+        window.set_frame(model::WindowFrame::new(0.1, 5.));
+        let frame_ua = window.frame_heat_transfer_coefficient()?;
+        assert!(frame_ua > 0.);
+        model.fenestrations[0] = std::sync::Arc::new(window);
+
+        let thermal_model = ThermalModel::new(&META_OPTIONS, (), &model, &mut state_header, 1)?;
+        let fen = &thermal_model.fenestrations[0];
+        // Only the glass is simulated; the frame is a parallel path
+        assert!((fen.area - 0.8 * 0.8).abs() < 1e-6);
+        assert!((fen.thermal_bridges_coefficient - frame_ua).abs() < 1e-9);
+
+        Ok(())
+    }
 }
@@ -19,8 +19,14 @@ SOFTWARE.
 */
 
 use crate::Float;
-use model::hvac::{ElectricHeater, IdealHeaterCooler, HVAC};
-use model::{Model, SimulationState};
+use model::hvac::{
+    ElectricHeater, HeatPump, HeatRecoveryVentilator, IdealHeaterCooler, RadiantSurfaceSystem, HVAC,
+};
+use model::{Model, SimulationState, SimulationStateElement, SimulationStateHeader};
+use polynomial::Polynomial;
+
+/// The rated COP of a [`HeatPump`] when none is given
+const DEFAULT_RATED_COP: Float = 3.;
 
 /// An HVAC element from the point of view of the thermal
 /// model.
@@ -42,11 +48,75 @@ pub enum ThermalHVAC {
         /// The space this heater is heating
         target_space_index: usize,
     },
+
+    /// Air-to-air heat pump
+    HeatPump {
+        /// A clone of the parent HVAC
+        parent: HeatPump,
+
+        /// The space this heat pump is heating/cooling
+        target_space_index: usize,
+
+        /// The COP when heating at rated conditions
+        rated_heating_cop: Float,
+
+        /// The COP when cooling at rated conditions
+        rated_cooling_cop: Float,
+
+        /// Modifies the heating COP as a function of outdoor temperature
+        heating_cop_curve: Box<Polynomial>,
+
+        /// Modifies the cooling COP as a function of outdoor temperature
+        cooling_cop_curve: Box<Polynomial>,
+
+        /// Modifies the COP as a function of the part load ratio
+        part_load_curve: Box<Polynomial>,
+    },
+
+    /// Radiant system. It does not deliver heat into the air, but into
+    /// the construction of a surface (see [`ThermalSurfaceData`](crate::surface::ThermalSurfaceData))
+    RadiantSurfaceSystem {
+        /// A clone of the parent HVAC
+        parent: RadiantSurfaceSystem,
+    },
+
+    /// Ventilation with heat recovery. It does not deliver heat directly, as
+    /// the air it supplies is accounted for as ventilation by the air flow model
+    HeatRecoveryVentilator {
+        /// A clone of the parent HVAC
+        parent: HeatRecoveryVentilator,
+    },
+}
+
+/// Builds a [`Polynomial`] out of a set of coefficients. If no coefficients
+/// are given, the polynomial is the constant 1.
+fn curve(coefficients: Result<&Vec<Float>, String>) -> Result<Polynomial, String> {
+    let mut ret = Polynomial::new();
+    match coefficients {
+        Ok(coefficients) => {
+            if coefficients.len() > ret.coefficients.len() {
+                return Err(format!(
+                    "Performance curves can have up to {} coefficients... found {}",
+                    ret.coefficients.len(),
+                    coefficients.len()
+                ));
+            }
+            coefficients.iter().for_each(|c| ret.push(*c));
+        }
+        Err(_) => ret.push(1.),
+    }
+    Ok(ret)
 }
 
 impl ThermalHVAC {
-    /// Builds a new [`ThermalHVAC`] from an HVAC and its location
-    pub fn from(hvac: &HVAC, model: &Model) -> Result<Self, String> {
+    /// Builds a new [`ThermalHVAC`] from an HVAC and its location, pushing
+    /// whatever physical state it needs into the `state`
+    pub fn from(
+        hvac: &HVAC,
+        hvac_index: usize,
+        model: &Model,
+        state: &mut SimulationStateHeader,
+    ) -> Result<Self, String> {
         match hvac {
             HVAC::ElectricHeater(e) => {
                 let parent = (**e).clone();
@@ -72,6 +142,49 @@ impl ThermalHVAC {
                 }
                 Err(format!("IdealHeaterCooler is supposed to be in a space called '{}'... but it was not found", parent.target_space()?))
             }
+            HVAC::HeatPump(e) => {
+                let parent = (**e).clone();
+                for (i, s) in model.spaces.iter().enumerate() {
+                    if s.name() == parent.target_space()? {
+                        let index = state.push(
+                            SimulationStateElement::HeatPumpElectricityConsumption(hvac_index),
+                            0.,
+                        )?;
+                        e.set_electricity_consumption_index(index)?;
+                        return Ok(Self::HeatPump {
+                            rated_heating_cop: parent.rated_heating_cop_or(
+                                crate::heat_model::MODULE_NAME,
+                                DEFAULT_RATED_COP,
+                            ),
+                            rated_cooling_cop: parent.rated_cooling_cop_or(
+                                crate::heat_model::MODULE_NAME,
+                                DEFAULT_RATED_COP,
+                            ),
+                            heating_cop_curve: Box::new(curve(parent.heating_cop_curve())?),
+                            cooling_cop_curve: Box::new(curve(parent.cooling_cop_curve())?),
+                            part_load_curve: Box::new(curve(parent.part_load_curve())?),
+                            parent,
+                            target_space_index: i,
+                        });
+                    }
+                }
+                Err(format!(
+                    "HeatPump is supposed to be in a space called '{}'... but it was not found",
+                    parent.target_space()?
+                ))
+            }
+            HVAC::RadiantSurfaceSystem(e) => {
+                let parent = (**e).clone();
+                let surface = parent.target_surface()?;
+                if model.get_surface(surface).is_err() {
+                    return Err(format!("RadiantSurfaceSystem '{}' is supposed to be in a surface called '{}'... but it was not found", parent.name(), surface));
+                }
+                Ok(Self::RadiantSurfaceSystem { parent })
+            }
+            HVAC::HeatRecoveryVentilator(e) => {
+                let parent = (**e).clone();
+                Ok(Self::HeatRecoveryVentilator { parent })
+            }
         }
     }
 
@@ -120,6 +233,138 @@ impl ThermalHVAC {
                 }
                 Ok(ret)
             }
+            Self::HeatPump {
+                parent,
+                target_space_index,
+                ..
+            } => {
+                let mut ret = Vec::with_capacity(1);
+                let consumption_power = match parent.heating_cooling_consumption(state) {
+                    Some(v) => v,
+                    None => {
+                        return Err(format!(
+                            "Could not get Heating consumption of HeatPump called '{}'",
+                            parent.name()
+                        ))
+                    }
+                };
+                ret.push((*target_space_index, consumption_power));
+                Ok(ret)
+            }
+            // The heat goes into the surface, not the air
+            Self::RadiantSurfaceSystem { .. } => Ok(Vec::with_capacity(0)),
+            // The heat goes in through the ventilation
+            Self::HeatRecoveryVentilator { .. } => Ok(Vec::with_capacity(0)),
+        }
+    }
+
+    /// Calculates the electricity consumed by the HVACs that do not
+    /// simply have a COP of 1 (i.e., heat pumps), based on the
+    /// heating/cooling power they deliver and the outdoor temperature.
+    pub fn update_electricity_consumption(
+        &self,
+        t_out: Float,
+        state: &mut SimulationState,
+    ) -> Result<(), String> {
+        if let Self::HeatPump {
+            parent,
+            rated_heating_cop,
+            rated_cooling_cop,
+            heating_cop_curve,
+            cooling_cop_curve,
+            part_load_curve,
+            ..
+        } = self
+        {
+            let delivered = parent.heating_cooling_consumption(state).ok_or_else(|| {
+                format!(
+                    "Could not get Heating consumption of HeatPump called '{}'",
+                    parent.name()
+                )
+            })?;
+            let (cop, capacity) = if delivered > 0. {
+                (
+                    rated_heating_cop * heating_cop_curve.eval(t_out),
+                    parent.max_heating_power_or("", delivered),
+                )
+            } else {
+                (
+                    rated_cooling_cop * cooling_cop_curve.eval(t_out),
+                    parent.max_cooling_power_or("", -delivered),
+                )
+            };
+            let electricity = if delivered.abs() > 0. && capacity > 0. {
+                let plr = (delivered.abs() / capacity).min(1.);
+                let cop = cop * part_load_curve.eval(plr);
+                if cop <= 0. {
+                    return Err(format!(
+                        "HeatPump called '{}' has a non-positive COP ({}) at {}C",
+                        parent.name(),
+                        cop,
+                        t_out
+                    ));
+                }
+                delivered.abs() / cop
+            } else {
+                0.
+            };
+            parent.set_electricity_consumption(state, electricity)?;
         }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use model::Space;
+
+    #[test]
+    fn test_heat_pump_electricity() -> Result<(), String> {
+        let mut model = Model::default();
+        model.add_space(Space::new("Room"));
+
+        let mut heat_pump = HeatPump::new("Heat pump");
+        heat_pump
+            .set_target_space("Room")
+            .set_max_heating_power(1000.)
+            .set_max_cooling_power(800.)
+            .set_rated_heating_cop(3.)
+            .set_heating_cop_curve(vec![1.0, 0.02])
+            .set_part_load_curve(vec![0.5, 0.5]);
+        let hvac = model.add_hvac(heat_pump.wrap())?;
+
+        let mut header = model.take_state().ok_or("Could not take state")?;
+        let thermal = ThermalHVAC::from(&hvac, 0, &model, &mut header)?;
+        let mut state = header.take_values().ok_or("Could not take values")?;
+
+        let heat_pump = match &hvac {
+            HVAC::HeatPump(h) => h,
+            _ => panic!("Expecting a HeatPump"),
+        };
+
+        // Heating at half load, at 10C
+        heat_pump.set_heating_cooling_consumption(&mut state, 500.)?;
+        thermal.update_electricity_consumption(10., &mut state)?;
+        let cop = 3. * (1.0 + 0.02 * 10.) * (0.5 + 0.5 * 0.5);
+        let found = heat_pump
+            .electricity_consumption(&state)
+            .ok_or("No electricity consumption")?;
+        assert!((found - 500. / cop).abs() < 1e-3);
+
+        // Cooling at full load... no cooling curve, so the rated COP is the default
+        heat_pump.set_heating_cooling_consumption(&mut state, -800.)?;
+        thermal.update_electricity_consumption(30., &mut state)?;
+        let found = heat_pump
+            .electricity_consumption(&state)
+            .ok_or("No electricity consumption")?;
+        assert!((found - 800. / DEFAULT_RATED_COP).abs() < 1e-3);
+
+        // Off
+        heat_pump.set_heating_cooling_consumption(&mut state, 0.)?;
+        thermal.update_electricity_consumption(30., &mut state)?;
+        assert_eq!(heat_pump.electricity_consumption(&state), Some(0.));
+
+        Ok(())
     }
 }
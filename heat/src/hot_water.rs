@@ -0,0 +1,393 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::heat_model::MODULE_NAME;
+use crate::Float;
+use model::{
+    Model, SimulationState, SimulationStateElement, SimulationStateHeader, SolarCollector,
+    WaterTank,
+};
+use std::sync::Arc;
+
+/// The density of water, in kg/m3
+const WATER_DENSITY: Float = 1000.;
+
+/// The specific heat capacity of water, in J/kg K
+const WATER_HEAT_CAPACITY: Float = 4186.;
+
+/// The default heat loss coefficient of a [`WaterTank`], in W/K
+const DEFAULT_TANK_HEAT_LOSS_COEFFICIENT: Float = 2.0;
+
+/// The default temperature of the mains water, in C
+const DEFAULT_MAINS_WATER_TEMPERATURE: Float = 15.;
+
+/// The default setpoint of a [`WaterTank`], in C
+const DEFAULT_TANK_SETPOINT: Float = 55.;
+
+/// The default optical efficiency of a [`SolarCollector`]
+const DEFAULT_OPTICAL_EFFICIENCY: Float = 0.75;
+
+/// The default heat loss coefficient of a [`SolarCollector`], in W/m2K
+const DEFAULT_COLLECTOR_HEAT_LOSS_COEFFICIENT: Float = 3.5;
+
+/// A [`SolarCollector`] from the point of view of the thermal model
+pub struct ThermalSolarCollector {
+    /// The parent SolarCollector
+    pub(crate) parent: SolarCollector,
+
+    /// The area of the absorber, in m2
+    area: Float,
+
+    /// The efficiency of the collector when there are no losses
+    optical_efficiency: Float,
+
+    /// The heat lost per unit of area, in W/m2K
+    heat_loss_coefficient: Float,
+}
+
+impl ThermalSolarCollector {
+    /// Builds a new [`ThermalSolarCollector`] from a [`SolarCollector`], pushing
+    /// its physical state into the `state`
+    pub fn from(
+        collector: &Arc<SolarCollector>,
+        collector_index: usize,
+        model: &Model,
+        state: &mut SimulationStateHeader,
+    ) -> Result<Self, String> {
+        if collector.heat_gain_index().is_none() {
+            let i = state.push(
+                SimulationStateElement::SolarCollectorHeatGain(collector_index),
+                0.0,
+            )?;
+            collector.set_heat_gain_index(i)?;
+        }
+        Ok(Self {
+            parent: (**collector).clone(),
+            area: collector.area(model)?,
+            optical_efficiency: collector
+                .optical_efficiency_or(MODULE_NAME, DEFAULT_OPTICAL_EFFICIENCY),
+            heat_loss_coefficient: collector
+                .heat_loss_coefficient_or(MODULE_NAME, DEFAULT_COLLECTOR_HEAT_LOSS_COEFFICIENT),
+        })
+    }
+
+    /// Calculates the useful heat delivered to the tank, in W, when the collector
+    /// receives an `irradiance` (in W/m2). The pump only runs if this is positive.
+    fn heat_gain(&self, irradiance: Float, t_water: Float, t_out: Float) -> Float {
+        let q = self.area
+            * (self.optical_efficiency * irradiance
+                - self.heat_loss_coefficient * (t_water - t_out));
+        q.max(0.0)
+    }
+}
+
+/// A [`WaterTank`] from the point of view of the thermal model
+pub struct ThermalWaterTank {
+    /// The parent WaterTank
+    pub(crate) parent: WaterTank,
+
+    /// The space where the tank is located, if any
+    pub(crate) target_space_index: Option<usize>,
+
+    /// The collectors heating this tank
+    collectors: Vec<ThermalSolarCollector>,
+
+    /// The heat capacity of the water in the tank, in J/K
+    capacitance: Float,
+
+    /// The heat lost per degree of difference with the surroundings, in W/K
+    pub(crate) heat_loss_coefficient: Float,
+
+    /// The temperature of the mains water, in C
+    mains_water_temperature: Float,
+
+    /// The temperature the backup heater tries to keep, in C
+    setpoint: Float,
+
+    /// The maximum power of the backup heater, in W
+    heater_power: Float,
+}
+
+/// Estimates the temperature of the water after `dt` seconds, based on the
+/// following equation (where $`A`$ and $`B`$ are assumed constant):
+///
+/// ```math
+/// C\frac{dT}{dt} = A - BT
+/// ```
+fn future_temperature(t_current: Float, a: Float, b: Float, c: Float, dt: Float) -> Float {
+    if b.abs() < 1e-9 {
+        return t_current + a * dt / c;
+    }
+    let exp = (-b * dt / c).exp();
+    a / b + (t_current - a / b) * exp
+}
+
+/// Calculates the (constant) heating power that would take the water from
+/// `t_current` to `t_target` in `dt` seconds (see [`future_temperature`])
+fn required_power(
+    t_current: Float,
+    t_target: Float,
+    a: Float,
+    b: Float,
+    c: Float,
+    dt: Float,
+) -> Float {
+    if b.abs() < 1e-9 {
+        return c * (t_target - t_current) / dt - a;
+    }
+    let exp = (-b * dt / c).exp();
+    b * (t_target - t_current * exp) / (1. - exp) - a
+}
+
+impl ThermalWaterTank {
+    /// Builds a new [`ThermalWaterTank`] from a [`WaterTank`] and its location, pushing
+    /// the physical state of the tank and of the [`SolarCollector`]s heating it into
+    /// the `state`
+    pub fn from(
+        tank: &Arc<WaterTank>,
+        tank_index: usize,
+        model: &Model,
+        state: &mut SimulationStateHeader,
+    ) -> Result<Self, String> {
+        let parent = (**tank).clone();
+        let setpoint = parent.setpoint_or(MODULE_NAME, DEFAULT_TANK_SETPOINT);
+
+        if tank.water_temperature_index().is_none() {
+            let i = state.push(
+                SimulationStateElement::WaterTankWaterTemperature(tank_index),
+                setpoint,
+            )?;
+            tank.set_water_temperature_index(i)?;
+        }
+        if tank.heater_power_consumption_index().is_none() {
+            let i = state.push(
+                SimulationStateElement::WaterTankHeaterPowerConsumption(tank_index),
+                0.0,
+            )?;
+            tank.set_heater_power_consumption_index(i)?;
+        }
+
+        let target_space_index = match parent.space() {
+            Ok(space) => match model.spaces.iter().position(|s| s.name() == space) {
+                Some(i) => Some(i),
+                None => {
+                    return Err(format!(
+                        "WaterTank '{}' is supposed to be in a space called '{}'... but it was not found",
+                        parent.name(),
+                        space
+                    ))
+                }
+            },
+            Err(_) => None,
+        };
+
+        let mut collectors = Vec::new();
+        for (i, collector) in model.solar_collectors.iter().enumerate() {
+            if collector.water_tank == parent.name {
+                collectors.push(ThermalSolarCollector::from(collector, i, model, state)?);
+            }
+        }
+
+        Ok(Self {
+            capacitance: WATER_DENSITY * parent.volume * WATER_HEAT_CAPACITY,
+            heat_loss_coefficient: parent
+                .heat_loss_coefficient_or(MODULE_NAME, DEFAULT_TANK_HEAT_LOSS_COEFFICIENT),
+            mains_water_temperature: parent
+                .mains_water_temperature_or(MODULE_NAME, DEFAULT_MAINS_WATER_TEMPERATURE),
+            heater_power: parent.heater_power_or(MODULE_NAME, 0.),
+            setpoint,
+            target_space_index,
+            collectors,
+            parent,
+        })
+    }
+
+    /// Advances `dt` seconds in time, updating the temperature of the water, the
+    /// power consumed by the backup heater and the heat delivered by the collectors.
+    ///
+    /// The heat gains and the draw are assumed to be constant during
+    /// this period, and the heat balance is solved analytically.
+    pub fn march(
+        &self,
+        model: &Model,
+        t_out: Float,
+        dt: Float,
+        state: &mut SimulationState,
+    ) -> Result<(), String> {
+        let t_current = self
+            .parent
+            .water_temperature(state)
+            .ok_or("WaterTank has no water temperature")?;
+
+        let t_ambient = match self.target_space_index {
+            Some(i) => model.spaces[i]
+                .dry_bulb_temperature(state)
+                .ok_or("Space containing a WaterTank has no temperature")?,
+            None => t_out,
+        };
+
+        // Solar gains
+        let mut solar_gains = 0.0;
+        for collector in self.collectors.iter() {
+            let irradiance = collector
+                .parent
+                .incident_solar_irradiance(state)
+                .unwrap_or(0.0);
+            let q = collector.heat_gain(irradiance, t_current, t_out);
+            collector.parent.set_heat_gain(state, q)?;
+            solar_gains += q;
+        }
+
+        // Draw
+        let draw = self.parent.draw_flow_rate(state).unwrap_or(0.0).max(0.0);
+        let draw_conductance = WATER_DENSITY * draw * WATER_HEAT_CAPACITY;
+
+        let a = solar_gains
+            + self.heat_loss_coefficient * t_ambient
+            + draw_conductance * self.mains_water_temperature;
+        let b = self.heat_loss_coefficient + draw_conductance;
+        let c = self.capacitance;
+
+        // Backup heater
+        let heater = if self.heater_power > 0. {
+            required_power(t_current, self.setpoint, a, b, c, dt).clamp(0., self.heater_power)
+        } else {
+            0.
+        };
+        self.parent.set_heater_power_consumption(state, heater)?;
+
+        let t_future = future_temperature(t_current, a + heater, b, c, dt);
+        self.parent.set_water_temperature(state, t_future)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use geometry::{Loop3D, Point3D, Polygon3D};
+
+    fn get_model(tank: WaterTank) -> Result<(Model, ThermalWaterTank, SimulationState), String> {
+        let mut model = Model::default();
+        let tank = model.add_water_tank(tank)?;
+
+        let mut the_loop = Loop3D::new();
+        the_loop.push(Point3D::new(0., 0., 0.))?;
+        the_loop.push(Point3D::new(1., 0., 0.))?;
+        the_loop.push(Point3D::new(1., 2., 0.))?;
+        the_loop.push(Point3D::new(0., 2., 0.))?;
+        the_loop.close()?;
+        let mut collector = SolarCollector::new("the collector", tank.name());
+        collector.set_vertices(Polygon3D::new(the_loop)?);
+        let collector = model.add_solar_collector(collector)?;
+
+        let mut state_header = model.take_state().ok_or("No state")?;
+        // This is usually done by the Solar model
+        let i = state_header.push(
+            SimulationStateElement::SolarCollectorSolarIrradiance(0),
+            0.0,
+        )?;
+        collector.set_incident_solar_irradiance_index(i)?;
+
+        let thermal_tank = ThermalWaterTank::from(&tank, 0, &model, &mut state_header)?;
+        let state = state_header.take_values().ok_or("No values")?;
+        Ok((model, thermal_tank, state))
+    }
+
+    #[test]
+    fn test_losses() -> Result<(), String> {
+        let mut tank = WaterTank::new("the tank", 0.2);
+        tank.set_heat_loss_coefficient(2.);
+        let (model, tank, mut state) = get_model(tank)?;
+
+        // Starts at setpoint
+        let t = tank.parent.water_temperature(&state).ok_or("No temp")?;
+        assert!((t - DEFAULT_TANK_SETPOINT).abs() < 1e-9);
+
+        // no heater, no sun... it cools down slowly
+        tank.march(&model, 10., 3600., &mut state)?;
+        let t = tank.parent.water_temperature(&state).ok_or("No temp")?;
+        let c = WATER_DENSITY * 0.2 * WATER_HEAT_CAPACITY;
+        let exp = 10. + (DEFAULT_TANK_SETPOINT - 10.) * (-2. * 3600. / c).exp();
+        assert!((t - exp).abs() < 1e-6, "t = {}, exp = {}", t, exp);
+        assert!(t < DEFAULT_TANK_SETPOINT);
+        Ok(())
+    }
+
+    #[test]
+    fn test_draw_and_heater() -> Result<(), String> {
+        let mut tank = WaterTank::new("the tank", 0.2);
+        tank.set_heater_power(3000.);
+        let (model, tank, mut state) = get_model(tank)?;
+
+        // A small draw: the heater keeps the setpoint
+        tank.parent.set_draw_flow_rate(&mut state, 0.00001)?;
+        tank.march(&model, 10., 600., &mut state)?;
+        let t = tank.parent.water_temperature(&state).ok_or("No temp")?;
+        assert!((t - DEFAULT_TANK_SETPOINT).abs() < 1e-6, "t = {}", t);
+        let heater = tank
+            .parent
+            .heater_power_consumption(&state)
+            .ok_or("No heater")?;
+        assert!(heater > 0. && heater < 3000., "heater = {}", heater);
+
+        // A large draw: the heater cannot keep up
+        tank.parent.set_draw_flow_rate(&mut state, 0.001)?;
+        tank.march(&model, 10., 600., &mut state)?;
+        let t = tank.parent.water_temperature(&state).ok_or("No temp")?;
+        assert!(t < DEFAULT_TANK_SETPOINT, "t = {}", t);
+        let heater = tank
+            .parent
+            .heater_power_consumption(&state)
+            .ok_or("No heater")?;
+        assert!((heater - 3000.).abs() < 1e-9, "heater = {}", heater);
+        Ok(())
+    }
+
+    #[test]
+    fn test_solar_gains() -> Result<(), String> {
+        let tank = WaterTank::new("the tank", 0.2);
+        let (model, tank, mut state) = get_model(tank)?;
+        let collector = &tank.collectors[0];
+
+        // Sunny: the tank heats up
+        collector
+            .parent
+            .set_incident_solar_irradiance(&mut state, 800.)?;
+        tank.march(&model, 20., 600., &mut state)?;
+        let q = collector.parent.heat_gain(&state).ok_or("No heat gain")?;
+        let exp = 2.
+            * (DEFAULT_OPTICAL_EFFICIENCY * 800.
+                - DEFAULT_COLLECTOR_HEAT_LOSS_COEFFICIENT * (DEFAULT_TANK_SETPOINT - 20.));
+        assert!((q - exp).abs() < 1e-6, "q = {}, exp = {}", q, exp);
+        let t = tank.parent.water_temperature(&state).ok_or("No temp")?;
+        assert!(t > DEFAULT_TANK_SETPOINT, "t = {}", t);
+
+        // Cloudy: the collector does not cool the tank
+        collector
+            .parent
+            .set_incident_solar_irradiance(&mut state, 50.)?;
+        tank.march(&model, 20., 600., &mut state)?;
+        let q = collector.parent.heat_gain(&state).ok_or("No heat gain")?;
+        assert!(q.abs() < 1e-9, "q = {}", q);
+        Ok(())
+    }
+}
@@ -66,6 +66,9 @@ pub mod convection;
 /// Definitions for the thermal properties of gases.
 pub mod gas;
 
+/// The apparent heat capacity of Phase Change Materials.
+pub mod phase_change;
+
 /// Glazing layer abstracted to only their optical properties.
 pub mod glazing;
 
@@ -78,4 +81,15 @@ pub mod surface;
 /// For calculating the temperatures within Spaces.
 pub mod zone;
 
+/// A two-dimensional solver for the junctions of Surfaces, which
+/// calculates their equivalent linear thermal transmittance.
+pub mod thermal_bridge_2d;
+
+/// Long-wave radiant exchange between the surfaces that face each Space,
+/// based on view factors calculated by ray-tracing.
+pub mod radiant_exchange;
+
+mod electric_equipment;
+mod ground;
+mod hot_water;
 mod luminaire;
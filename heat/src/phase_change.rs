@@ -0,0 +1,146 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use crate::Float;
+use polynomial::Polynomial;
+
+/// The apparent specific heat capacity of a Phase Change Material,
+/// derived from the enthalpy curve of a [`model::substance::normal::PhaseChange`].
+///
+/// Within the phase change range, the specific heat capacity is the derivative
+/// of the enthalpy, $`c_p = dh/dT`$. It is never smaller than the (sensible)
+/// `specific_heat_capacity` of the substance, which is used outside of the range.
+/// This keeps the numerical stability of the discretization, which is calculated
+/// with the sensible heat capacity.
+#[derive(Debug, Clone)]
+pub struct PhaseChange {
+    /// The derivative of the enthalpy curve, in J/kg.K
+    enthalpy_derivative: Polynomial,
+
+    /// The temperature at which the phase change starts, in C
+    min_temperature: Float,
+
+    /// The temperature at which the phase change ends, in C
+    max_temperature: Float,
+
+    /// The specific heat capacity outside of the phase change range, in J/kg.K
+    specific_heat_capacity: Float,
+}
+
+impl PhaseChange {
+    /// Creates a new `PhaseChange` from its specification and the (sensible)
+    /// specific heat capacity of the substance
+    pub fn new(
+        spec: &model::substance::normal::PhaseChange,
+        specific_heat_capacity: Float,
+    ) -> Result<Self, String> {
+        let mut enthalpy = Polynomial::new();
+        if spec.enthalpy.len() > enthalpy.coefficients.len() {
+            return Err(format!(
+                "The enthalpy of a PhaseChange can have a maximum of {} coefficients... found {}",
+                enthalpy.coefficients.len(),
+                spec.enthalpy.len()
+            ));
+        }
+        if spec.min_temperature > spec.max_temperature {
+            return Err(format!(
+                "The min_temperature of a PhaseChange ({} C) is greater than its max_temperature ({} C)",
+                spec.min_temperature, spec.max_temperature
+            ));
+        }
+        for v in spec.enthalpy.iter() {
+            enthalpy.push(*v);
+        }
+        Ok(Self {
+            enthalpy_derivative: enthalpy.derivative(),
+            min_temperature: spec.min_temperature,
+            max_temperature: spec.max_temperature,
+            specific_heat_capacity,
+        })
+    }
+
+    /// The apparent specific heat capacity (in J/kg.K) at a certain temperature (in C)
+    pub fn specific_heat_capacity(&self, temperature: Float) -> Float {
+        if temperature < self.min_temperature || temperature > self.max_temperature {
+            return self.specific_heat_capacity;
+        }
+        self.enthalpy_derivative
+            .eval(temperature)
+            .max(self.specific_heat_capacity)
+    }
+
+    /// The heat capacity (in J/kg.K) that is added to the sensible one at
+    /// a certain temperature (in C)
+    pub fn latent_heat_capacity(&self, temperature: Float) -> Float {
+        self.specific_heat_capacity(temperature) - self.specific_heat_capacity
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use validate::assert_close;
+
+    fn spec(
+        enthalpy: Vec<Float>,
+        min_temperature: Float,
+        max_temperature: Float,
+    ) -> model::substance::normal::PhaseChange {
+        let mut spec = model::substance::normal::PhaseChange::new(min_temperature, max_temperature);
+        spec.enthalpy = enthalpy;
+        spec
+    }
+
+    #[test]
+    fn test_specific_heat_capacity() -> Result<(), String> {
+        // h = -110000 + 5000 T + 100 T^2 ==> cp = 5000 + 200 T
+        let pc = PhaseChange::new(&spec(vec![-1.1e5, 5000., 100.], 21., 25.), 2000.)?;
+
+        // Outside of the range
+        assert_close!(pc.specific_heat_capacity(10.), 2000.);
+        assert_close!(pc.specific_heat_capacity(30.), 2000.);
+        assert_close!(pc.latent_heat_capacity(30.), 0.);
+
+        // Within the range
+        assert_close!(pc.specific_heat_capacity(22.), 5000. + 200. * 22.);
+        assert_close!(pc.latent_heat_capacity(22.), 3000. + 200. * 22.);
+        Ok(())
+    }
+
+    #[test]
+    fn test_never_below_sensible() -> Result<(), String> {
+        // cp = -100 within the range
+        let pc = PhaseChange::new(&spec(vec![0., -100.], 0., 10.), 900.)?;
+        assert_close!(pc.specific_heat_capacity(5.), 900.);
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors() {
+        let too_many = spec(vec![0.; 13], 0., 10.);
+        assert!(PhaseChange::new(&too_many, 900.).is_err());
+
+        let inverted = spec(vec![0., 1.], 10., 0.);
+        assert!(PhaseChange::new(&inverted, 900.).is_err());
+    }
+}
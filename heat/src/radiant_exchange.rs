@@ -0,0 +1,589 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::surface::{ThermalFenestration, ThermalSurface, ThermalSurfaceData};
+use crate::Float;
+use geometry::{Point3D, Ray3D, Triangulation3D, Vector3D};
+use model::{Model, SurfaceTrait};
+use rendering::primitive_samplers::sample_triangle_surface;
+use rendering::rand::{get_rng, Rng};
+use rendering::samplers::{local_to_world, sample_cosine_weighted_horizontal_hemisphere};
+use rendering::{Scene, SceneElement, SimpleModelReader, Wavelengths};
+
+/// Offset for the starting point of the rays.
+const DELTA: Float = 0.001;
+
+/// A side of a surface or fenestration that faces a [`ThermalZone`](crate::zone::ThermalZone)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InteriorSide {
+    /// Whether this is the side of a fenestration or a surface
+    pub fenestration: bool,
+
+    /// The index of the surface or fenestration in the
+    /// [`ThermalModel`](crate::heat_model::ThermalModel)
+    pub index: usize,
+
+    /// Whether this is the front or the back side
+    pub front: bool,
+}
+
+/// The sides of the surfaces and fenestrations that face a
+/// [`ThermalZone`](crate::zone::ThermalZone), which exchange long-wave
+/// radiation with each other.
+#[derive(Debug, Clone, Default)]
+pub struct Enclosure {
+    /// The sides facing the zone
+    pub sides: Vec<InteriorSide>,
+
+    /// The area of each side
+    pub areas: Vec<Float>,
+
+    /// The emissivity of each side
+    pub emissivities: Vec<Float>,
+
+    /// The view factor between each pair of sides (i.e., `view_factors[i][j]`
+    /// is the fraction of the radiation leaving side `i` that reaches side `j`)
+    pub view_factors: Vec<Vec<Float>>,
+}
+
+/// Adds the sides of `surfaces` that face a zone to an [`Enclosure`]
+fn add_facing_sides<T: SurfaceTrait + Send + Sync>(
+    enclosure: &mut Enclosure,
+    zone_index: usize,
+    surfaces: &[ThermalSurfaceData<T>],
+    fenestration: bool,
+) {
+    for (index, s) in surfaces.iter().enumerate() {
+        for (front, space_index, emissivity) in [
+            (true, s.front_space_index, s.front_emissivity),
+            (false, s.back_space_index, s.back_emissivity),
+        ] {
+            if space_index == Some(zone_index) {
+                enclosure.sides.push(InteriorSide {
+                    fenestration,
+                    index,
+                    front,
+                });
+                enclosure.areas.push(s.area);
+                enclosure.emissivities.push(emissivity);
+            }
+        }
+    }
+}
+
+/// Inverts a square matrix through Gauss-Jordan elimination with partial pivoting
+fn invert(mut m: Vec<Vec<Float>>) -> Result<Vec<Vec<Float>>, String> {
+    let n = m.len();
+    let mut inv: Vec<Vec<Float>> = (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1. } else { 0. }).collect())
+        .collect();
+    for col in 0..n {
+        let pivot = (col..n)
+            .max_by(|a, b| m[*a][col].abs().total_cmp(&m[*b][col].abs()))
+            .unwrap_or(col);
+        if m[pivot][col].abs() < 1e-12 {
+            return Err("Could not invert the long-wave radiation exchange matrix".to_string());
+        }
+        m.swap(col, pivot);
+        inv.swap(col, pivot);
+        let p = m[col][col];
+        for j in 0..n {
+            m[col][j] /= p;
+            inv[col][j] /= p;
+        }
+        for row in 0..n {
+            if row == col || m[row][col] == 0. {
+                continue;
+            }
+            let factor = m[row][col];
+            for j in 0..n {
+                m[row][j] -= factor * m[col][j];
+                inv[row][j] -= factor * inv[col][j];
+            }
+        }
+    }
+    Ok(inv)
+}
+
+/// Calculates Hottel's exchange factors ($`\mathscr{F}_{ij}`$) between the
+/// grey and diffuse sides of an enclosure; that is, the fraction of the
+/// radiation emitted by a black body in the place of side `i` that is absorbed
+/// by side `j`, directly or after any number of reflections:
+///
+/// ```math
+/// \mathscr{F} = \epsilon F \left( I - \rho F \right)^{-1} \epsilon
+/// ```
+///
+/// where $`\epsilon`$ and $`\rho = 1 - \epsilon`$ are diagonal matrices with the
+/// emissivities and reflectances of the sides, and $`F`$ contains the view factors.
+/// In a closed enclosure, each row adds up to the emissivity of its side.
+pub fn script_f(
+    view_factors: &[Vec<Float>],
+    emissivities: &[Float],
+) -> Result<Vec<Vec<Float>>, String> {
+    let n = emissivities.len();
+    if view_factors.len() != n || view_factors.iter().any(|row| row.len() != n) {
+        return Err(format!(
+            "Expecting a {}x{} matrix of view factors in order to calculate the exchange factors",
+            n, n
+        ));
+    }
+
+    // I - rho*F
+    let m = (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| {
+                    let identity = if i == j { 1. } else { 0. };
+                    identity - (1. - emissivities[i]) * view_factors[i][j]
+                })
+                .collect()
+        })
+        .collect();
+    let inv = invert(m)?;
+
+    let ret = (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| {
+                    let g: Float = (0..n).map(|k| view_factors[i][k] * inv[k][j]).sum();
+                    emissivities[i] * g * emissivities[j]
+                })
+                .collect()
+        })
+        .collect();
+    Ok(ret)
+}
+
+impl Enclosure {
+    /// Collects the sides of the surfaces and fenestrations that face a zone
+    pub fn new(
+        zone_index: usize,
+        surfaces: &[ThermalSurface],
+        fenestrations: &[ThermalFenestration],
+    ) -> Self {
+        let mut ret = Self::default();
+        add_facing_sides(&mut ret, zone_index, surfaces, false);
+        add_facing_sides(&mut ret, zone_index, fenestrations, true);
+        ret
+    }
+
+    /// Calculates the view factors between the sides of the `Enclosure` by
+    /// sending `n_rays` rays—distributed according to the cosine of their
+    /// angle with the normal—from random points in each of them.
+    ///
+    /// Rays that leave the enclosure (e.g., through an `Opening`) are lost. Then,
+    /// the view factors are made reciprocal ($`A_i F_{ij} = A_j F_{ji}`$) and
+    /// normalized, so the enclosure is closed.
+    ///
+    /// The `triangle_map` contains the element of the model to which each triangle
+    /// of the `scene` belongs (in the order of its acceleration structure), and
+    /// `fenestration_indexes` the index of each of the fenestrations of the model in the
+    /// [`ThermalModel`](crate::heat_model::ThermalModel), if any.
+    #[allow(clippy::too_many_arguments)]
+    pub fn calc_view_factors(
+        &mut self,
+        model: &Model,
+        scene: &Scene,
+        triangle_map: &[(SceneElement, usize)],
+        fenestration_indexes: &[Option<usize>],
+        surfaces: &[ThermalSurface],
+        fenestrations: &[ThermalFenestration],
+        n_rays: usize,
+    ) -> Result<(), String> {
+        let n = self.sides.len();
+        let mut rng = get_rng();
+        let mut node_aux = [0; 32];
+        let mut view_factors = vec![vec![0.; n]; n];
+
+        for (i, side) in self.sides.iter().enumerate() {
+            let polygon = if side.fenestration {
+                fenestrations[side.index].parent.vertices()
+            } else {
+                surfaces[side.index].parent.vertices()
+            };
+            let normal = if side.front {
+                polygon.normal()
+            } else {
+                polygon.normal() * -1.
+            };
+            let t: Triangulation3D = polygon.try_into()?;
+            let triangles = t.get_trilist();
+            let areas: Vec<Float> = triangles.iter().map(|t| t.area()).collect();
+            let total_area: Float = areas.iter().sum();
+            if triangles.is_empty() || total_area <= 0. || n_rays == 0 {
+                continue;
+            }
+            let e1 = normal.get_perpendicular()?;
+            let e2 = normal.cross(e1);
+
+            for _ in 0..n_rays {
+                // Choose a triangle, and a point in it
+                let mut r: Float = rng.gen::<Float>() * total_area;
+                let mut t = 0;
+                while t + 1 < triangles.len() && r > areas[t] {
+                    r -= areas[t];
+                    t += 1;
+                }
+                let origin = sample_triangle_surface(&triangles[t], &mut rng) + normal * DELTA;
+
+                let local = sample_cosine_weighted_horizontal_hemisphere(rng.gen());
+                let (x, y, z) = local_to_world(
+                    e1,
+                    e2,
+                    normal,
+                    Point3D::new(0., 0., 0.),
+                    local.x,
+                    local.y,
+                    local.z,
+                );
+                let direction = Vector3D::new(x, y, z);
+
+                let hit = match scene.cast_ray(Ray3D { origin, direction }, &mut node_aux) {
+                    Some((triangle, _)) => triangle_map[triangle],
+                    None => continue,
+                };
+                let (fenestration, index, hit_normal) = match hit {
+                    (SceneElement::Surface, i) => (false, i, model.surfaces[i].vertices.normal()),
+                    (SceneElement::Fenestration, i) => match fenestration_indexes[i] {
+                        Some(index) => (true, index, model.fenestrations[i].vertices.normal()),
+                        None => continue,
+                    },
+                    (SceneElement::ShadingSurface, _) => continue,
+                };
+                let hit = InteriorSide {
+                    fenestration,
+                    index,
+                    front: direction * hit_normal < 0.,
+                };
+                if let Some(j) = self.sides.iter().position(|s| *s == hit) {
+                    view_factors[i][j] += 1.;
+                }
+            }
+            view_factors[i]
+                .iter_mut()
+                .for_each(|f| *f /= n_rays as Float);
+        }
+
+        // Reciprocity and closure
+        let areas = &self.areas;
+        self.view_factors = (0..n)
+            .map(|i| {
+                let mut row: Vec<Float> = (0..n)
+                    .map(|j| {
+                        if areas[i] > 0. {
+                            (areas[i] * view_factors[i][j] + areas[j] * view_factors[j][i])
+                                / (2. * areas[i])
+                        } else {
+                            0.
+                        }
+                    })
+                    .collect();
+                let total: Float = row.iter().sum();
+                if total > 0. {
+                    row.iter_mut().for_each(|f| *f /= total);
+                }
+                row
+            })
+            .collect();
+        Ok(())
+    }
+}
+
+/// Calculates the long-wave radiant exchange between the sides of the
+/// surfaces and fenestrations that face each zone, by ray-tracing their
+/// view factors (sending `n_rays` from each of them) and calculating their
+/// exchange factors (see [`script_f`]).
+///
+/// Then, it fills the `front_radiant_exchange` and `back_radiant_exchange` of
+/// the surfaces and fenestrations, which are used to calculate the radiant
+/// temperature that each of them sees.
+pub(crate) fn add_radiant_exchange(
+    model: &Model,
+    n_zones: usize,
+    surfaces: &mut [ThermalSurface],
+    fenestrations: &mut [ThermalFenestration],
+    n_rays: usize,
+) -> Result<(), String> {
+    let mut reader = SimpleModelReader::default();
+    let (mut scene, map) = reader.build_scene(model, &Wavelengths::Solar)?;
+    let mapping = scene.build_accelerator();
+    let triangle_map: Vec<(SceneElement, usize)> = mapping.iter().map(|i| map[*i]).collect();
+    let fenestration_indexes: Vec<Option<usize>> = model
+        .fenestrations
+        .iter()
+        .map(|f| {
+            fenestrations
+                .iter()
+                .position(|tf| tf.parent.name() == f.name())
+        })
+        .collect();
+
+    for zone_index in 0..n_zones {
+        let mut enclosure = Enclosure::new(zone_index, surfaces, fenestrations);
+        if enclosure.sides.is_empty() {
+            continue;
+        }
+        enclosure.calc_view_factors(
+            model,
+            &scene,
+            &triangle_map,
+            &fenestration_indexes,
+            surfaces,
+            fenestrations,
+            n_rays,
+        )?;
+        let exchange_factors = script_f(&enclosure.view_factors, &enclosure.emissivities)?;
+
+        // The index of the temperature of each side in the SimulationState
+        let temperature_indexes: Vec<usize> = enclosure
+            .sides
+            .iter()
+            // The inherent methods of these objects return an Option, so
+            // the ones in SurfaceTrait are called explicitly
+            .map(|side| match (side.fenestration, side.front) {
+                (false, true) => {
+                    SurfaceTrait::first_node_temperature_index(&*surfaces[side.index].parent)
+                }
+                (false, false) => {
+                    SurfaceTrait::last_node_temperature_index(&*surfaces[side.index].parent)
+                }
+                (true, true) => {
+                    SurfaceTrait::first_node_temperature_index(&*fenestrations[side.index].parent)
+                }
+                (true, false) => {
+                    SurfaceTrait::last_node_temperature_index(&*fenestrations[side.index].parent)
+                }
+            })
+            .collect();
+
+        for (i, side) in enclosure.sides.iter().enumerate() {
+            let emissivity = enclosure.emissivities[i];
+            if emissivity <= 0. {
+                continue;
+            }
+            let exchange: Vec<(usize, Float)> = exchange_factors[i]
+                .iter()
+                .zip(temperature_indexes.iter())
+                .filter(|(f, _)| **f > 0.)
+                .map(|(f, t)| (*t, f / emissivity))
+                .collect();
+            if exchange.is_empty() {
+                continue;
+            }
+            let (front, back) = if side.fenestration {
+                let s = &mut fenestrations[side.index];
+                (&mut s.front_radiant_exchange, &mut s.back_radiant_exchange)
+            } else {
+                let s = &mut surfaces[side.index];
+                (&mut s.front_radiant_exchange, &mut s.back_radiant_exchange)
+            };
+            if side.front {
+                *front = exchange;
+            } else {
+                *back = exchange;
+            }
+        }
+    }
+    Ok(())
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use crate::heat_model::ThermalModel;
+    use communication::{MetaOptions, SimulationModel};
+    use geometry::{Loop3D, Polygon3D};
+    use model::{
+        substance::Normal, Boundary, Construction, Material, Space, Surface, ThermalOptions,
+    };
+    use validate::assert_close;
+
+    const META_OPTIONS: MetaOptions = MetaOptions {
+        latitude: 0.,
+        longitude: 0.,
+        standard_meridian: 0.,
+        elevation: 0.0,
+        daylight_saving: None,
+    };
+
+    /// A cube of 1m, whose faces point outwards
+    fn cube(emissivity: Float, n_rays: usize) -> Result<Model, String> {
+        let mut model = Model::default();
+        let mut space = Space::new("Box");
+        space.set_volume(1.);
+        model.add_space(space);
+
+        let mut concrete = Normal::new("concrete");
+        concrete
+            .set_density(1700.)
+            .set_specific_heat_capacity(800.)
+            .set_thermal_conductivity(0.816)
+            .set_front_thermal_absorbtance(emissivity)
+            .set_back_thermal_absorbtance(emissivity);
+        model.add_substance(concrete.wrap());
+        model.add_material(Material::new("concrete wall", "concrete", 0.1));
+        let mut construction = Construction::new("wall");
+        construction.materials.push("concrete wall".to_string());
+        model.add_construction(construction);
+
+        let faces = [
+            (
+                "bottom",
+                [(0., 0., 0.), (0., 1., 0.), (1., 1., 0.), (1., 0., 0.)],
+            ),
+            (
+                "top",
+                [(0., 0., 1.), (1., 0., 1.), (1., 1., 1.), (0., 1., 1.)],
+            ),
+            (
+                "south",
+                [(0., 0., 0.), (1., 0., 0.), (1., 0., 1.), (0., 0., 1.)],
+            ),
+            (
+                "north",
+                [(0., 1., 0.), (0., 1., 1.), (1., 1., 1.), (1., 1., 0.)],
+            ),
+            (
+                "west",
+                [(0., 0., 0.), (0., 0., 1.), (0., 1., 1.), (0., 1., 0.)],
+            ),
+            (
+                "east",
+                [(1., 0., 0.), (1., 1., 0.), (1., 1., 1.), (1., 0., 1.)],
+            ),
+        ];
+        for (name, vertices) in faces {
+            let mut the_loop = Loop3D::new();
+            for (x, y, z) in vertices {
+                the_loop.push(Point3D::new(x, y, z))?;
+            }
+            the_loop.close()?;
+            model.add_surface(Surface::new(
+                name,
+                Polygon3D::new(the_loop)?,
+                "wall",
+                Boundary::Outdoor,
+                Boundary::Space {
+                    space: "Box".to_string(),
+                },
+            ))?;
+        }
+
+        let mut options = ThermalOptions::default();
+        options.set_n_view_factor_rays(n_rays);
+        model.thermal_options = Some(options);
+        Ok(model)
+    }
+
+    #[test]
+    fn test_script_f() -> Result<(), String> {
+        // Two infinite parallel plates
+        let view_factors = vec![vec![0., 1.], vec![1., 0.]];
+        let f = script_f(&view_factors, &[0.8, 0.5])?;
+        let expected = 1. / (1. / 0.8 + 1. / 0.5 - 1.);
+        assert_close!(f[0][1], expected);
+        assert_close!(f[1][0], expected);
+
+        // Black bodies only see each other directly
+        let f = script_f(&view_factors, &[1., 1.])?;
+        assert_close!(f[0][0], 0.);
+        assert_close!(f[0][1], 1.);
+
+        // Rows add up to the emissivity in closed enclosures
+        let view_factors = vec![
+            vec![0., 0.5, 0.5],
+            vec![0.25, 0.5, 0.25],
+            vec![0.25, 0.25, 0.5],
+        ];
+        let emissivities = [0.9, 0.3, 0.6];
+        let f = script_f(&view_factors, &emissivities)?;
+        for (row, e) in f.iter().zip(emissivities.iter()) {
+            assert_close!(row.iter().sum::<Float>(), *e, 1e-9);
+        }
+
+        assert!(script_f(&view_factors, &[0.9, 0.3]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_cube() -> Result<(), String> {
+        let mut model = cube(1., 5000)?;
+        let mut header = model.take_state().ok_or("Could not take state")?;
+        let thermal_model = ThermalModel::new(&META_OPTIONS, (), &model, &mut header, 1)?;
+        let mut state = header.take_values().ok_or("Could not take state")?;
+
+        let enclosure = Enclosure::new(0, &thermal_model.surfaces, &thermal_model.fenestrations);
+        assert_eq!(enclosure.sides.len(), 6);
+        assert!(enclosure.sides.iter().all(|s| !s.front && !s.fenestration));
+
+        for (i, s) in thermal_model.surfaces.iter().enumerate() {
+            // The outside is not an enclosure
+            assert!(s.front_radiant_exchange.is_empty());
+            assert_eq!(s.back_radiant_exchange.len(), 5);
+
+            // Black surfaces: these are the view factors
+            let total: Float = s.back_radiant_exchange.iter().map(|(_, f)| f).sum();
+            assert_close!(total, 1., 1e-9);
+            let opposite =
+                SurfaceTrait::last_node_temperature_index(&*thermal_model.surfaces[i ^ 1].parent);
+            for (index, f) in s.back_radiant_exchange.iter() {
+                let expected = if *index == opposite { 0.1998 } else { 0.2000 };
+                assert_close!(*f, expected, 0.03);
+            }
+        }
+
+        // Same temperature everywhere means the same radiant temperature
+        for s in thermal_model.surfaces.iter() {
+            for (index, _) in s.back_radiant_exchange.iter() {
+                state[*index] = 17.;
+            }
+        }
+        let (_, back_env, ..) =
+            thermal_model.surfaces[0].calc_border_conditions(&state, 10., 25., 0., 0.);
+        assert_close!(back_env.rad_temperature, 17., 1e-6);
+        assert_close!(back_env.air_temperature, 25.);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_no_rays() -> Result<(), String> {
+        // Without n_view_factor_rays, surfaces see the air
+        let mut model = cube(0.9, 10)?;
+        model.thermal_options = None;
+        let mut header = model.take_state().ok_or("Could not take state")?;
+        let thermal_model = ThermalModel::new(&META_OPTIONS, (), &model, &mut header, 1)?;
+        let state = header.take_values().ok_or("Could not take state")?;
+        assert!(thermal_model
+            .surfaces
+            .iter()
+            .all(|s| s.back_radiant_exchange.is_empty()));
+
+        let (_, back_env, ..) =
+            thermal_model.surfaces[0].calc_border_conditions(&state, 10., 25., 0., 0.);
+        assert_close!(back_env.rad_temperature, 25.);
+        Ok(())
+    }
+}
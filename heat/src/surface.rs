@@ -24,9 +24,10 @@ use crate::glazing::Glazing;
 use crate::Float;
 use geometry::Vector3D;
 use matrix::{Matrix, NDiagGenericMatrix, NDiagMatrix};
+use model::hvac::RadiantSurfaceSystem;
 use model::{
-    Boundary, Construction, Fenestration, Model, SimulationStateHeader, Substance, Surface,
-    SurfaceTrait, TerrainClass,
+    Boundary, Construction, ConvectionModel, Fenestration, Model, SimulationStateHeader, Substance,
+    Surface, SurfaceTrait, TerrainClass, ThermalOptions,
 };
 use model::{SimulationState, SiteDetails};
 use std::sync::Arc;
@@ -77,6 +78,20 @@ fn scale(a: &mut [Float], v: Float) {
     a.iter_mut().for_each(|x| *x *= v);
 }
 
+/// Calculates the mean radiant temperature seen by the side of a surface,
+/// weighting the fourth power of the temperature of the sides it exchanges
+/// long-wave radiation with. Returns `None` if there are none.
+fn radiant_temperature(exchange: &[(usize, Float)], state: &SimulationState) -> Option<Float> {
+    if exchange.is_empty() {
+        return None;
+    }
+    let t4: Float = exchange
+        .iter()
+        .map(|(i, f)| f * (state[*i] + 273.15).powi(4))
+        .sum();
+    Some(t4.powf(0.25) - 273.15)
+}
+
 impl ChunkMemory {
     /// Allocates memory for running a simulation of a chunk.
     ///
@@ -342,6 +357,33 @@ pub struct ThermalSurfaceData<T: SurfaceTrait + Send + Sync> {
     /// this allows setting a fixed convection
     /// coefficient
     pub back_hs: Option<Float>,
+
+    /// The [`RadiantSurfaceSystem`]s injecting heat into this surface, and
+    /// the index of the node where they do it
+    pub heat_sources: Vec<(usize, Arc<RadiantSurfaceSystem>)>,
+
+    /// The heat transfer coefficient (in W/K) of the linear thermal
+    /// bridges of this surface, which connect the environments at each
+    /// side of it in parallel with its construction
+    pub thermal_bridges_coefficient: Float,
+
+    /// The index (in the [`SimulationState`]) of the temperature of each of
+    /// the sides with which the front of this surface exchanges long-wave
+    /// radiation, and the fraction of it exchanged with each of them
+    /// (see [`radiant_exchange`](crate::radiant_exchange)). If empty, the front
+    /// of the surface is assumed to see the temperature of the air.
+    pub front_radiant_exchange: Vec<(usize, Float)>,
+
+    /// Same as `front_radiant_exchange`, but for the back side
+    pub back_radiant_exchange: Vec<(usize, Float)>,
+
+    /// The correlation used for calculating the front convection
+    /// coefficient, unless it is fixed through `front_hs`
+    pub front_convection_model: ConvectionModel,
+
+    /// The correlation used for calculating the back convection
+    /// coefficient, unless it is fixed through `back_hs`
+    pub back_convection_model: ConvectionModel,
 }
 
 impl<T: SurfaceTrait + Send + Sync> ThermalSurfaceData<T> {
@@ -536,6 +578,12 @@ impl<T: SurfaceTrait + Send + Sync> ThermalSurfaceData<T> {
             nomass_chunks,
             front_hs,
             back_hs,
+            heat_sources: Vec::new(),
+            thermal_bridges_coefficient: 0.0,
+            front_radiant_exchange: Vec::new(),
+            back_radiant_exchange: Vec::new(),
+            front_convection_model: ConvectionModel::default(),
+            back_convection_model: ConvectionModel::default(),
         })
     }
 
@@ -565,6 +613,47 @@ impl<T: SurfaceTrait + Send + Sync> ThermalSurfaceData<T> {
         }
     }
 
+    /// Sets the surface coefficient of the sides of the surface that
+    /// lead to the `Ground`, unless they have been fixed already. A `None`
+    /// coefficient means that natural convection is used.
+    pub fn set_ground_coupling(&mut self, coefficient: Option<Float>) {
+        if let Boundary::Ground = self.front_boundary {
+            if self.front_hs.is_none() {
+                self.front_hs = coefficient;
+            }
+        }
+        if let Boundary::Ground = self.back_boundary {
+            if self.back_hs.is_none() {
+                self.back_hs = coefficient;
+            }
+        }
+    }
+
+    /// Sets the correlations used for calculating the convection coefficients
+    /// of each side of the surface: the ones given for the surface itself (i.e.,
+    /// `front` and `back`) or, if not given, the interior or exterior ones in
+    /// the [`ThermalOptions`], depending on whether they face the outdoors.
+    pub fn set_convection_models(
+        &mut self,
+        front: Option<ConvectionModel>,
+        back: Option<ConvectionModel>,
+        options: Option<&ThermalOptions>,
+    ) {
+        let choose = |own: Option<ConvectionModel>, boundary: &Boundary| {
+            own.or_else(|| {
+                let options = options?;
+                let model = match boundary {
+                    Boundary::Outdoor => options.exterior_convection_model(),
+                    _ => options.interior_convection_model(),
+                };
+                model.ok().copied()
+            })
+            .unwrap_or_default()
+        };
+        self.front_convection_model = choose(front, &self.front_boundary);
+        self.back_convection_model = choose(back, &self.back_boundary);
+    }
+
     /// Calculates the border conditions
     pub fn calc_border_conditions(
         &self,
@@ -579,6 +668,8 @@ impl<T: SurfaceTrait + Send + Sync> ThermalSurfaceData<T> {
         let ir_back = self.parent.back_infrared_irradiance(state);
 
         let windward = is_windward(wind_direction, self.cos_tilt, self.normal);
+        let front_model = &self.front_convection_model;
+        let back_model = &self.back_convection_model;
 
         // TODO: There is something to do here if we are talking about windows
         let (front_env, mut front_hs) = match &self.front_boundary {
@@ -594,7 +685,7 @@ impl<T: SurfaceTrait + Send + Sync> ThermalSurfaceData<T> {
 
                 let front_hs = match self.front_hs {
                     Some(v) => v,
-                    None => front_env.get_tarp_natural_convection_coefficient(),
+                    None => front_env.get_interior_convection_coefficient(front_model),
                 };
 
                 (front_env, front_hs)
@@ -603,7 +694,8 @@ impl<T: SurfaceTrait + Send + Sync> ThermalSurfaceData<T> {
                 let front_env = ConvectionParams {
                     air_temperature: t_front,
                     air_speed: 0.0,
-                    rad_temperature: t_front,
+                    rad_temperature: radiant_temperature(&self.front_radiant_exchange, state)
+                        .unwrap_or(t_front),
                     surface_temperature: self.parent.front_temperature(state),
                     roughness_index: 1,
                     cos_surface_tilt: self.cos_tilt,
@@ -611,7 +703,7 @@ impl<T: SurfaceTrait + Send + Sync> ThermalSurfaceData<T> {
 
                 let front_hs = match self.front_hs {
                     Some(v) => v,
-                    None => front_env.get_tarp_natural_convection_coefficient(),
+                    None => front_env.get_interior_convection_coefficient(front_model),
                 };
 
                 (front_env, front_hs)
@@ -628,12 +720,30 @@ impl<T: SurfaceTrait + Send + Sync> ThermalSurfaceData<T> {
 
                 let front_hs = match self.front_hs {
                     Some(v) => v,
-                    None => front_env.get_tarp_natural_convection_coefficient(),
+                    None => front_env.get_interior_convection_coefficient(front_model),
+                };
+
+                (front_env, front_hs)
+            }
+            Boundary::Ground => {
+                // No radiant exchange with the soil
+                let surface_temperature = self.parent.front_temperature(state);
+                let front_env = ConvectionParams {
+                    air_temperature: t_front,
+                    air_speed: 0.0,
+                    rad_temperature: surface_temperature,
+                    surface_temperature,
+                    roughness_index: 1,
+                    cos_surface_tilt: self.cos_tilt,
+                };
+
+                let front_hs = match self.front_hs {
+                    Some(v) => v,
+                    None => front_env.get_interior_convection_coefficient(front_model),
                 };
 
                 (front_env, front_hs)
             }
-            Boundary::Ground => unreachable!(),
             Boundary::Outdoor => {
                 let mut front_env = ConvectionParams {
                     air_temperature: t_front,
@@ -647,7 +757,8 @@ impl<T: SurfaceTrait + Send + Sync> ThermalSurfaceData<T> {
 
                 let front_hs = match self.front_hs {
                     Some(v) => v,
-                    None => front_env.get_tarp_convection_coefficient(
+                    None => front_env.get_exterior_convection_coefficient(
+                        front_model,
                         self.area,
                         self.perimeter,
                         windward,
@@ -667,14 +778,15 @@ impl<T: SurfaceTrait + Send + Sync> ThermalSurfaceData<T> {
                 let back_env = ConvectionParams {
                     air_temperature: t_back,
                     air_speed: 0.0,
-                    rad_temperature: t_back, //self.parent.back_temperature(state),//(ir_back/crate::SIGMA).powf(0.25) - 273.15,
+                    rad_temperature: radiant_temperature(&self.back_radiant_exchange, state)
+                        .unwrap_or(t_back),
                     surface_temperature: self.parent.back_temperature(state),
                     roughness_index: 1,
                     cos_surface_tilt: self.cos_tilt,
                 };
                 let back_hs = match self.back_hs {
                     Some(v) => v,
-                    None => back_env.get_tarp_natural_convection_coefficient(),
+                    None => back_env.get_interior_convection_coefficient(back_model),
                 };
 
                 (back_env, back_hs)
@@ -691,12 +803,30 @@ impl<T: SurfaceTrait + Send + Sync> ThermalSurfaceData<T> {
 
                 let back_hs = match self.back_hs {
                     Some(v) => v,
-                    None => back_env.get_tarp_natural_convection_coefficient(),
+                    None => back_env.get_interior_convection_coefficient(back_model),
+                };
+
+                (back_env, back_hs)
+            }
+            Boundary::Ground => {
+                // No radiant exchange with the soil
+                let surface_temperature = self.parent.back_temperature(state);
+                let back_env = ConvectionParams {
+                    air_temperature: t_back,
+                    air_speed: 0.0,
+                    rad_temperature: surface_temperature,
+                    surface_temperature,
+                    roughness_index: 1,
+                    cos_surface_tilt: self.cos_tilt,
+                };
+
+                let back_hs = match self.back_hs {
+                    Some(v) => v,
+                    None => back_env.get_interior_convection_coefficient(back_model),
                 };
 
                 (back_env, back_hs)
             }
-            Boundary::Ground => unreachable!(),
             Boundary::Outdoor => {
                 let surface_temperature = self.parent.back_temperature(state);
                 let back_env = ConvectionParams {
@@ -709,7 +839,8 @@ impl<T: SurfaceTrait + Send + Sync> ThermalSurfaceData<T> {
                 };
                 let back_hs = match self.back_hs {
                     Some(v) => v,
-                    None => back_env.get_tarp_convection_coefficient(
+                    None => back_env.get_exterior_convection_coefficient(
+                        back_model,
                         self.area,
                         self.perimeter,
                         windward,
@@ -782,6 +913,13 @@ impl<T: SurfaceTrait + Send + Sync> ThermalSurfaceData<T> {
         {
             memory.c[i] = *mass;
         }
+        // Phase Change Materials store more heat near their phase change temperature
+        for (node, pcm_mass, pc) in self.discretization.phase_change.iter() {
+            if (ini..fin).contains(node) {
+                let t = global_temperatures.get(*node, 0)?;
+                memory.c[node - ini] += pcm_mass * pc.latent_heat_capacity(t);
+            }
+        }
 
         // ... here we add solar gains
         for (local_i, global_i) in (ini..fin).enumerate() {
@@ -1283,6 +1421,17 @@ impl<T: SurfaceTrait + Send + Sync> ThermalSurfaceData<T> {
             .scale_into(solar_back, &mut memory.solar_radiation_aux)?;
         memory.solar_radiation += &memory.solar_radiation_aux;
 
+        // ... and the heat injected by radiant systems, which is treated
+        // just like absorbed radiation
+        for (node, system) in self.heat_sources.iter() {
+            let power = system
+                .heating_cooling_consumption(state)
+                .ok_or("Could not get power consumption of RadiantSurfaceSystem")?;
+            memory
+                .solar_radiation
+                .add_to_element(*node, 0, power / self.area)?;
+        }
+
         /////////////////////
         // 2nd: Calculate the temperature in all no-mass nodes.
         // Also, the heat flow into
@@ -1496,6 +1645,7 @@ mod testing {
                 0.0,
                 0.0,
                 t_environment,
+                None,
                 dt,
                 &model,
                 &mut state,
@@ -1554,6 +1704,127 @@ mod testing {
         Ok(())
     }
 
+    #[test]
+    fn test_march_radiant_system() -> Result<(), String> {
+        let mut model = Model::default();
+
+        /* SUBSTANCES */
+        let brickwork = add_brickwork(&mut model)?;
+
+        /* MATERIALS */
+        let thickness = 20. / 1000.;
+        let m1 = add_material(&mut model, brickwork, thickness);
+
+        /* CONSTRUCTION: two identical layers, heat goes in between */
+        let mut c = Construction::new("construction".to_string());
+        c.materials.push(m1.name().clone());
+        c.materials.push(m1.name().clone());
+        let c = model.add_construction(c);
+
+        /* GEOMETRY */
+        let mut the_loop = Loop3D::new();
+        let l = 1. as Float;
+        the_loop.push(Point3D::new(-l, -l, 0.))?;
+        the_loop.push(Point3D::new(l, -l, 0.))?;
+        the_loop.push(Point3D::new(l, l, 0.))?;
+        the_loop.push(Point3D::new(-l, l, 0.))?;
+        the_loop.close()?;
+        let p = Polygon3D::new(the_loop)?;
+
+        /* SURFACE */
+        let h = 10.0;
+        let mut s = Surface::new(
+            "Surface 1",
+            p,
+            c.name(),
+            Boundary::Outdoor,
+            Boundary::Outdoor,
+        );
+        s.set_precalculated_back_convection_coef(h);
+        s.set_precalculated_front_convection_coef(h);
+        let surface = model.add_surface(s)?;
+
+        /* RADIANT SYSTEM */
+        let power = 200.;
+        let mut system = RadiantSurfaceSystem::new("floor heating");
+        system.set_target_surface("Surface 1").set_source_layer(0);
+        let system = match model.add_hvac(system.wrap())? {
+            model::HVAC::RadiantSurfaceSystem(s) => s,
+            _ => unreachable!(),
+        };
+
+        let main_dt = 300.0;
+        let max_dx = m1.thickness / 2.0;
+        let min_dt = 1.0;
+        let d = Discretization::new(&c, &model, main_dt, max_dx, min_dt, 1., 0.)?;
+        let dt = main_dt / d.tstep_subdivision as Float;
+        let source_node = d.interface_node(0)?;
+        let n_nodes = d.segments.len();
+        let normal = geometry::Vector3D::new(0., 0., 1.);
+        let perimeter = 8. * l;
+        let mut state_header = model.take_state().ok_or("Could not take state")?;
+        let mut ts = ThermalSurface::new(
+            &mut state_header,
+            &model,
+            &None,
+            0,
+            &surface,
+            surface.area(),
+            perimeter,
+            10.,
+            normal,
+            &c,
+            d,
+        )?;
+        ts.heat_sources.push((source_node, Arc::clone(&system)));
+
+        let mut state = state_header.take_values().ok_or("Could not take values")?;
+        system.set_heating_cooling_consumption(&mut state, power)?;
+
+        let t_environment = 10.;
+        let mut memory = ts.allocate_memory();
+        ts.parent
+            .get_node_temperatures(&state, &mut memory.temperatures)?;
+        let surfaces = vec![ts];
+        let mut alloc = vec![memory];
+
+        // March for a couple of days
+        let mut time = 0.0;
+        while time < 48. * 3600. {
+            crate::heat_model::iterate_surfaces(
+                &surfaces,
+                &mut alloc,
+                0.0,
+                0.0,
+                t_environment,
+                None,
+                dt,
+                &model,
+                &mut state,
+            )?;
+            time += dt;
+        }
+
+        // At steady state, half the heat leaves through each side
+        let q = power / surface.area() / 2.;
+        let t_surface = t_environment + q / h;
+        let t_source = t_surface + q * thickness / 0.816;
+
+        let front = surface.front_temperature(&state);
+        let back = surface.back_temperature(&state);
+        assert!((front - t_surface).abs() < 0.05, "front = {}", front);
+        assert!((back - t_surface).abs() < 0.05, "back = {}", back);
+
+        let mut temperatures = Matrix::new(0.0, n_nodes, 1);
+        surfaces[0]
+            .parent
+            .get_node_temperatures(&state, &mut temperatures)?;
+        let found = temperatures.get(source_node, 0)?;
+        assert!((found - t_source).abs() < 0.05, "source = {}", found);
+
+        Ok(())
+    }
+
     #[test]
     fn test_march_massive_2() -> Result<(), String> {
         let mut model = Model::default();
@@ -1634,7 +1905,7 @@ mod testing {
         let mut final_qback: Float = 123123123.;
         while change.abs() > 1E-10 {
             crate::heat_model::iterate_surfaces(
-                &surfaces, &mut alloc, 0.0, 0.0, 10.0, dt, &model, &mut state,
+                &surfaces, &mut alloc, 0.0, 0.0, 10.0, None, dt, &model, &mut state,
             )?;
 
             let q_front = surface
@@ -1675,6 +1946,120 @@ mod testing {
         Ok(())
     }
 
+    #[test]
+    fn test_march_ground() -> Result<(), String> {
+        let mut model = Model::default();
+
+        /* SUBSTANCES */
+        let brickwork = add_brickwork(&mut model)?;
+
+        /* MATERIALS */
+        let m1 = add_material(&mut model, brickwork, 20. / 1000.);
+
+        /* CONSTRUCTION */
+        let mut c = Construction::new("construction".to_string());
+        c.materials.push(m1.name().clone());
+        let c = model.add_construction(c);
+
+        /* GEOMETRY */
+        let mut the_loop = Loop3D::new();
+        let l = 1. as Float;
+        the_loop.push(Point3D::new(-l, -l, 0.))?;
+        the_loop.push(Point3D::new(l, -l, 0.))?;
+        the_loop.push(Point3D::new(l, l, 0.))?;
+        the_loop.push(Point3D::new(-l, l, 0.))?;
+        the_loop.close()?;
+        let p = Polygon3D::new(the_loop)?;
+
+        /* SURFACE */
+        let back = Boundary::AmbientTemperature { temperature: 20.0 };
+        let mut s = Surface::new("Slab", p, c.name(), Boundary::Ground, back.clone());
+        s.set_precalculated_back_convection_coef(10.0);
+        let surface = model.add_surface(s)?;
+
+        let main_dt = 300.0;
+        let max_dx = m1.thickness / 2.0;
+        let min_dt = 1.0;
+        let d = Discretization::new(&c, &model, main_dt, max_dx, min_dt, 1., 0.)?;
+        let dt = main_dt / d.tstep_subdivision as Float;
+        let normal = geometry::Vector3D::new(0., 0., -1.);
+        let perimeter = 8. * l;
+        let mut state_header = SimulationStateHeader::new();
+        let mut ts = ThermalSurface::new(
+            &mut state_header,
+            &model,
+            &None,
+            0,
+            &surface,
+            surface.area(),
+            perimeter,
+            0.,
+            normal,
+            &c,
+            d,
+        )?;
+        ts.set_front_boundary(Boundary::Ground, &model);
+        ts.set_back_boundary(back, &model);
+        // A soil layer with a conductance of 3 W/m2K
+        ts.set_ground_coupling(Some(3.));
+        assert_eq!(ts.front_hs, Some(3.));
+        assert_eq!(ts.back_hs, Some(10.));
+
+        let mut state = state_header.take_values().ok_or("Could not take values")?;
+
+        let memory = ts.allocate_memory();
+        let surfaces = vec![ts];
+        let mut alloc = vec![memory];
+
+        // March until q converges
+        let t_ground = 10.;
+        let mut change: Float = 99.0;
+        let mut counter: usize = 0;
+        let mut previous_q: Float = -125.0;
+        while change.abs() > 1E-10 {
+            crate::heat_model::iterate_surfaces(
+                &surfaces,
+                &mut alloc,
+                0.0,
+                0.0,
+                0.0,
+                Some(t_ground),
+                dt,
+                &model,
+                &mut state,
+            )?;
+
+            let q_front = surface
+                .front_convective_heat_flow(&state)
+                .ok_or("front_convective_heat_flow")?;
+            change = (q_front - previous_q).abs();
+            previous_q = q_front;
+
+            counter += 1;
+            if counter > 99999 {
+                panic!("Exceded number of iterations")
+            }
+        }
+
+        // Heat flows from the space into the ground...
+        let ts_front = surface.front_temperature(&state);
+        let ts_back = surface.back_temperature(&state);
+        assert!(t_ground < ts_front && ts_front < ts_back && ts_back < 20.);
+
+        // ... and everything conducted through the slab goes through the
+        // soil layer (i.e., there is no radiant exchange with the ground)
+        let q_conduction = (ts_back - ts_front) * 0.816 / m1.thickness;
+        assert!(
+            (previous_q - q_conduction).abs() < 1e-3,
+            "q_front = {} | q_conduction = {}",
+            previous_q,
+            q_conduction
+        );
+        assert!((previous_q - 3. * (ts_front - t_ground)).abs() < 1e-3);
+
+        Ok(())
+    }
+
     #[test]
     fn test_march_nomass() -> Result<(), String> {
         let mut model = Model::default();
@@ -1743,7 +2128,7 @@ mod testing {
         let mut alloc = vec![memory];
 
         crate::heat_model::iterate_surfaces(
-            &surfaces, &mut alloc, 0.0, 0.0, 10.0, dt, &model, &mut state,
+            &surfaces, &mut alloc, 0.0, 0.0, 10.0, None, dt, &model, &mut state,
         )?;
 
         let q_in = surface
@@ -1867,7 +2252,7 @@ mod testing {
         // q_out = -(30-10)/R
 
         crate::heat_model::iterate_surfaces(
-            &surfaces, &mut alloc, 0.0, 0.0, 10.0, dt, &model, &mut state,
+            &surfaces, &mut alloc, 0.0, 0.0, 10.0, None, dt, &model, &mut state,
         )?;
 
         let q_front = surface
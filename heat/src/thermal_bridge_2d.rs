@@ -0,0 +1,775 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::ground::ground_temperature;
+use crate::heat_model::get_boundary_temperature;
+use crate::Float;
+use calendar::Date;
+use communication::{ErrorHandling, MetaOptions, SimulationModel};
+use model::{Boundary, Model, SimulationState, SimulationStateHeader, Substance, ThermalBridge};
+use std::borrow::Borrow;
+use weather::WeatherTrait;
+
+/// The over-relaxation factor used when solving the steady state
+const OMEGA: Float = 1.9;
+
+/// The maximum temperature change (in K, for a temperature difference of 1 K)
+/// between two iterations of the steady state solver at which it is
+/// considered to have converged
+const TOLERANCE: Float = 1e-12;
+
+/// The maximum number of iterations of the steady state solver
+const MAX_ITERATIONS: usize = 100_000;
+
+/// The temperature at which the details start the simulation, in C
+const INITIAL_TEMPERATURE: Float = 20.;
+
+/// A side of the [`Surface`](model::Surface) to which a [`BridgeDetail`]
+/// belongs. Each side leads to the environment given by the `front_boundary`
+/// or `back_boundary` of the `Surface`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetailSide {
+    /// The front of the Surface
+    Front,
+    /// The back of the Surface
+    Back,
+}
+
+/// An edge of the rectangle that contains a [`BridgeDetail`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetailEdge {
+    /// The edge at `x = 0`
+    Left,
+    /// The edge at the largest `x`
+    Right,
+    /// The edge at `y = 0`
+    Bottom,
+    /// The edge at the largest `y`
+    Top,
+}
+
+/// A rectangle of a [`BridgeDetail`] made of a single [`Substance`].
+/// Regions declared later override the ones declared before them.
+#[derive(Debug, Clone)]
+pub struct DetailRegion {
+    /// The name of the [`Substance`] in the model. It needs to be a `Normal`
+    /// one, with thermal conductivity, density and specific heat capacity.
+    pub substance: String,
+
+    /// The minimum and maximum `x` coordinates of the region, in m
+    pub x: (Float, Float),
+
+    /// The minimum and maximum `y` coordinates of the region, in m
+    pub y: (Float, Float),
+}
+
+/// A segment of the edge of a [`BridgeDetail`] in contact with the
+/// environment at one side of the `Surface`. Edges with no boundaries
+/// are adiabatic.
+#[derive(Debug, Clone)]
+pub struct DetailBoundary {
+    /// The environment at this boundary
+    pub side: DetailSide,
+
+    /// The edge in which the boundary is
+    pub edge: DetailEdge,
+
+    /// The start and end of the segment, in m, measured along the edge
+    /// (i.e., `y` for the `Left` and `Right` edges, `x` for the others)
+    pub range: (Float, Float),
+
+    /// The surface resistance of the boundary, in $`m^2 K/W`$ (e.g., [`R_SI`](model::R_SI))
+    pub surface_resistance: Float,
+}
+
+/// A flanking element of a [`BridgeDetail`]; i.e., a part of the detail that
+/// would be accounted for by the one-dimensional heat transfer through a
+/// [`Construction`](model::Construction).
+#[derive(Debug, Clone)]
+pub struct FlankingElement {
+    /// The name of the `Construction`
+    pub construction: String,
+
+    /// The length of the flanking element within the detail, in m
+    pub length: Float,
+}
+
+/// A two-dimensional section through a junction (e.g., a slab edge or a
+/// wall-to-floor connection) of a [`Surface`](model::Surface).
+///
+/// The detail is meshed into square cells of size `cell_size`, whose
+/// properties are given by the [`DetailRegion`] that contains their centre;
+/// cells outside every region are voids. Following ISO 10211, the linear
+/// thermal transmittance of the junction is
+///
+/// ```math
+/// \psi = L_{2D} - \sum_j U_j l_j
+/// ```
+///
+/// where $`L_{2D}`$ is the steady state heat flow through the detail per
+/// unit temperature difference and per meter of junction, and $`U_j`$ and
+/// $`l_j`$ are the U-value and length of each [`FlankingElement`].
+#[derive(Debug, Clone)]
+pub struct BridgeDetail {
+    /// The name of the [`Surface`](model::Surface) to which the
+    /// junction belongs
+    pub surface: String,
+
+    /// The length of the junction, in m
+    pub length: Float,
+
+    /// The size of the cells, in m
+    pub cell_size: Float,
+
+    /// The regions of the detail
+    pub regions: Vec<DetailRegion>,
+
+    /// The boundaries of the detail
+    pub boundaries: Vec<DetailBoundary>,
+
+    /// The flanking elements, whose heat flow is already accounted
+    /// for by the `Construction`s of the model
+    pub flanking: Vec<FlankingElement>,
+}
+
+/// The finite-difference network of a [`BridgeDetail`], per meter of junction
+#[derive(Debug, Clone)]
+struct Grid {
+    /// The heat capacity of each cell, in J/m.K (zero for voids)
+    capacity: Vec<Float>,
+
+    /// The neighbours of each cell, and the conductance (in W/m.K)
+    /// connecting them
+    neighbours: Vec<Vec<(usize, Float)>>,
+
+    /// The conductance (in W/m.K) between each cell and the
+    /// environment at the front and back of the surface
+    boundaries: Vec<[Float; 2]>,
+}
+
+impl Grid {
+    /// Meshes a [`BridgeDetail`]
+    fn new(detail: &BridgeDetail, model: &Model) -> Result<Self, String> {
+        let h = detail.cell_size;
+        if h <= 0. {
+            return Err(format!(
+                "The cell_size of the BridgeDetail of Surface '{}' must be positive... found {}",
+                detail.surface, h
+            ));
+        }
+        if detail.regions.is_empty() {
+            return Err(format!(
+                "The BridgeDetail of Surface '{}' has no regions",
+                detail.surface
+            ));
+        }
+
+        let width = detail.regions.iter().fold(0., |a: Float, r| a.max(r.x.1));
+        let height = detail.regions.iter().fold(0., |a: Float, r| a.max(r.y.1));
+        let nx = ((width / h) - 1e-6).ceil().max(1.) as usize;
+        let ny = ((height / h) - 1e-6).ceil().max(1.) as usize;
+        let centre = |n: usize| (n as Float + 0.5) * h;
+
+        // Conductivity and volumetric heat capacity of each cell
+        let mut properties: Vec<Option<(Float, Float)>> = vec![None; nx * ny];
+        for region in detail.regions.iter() {
+            if region.x.0 >= region.x.1 || region.y.0 >= region.y.1 {
+                return Err(format!(
+                    "A region of the BridgeDetail of Surface '{}' has an empty or inverted range",
+                    detail.surface
+                ));
+            }
+            let (k, rho_cp) = match model.get_substance(&region.substance)? {
+                Substance::Normal(s) => {
+                    let k = *s.thermal_conductivity()?;
+                    let rho_cp = s.density()? * s.specific_heat_capacity()?;
+                    (k, rho_cp)
+                }
+                Substance::Gas(_) => {
+                    return Err(format!(
+                        "Substance '{}' in the BridgeDetail of Surface '{}' is a Gas... cavities are not supported",
+                        region.substance, detail.surface
+                    ))
+                }
+            };
+            if k <= 0. || rho_cp <= 0. {
+                return Err(format!(
+                    "Substance '{}' in the BridgeDetail of Surface '{}' needs a positive thermal conductivity, density and specific heat capacity",
+                    region.substance, detail.surface
+                ));
+            }
+            for j in 0..ny {
+                for i in 0..nx {
+                    let (x, y) = (centre(i), centre(j));
+                    if x > region.x.0 && x < region.x.1 && y > region.y.0 && y < region.y.1 {
+                        properties[j * nx + i] = Some((k, rho_cp));
+                    }
+                }
+            }
+        }
+
+        let n = nx * ny;
+        let capacity = properties
+            .iter()
+            .map(|p| p.map(|(_, rho_cp)| rho_cp * h * h).unwrap_or(0.))
+            .collect();
+
+        // Square cells: the faces are as long as the distance between centres
+        let mut neighbours = vec![Vec::new(); n];
+        for j in 0..ny {
+            for i in 0..nx {
+                let this = j * nx + i;
+                let k1 = match properties[this] {
+                    Some((k, _)) => k,
+                    None => continue,
+                };
+                let mut others = Vec::with_capacity(2);
+                if i + 1 < nx {
+                    others.push(this + 1)
+                }
+                if j + 1 < ny {
+                    others.push(this + nx)
+                }
+                for other in others {
+                    if let Some((k2, _)) = properties[other] {
+                        let g = 2. * k1 * k2 / (k1 + k2);
+                        neighbours[this].push((other, g));
+                        neighbours[other].push((this, g));
+                    }
+                }
+            }
+        }
+
+        let mut boundaries = vec![[0.; 2]; n];
+        for b in detail.boundaries.iter() {
+            if b.surface_resistance < 0. {
+                return Err(format!(
+                    "The surface_resistance of a boundary of the BridgeDetail of Surface '{}' cannot be negative",
+                    detail.surface
+                ));
+            }
+            let cells: Vec<(usize, Float)> = match b.edge {
+                DetailEdge::Left => (0..ny).map(|j| (j * nx, centre(j))).collect(),
+                DetailEdge::Right => (0..ny).map(|j| (j * nx + nx - 1, centre(j))).collect(),
+                DetailEdge::Bottom => (0..nx).map(|i| (i, centre(i))).collect(),
+                DetailEdge::Top => (0..nx).map(|i| ((ny - 1) * nx + i, centre(i))).collect(),
+            };
+            let side = match b.side {
+                DetailSide::Front => 0,
+                DetailSide::Back => 1,
+            };
+            for (cell, position) in cells {
+                if position < b.range.0 || position > b.range.1 {
+                    continue;
+                }
+                if let Some((k, _)) = properties[cell] {
+                    boundaries[cell][side] += h / (h / (2. * k) + b.surface_resistance);
+                }
+            }
+        }
+        for side in 0..2 {
+            if boundaries.iter().all(|b| b[side] == 0.) {
+                return Err(format!(
+                    "The BridgeDetail of Surface '{}' needs to be in contact with the environments at both sides of the Surface",
+                    detail.surface
+                ));
+            }
+        }
+
+        Ok(Self {
+            capacity,
+            neighbours,
+            boundaries,
+        })
+    }
+
+    /// The heat entering a cell from its neighbours and
+    /// the environment (the numerator) and the sum of the
+    /// conductances around it (the denominator)
+    fn balance(&self, cell: usize, temperatures: &[Float], t_env: [Float; 2]) -> (Float, Float) {
+        let [g_front, g_back] = self.boundaries[cell];
+        let mut num = g_front * t_env[0] + g_back * t_env[1];
+        let mut den = g_front + g_back;
+        for (other, g) in self.neighbours[cell].iter() {
+            num += g * temperatures[*other];
+            den += g;
+        }
+        (num, den)
+    }
+
+    /// Solves the steady state temperatures of the cells
+    fn steady_state(&self, t_env: [Float; 2]) -> Result<Vec<Float>, String> {
+        let mut temperatures = vec![(t_env[0] + t_env[1]) / 2.; self.capacity.len()];
+        for _ in 0..MAX_ITERATIONS {
+            let mut max_change: Float = 0.;
+            for cell in 0..temperatures.len() {
+                let (num, den) = self.balance(cell, &temperatures, t_env);
+                if den == 0. {
+                    continue;
+                }
+                let change = OMEGA * (num / den - temperatures[cell]);
+                temperatures[cell] += change;
+                max_change = max_change.max(change.abs());
+            }
+            if max_change < TOLERANCE * (t_env[0] - t_env[1]).abs().max(1.) {
+                return Ok(temperatures);
+            }
+        }
+        Err("The steady state of a BridgeDetail did not converge".to_string())
+    }
+
+    /// The largest timestep (in seconds) that keeps the explicit solution stable
+    fn max_timestep(&self) -> Float {
+        (0..self.capacity.len())
+            .filter(|cell| self.capacity[*cell] > 0.)
+            .map(|cell| {
+                let g: Float = self.boundaries[cell].iter().sum::<Float>()
+                    + self.neighbours[cell].iter().map(|(_, g)| g).sum::<Float>();
+                self.capacity[cell] / g
+            })
+            .fold(Float::MAX, |a, b| a.min(b))
+    }
+
+    /// Marches the temperatures of the cells forward by `dt` seconds
+    /// with an explicit scheme
+    fn march(
+        &self,
+        temperatures: &mut [Float],
+        scratch: &mut [Float],
+        t_env: [Float; 2],
+        dt: Float,
+    ) {
+        for cell in 0..temperatures.len() {
+            let (num, den) = self.balance(cell, temperatures, t_env);
+            scratch[cell] = if self.capacity[cell] > 0. {
+                temperatures[cell] + dt / self.capacity[cell] * (num - den * temperatures[cell])
+            } else {
+                temperatures[cell]
+            };
+        }
+        temperatures.copy_from_slice(scratch);
+    }
+
+    /// The heat flow (in W/m) entering the detail from the front environment
+    fn heat_flow(&self, temperatures: &[Float], t_front: Float) -> Float {
+        self.boundaries
+            .iter()
+            .zip(temperatures.iter())
+            .map(|(g, t)| g[0] * (t_front - t))
+            .sum()
+    }
+}
+
+/// A [`BridgeDetail`] ready to be simulated
+#[derive(Debug, Clone)]
+struct ThermalDetail {
+    /// The index of the `Surface` in the model
+    surface: usize,
+
+    /// The length of the junction, in m
+    length: Float,
+
+    /// The mesh
+    grid: Grid,
+
+    /// The linear thermal transmittance of the junction, in W/m.K
+    psi: Float,
+
+    /// The number of explicit steps needed to advance one timestep
+    substeps: usize,
+}
+
+/// The memory needed for simulating a [`ThermalBridgeModel`]
+#[derive(Debug, Clone)]
+pub struct ThermalBridgeModelMemory {
+    /// The temperature of the cells of each detail
+    temperatures: Vec<Vec<Float>>,
+
+    /// Scratch space for the explicit solver
+    scratch: Vec<Float>,
+
+    /// The heat flow (in W) entering each detail from the front
+    /// of its `Surface` during the last timestep
+    pub heat_flows: Vec<Float>,
+}
+
+/// A two-dimensional finite-difference model of the junctions of the
+/// [`Surface`](model::Surface)s of a model.
+///
+/// When created, it calculates the linear thermal transmittance ($`\psi`$) of
+/// each [`BridgeDetail`], which can be fed back into the one-dimensional
+/// [`ThermalModel`](crate::heat_model::ThermalModel) as [`ThermalBridge`]s by
+/// calling [`ThermalBridgeModel::add_thermal_bridges`] before creating it.
+/// When marched, it solves the transient heat transfer through the details,
+/// driven by the environments at each side of their `Surface`s.
+#[derive(Debug, Clone)]
+pub struct ThermalBridgeModel {
+    /// The details
+    details: Vec<ThermalDetail>,
+
+    /// The timestep, in seconds
+    dt: Float,
+
+    /// Whether any of the details lead to the ground
+    has_ground_boundaries: bool,
+}
+
+impl ErrorHandling for ThermalBridgeModel {
+    fn module_name() -> &'static str {
+        "Thermal Bridge Model"
+    }
+}
+
+impl SimulationModel for ThermalBridgeModel {
+    type OutputType = Self;
+    type OptionType = Vec<BridgeDetail>;
+    type AllocType = ThermalBridgeModelMemory;
+
+    fn new<M: Borrow<Model>>(
+        _meta_options: &MetaOptions,
+        options: Vec<BridgeDetail>,
+        model: M,
+        _state: &mut SimulationStateHeader,
+        n: usize,
+    ) -> Result<Self, String> {
+        let model = model.borrow();
+        let dt = 60. * 60. / n as Float;
+
+        let mut details = Vec::with_capacity(options.len());
+        let mut has_ground_boundaries = false;
+        for detail in options.iter() {
+            let surface = match model
+                .surfaces
+                .iter()
+                .position(|s| s.name() == &detail.surface)
+            {
+                Some(i) => i,
+                None => {
+                    return Self::user_error(format!(
+                        "A BridgeDetail refers to Surface '{}', which does not exist",
+                        detail.surface
+                    ))
+                }
+            };
+            let boundaries = [
+                &model.surfaces[surface].front_boundary,
+                &model.surfaces[surface].back_boundary,
+            ];
+            if boundaries.iter().any(|b| matches!(b, Boundary::Adiabatic)) {
+                return Self::user_error(format!(
+                    "Surface '{}' has a BridgeDetail but an Adiabatic boundary",
+                    detail.surface
+                ));
+            }
+            has_ground_boundaries |= boundaries.iter().any(|b| matches!(b, Boundary::Ground));
+
+            let grid = Grid::new(detail, model)?;
+            let temperatures = grid.steady_state([1., 0.])?;
+            let l_2d = grid.heat_flow(&temperatures, 1.);
+            let mut flanking = 0.;
+            for f in detail.flanking.iter() {
+                let construction = model.get_construction(&f.construction)?;
+                flanking += construction.u_value(model)? * f.length;
+            }
+            let substeps = (dt / grid.max_timestep()).ceil().max(1.) as usize;
+
+            details.push(ThermalDetail {
+                surface,
+                length: detail.length,
+                grid,
+                psi: l_2d - flanking,
+                substeps,
+            });
+        }
+
+        Ok(Self {
+            details,
+            dt,
+            has_ground_boundaries,
+        })
+    }
+
+    fn allocate_memory(&self, _state: &SimulationState) -> Result<Self::AllocType, String> {
+        let temperatures: Vec<Vec<Float>> = self
+            .details
+            .iter()
+            .map(|d| vec![INITIAL_TEMPERATURE; d.grid.capacity.len()])
+            .collect();
+        let scratch = vec![0.; temperatures.iter().map(|t| t.len()).max().unwrap_or(0)];
+        Ok(ThermalBridgeModelMemory {
+            heat_flows: vec![0.; temperatures.len()],
+            temperatures,
+            scratch,
+        })
+    }
+
+    fn march<W: WeatherTrait, M: Borrow<Model>>(
+        &self,
+        date: Date,
+        weather: &W,
+        model: M,
+        state: &mut SimulationState,
+        alloc: &mut ThermalBridgeModelMemory,
+    ) -> Result<(), String> {
+        let model = model.borrow();
+        let t_out = weather.get_weather_data(date).dry_bulb_temperature;
+        let t_ground = if self.has_ground_boundaries {
+            Some(ground_temperature(
+                model.site_details.as_ref(),
+                weather,
+                date,
+            )?)
+        } else {
+            None
+        };
+
+        for (i, detail) in self.details.iter().enumerate() {
+            let surface = &model.surfaces[detail.surface];
+            let t_env = [
+                get_boundary_temperature(&surface.front_boundary, t_out, t_ground, model, state)?,
+                get_boundary_temperature(&surface.back_boundary, t_out, t_ground, model, state)?,
+            ];
+            let temperatures = &mut alloc.temperatures[i];
+            let scratch = &mut alloc.scratch[..temperatures.len()];
+            let dt = self.dt / detail.substeps as Float;
+            for _ in 0..detail.substeps {
+                detail.grid.march(temperatures, scratch, t_env, dt);
+            }
+            alloc.heat_flows[i] = detail.length * detail.grid.heat_flow(temperatures, t_env[0]);
+        }
+        Ok(())
+    }
+}
+
+impl ThermalBridgeModel {
+    /// The linear thermal transmittance (in W/m.K) of each detail
+    pub fn psi_values(&self) -> Vec<Float> {
+        self.details.iter().map(|d| d.psi).collect()
+    }
+
+    /// Adds the [`ThermalBridge`]s equivalent to each detail to their
+    /// `Surface`s, so they are considered by the one-dimensional thermal model
+    pub fn add_thermal_bridges(&self, model: &mut Model) -> Result<(), String> {
+        for detail in self.details.iter() {
+            let surface = model.surfaces.get_mut(detail.surface).ok_or_else(|| {
+                "The model does not match the one used to create the ThermalBridgeModel".to_string()
+            })?;
+            let mut s = (**surface).clone();
+            s.thermal_bridges
+                .push(ThermalBridge::new(detail.psi, detail.length));
+            *surface = std::sync::Arc::new(s);
+        }
+        Ok(())
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use geometry::{Loop3D, Point3D, Polygon3D};
+    use model::{substance::Normal, Construction, Material, Surface, R_SE, R_SI};
+    use validate::assert_close;
+    use weather::SyntheticWeather;
+
+    /// A model with a 200mm concrete wall between a room at
+    /// 20C and the outdoors, and some polystyrene
+    fn get_model() -> Result<Model, String> {
+        let mut model = Model::default();
+        let mut concrete = Normal::new("Concrete");
+        concrete
+            .set_thermal_conductivity(1.63)
+            .set_density(2400.)
+            .set_specific_heat_capacity(900.);
+        model.add_substance(concrete.wrap());
+        let mut polystyrene = Normal::new("Polystyrene");
+        polystyrene
+            .set_thermal_conductivity(0.035)
+            .set_density(20.)
+            .set_specific_heat_capacity(1400.);
+        model.add_substance(polystyrene.wrap());
+
+        model.add_material(Material::new("Concrete 200mm", "Concrete", 0.2));
+        model.add_material(Material::new("Polystyrene 100mm", "Polystyrene", 0.1));
+        let mut wall = Construction::new("Wall");
+        wall.materials.push("Concrete 200mm".into());
+        model.add_construction(wall);
+        let mut insulated = Construction::new("Insulated");
+        insulated.materials.push("Polystyrene 100mm".into());
+        model.add_construction(insulated);
+
+        let mut outer = Loop3D::new();
+        outer.push(Point3D::new(0., 0., 0.))?;
+        outer.push(Point3D::new(2., 0., 0.))?;
+        outer.push(Point3D::new(2., 0., 2.))?;
+        outer.push(Point3D::new(0., 0., 2.))?;
+        outer.close()?;
+        let surface = Surface::new(
+            "Wall",
+            Polygon3D::new(outer)?,
+            "Wall",
+            Boundary::AmbientTemperature { temperature: 20. },
+            Boundary::Outdoor,
+        );
+        model.add_surface(surface)?;
+        Ok(model)
+    }
+
+    /// A section of a `thickness` thick layer of `substance`, 1m high,
+    /// with the front at `x = 0` and the back at `x = thickness`
+    fn get_detail(substance: &str, thickness: Float, construction: &str) -> BridgeDetail {
+        let boundary = |side, edge, surface_resistance| DetailBoundary {
+            side,
+            edge,
+            range: (0., 1.),
+            surface_resistance,
+        };
+        BridgeDetail {
+            surface: "Wall".into(),
+            length: 2.,
+            cell_size: 0.02,
+            regions: vec![DetailRegion {
+                substance: substance.into(),
+                x: (0., thickness),
+                y: (0., 1.),
+            }],
+            boundaries: vec![
+                boundary(DetailSide::Front, DetailEdge::Left, R_SI),
+                boundary(DetailSide::Back, DetailEdge::Right, R_SE),
+            ],
+            flanking: vec![FlankingElement {
+                construction: construction.into(),
+                length: 1.,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_homogeneous_wall() -> Result<(), String> {
+        // A wall with no junction has no thermal bridge
+        let model = get_model()?;
+        let mut header = SimulationStateHeader::new();
+        let detail = get_detail("Concrete", 0.2, "Wall");
+        let bridges = ThermalBridgeModel::new(
+            &MetaOptions::default(),
+            vec![detail],
+            &model,
+            &mut header,
+            1,
+        )?;
+        assert_close!(bridges.psi_values()[0], 0.0, 1e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn test_concrete_column() -> Result<(), String> {
+        // A concrete column going through the insulation
+        let mut model = get_model()?;
+        let mut header = SimulationStateHeader::new();
+        let mut detail = get_detail("Polystyrene", 0.1, "Insulated");
+        detail.regions.push(DetailRegion {
+            substance: "Concrete".into(),
+            x: (0., 0.1),
+            y: (0.4, 0.6),
+        });
+        let bridges = ThermalBridgeModel::new(
+            &MetaOptions::default(),
+            vec![detail],
+            &model,
+            &mut header,
+            1,
+        )?;
+        let psi = bridges.psi_values()[0];
+        // A bit more than a 200mm wide strip of bare concrete, as
+        // the heat also spreads sideways
+        let insulated = model.get_construction("Insulated")?.u_value(&model)?;
+        let bare = 1. / (R_SI + 0.1 / 1.63 + R_SE);
+        assert!(psi > 0.2 * (bare - insulated));
+        assert!(psi < 0.25 * (bare - insulated));
+
+        // Fed back into the model
+        bridges.add_thermal_bridges(&mut model)?;
+        let surface = model.get_surface("Wall")?;
+        assert_eq!(surface.thermal_bridges.len(), 1);
+        assert_close!(surface.thermal_bridges[0].psi, psi);
+        assert_close!(surface.thermal_bridges[0].length, 2.);
+        Ok(())
+    }
+
+    #[test]
+    fn test_march() -> Result<(), String> {
+        // Outdoors is at 0C, and the front of the wall at 20C
+        let model = get_model()?;
+        let mut header = SimulationStateHeader::new();
+        let detail = get_detail("Concrete", 0.2, "Wall");
+        let bridges = ThermalBridgeModel::new(
+            &MetaOptions::default(),
+            vec![detail],
+            &model,
+            &mut header,
+            1,
+        )?;
+        let mut state = header.take_values().ok_or("No values")?;
+        let mut memory = bridges.allocate_memory(&state)?;
+        let weather = SyntheticWeather::default();
+        let mut date = Date {
+            month: 1,
+            day: 1,
+            hour: 0.,
+            year: None,
+        };
+        for _ in 0..200 {
+            bridges.march(date, &weather, &model, &mut state, &mut memory)?;
+            date.add_hours(1.);
+        }
+
+        // Steady state: 1m high, 2m long
+        let u = model.get_construction("Wall")?.u_value(&model)?;
+        assert_close!(memory.heat_flows[0], 20. * u * 2., 1e-3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors() -> Result<(), String> {
+        let model = get_model()?;
+        let mut header = SimulationStateHeader::new();
+        let new = |detail: BridgeDetail, header: &mut SimulationStateHeader| {
+            ThermalBridgeModel::new(&MetaOptions::default(), vec![detail], &model, header, 1)
+        };
+
+        // Missing surface
+        let mut detail = get_detail("Concrete", 0.2, "Wall");
+        detail.surface = "Roof".into();
+        assert!(new(detail, &mut header).is_err());
+
+        // No back boundary
+        let mut detail = get_detail("Concrete", 0.2, "Wall");
+        detail.boundaries.pop();
+        assert!(new(detail, &mut header).is_err());
+
+        // Missing substance
+        let detail = get_detail("Steel", 0.2, "Wall");
+        assert!(new(detail, &mut header).is_err());
+        Ok(())
+    }
+}
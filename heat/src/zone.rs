@@ -18,10 +18,71 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 
+use crate::surface::{ThermalFenestration, ThermalSurface, ThermalSurfaceData};
 use crate::Float;
-use model::{SimulationStateElement, SimulationStateHeader, Space};
+use geometry::{Loop3D, Point3D, Polygon3D};
+use model::{SimulationState, SimulationStateElement, SimulationStateHeader, Space, SurfaceTrait};
 use std::sync::Arc;
 
+/// Calculates the solid angle (in sr) that a [`Loop3D`] subtends when seen
+/// from a certain point, by adding up the solid angles of the triangles of a fan,
+/// following Van Oosterom and Strackee (1983).
+fn loop_solid_angle(point: Point3D, the_loop: &Loop3D) -> Float {
+    let vertices = the_loop.vertices();
+    if vertices.len() < 3 {
+        return 0.0;
+    }
+    let a = vertices[0] - point;
+    let la = a.length();
+    let mut ret = 0.0;
+    for w in vertices[1..].windows(2) {
+        let b = w[0] - point;
+        let c = w[1] - point;
+        let (lb, lc) = (b.length(), c.length());
+        let numerator = a * b.cross(c);
+        let denominator = la * lb * lc + (a * b) * lc + (a * c) * lb + (b * c) * la;
+        ret += 2. * numerator.atan2(denominator);
+    }
+    ret.abs()
+}
+
+/// Collects the sides of the surfaces that face a certain zone. Each element
+/// contains the index of the temperature of such side in the [`SimulationState`],
+/// the geometry of the surface and its area.
+fn facing_sides<T: SurfaceTrait + Send + Sync>(
+    zone_index: usize,
+    surfaces: &[ThermalSurfaceData<T>],
+) -> Vec<(usize, &Polygon3D, Float)> {
+    let mut ret = Vec::new();
+    for s in surfaces.iter() {
+        if s.front_space_index == Some(zone_index) {
+            ret.push((
+                s.parent.first_node_temperature_index(),
+                s.parent.vertices(),
+                s.area,
+            ));
+        }
+        if s.back_space_index == Some(zone_index) {
+            ret.push((
+                s.parent.last_node_temperature_index(),
+                s.parent.vertices(),
+                s.area,
+            ));
+        }
+    }
+    ret
+}
+
+/// Calculates the solid angle (in sr) that a [`Polygon3D`] subtends when seen
+/// from a certain point, discounting its holes.
+fn polygon_solid_angle(point: Point3D, polygon: &Polygon3D) -> Result<Float, String> {
+    let mut ret = loop_solid_angle(point, polygon.outer());
+    for i in 0..polygon.n_inner_loops() {
+        ret -= loop_solid_angle(point, polygon.inner(i)?);
+    }
+    Ok(ret.max(0.0))
+}
+
 /// A thermal representation of a [`Space`]
 pub struct ThermalZone {
     /// The `Space` that this [`Thermal Zone`] represents
@@ -29,6 +90,16 @@ pub struct ThermalZone {
 
     /// volume of the zone
     volume: Float,
+
+    /// The view factors between the centre of the zone and the sides of
+    /// the surfaces and fenestrations that face it. Each element contains
+    /// the index of the temperature of such side in the
+    /// [`SimulationState`] and the view factor.
+    pub view_factors: Vec<(usize, Float)>,
+
+    /// The number of identical copies of the `reference_space`
+    /// in the building (i.e., its `multiplier`)
+    pub multiplier: Float,
 }
 
 impl ThermalZone {
@@ -49,12 +120,89 @@ impl ThermalZone {
         )?;
         space.set_dry_bulb_temperature_index(state_index)?;
 
+        let state_index = state.push(
+            SimulationStateElement::SpaceMeanRadiantTemperature(space_index),
+            22.0,
+        )?;
+        space.set_mean_radiant_temperature_index(state_index)?;
+
         Ok(ThermalZone {
             reference_space: Arc::clone(space),
             volume,
+            view_factors: Vec::new(),
+            multiplier: space.multiplier_or(crate::heat_model::MODULE_NAME, 1) as Float,
         })
     }
 
+    /// Calculates the view factors between the centre of the zone and the
+    /// surfaces and fenestrations that face it.
+    ///
+    /// The centre of the zone is the area-weighted average of the centroids of
+    /// such surfaces, and the view factor of each of them is the fraction of the
+    /// total solid angle it subtends when seen from there. If none of them can
+    /// be seen from the centre (i.e., they are all coplanar with it), the view
+    /// factors are proportional to their areas.
+    pub fn calc_view_factors(
+        &mut self,
+        zone_index: usize,
+        surfaces: &[ThermalSurface],
+        fenestrations: &[ThermalFenestration],
+    ) -> Result<(), String> {
+        // Collect the sides facing this zone
+        let mut sides = facing_sides(zone_index, surfaces);
+        sides.extend(facing_sides(zone_index, fenestrations));
+        let total_area: Float = sides.iter().map(|(_, _, area)| area).sum();
+        if total_area <= 0.0 {
+            self.view_factors = Vec::new();
+            return Ok(());
+        }
+
+        let mut centre = Point3D::new(0., 0., 0.);
+        for (_, polygon, area) in sides.iter() {
+            centre += polygon.outer().centroid()? * (*area / total_area);
+        }
+
+        let mut view_factors = Vec::with_capacity(sides.len());
+        for (index, polygon, _) in sides.iter() {
+            view_factors.push((*index, polygon_solid_angle(centre, polygon)?));
+        }
+        let total: Float = view_factors.iter().map(|(_, omega)| omega).sum();
+        if total > 0.0 {
+            view_factors
+                .iter_mut()
+                .for_each(|(_, omega)| *omega /= total);
+        } else {
+            // All sides are coplanar with the centre (e.g., a zone with a
+            // single surface), so none of them can be seen from there.
+            view_factors = sides
+                .iter()
+                .map(|(index, _, area)| (*index, area / total_area))
+                .collect();
+        }
+        self.view_factors = view_factors;
+        Ok(())
+    }
+
+    /// Calculates the mean radiant temperature (in C) at the centre of the zone,
+    /// based on the temperature of the surfaces around it
+    ///
+    /// ```math
+    /// T_{mrt}^4 = \displaystyle\sum_{i=surf.}{F_i T_i^4}
+    /// ```
+    ///
+    /// Returns `None` if the zone has no surfaces.
+    pub fn mean_radiant_temperature(&self, state: &SimulationState) -> Option<Float> {
+        if self.view_factors.is_empty() {
+            return None;
+        }
+        let t4: Float = self
+            .view_factors
+            .iter()
+            .map(|(i, f)| f * (state[*i] + 273.15).powi(4))
+            .sum();
+        Some(t4.powf(0.25) - 273.15)
+    }
+
     /// Retrieves the heat capacity of the ThermalZone's air
     pub fn mcp(&self, temp: Float) -> Float {
         let air = crate::gas::AIR;
@@ -64,3 +212,65 @@ impl ThermalZone {
         self.volume * air_density * air_specific_heat / 1.
     }
 }
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    /// A square of side `2*half` centred at `(0, 0, z)`
+    fn square(half: Float, z: Float) -> Result<Loop3D, String> {
+        let mut the_loop = Loop3D::new();
+        the_loop.push(Point3D::new(-half, -half, z))?;
+        the_loop.push(Point3D::new(half, -half, z))?;
+        the_loop.push(Point3D::new(half, half, z))?;
+        the_loop.push(Point3D::new(-half, half, z))?;
+        the_loop.close()?;
+        Ok(the_loop)
+    }
+
+    #[test]
+    fn test_solid_angle() -> Result<(), String> {
+        // The face of a cube seen from its centre
+        let face = square(1., 1.)?;
+        let exp = 4. * crate::PI / 6.;
+        let found = loop_solid_angle(Point3D::new(0., 0., 0.), &face);
+        assert!(
+            (found - exp).abs() < 1e-9,
+            "found = {}, exp = {}",
+            found,
+            exp
+        );
+
+        // The same from behind
+        let found = loop_solid_angle(Point3D::new(0., 0., 2.), &face);
+        assert!(
+            (found - exp).abs() < 1e-9,
+            "found = {}, exp = {}",
+            found,
+            exp
+        );
+
+        // From the plane of the loop
+        let found = loop_solid_angle(Point3D::new(5., 0., 1.), &face);
+        assert!(found.abs() < 1e-9, "found = {}", found);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_solid_angle_with_hole() -> Result<(), String> {
+        let point = Point3D::new(0., 0., 0.);
+        let mut polygon = Polygon3D::new(square(1., 1.)?)?;
+        let full = polygon_solid_angle(point, &polygon)?;
+        let hole = square(0.5, 1.)?;
+        let hole_angle = loop_solid_angle(point, &hole);
+        polygon.cut_hole(hole)?;
+        let found = polygon_solid_angle(point, &polygon)?;
+        assert!(
+            (found - (full - hole_angle)).abs() < 1e-9,
+            "found = {}",
+            found
+        );
+        Ok(())
+    }
+}
@@ -93,6 +93,7 @@ const META_OPTIONS: MetaOptions = MetaOptions {
     longitude: 0.,
     standard_meridian: 0.,
     elevation: 0.0,
+    daylight_saving: None,
 };
 
 fn march_with_window() -> Result<(Vec<Float>, Vec<Float>), String> {
@@ -154,6 +155,7 @@ fn march_with_window() -> Result<(Vec<Float>, Vec<Float>), String> {
         day: 1,
         hour: 0.0,
         month: 1,
+        year: None,
     };
 
     // test model
@@ -249,6 +251,7 @@ fn very_simple_march() -> Result<(Vec<Float>, Vec<Float>), String> {
         day: 1,
         hour: 0.0,
         month: 1,
+        year: None,
     };
 
     let n = 1000;
@@ -350,6 +353,7 @@ fn march_with_window_and_luminaire() -> Result<(Vec<Float>, Vec<Float>), String>
         day: 1,
         hour: 0.0,
         month: 1,
+        year: None,
     };
 
     // March:
@@ -453,6 +457,7 @@ fn march_with_window_and_heater() -> Result<(Vec<Float>, Vec<Float>), String> {
         day: 1,
         hour: 0.0,
         month: 1,
+        year: None,
     };
 
     // March:
@@ -572,6 +577,7 @@ fn march_with_window_heater_and_infiltration() -> Result<(Vec<Float>, Vec<Float>
         day: 1,
         hour: 0.0,
         month: 1,
+        year: None,
     };
 
     // March:
@@ -642,6 +648,7 @@ fn march_model(
         month: 1,
         day: 1,
         hour: 0.0,
+        year: None,
     };
     let n = outdoor_temp.len();
     let mut exp = Vec::with_capacity(n);
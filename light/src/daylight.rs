@@ -0,0 +1,535 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::Float;
+use calendar::Date;
+use communication::MetaOptions;
+use geometry::{Point3D, Ray3D, Vector3D};
+use matrix::Matrix;
+use model::Model;
+use rendering::colour_matrix::colour_matrix_to_luminance;
+use rendering::spectral::{SampledSpectrum, EML_PER_MELANOPIC_EDI};
+use rendering::{DCFactory, Scene, Wavelengths};
+use std::path::PathBuf;
+use weather::{CurrentWeather, PerezSky, ReinhartSky, SkyUnits, Solar};
+
+/// The name of the module
+const MODULE_NAME: &str = "Annual Daylight";
+
+/// The illuminance (in lux) that needs to be reached for a sensor to be
+/// considered daylit, when calculating the Daylight Autonomy
+pub const DAYLIGHT_AUTONOMY_THRESHOLD: Float = 300.;
+
+/// The fraction of the occupied hours that a sensor needs to be daylit
+/// for in order to count towards the spatial Daylight Autonomy
+pub const SPATIAL_DAYLIGHT_AUTONOMY_FRACTION: Float = 0.5;
+
+/// The illuminances (in lux) under and over which daylight is not
+/// considered useful, when calculating the Useful Daylight Illuminance
+pub const USEFUL_DAYLIGHT_ILLUMINANCE_RANGE: (Float, Float) = (100., 3000.);
+
+/// The melanopic Equivalent Daylight Illuminance (M-EDI, in lux) that
+/// needs to be reached at a sensor for it to provide circadian stimulus, as
+/// required by WELL v2 (feature L03)
+pub const CIRCADIAN_MELANOPIC_EDI_THRESHOLD: Float = 136.;
+
+/// The hours of the day (local standard time) considered to be occupied
+/// (i.e., from 8:00 to 18:00, as in IES LM-83)
+const OCCUPIED_HOURS: (Float, Float) = (8., 18.);
+
+/// A named set of points at which the illuminance is calculated.
+///
+/// Each sensor is a [`Ray3D`], whose origin is the position of the sensor
+/// and whose direction is the normal of the (virtual) illuminance meter.
+#[derive(Debug, Clone)]
+pub struct SensorGrid {
+    /// The name of the grid
+    pub name: String,
+
+    /// The sensors
+    pub sensors: Vec<Ray3D>,
+}
+
+impl SensorGrid {
+    /// Reads a grid written in Radiance's format—i.e., one sensor per line,
+    /// with the six values `x y z dx dy dz`—from a string
+    pub fn from_pts<S: Into<String>>(name: S, pts: &str) -> Result<Self, String> {
+        let name = name.into();
+        let mut sensors = Vec::new();
+        for (ln, line) in pts.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let values = line
+                .split_ascii_whitespace()
+                .map(|x| x.parse::<Float>())
+                .collect::<Result<Vec<Float>, _>>()
+                .map_err(|e| format!("Sensor grid '{}', line {}: {}", name, ln + 1, e))?;
+            if values.len() != 6 {
+                return Err(format!(
+                    "Sensor grid '{}', line {}: expecting six values (i.e., 'x y z dx dy dz'), found {}",
+                    name,
+                    ln + 1,
+                    values.len()
+                ));
+            }
+            let direction = Vector3D::new(values[3], values[4], values[5]);
+            if direction.length() < 1e-9 {
+                return Err(format!(
+                    "Sensor grid '{}', line {}: the direction of the sensor has zero length",
+                    name,
+                    ln + 1
+                ));
+            }
+            sensors.push(Ray3D {
+                origin: Point3D::new(values[0], values[1], values[2]),
+                direction: direction.get_normalized(),
+            });
+        }
+        if sensors.is_empty() {
+            return Err(format!("Sensor grid '{}' has no sensors", name));
+        }
+        Ok(Self { name, sensors })
+    }
+
+    /// Reads a grid written in Radiance's format from a file. The grid
+    /// is named after the stem of the file
+    pub fn from_file(filename: &str) -> Result<Self, String> {
+        let pts = std::fs::read_to_string(filename)
+            .map_err(|e| format!("Could not read sensor grid file '{}': {}", filename, e))?;
+        let name = std::path::Path::new(filename)
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or(filename);
+        Self::from_pts(name, &pts)
+    }
+}
+
+/// The statistics accumulated by a single sensor over the occupied hours
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SensorStatistics {
+    /// The number of occupied hours evaluated
+    pub occupied_hours: Float,
+
+    /// The number of hours with an illuminance over [`DAYLIGHT_AUTONOMY_THRESHOLD`]
+    pub autonomous_hours: Float,
+
+    /// The number of hours with an illuminance under the lower limit of
+    /// [`USEFUL_DAYLIGHT_ILLUMINANCE_RANGE`]
+    pub fell_short_hours: Float,
+
+    /// The number of hours with an illuminance within [`USEFUL_DAYLIGHT_ILLUMINANCE_RANGE`]
+    pub useful_hours: Float,
+
+    /// The number of hours with an illuminance over the upper limit of
+    /// [`USEFUL_DAYLIGHT_ILLUMINANCE_RANGE`]
+    pub exceeded_hours: Float,
+
+    /// The sum of the melanopic Equivalent Daylight Illuminance of each
+    /// hour (i.e., in lux.h)
+    pub melanopic_edi_hours: Float,
+
+    /// The number of hours with a melanopic Equivalent Daylight Illuminance
+    /// over [`CIRCADIAN_MELANOPIC_EDI_THRESHOLD`]
+    pub circadian_hours: Float,
+}
+
+impl SensorStatistics {
+    /// Accounts for `hours` hours at a certain illuminance and melanopic
+    /// Equivalent Daylight Illuminance, both in lux
+    fn record(&mut self, hours: Float, illuminance: Float, melanopic_edi: Float) {
+        let (lower, upper) = USEFUL_DAYLIGHT_ILLUMINANCE_RANGE;
+        self.occupied_hours += hours;
+        self.melanopic_edi_hours += melanopic_edi * hours;
+        if melanopic_edi >= CIRCADIAN_MELANOPIC_EDI_THRESHOLD {
+            self.circadian_hours += hours;
+        }
+        if illuminance >= DAYLIGHT_AUTONOMY_THRESHOLD {
+            self.autonomous_hours += hours;
+        }
+        if illuminance < lower {
+            self.fell_short_hours += hours;
+        } else if illuminance > upper {
+            self.exceeded_hours += hours;
+        } else {
+            self.useful_hours += hours;
+        }
+    }
+
+    /// Divides a number of hours by the occupied hours
+    fn fraction(&self, hours: Float) -> Float {
+        if self.occupied_hours > 0. {
+            hours / self.occupied_hours
+        } else {
+            0.
+        }
+    }
+
+    /// The Daylight Autonomy: the fraction (from 0 to 1) of the occupied
+    /// hours in which the illuminance is over [`DAYLIGHT_AUTONOMY_THRESHOLD`]
+    pub fn daylight_autonomy(&self) -> Float {
+        self.fraction(self.autonomous_hours)
+    }
+
+    /// The Useful Daylight Illuminance: the fractions (from 0 to 1) of the
+    /// occupied hours in which the illuminance is under, within and over
+    /// [`USEFUL_DAYLIGHT_ILLUMINANCE_RANGE`], respectively
+    pub fn useful_daylight_illuminance(&self) -> (Float, Float, Float) {
+        (
+            self.fraction(self.fell_short_hours),
+            self.fraction(self.useful_hours),
+            self.fraction(self.exceeded_hours),
+        )
+    }
+
+    /// The average melanopic Equivalent Daylight Illuminance (M-EDI, as
+    /// defined in CIE S 026) over the occupied hours, in lux
+    pub fn mean_melanopic_edi(&self) -> Float {
+        self.fraction(self.melanopic_edi_hours)
+    }
+
+    /// The average Equivalent Melanopic Lux (EML, as used in WELL v1)
+    /// over the occupied hours
+    pub fn mean_equivalent_melanopic_lux(&self) -> Float {
+        self.mean_melanopic_edi() * EML_PER_MELANOPIC_EDI
+    }
+
+    /// The fraction (from 0 to 1) of the occupied hours in which the
+    /// melanopic Equivalent Daylight Illuminance is over
+    /// [`CIRCADIAN_MELANOPIC_EDI_THRESHOLD`]
+    pub fn circadian_autonomy(&self) -> Float {
+        self.fraction(self.circadian_hours)
+    }
+}
+
+/// Calculates annual (i.e., climate-based) daylight metrics over a set of
+/// [`SensorGrid`]s: Daylight Autonomy (DA), Useful Daylight Illuminance (UDI),
+/// spatial Daylight Autonomy (sDA) and circadian metrics (i.e., melanopic
+/// Equivalent Daylight Illuminance and Equivalent Melanopic Lux).
+///
+/// The Daylight Coefficients of the sensors are calculated once, when
+/// creating the object, and then multiplied by the Perez sky of each
+/// timestep in order to obtain the illuminance at each sensor.
+///
+/// The melanopic illuminance is derived from the illuminance through the
+/// melanopic Daylight Efficacy Ratio of the daylight (see
+/// [`SampledSpectrum::melanopic_daylight_efficacy_ratio`]). By default, the
+/// daylight is assumed to have the spectrum of D65, but a different one
+/// can be set—e.g., one approximated from a Correlated Colour Temperature
+/// through [`SampledSpectrum::black_body`]—by calling [`Self::set_daylight_spectrum`].
+/// Circadian metrics are usually evaluated on sensors looking
+/// horizontally, at eye level.
+pub struct AnnualDaylight {
+    /// The calculator for solar position and other solar variables
+    solar: Solar,
+
+    /// The MF discretization scheme for the sky.
+    sky_discretization: usize,
+
+    /// The sensor grids
+    grids: Vec<SensorGrid>,
+
+    /// The Daylight Coefficient matrix of each grid, in lux per unit of
+    /// visible sky radiance
+    daylight_coefficients: Vec<Matrix>,
+
+    /// The statistics of each sensor in each grid
+    statistics: Vec<Vec<SensorStatistics>>,
+
+    /// The melanopic Daylight Efficacy Ratio of the daylight
+    melanopic_ratio: Float,
+}
+
+impl AnnualDaylight {
+    /// Creates a new [`AnnualDaylight`], calculating the Daylight
+    /// Coefficients of each sensor in the visible spectrum.
+    ///
+    /// The sky discretization, number of ambient samples and Daylight
+    /// Coefficient cache are taken from the model's `SolarOptions`, if any.
+    ///
+    /// This will trigger ray-tracing processes, so it might be slow.
+    pub fn new(
+        meta_options: &MetaOptions,
+        model: &Model,
+        grids: Vec<SensorGrid>,
+    ) -> Result<Self, String> {
+        if grids.is_empty() {
+            return Err("No sensor grids were given for calculating daylight metrics".into());
+        }
+
+        let (mf, n_ambient_samples, cache_dir) = match &model.solar_options {
+            Some(options) => (
+                options.solar_sky_discretization_or(MODULE_NAME, 1),
+                options.solar_ambient_divitions_or(MODULE_NAME, 300),
+                options
+                    .daylight_coefficients_cache()
+                    .ok()
+                    .map(PathBuf::from),
+            ),
+            None => (1, 300, None),
+        };
+
+        let mut scene = Scene::from_simple_model(model, Wavelengths::Visible)?;
+        scene.build_accelerator();
+
+        let factory = DCFactory {
+            reinhart: ReinhartSky::new(mf),
+            n_ambient_samples,
+            cache_dir,
+            ..DCFactory::default()
+        };
+
+        let n_sensors: usize = grids.iter().map(|g| g.sensors.len()).sum();
+        let progress_bar = utils::ProgressBar::new(
+            "Calculating Daylight Coefficients".to_string(),
+            n_sensors * factory.n_ambient_samples,
+        );
+        let daylight_coefficients = grids
+            .iter()
+            .map(|g| {
+                let dc = factory.calc_dc(&g.sensors, &scene, Some(&progress_bar));
+                colour_matrix_to_luminance(&dc)
+            })
+            .collect();
+        progress_bar.done();
+
+        let latitude = meta_options.latitude;
+        let longitude = -meta_options.longitude;
+        let standard_meridian = -meta_options.standard_meridian;
+
+        Ok(Self {
+            solar: Solar::new(latitude, longitude, standard_meridian),
+            sky_discretization: mf,
+            statistics: grids
+                .iter()
+                .map(|g| vec![SensorStatistics::default(); g.sensors.len()])
+                .collect(),
+            grids,
+            daylight_coefficients,
+            melanopic_ratio: 1.,
+        })
+    }
+
+    /// Sets the spectral distribution of the daylight, used for
+    /// calculating melanopic illuminances. It needs to be set before
+    /// recording any timestep.
+    pub fn set_daylight_spectrum(&mut self, spectrum: &SampledSpectrum) {
+        self.melanopic_ratio = spectrum.melanopic_daylight_efficacy_ratio();
+    }
+
+    /// Accounts for a timestep of `dt` seconds, ending at `date`.
+    /// Timesteps out of the occupied hours are ignored.
+    pub fn record(
+        &mut self,
+        date: Date,
+        dt: Float,
+        weather_data: CurrentWeather,
+    ) -> Result<(), String> {
+        let (start, end) = OCCUPIED_HOURS;
+        if date.hour < start || date.hour >= end {
+            return Ok(());
+        }
+        let sky = PerezSky::gen_sky_vec(
+            self.sky_discretization,
+            &self.solar,
+            date,
+            weather_data,
+            SkyUnits::Visible,
+            0.2,  // albedo
+            true, // add sky
+            true, // add sun
+        )?;
+        let hours = dt / 3600.;
+        for (dc, stats) in self
+            .daylight_coefficients
+            .iter()
+            .zip(self.statistics.iter_mut())
+        {
+            let illuminance = dc.fast_prod(&sky)?;
+            for (i, s) in stats.iter_mut().enumerate() {
+                let lux = illuminance.get(i, 0)?;
+                s.record(hours, lux, lux * self.melanopic_ratio);
+            }
+        }
+        Ok(())
+    }
+
+    /// Gets the statistics of the sensors in the grid at index `grid_index`
+    pub fn statistics(&self, grid_index: usize) -> Option<&[SensorStatistics]> {
+        self.statistics.get(grid_index).map(|s| s.as_slice())
+    }
+
+    /// The spatial Daylight Autonomy of the grid at index `grid_index`: the
+    /// fraction (from 0 to 1) of its sensors whose Daylight Autonomy is at
+    /// least [`SPATIAL_DAYLIGHT_AUTONOMY_FRACTION`]
+    pub fn spatial_daylight_autonomy(&self, grid_index: usize) -> Option<Float> {
+        let stats = self.statistics.get(grid_index)?;
+        let n = stats
+            .iter()
+            .filter(|s| s.daylight_autonomy() >= SPATIAL_DAYLIGHT_AUTONOMY_FRACTION)
+            .count();
+        Some(n as Float / stats.len() as Float)
+    }
+
+    /// Writes the metrics of all the sensors, in CSV format. The
+    /// spatial Daylight Autonomy of each grid is repeated in all its rows.
+    pub fn write_report<T: std::io::Write>(&self, mut out: T) -> std::io::Result<()> {
+        writeln!(
+            out,
+            "Grid,Sensor,X,Y,Z,DA,UDI fell-short,UDI useful,UDI exceeded,Grid sDA,Mean M-EDI,Mean EML,Circadian autonomy"
+        )?;
+        for (grid_index, (grid, stats)) in self.grids.iter().zip(self.statistics.iter()).enumerate()
+        {
+            let sda = self.spatial_daylight_autonomy(grid_index).unwrap_or(0.);
+            for (i, (sensor, s)) in grid.sensors.iter().zip(stats.iter()).enumerate() {
+                let (fell_short, useful, exceeded) = s.useful_daylight_illuminance();
+                writeln!(
+                    out,
+                    "{},{},{:.3},{:.3},{:.3},{:.3},{:.3},{:.3},{:.3},{:.3},{:.1},{:.1},{:.3}",
+                    grid.name,
+                    i,
+                    sensor.origin.x,
+                    sensor.origin.y,
+                    sensor.origin.z,
+                    s.daylight_autonomy(),
+                    fell_short,
+                    useful,
+                    exceeded,
+                    sda,
+                    s.mean_melanopic_edi(),
+                    s.mean_equivalent_melanopic_lux(),
+                    s.circadian_autonomy(),
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn test_from_pts() -> Result<(), String> {
+        let grid = SensorGrid::from_pts("the grid", "# a comment\n1 2 3 0 0 2\n\n4 5 6 0 1 0\n")?;
+        assert_eq!(grid.name, "the grid");
+        assert_eq!(grid.sensors.len(), 2);
+        assert!(grid.sensors[0].origin.compare(Point3D::new(1., 2., 3.)));
+        assert!(grid.sensors[0].direction.compare(Vector3D::new(0., 0., 1.)));
+        assert!(grid.sensors[1].direction.compare(Vector3D::new(0., 1., 0.)));
+
+        assert!(SensorGrid::from_pts("bad", "1 2 3 0 0").is_err());
+        assert!(SensorGrid::from_pts("bad", "1 2 3 0 0 a").is_err());
+        assert!(SensorGrid::from_pts("bad", "1 2 3 0 0 0").is_err());
+        assert!(SensorGrid::from_pts("bad", "").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sensor_statistics() {
+        let mut s = SensorStatistics::default();
+        assert_eq!(s.daylight_autonomy(), 0.);
+
+        s.record(1., 50., 40.); // fell short
+        s.record(1., 500., 400.); // useful and autonomous
+        s.record(2., 5000., 4000.); // exceeded and autonomous
+
+        assert!((s.occupied_hours - 4.).abs() < 1e-9);
+        assert!((s.daylight_autonomy() - 0.75).abs() < 1e-9);
+        let (fell_short, useful, exceeded) = s.useful_daylight_illuminance();
+        assert!((fell_short - 0.25).abs() < 1e-9);
+        assert!((useful - 0.25).abs() < 1e-9);
+        assert!((exceeded - 0.5).abs() < 1e-9);
+
+        // Circadian
+        assert!((s.mean_melanopic_edi() - 2110.).abs() < 1e-9);
+        assert!((s.mean_equivalent_melanopic_lux() - 2110. / 0.9058).abs() < 1e-9);
+        assert!((s.circadian_autonomy() - 0.75).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_annual_daylight() -> Result<(), String> {
+        let meta_options = MetaOptions {
+            latitude: (-41.3 as Float).to_radians(),
+            longitude: (174.78 as Float).to_radians(),
+            standard_meridian: (180. as Float).to_radians(),
+            elevation: 0.0,
+            daylight_saving: None,
+        };
+        let (model, _header) = Model::from_file("./tests/wall/wall.spl")?;
+
+        // One sensor looking at the sky, away from the wall, and one
+        // looking down into the ground
+        let grid = SensorGrid::from_pts("the grid", "5 -10 1 0 0 1\n5 -10 1 0 0 -1")?;
+        let mut daylight = AnnualDaylight::new(&meta_options, &model, vec![grid])?;
+
+        let weather_data = CurrentWeather {
+            dry_bulb_temperature: 20.,
+            dew_point_temperature: 10.,
+            direct_normal_radiation: 800.,
+            diffuse_horizontal_radiation: 150.,
+            ..CurrentWeather::default()
+        };
+        // Unoccupied... ignored
+        let night = Date {
+            month: 1,
+            day: 1,
+            hour: 3.,
+            year: None,
+        };
+        daylight.record(night, 3600., weather_data)?;
+        let stats = daylight.statistics(0).ok_or("No statistics")?;
+        assert_eq!(stats[0].occupied_hours, 0.);
+
+        // A sunny summer noon
+        let noon = Date {
+            month: 1,
+            day: 1,
+            hour: 12.5,
+            year: None,
+        };
+        daylight.record(noon, 3600., weather_data)?;
+        let stats = daylight.statistics(0).ok_or("No statistics")?;
+        assert!((stats[0].occupied_hours - 1.).abs() < 1e-9);
+        assert!((stats[0].daylight_autonomy() - 1.).abs() < 1e-9);
+        assert!((stats[0].exceeded_hours - 1.).abs() < 1e-9);
+        assert!((stats[1].occupied_hours - 1.).abs() < 1e-9);
+
+        let sda = daylight
+            .spatial_daylight_autonomy(0)
+            .ok_or("No spatial daylight autonomy")?;
+        assert!(sda >= 0.5, "sda = {}", sda);
+
+        // Daylight is D65 by default... so M-EDI equals the illuminance
+        assert!(stats[0].mean_melanopic_edi() > CIRCADIAN_MELANOPIC_EDI_THRESHOLD);
+        assert!((stats[0].circadian_autonomy() - 1.).abs() < 1e-9);
+
+        let mut report = Vec::new();
+        daylight
+            .write_report(&mut report)
+            .map_err(|e| e.to_string())?;
+        let report = String::from_utf8(report).map_err(|e| e.to_string())?;
+        assert_eq!(report.lines().count(), 3);
+        assert!(report.lines().nth(1).unwrap().starts_with("the grid,0,"));
+        Ok(())
+    }
+}
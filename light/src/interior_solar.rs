@@ -0,0 +1,352 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::solar_surface::SolarSurface;
+use crate::Float;
+use geometry::{Ray3D, Vector3D};
+use model::{
+    Boundary, Fenestration, FenestrationType, Model, SimulationState, Substance, SurfaceTrait,
+};
+use rendering::{Scene, SceneElement, SimpleModelReader, Wavelengths};
+
+/// Offset for the starting point of the rays.
+const DELTA: Float = 0.001;
+
+/// An exterior fenestration through which the sun can enter a space
+struct BeamWindow {
+    /// The index of the fenestration in the model
+    index: usize,
+
+    /// The points sampled over the fenestration
+    surface: SolarSurface,
+
+    /// The normal of the fenestration, pointing outdoors
+    outward: Vector3D,
+
+    /// The solar transmittance of the glazing
+    transmittance: Float,
+
+    /// The area of the fenestration
+    area: Float,
+}
+
+/// The beam solar irradiance (in W/m2) that is transmitted through the
+/// fenestrations and reaches each side of the surfaces and fenestrations
+/// of the model during a timestep
+#[derive(Debug, Clone)]
+pub(crate) struct InteriorBeam {
+    pub front_surfaces: Vec<Float>,
+    pub back_surfaces: Vec<Float>,
+    pub front_fenestrations: Vec<Float>,
+    pub back_fenestrations: Vec<Float>,
+}
+
+/// Distributes the beam solar radiation that enters the spaces through
+/// the fenestrations onto the interior surfaces it actually hits, by
+/// tracing the sun's rays from a set of points over each fenestration.
+pub(crate) struct InteriorSolar {
+    /// The scene used for tracing the rays
+    scene: Scene,
+
+    /// The element of the model to which each triangle of the `scene` belongs
+    triangle_map: Vec<(SceneElement, usize)>,
+
+    /// The fenestrations that lead outdoors
+    windows: Vec<BeamWindow>,
+}
+
+/// Calculates the solar transmittance of a fenestration as the product of
+/// the transmittances of its solid layers (i.e., ignoring inter-reflections)
+fn transmittance(model: &Model, fenestration: &Fenestration) -> Result<Float, String> {
+    let construction = model.get_construction(&fenestration.construction)?;
+    let mut tau = 1.;
+    for material in construction.materials.iter() {
+        if let Substance::Normal(s) = model.get_material_substance(material)? {
+            tau *= s.solar_transmittance().unwrap_or(&0.0);
+        }
+    }
+    Ok(tau)
+}
+
+impl InteriorSolar {
+    /// Builds the scene of the model and samples `n_points` over each of
+    /// the fenestrations that separate a space from the outdoors
+    pub fn new(model: &Model, n_points: usize) -> Result<Self, String> {
+        let mut reader = SimpleModelReader::default();
+        let (mut scene, map) = reader.build_scene(model, &Wavelengths::Solar)?;
+        let mapping = scene.build_accelerator();
+        let triangle_map = mapping.iter().map(|i| map[*i]).collect();
+
+        let mut windows = Vec::new();
+        for (index, fen) in model.fenestrations.iter().enumerate() {
+            if let FenestrationType::Opening = fen.category {
+                continue;
+            }
+            let outward = match (&fen.front_boundary, &fen.back_boundary) {
+                (Boundary::Outdoor, Boundary::Space { .. }) => fen.vertices.normal(),
+                (Boundary::Space { .. }, Boundary::Outdoor) => fen.vertices.normal() * -1.,
+                _ => continue,
+            };
+            let transmittance = transmittance(model, fen)?;
+            if transmittance <= 0. {
+                continue;
+            }
+            windows.push(BeamWindow {
+                index,
+                surface: SolarSurface::new(n_points, &fen.vertices, false, false)?,
+                outward,
+                transmittance,
+                area: fen.area(),
+            });
+        }
+
+        Ok(Self {
+            scene,
+            triangle_map,
+            windows,
+        })
+    }
+
+    /// Calculates the beam solar irradiance that reaches each side of the
+    /// surfaces and fenestrations of the `model`, given the direction of
+    /// the sun (pointing towards it, or `None` if it is below the horizon)
+    /// and the direct normal irradiance.
+    ///
+    /// The radiation that reaches each point of a fenestration is reduced by
+    /// its transmittance and by its shading devices, tint and frame.
+    pub fn beam_irradiance(
+        &self,
+        model: &Model,
+        state: &SimulationState,
+        sun: Option<Vector3D>,
+        direct_normal_irradiance: Float,
+    ) -> InteriorBeam {
+        let mut ret = InteriorBeam {
+            front_surfaces: vec![0.0; model.surfaces.len()],
+            back_surfaces: vec![0.0; model.surfaces.len()],
+            front_fenestrations: vec![0.0; model.fenestrations.len()],
+            back_fenestrations: vec![0.0; model.fenestrations.len()],
+        };
+        let sun = match sun {
+            Some(v) if direct_normal_irradiance > 0. => v,
+            _ => return ret,
+        };
+
+        let mut node_aux = [0; 32];
+        let direction = sun * -1.;
+        for window in self.windows.iter() {
+            let cos = window.outward * sun;
+            if cos <= 0. || window.surface.points.is_empty() {
+                continue;
+            }
+            let fen = &model.fenestrations[window.index];
+            let factor = fen.solar_shading_factor(state)
+                * fen.solar_tint_factor(state)
+                * fen.glazed_fraction().unwrap_or(1.);
+            // The power (in W) that enters through each point
+            let power =
+                direct_normal_irradiance * cos * window.transmittance * factor * window.area
+                    / window.surface.points.len() as Float;
+
+            for point in window.surface.points.iter() {
+                // Is the sun visible from the outside?
+                let ray = Ray3D {
+                    origin: *point + window.outward * DELTA,
+                    direction: sun,
+                };
+                if self.scene.cast_ray(ray, &mut node_aux).is_some() {
+                    continue;
+                }
+
+                // Where does it go once inside?
+                let ray = Ray3D {
+                    origin: *point - window.outward * DELTA,
+                    direction,
+                };
+                let (element, i) = match self.scene.cast_ray(ray, &mut node_aux) {
+                    Some((triangle, _)) => self.triangle_map[triangle],
+                    None => continue,
+                };
+                match element {
+                    SceneElement::Surface => {
+                        let s = &model.surfaces[i];
+                        let irradiance = power / s.area();
+                        if direction * s.vertices.normal() < 0. {
+                            ret.front_surfaces[i] += irradiance;
+                        } else {
+                            ret.back_surfaces[i] += irradiance;
+                        }
+                    }
+                    SceneElement::Fenestration => {
+                        let s = &model.fenestrations[i];
+                        let irradiance = power / s.area();
+                        if direction * s.vertices.normal() < 0. {
+                            ret.front_fenestrations[i] += irradiance;
+                        } else {
+                            ret.back_fenestrations[i] += irradiance;
+                        }
+                    }
+                    SceneElement::ShadingSurface => {}
+                }
+            }
+        }
+        ret
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use geometry::{Loop3D, Point3D, Polygon3D};
+    use model::{substance::Normal, Construction, Material, Space, Surface};
+    use validate::assert_close;
+
+    fn get_loop(vertices: &[(Float, Float, Float)]) -> Result<Loop3D, String> {
+        let mut the_loop = Loop3D::new();
+        for (x, y, z) in vertices {
+            the_loop.push(Point3D::new(*x, *y, *z))?;
+        }
+        the_loop.close()?;
+        Ok(the_loop)
+    }
+
+    /// A room of 1m x 1m x 1m with a skylight of 0.5m x 0.5m in the middle
+    /// of the roof
+    fn get_model() -> Result<Model, String> {
+        let mut model = Model::default();
+        model.add_space(Space::new("Room"));
+
+        let mut glass = Normal::new("glass substance");
+        glass.set_solar_transmittance(0.8);
+        model.add_substance(glass.wrap());
+        model.add_substance(Normal::new("concrete").wrap());
+        model.add_material(Material::new("glass", "glass substance", 0.006));
+        model.add_material(Material::new("concrete wall", "concrete", 0.2));
+        let mut c = Construction::new("glass");
+        c.materials.push("glass".into());
+        model.add_construction(c);
+        let mut c = Construction::new("wall");
+        c.materials.push("concrete wall".into());
+        model.add_construction(c);
+
+        let skylight = [
+            (0.25, 0.25, 1.),
+            (0.75, 0.25, 1.),
+            (0.75, 0.75, 1.),
+            (0.25, 0.75, 1.),
+        ];
+        let faces = [
+            (
+                "bottom",
+                [(0., 0., 0.), (0., 1., 0.), (1., 1., 0.), (1., 0., 0.)],
+            ),
+            (
+                "top",
+                [(0., 0., 1.), (1., 0., 1.), (1., 1., 1.), (0., 1., 1.)],
+            ),
+            (
+                "south",
+                [(0., 0., 0.), (1., 0., 0.), (1., 0., 1.), (0., 0., 1.)],
+            ),
+            (
+                "north",
+                [(0., 1., 0.), (0., 1., 1.), (1., 1., 1.), (1., 1., 0.)],
+            ),
+            (
+                "west",
+                [(0., 0., 0.), (0., 0., 1.), (0., 1., 1.), (0., 1., 0.)],
+            ),
+            (
+                "east",
+                [(1., 0., 0.), (1., 1., 0.), (1., 1., 1.), (1., 0., 1.)],
+            ),
+        ];
+        for (name, vertices) in faces {
+            let mut polygon = Polygon3D::new(get_loop(&vertices)?)?;
+            if name == "top" {
+                polygon.cut_hole(get_loop(&skylight)?)?;
+            }
+            model.add_surface(Surface::new(
+                name,
+                polygon,
+                "wall",
+                Boundary::Outdoor,
+                Boundary::Space {
+                    space: "Room".into(),
+                },
+            ))?;
+        }
+        model.add_fenestration(Fenestration::new(
+            "skylight",
+            Polygon3D::new(get_loop(&skylight)?)?,
+            "glass",
+            FenestrationType::Window,
+            Boundary::Outdoor,
+            Boundary::Space {
+                space: "Room".into(),
+            },
+        ))?;
+        Ok(model)
+    }
+
+    #[test]
+    fn test_transmittance() -> Result<(), String> {
+        let model = get_model()?;
+        assert_close!(transmittance(&model, &model.fenestrations[0])?, 0.8);
+        Ok(())
+    }
+
+    #[test]
+    fn test_beam_irradiance() -> Result<(), String> {
+        let mut model = get_model()?;
+        let mut header = model.take_state().ok_or("Could not take state")?;
+        let state = header.take_values().ok_or("Could not take values")?;
+        let interior = InteriorSolar::new(&model, 100)?;
+        assert_eq!(interior.windows.len(), 1);
+
+        // Sun at the zenith: everything goes to the floor
+        let beam = interior.beam_irradiance(&model, &state, Some(Vector3D::new(0., 0., 1.)), 1000.);
+        assert_close!(beam.back_surfaces[0], 1000. * 0.8 * 0.25, 1e-6);
+        assert_close!(beam.front_surfaces[0], 0.);
+        let total: Float = beam.back_surfaces.iter().sum();
+        assert_close!(total, beam.back_surfaces[0]);
+
+        // Sun from the East, 45 degrees high: everything goes to the West wall
+        let sun = Vector3D::new(1., 0., 1.).get_normalized();
+        let beam = interior.beam_irradiance(&model, &state, Some(sun), 1000.);
+        assert_close!(beam.back_surfaces[0], 0.);
+        let expected = 1000. * 0.8 * (45. as Float).to_radians().cos() * 0.25;
+        assert_close!(beam.back_surfaces[4], expected, 1e-6);
+
+        // Sun behind the skylight, and no sun
+        let sun = Vector3D::new(1., 0., -0.1).get_normalized();
+        let beam = interior.beam_irradiance(&model, &state, Some(sun), 1000.);
+        assert!(beam.back_surfaces.iter().all(|v| *v == 0.));
+        let beam = interior.beam_irradiance(&model, &state, Some(Vector3D::new(0., 0., 1.)), 0.);
+        assert!(beam.back_surfaces.iter().all(|v| *v == 0.));
+        let beam = interior.beam_irradiance(&model, &state, None, 1000.);
+        assert!(beam.back_surfaces.iter().all(|v| *v == 0.));
+        Ok(())
+    }
+}
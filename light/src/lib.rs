@@ -25,7 +25,11 @@ SOFTWARE.
 //! * **Calculating Incident Solar Radiation in each surface**: Contrary to EnergyPlus (and probably other tools I am less familiar with), this module uses Daylight Coefficients for performing this simulation. This method was stolen from the
 //! daylighting simulation world, and has the advantage of being extremely robust, and therefore capable of handling complex geometries. Perhaps the main drawback is that—because the concept of Thermal Zone does not fit within Lighting calculations (it is quite artificial for radiation purposes, actually)—reporting the "Solar Heat Gains" in a zone needs significant post-processing.
 //! * **Calculating view factors for Infrared calculations**: This is in development... for now, it does nothing.
-//! * **Daylighting Calculations**: Because this module is based on ray-tracing, it can perform daylight calculations. It is unclear, however, which climate based daylight metrics to include and how... if you have any idea, let me know.
+//! * **Interior solar distribution**: Optionally, the beam solar radiation transmitted through the fenestrations is traced—on every timestep—onto the interior surfaces it actually hits, instead of being represented by the patches of the discretized sky.
+//! * **Shading surfaces**: Overhangs, fins and neighbouring buildings can be modelled as `ShadingSurface`s, which cast shadows and reflect the sun (and affect the view factors) without being part of the thermal calculations.
+//! * **Shading devices**: The radiation reaching the surfaces responds to the deployment of the blinds and shades installed on the fenestrations, to the tint of their switchable glazings, and to the area taken by their frames.
+//! * **Photovoltaic panels and solar collectors**: The solar radiation incident on each `Photovoltaic` panel and `SolarCollector` is calculated, either from the `Surface` it is installed on or from its own geometry. The electricity they generate is calculated by the `electrical` module, and the heat they deliver to the `WaterTank`s by the `heat` module.
+//! * **Daylighting Calculations**: Because this module is based on ray-tracing, it can perform daylight calculations. The average illuminance over each `SensorGrid` of the model is calculated on every timestep, so control scripts can react to it. Also, the `AnnualDaylight` object calculates Daylight Autonomy (DA), Useful Daylight Illuminance (UDI) and spatial Daylight Autonomy (sDA) over user-defined sensor grids, using Daylight Coefficients and the Perez sky of each timestep.
 
 /// The kind of Floating point number used in the
 /// library... the `"float"` feature means it becomes `f32`
@@ -50,6 +54,12 @@ pub const PI: Float = std::f64::consts::PI;
 /// calculating solar and lighting factors.
 pub mod solar_model;
 pub use solar_model::SolarModel;
+mod interior_solar;
 mod optical_info;
+mod shading;
 mod solar_surface;
 pub use optical_info::{IRViewFactorSet, OpticalInfo};
+
+/// Climate-based daylight metrics over sensor grids
+pub mod daylight;
+pub use daylight::{AnnualDaylight, SensorGrid};
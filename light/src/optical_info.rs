@@ -21,8 +21,10 @@ use crate::solar_surface::SolarSurface;
 use crate::Float;
 use matrix::Matrix;
 use model::{Model, SimulationStateHeader, SolarOptions};
+use rendering::colour_matrix::colour_matrix_to_luminance;
 use rendering::{DCFactory, Scene, Wavelengths};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 use weather::ReinhartSky;
 
 #[cfg(feature = "parallel")]
@@ -69,6 +71,22 @@ pub struct OpticalInfo {
 
     /// The Daylight Coefficients matrix for the back-side of the fenestrations in the scene
     pub back_fenestrations_dc: Matrix,
+
+    /// The Daylight Coefficients matrix for the photovoltaic panels that are
+    /// not installed on a surface (the others have rows of zeroes)
+    #[serde(default)]
+    pub photovoltaics_dc: Matrix,
+
+    /// The Daylight Coefficients matrix for the solar collectors that are
+    /// not installed on a surface (the others have rows of zeroes)
+    #[serde(default)]
+    pub solar_collectors_dc: Matrix,
+
+    /// The Daylight Coefficients matrix of each sensor grid, in the visible
+    /// spectrum. Multiplying it by a visible sky vector yields the illuminance
+    /// of each sensor, in lux
+    #[serde(default)]
+    pub sensor_grids_dc: Vec<Matrix>,
 }
 
 impl OpticalInfo {
@@ -89,6 +107,14 @@ impl OpticalInfo {
         let surfaces = SolarSurface::make_surfaces(&model.surfaces, state, n_solar_rays)?;
         let fenestrations =
             SolarSurface::make_fenestrations(&model.fenestrations, state, n_solar_rays)?;
+        let photovoltaics = SolarSurface::make_panels(
+            model.photovoltaics.iter().map(|pv| pv.vertices()),
+            n_solar_rays,
+        )?;
+        let solar_collectors = SolarSurface::make_panels(
+            model.solar_collectors.iter().map(|c| c.vertices()),
+            n_solar_rays,
+        )?;
 
         // build scene
         let mut solar_scene = Scene::from_simple_model(model, Wavelengths::Solar)?;
@@ -100,7 +126,10 @@ impl OpticalInfo {
             n_ambient_samples: options
                 .solar_ambient_divitions_or(crate::solar_model::MODULE_NAME, 300),
             reinhart: ReinhartSky::new(mf),
-            // ..DCFactory::default()
+            cache_dir: options
+                .daylight_coefficients_cache()
+                .ok()
+                .map(PathBuf::from),
         };
 
         let mut nsensors = 0;
@@ -110,9 +139,13 @@ impl OpticalInfo {
         for s in fenestrations.iter() {
             nsensors += s.points.len();
         }
+        let mut npanel_sensors = 0;
+        for s in photovoltaics.iter().chain(solar_collectors.iter()) {
+            npanel_sensors += s.points.len();
+        }
         let progress_bar = utils::ProgressBar::new(
             "Calculating Solar Exposures".to_string(),
-            2 * nsensors * solar_dc_factory.n_ambient_samples,
+            (2 * nsensors + npanel_sensors) * solar_dc_factory.n_ambient_samples,
         );
 
         // calculate
@@ -147,8 +180,26 @@ impl OpticalInfo {
             false,
             Some(&progress_bar),
         )?;
+
+        let photovoltaics_dc = SolarSurface::calc_solar_dc_matrix(
+            &photovoltaics,
+            &solar_scene,
+            &solar_dc_factory,
+            true,
+            Some(&progress_bar),
+        )?;
+
+        let solar_collectors_dc = SolarSurface::calc_solar_dc_matrix(
+            &solar_collectors,
+            &solar_scene,
+            &solar_dc_factory,
+            true,
+            Some(&progress_bar),
+        )?;
         progress_bar.done();
 
+        let sensor_grids_dc = Self::calc_sensor_grids_dc(model, mf, &solar_dc_factory)?;
+
         #[cfg(not(feature = "parallel"))]
         let surf_iter = surfaces.iter();
         #[cfg(not(feature = "parallel"))]
@@ -184,8 +235,53 @@ impl OpticalInfo {
             back_surfaces_dc,
             front_fenestrations_dc,
             back_fenestrations_dc,
+            photovoltaics_dc,
+            solar_collectors_dc,
+            sensor_grids_dc,
         })
     }
+
+    /// Calculates the (visible) Daylight Coefficients of the sensors in
+    /// each `SensorGrid` of the model
+    fn calc_sensor_grids_dc(
+        model: &Model,
+        mf: usize,
+        solar_dc_factory: &DCFactory,
+    ) -> Result<Vec<Matrix>, String> {
+        if model.sensor_grids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut visible_scene = Scene::from_simple_model(model, Wavelengths::Visible)?;
+        visible_scene.build_accelerator();
+
+        // Light bounces indoors, so it needs more than one
+        let visible_dc_factory = DCFactory {
+            reinhart: ReinhartSky::new(mf),
+            n_ambient_samples: solar_dc_factory.n_ambient_samples,
+            cache_dir: solar_dc_factory.cache_dir.clone(),
+            ..DCFactory::default()
+        };
+
+        let mut sensors = Vec::with_capacity(model.sensor_grids.len());
+        for grid in model.sensor_grids.iter() {
+            sensors.push(grid.sensors()?);
+        }
+        let n_sensors: usize = sensors.iter().map(|s| s.len()).sum();
+        let progress_bar = utils::ProgressBar::new(
+            "Calculating Daylight Coefficients".to_string(),
+            n_sensors * visible_dc_factory.n_ambient_samples,
+        );
+        let ret = sensors
+            .iter()
+            .map(|rays| {
+                let dc = visible_dc_factory.calc_dc(rays, &visible_scene, Some(&progress_bar));
+                colour_matrix_to_luminance(&dc)
+            })
+            .collect();
+        progress_bar.done();
+        Ok(ret)
+    }
 }
 
 #[cfg(test)]
@@ -193,8 +289,8 @@ mod testing {
 
     use json5;
     use model::{
-        substance::Normal, Construction, Fenestration, Material, Model, SimulationStateHeader,
-        SolarOptions, Surface,
+        substance::Normal, Construction, Fenestration, Material, Model, Photovoltaic,
+        SimulationStateHeader, SolarOptions, Surface,
     };
 
     use crate::OpticalInfo;
@@ -279,7 +375,36 @@ mod testing {
         .map_err(|e| e.to_string())?;
         model.add_fenestration(fen)?;
 
+        let pv: Photovoltaic = json5::from_str(
+            "{
+            name: 'Panel on surface',
+            surface: 'the surface',
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        model.add_photovoltaic(pv)?;
+
+        let pv: Photovoltaic = json5::from_str(
+            "{
+            name: 'Standalone panel',
+            vertices: [
+                0, 0, 3, // X, Y and Z of Vertex 0
+                1, 0, 3, // X, Y and Z of Vertex 1
+                1, 1, 3, // X, Y and Z of Vertex 2
+                0, 1, 3  // ...
+            ]
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        model.add_photovoltaic(pv)?;
+
         let info = OpticalInfo::new(&options, &model, &mut state)?;
+        assert_eq!(info.photovoltaics_dc.size(), (2, 146)); // 2 panels, 146 patches
+        assert!(info.solar_collectors_dc.is_empty()); // no collectors
+        for patch in 0..146 {
+            // the panel on the surface does not need a DC
+            assert!(info.photovoltaics_dc.get(0, patch)?.abs() < 1e-9);
+        }
         assert_eq!(info.back_fenestrations_dc.size(), (2, 146)); // 2 fenestrations, 146 patches
         assert_eq!(info.front_fenestrations_dc.size(), (2, 146)); // 2 fenestrations, 146 patches
         assert_eq!(info.back_surfaces_dc.size(), (2, 146)); // 2 fenestrations, 146 patches
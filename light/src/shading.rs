@@ -0,0 +1,272 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use crate::Float;
+use model::{Boundary, FenestrationType, Model, ShadingPosition, SimulationState, SurfaceTrait};
+use std::collections::HashMap;
+
+/// The factors by which the incident solar radiation is modified due to
+/// the [`ShadingDevice`](model::ShadingDevice)s,
+/// [`SwitchableGlazing`](model::SwitchableGlazing)s and
+/// [`WindowFrame`](model::WindowFrame)s in the model, at a certain timestep.
+///
+/// * The outdoor side of a `Fenestration` with an `Exterior` device receives less sun
+/// * The sides of the surfaces facing a `Space` receive less sun, in proportion to
+///   the (area-weighted) fraction of the radiation that goes through the exterior
+///   fenestrations of that `Space` (i.e., through their devices and tinted glass,
+///   and past their frames).
+///
+/// This is an approximation, as the Daylight Coefficients used for the interior
+/// surfaces do not distinguish which `Fenestration` the sun came through.
+#[derive(Default)]
+pub(crate) struct ShadingFactors {
+    /// The factor of the outdoor side of each fenestration
+    outdoor: Vec<Float>,
+
+    /// The factor of the surfaces facing each space
+    spaces: HashMap<String, Float>,
+}
+
+impl ShadingFactors {
+    /// Calculates the factors based on the state of the shading devices
+    /// and switchable glazings
+    pub fn new(model: &Model, state: &SimulationState) -> Self {
+        // Nothing to do if there are no shades, switchable glazings or frames
+        if model.fenestrations.iter().all(|f| {
+            f.shading_device().is_err() && f.switchable_glazing().is_err() && f.frame().is_err()
+        }) {
+            return Self::default();
+        }
+
+        let mut outdoor = Vec::with_capacity(model.fenestrations.len());
+        // (area, shaded area) of the exterior fenestrations of each space
+        let mut areas: HashMap<String, (Float, Float)> = HashMap::new();
+        for fen in model.fenestrations.iter() {
+            let factor = fen.solar_shading_factor(state);
+            let is_exterior = matches!(
+                fen.shading_device().map(|d| d.position),
+                Ok(ShadingPosition::Exterior)
+            );
+            outdoor.push(if is_exterior { factor } else { 1. });
+
+            if let FenestrationType::Opening = fen.category {
+                continue;
+            }
+            let space = match (&fen.front_boundary, &fen.back_boundary) {
+                (Boundary::Outdoor, Boundary::Space { space })
+                | (Boundary::Space { space }, Boundary::Outdoor) => space,
+                _ => continue,
+            };
+            let area = fen.area();
+            let (total, shaded) = areas.entry(space.clone()).or_insert((0., 0.));
+            *total += area;
+            // Frames that do not fit are reported by the thermal model
+            let glazed = fen.glazed_fraction().unwrap_or(1.);
+            *shaded += area * factor * fen.solar_tint_factor(state) * glazed;
+        }
+
+        let spaces = areas
+            .into_iter()
+            .filter(|(_, (total, _))| *total > 0.)
+            .map(|(space, (total, shaded))| (space, shaded / total))
+            .collect();
+
+        Self { outdoor, spaces }
+    }
+
+    /// Gets the factor for the side of a [`Surface`](model::Surface) facing a
+    /// certain [`Boundary`]
+    pub fn surface(&self, boundary: &Boundary) -> Float {
+        match boundary {
+            Boundary::Space { space } => *self.spaces.get(space).unwrap_or(&1.),
+            _ => 1.,
+        }
+    }
+
+    /// Gets the factor for the side of the `index`th [`Fenestration`](model::Fenestration)
+    /// facing a certain [`Boundary`]
+    pub fn fenestration(&self, index: usize, boundary: &Boundary) -> Float {
+        match boundary {
+            Boundary::Outdoor => *self.outdoor.get(index).unwrap_or(&1.),
+            _ => self.surface(boundary),
+        }
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use geometry::{Loop3D, Point3D, Polygon3D};
+    use model::{
+        substance::Normal, Construction, Fenestration, Material, ShadingDevice, Space,
+        SwitchableGlazing, WindowFrame,
+    };
+    use std::sync::Arc;
+
+    /// Builds a model with a Space and three 1m2 windows leading to the outdoors.
+    /// The first one has a switchable glazing, the second one an exterior shade
+    /// and the last one an interior one.
+    fn get_model(shaded: bool) -> Result<(Model, Vec<Arc<Fenestration>>), String> {
+        let mut model = Model::default();
+        model.add_substance(Normal::new("glass substance").wrap());
+        model.add_material(Material::new("glass", "glass substance", 0.006));
+        let mut c = Construction::new("glass");
+        c.materials.push("glass".into());
+        model.add_construction(c);
+        model.add_space(Space::new("Room"));
+
+        let shades = [
+            None,
+            Some(ShadingDevice::new(0.2, ShadingPosition::Exterior)),
+            Some(ShadingDevice::new(0.4, ShadingPosition::Interior)),
+        ];
+        let mut windows = Vec::with_capacity(shades.len());
+        for (i, shade) in shades.into_iter().enumerate() {
+            let x = 2. * i as Float;
+            let mut the_loop = Loop3D::new();
+            the_loop.push(Point3D::new(x, 0., 0.))?;
+            the_loop.push(Point3D::new(x + 1., 0., 0.))?;
+            the_loop.push(Point3D::new(x + 1., 0., 1.))?;
+            the_loop.push(Point3D::new(x, 0., 1.))?;
+            the_loop.close()?;
+            let mut fen = Fenestration::new(
+                format!("window {}", i),
+                Polygon3D::new(the_loop)?,
+                "glass".to_string(),
+                FenestrationType::Window,
+                Boundary::Outdoor,
+                Boundary::Space {
+                    space: "Room".into(),
+                },
+            );
+            if let (true, Some(shade)) = (shaded, shade) {
+                fen.set_shading_device(shade);
+            } else if shaded {
+                fen.set_switchable_glazing(SwitchableGlazing::new(0.1));
+            }
+            windows.push(model.add_fenestration(fen)?);
+        }
+        Ok((model, windows))
+    }
+
+    #[test]
+    fn test_no_shades() -> Result<(), String> {
+        let (mut model, windows) = get_model(false)?;
+        let state = model
+            .take_state()
+            .ok_or("No state")?
+            .take_values()
+            .ok_or("No values")?;
+
+        let factors = ShadingFactors::new(&model, &state);
+        assert!(factors.outdoor.is_empty());
+        assert!(factors.spaces.is_empty());
+        assert!((factors.surface(&windows[0].back_boundary) - 1.).abs() < 1e-6);
+        assert!((factors.fenestration(1, &Boundary::Outdoor) - 1.).abs() < 1e-6);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_factors() -> Result<(), String> {
+        let (mut model, windows) = get_model(true)?;
+        let mut state = model
+            .take_state()
+            .ok_or("No state")?
+            .take_values()
+            .ok_or("No values")?;
+
+        // Retracted shades do nothing
+        let factors = ShadingFactors::new(&model, &state);
+        assert_eq!(factors.outdoor.len(), 3);
+        for i in 0..3 {
+            assert!((factors.fenestration(i, &Boundary::Outdoor) - 1.).abs() < 1e-6);
+        }
+        assert!((factors.surface(&windows[0].back_boundary) - 1.).abs() < 1e-6);
+
+        // Deploy the shades
+        windows[1].set_shade_open_fraction(&mut state, 0.)?;
+        windows[2].set_shade_open_fraction(&mut state, 0.)?;
+        let factors = ShadingFactors::new(&model, &state);
+        assert!((factors.fenestration(0, &Boundary::Outdoor) - 1.).abs() < 1e-6);
+        assert!((factors.fenestration(1, &Boundary::Outdoor) - 0.2).abs() < 1e-6);
+        // interior shades do not block the sun reaching the glass...
+        assert!((factors.fenestration(2, &Boundary::Outdoor) - 1.).abs() < 1e-6);
+
+        // ... but both reduce the sun reaching the space
+        let exp = (1. + 0.2 + 0.4) / 3.;
+        let found = factors.surface(&windows[0].back_boundary);
+        assert!((found - exp).abs() < 1e-5, "expecting {exp}, found {found}");
+        assert!((factors.fenestration(1, &windows[1].back_boundary) - exp).abs() < 1e-5);
+        assert!((factors.surface(&Boundary::Outdoor) - 1.).abs() < 1e-6);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_tint() -> Result<(), String> {
+        let (mut model, windows) = get_model(true)?;
+        let mut state = model
+            .take_state()
+            .ok_or("No state")?
+            .take_values()
+            .ok_or("No values")?;
+
+        // Clear glazing does nothing
+        assert!(windows[0].tint_level(&state).ok_or("No tint level")?.abs() < 1e-6);
+        let factors = ShadingFactors::new(&model, &state);
+        assert!((factors.surface(&windows[0].back_boundary) - 1.).abs() < 1e-6);
+
+        // Tint the glass
+        windows[0].set_tint_level(&mut state, 1.)?;
+        let factors = ShadingFactors::new(&model, &state);
+        // the glass still receives the sun...
+        assert!((factors.fenestration(0, &Boundary::Outdoor) - 1.).abs() < 1e-6);
+        // ... but the space does not
+        let exp = (0.1 + 1. + 1.) / 3.;
+        let found = factors.surface(&windows[0].back_boundary);
+        assert!((found - exp).abs() < 1e-5, "expecting {exp}, found {found}");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_frame() -> Result<(), String> {
+        let (mut model, windows) = get_model(false)?;
+        // A 10cm frame leaves 0.8 x 0.8 of glass
+        let mut framed = (*model.fenestrations[2]).clone();
+        framed.set_frame(WindowFrame::new(0.1, 5.));
+        model.fenestrations[2] = Arc::new(framed);
+        let state = model
+            .take_state()
+            .ok_or("No state")?
+            .take_values()
+            .ok_or("No values")?;
+
+        let factors = ShadingFactors::new(&model, &state);
+        // The frame does not shade the outside of the window...
+        assert!((factors.fenestration(2, &Boundary::Outdoor) - 1.).abs() < 1e-6);
+        // ... but less sun gets into the space
+        let exp = (1. + 1. + 0.64) / 3.;
+        let found = factors.surface(&windows[0].back_boundary);
+        assert!((found - exp).abs() < 1e-5, "expecting {exp}, found {found}");
+
+        Ok(())
+    }
+}
@@ -17,20 +17,22 @@ LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
 OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
-use crate::{solar_surface::SolarSurface, Float};
+use crate::{shading::ShadingFactors, solar_surface::SolarSurface, Float};
 use calendar::Date;
 use communication::{ErrorHandling, MetaOptions, SimulationModel};
+use matrix::Matrix;
 use model::{
-    print_warning, Boundary, FenestrationType, Model, SimulationState, SimulationStateHeader,
-    SolarOptions,
+    print_warning, Boundary, FenestrationType, Model, SimulationState, SimulationStateElement,
+    SimulationStateHeader, SolarOptions,
 };
 use std::borrow::Borrow;
 use std::fs::File;
 use std::io::Write;
 use std::path::Path;
 use weather::{CurrentWeather, WeatherTrait};
-use weather::{PerezSky, SkyUnits, Solar};
+use weather::{PerezSky, SkyUnits, Solar, Time};
 
+use crate::interior_solar::{InteriorBeam, InteriorSolar};
 use crate::optical_info::OpticalInfo;
 
 /// The name of the module
@@ -56,6 +58,10 @@ pub struct SolarModel {
 
     /// The MF discretization scheme for the sky.
     solar_sky_discretization: usize,
+
+    /// Traces the beam solar radiation that enters the spaces, if
+    /// requested in the [`SolarOptions`]
+    interior_solar: Option<InteriorSolar>,
 }
 
 impl SolarModel {
@@ -74,10 +80,7 @@ impl SolarModel {
         }
 
         let db = weather_data.dry_bulb_temperature;
-        let horizontal_ir = match weather_data.horizontal_infrared_radiation_intensity {
-            Some(v) => v,
-            None => weather_data.derive_horizontal_ir(),
-        };
+        let horizontal_ir = weather_data.horizontal_ir();
 
         // surfaces are never skipped
         for (index, surface) in model.surfaces.iter().enumerate() {
@@ -202,6 +205,82 @@ impl SolarModel {
         Ok(())
     }
 
+    /// Calculates the incident solar irradiance of a panel (i.e., a `Photovoltaic`
+    /// or a `SolarCollector`) averaged over the period.
+    ///
+    /// Panels installed on a `surface` receive the irradiance of its outdoor side,
+    /// and the others receive the irradiance `v` calculated with their own DC.
+    fn panel_irradiance(
+        model: &Model,
+        state: &SimulationState,
+        surface: Result<&String, String>,
+        v: Float,
+        old_v: Option<Float>,
+    ) -> Result<Float, String> {
+        match surface {
+            Ok(surface) => {
+                // Already averaged
+                let s = model.get_surface(surface)?;
+                let v = if matches!(s.back_boundary, Boundary::Outdoor)
+                    && !matches!(s.front_boundary, Boundary::Outdoor)
+                {
+                    s.back_incident_solar_irradiance(state)
+                } else {
+                    s.front_incident_solar_irradiance(state)
+                };
+                v.ok_or(format!(
+                    "Could not get incident solar irradiance of surface '{}', which has a panel",
+                    surface
+                ))
+            }
+            Err(_) => {
+                // Average of the period
+                let old_v =
+                    old_v.ok_or("Could not get previous incident solar irradiance (panel)")?;
+                Ok((v.max(0.0) + old_v) / 2.)
+            }
+        }
+    }
+
+    /// Calculates the average illuminance over each `SensorGrid`
+    fn update_illuminance(
+        &self,
+        date: Date,
+        weather_data: CurrentWeather,
+        model: &Model,
+        state: &mut SimulationState,
+    ) -> Result<(), String> {
+        if model.sensor_grids.is_empty() {
+            return Ok(());
+        }
+
+        let vec = PerezSky::gen_sky_vec(
+            self.solar_sky_discretization,
+            &self.solar,
+            date,
+            weather_data,
+            SkyUnits::Visible,
+            0.2,  // albedo
+            true, // add sky
+            true, // add sun
+        )?;
+
+        for (grid, dc) in model
+            .sensor_grids
+            .iter()
+            .zip(self.optical_info.sensor_grids_dc.iter())
+        {
+            let illuminance = dc.fast_prod(&vec)?;
+            let (n_sensors, ..) = illuminance.size();
+            let mut total = 0.0;
+            for i in 0..n_sensors {
+                total += illuminance.get(i, 0)?.max(0.0);
+            }
+            grid.set_illuminance(state, total / n_sensors as Float)?;
+        }
+        Ok(())
+    }
+
     fn update_solar_radiation(
         &self,
         date: Date,
@@ -225,6 +304,12 @@ impl SolarModel {
                 s.set_front_incident_solar_irradiance(state, 0.0)?;
                 s.set_back_incident_solar_irradiance(state, 0.0)?;
             }
+            for pv in model.photovoltaics.iter() {
+                pv.set_incident_solar_irradiance(state, 0.0)?;
+            }
+            for c in model.solar_collectors.iter() {
+                c.set_incident_solar_irradiance(state, 0.0)?;
+            }
             return Ok(());
         }
 
@@ -239,9 +324,42 @@ impl SolarModel {
             true, // add sun
         )?;
 
+        // Blinds and shades reduce the radiation
+        let shading = ShadingFactors::new(model, state);
+
+        // If the beam solar radiation that enters the spaces is traced, the
+        // interior sides of the surfaces only see the sky without the sun
+        let interior: Option<(Matrix, InteriorBeam)> = match &self.interior_solar {
+            Some(interior_solar) => {
+                let sky_vec = PerezSky::gen_sky_vec(
+                    self.solar_sky_discretization,
+                    &self.solar,
+                    date,
+                    weather_data,
+                    SkyUnits::Solar,
+                    0.2,   // albedo
+                    true,  // add sky
+                    false, // add sun
+                )?;
+                let sun = self.solar.sun_position(Time::Standard(date.day_of_year()));
+                let beam = interior_solar.beam_irradiance(model, state, sun, direct_normal_irrad);
+                Some((sky_vec, beam))
+            }
+            None => None,
+        };
+
         // Process Solar Irradiance in Surfaces
         if !self.optical_info.front_surfaces_dc.is_empty() {
-            let solar_irradiance = &self.optical_info.front_surfaces_dc * &vec;
+            let solar_irradiance = self.optical_info.front_surfaces_dc.fast_prod(&vec)?;
+            let interior_irradiance = interior
+                .as_ref()
+                .map(|(sky_vec, beam)| -> Result<_, String> {
+                    Ok((
+                        self.optical_info.front_surfaces_dc.fast_prod(sky_vec)?,
+                        &beam.front_surfaces,
+                    ))
+                })
+                .transpose()?;
 
             for (i, s) in model.surfaces.iter().enumerate() {
                 if !SolarSurface::boundary_receives_sun(&s.front_boundary) {
@@ -249,7 +367,14 @@ impl SolarModel {
                     s.set_front_incident_solar_irradiance(state, 0.0)?;
                 }
                 // Average of the period
-                let mut v = solar_irradiance.get(i, 0)?;
+                let mut v = match &interior_irradiance {
+                    Some((irradiance, beam))
+                        if matches!(s.front_boundary, Boundary::Space { .. }) =>
+                    {
+                        irradiance.get(i, 0)? * shading.surface(&s.front_boundary) + beam[i]
+                    }
+                    _ => solar_irradiance.get(i, 0)? * shading.surface(&s.front_boundary),
+                };
                 if v < 0.0 {
                     v = 0.0
                 }
@@ -260,7 +385,16 @@ impl SolarModel {
             }
         }
         if !self.optical_info.back_surfaces_dc.is_empty() {
-            let solar_irradiance = &self.optical_info.back_surfaces_dc * &vec;
+            let solar_irradiance = self.optical_info.back_surfaces_dc.fast_prod(&vec)?;
+            let interior_irradiance = interior
+                .as_ref()
+                .map(|(sky_vec, beam)| -> Result<_, String> {
+                    Ok((
+                        self.optical_info.back_surfaces_dc.fast_prod(sky_vec)?,
+                        &beam.back_surfaces,
+                    ))
+                })
+                .transpose()?;
 
             for (i, s) in model.surfaces.iter().enumerate() {
                 if !SolarSurface::boundary_receives_sun(&s.back_boundary) {
@@ -268,7 +402,14 @@ impl SolarModel {
                     s.set_back_incident_solar_irradiance(state, 0.0)?;
                 }
                 // Average of the period
-                let mut v = solar_irradiance.get(i, 0)?;
+                let mut v = match &interior_irradiance {
+                    Some((irradiance, beam))
+                        if matches!(s.back_boundary, Boundary::Space { .. }) =>
+                    {
+                        irradiance.get(i, 0)? * shading.surface(&s.back_boundary) + beam[i]
+                    }
+                    _ => solar_irradiance.get(i, 0)? * shading.surface(&s.back_boundary),
+                };
                 if v < 0.0 {
                     v = 0.0
                 }
@@ -281,7 +422,18 @@ impl SolarModel {
 
         // Process Solar Irradiance in Fenestration
         if !self.optical_info.front_fenestrations_dc.is_empty() {
-            let solar_irradiance = &self.optical_info.front_fenestrations_dc * &vec;
+            let solar_irradiance = self.optical_info.front_fenestrations_dc.fast_prod(&vec)?;
+            let interior_irradiance = interior
+                .as_ref()
+                .map(|(sky_vec, beam)| -> Result<_, String> {
+                    Ok((
+                        self.optical_info
+                            .front_fenestrations_dc
+                            .fast_prod(sky_vec)?,
+                        &beam.front_fenestrations,
+                    ))
+                })
+                .transpose()?;
 
             for (i, s) in model.fenestrations.iter().enumerate() {
                 if let FenestrationType::Opening = s.category {
@@ -292,7 +444,14 @@ impl SolarModel {
                     s.set_front_incident_solar_irradiance(state, 0.0)?;
                 }
                 // Average of the period
-                let v = solar_irradiance.get(i, 0)?;
+                let v = match &interior_irradiance {
+                    Some((irradiance, beam))
+                        if matches!(s.front_boundary, Boundary::Space { .. }) =>
+                    {
+                        irradiance.get(i, 0)? * shading.fenestration(i, &s.front_boundary) + beam[i]
+                    }
+                    _ => solar_irradiance.get(i, 0)? * shading.fenestration(i, &s.front_boundary),
+                };
                 let old_v = s.front_incident_solar_irradiance(state).ok_or(
                     "Could not get previous front incident solar irradiance (fenestration)",
                 )?;
@@ -300,7 +459,16 @@ impl SolarModel {
             }
         }
         if !self.optical_info.back_fenestrations_dc.is_empty() {
-            let solar_irradiance = &self.optical_info.back_fenestrations_dc * &vec;
+            let solar_irradiance = self.optical_info.back_fenestrations_dc.fast_prod(&vec)?;
+            let interior_irradiance = interior
+                .as_ref()
+                .map(|(sky_vec, beam)| -> Result<_, String> {
+                    Ok((
+                        self.optical_info.back_fenestrations_dc.fast_prod(sky_vec)?,
+                        &beam.back_fenestrations,
+                    ))
+                })
+                .transpose()?;
             for (i, s) in model.fenestrations.iter().enumerate() {
                 if let FenestrationType::Opening = s.category {
                     continue;
@@ -309,13 +477,49 @@ impl SolarModel {
                     s.set_back_incident_solar_irradiance(state, 0.0)?;
                 }
                 // Average of the period
-                let v = solar_irradiance.get(i, 0)?;
+                let v = match &interior_irradiance {
+                    Some((irradiance, beam))
+                        if matches!(s.back_boundary, Boundary::Space { .. }) =>
+                    {
+                        irradiance.get(i, 0)? * shading.fenestration(i, &s.back_boundary) + beam[i]
+                    }
+                    _ => solar_irradiance.get(i, 0)? * shading.fenestration(i, &s.back_boundary),
+                };
                 let old_v = s.back_incident_solar_irradiance(state).ok_or(
                     "Could not get previous back incident solar irradiance (fenestration)",
                 )?;
                 s.set_back_incident_solar_irradiance(state, (v + old_v) / 2.)?;
             }
         }
+
+        // Process Solar Irradiance in Photovoltaic panels and Solar Collectors. This
+        // needs to be done after the surfaces, as some of them are installed on them.
+        if !self.optical_info.photovoltaics_dc.is_empty() {
+            let solar_irradiance = self.optical_info.photovoltaics_dc.fast_prod(&vec)?;
+            for (i, pv) in model.photovoltaics.iter().enumerate() {
+                let v = Self::panel_irradiance(
+                    model,
+                    state,
+                    pv.surface(),
+                    solar_irradiance.get(i, 0)?,
+                    pv.incident_solar_irradiance(state),
+                )?;
+                pv.set_incident_solar_irradiance(state, v)?;
+            }
+        }
+        if !self.optical_info.solar_collectors_dc.is_empty() {
+            let solar_irradiance = self.optical_info.solar_collectors_dc.fast_prod(&vec)?;
+            for (i, c) in model.solar_collectors.iter().enumerate() {
+                let v = Self::panel_irradiance(
+                    model,
+                    state,
+                    c.surface(),
+                    solar_irradiance.get(i, 0)?,
+                    c.incident_solar_irradiance(state),
+                )?;
+                c.set_incident_solar_irradiance(state, v)?;
+            }
+        }
         Ok(())
     }
 }
@@ -394,6 +598,45 @@ impl SimulationModel for SolarModel {
             OpticalInfo::new(&options, model, state)?
         };
 
+        // The optical data might have been read from a file, which does not
+        // push the state of the photovoltaic panels and solar collectors.
+        for (i, pv) in model.photovoltaics.iter().enumerate() {
+            if pv.incident_solar_irradiance_index().is_none() {
+                let i = state.push(SimulationStateElement::PhotovoltaicSolarIrradiance(i), 0.0)?;
+                pv.set_incident_solar_irradiance_index(i)?;
+            }
+        }
+        for (i, c) in model.solar_collectors.iter().enumerate() {
+            if c.incident_solar_irradiance_index().is_none() {
+                let i = state.push(
+                    SimulationStateElement::SolarCollectorSolarIrradiance(i),
+                    0.0,
+                )?;
+                c.set_incident_solar_irradiance_index(i)?;
+            }
+        }
+        for (i, grid) in model.sensor_grids.iter().enumerate() {
+            if grid.illuminance_index().is_none() {
+                let i = state.push(SimulationStateElement::SensorGridIlluminance(i), 0.0)?;
+                grid.set_illuminance_index(i)?;
+            }
+        }
+        if optical_info.sensor_grids_dc.len() != model.sensor_grids.len() {
+            return Err(
+                "optical data does not match the sensor grids in the model. Delete the optical data file so it is calculated again".to_string()
+            );
+        }
+        let (pv_rows, ..) = optical_info.photovoltaics_dc.size();
+        let (collector_rows, ..) = optical_info.solar_collectors_dc.size();
+        if (!model.photovoltaics.is_empty() && pv_rows != model.photovoltaics.len())
+            || (!model.solar_collectors.is_empty()
+                && collector_rows != model.solar_collectors.len())
+        {
+            return Err(
+                "optical data does not match the photovoltaic panels or solar collectors in the model. Delete the optical data file so it is calculated again".to_string()
+            );
+        }
+
         // Create the Solar object
         let latitude = meta_options.latitude;
         let longitude = -meta_options.longitude;
@@ -405,7 +648,9 @@ impl SimulationModel for SolarModel {
             return Ok(Self {
                 optical_info,
                 solar,
-                solar_sky_discretization: 1, // not really relevant
+                // only relevant for the sensor grids
+                solar_sky_discretization: options.solar_sky_discretization_or(MODULE_NAME, 1),
+                interior_solar: None,
             });
         }
 
@@ -429,10 +674,17 @@ impl SimulationModel for SolarModel {
             }
         }
 
+        // Ray-tracing of the sun that enters the spaces
+        let interior_solar = match options.n_interior_solar_points() {
+            Ok(n_points) => Some(InteriorSolar::new(model, *n_points)?),
+            Err(_) => None,
+        };
+
         Ok(Self {
             optical_info,
             solar,
             solar_sky_discretization: mf,
+            interior_solar,
         })
     }
 
@@ -451,6 +703,7 @@ impl SimulationModel for SolarModel {
 
         self.update_ir_radiation(&weather_data, model, state)?;
         self.update_solar_radiation(date, weather_data, model, state)?;
+        self.update_illuminance(date, weather_data, model, state)?;
 
         Ok(())
     }
@@ -459,7 +712,10 @@ impl SimulationModel for SolarModel {
 #[cfg(test)]
 mod testing {
     use super::*;
-    use model::{substance::Normal, Construction, Fenestration, Material, Surface};
+    use model::{
+        substance::Normal, Construction, Fenestration, Material, Photovoltaic, SensorGrid,
+        SolarCollector, Surface,
+    };
     use schedule::ScheduleConstant;
     use weather::SyntheticWeather;
 
@@ -478,6 +734,7 @@ mod testing {
             longitude: 72.,
             standard_meridian: 70.,
             elevation: 0.0,
+            daylight_saving: None,
         };
         let (model, mut state_header) = Model::from_file("./tests/wall/wall.spl")?;
         let mut solar_options = model.solar_options.clone().ok_or("no solar options")?;
@@ -493,6 +750,7 @@ mod testing {
             longitude: 72.,
             standard_meridian: 70.,
             elevation: 0.0,
+            daylight_saving: None,
         };
         let mut solar_options = model.solar_options.clone().ok_or("No solar options 2")?;
         solar_options.set_solar_sky_discretization(2);
@@ -599,6 +857,7 @@ mod testing {
             longitude: (174.78 as Float).to_radians(),
             standard_meridian: (180. as Float).to_radians(),
             elevation: 0.0,
+            daylight_saving: None,
         };
 
         let mut state_header = SimulationStateHeader::new();
@@ -624,6 +883,7 @@ mod testing {
                 month: 1,
                 day: 1,
                 hour: 12.,
+                year: None,
             },
             &weather,
             &model,
@@ -680,4 +940,236 @@ mod testing {
 
         Ok(())
     }
+
+    #[test]
+    fn test_photovoltaics() -> Result<(), String> {
+        let mut model = Model::default();
+
+        let substance = Normal::new("the substance");
+        model.add_substance(substance.wrap());
+
+        let material = Material::new("the material", "the substance", 0.1);
+        model.add_material(material);
+
+        let mut construction = Construction::new("the construction");
+        construction.materials.push("the material".into());
+        model.add_construction(construction);
+
+        let s: Surface = json5::from_str(
+            "{
+            name: 'the roof',
+            construction:'the construction',
+            vertices: [
+                0, 0, 10, // X, Y and Z of Vertex 0
+                1, 0, 10, // X, Y and Z of Vertex 1
+                1, 1, 10, // X, Y and Z of Vertex 2
+                0, 1, 10  // ...
+            ]
+         }",
+        )
+        .map_err(|e| e.to_string())?;
+        model.add_surface(s)?;
+
+        let pv: Photovoltaic = json5::from_str(
+            "{
+            name: 'Roof Panel',
+            surface: 'the roof',
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        model.add_photovoltaic(pv)?;
+
+        let pv: Photovoltaic = json5::from_str(
+            "{
+            name: 'Standalone Panel',
+            vertices: [
+                5, 5, 0, // X, Y and Z of Vertex 0
+                6, 5, 0, // X, Y and Z of Vertex 1
+                6, 6, 0, // X, Y and Z of Vertex 2
+                5, 6, 0  // ...
+            ]
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        model.add_photovoltaic(pv)?;
+
+        let collector: SolarCollector = json5::from_str(
+            "{
+            name: 'Standalone Collector',
+            water_tank: 'the tank',
+            vertices: [
+                8, 8, 0, // X, Y and Z of Vertex 0
+                9, 8, 0, // X, Y and Z of Vertex 1
+                9, 9, 0, // X, Y and Z of Vertex 2
+                8, 9, 0  // ...
+            ]
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        model.add_solar_collector(collector)?;
+
+        let meta_options = MetaOptions {
+            latitude: (-41.3 as Float).to_radians(),
+            longitude: (174.78 as Float).to_radians(),
+            standard_meridian: (180. as Float).to_radians(),
+            elevation: 0.0,
+            daylight_saving: None,
+        };
+
+        let mut state_header = SimulationStateHeader::new();
+        let mut options = SolarOptions::new();
+        options.set_n_solar_irradiance_points(10);
+        options.set_solar_ambient_divitions(300);
+        options.set_solar_sky_discretization(1);
+
+        let solar_model = SolarModel::new(&meta_options, options, &model, &mut state_header, 1)?;
+
+        let mut weather = SyntheticWeather::default();
+        weather.dew_point_temperature = Box::new(ScheduleConstant::new(11.));
+        weather.dry_bulb_temperature = Box::new(ScheduleConstant::new(24.));
+        weather.opaque_sky_cover = Box::new(ScheduleConstant::new(0.));
+        weather.direct_normal_radiation = Box::new(ScheduleConstant::new(400.));
+        weather.diffuse_horizontal_radiation = Box::new(ScheduleConstant::new(200.));
+
+        let mut state = state_header.take_values().ok_or("Could not take values")?;
+        solar_model.march(
+            Date {
+                month: 1,
+                day: 1,
+                hour: 12.,
+                year: None,
+            },
+            &weather,
+            &model,
+            &mut state,
+            &mut (),
+        )?;
+
+        // The panel on the roof receives what the roof receives
+        let roof = model.surfaces[0]
+            .front_incident_solar_irradiance(&state)
+            .ok_or("No front solar irradiance")?;
+        let v = model.photovoltaics[0]
+            .incident_solar_irradiance(&state)
+            .ok_or("No photovoltaic solar irradiance")?;
+        assert!(roof > 20., "roof = {}", roof);
+        assert!((v - roof).abs() < 1e-9, "v = {}, roof = {}", v, roof);
+
+        // The standalone panel receives its own
+        let v = model.photovoltaics[1]
+            .incident_solar_irradiance(&state)
+            .ok_or("No photovoltaic solar irradiance")?;
+        assert!(v > 20., "v = {}", v);
+
+        // And so does the collector
+        let v = model.solar_collectors[0]
+            .incident_solar_irradiance(&state)
+            .ok_or("No collector solar irradiance")?;
+        assert!(v > 20., "v = {}", v);
+
+        Ok(())
+    }
+    #[test]
+    fn test_sensor_grids() -> Result<(), String> {
+        let mut model = Model::default();
+
+        let substance = Normal::new("the substance");
+        model.add_substance(substance.wrap());
+
+        let material = Material::new("the material", "the substance", 0.1);
+        model.add_material(material);
+
+        let mut construction = Construction::new("the construction");
+        construction.materials.push("the material".into());
+        model.add_construction(construction);
+
+        let s: Surface = json5::from_str(
+            "{
+            name: 'the roof',
+            construction:'the construction',
+            vertices: [
+                0, 0, 10, // X, Y and Z of Vertex 0
+                1, 0, 10, // X, Y and Z of Vertex 1
+                1, 1, 10, // X, Y and Z of Vertex 2
+                0, 1, 10  // ...
+            ]
+         }",
+        )
+        .map_err(|e| e.to_string())?;
+        model.add_surface(s)?;
+
+        // Outdoors, looking at the sky
+        let grid: SensorGrid = json5::from_str(
+            "{
+            name: 'the grid',
+            points: [5, 5, 1, 6, 5, 1]
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        model.add_sensor_grid(grid)?;
+
+        let meta_options = MetaOptions {
+            latitude: (-41.3 as Float).to_radians(),
+            longitude: (174.78 as Float).to_radians(),
+            standard_meridian: (180. as Float).to_radians(),
+            elevation: 0.0,
+            daylight_saving: None,
+        };
+
+        let mut state_header = SimulationStateHeader::new();
+        let mut options = SolarOptions::new();
+        options.set_n_solar_irradiance_points(10);
+        options.set_solar_ambient_divitions(300);
+        options.set_solar_sky_discretization(1);
+
+        let solar_model = SolarModel::new(&meta_options, options, &model, &mut state_header, 1)?;
+        assert_eq!(solar_model.optical_info.sensor_grids_dc.len(), 1);
+
+        let mut weather = SyntheticWeather::default();
+        weather.dew_point_temperature = Box::new(ScheduleConstant::new(11.));
+        weather.dry_bulb_temperature = Box::new(ScheduleConstant::new(24.));
+        weather.opaque_sky_cover = Box::new(ScheduleConstant::new(0.));
+        weather.direct_normal_radiation = Box::new(ScheduleConstant::new(400.));
+        weather.diffuse_horizontal_radiation = Box::new(ScheduleConstant::new(200.));
+
+        let mut state = state_header.take_values().ok_or("Could not take values")?;
+        solar_model.march(
+            Date {
+                month: 1,
+                day: 1,
+                hour: 12.,
+                year: None,
+            },
+            &weather,
+            &model,
+            &mut state,
+            &mut (),
+        )?;
+        let illuminance = model.sensor_grids[0]
+            .illuminance(&state)
+            .ok_or("No illuminance")?;
+        assert!(illuminance > 10000., "illuminance = {}", illuminance);
+
+        // Darkness at night
+        weather.direct_normal_radiation = Box::new(ScheduleConstant::new(0.));
+        weather.diffuse_horizontal_radiation = Box::new(ScheduleConstant::new(0.));
+        solar_model.march(
+            Date {
+                month: 1,
+                day: 1,
+                hour: 1.,
+                year: None,
+            },
+            &weather,
+            &model,
+            &mut state,
+            &mut (),
+        )?;
+        let illuminance = model.sensor_grids[0]
+            .illuminance(&state)
+            .ok_or("No illuminance")?;
+        assert!(illuminance.abs() < 1e-9, "illuminance = {}", illuminance);
+
+        Ok(())
+    }
 }
@@ -255,6 +255,33 @@ impl SolarSurface {
         Ok(ret)
     }
 
+    /// Builds a set of SolarSurfaces from panels (i.e., `Photovoltaic`
+    /// panels or `SolarCollector`s), based on their `vertices`.
+    ///
+    /// Panels installed on a `Surface` (i.e., with no `vertices`) take the
+    /// irradiance of that `Surface`, so they become a `SolarSurface` that
+    /// receives no sun (i.e., whose DC is zero).
+    pub fn make_panels<'a, I: Iterator<Item = Result<&'a Polygon3D, String>>>(
+        vertices: I,
+        n_rays: usize,
+    ) -> Result<Vec<SolarSurface>, String> {
+        let mut ret: Vec<SolarSurface> = Vec::new();
+
+        for v in vertices {
+            match v {
+                Ok(vertices) => ret.push(SolarSurface::new(n_rays, vertices, true, false)?),
+                Err(_) => ret.push(SolarSurface {
+                    points: Vec::new(),
+                    normal: Vector3D::new(0., 0., 1.),
+                    receives_sun_front: false,
+                    receives_sun_back: false,
+                }),
+            }
+        }
+
+        Ok(ret)
+    }
+
     /// Gets the front rays of a surface
     pub fn front_rays(&self) -> Vec<Ray3D> {
         self.points
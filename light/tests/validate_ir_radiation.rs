@@ -58,6 +58,7 @@ fn get_simple_results(
         longitude: lon.to_radians(),
         standard_meridian: std_mer.to_radians(),
         elevation: 0.0,
+        daylight_saving: None,
     };
 
     let zone_volume = 600.;
@@ -86,6 +87,7 @@ fn get_simple_results(
         month: 1,
         day: 1,
         hour: 0.5,
+        year: None,
     };
     let mut found = Vec::with_capacity(horizontal_ir.len());
     let mut expected = Vec::with_capacity(horizontal_ir.len());
@@ -52,6 +52,7 @@ fn get_simple_results(
         longitude: lon.to_radians(),
         standard_meridian: std_mer.to_radians(),
         elevation: 0.0,
+        daylight_saving: None,
     };
 
     let zone_volume = 600.;
@@ -80,6 +81,7 @@ fn get_simple_results(
         month: 1,
         day: 1,
         hour: 0.5,
+        year: None,
     };
     let mut ret = Vec::with_capacity(diffuse_horizontal_rad.len());
     for (diffuse_horizontal, direct_normal) in
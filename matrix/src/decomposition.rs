@@ -0,0 +1,445 @@
+use crate::Float;
+use crate::Matrix;
+
+/// Elements smaller than this are considered to be zero when factorizing
+const TINY: Float = 1e-26;
+
+/// Checks that a matrix to be factorized is square
+fn check_square(m: &Matrix, factorization: &str) -> Result<usize, String> {
+    let (nrows, ncols) = m.size();
+    if nrows != ncols {
+        return Err(format!(
+            "{} factorization only works for squared matrices... found {} by {}",
+            factorization, nrows, ncols
+        ));
+    }
+    Ok(nrows)
+}
+
+/// Checks that the right-hand side `b` of an $`A \times x=b`$ problem
+/// has as many rows as the factorized matrix
+fn check_rhs(n: usize, b: &Matrix) -> Result<(), String> {
+    if b.nrows != n {
+        return Err(format!(
+            "Solving Ax=b requires b to have as many rows as A... found {} and {}, respectively",
+            b.nrows, n
+        ));
+    }
+    Ok(())
+}
+
+/// The [LU decomposition](https://en.wikipedia.org/wiki/LU_decomposition) (with
+/// partial pivoting) of a square [`Matrix`] $`A`$, such that $`P \times A = L \times U`$.
+///
+/// Factorizing a matrix takes $`O(n^3)`$ operations, but then every
+/// $`A \times x=b`$ problem can be solved in $`O(n^2)`$ operations, so a
+/// matrix that does not change can be factorized once and used many times.
+///
+/// ```
+/// use matrix::Matrix;
+///
+/// let a = Matrix::from_data(2, 2, vec![0., 2., 1., 1.]);
+/// let lu = a.lu().unwrap();
+/// for rhs in [vec![2., 2.], vec![4., 3.]] {
+///     let b = Matrix::from_data(2, 1, rhs);
+///     let x = lu.solve(&b).unwrap();
+///     let mut check = Matrix::new(0., 2, 1);
+///     a.prod_into(&x, &mut check).unwrap();
+///     assert!(check.compare(&b));
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LuDecomposition {
+    /// $`L`$ (below the diagonal, whose ones are not stored)
+    /// and $`U`$ (on and above the diagonal), together
+    lu: Matrix,
+
+    /// The original row of each row of the factorized matrix
+    pivots: Vec<usize>,
+
+    /// Whether an odd number of rows were swapped
+    odd_swaps: bool,
+}
+
+impl LuDecomposition {
+    /// Factorizes a matrix. Returns an error if it is not square
+    /// or if it is singular.
+    pub fn new(a: &Matrix) -> Result<Self, String> {
+        let n = check_square(a, "LU")?;
+        let mut lu = a.clone();
+        let mut pivots: Vec<usize> = (0..n).collect();
+        let mut odd_swaps = false;
+
+        for c in 0..n {
+            // Choose the largest pivot
+            let p = (c..n)
+                .max_by(|i, j| {
+                    lu.data[lu.index(*i, c)]
+                        .abs()
+                        .total_cmp(&lu.data[lu.index(*j, c)].abs())
+                })
+                .expect("There should be a pivot");
+            let pivot = lu.data[lu.index(p, c)];
+            if pivot.abs() < TINY {
+                return Err(format!(
+                    "Found a (nearly) zero pivot: {}. The matrix is not invertible",
+                    pivot
+                ));
+            }
+            if p != c {
+                for col in 0..n {
+                    let (i, j) = (lu.index(c, col), lu.index(p, col));
+                    lu.data.swap(i, j);
+                }
+                pivots.swap(c, p);
+                odd_swaps = !odd_swaps;
+            }
+
+            for r in c + 1..n {
+                let i = lu.index(r, c);
+                let factor = lu.data[i] / pivot;
+                lu.data[i] = factor;
+                if factor != 0.0 {
+                    for col in c + 1..n {
+                        let from = lu.data[lu.index(c, col)];
+                        let into = lu.index(r, col);
+                        lu.data[into] -= factor * from;
+                    }
+                }
+            }
+        }
+        Ok(Self {
+            lu,
+            pivots,
+            odd_swaps,
+        })
+    }
+
+    /// Solves $`A \times x=b`$ for a single right-hand side, given as a
+    /// slice that is replaced by the solution
+    pub fn solve_slice(&self, b: &mut [Float]) -> Result<(), String> {
+        let n = self.pivots.len();
+        if b.len() != n {
+            return Err(format!(
+                "Solving Ax=b requires b to have as many rows as A... found {} and {}, respectively",
+                b.len(),
+                n
+            ));
+        }
+        let permuted: Vec<Float> = self.pivots.iter().map(|p| b[*p]).collect();
+        b.copy_from_slice(&permuted);
+
+        // Forward substitution (L has ones in the diagonal)
+        for r in 1..n {
+            let row = &self.lu.data[r * n..r * n + r];
+            let s: Float = row.iter().zip(b[..r].iter()).map(|(l, x)| l * x).sum();
+            b[r] -= s;
+        }
+        // Backward substitution
+        for r in (0..n).rev() {
+            let row = &self.lu.data[r * n..(r + 1) * n];
+            let s: Float = row[r + 1..]
+                .iter()
+                .zip(b[r + 1..].iter())
+                .map(|(u, x)| u * x)
+                .sum();
+            b[r] = (b[r] - s) / row[r];
+        }
+        Ok(())
+    }
+
+    /// Solves $`A \times X=B`$, where each column of `b` is a right-hand
+    /// side. The solution replaces `b`.
+    pub fn solve_into(&self, b: &mut Matrix) -> Result<(), String> {
+        let n = self.pivots.len();
+        check_rhs(n, b)?;
+        let mut column = vec![0.0; n];
+        for c in 0..b.ncols {
+            for (r, v) in column.iter_mut().enumerate() {
+                *v = b.data[b.index(r, c)];
+            }
+            self.solve_slice(&mut column)?;
+            for (r, v) in column.iter().enumerate() {
+                let i = b.index(r, c);
+                b.data[i] = *v;
+            }
+        }
+        Ok(())
+    }
+
+    /// Solves $`A \times X=B`$, where each column of `b` is a right-hand side
+    pub fn solve(&self, b: &Matrix) -> Result<Matrix, String> {
+        let mut x = b.clone();
+        self.solve_into(&mut x)?;
+        Ok(x)
+    }
+
+    /// The inverse of the factorized matrix
+    pub fn inverse(&self) -> Result<Matrix, String> {
+        let mut ret = Matrix::eye(self.pivots.len());
+        self.solve_into(&mut ret)?;
+        Ok(ret)
+    }
+
+    /// The determinant of the factorized matrix
+    pub fn determinant(&self) -> Float {
+        let n = self.pivots.len();
+        let d: Float = (0..n).map(|i| self.lu.data[self.lu.index(i, i)]).product();
+        if self.odd_swaps {
+            -d
+        } else {
+            d
+        }
+    }
+}
+
+/// The [Cholesky decomposition](https://en.wikipedia.org/wiki/Cholesky_decomposition)
+/// of a symmetric, positive-definite [`Matrix`] $`A`$ (e.g., a thermal
+/// network), such that $`A = L \times L^T`$.
+///
+/// It takes about half the operations of an [`LuDecomposition`], and
+/// it is numerically stable without pivoting.
+#[derive(Debug, Clone)]
+pub struct CholeskyDecomposition {
+    /// The lower-triangular factor $`L`$
+    l: Matrix,
+}
+
+impl CholeskyDecomposition {
+    /// Factorizes a matrix. Returns an error if it is not square or if
+    /// it is not positive-definite. Only the lower triangle of the matrix
+    /// is used (i.e., it is assumed to be symmetric).
+    pub fn new(a: &Matrix) -> Result<Self, String> {
+        let n = check_square(a, "Cholesky")?;
+        let mut l = Matrix::new(0.0, n, n);
+        for r in 0..n {
+            for c in 0..=r {
+                let s: Float = (0..c)
+                    .map(|k| l.data[l.index(r, k)] * l.data[l.index(c, k)])
+                    .sum();
+                let v = a.data[a.index(r, c)] - s;
+                let i = l.index(r, c);
+                if r == c {
+                    if v <= TINY {
+                        return Err(format!(
+                            "Cholesky factorization requires a positive-definite matrix... found a pivot of {}",
+                            v
+                        ));
+                    }
+                    l.data[i] = v.sqrt();
+                } else {
+                    l.data[i] = v / l.data[l.index(c, c)];
+                }
+            }
+        }
+        Ok(Self { l })
+    }
+
+    /// Solves $`A \times x=b`$ for a single right-hand side, given as a
+    /// slice that is replaced by the solution
+    pub fn solve_slice(&self, b: &mut [Float]) -> Result<(), String> {
+        let n = self.l.nrows;
+        if b.len() != n {
+            return Err(format!(
+                "Solving Ax=b requires b to have as many rows as A... found {} and {}, respectively",
+                b.len(),
+                n
+            ));
+        }
+        // L y = b
+        for r in 0..n {
+            let row = &self.l.data[r * n..(r + 1) * n];
+            let s: Float = row[..r].iter().zip(b[..r].iter()).map(|(l, y)| l * y).sum();
+            b[r] = (b[r] - s) / row[r];
+        }
+        // L^T x = y
+        for r in (0..n).rev() {
+            let s: Float = (r + 1..n).map(|k| self.l.data[k * n + r] * b[k]).sum();
+            b[r] = (b[r] - s) / self.l.data[r * n + r];
+        }
+        Ok(())
+    }
+
+    /// Solves $`A \times X=B`$, where each column of `b` is a right-hand
+    /// side. The solution replaces `b`.
+    pub fn solve_into(&self, b: &mut Matrix) -> Result<(), String> {
+        let n = self.l.nrows;
+        check_rhs(n, b)?;
+        let mut column = vec![0.0; n];
+        for c in 0..b.ncols {
+            for (r, v) in column.iter_mut().enumerate() {
+                *v = b.data[b.index(r, c)];
+            }
+            self.solve_slice(&mut column)?;
+            for (r, v) in column.iter().enumerate() {
+                let i = b.index(r, c);
+                b.data[i] = *v;
+            }
+        }
+        Ok(())
+    }
+
+    /// Solves $`A \times X=B`$, where each column of `b` is a right-hand side
+    pub fn solve(&self, b: &Matrix) -> Result<Matrix, String> {
+        let mut x = b.clone();
+        self.solve_into(&mut x)?;
+        Ok(x)
+    }
+}
+
+impl Matrix {
+    /// Calculates the [`LuDecomposition`] of the matrix, which can
+    /// be used for solving many $`A \times x=b`$ problems
+    pub fn lu(&self) -> Result<LuDecomposition, String> {
+        LuDecomposition::new(self)
+    }
+
+    /// Calculates the [`CholeskyDecomposition`] of a symmetric and positive-definite
+    /// matrix, which can be used for solving many $`A \times x=b`$ problems
+    pub fn cholesky(&self) -> Result<CholeskyDecomposition, String> {
+        CholeskyDecomposition::new(self)
+    }
+
+    /// The largest distance between the diagonal and a non-zero element
+    fn half_bandwidth(&self) -> usize {
+        let mut ret = 0;
+        for r in 0..self.nrows {
+            for c in 0..self.ncols {
+                if self.data[self.index(r, c)] != 0.0 {
+                    ret = ret.max(r.abs_diff(c));
+                }
+            }
+        }
+        ret
+    }
+
+    /// Solves $`A \times X=B`$, where each column of `b` is a right-hand side.
+    ///
+    /// N-diagonal matrices (e.g., tri-diagonal ones) are solved through
+    /// [`Matrix::n_diag_gaussian`], which is much faster. Other matrices—or
+    /// those for which such method fails—are solved through an [`LuDecomposition`].
+    /// When solving several problems with the same matrix, factorizing it
+    /// once (see [`Matrix::lu`]) is faster.
+    pub fn solve(&self, b: &Matrix) -> Result<Matrix, String> {
+        let n = check_square(self, "LU")?;
+        check_rhs(n, b)?;
+        let band = 2 * self.half_bandwidth() + 1;
+        if band < n {
+            if let Ok(x) = self.n_diag_gaussian(b, band) {
+                return Ok(x);
+            }
+        }
+        self.lu()?.solve(b)
+    }
+
+    /// Calculates the inverse of the matrix, through an [`LuDecomposition`].
+    ///
+    /// Inverting a matrix is rarely needed: solving $`A \times x=b`$
+    /// problems (see [`Matrix::solve`]) is faster and more accurate.
+    pub fn inverse(&self) -> Result<Matrix, String> {
+        self.lu()?.inverse()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn close(a: &Matrix, b: &Matrix) -> bool {
+        // Looser when `Float` is `f32`
+        let tol = Float::max(1e-6, 1e3 * Float::EPSILON);
+        a.size() == b.size()
+            && a.as_slice()
+                .iter()
+                .zip(b.as_slice().iter())
+                .all(|(a, b)| (a - b).abs() < tol)
+    }
+
+    fn prod(a: &Matrix, b: &Matrix) -> Matrix {
+        let mut ret = Matrix::new(0., a.size().0, b.size().1);
+        a.prod_into(b, &mut ret).unwrap();
+        ret
+    }
+
+    #[test]
+    fn test_lu() -> Result<(), String> {
+        // Needs pivoting
+        let a = Matrix::from_data(3, 3, vec![0., 2., 1., 1., 1., 0., 3., 0., 1.]);
+        let lu = a.lu()?;
+        assert!((lu.determinant() - (-5.)).abs() < 1e-9);
+
+        // Batched solve
+        let b = Matrix::from_data(3, 2, vec![1., 2., 3., 4., 5., 6.]);
+        let x = lu.solve(&b)?;
+        assert!(close(&prod(&a, &x), &b));
+
+        let inv = a.inverse()?;
+        assert!(close(&prod(&a, &inv), &Matrix::eye(3)));
+
+        let mut b = vec![1., 3., 5.];
+        lu.solve_slice(&mut b)?;
+        assert!(close(
+            &Matrix::from_data(3, 1, b),
+            &Matrix::from_data(3, 1, x.as_slice().iter().step_by(2).copied().collect())
+        ));
+
+        // Errors
+        let singular = Matrix::from_data(2, 2, vec![1., 2., 2., 4.]);
+        assert!(singular.lu().is_err());
+        assert!(Matrix::new(1., 2, 3).lu().is_err());
+        assert!(lu.solve(&Matrix::new(1., 2, 1)).is_err());
+        assert!(lu.solve_slice(&mut [1., 2.]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_cholesky() -> Result<(), String> {
+        let a = Matrix::from_data(3, 3, vec![4., 12., -16., 12., 37., -43., -16., -43., 98.]);
+        let cholesky = a.cholesky()?;
+        let expected = Matrix::from_data(3, 3, vec![2., 0., 0., 6., 1., 0., -8., 5., 3.]);
+        assert!(close(&cholesky.l, &expected));
+
+        let b = Matrix::from_data(3, 2, vec![1., 0., 0., 1., 2., 3.]);
+        let x = cholesky.solve(&b)?;
+        assert!(close(&prod(&a, &x), &b));
+        assert!(close(&x, &a.lu()?.solve(&b)?));
+
+        // Not positive-definite
+        let a = Matrix::from_data(2, 2, vec![1., 2., 2., 1.]);
+        assert!(a.cholesky().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_solve() -> Result<(), String> {
+        // Tri-diagonal... uses the fast path
+        let mut a = Matrix::new(0., 5, 5);
+        for i in 0..5 {
+            a.set(i, i, 2.)?;
+            if i > 0 {
+                a.set(i, i - 1, -1.)?;
+                a.set(i - 1, i, -1.)?;
+            }
+        }
+        assert_eq!(a.half_bandwidth(), 1);
+        let b = Matrix::from_data(5, 1, vec![1., 0., 0., 0., 1.]);
+        let x = a.solve(&b)?;
+        assert!(close(&x, &Matrix::from_data(5, 1, vec![1.; 5])));
+
+        // Tri-diagonal, but with a zero in the diagonal... falls back to LU
+        let a = Matrix::from_data(
+            4,
+            4,
+            vec![
+                0., 1., 0., 0., 1., 0., 1., 0., 0., 1., 2., 1., 0., 0., 1., 2.,
+            ],
+        );
+        assert!(a.n_diag_gaussian(&Matrix::new(1., 4, 1), 3).is_err());
+        let b = Matrix::from_data(4, 1, vec![1., 2., 3., 4.]);
+        let x = a.solve(&b)?;
+        assert!(close(&prod(&a, &x), &b));
+
+        assert!(Matrix::new(1., 2, 3).solve(&b).is_err());
+        Ok(())
+    }
+}
@@ -247,6 +247,9 @@ impl<T: Numberish> GenericMatrix<T> {
 
         // Clear
         *into *= T::zero();
+        if self.ncols == 0 || other.ncols == 0 {
+            return Ok(());
+        }
 
         // Multiply.
         let self_rows: Vec<&[T]> = self.data.chunks_exact(self.ncols).collect();
@@ -260,14 +263,13 @@ impl<T: Numberish> GenericMatrix<T> {
             .zip(into.data.par_chunks_exact_mut(other.ncols));
 
         self_rows.for_each(|(row_data, into_data)| {
-            for (col, item) in into_data.iter_mut().enumerate().take(other.ncols) {
-                let coldata = other.data.iter().skip(col).step_by(other.ncols);
-                let row_data = row_data.iter();
-                let aux = row_data
-                    .zip(coldata)
-                    .map(|(a, b)| *a * *b)
-                    .fold(T::zero(), |acc, val| acc + val);
-                *item = aux;
+            // Adding up scaled rows of `other` (instead of walking down its
+            // columns) reads memory contiguously, which allows the compiler
+            // to vectorize the inner loop
+            for (a, other_row) in row_data.iter().zip(other.data.chunks_exact(other.ncols)) {
+                for (item, b) in into_data.iter_mut().zip(other_row.iter()) {
+                    *item += *a * *b;
+                }
             }
         });
 
@@ -67,3 +67,13 @@ pub use crate::n_diag_generic_matrix::NDiagGenericMatrix;
 /// a more compact version of an N-Diagonal matrix for floating point numbers
 pub mod n_diag_matrix;
 pub use crate::n_diag_matrix::NDiagMatrix;
+
+/// LU and Cholesky factorizations, for solving many linear
+/// systems with the same matrix
+pub mod decomposition;
+pub use crate::decomposition::{CholeskyDecomposition, LuDecomposition};
+
+/// A compressed-sparse-row matrix of floating point numbers, with
+/// iterative solvers
+pub mod sparse_matrix;
+pub use crate::sparse_matrix::SparseMatrix;
@@ -202,4 +202,98 @@ impl Matrix {
             max_err,
         ))
     }
+
+    /// Multiplies `self` by `other`, putting the result into `into`.
+    ///
+    /// If the `faer` feature is enabled, this uses the SIMD-accelerated and
+    /// cache-blocked kernels of [faer](https://docs.rs/faer), which are much
+    /// faster for large matrices (e.g., multiplying daylight coefficients by
+    /// a year of sky vectors). Otherwise, it is the same as [`GenericMatrix::prod_into`].
+    pub fn fast_prod_into(&self, other: &Matrix, into: &mut Matrix) -> Result<(), String> {
+        #[cfg(feature = "faer")]
+        {
+            if self.ncols != other.nrows {
+                return Err("Size mismatch for Matrix multiplication".to_string());
+            }
+            if into.nrows != self.nrows || into.ncols != other.ncols {
+                return Err("Result matrix size mismatch for Matrix multiplication".to_string());
+            }
+            let lhs = faer::MatRef::from_row_major_slice(&self.data, self.nrows, self.ncols);
+            let rhs = faer::MatRef::from_row_major_slice(&other.data, other.nrows, other.ncols);
+            let acc =
+                faer::MatMut::from_row_major_slice_mut(&mut into.data, into.nrows, into.ncols);
+
+            #[cfg(feature = "parallel")]
+            let parallelism = faer::Par::rayon(0);
+            #[cfg(not(feature = "parallel"))]
+            let parallelism = faer::Par::Seq;
+
+            // `Replace` means that whatever is in `into` is overwritten
+            faer::linalg::matmul::matmul(acc, faer::Accum::Replace, lhs, rhs, 1.0, parallelism);
+            Ok(())
+        }
+        #[cfg(not(feature = "faer"))]
+        self.prod_into(other, into)
+    }
+
+    /// Multiplies `self` by `other`, returning a new matrix. See [`Matrix::fast_prod_into`].
+    pub fn fast_prod(&self, other: &Matrix) -> Result<Matrix, String> {
+        let mut ret = Matrix::new(0.0, self.nrows, other.ncols);
+        self.fast_prod_into(other, &mut ret)?;
+        Ok(ret)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fast_prod() -> Result<(), String> {
+        let a = Matrix::from_data(2, 3, vec![1., 2., 3., 4., 5., 6.]);
+        let b = Matrix::from_data(3, 2, vec![7., 8., 9., 10., 11., 12.]);
+        let expected = Matrix::from_data(2, 2, vec![58., 64., 139., 154.]);
+        assert!(a.fast_prod(&b)?.compare(&expected));
+        assert!((&a * &b).compare(&expected));
+
+        // The result is overwritten, not accumulated
+        let mut into = Matrix::new(1.0, 2, 2);
+        a.fast_prod_into(&b, &mut into)?;
+        assert!(into.compare(&expected));
+
+        assert!(a.fast_prod(&a).is_err());
+        assert!(a.fast_prod_into(&b, &mut Matrix::new(0.0, 3, 3)).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_fast_prod_matches_generic() -> Result<(), String> {
+        // Large and oddly shaped, so faer's blocked kernels and their
+        // remainders are exercised when the feature is enabled
+        let (n, k, m) = (67, 145, 33);
+        let a = Matrix::from_data(
+            n,
+            k,
+            (0..n * k).map(|i| ((i * 7) % 13) as Float - 6.).collect(),
+        );
+        let b = Matrix::from_data(
+            k,
+            m,
+            (0..k * m).map(|i| ((i * 5) % 11) as Float / 3.).collect(),
+        );
+
+        let generic = &a * &b;
+        let fast = a.fast_prod(&b)?;
+        assert_eq!(fast.size(), (n, m));
+        for r in 0..n {
+            for c in 0..m {
+                let (exp, found) = (generic.get(r, c)?, fast.get(r, c)?);
+                assert!(
+                    (exp - found).abs() <= 1e-4 * exp.abs().max(1.),
+                    "({r},{c}): expecting {exp}, found {found}"
+                );
+            }
+        }
+        Ok(())
+    }
 }
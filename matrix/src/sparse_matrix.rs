@@ -0,0 +1,554 @@
+use crate::Float;
+use crate::Matrix;
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// A matrix of `Float` stored in [Compressed Sparse Row](https://en.wikipedia.org/wiki/Sparse_matrix#Compressed_sparse_row_(CSR,_CRS_or_Yale_format))
+/// format; that is, only its non-zero elements are stored, row by row.
+///
+/// This is much more compact than a [`Matrix`] when most of the elements are
+/// zero (e.g., daylight-coefficient matrices, or the thermal network of a
+/// building with many zones), and matrix-vector products only take time
+/// proportional to the number of non-zero elements.
+///
+/// ```
+/// use matrix::SparseMatrix;
+///
+/// // [ 4 -1  0 ]
+/// // [-1  4 -1 ]
+/// // [ 0 -1  4 ]
+/// let a = SparseMatrix::from_triplets(
+///     3,
+///     3,
+///     &[(0, 0, 4.), (0, 1, -1.), (1, 0, -1.), (1, 1, 4.), (1, 2, -1.), (2, 1, -1.), (2, 2, 4.)],
+/// )
+/// .unwrap();
+/// assert_eq!(a.nnz(), 7);
+///
+/// let b = vec![3., 2., 3.];
+/// let mut x = vec![0.; 3];
+/// a.conjugate_gradient(&b, &mut x, 100, 1e-9).unwrap();
+/// assert!(x.iter().all(|v| (v - 1.).abs() < 1e-6));
+/// ```
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+pub struct SparseMatrix {
+    pub(crate) nrows: usize,
+    pub(crate) ncols: usize,
+
+    // The position in `col_indices` and `values` at which each
+    // row starts, plus the total number of non-zero elements
+    row_starts: Vec<usize>,
+
+    // The column of each non-zero element, sorted within each row
+    col_indices: Vec<usize>,
+
+    // The value of each non-zero element
+    values: Vec<Float>,
+}
+
+/// The Euclidean norm of a vector
+fn norm(v: &[Float]) -> Float {
+    dot(v, v).sqrt()
+}
+
+/// The dot product of two vectors
+fn dot(a: &[Float], b: &[Float]) -> Float {
+    a.iter().zip(b.iter()).map(|(a, b)| a * b).sum()
+}
+
+impl SparseMatrix {
+    /// Creates a `SparseMatrix` out of its non-zero elements, given as
+    /// `(row, column, value)` triplets in any order. Repeated positions
+    /// are added up, and explicit zeroes are not stored.
+    pub fn from_triplets(
+        nrows: usize,
+        ncols: usize,
+        triplets: &[(usize, usize, Float)],
+    ) -> Result<Self, String> {
+        if let Some((r, c, _)) = triplets.iter().find(|(r, c, _)| *r >= nrows || *c >= ncols) {
+            return Err(format!(
+                "Element ({}, {}) is out of bounds of a {} by {} SparseMatrix",
+                r, c, nrows, ncols
+            ));
+        }
+        let mut sorted = triplets.to_vec();
+        sorted.sort_by_key(|(r, c, _)| (*r, *c));
+
+        let mut row_starts = vec![0; nrows + 1];
+        let mut col_indices: Vec<usize> = Vec::with_capacity(sorted.len());
+        let mut values: Vec<Float> = Vec::with_capacity(sorted.len());
+        let mut last: Option<(usize, usize)> = None;
+        for (r, c, v) in sorted {
+            if last == Some((r, c)) {
+                *values.last_mut().expect("There should be a value") += v;
+            } else {
+                col_indices.push(c);
+                values.push(v);
+                row_starts[r + 1] += 1;
+                last = Some((r, c));
+            }
+        }
+        for r in 0..nrows {
+            row_starts[r + 1] += row_starts[r];
+        }
+
+        let mut ret = Self {
+            nrows,
+            ncols,
+            row_starts,
+            col_indices,
+            values,
+        };
+        ret.remove_zeroes();
+        Ok(ret)
+    }
+
+    /// Creates a `SparseMatrix` with the non-zero elements of a [`Matrix`]
+    pub fn from_dense(m: &Matrix) -> Self {
+        let (nrows, ncols) = m.size();
+        let mut row_starts = Vec::with_capacity(nrows + 1);
+        let mut col_indices = Vec::new();
+        let mut values = Vec::new();
+        row_starts.push(0);
+        for row in m.data.chunks_exact(ncols.max(1)).take(nrows) {
+            for (c, v) in row.iter().enumerate() {
+                if *v != 0.0 {
+                    col_indices.push(c);
+                    values.push(*v);
+                }
+            }
+            row_starts.push(values.len());
+        }
+        row_starts.resize(nrows + 1, values.len());
+        Self {
+            nrows,
+            ncols,
+            row_starts,
+            col_indices,
+            values,
+        }
+    }
+
+    /// Creates a [`Matrix`] with all the elements of the `SparseMatrix`
+    pub fn to_dense(&self) -> Matrix {
+        let mut m = Matrix::new(0.0, self.nrows, self.ncols);
+        for r in 0..self.nrows {
+            for (c, v) in self.row(r) {
+                m.data[r * self.ncols + c] = v;
+            }
+        }
+        m
+    }
+
+    /// Removes the elements that are exactly zero
+    fn remove_zeroes(&mut self) {
+        let mut row_starts = Vec::with_capacity(self.nrows + 1);
+        let mut col_indices = Vec::with_capacity(self.col_indices.len());
+        let mut values = Vec::with_capacity(self.values.len());
+        row_starts.push(0);
+        for r in 0..self.nrows {
+            for (c, v) in self.row(r) {
+                if v != 0.0 {
+                    col_indices.push(c);
+                    values.push(v);
+                }
+            }
+            row_starts.push(values.len());
+        }
+        self.row_starts = row_starts;
+        self.col_indices = col_indices;
+        self.values = values;
+    }
+
+    /// Iterates over the non-zero elements of a row, as `(column, value)`
+    fn row(&self, nrow: usize) -> impl Iterator<Item = (usize, Float)> + '_ {
+        let (start, end) = (self.row_starts[nrow], self.row_starts[nrow + 1]);
+        self.col_indices[start..end]
+            .iter()
+            .copied()
+            .zip(self.values[start..end].iter().copied())
+    }
+
+    /// Gets the number of rows and columns
+    pub fn size(&self) -> (usize, usize) {
+        (self.nrows, self.ncols)
+    }
+
+    /// Gets the number of non-zero elements
+    pub fn nnz(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Gets an element from the matrix
+    pub fn get(&self, nrow: usize, ncol: usize) -> Result<Float, String> {
+        if nrow >= self.nrows || ncol >= self.ncols {
+            return Err("Row or Column out of bounds.".to_string());
+        }
+        let (start, end) = (self.row_starts[nrow], self.row_starts[nrow + 1]);
+        match self.col_indices[start..end].binary_search(&ncol) {
+            Ok(i) => Ok(self.values[start + i]),
+            Err(_) => Ok(0.0),
+        }
+    }
+
+    /// Multiplies the matrix by a vector `x`, putting the result into `into`
+    pub fn mul_vec_into(&self, x: &[Float], into: &mut [Float]) -> Result<(), String> {
+        if x.len() != self.ncols {
+            return Err(format!("Size mismatch for SparseMatrix-vector multiplication... the matrix has {} columns, but the vector has {} elements", self.ncols, x.len()));
+        }
+        if into.len() != self.nrows {
+            return Err(format!("Result vector size mismatch for SparseMatrix-vector multiplication... expecting {} elements, found {}", self.nrows, into.len()));
+        }
+
+        #[cfg(not(feature = "parallel"))]
+        let rows = into.iter_mut().enumerate();
+        #[cfg(feature = "parallel")]
+        let rows = into.par_iter_mut().enumerate();
+
+        rows.for_each(|(r, item)| {
+            *item = self.row(r).map(|(c, v)| v * x[c]).sum();
+        });
+        Ok(())
+    }
+
+    /// Multiplies the matrix by a vector `x`, returning the result
+    pub fn mul_vec(&self, x: &[Float]) -> Result<Vec<Float>, String> {
+        let mut ret = vec![0.0; self.nrows];
+        self.mul_vec_into(x, &mut ret)?;
+        Ok(ret)
+    }
+
+    /// Multiplies the matrix by a dense matrix `other`, putting the result
+    /// into `into` (e.g., a matrix of daylight coefficients by several
+    /// sky vectors)
+    pub fn prod_into(&self, other: &Matrix, into: &mut Matrix) -> Result<(), String> {
+        let (other_rows, other_cols) = other.size();
+        if self.ncols != other_rows {
+            return Err("Size mismatch for SparseMatrix multiplication".to_string());
+        }
+        if into.size() != (self.nrows, other_cols) {
+            return Err("Result matrix size mismatch for SparseMatrix multiplication".to_string());
+        }
+
+        #[cfg(not(feature = "parallel"))]
+        let rows = into.data.chunks_exact_mut(other_cols.max(1)).enumerate();
+        #[cfg(feature = "parallel")]
+        let rows = into
+            .data
+            .par_chunks_exact_mut(other_cols.max(1))
+            .enumerate();
+
+        rows.for_each(|(r, into_row)| {
+            into_row.iter_mut().for_each(|v| *v = 0.0);
+            for (c, v) in self.row(r) {
+                let other_row = &other.data[c * other_cols..(c + 1) * other_cols];
+                for (to, from) in into_row.iter_mut().zip(other_row.iter()) {
+                    *to += v * from;
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// Checks that the problem $`A \times x=b`$ is well posed for an iterative `solver`
+    fn check_system(&self, b: &[Float], x: &[Float], solver: &str) -> Result<(), String> {
+        if self.ncols != self.nrows {
+            return Err(format!("{} algorithm (for solving Ax=b) only works for squared matrices A... found A to be {} by {}", solver, self.nrows, self.ncols));
+        }
+        if self.nrows != b.len() || self.ncols != x.len() {
+            return Err(format!("{} algorithm (for solving Ax=b) requires b and x to have as many elements as A has rows... found {}, {} and {}, respectively", solver, b.len(), x.len(), self.nrows));
+        }
+        Ok(())
+    }
+
+    /// Solves an $`A \times x=b`$ problem using the [Conjugate Gradient](https://en.wikipedia.org/wiki/Conjugate_gradient_method)
+    /// method, which requires $`A`$ to be symmetric and positive-definite
+    /// (e.g., a thermal network). The initial value of `x` is used as the
+    /// first guess, and it is replaced by the solution.
+    ///
+    /// Iterates until the norm of the residual is smaller than `tolerance`
+    /// times the norm of `b`, returning the number of iterations; or returns
+    /// an error if this does not happen within `max_iter` iterations.
+    pub fn conjugate_gradient(
+        &self,
+        b: &[Float],
+        x: &mut [Float],
+        max_iter: usize,
+        tolerance: Float,
+    ) -> Result<usize, String> {
+        self.check_system(b, x, "Conjugate Gradient")?;
+        let n = self.nrows;
+        let target = tolerance * norm(b);
+
+        let mut r = self.mul_vec(x)?;
+        r.iter_mut().zip(b.iter()).for_each(|(r, b)| *r = b - *r);
+        let mut p = r.clone();
+        let mut ap = vec![0.0; n];
+        let mut rr = dot(&r, &r);
+        if rr.sqrt() <= target {
+            return Ok(0);
+        }
+
+        for it in 1..=max_iter {
+            self.mul_vec_into(&p, &mut ap)?;
+            let pap = dot(&p, &ap);
+            if pap == 0.0 {
+                return Err(
+                    "Conjugate Gradient algorithm broke down... is the matrix positive-definite?"
+                        .to_string(),
+                );
+            }
+            let alpha = rr / pap;
+            x.iter_mut()
+                .zip(p.iter())
+                .for_each(|(x, p)| *x += alpha * p);
+            r.iter_mut()
+                .zip(ap.iter())
+                .for_each(|(r, ap)| *r -= alpha * ap);
+            let new_rr = dot(&r, &r);
+            if new_rr.sqrt() <= target {
+                return Ok(it);
+            }
+            let beta = new_rr / rr;
+            p.iter_mut()
+                .zip(r.iter())
+                .for_each(|(p, r)| *p = r + beta * *p);
+            rr = new_rr;
+        }
+
+        Err(format!(
+            "Conjugate Gradient algorithm did not converge after {} iterations. Expecting residual {}... found {}",
+            max_iter,
+            target,
+            rr.sqrt(),
+        ))
+    }
+
+    /// Solves an $`A \times x=b`$ problem using the [BiCGSTAB](https://en.wikipedia.org/wiki/Biconjugate_gradient_stabilized_method)
+    /// method, which works for non-symmetric matrices $`A`$ as well. The
+    /// initial value of `x` is used as the first guess, and it is replaced
+    /// by the solution.
+    ///
+    /// Iterates until the norm of the residual is smaller than `tolerance`
+    /// times the norm of `b`, returning the number of iterations; or returns
+    /// an error if this does not happen within `max_iter` iterations.
+    pub fn bicgstab(
+        &self,
+        b: &[Float],
+        x: &mut [Float],
+        max_iter: usize,
+        tolerance: Float,
+    ) -> Result<usize, String> {
+        self.check_system(b, x, "BiCGSTAB")?;
+        let n = self.nrows;
+        let target = tolerance * norm(b);
+
+        let mut r = self.mul_vec(x)?;
+        r.iter_mut().zip(b.iter()).for_each(|(r, b)| *r = b - *r);
+        if norm(&r) <= target {
+            return Ok(0);
+        }
+        let r0 = r.clone();
+        let mut rho = 1.0;
+        let mut alpha = 1.0;
+        let mut omega = 1.0;
+        let mut v = vec![0.0; n];
+        let mut p = vec![0.0; n];
+        let mut s = vec![0.0; n];
+        let mut t = vec![0.0; n];
+
+        for it in 1..=max_iter {
+            let new_rho = dot(&r0, &r);
+            if new_rho == 0.0 || omega == 0.0 {
+                return Err("BiCGSTAB algorithm broke down".to_string());
+            }
+            let beta = (new_rho / rho) * (alpha / omega);
+            rho = new_rho;
+            for ((p, r), v) in p.iter_mut().zip(r.iter()).zip(v.iter()) {
+                *p = r + beta * (*p - omega * v);
+            }
+            self.mul_vec_into(&p, &mut v)?;
+            let r0v = dot(&r0, &v);
+            if r0v == 0.0 {
+                return Err("BiCGSTAB algorithm broke down".to_string());
+            }
+            alpha = rho / r0v;
+            for ((s, r), v) in s.iter_mut().zip(r.iter()).zip(v.iter()) {
+                *s = r - alpha * v;
+            }
+            if norm(&s) <= target {
+                x.iter_mut()
+                    .zip(p.iter())
+                    .for_each(|(x, p)| *x += alpha * p);
+                return Ok(it);
+            }
+            self.mul_vec_into(&s, &mut t)?;
+            let tt = dot(&t, &t);
+            omega = if tt == 0.0 { 0.0 } else { dot(&t, &s) / tt };
+            for (((x, r), p), (s, t)) in x
+                .iter_mut()
+                .zip(r.iter_mut())
+                .zip(p.iter())
+                .zip(s.iter().zip(t.iter()))
+            {
+                *x += alpha * p + omega * s;
+                *r = s - omega * t;
+            }
+            if norm(&r) <= target {
+                return Ok(it);
+            }
+        }
+
+        Err(format!(
+            "BiCGSTAB algorithm did not converge after {} iterations. Expecting residual {}... found {}",
+            max_iter,
+            target,
+            norm(&r),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 1D Laplacian (i.e., tri-diagonal, symmetric and positive-definite)
+    fn laplacian(n: usize) -> SparseMatrix {
+        let mut triplets = Vec::new();
+        for i in 0..n {
+            triplets.push((i, i, 2.));
+            if i > 0 {
+                triplets.push((i, i - 1, -1.));
+            }
+            if i + 1 < n {
+                triplets.push((i, i + 1, -1.));
+            }
+        }
+        SparseMatrix::from_triplets(n, n, &triplets).unwrap()
+    }
+
+    #[test]
+    fn test_from_triplets() -> Result<(), String> {
+        let m = SparseMatrix::from_triplets(
+            3,
+            4,
+            &[(2, 3, 1.), (0, 1, 2.), (0, 1, 3.), (1, 0, 0.), (2, 0, -1.)],
+        )?;
+        assert_eq!(m.size(), (3, 4));
+        // Repeated elements are added, zeroes are not stored
+        assert_eq!(m.nnz(), 3);
+        assert_eq!(m.get(0, 1)?, 5.);
+        assert_eq!(m.get(1, 0)?, 0.);
+        assert_eq!(m.get(2, 0)?, -1.);
+        assert_eq!(m.get(2, 3)?, 1.);
+        assert!(m.get(3, 0).is_err());
+
+        assert!(SparseMatrix::from_triplets(2, 2, &[(2, 0, 1.)]).is_err());
+
+        let empty = SparseMatrix::from_triplets(2, 2, &[])?;
+        assert_eq!(empty.nnz(), 0);
+        assert_eq!(empty.mul_vec(&[1., 1.])?, vec![0., 0.]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_dense() -> Result<(), String> {
+        let dense = Matrix::from_data(2, 3, vec![1., 0., 2., 0., 0., 3.]);
+        let sparse = SparseMatrix::from_dense(&dense);
+        assert_eq!(sparse.nnz(), 3);
+        assert!(sparse.to_dense().compare(&dense));
+        for r in 0..2 {
+            for c in 0..3 {
+                assert_eq!(sparse.get(r, c)?, dense.get(r, c)?);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_products() -> Result<(), String> {
+        let dense = Matrix::from_data(2, 3, vec![1., 0., 2., 0., 4., 3.]);
+        let sparse = SparseMatrix::from_dense(&dense);
+
+        assert_eq!(sparse.mul_vec(&[1., 2., 3.])?, vec![7., 17.]);
+        assert!(sparse.mul_vec(&[1., 2.]).is_err());
+        assert!(sparse.mul_vec_into(&[1., 2., 3.], &mut [0.; 3]).is_err());
+
+        let other = Matrix::from_data(3, 2, vec![1., 2., 3., 4., 5., 6.]);
+        let mut expected = Matrix::new(0., 2, 2);
+        dense.prod_into(&other, &mut expected)?;
+        let mut found = Matrix::new(1., 2, 2);
+        sparse.prod_into(&other, &mut found)?;
+        assert!(found.compare(&expected));
+        assert!(sparse.prod_into(&dense, &mut found).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_conjugate_gradient() -> Result<(), String> {
+        let n = 50;
+        let a = laplacian(n);
+        let expected: Vec<Float> = (0..n).map(|i| (i as Float).sin()).collect();
+        let b = a.mul_vec(&expected)?;
+
+        let mut x = vec![0.; n];
+        let iterations = a.conjugate_gradient(&b, &mut x, 1000, 1e-6)?;
+        // CG converges in at most n iterations (in exact arithmetic)
+        assert!(iterations <= n + 5);
+        for (x, e) in x.iter().zip(expected.iter()) {
+            assert!((x - e).abs() < 1e-3, "{} vs {}", x, e);
+        }
+
+        // Already solved
+        assert_eq!(a.conjugate_gradient(&b, &mut x, 1000, 1e-3)?, 0);
+
+        // Not enough iterations
+        let mut x = vec![0.; n];
+        assert!(a.conjugate_gradient(&b, &mut x, 2, 1e-6).is_err());
+        assert!(a.conjugate_gradient(&b[1..], &mut x, 2, 1e-6).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_bicgstab() -> Result<(), String> {
+        // Non-symmetric, diagonally dominant
+        let n = 30;
+        let mut triplets = Vec::new();
+        for i in 0..n {
+            triplets.push((i, i, 4.));
+            if i > 0 {
+                triplets.push((i, i - 1, -1.5));
+            }
+            if i + 1 < n {
+                triplets.push((i, i + 1, -0.5));
+            }
+        }
+        triplets.push((0, n - 1, 1.));
+        let a = SparseMatrix::from_triplets(n, n, &triplets)?;
+        let expected: Vec<Float> = (0..n).map(|i| 1. + i as Float / 10.).collect();
+        let b = a.mul_vec(&expected)?;
+
+        let mut x = vec![0.; n];
+        a.bicgstab(&b, &mut x, 200, 1e-6)?;
+        for (x, e) in x.iter().zip(expected.iter()) {
+            assert!((x - e).abs() < 1e-3, "{} vs {}", x, e);
+        }
+
+        let rectangular = SparseMatrix::from_triplets(2, 3, &[(0, 0, 1.)])?;
+        assert!(rectangular
+            .bicgstab(&[1., 1.], &mut [0.; 3], 10, 1e-6)
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_serde() -> Result<(), String> {
+        let a = laplacian(4);
+        let json = serde_json::to_string(&a).map_err(|e| e.to_string())?;
+        let b: SparseMatrix = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+        assert!(a.to_dense().compare(&b.to_dense()));
+        Ok(())
+    }
+}
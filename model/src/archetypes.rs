@@ -0,0 +1,906 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Generators of typical buildings and models, which are the usual starting
+//! point for early-design studies.
+
+use crate::builder::{Facade, SpaceBuilder};
+use crate::hvac::IdealHeaterCooler;
+use crate::substance::gas::GasSpecification;
+use crate::substance::{Gas, Normal};
+use crate::{
+    Boundary, Construction, ElectricEquipment, Float, Infiltration, Luminaire, Material, Model,
+    Schedule, ScheduleProfile, Space, SpacePurpose, SurfaceMatchingOptions, SurfaceType,
+};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// How each floor of a [`Shoebox`] is divided into spaces
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Zoning {
+    /// A single space per floor
+    SingleZone,
+
+    /// A core space surrounded by four perimeter spaces, one per facade,
+    /// whose depth is `perimeter_depth` metres
+    CorePerimeter {
+        /// The depth of the perimeter spaces, in m
+        perimeter_depth: Float,
+    },
+}
+
+/// The names of the [`Construction`](crate::Construction)s used in each
+/// part of a [`Shoebox`]. These need to exist in the model.
+#[derive(Debug, Clone)]
+pub struct ConstructionSet {
+    /// Walls leading outdoors
+    pub exterior_wall: String,
+
+    /// Walls between two spaces
+    pub interior_wall: String,
+
+    /// The floor of the spaces in the first storey
+    pub ground_floor: String,
+
+    /// Floors between two storeys
+    pub interior_floor: String,
+
+    /// The roof of the spaces in the last storey
+    pub roof: String,
+
+    /// Windows
+    pub window: String,
+}
+
+impl Default for ConstructionSet {
+    fn default() -> Self {
+        Self {
+            exterior_wall: "Exterior Wall".to_string(),
+            interior_wall: "Interior Wall".to_string(),
+            ground_floor: "Ground Floor".to_string(),
+            interior_floor: "Interior Floor".to_string(),
+            roof: "Roof".to_string(),
+            window: "Window".to_string(),
+        }
+    }
+}
+
+/// A rectangular building of several identical floors.
+///
+/// ```rust
+/// use model::{Construction, Model};
+/// use model::archetypes::{ConstructionSet, Shoebox, Zoning};
+///
+/// let mut model = Model::default();
+/// let constructions = ConstructionSet::default();
+/// for name in [
+///     &constructions.exterior_wall,
+///     &constructions.interior_wall,
+///     &constructions.ground_floor,
+///     &constructions.interior_floor,
+///     &constructions.roof,
+///     &constructions.window,
+/// ] {
+///     model.add_construction(Construction::new(name));
+/// }
+///
+/// let spaces = Shoebox {
+///     n_floors: 3,
+///     zoning: Zoning::CorePerimeter { perimeter_depth: 4.5 },
+///     window_to_wall_ratio: [0.4, 0.3, 0.2, 0.3],
+///     constructions,
+///     ..Default::default()
+/// }
+/// .build(&mut model)
+/// .unwrap();
+/// assert_eq!(spaces.len(), 15);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Shoebox {
+    /// The size of the building along `X`, in m
+    pub width: Float,
+
+    /// The size of the building along `Y`, in m
+    pub depth: Float,
+
+    /// The floor-to-floor height, in m
+    pub floor_height: Float,
+
+    /// The number of floors
+    pub n_floors: usize,
+
+    /// How each floor is divided into spaces
+    pub zoning: Zoning,
+
+    /// The ratio between the window and the wall areas of the
+    /// South, East, North and West facades (in that order)
+    pub window_to_wall_ratio: [Float; 4],
+
+    /// In degrees. When 0, the `depth` goes from South to North
+    pub orientation: Float,
+
+    /// The constructions of the building
+    pub constructions: ConstructionSet,
+
+    /// The boundary below the first storey. Defaults to `Ground`, but it
+    /// can be `Adiabatic` for modelling a floor in the middle of a
+    /// taller building.
+    pub floor_boundary: Boundary,
+
+    /// The boundary above the last storey. Defaults to `Outdoor`
+    pub roof_boundary: Boundary,
+}
+
+impl Default for Shoebox {
+    fn default() -> Self {
+        Self {
+            width: 20.,
+            depth: 15.,
+            floor_height: 3.,
+            n_floors: 1,
+            zoning: Zoning::SingleZone,
+            window_to_wall_ratio: [0.; 4],
+            orientation: 0.,
+            constructions: ConstructionSet::default(),
+            floor_boundary: Boundary::Ground,
+            roof_boundary: Boundary::Outdoor,
+        }
+    }
+}
+
+/// A space on each floor of a [`Shoebox`], as (name, footprint,
+/// whether each edge of the footprint is exterior)
+type ShoeboxZone = (&'static str, Vec<(Float, Float)>, [bool; 4]);
+
+impl Shoebox {
+    /// The spaces on each floor
+    fn zones(&self) -> Result<Vec<ShoeboxZone>, String> {
+        let (w, d) = (self.width, self.depth);
+        if w <= 0. || d <= 0. || self.floor_height <= 0. {
+            return Err(format!(
+                "The dimensions of a Shoebox must be positive... found {} x {} x {}",
+                w, d, self.floor_height
+            ));
+        }
+        match self.zoning {
+            Zoning::SingleZone => Ok(vec![(
+                "",
+                vec![(0., 0.), (w, 0.), (w, d), (0., d)],
+                [true; 4],
+            )]),
+            Zoning::CorePerimeter { perimeter_depth: p } => {
+                if p <= 0. || 2. * p >= w.min(d) {
+                    return Err(format!(
+                        "The perimeter depth of a {} x {} Shoebox must be between 0 and {}... found {}",
+                        w,
+                        d,
+                        w.min(d) / 2.,
+                        p
+                    ));
+                }
+                let perimeter = [true, false, false, false];
+                Ok(vec![
+                    (
+                        "South Perimeter",
+                        vec![(0., 0.), (w, 0.), (w - p, p), (p, p)],
+                        perimeter,
+                    ),
+                    (
+                        "East Perimeter",
+                        vec![(w, 0.), (w, d), (w - p, d - p), (w - p, p)],
+                        perimeter,
+                    ),
+                    (
+                        "North Perimeter",
+                        vec![(w, d), (0., d), (p, d - p), (w - p, d - p)],
+                        perimeter,
+                    ),
+                    (
+                        "West Perimeter",
+                        vec![(0., d), (0., 0.), (p, p), (p, d - p)],
+                        perimeter,
+                    ),
+                    (
+                        "Core",
+                        vec![(p, p), (w - p, p), (w - p, d - p), (p, d - p)],
+                        [false; 4],
+                    ),
+                ])
+            }
+        }
+    }
+
+    /// Adds the spaces, surfaces and windows of the building to the `model`,
+    /// connecting the spaces that are next to each other. Spaces are named
+    /// after their floor (e.g., `"Floor 2 Core"`, or just `"Floor 2"` if there
+    /// is a single zone per floor).
+    pub fn build(&self, model: &mut Model) -> Result<Vec<Arc<Space>>, String> {
+        if self.n_floors == 0 {
+            return Err("A Shoebox needs at least one floor".to_string());
+        }
+        let zones = self.zones()?;
+        let c = &self.constructions;
+        model.get_construction(&c.interior_wall)?;
+        model.get_construction(&c.interior_floor)?;
+
+        // Slabs shared with other spaces (or with a building that is not
+        // modelled) get the interior construction
+        let is_interior = |b: &Boundary| matches!(b, Boundary::Adiabatic | Boundary::Space { .. });
+        let ground_floor = if is_interior(&self.floor_boundary) {
+            &c.interior_floor
+        } else {
+            &c.ground_floor
+        };
+        let roof = if is_interior(&self.roof_boundary) {
+            &c.interior_floor
+        } else {
+            &c.roof
+        };
+
+        let mut spaces = Vec::with_capacity(self.n_floors * zones.len());
+        for floor in 0..self.n_floors {
+            let is_top = floor + 1 == self.n_floors;
+            for (zone, footprint, exterior) in zones.iter() {
+                let name = if zone.is_empty() {
+                    format!("Floor {}", floor + 1)
+                } else {
+                    format!("Floor {} {}", floor + 1, zone)
+                };
+                let mut builder = SpaceBuilder::extruded(footprint, self.floor_height)
+                    .name(name)
+                    .storey(floor)
+                    .origin(0., 0., floor as Float * self.floor_height)
+                    .orientation(self.orientation)
+                    .construction(c.exterior_wall.clone())
+                    .window_construction(c.window.clone())
+                    .floor_construction(if floor == 0 {
+                        ground_floor
+                    } else {
+                        &c.interior_floor
+                    })
+                    .roof_construction(if is_top { roof } else { &c.interior_floor })
+                    // Floors above the first one are matched with the roofs below
+                    .floor_boundary(if floor == 0 {
+                        self.floor_boundary.clone()
+                    } else {
+                        Boundary::Outdoor
+                    })
+                    .roof_boundary(if is_top {
+                        self.roof_boundary.clone()
+                    } else {
+                        Boundary::Outdoor
+                    });
+                for (facade, ratio) in Facade::ALL.iter().zip(self.window_to_wall_ratio) {
+                    builder = builder.facade_window_to_wall_ratio(*facade, ratio);
+                }
+                for (edge, _) in exterior.iter().enumerate().filter(|(_, ext)| !**ext) {
+                    builder = builder.edge_window_to_wall_ratio(edge, 0.);
+                }
+                spaces.push(builder.build(model)?);
+            }
+        }
+
+        model.match_surfaces(&SurfaceMatchingOptions::default())?;
+
+        // Walls between our spaces get the interior construction
+        let names: HashSet<&String> = spaces.iter().map(|s| s.name()).collect();
+        let is_ours =
+            |b: &Boundary| matches!(b, Boundary::Space { space } if names.contains(space));
+        for s in model.surfaces.iter_mut() {
+            if is_ours(&s.front_boundary)
+                && is_ours(&s.back_boundary)
+                && matches!(s.category(), Ok(SurfaceType::InteriorWall))
+            {
+                Arc::make_mut(s).construction = c.interior_wall.clone();
+            }
+        }
+
+        Ok(spaces)
+    }
+}
+
+/// The climate for which the constructions of a [`Typology`] are chosen,
+/// loosely following the ASHRAE 169 climate zones
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClimateZone {
+    /// ASHRAE zones 0 and 1 (e.g., Singapore, Miami)
+    Hot,
+    /// ASHRAE zone 2 (e.g., Brisbane, Houston)
+    Warm,
+    /// ASHRAE zone 3 (e.g., Santiago, Barcelona)
+    Mixed,
+    /// ASHRAE zones 4 and 5 (e.g., Wellington, London)
+    Cool,
+    /// ASHRAE zones 6 to 8 (e.g., Oslo, Montreal)
+    Cold,
+}
+
+impl ClimateZone {
+    /// The thickness of the insulation in walls, roofs and ground floors, in m
+    fn insulation(&self) -> (Float, Float, Float) {
+        match self {
+            Self::Hot => (0.03, 0.08, 0.02),
+            Self::Warm => (0.05, 0.10, 0.03),
+            Self::Mixed => (0.08, 0.15, 0.05),
+            Self::Cool => (0.12, 0.20, 0.08),
+            Self::Cold => (0.18, 0.28, 0.12),
+        }
+    }
+
+    /// The gas filling the cavities of the windows, and the number of panes
+    fn glazing(&self) -> (GasSpecification, usize) {
+        match self {
+            Self::Hot | Self::Warm | Self::Mixed => (GasSpecification::Air, 2),
+            Self::Cool => (GasSpecification::Argon, 2),
+            Self::Cold => (GasSpecification::Argon, 3),
+        }
+    }
+}
+
+/// A common kind of building, which can be turned into a complete
+/// [`Model`] (i.e., geometry, constructions, internal loads, schedules
+/// and HVAC) that is ready to be simulated.
+///
+/// ```rust
+/// use model::archetypes::{ClimateZone, Typology};
+///
+/// let model = Typology::DetachedHouse.model(ClimateZone::Cool).unwrap();
+/// assert_eq!(model.spaces.len(), 2);
+/// assert_eq!(model.hvacs.len(), 2);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Typology {
+    /// A two-storey, 10 x 8 m house standing on the ground
+    DetachedHouse,
+
+    /// A 12 x 8 m flat in the middle of a block, with windows
+    /// facing South and North. Its floor, ceiling, and East and West
+    /// walls are shared with other flats, so they are adiabatic.
+    Apartment,
+
+    /// A 30 x 20 m floor of an office tower, split into a core and four
+    /// perimeter spaces. Its floor and ceiling are adiabatic.
+    OfficeFloor,
+}
+
+/// The internal loads of a [`Typology`]
+struct Loads {
+    /// Lighting power density, in W/m2
+    lighting: Float,
+    /// Electric equipment power density, in W/m2
+    equipment: Float,
+    /// Floor area per person, in m2
+    area_per_person: Float,
+    /// Infiltration, in air changes per hour
+    air_changes: Float,
+    /// Heating and cooling setpoints, in C
+    setpoints: (Float, Float),
+    /// The fraction of the occupants that are present on weekdays
+    /// and weekends (one value per hour)
+    occupancy: [[Float; 24]; 2],
+    /// The fraction of the lighting power used on weekdays and weekends
+    lighting_use: [[Float; 24]; 2],
+    /// The fraction of the equipment power used on weekdays and weekends
+    equipment_use: [[Float; 24]; 2],
+    /// Whether the HVAC is only available while the building is occupied
+    office_hours: bool,
+}
+
+#[rustfmt::skip]
+const RESIDENTIAL_OCCUPANCY: [[Float; 24]; 2] = [
+    [1., 1., 1., 1., 1., 1., 1., 0.7, 0.4, 0.2, 0.2, 0.2, 0.2, 0.2, 0.2, 0.2, 0.2, 0.5, 0.8, 0.9, 1., 1., 1., 1.],
+    [1., 1., 1., 1., 1., 1., 1., 1., 0.9, 0.7, 0.5, 0.5, 0.6, 0.5, 0.5, 0.5, 0.6, 0.7, 0.8, 0.9, 1., 1., 1., 1.],
+];
+
+#[rustfmt::skip]
+const RESIDENTIAL_LIGHTING: [[Float; 24]; 2] = [
+    [0.1, 0.05, 0.05, 0.05, 0.05, 0.1, 0.3, 0.5, 0.3, 0.1, 0.1, 0.1, 0.1, 0.1, 0.1, 0.1, 0.2, 0.4, 0.7, 0.9, 0.9, 0.8, 0.5, 0.2],
+    [0.1, 0.05, 0.05, 0.05, 0.05, 0.05, 0.1, 0.3, 0.4, 0.3, 0.2, 0.2, 0.2, 0.2, 0.2, 0.2, 0.3, 0.5, 0.7, 0.9, 0.9, 0.8, 0.6, 0.3],
+];
+
+#[rustfmt::skip]
+const RESIDENTIAL_EQUIPMENT: [[Float; 24]; 2] = [
+    [0.3, 0.3, 0.3, 0.3, 0.3, 0.3, 0.5, 0.7, 0.5, 0.3, 0.3, 0.3, 0.4, 0.3, 0.3, 0.3, 0.4, 0.6, 0.9, 1., 0.9, 0.7, 0.5, 0.4],
+    [0.3, 0.3, 0.3, 0.3, 0.3, 0.3, 0.3, 0.5, 0.7, 0.6, 0.5, 0.6, 0.8, 0.6, 0.5, 0.5, 0.6, 0.7, 0.9, 1., 0.9, 0.7, 0.5, 0.4],
+];
+
+#[rustfmt::skip]
+const OFFICE_OCCUPANCY: [[Float; 24]; 2] = [
+    [0., 0., 0., 0., 0., 0., 0., 0.1, 0.5, 0.9, 0.95, 0.95, 0.5, 0.9, 0.95, 0.95, 0.9, 0.5, 0.1, 0.05, 0., 0., 0., 0.],
+    [0.; 24],
+];
+
+#[rustfmt::skip]
+const OFFICE_LIGHTING: [[Float; 24]; 2] = [
+    [0.05, 0.05, 0.05, 0.05, 0.05, 0.05, 0.1, 0.3, 0.9, 0.9, 0.9, 0.9, 0.8, 0.9, 0.9, 0.9, 0.9, 0.7, 0.3, 0.1, 0.05, 0.05, 0.05, 0.05],
+    [0.05; 24],
+];
+
+#[rustfmt::skip]
+const OFFICE_EQUIPMENT: [[Float; 24]; 2] = [
+    [0.2, 0.2, 0.2, 0.2, 0.2, 0.2, 0.3, 0.5, 0.9, 0.9, 0.9, 0.9, 0.8, 0.9, 0.9, 0.9, 0.9, 0.6, 0.4, 0.3, 0.2, 0.2, 0.2, 0.2],
+    [0.2; 24],
+];
+
+/// Turns weekday and weekend profiles into a weekly [`ScheduleProfile`]
+fn weekly(profiles: &[[Float; 24]; 2], scale: Float) -> ScheduleProfile {
+    let [weekday, weekend] = profiles.map(|p| p.iter().map(|v| v * scale).collect::<Vec<_>>());
+    let mut days = vec![weekday; 5];
+    days.extend([weekend.clone(), weekend]);
+    ScheduleProfile::Weekly { days }
+}
+
+/// An opaque [`Normal`] substance with common surface properties
+pub(crate) fn opaque(
+    name: &str,
+    conductivity: Float,
+    density: Float,
+    specific_heat: Float,
+) -> Normal {
+    let mut sub = Normal::new(name);
+    sub.set_thermal_conductivity(conductivity)
+        .set_density(density)
+        .set_specific_heat_capacity(specific_heat)
+        .set_front_solar_absorbtance(0.7)
+        .set_back_solar_absorbtance(0.7)
+        .set_front_thermal_absorbtance(0.9)
+        .set_back_thermal_absorbtance(0.9)
+        .set_front_visible_reflectance(0.3)
+        .set_back_visible_reflectance(0.3);
+    sub
+}
+
+/// A [`Normal`] substance of clear float glass, called `"Clear Glass"`
+pub(crate) fn clear_glass() -> Normal {
+    let mut glass = Normal::new("Clear Glass");
+    glass
+        .set_thermal_conductivity(1.)
+        .set_density(2500.)
+        .set_specific_heat_capacity(840.)
+        .set_solar_transmittance(0.77)
+        .set_front_solar_absorbtance(0.16)
+        .set_back_solar_absorbtance(0.16)
+        .set_visible_transmissivity(0.88)
+        .set_front_visible_reflectance(0.08)
+        .set_back_visible_reflectance(0.08)
+        .set_front_thermal_absorbtance(0.84)
+        .set_back_thermal_absorbtance(0.84);
+    glass
+}
+
+/// Adds (if needed) a [`Material`] of `thickness` metres made of `substance`,
+/// returning its name (e.g., `"Concrete 150mm"`)
+pub(crate) fn layer(model: &mut Model, substance: &str, thickness: Float) -> String {
+    let name = format!("{} {:.0}mm", substance, thickness * 1000.);
+    if model.get_material(&name).is_err() {
+        model.add_material(Material::new(name.clone(), substance.to_string(), thickness));
+    }
+    name
+}
+
+/// Adds the substances, materials and constructions suitable for
+/// a `climate` to the `model`
+fn add_constructions(model: &mut Model, climate: ClimateZone) -> ConstructionSet {
+    for sub in [
+        opaque("Concrete", 1.4, 2300., 880.),
+        opaque("Brick", 0.72, 1920., 840.),
+        opaque("Mineral Wool", 0.04, 30., 840.),
+        opaque("Gypsum", 0.16, 800., 1090.),
+        opaque("Timber", 0.14, 530., 1600.),
+    ] {
+        model.add_substance(sub.wrap());
+    }
+    model.add_substance(clear_glass().wrap());
+    let (gas, panes) = climate.glazing();
+    let gas_name = match gas {
+        GasSpecification::Argon => "Argon",
+        _ => "Air",
+    };
+    let mut cavity = Gas::new(gas_name);
+    cavity.set_gas(gas);
+    model.add_substance(cavity.wrap());
+
+    // Layers go from the outside (front) to the inside (back)
+    let (wall_insulation, roof_insulation, floor_insulation) = climate.insulation();
+    let constructions = ConstructionSet::default();
+    let layers = [
+        (
+            &constructions.exterior_wall,
+            vec![
+                ("Brick", 0.11),
+                ("Mineral Wool", wall_insulation),
+                ("Concrete", 0.15),
+                ("Gypsum", 0.013),
+            ],
+        ),
+        (
+            &constructions.interior_wall,
+            vec![("Gypsum", 0.013), ("Mineral Wool", 0.05), ("Gypsum", 0.013)],
+        ),
+        (
+            &constructions.ground_floor,
+            vec![
+                ("Concrete", 0.15),
+                ("Mineral Wool", floor_insulation),
+                ("Timber", 0.02),
+            ],
+        ),
+        (
+            &constructions.interior_floor,
+            vec![("Gypsum", 0.013), ("Concrete", 0.2), ("Timber", 0.02)],
+        ),
+        (
+            &constructions.roof,
+            vec![
+                ("Concrete", 0.05),
+                ("Mineral Wool", roof_insulation),
+                ("Concrete", 0.2),
+                ("Gypsum", 0.013),
+            ],
+        ),
+    ];
+    let mut window = vec![("Clear Glass", 0.006)];
+    for _ in 1..panes {
+        window.extend([(gas_name, 0.012), ("Clear Glass", 0.006)]);
+    }
+
+    for (name, layers) in layers
+        .into_iter()
+        .chain(std::iter::once((&constructions.window, window)))
+    {
+        let mut construction = Construction::new(name);
+        for (substance, thickness) in layers {
+            let material = layer(model, substance, thickness);
+            construction.materials.push(material);
+        }
+        model.add_construction(construction);
+    }
+    constructions
+}
+
+impl Typology {
+    /// The geometry and internal loads of the typology
+    fn specs(&self) -> (Shoebox, Loads, Vec<SpacePurpose>) {
+        let residential = |air_changes| Loads {
+            lighting: 5.,
+            equipment: 4.,
+            area_per_person: 30.,
+            air_changes,
+            setpoints: (20., 26.),
+            occupancy: RESIDENTIAL_OCCUPANCY,
+            lighting_use: RESIDENTIAL_LIGHTING,
+            equipment_use: RESIDENTIAL_EQUIPMENT,
+            office_hours: false,
+        };
+        match self {
+            Self::DetachedHouse => (
+                Shoebox {
+                    width: 10.,
+                    depth: 8.,
+                    floor_height: 2.7,
+                    n_floors: 2,
+                    window_to_wall_ratio: [0.3, 0.15, 0.15, 0.15],
+                    ..Default::default()
+                },
+                residential(0.5),
+                vec![SpacePurpose::LivingRoom, SpacePurpose::Bedroom],
+            ),
+            Self::Apartment => (
+                Shoebox {
+                    width: 12.,
+                    depth: 8.,
+                    floor_height: 2.7,
+                    window_to_wall_ratio: [0.3, 0., 0.2, 0.],
+                    floor_boundary: Boundary::Adiabatic,
+                    roof_boundary: Boundary::Adiabatic,
+                    ..Default::default()
+                },
+                residential(0.3),
+                vec![SpacePurpose::LivingRoom, SpacePurpose::Bedroom],
+            ),
+            Self::OfficeFloor => (
+                Shoebox {
+                    width: 30.,
+                    depth: 20.,
+                    floor_height: 3.5,
+                    zoning: Zoning::CorePerimeter {
+                        perimeter_depth: 4.5,
+                    },
+                    window_to_wall_ratio: [0.4; 4],
+                    floor_boundary: Boundary::Adiabatic,
+                    roof_boundary: Boundary::Adiabatic,
+                    ..Default::default()
+                },
+                Loads {
+                    lighting: 10.,
+                    equipment: 12.,
+                    area_per_person: 10.,
+                    air_changes: 0.2,
+                    setpoints: (21., 24.),
+                    occupancy: OFFICE_OCCUPANCY,
+                    lighting_use: OFFICE_LIGHTING,
+                    equipment_use: OFFICE_EQUIPMENT,
+                    office_hours: true,
+                },
+                vec![SpacePurpose::Office],
+            ),
+        }
+    }
+
+    /// Creates a new [`Model`] of this typology, with constructions that
+    /// suit the `climate`. Each space gets its own
+    /// [`IdealHeaterCooler`], [`Luminaire`] and [`ElectricEquipment`].
+    pub fn model(&self, climate: ClimateZone) -> Result<Model, String> {
+        let (mut shoebox, loads, purposes) = self.specs();
+        let mut model = Model::default();
+        shoebox.constructions = add_constructions(&mut model, climate);
+
+        let names: Vec<String> = shoebox
+            .build(&mut model)?
+            .iter()
+            .map(|s| s.name().clone())
+            .collect();
+
+        // Party walls are shared with other flats
+        if *self == Self::Apartment {
+            for s in model.surfaces.iter_mut() {
+                if s.name().ends_with(" East Wall") || s.name().ends_with(" West Wall") {
+                    let s = Arc::make_mut(s);
+                    s.front_boundary = Boundary::Adiabatic;
+                    s.construction = shoebox.constructions.interior_wall.clone();
+                }
+            }
+        }
+
+        model.add_schedule(Schedule::new("Lighting", weekly(&loads.lighting_use, 1.)));
+        model.add_schedule(Schedule::new("Equipment", weekly(&loads.equipment_use, 1.)));
+        if loads.office_hours {
+            let available = loads
+                .occupancy
+                .map(|p| p.map(|v| if v > 0. { 1. } else { 0. }));
+            model.add_schedule(Schedule::new("HVAC Availability", weekly(&available, 1.)));
+        }
+
+        let (_, areas) = model.get_space_sizes();
+        for name in names {
+            let area = areas.get(&name).copied().unwrap_or(0.);
+            let occupancy = format!("{} Occupancy", name);
+            model.add_schedule(Schedule::new(
+                &occupancy,
+                weekly(&loads.occupancy, area / loads.area_per_person),
+            ));
+
+            let i = model
+                .spaces
+                .iter()
+                .position(|s| s.name() == &name)
+                .ok_or_else(|| format!("Could not find Space '{}'", name))?;
+            let space = Arc::make_mut(&mut model.spaces[i]);
+            let volume = space
+                .volume
+                .ok_or_else(|| format!("Space '{}' has no volume", name))?;
+            space
+                .set_infiltration(Infiltration::Constant {
+                    flow: loads.air_changes * volume / 3600.,
+                })
+                .set_occupancy_schedule(occupancy);
+            space.purposes = purposes.clone();
+
+            let mut luminaire = Luminaire::new(format!("{} Lights", name));
+            luminaire
+                .set_max_power(loads.lighting * area)
+                .set_target_space(&name)
+                .set_schedule("Lighting");
+            model.add_luminaire(luminaire)?;
+
+            let mut equipment = ElectricEquipment::new(format!("{} Equipment", name));
+            equipment
+                .set_max_power(loads.equipment * area)
+                .set_target_space(&name)
+                .set_schedule("Equipment");
+            model.add_electric_equipment(equipment)?;
+
+            let mut hvac = IdealHeaterCooler::new(format!("{} HVAC", name));
+            hvac.target_space = Some(name.clone());
+            hvac.set_heating_setpoint(loads.setpoints.0)
+                .set_cooling_setpoint(loads.setpoints.1);
+            if loads.office_hours {
+                hvac.set_availability_schedule("HVAC Availability");
+            }
+            model.add_hvac(hvac.wrap())?;
+        }
+
+        Ok(model)
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use crate::{Construction, SurfaceTrait};
+
+    fn model(constructions: &ConstructionSet) -> Model {
+        let mut model = Model::default();
+        for name in [
+            &constructions.exterior_wall,
+            &constructions.interior_wall,
+            &constructions.ground_floor,
+            &constructions.interior_floor,
+            &constructions.roof,
+            &constructions.window,
+        ] {
+            model.add_construction(Construction::new(name));
+        }
+        model
+    }
+
+    #[test]
+    fn test_single_zone() -> Result<(), String> {
+        let shoebox = Shoebox {
+            n_floors: 2,
+            window_to_wall_ratio: [0.4, 0., 0.2, 0.],
+            ..Default::default()
+        };
+        let mut model = model(&shoebox.constructions);
+        let spaces = shoebox.build(&mut model)?;
+        assert_eq!(spaces.len(), 2);
+        assert_eq!(spaces[1].name(), "Floor 2");
+        assert_eq!(*spaces[1].storey()?, 1);
+
+        // 2 x 6 surfaces, but the slab between floors is shared
+        assert_eq!(model.surfaces.len(), 11);
+        assert_eq!(model.fenestrations.len(), 4);
+
+        let (total_area, areas) = model.get_space_sizes();
+        assert!((total_area - 2. * 20. * 15.).abs() < 1e-6);
+        assert!((areas["Floor 2"] - 20. * 15.).abs() < 1e-6);
+
+        let south = model.get_fenestration("Floor 1 South Window")?;
+        assert!((south.area() - 0.4 * 20. * 3.).abs() < 1e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn test_core_perimeter() -> Result<(), String> {
+        let shoebox = Shoebox {
+            n_floors: 2,
+            zoning: Zoning::CorePerimeter {
+                perimeter_depth: 4.,
+            },
+            window_to_wall_ratio: [0.3; 4],
+            ..Default::default()
+        };
+        let mut model = model(&shoebox.constructions);
+        let spaces = shoebox.build(&mut model)?;
+        assert_eq!(spaces.len(), 10);
+
+        // 60 surfaces, minus 8 interior walls and 5 floors
+        assert_eq!(model.surfaces.len(), 60 - 2 * 8 - 5);
+        // Only the perimeter spaces have windows
+        assert_eq!(model.fenestrations.len(), 8);
+
+        let (total_area, areas) = model.get_space_sizes();
+        assert!((total_area - 2. * 20. * 15.).abs() < 1e-6);
+        assert!((areas["Floor 2 Core"] - 12. * 7.).abs() < 1e-6);
+
+        let n_interior = model
+            .surfaces
+            .iter()
+            .filter(|s| s.construction == shoebox.constructions.interior_wall)
+            .count();
+        assert_eq!(n_interior, 16);
+        assert!(model
+            .surfaces
+            .iter()
+            .filter(|s| s.construction == shoebox.constructions.interior_wall)
+            .all(|s| matches!(s.front_boundary, Boundary::Space { .. })));
+        Ok(())
+    }
+
+    #[test]
+    fn test_typologies() -> Result<(), String> {
+        let climates = [
+            ClimateZone::Hot,
+            ClimateZone::Warm,
+            ClimateZone::Mixed,
+            ClimateZone::Cool,
+            ClimateZone::Cold,
+        ];
+        for climate in climates {
+            let house = Typology::DetachedHouse.model(climate)?;
+            assert_eq!(house.spaces.len(), 2);
+            assert_eq!(house.surfaces.len(), 11);
+            assert_eq!(house.fenestrations.len(), 8);
+            assert_eq!(house.hvacs.len(), 2);
+            assert_eq!(house.luminaires.len(), 2);
+            assert_eq!(house.electric_equipment.len(), 2);
+
+            let flat = Typology::Apartment.model(climate)?;
+            assert_eq!(flat.spaces.len(), 1);
+            assert_eq!(flat.fenestrations.len(), 2);
+            let n_adiabatic = flat
+                .surfaces
+                .iter()
+                .filter(|s| matches!(s.front_boundary, Boundary::Adiabatic))
+                .count();
+            assert_eq!(n_adiabatic, 4);
+
+            let office = Typology::OfficeFloor.model(climate)?;
+            assert_eq!(office.spaces.len(), 5);
+            assert_eq!(office.fenestrations.len(), 4);
+            assert!(office.get_schedule("HVAC Availability").is_ok());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_climate_constructions() -> Result<(), String> {
+        let hot = Typology::DetachedHouse.model(ClimateZone::Hot)?;
+        let cold = Typology::DetachedHouse.model(ClimateZone::Cold)?;
+
+        let wall = hot.get_construction("Exterior Wall")?;
+        assert!(wall.materials.contains(&"Mineral Wool 30mm".to_string()));
+        let wall = cold.get_construction("Exterior Wall")?;
+        assert!(wall.materials.contains(&"Mineral Wool 180mm".to_string()));
+
+        // Double vs triple glazing
+        assert_eq!(hot.get_construction("Window")?.materials.len(), 3);
+        let window = cold.get_construction("Window")?;
+        assert_eq!(window.materials.len(), 5);
+        assert_eq!(window.materials[1], "Argon 12mm");
+
+        // Occupancy is proportional to the floor area
+        let people = cold.get_schedule("Floor 1 Occupancy")?;
+        let night = calendar::Date {
+            month: 1,
+            day: 1,
+            hour: 2.,
+            year: None,
+        };
+        assert!((people.value(night)? - 80. / 30.).abs() < 1e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors() {
+        let shoebox = Shoebox {
+            zoning: Zoning::CorePerimeter {
+                perimeter_depth: 8.,
+            },
+            ..Default::default()
+        };
+        let mut model = model(&shoebox.constructions);
+        assert!(shoebox.build(&mut model).is_err());
+
+        let shoebox = Shoebox {
+            n_floors: 0,
+            ..Default::default()
+        };
+        assert!(shoebox.build(&mut model).is_err());
+
+        // Missing constructions
+        let shoebox = Shoebox::default();
+        assert!(shoebox.build(&mut Model::default()).is_err());
+    }
+}
@@ -50,6 +50,13 @@ pub enum Boundary {
     Outdoor,
 
     /// The Surface is in contact with the Ground
+    ///
+    /// Border conditions:
+    /// * **Solar Radiation**: None
+    /// * **Net Shortwave (IR) Radiation**: None
+    /// * **Temperature**: As given by the `ground_temperature` of the [`SiteDetails`](crate::SiteDetails)
+    /// * **Convection Coefficient**: As given by the `ground_coupling` of the [`SiteDetails`](crate::SiteDetails)
+    /// * **Wind**: No
     Ground,
 
     /// The Surface leads to another space whose temperature
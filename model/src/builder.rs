@@ -0,0 +1,549 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Fluent constructors that generate the geometry of common elements
+//! (e.g., surfaces, fenestrations and their boundaries) automatically.
+//!
+//! ```rust
+//! use model::{Construction, Model};
+//! use model::builder::SpaceBuilder;
+//!
+//! let mut model = Model::default();
+//! model.add_construction(Construction::new("Wall"));
+//! model.add_construction(Construction::new("Glazing"));
+//!
+//! let office = SpaceBuilder::rectangular(4., 5., 2.7)
+//!     .name("Office")
+//!     .window_to_wall_ratio(0.4)
+//!     .construction("Wall")
+//!     .window_construction("Glazing")
+//!     .build(&mut model)
+//!     .unwrap();
+//!
+//! assert_eq!(office.volume, Some(4. * 5. * 2.7));
+//! assert_eq!(model.surfaces.len(), 6); // 4 walls, a floor and a roof
+//! assert_eq!(model.fenestrations.len(), 4);
+//! ```
+
+use crate::{
+    Boundary, Fenestration, FenestrationType, Float, Model, Space, Surface, SurfaceType,
+    Transformable,
+};
+use geometry::{Loop3D, Point3D, Polygon3D, Transform};
+use std::sync::Arc;
+
+/// The walls of a rectangular [`Space`], named after the direction
+/// they face when the space has not been rotated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Facade {
+    /// The wall facing `-Y`
+    South,
+    /// The wall facing `+X`
+    East,
+    /// The wall facing `+Y`
+    North,
+    /// The wall facing `-X`
+    West,
+}
+
+impl Facade {
+    /// All the facades
+    pub const ALL: [Facade; 4] = [Facade::South, Facade::East, Facade::North, Facade::West];
+
+    /// Returns the facade as a str
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Facade::South => "South",
+            Facade::East => "East",
+            Facade::North => "North",
+            Facade::West => "West",
+        }
+    }
+
+    /// Gets the facade of a wall whose bottom edge goes in the direction
+    /// `(dx, dy)`, when walking counterclockwise around the space. This
+    /// is the direction its outward normal is closest to.
+    fn from_direction(dx: Float, dy: Float) -> Self {
+        // The outward normal is (dy, -dx)
+        let (nx, ny) = (dy, -dx);
+        if ny.abs() >= nx.abs() {
+            if ny < 0. {
+                Facade::South
+            } else {
+                Facade::North
+            }
+        } else if nx > 0. {
+            Facade::East
+        } else {
+            Facade::West
+        }
+    }
+
+    /// The index of the facade in [`Facade::ALL`]
+    fn index(&self) -> usize {
+        match self {
+            Facade::South => 0,
+            Facade::East => 1,
+            Facade::North => 2,
+            Facade::West => 3,
+        }
+    }
+}
+
+/// Builds a [`Space`] by extruding a footprint (e.g., a rectangle), creating
+/// its walls, a floor and a roof, and (optionally) a window centred on each wall.
+///
+/// The surfaces are created with their front boundary leading away from
+/// the space (i.e., `Outdoor` for walls, and the `floor_boundary` and
+/// `roof_boundary` for the floor and the roof). Spaces that are next to
+/// each other can be connected afterwards through [`Model::match_surfaces`].
+#[derive(Debug, Clone)]
+pub struct SpaceBuilder {
+    name: String,
+    footprint: Vec<(Float, Float)>,
+    height: Float,
+    origin: Point3D,
+    orientation: Float,
+    window_to_wall_ratio: [Float; 4],
+    edge_window_to_wall_ratio: Vec<Option<Float>>,
+    wall_construction: Option<String>,
+    floor_construction: Option<String>,
+    roof_construction: Option<String>,
+    window_construction: Option<String>,
+    floor_boundary: Boundary,
+    roof_boundary: Boundary,
+    storey: Option<usize>,
+}
+
+impl SpaceBuilder {
+    /// Starts building a rectangular space that is `width` metres
+    /// wide (along `X`), `depth` metres deep (along `Y`) and `height`
+    /// metres high. Its corner is at the origin.
+    pub fn rectangular(width: Float, depth: Float, height: Float) -> Self {
+        Self::extruded(
+            &[(0., 0.), (width, 0.), (width, depth), (0., depth)],
+            height,
+        )
+    }
+
+    /// Starts building a space whose floor is the polygon given by the
+    /// `(x, y)` coordinates in `footprint`, which must be listed
+    /// counterclockwise when seen from above. Each edge of the footprint
+    /// becomes a wall, in the same order.
+    pub fn extruded(footprint: &[(Float, Float)], height: Float) -> Self {
+        Self {
+            name: "Space".to_string(),
+            footprint: footprint.to_vec(),
+            height,
+            origin: Point3D::new(0., 0., 0.),
+            orientation: 0.,
+            window_to_wall_ratio: [0.; 4],
+            edge_window_to_wall_ratio: vec![None; footprint.len()],
+            wall_construction: None,
+            floor_construction: None,
+            roof_construction: None,
+            window_construction: None,
+            floor_boundary: Boundary::Ground,
+            roof_boundary: Boundary::Outdoor,
+            storey: None,
+        }
+    }
+
+    /// Sets the name of the space, which is also used
+    /// for naming its surfaces (e.g., `"Office South Wall"`)
+    pub fn name<S: Into<String>>(mut self, name: S) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Sets the storey in which the space is located, indexing from 0
+    pub fn storey(mut self, storey: usize) -> Self {
+        self.storey = Some(storey);
+        self
+    }
+
+    /// Moves the corner of the space to `(x, y, z)`
+    pub fn origin(mut self, x: Float, y: Float, z: Float) -> Self {
+        self.origin = Point3D::new(x, y, z);
+        self
+    }
+
+    /// Rotates the space counterclockwise around its corner, in degrees
+    pub fn orientation(mut self, degrees: Float) -> Self {
+        self.orientation = degrees;
+        self
+    }
+
+    /// Sets the ratio between the window and the wall areas of all facades
+    pub fn window_to_wall_ratio(mut self, ratio: Float) -> Self {
+        self.window_to_wall_ratio = [ratio; 4];
+        self
+    }
+
+    /// Sets the ratio between the window and the wall areas of a single facade
+    pub fn facade_window_to_wall_ratio(mut self, facade: Facade, ratio: Float) -> Self {
+        self.window_to_wall_ratio[facade.index()] = ratio;
+        self
+    }
+
+    /// Sets the ratio between the window and the wall areas of the wall
+    /// built over the `edge`-th edge of the footprint, overriding the
+    /// ratio of its facade (e.g., for leaving interior walls without windows)
+    pub fn edge_window_to_wall_ratio(mut self, edge: usize, ratio: Float) -> Self {
+        if let Some(r) = self.edge_window_to_wall_ratio.get_mut(edge) {
+            *r = Some(ratio);
+        }
+        self
+    }
+
+    /// Sets the construction of the walls, floor and roof
+    pub fn construction<S: Into<String>>(mut self, construction: S) -> Self {
+        self.wall_construction = Some(construction.into());
+        self
+    }
+
+    /// Sets the construction of the floor, if different from the walls'
+    pub fn floor_construction<S: Into<String>>(mut self, construction: S) -> Self {
+        self.floor_construction = Some(construction.into());
+        self
+    }
+
+    /// Sets the construction of the roof, if different from the walls'
+    pub fn roof_construction<S: Into<String>>(mut self, construction: S) -> Self {
+        self.roof_construction = Some(construction.into());
+        self
+    }
+
+    /// Sets the construction of the windows
+    pub fn window_construction<S: Into<String>>(mut self, construction: S) -> Self {
+        self.window_construction = Some(construction.into());
+        self
+    }
+
+    /// Sets what is under the floor. Defaults to `Ground`.
+    pub fn floor_boundary(mut self, boundary: Boundary) -> Self {
+        self.floor_boundary = boundary;
+        self
+    }
+
+    /// Sets what is above the roof. Defaults to `Outdoor`.
+    pub fn roof_boundary(mut self, boundary: Boundary) -> Self {
+        self.roof_boundary = boundary;
+        self
+    }
+
+    /// Adds the space, its surfaces and its fenestrations to the `model`
+    pub fn build(&self, model: &mut Model) -> Result<Arc<Space>, String> {
+        let h = self.height;
+        let n = self.footprint.len();
+        if n < 3 {
+            return Err(format!(
+                "The footprint of Space '{}' needs at least 3 vertices",
+                self.name
+            ));
+        }
+        // Shoelace formula... positive when counterclockwise
+        let area = (0..n)
+            .map(|i| {
+                let (x0, y0) = self.footprint[i];
+                let (x1, y1) = self.footprint[(i + 1) % n];
+                x0 * y1 - x1 * y0
+            })
+            .sum::<Float>()
+            / 2.;
+        if area <= 0. || h <= 0. {
+            return Err(format!(
+                "Space '{}' needs a positive height and a counterclockwise footprint... found a height of {} and an area of {}",
+                self.name, h, area
+            ));
+        }
+        let facades: Vec<Facade> = (0..n)
+            .map(|i| {
+                let (a, b) = (self.footprint[i], self.footprint[(i + 1) % n]);
+                Facade::from_direction(b.0 - a.0, b.1 - a.1)
+            })
+            .collect();
+        let ratios: Vec<Float> = facades
+            .iter()
+            .zip(self.edge_window_to_wall_ratio.iter())
+            .map(|(facade, edge_ratio)| {
+                edge_ratio.unwrap_or(self.window_to_wall_ratio[facade.index()])
+            })
+            .collect();
+        if let Some(r) = ratios.iter().find(|r| !(0. ..1.).contains(*r)) {
+            return Err(format!(
+                "The window-to-wall ratio of Space '{}' must be between 0 and 1... found {}",
+                self.name, r
+            ));
+        }
+        let wall_construction = self
+            .wall_construction
+            .clone()
+            .ok_or_else(|| format!("Space '{}' has not been given a construction", self.name))?;
+        let window_construction = if ratios.iter().any(|r| *r > 0.) {
+            Some(self.window_construction.clone().ok_or_else(|| {
+                format!(
+                    "Space '{}' has windows, but no window construction",
+                    self.name
+                )
+            })?)
+        } else {
+            None
+        };
+
+        let mut transform = Transform::translate(self.origin.x, self.origin.y, self.origin.z);
+        transform *= Transform::rotate_z(self.orientation);
+
+        let mut space = Space::new(self.name.clone());
+        space.set_volume(area * h);
+        if let Some(storey) = self.storey {
+            space.set_storey(storey);
+        }
+        let space = model.add_space(space);
+        let inside = Boundary::Space {
+            space: self.name.clone(),
+        };
+
+        let up = Point3D::new(0., 0., h);
+        for (i, facade) in facades.iter().enumerate() {
+            // Walls are named after their facade, numbered only if there are several
+            let repeated = facades.iter().filter(|f| *f == facade).count() > 1;
+            let n_facade = facades[..i].iter().filter(|f| *f == facade).count() + 1;
+            let suffix = if repeated {
+                format!("{} {}", facade.as_str(), n_facade)
+            } else {
+                facade.as_str().to_string()
+            };
+
+            // The bottom edge of the wall is counterclockwise as seen from the outside
+            let (ax, ay) = self.footprint[i];
+            let (bx, by) = self.footprint[(i + 1) % n];
+            let (a, b) = (Point3D::new(ax, ay, 0.), Point3D::new(bx, by, 0.));
+            let corners = [a, b, b + up, a + up];
+            let wall_name = format!("{} {} Wall", self.name, suffix);
+            let mut wall = Surface::new(
+                wall_name.clone(),
+                polygon(&corners)?,
+                wall_construction.clone(),
+                Boundary::Outdoor,
+                inside.clone(),
+            );
+            wall.set_category(SurfaceType::ExteriorWall);
+            wall.transform(&transform)?;
+            model.add_surface(wall)?;
+
+            if let (Some(construction), true) = (&window_construction, ratios[i] > 0.) {
+                // A scaled-down copy of the wall, around its centre
+                let centre = (a + b + up) * 0.5;
+                let f = ratios[i].sqrt();
+                let corners = corners.map(|p| centre + (p - centre) * f);
+                let mut window = Fenestration::new(
+                    format!("{} {} Window", self.name, suffix),
+                    polygon(&corners)?,
+                    construction.clone(),
+                    FenestrationType::Window,
+                    Boundary::Outdoor,
+                    inside.clone(),
+                );
+                window.set_parent_surface(wall_name);
+                window.transform(&transform)?;
+                model.add_fenestration(window)?;
+            }
+        }
+
+        // Floor, facing down
+        let floor_category = match &self.floor_boundary {
+            Boundary::Ground => SurfaceType::GroundFloor,
+            Boundary::Space { .. } => SurfaceType::InteriorFloor,
+            _ => SurfaceType::ExteriorFloor,
+        };
+        let corners: Vec<Point3D> = self
+            .footprint
+            .iter()
+            .rev()
+            .map(|(x, y)| Point3D::new(*x, *y, 0.))
+            .collect();
+        let mut floor = Surface::new(
+            format!("{} Floor", self.name),
+            polygon(&corners)?,
+            self.floor_construction
+                .clone()
+                .unwrap_or_else(|| wall_construction.clone()),
+            self.floor_boundary.clone(),
+            inside.clone(),
+        );
+        floor.set_category(floor_category);
+        floor.transform(&transform)?;
+        model.add_surface(floor)?;
+
+        // Roof, facing up
+        let roof_category = match &self.roof_boundary {
+            Boundary::Space { .. } => SurfaceType::Ceiling,
+            _ => SurfaceType::Roof,
+        };
+        let corners: Vec<Point3D> = self
+            .footprint
+            .iter()
+            .map(|(x, y)| Point3D::new(*x, *y, h))
+            .collect();
+        let mut roof = Surface::new(
+            format!("{} Roof", self.name),
+            polygon(&corners)?,
+            self.roof_construction
+                .clone()
+                .unwrap_or_else(|| wall_construction.clone()),
+            self.roof_boundary.clone(),
+            inside,
+        );
+        roof.set_category(roof_category);
+        roof.transform(&transform)?;
+        model.add_surface(roof)?;
+
+        Ok(space)
+    }
+}
+
+/// Creates a [`Polygon3D`] out of its corners
+fn polygon(corners: &[Point3D]) -> Result<Polygon3D, String> {
+    let mut the_loop = Loop3D::with_capacity(corners.len());
+    for p in corners {
+        the_loop.push(*p)?;
+    }
+    the_loop.close()?;
+    Polygon3D::new(the_loop)
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use crate::{Construction, SurfaceMatchingOptions, SurfaceTrait};
+    use geometry::Vector3D;
+
+    fn model() -> Model {
+        let mut model = Model::default();
+        model.add_construction(Construction::new("Wall"));
+        model.add_construction(Construction::new("Glazing"));
+        model
+    }
+
+    #[test]
+    fn test_build() -> Result<(), String> {
+        let mut model = model();
+        SpaceBuilder::rectangular(4., 5., 3.)
+            .name("Office")
+            .facade_window_to_wall_ratio(Facade::South, 0.4)
+            .construction("Wall")
+            .window_construction("Glazing")
+            .build(&mut model)?;
+
+        assert_eq!(model.surfaces.len(), 6);
+        assert_eq!(model.fenestrations.len(), 1);
+
+        let wall = model.get_surface("Office South Wall")?;
+        let window = model.get_fenestration("Office South Window")?;
+        assert!(wall.normal().compare(Vector3D::new(0., -1., 0.)));
+        assert!((window.area() - 0.4 * 12.).abs() < 1e-6);
+        assert!((wall.area() - 0.6 * 12.).abs() < 1e-6);
+        assert!(matches!(&window.back_boundary, Boundary::Space{space} if space == "Office"));
+
+        let floor = model.get_surface("Office Floor")?;
+        assert!(floor.normal().compare(Vector3D::new(0., 0., -1.)));
+        assert_eq!(*floor.category()?, SurfaceType::GroundFloor);
+        assert!(matches!(floor.front_boundary, Boundary::Ground));
+        assert!(model
+            .get_surface("Office Roof")?
+            .normal()
+            .compare(Vector3D::new(0., 0., 1.)));
+
+        let (total_area, _) = model.get_space_sizes();
+        assert!((total_area - 20.).abs() < 1e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn test_orientation() -> Result<(), String> {
+        let mut model = model();
+        SpaceBuilder::rectangular(4., 5., 3.)
+            .origin(10., 0., 0.)
+            .orientation(90.)
+            .construction("Wall")
+            .build(&mut model)?;
+
+        // The 'South' wall faces East now
+        let wall = model.get_surface("Space South Wall")?;
+        assert!(wall.normal().compare(Vector3D::new(1., 0., 0.)));
+        assert!(wall
+            .outer()
+            .vertices()
+            .iter()
+            .all(|p| (p.x - 10.).abs() < 1e-9));
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut model = model();
+        // No construction
+        assert!(SpaceBuilder::rectangular(4., 5., 3.)
+            .build(&mut model)
+            .is_err());
+        // Windows, but no window construction
+        assert!(SpaceBuilder::rectangular(4., 5., 3.)
+            .construction("Wall")
+            .window_to_wall_ratio(0.2)
+            .build(&mut model)
+            .is_err());
+        // Too many windows
+        assert!(SpaceBuilder::rectangular(4., 5., 3.)
+            .construction("Wall")
+            .window_construction("Glazing")
+            .window_to_wall_ratio(1.)
+            .build(&mut model)
+            .is_err());
+        assert!(SpaceBuilder::rectangular(0., 5., 3.)
+            .construction("Wall")
+            .build(&mut model)
+            .is_err());
+    }
+
+    #[test]
+    fn test_adjacent_spaces() -> Result<(), String> {
+        let mut model = model();
+        SpaceBuilder::rectangular(4., 5., 3.)
+            .name("Kitchen")
+            .construction("Wall")
+            .build(&mut model)?;
+        SpaceBuilder::rectangular(4., 5., 3.)
+            .name("Living")
+            .origin(4., 0., 0.)
+            .construction("Wall")
+            .build(&mut model)?;
+
+        let n = model.match_surfaces(&SurfaceMatchingOptions::default())?;
+        assert_eq!(n, 1);
+        let wall = model.get_surface("Kitchen East Wall")?;
+        assert!(matches!(&wall.front_boundary, Boundary::Space{space} if space == "Living"));
+        Ok(())
+    }
+}
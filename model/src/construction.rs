@@ -18,6 +18,7 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 
+use crate::ThermalBridge;
 use derive::ObjectIO;
 use serde::{Deserialize, Serialize};
 
@@ -49,6 +50,13 @@ pub struct Construction {
     /// The indices of the Material objects in the
     /// materials property of the Model object
     pub materials: Vec<String>,
+
+    /// The linear [`ThermalBridge`](crate::ThermalBridge)s in every
+    /// `Surface` with this Construction (e.g., studs, or wall ties). Their `length`
+    /// is given per square meter of surface.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub thermal_bridges: Vec<ThermalBridge>,
     // front finishing
     // back finishing
 }
@@ -0,0 +1,353 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::archetypes::{clear_glass, layer, opaque};
+use crate::substance::gas::GasSpecification;
+use crate::substance::Gas;
+use crate::{Construction, Float, Model, SimpleError, Substance};
+use derive::ObjectIO;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// The layers of each preset in the library, as the name of their substance
+/// and their thickness (in m). Layers go from the outside (front) to the
+/// inside (back).
+const PRESETS: [(&str, &[(&str, Float)]); 12] = [
+    ("concrete-slab", &[("Concrete", 0.1)]),
+    (
+        "concrete-slab-insulated",
+        &[("Polystyrene", 0.05), ("Concrete", 0.1)],
+    ),
+    (
+        "NZ-3604-wall",
+        &[
+            ("Timber", 0.018),
+            ("Air", 0.02),
+            ("Glass Wool", 0.09),
+            ("Gypsum", 0.01),
+        ],
+    ),
+    (
+        "NZ-3604-wall-140",
+        &[
+            ("Timber", 0.018),
+            ("Air", 0.02),
+            ("Glass Wool", 0.14),
+            ("Gypsum", 0.01),
+        ],
+    ),
+    (
+        "NZ-3604-wall-uninsulated",
+        &[("Timber", 0.018), ("Air", 0.09), ("Gypsum", 0.01)],
+    ),
+    (
+        "brick-veneer-wall",
+        &[
+            ("Brick", 0.07),
+            ("Air", 0.04),
+            ("Glass Wool", 0.09),
+            ("Gypsum", 0.01),
+        ],
+    ),
+    (
+        "interior-wall",
+        &[("Gypsum", 0.01), ("Air", 0.09), ("Gypsum", 0.01)],
+    ),
+    ("NZ-3604-ceiling", &[("Glass Wool", 0.19), ("Gypsum", 0.01)]),
+    ("single-glazing", &[("Clear Glass", 0.006)]),
+    (
+        "double-glazing",
+        &[
+            ("Clear Glass", 0.006),
+            ("Air", 0.012),
+            ("Clear Glass", 0.006),
+        ],
+    ),
+    (
+        "double-glazing-argon",
+        &[
+            ("Clear Glass", 0.006),
+            ("Argon", 0.012),
+            ("Clear Glass", 0.006),
+        ],
+    ),
+    (
+        "triple-glazing",
+        &[
+            ("Clear Glass", 0.004),
+            ("Argon", 0.012),
+            ("Clear Glass", 0.004),
+            ("Argon", 0.012),
+            ("Clear Glass", 0.004),
+        ],
+    ),
+];
+
+/// The substances used by the presets in the library
+fn library_substance(name: &str) -> Substance {
+    let gas = |spec: GasSpecification| {
+        let mut gas = Gas::new(name);
+        gas.set_gas(spec);
+        gas.wrap()
+    };
+    match name {
+        "Concrete" => opaque(name, 1.4, 2300., 880.).wrap(),
+        "Brick" => opaque(name, 0.72, 1920., 840.).wrap(),
+        "Polystyrene" => opaque(name, 0.035, 20., 1450.).wrap(),
+        "Glass Wool" => opaque(name, 0.035, 12., 840.).wrap(),
+        "Gypsum" => opaque(name, 0.16, 800., 1090.).wrap(),
+        "Timber" => opaque(name, 0.14, 530., 1600.).wrap(),
+        "Clear Glass" => clear_glass().wrap(),
+        "Air" => gas(GasSpecification::Air),
+        "Argon" => gas(GasSpecification::Argon),
+        _ => unreachable!("Substance '{}' is not in the library", name),
+    }
+}
+
+/// Adds the `preset` to the `model` as a [`Construction`] called `name`
+/// (unless it already exists)
+fn add_preset(
+    model: &mut Model,
+    preset: &str,
+    name: &str,
+) -> Result<Arc<Construction>, SimpleError> {
+    if let Ok(c) = model.get_construction(name) {
+        return Ok(c);
+    }
+    let (_, layers) = PRESETS.iter().find(|(p, _)| *p == preset).ok_or_else(|| {
+        SimpleError::Other(format!(
+            "There is no construction preset called '{}'. Available presets are: {}",
+            preset,
+            Construction::library_presets().join(", ")
+        ))
+    })?;
+
+    let mut construction = Construction::new(name);
+    for (substance, thickness) in layers.iter() {
+        if model.get_substance(*substance).is_err() {
+            model.add_substance(library_substance(substance));
+        }
+        let material = layer(model, substance, *thickness);
+        construction.materials.push(material);
+    }
+    Ok(model.add_construction(construction))
+}
+
+impl Construction {
+    /// The names of the presets that can be loaded through
+    /// [`Construction::from_library`]
+    pub fn library_presets() -> Vec<&'static str> {
+        PRESETS.iter().map(|(name, _)| *name).collect()
+    }
+
+    /// Adds a [`Construction`] from the library of common constructions
+    /// to a `model`, together with the [`Substance`]s and
+    /// [`Material`](crate::Material)s it needs. The `Construction` is
+    /// named after the `preset`. Substances and materials that already
+    /// exist in the `model` (by name) are reused, and so is the
+    /// `Construction` if the preset was loaded before.
+    ///
+    /// ```
+    /// use model::{Construction, Model};
+    ///
+    /// let mut model = Model::default();
+    /// let wall = Construction::from_library("NZ-3604-wall", &mut model).unwrap();
+    /// assert_eq!(wall.materials.len(), 4);
+    /// assert_eq!(model.constructions.len(), 1);
+    ///
+    /// assert!(Construction::from_library("Cardboard wall", &mut model).is_err());
+    /// ```
+    pub fn from_library(preset: &str, model: &mut Model) -> Result<Arc<Construction>, SimpleError> {
+        add_preset(model, preset, preset)
+    }
+}
+
+/// A [`Construction`] taken from the library of common constructions,
+/// which adds the `Construction`—as well as its
+/// [`Material`](crate::Material)s and [`Substance`]s—to the model.
+/// The presets go from the outside (front) to the inside (back), and are:
+///
+/// | Preset | Layers |
+/// |--------|--------|
+/// | `concrete-slab` | 100mm concrete |
+/// | `concrete-slab-insulated` | 50mm polystyrene, 100mm concrete |
+/// | `NZ-3604-wall` | 18mm timber weatherboard, 20mm cavity, 90mm glass wool, 10mm plasterboard |
+/// | `NZ-3604-wall-140` | 18mm timber weatherboard, 20mm cavity, 140mm glass wool, 10mm plasterboard |
+/// | `NZ-3604-wall-uninsulated` | 18mm timber weatherboard, 90mm cavity, 10mm plasterboard |
+/// | `brick-veneer-wall` | 70mm brick, 40mm cavity, 90mm glass wool, 10mm plasterboard |
+/// | `interior-wall` | 10mm plasterboard, 90mm cavity, 10mm plasterboard |
+/// | `NZ-3604-ceiling` | 190mm glass wool, 10mm plasterboard |
+/// | `single-glazing` | 6mm clear glass |
+/// | `double-glazing` | 6mm clear glass, 12mm air, 6mm clear glass |
+/// | `double-glazing-argon` | 6mm clear glass, 12mm argon, 6mm clear glass |
+/// | `triple-glazing` | 4mm clear glass, 12mm argon, 4mm clear glass, 12mm argon, 4mm clear glass |
+///
+/// Substances and materials are named after what they are made of (e.g.,
+/// `"Glass Wool"` and `"Glass Wool 90mm"`), and those that already exist
+/// in the model are reused.
+///
+/// ## Examples
+///
+/// #### `.spl`
+/// ```rs
+/// {{#include ../../../model/tests/scanner/construction_preset.spl}}
+/// ```
+/// #### `.json`
+/// ```rs
+/// {{#include ../../../model/tests/scanner/construction_preset.json}}
+/// ```
+#[derive(Debug, Clone, ObjectIO, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConstructionPreset {
+    /// The name of the preset in the library
+    pub preset: String,
+
+    /// The name given to the `Construction` in the model. If none
+    /// is given, the name of the `preset` is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    name: Option<String>,
+}
+
+impl ConstructionPreset {
+    /// Adds the preset to the `model`, returning the new [`Construction`]
+    pub fn add_to_model(&self, model: &mut Model) -> Result<Arc<Construction>, SimpleError> {
+        let name = self.name.as_deref().unwrap_or(&self.preset);
+        add_preset(model, &self.preset, name)
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn serde() -> Result<(), String> {
+        use json5;
+        use std::fs;
+
+        // Hardcode a reference
+        let mut hardcoded_ref = ConstructionPreset::new("NZ-3604-wall");
+        hardcoded_ref.set_name("Exterior wall");
+
+        // Deserialize from hardcoded string and check they are the same
+        let from_hardcoded_json: ConstructionPreset = json5::from_str(
+            "{
+            preset: 'NZ-3604-wall',
+            name: 'Exterior wall',
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_hardcoded_json)
+        );
+
+        // Read json file (used in DOC), Deserialize, and compare
+        let json_data = fs::read_to_string("./tests/scanner/construction_preset.json")
+            .map_err(|e| e.to_string())?;
+        let from_json_file: ConstructionPreset =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_json_file)
+        );
+
+        // Serialize and deserialize again... check that everythin matches the pattern
+        let rust_json = serde_json::to_string(&hardcoded_ref).map_err(|e| e.to_string())?;
+        let from_serialized: ConstructionPreset =
+            serde_json::from_str(&rust_json).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_serialized)
+        );
+
+        // check simple
+        let (model, ..) = Model::from_file("./tests/scanner/construction_preset.spl")
+            .map_err(|e| e.to_string())?;
+        assert_eq!(model.constructions.len(), 2);
+        let wall = model.get_construction("Exterior wall")?;
+        assert_eq!(wall.materials[2], "Glass Wool 90mm");
+        let window = model.get_construction("double-glazing")?;
+        assert_eq!(window.materials.len(), 3);
+        // "Gypsum", "Timber", "Air", "Glass Wool" and "Clear Glass"
+        assert_eq!(model.substances.len(), 5);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_all_presets() -> Result<(), String> {
+        let mut model = Model::default();
+        for preset in Construction::library_presets() {
+            let c = Construction::from_library(preset, &mut model)?;
+            let u = c.u_value(&model)?;
+            assert!(u > 0.1 && u < 6., "U-value of '{}' is {}", preset, u);
+        }
+        assert_eq!(model.constructions.len(), PRESETS.len());
+
+        // Loading them again does not duplicate anything
+        let n_materials = model.materials.len();
+        let n_substances = model.substances.len();
+        Construction::from_library("NZ-3604-wall", &mut model)?;
+        assert_eq!(model.constructions.len(), PRESETS.len());
+        assert_eq!(model.materials.len(), n_materials);
+        assert_eq!(model.substances.len(), n_substances);
+
+        let u =
+            |name: &str| -> Result<Float, String> { model.get_construction(name)?.u_value(&model) };
+        let wall = u("NZ-3604-wall")?;
+        assert!(wall > 0.25 && wall < 0.4, "U = {}", wall);
+        assert!(u("NZ-3604-wall-140")? < wall);
+        assert!(u("NZ-3604-wall-uninsulated")? > 1.);
+        assert!(u("concrete-slab-insulated")? < u("concrete-slab")?);
+        assert!(u("triple-glazing")? < u("double-glazing-argon")?);
+        assert!(u("double-glazing-argon")? < u("double-glazing")?);
+        assert!(u("double-glazing")? < u("single-glazing")?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_existing_substances() -> Result<(), String> {
+        // Substances with the same name are reused, even if different
+        let mut model = Model::default();
+        model.add_substance(opaque("Timber", 0.2, 600., 1600.).wrap());
+        Construction::from_library("NZ-3604-wall", &mut model)?;
+        assert_eq!(model.substances.len(), 4);
+        if let Substance::Normal(timber) = model.get_substance("Timber")? {
+            assert_eq!(timber.thermal_conductivity()?, &0.2);
+        } else {
+            panic!("Timber should be a Normal substance");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_preset() {
+        let mut model = Model::default();
+        let err = Construction::from_library("Cardboard wall", &mut model).unwrap_err();
+        assert!(err.to_string().contains("NZ-3604-wall"));
+        assert!(model.constructions.is_empty());
+        assert!(model.substances.is_empty());
+    }
+}
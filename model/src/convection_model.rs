@@ -0,0 +1,127 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::Float;
+use derive::ObjectIO;
+use serde::{Deserialize, Serialize};
+
+/// The correlation used for calculating the convective heat transfer
+/// coefficient between a side of a `Surface` or `Fenestration` and the air.
+///
+/// The correlation used for the interior and exterior sides of all
+/// the surfaces can be set through the [`ThermalOptions`](crate::ThermalOptions),
+/// and overridden for each side of a `Surface` or `Fenestration`. Sides
+/// with a `precalculated_front_convection_coef` or
+/// `precalculated_back_convection_coef` use that value instead.
+///
+/// > **Note**: This object cannot be declared by itself in a `SIMPLE` model. It is always
+/// embedded on a `Surface`, a `Fenestration` or the `ThermalOptions`
+///
+/// ## Examples
+///
+/// #### A `Doe2` model (in `.json`)
+/// ```json
+/// {{#include ../../../model/tests/scanner/convection_model_doe2.json}}
+/// ```
+/// #### A `Fixed` coefficient (in `.json`)
+/// ```json
+/// {{#include ../../../model/tests/scanner/convection_model_fixed.json}}
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, ObjectIO, Serialize, Deserialize, Default)]
+#[serde(tag = "type")]
+#[serde(deny_unknown_fields)]
+pub enum ConvectionModel {
+    /// The TARP correlations, as described in EnergyPlus' Engineering
+    /// Reference. Interior sides only consider natural convection, while
+    /// exterior sides add a forced component that depends on the wind
+    /// and the roughness of the surface. This is the default.
+    #[default]
+    Tarp,
+
+    /// The DOE-2 correlations, as described in EnergyPlus' Engineering
+    /// Reference. Exterior sides combine the TARP natural convection with
+    /// a forced component fitted to measurements over glass; interior
+    /// sides use the TARP natural convection.
+    Doe2,
+
+    /// Fixed coefficients that depend on the orientation of the surface and
+    /// on the direction of the heat flow (for interior sides), or on the
+    /// wind speed and the roughness of the surface (for exterior sides), as
+    /// given by EnergyPlus' `Simple` and `SimpleCombined` algorithms.
+    ///
+    /// Note that the exterior coefficients include the long-wave radiant
+    /// exchange, which is also calculated separately by SIMPLE.
+    SimpleCombined,
+
+    /// A fixed convection coefficient
+    Fixed {
+        /// The convection coefficient, in `W/m2K`
+        coefficient: Float,
+    },
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn serde_doe2() -> Result<(), String> {
+        let from_hardcoded_json: ConvectionModel =
+            json5::from_str("{ type: 'Doe2' }").map_err(|e| e.to_string())?;
+        assert_eq!(from_hardcoded_json, ConvectionModel::Doe2);
+
+        // Read json file (used in DOC), Deserialize, and compare
+        let json_data = fs::read_to_string("./tests/scanner/convection_model_doe2.json")
+            .map_err(|e| e.to_string())?;
+        let from_json_file: ConvectionModel =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(from_json_file, ConvectionModel::Doe2);
+
+        assert_eq!(ConvectionModel::default(), ConvectionModel::Tarp);
+        Ok(())
+    }
+
+    #[test]
+    fn serde_fixed() -> Result<(), String> {
+        let hardcoded_ref = ConvectionModel::Fixed { coefficient: 3.5 };
+
+        // Read json file (used in DOC), Deserialize, and compare
+        let json_data = fs::read_to_string("./tests/scanner/convection_model_fixed.json")
+            .map_err(|e| e.to_string())?;
+        let from_json_file: ConvectionModel =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(from_json_file, hardcoded_ref);
+
+        // Serialize and deserialize again
+        let rust_json = serde_json::to_string(&hardcoded_ref).map_err(|e| e.to_string())?;
+        let from_serialized: ConvectionModel =
+            serde_json::from_str(&rust_json).map_err(|e| e.to_string())?;
+        assert_eq!(from_serialized, hardcoded_ref);
+
+        // Unknown models are rejected
+        assert!(json5::from_str::<ConvectionModel>("{ type: 'Magic' }").is_err());
+        Ok(())
+    }
+}
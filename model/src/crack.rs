@@ -0,0 +1,104 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use crate::Float;
+use derive::ObjectIO;
+use serde::{Deserialize, Serialize};
+
+/// A crack through which air can leak, following a power law.
+///
+/// The mass flow through the crack (in `kg/s`) is calculated as
+///
+/// $$ \dot{m} = C \Delta P ^ n $$
+///
+/// where $C$ is the `flow_coefficient` (i.e., the mass flow at a
+/// pressure difference of $1 Pa$) and $n$ is the `flow_exponent`. This
+/// is equivalent to EnergyPlus' `AirflowNetwork:MultiZone:Surface:Crack`.
+///
+/// Cracks are only used by the [`Infiltration::AirFlowNetwork`](crate::Infiltration)
+/// calculations.
+///
+/// ## Example
+///
+/// #### `.json`
+/// ```json
+/// {{#include ../../../model/tests/scanner/crack.json}}
+/// ```
+///
+/// > **Note**: This object cannot be declared by itself in a `SIMPLE` model,
+/// as it is always embeded on a `Surface` or a `Fenestration`
+#[derive(Debug, Clone, ObjectIO, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Crack {
+    /// The air mass flow through the crack at a pressure
+    /// difference of $1 Pa$, in $kg/s$
+    pub flow_coefficient: Float,
+
+    /// The flow exponent $n$, between $0.5$ (i.e., a large opening) and $1$
+    /// (i.e., laminar flow). If none is given, a value of $0.65$ is assumed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    flow_exponent: Option<Float>,
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn serde() -> Result<(), String> {
+        use json5;
+        use std::fs;
+
+        // Hardcode a reference
+        let mut hardcoded_ref = Crack::new(0.001);
+        hardcoded_ref.set_flow_exponent(0.7);
+
+        // Deserialize from hardcoded string and check they are the same
+        let from_hardcoded_json: Crack = json5::from_str(
+            "{
+            flow_coefficient: 0.001,
+            flow_exponent: 0.7
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_hardcoded_json)
+        );
+
+        // Read json file (used in DOC), Deserialize, and compare
+        let json_data =
+            fs::read_to_string("./tests/scanner/crack.json").map_err(|e| e.to_string())?;
+        let from_json_file: Crack = serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_json_file)
+        );
+
+        // Serialize and deserialize again... check that everythin matches the pattern
+        let rust_json = serde_json::to_string(&hardcoded_ref).map_err(|e| e.to_string())?;
+        let from_serialized: Crack = serde_json::from_str(&rust_json).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_serialized)
+        );
+
+        Ok(())
+    }
+}
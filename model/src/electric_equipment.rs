@@ -0,0 +1,145 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use crate::model::Model;
+use crate::simulation_state_element::StateElementField;
+use crate::Float;
+use derive::{ObjectAPI, ObjectIO};
+use serde::{Deserialize, Serialize};
+
+/// An electric appliance or group of appliances (i.e., plug loads) that
+/// dissipate heat into a space (e.g., computers, fridges or TVs).
+///
+/// ## Examples
+///
+/// ##### `.spl`
+/// ```json
+/// {{#include ../../../model/tests/scanner/electric_equipment.spl}}
+/// ```
+///
+/// ##### `.json`
+/// ```json
+/// {{#include ../../../model/tests/scanner/electric_equipment.json}}
+/// ```
+#[derive(Debug, ObjectIO, ObjectAPI, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ElectricEquipment {
+    /// The name of the ElectricEquipment
+    name: String,
+
+    /// The maximum power consumption, in Watts
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_power: Option<Float>,
+
+    /// The name of the space in which the equipment is located, and into
+    /// which its heat is dissipated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target_space: Option<String>,
+
+    /// The fraction of the power consumption that is released as
+    /// long-wave radiation. The rest (i.e., `1 - fraction_radiant`)
+    /// is released by convection into the air of the `target_space`.
+    ///
+    /// > **Note:** At present, the thermal model delivers all the heat into
+    /// > the air of the space, regardless of this value.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fraction_radiant: Option<Float>,
+
+    /// The fraction of the power consumption that is released as
+    /// moisture (i.e., latent heat) into the `target_space` (e.g.,
+    /// kettles or dishwashers). This heat is not added to the air
+    /// temperature but to its humidity.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fraction_latent: Option<Float>,
+
+    /// The name of the [`Schedule`](crate::Schedule) that multiplies the
+    /// `max_power` of the equipment. If none is given, the power consumption
+    /// is only modified by the control algorithms.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schedule: Option<String>,
+
+    /// The state of the equipment
+    #[operational]
+    #[serde(skip)]
+    power_consumption: StateElementField,
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use crate::Model;
+
+    #[test]
+    fn serde() -> Result<(), String> {
+        use json5;
+        use std::fs;
+
+        // Hardcode a reference
+        let mut hardcoded_ref = ElectricEquipment::new("Some Fridge");
+        hardcoded_ref
+            .set_max_power(150.)
+            .set_target_space("Kitchen")
+            .set_fraction_radiant(0.3);
+
+        // Deserialize from hardcoded string and check they are the same
+        let from_hardcoded_json: ElectricEquipment = json5::from_str(
+            "{
+            name: 'Some Fridge',
+            max_power: 150,
+            target_space: 'Kitchen',
+            fraction_radiant: 0.3,
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_hardcoded_json)
+        );
+
+        // Read json file (used in DOC), Deserialize, and compare
+        let filename = "./tests/scanner/electric_equipment";
+        let json_file = format!("{}.json", filename);
+        let json_data = fs::read_to_string(json_file).map_err(|e| e.to_string())?;
+        let from_json_file: ElectricEquipment =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_json_file)
+        );
+
+        // Serialize and deserialize again... check that everythin matches the pattern
+        let rust_json = serde_json::to_string(&hardcoded_ref).map_err(|e| e.to_string())?;
+        let from_serialized: ElectricEquipment =
+            serde_json::from_str(&rust_json).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_serialized)
+        );
+
+        // check simple
+        let (model, ..) = Model::from_file("./tests/scanner/electric_equipment.spl")?;
+        assert_eq!(model.electric_equipment.len(), 1);
+        assert_eq!(
+            model.electric_equipment[0].name(),
+            &"Some Fridge".to_string()
+        );
+
+        Ok(())
+    }
+}
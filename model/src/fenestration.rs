@@ -17,7 +17,10 @@ LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
 OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
-use crate::{Float, SurfaceTrait};
+use crate::{
+    ConvectionModel, Crack, Float, FrameAreas, ShadingDevice, SurfaceTrait, SwitchableGlazing,
+    WindowFrame,
+};
 
 use derive::{ObjectAPI, ObjectIO};
 
@@ -38,10 +41,15 @@ use crate::{SimulationState, SimulationStateElement, SimulationStateHeader};
 /// {{#include ../../../model/tests/scanner/fenestration_position.json}}
 /// ```
 ///
+/// #### `.json` (with discrete positions)
+/// ```json
+/// {{#include ../../../model/tests/scanner/fenestration_position_discrete.json}}
+/// ```
+///
 /// > **Note**: This object cannot be declared by itself in a `SIMPLE` model,
 /// as it is always embeded on a `Fenestration` object
 ///
-#[derive(Debug, Copy, Clone, ObjectIO, Serialize, Deserialize)]
+#[derive(Debug, Clone, ObjectIO, Serialize, Deserialize)]
 #[serde(tag = "type")]
 #[serde(deny_unknown_fields)]
 pub enum FenestrationPosition {
@@ -66,6 +74,71 @@ pub enum FenestrationPosition {
         /// The open-fraction when this `Fenestration` is closed
         closed: Option<Float>,
     },
+    /// It can only be placed at some discrete positions (e.g., closed,
+    /// trickle ventilation, half open and fully open). The `Fenestration`
+    /// starts at the first one, and the current position can be changed by
+    /// name through the `position` property of the Rhai API.
+    Discrete {
+        /// The positions in which this `Fenestration` can be placed
+        positions: Vec<OpeningPosition>,
+    },
+}
+
+/// One of the positions of a [`Fenestration`] whose operation is
+/// [`FenestrationPosition::Discrete`]. The effective area through which
+/// air flows can be given as a fraction of the area of the `Fenestration`
+/// (i.e., `open_fraction`) or directly as an `open_area` (e.g., that of a
+/// trickle vent); exactly one of them must be given.
+///
+/// ## Example
+///
+/// #### `.json`
+/// ```json
+/// {{#include ../../../model/tests/scanner/opening_position.json}}
+/// ```
+///
+/// > **Note**: This object cannot be declared by itself in a `SIMPLE` model,
+/// as it is always embeded on a `FenestrationPosition` object
+#[derive(Debug, Clone, ObjectIO, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct OpeningPosition {
+    /// The name of the position (e.g., `"closed"` or `"trickle"`), which
+    /// must be unique within the `Fenestration`
+    pub name: String,
+
+    /// The fraction of the area of the `Fenestration` that is open at
+    /// this position
+    #[serde(skip_serializing_if = "Option::is_none")]
+    open_fraction: Option<Float>,
+
+    /// The effective open area at this position, in $`m^2`$
+    #[serde(skip_serializing_if = "Option::is_none")]
+    open_area: Option<Float>,
+}
+
+impl OpeningPosition {
+    /// Calculates the open fraction of this position when installed on
+    /// a fenestration of a certain `area` (in $`m^2`$)
+    pub fn fraction_of(&self, area: Float) -> Result<Float, String> {
+        let fraction = match (self.open_fraction, self.open_area) {
+            (Some(fraction), None) => fraction,
+            (None, Some(open_area)) if area > 0. => open_area / area,
+            (None, Some(_)) => 0.,
+            _ => {
+                return Err(format!(
+                    "OpeningPosition '{}' must have either an 'open_fraction' or an 'open_area'",
+                    self.name
+                ))
+            }
+        };
+        if !(0. ..=1.).contains(&fraction) {
+            return Err(format!(
+                "The open fraction of OpeningPosition '{}' must be between 0 and 1... found {}",
+                self.name, fraction
+            ));
+        }
+        Ok(fraction)
+    }
 }
 
 /// Defines whether the fenestration is a Door or a Window.
@@ -132,6 +205,11 @@ pub struct Fenestration {
     /// The opportunity for operating the Fenestration.
     /// If none is given, the window is assumed to be Fixed
     /// at Closed position.
+    ///
+    /// Operable fenestrations that lead outdoors ventilate the
+    /// [`Space`](crate::Space) they face according to their `open_fraction`,
+    /// even if such space is not in an
+    /// [`Infiltration::AirFlowNetwork`](crate::Infiltration).
     #[serde(skip_serializing_if = "Option::is_none")]
     operation: Option<FenestrationPosition>,
 
@@ -159,6 +237,16 @@ pub struct Fenestration {
     #[serde(skip_serializing_if = "Option::is_none")]
     precalculated_back_convection_coef: Option<Float>,
 
+    /// The correlation used for calculating the front convection coefficient,
+    /// overriding the one set in the [`ThermalOptions`](crate::ThermalOptions)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    front_convection_model: Option<ConvectionModel>,
+
+    /// The correlation used for calculating the back convection coefficient,
+    /// overriding the one set in the [`ThermalOptions`](crate::ThermalOptions)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    back_convection_model: Option<ConvectionModel>,
+
     /// The name of the surface containing this `Fenestration`,
     /// if any. A hole will be made in the parent surface in order
     /// to accomodate
@@ -178,6 +266,43 @@ pub struct Fenestration {
     #[serde(skip_serializing)]
     parent_surface: Option<String>,
 
+    /// The [`Crack`](crate::Crack) through which air leaks across this
+    /// fenestration (e.g., around the frame). This is only used when
+    /// the [`Space`](crate::Space)s at each side of it have an
+    /// [`Infiltration::AirFlowNetwork`](crate::Infiltration).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    crack: Option<Crack>,
+
+    /// The discharge coefficient of the `Fenestration` when it is
+    /// open, used by the [`Infiltration::AirFlowNetwork`](crate::Infiltration)
+    /// and natural ventilation calculations. If none is given, a value of
+    /// $0.6$ is assumed.
+    ///
+    /// The mass flow through the opening is $`C_d A_{open} \sqrt{2 \rho \Delta P}`$,
+    /// where $`A_{open}`$ is the area of the `Fenestration` multiplied by its
+    /// open fraction. Fenestrations of category `Opening` are always fully open.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    discharge_coefficient: Option<Float>,
+
+    /// The [`ShadingDevice`](crate::ShadingDevice) (e.g., a blind or a shade)
+    /// installed on this `Fenestration`, if any. How much it is deployed
+    /// is controlled through the `shade_open_fraction`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    shading_device: Option<ShadingDevice>,
+
+    /// The [`SwitchableGlazing`](crate::SwitchableGlazing) of this `Fenestration`,
+    /// if its glass can be tinted (e.g., electrochromic glass). How tinted it
+    /// is is controlled through the `tint_level`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    switchable_glazing: Option<SwitchableGlazing>,
+
+    /// The [`WindowFrame`](crate::WindowFrame) of this `Fenestration`, if
+    /// any. The frame and its dividers take part of the area of the
+    /// `Fenestration`, so only the rest of it is simulated with its
+    /// `construction`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    frame: Option<WindowFrame>,
+
     #[physical("front_temperature")]
     #[serde(skip)]
     first_node_temperature: StateElementField,
@@ -192,6 +317,18 @@ pub struct Fenestration {
     #[serde(skip)]
     open_fraction: StateElementField,
 
+    /// Index of the SimulationStateElement representing
+    /// how open (i.e., retracted) the shading device is
+    #[operational]
+    #[serde(skip)]
+    shade_open_fraction: StateElementField,
+
+    /// Index of the SimulationStateElement representing
+    /// how tinted the switchable glazing is
+    #[operational]
+    #[serde(skip)]
+    tint_level: StateElementField,
+
     #[physical]
     #[serde(skip)]
     front_convection_coefficient: StateElementField,
@@ -509,11 +646,202 @@ impl Fenestration {
                 FenestrationPosition::Fixed { .. } => false,
                 FenestrationPosition::Continuous { .. } => true,
                 FenestrationPosition::Binary { .. } => true,
+                FenestrationPosition::Discrete { positions } => positions.len() > 1,
             }
         } else {
             false
         }
     }
+
+    /// Gets the [`OpeningPosition`]s of this fenestration, if its operation
+    /// is [`FenestrationPosition::Discrete`]
+    fn discrete_positions(&self) -> Result<&Vec<OpeningPosition>, String> {
+        match &self.operation {
+            Some(FenestrationPosition::Discrete { positions }) => Ok(positions),
+            _ => Err(format!(
+                "Fenestration '{}' does not have discrete positions",
+                self.name
+            )),
+        }
+    }
+
+    /// Checks that the [`OpeningPosition`]s of this fenestration (if any) are
+    /// valid, returning the open fraction of the first one (i.e., the initial
+    /// one). Fenestrations without discrete positions start closed.
+    pub fn initial_open_fraction(&self) -> Result<Float, String> {
+        let positions = match self.discrete_positions() {
+            Ok(p) => p,
+            Err(_) => return Ok(0.),
+        };
+        let area = self.area();
+        for (i, p) in positions.iter().enumerate() {
+            p.fraction_of(area)?;
+            if positions[..i].iter().any(|other| other.name == p.name) {
+                return Err(format!(
+                    "Fenestration '{}' has more than one position called '{}'",
+                    self.name, p.name
+                ));
+            }
+        }
+        match positions.first() {
+            Some(p) => p.fraction_of(area),
+            None => Err(format!(
+                "Fenestration '{}' has discrete operation but no positions",
+                self.name
+            )),
+        }
+    }
+
+    /// Gets the name of the [`OpeningPosition`] whose open fraction is closest
+    /// to the current open fraction of this fenestration
+    pub fn current_position(&self, state: &SimulationState) -> Result<&str, String> {
+        let positions = self.discrete_positions()?;
+        let current = self
+            .open_fraction(state)
+            .ok_or_else(|| format!("Fenestration '{}' has no open fraction", self.name))?;
+        let area = self.area();
+        let mut ret: Option<(&str, Float)> = None;
+        for p in positions.iter() {
+            let distance = (p.fraction_of(area)? - current).abs();
+            if ret.is_none_or(|(_, d)| distance < d) {
+                ret = Some((&p.name, distance));
+            }
+        }
+        ret.map(|(name, _)| name)
+            .ok_or_else(|| format!("Fenestration '{}' has no positions", self.name))
+    }
+
+    /// Places this fenestration at the [`OpeningPosition`] called `name`,
+    /// setting its open fraction accordingly
+    pub fn set_position(&self, state: &mut SimulationState, name: &str) -> Result<(), String> {
+        let position = self
+            .discrete_positions()?
+            .iter()
+            .find(|p| p.name == name)
+            .ok_or_else(|| {
+                format!(
+                    "Fenestration '{}' has no position called '{}'",
+                    self.name, name
+                )
+            })?;
+        let fraction = position.fraction_of(self.area())?;
+        self.set_open_fraction(state, fraction)
+    }
+
+    /// Gets the width and height (in $`m`$) of the rectangle used for
+    /// calculating the areas of the [`WindowFrame`]. Its height is that of
+    /// the `Fenestration` along its plane—or the square root of its area, if
+    /// it is horizontal—and its width is such that both have the same area.
+    pub fn equivalent_dimensions(&self) -> (Float, Float) {
+        let area = self.area();
+        let normal = self.vertices.normal();
+        let sin_tilt = (1. - normal.z * normal.z).max(0.).sqrt();
+        let height = match self.vertices.outer().bbox() {
+            Ok(bbox) if sin_tilt > 1e-3 => (bbox.max.z - bbox.min.z) / sin_tilt,
+            _ => area.sqrt(),
+        };
+        if height > 0. {
+            (area / height, height)
+        } else {
+            (0., 0.)
+        }
+    }
+
+    /// Calculates the [`FrameAreas`] of the [`WindowFrame`] of this
+    /// `Fenestration`, if it has one
+    pub fn frame_areas(&self) -> Result<Option<FrameAreas>, String> {
+        match &self.frame {
+            Some(frame) => {
+                let (width, height) = self.equivalent_dimensions();
+                let areas = frame
+                    .areas(width, height)
+                    .map_err(|e| format!("Fenestration '{}': {}", self.name, e))?;
+                Ok(Some(areas))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Gets the area of the glazing of the `Fenestration` (i.e., its area
+    /// minus that of its [`WindowFrame`] and dividers), in $`m^2`$
+    pub fn glazed_area(&self) -> Result<Float, String> {
+        match self.frame_areas()? {
+            Some(areas) => Ok(areas.glazing),
+            None => Ok(self.area()),
+        }
+    }
+
+    /// Gets the fraction of the area of the `Fenestration` that is glazed
+    pub fn glazed_fraction(&self) -> Result<Float, String> {
+        let area = self.area();
+        if area > 0. {
+            Ok(self.glazed_area()? / area)
+        } else {
+            Ok(1.)
+        }
+    }
+
+    /// Calculates the heat transfer coefficient (in $`W/K`$) of the
+    /// [`WindowFrame`] of this `Fenestration`. This is zero if it has no frame.
+    pub fn frame_heat_transfer_coefficient(&self) -> Result<Float, String> {
+        match (&self.frame, self.frame_areas()?) {
+            (Some(frame), Some(areas)) => frame.heat_transfer_coefficient(&areas),
+            _ => Ok(0.),
+        }
+    }
+
+    /// Calculates the U-value of the whole `Fenestration` (in $`W/m^2K`$),
+    /// accounting for the glazing (i.e., its `construction`), and for the
+    /// frame, dividers and edge of the glass of its [`WindowFrame`]
+    pub fn u_value(&self, model: &Model) -> Result<Float, String> {
+        let area = self.area();
+        if area <= 0. {
+            return Err(format!("Fenestration '{}' has no area", self.name));
+        }
+        let construction = model.get_construction(&self.construction)?;
+        let glazing = construction.u_value(model)? * self.glazed_area()?;
+        Ok((glazing + self.frame_heat_transfer_coefficient()?) / area)
+    }
+
+    /// Gets the fraction of the solar radiation that goes through the
+    /// [`ShadingDevice`] of this fenestration. This is 1 if there is no
+    /// device.
+    pub fn solar_shading_factor(&self, state: &SimulationState) -> Float {
+        match &self.shading_device {
+            Some(d) => d.solar_factor(self.shade_open_fraction(state).unwrap_or(1.)),
+            None => 1.,
+        }
+    }
+
+    /// Gets the fraction of the visible light that goes through the
+    /// [`ShadingDevice`] of this fenestration. This is 1 if there is no
+    /// device.
+    pub fn visible_shading_factor(&self, state: &SimulationState) -> Float {
+        match &self.shading_device {
+            Some(d) => d.visible_factor(self.shade_open_fraction(state).unwrap_or(1.)),
+            None => 1.,
+        }
+    }
+
+    /// Gets the fraction of the solar radiation transmitted by the clear
+    /// glazing that goes through the [`SwitchableGlazing`] of this fenestration,
+    /// given its current tint. This is 1 if the glazing is not switchable.
+    pub fn solar_tint_factor(&self, state: &SimulationState) -> Float {
+        match &self.switchable_glazing {
+            Some(g) => g.solar_factor(self.tint_level(state).unwrap_or(0.)),
+            None => 1.,
+        }
+    }
+
+    /// Gets the fraction of the visible light transmitted by the clear
+    /// glazing that goes through the [`SwitchableGlazing`] of this fenestration,
+    /// given its current tint. This is 1 if the glazing is not switchable.
+    pub fn visible_tint_factor(&self, state: &SimulationState) -> Float {
+        match &self.switchable_glazing {
+            Some(g) => g.visible_factor(self.tint_level(state).unwrap_or(0.)),
+            None => 1.,
+        }
+    }
 }
 
 /***********/
@@ -699,4 +1027,155 @@ mod testing {
 
         Ok(())
     }
+
+    #[test]
+    fn test_frame() -> Result<(), String> {
+        let mut model = Model::default();
+        crate::Construction::from_library("single-glazing", &mut model)?;
+        let mut fen: Fenestration = json5::from_str(
+            "{
+            name: 'Window',
+            construction: 'single-glazing',
+            vertices: [
+                0, 0, 0.5,
+                1.2, 0, 0.5,
+                1.2, 0, 2.0,
+                0, 0, 2.0,
+            ],
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+
+        // No frame
+        let u_glass = model.get_construction("single-glazing")?.u_value(&model)?;
+        assert!((fen.glazed_area()? - 1.8).abs() < 1e-6);
+        assert!(fen.frame_heat_transfer_coefficient()?.abs() < 1e-9);
+        assert!((fen.u_value(&model)? - u_glass).abs() < 1e-6);
+
+        // A 5cm frame
+        let (width, height) = fen.equivalent_dimensions();
+        assert!((width - 1.2).abs() < 1e-6);
+        assert!((height - 1.5).abs() < 1e-6);
+        fen.set_frame(WindowFrame::new(0.05, 5.));
+        let glazed = 1.1 * 1.4;
+        assert!((fen.glazed_area()? - glazed).abs() < 1e-6);
+        assert!((fen.glazed_fraction()? - glazed / 1.8).abs() < 1e-6);
+        let u_frame = 1. / (crate::R_SI + 1. / 5. + crate::R_SE);
+        let expected = (u_glass * glazed + u_frame * (1.8 - glazed)) / 1.8;
+        assert!((fen.u_value(&model)? - expected).abs() < 1e-6);
+
+        // Frames that do not fit
+        fen.set_frame(WindowFrame::new(0.8, 5.));
+        assert!(fen.glazed_area().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_discrete_positions() -> Result<(), String> {
+        use std::fs;
+
+        let json_data = fs::read_to_string("./tests/scanner/fenestration_position_discrete.json")
+            .map_err(|e| e.to_string())?;
+        let operation: FenestrationPosition =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+
+        let opening: OpeningPosition = serde_json::from_str(
+            &fs::read_to_string("./tests/scanner/opening_position.json")
+                .map_err(|e| e.to_string())?,
+        )
+        .map_err(|e| e.to_string())?;
+        assert_eq!(opening.name, "trickle");
+        assert!((opening.fraction_of(2.)? - 0.004).abs() < 1e-9);
+
+        let mut model = Model::default();
+        crate::Construction::from_library("single-glazing", &mut model)?;
+        // A 2m2 window
+        let mut fen: Fenestration = json5::from_str(
+            "{
+            name: 'Window',
+            construction: 'single-glazing',
+            back_boundary: { type: 'Space', space: 'Room' },
+            vertices: [
+                0, 0, 0,
+                2, 0, 0,
+                2, 0, 1,
+                0, 0, 1,
+            ],
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert!(fen.set_position(&mut vec![0.], "half").is_err());
+        fen.set_operation(operation);
+        assert!(fen.is_operable());
+        let fen = model.add_fenestration(fen)?;
+        let mut state = model
+            .take_state()
+            .ok_or("No state")?
+            .take_values()
+            .ok_or("No values")?;
+
+        // It starts at the first position
+        assert_eq!(fen.current_position(&state)?, "closed");
+        assert!(fen.open_fraction(&state).ok_or("No open fraction")?.abs() < 1e-9);
+
+        fen.set_position(&mut state, "trickle")?;
+        assert_eq!(fen.current_position(&state)?, "trickle");
+        let open = fen.open_fraction(&state).ok_or("No open fraction")?;
+        assert!((open - 0.004).abs() < 1e-9);
+
+        fen.set_position(&mut state, "half")?;
+        assert!((fen.open_fraction(&state).ok_or("No open fraction")? - 0.5).abs() < 1e-9);
+        // Continuous values are reported as the closest position
+        fen.set_open_fraction(&mut state, 0.9)?;
+        assert_eq!(fen.current_position(&state)?, "full");
+
+        assert!(fen.set_position(&mut state, "ajar").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_positions() -> Result<(), String> {
+        let positions = |json: &str| -> Result<FenestrationPosition, String> {
+            json5::from_str(json).map_err(|e| e.to_string())
+        };
+        let mut fen: Fenestration = json5::from_str(
+            "{
+            name: 'Window',
+            construction: 'glass',
+            vertices: [0, 0, 0, 1, 0, 0, 1, 0, 1, 0, 0, 1],
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert!(fen.initial_open_fraction()?.abs() < 1e-9);
+
+        // No positions
+        fen.set_operation(positions("{type: 'Discrete', positions: []}")?);
+        assert!(fen.initial_open_fraction().is_err());
+
+        // Repeated names
+        fen.set_operation(positions(
+            "{type: 'Discrete', positions: [{name: 'a', open_fraction: 0}, {name: 'a', open_fraction: 1}]}",
+        )?);
+        assert!(fen.initial_open_fraction().is_err());
+
+        // Both an open fraction and an open area
+        fen.set_operation(positions(
+            "{type: 'Discrete', positions: [{name: 'a', open_fraction: 0, open_area: 0.1}]}",
+        )?);
+        assert!(fen.initial_open_fraction().is_err());
+
+        // Larger than the window
+        fen.set_operation(positions(
+            "{type: 'Discrete', positions: [{name: 'a', open_area: 2}]}",
+        )?);
+        assert!(fen.initial_open_fraction().is_err());
+
+        // A single position cannot be operated
+        fen.set_operation(positions(
+            "{type: 'Discrete', positions: [{name: 'trickle', open_area: 0.01}]}",
+        )?);
+        assert!((fen.initial_open_fraction()? - 0.01).abs() < 1e-9);
+        assert!(!fen.is_operable());
+        Ok(())
+    }
 }
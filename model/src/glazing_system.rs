@@ -0,0 +1,503 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::error_msgs::print_warning_no_module;
+use crate::substance::gas::GasSpecification;
+use crate::substance::{Gas, Normal};
+use crate::{Construction, Float, Material, Model};
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use utils::SimpleError;
+
+/// The density given to the panes of a [`GlazingSystem`], as
+/// WINDOW does not report it
+const GLASS_DENSITY: Float = 2500.;
+
+/// The specific heat capacity given to the panes of a [`GlazingSystem`], as
+/// WINDOW does not report it
+const GLASS_SPECIFIC_HEAT: Float = 840.;
+
+/// The number of values after the ID and the name of a pane
+/// in a WINDOW report (i.e., thickness, 9 optical properties and
+/// conductivity)
+const N_PANE_VALUES: usize = 11;
+
+/// A single glazing layer, with the properties reported by LBNL's
+/// WINDOW (and listed in its glass library, which is built from the
+/// International Glazing Database)
+#[derive(Debug, Clone)]
+pub struct Pane {
+    /// The ID of the pane (e.g., its NFRC ID)
+    pub id: String,
+
+    /// The name of the pane (e.g., `CLEAR_6.DAT`)
+    pub name: String,
+
+    /// The thickness, in m
+    pub thickness: Float,
+
+    /// The solar transmittance at normal incidence
+    pub solar_transmittance: Float,
+
+    /// The solar reflectance of the front (i.e., outside) side
+    pub front_solar_reflectance: Float,
+
+    /// The solar reflectance of the back (i.e., inside) side
+    pub back_solar_reflectance: Float,
+
+    /// The visible transmittance at normal incidence
+    pub visible_transmittance: Float,
+
+    /// The visible reflectance of the front (i.e., outside) side
+    pub front_visible_reflectance: Float,
+
+    /// The visible reflectance of the back (i.e., inside) side
+    pub back_visible_reflectance: Float,
+
+    /// The infrared transmittance
+    pub infrared_transmittance: Float,
+
+    /// The emissivity of the front (i.e., outside) side
+    pub front_emissivity: Float,
+
+    /// The emissivity of the back (i.e., inside) side
+    pub back_emissivity: Float,
+
+    /// The thermal conductivity, in W/m.K
+    pub conductivity: Float,
+}
+
+impl Pane {
+    /// Parses a pane from a row of a WINDOW report or glass library; that is, its ID,
+    /// its name (which may contain spaces), and then its thickness (in mm), `Tsol`,
+    /// `Rsol1`, `Rsol2`, `Tvis`, `Rvis1`, `Rvis2`, `Tir`, `Emis1`, `Emis2` and
+    /// conductivity (in W/m.K). Side `1` is the front (i.e., outside) one.
+    ///
+    /// ```
+    /// use model::glazing_system::Pane;
+    ///
+    /// let pane = Pane::from_record("103  CLEAR_6.DAT  5.7  .771 .070 .070  .884 .080 .080  .000 .840 .840  1.00").unwrap();
+    /// assert_eq!(pane.name, "CLEAR_6.DAT");
+    /// assert!((pane.thickness - 0.0057).abs() < 1e-9);
+    /// ```
+    pub fn from_record(record: &str) -> Result<Self, SimpleError> {
+        let tokens: Vec<&str> = record.split_whitespace().collect();
+        if tokens.len() < N_PANE_VALUES + 2 {
+            return Err(SimpleError::Parse(format!(
+                "Expecting an ID, a name and {} values in glazing layer '{}'",
+                N_PANE_VALUES, record
+            )));
+        }
+        let first_value = tokens.len() - N_PANE_VALUES;
+        let mut values = [0.; N_PANE_VALUES];
+        for (v, token) in values.iter_mut().zip(tokens[first_value..].iter()) {
+            *v = token.parse().map_err(|_| {
+                SimpleError::Parse(format!(
+                    "Invalid value '{}' in glazing layer '{}'",
+                    token, record
+                ))
+            })?;
+        }
+        let [thickness, tsol, rsol1, rsol2, tvis, rvis1, rvis2, tir, emis1, emis2, conductivity] =
+            values;
+        Ok(Self {
+            id: tokens[0].to_string(),
+            name: tokens[1..first_value].join(" "),
+            thickness: thickness / 1000.,
+            solar_transmittance: tsol,
+            front_solar_reflectance: rsol1,
+            back_solar_reflectance: rsol2,
+            visible_transmittance: tvis,
+            front_visible_reflectance: rvis1,
+            back_visible_reflectance: rvis2,
+            infrared_transmittance: tir,
+            front_emissivity: emis1,
+            back_emissivity: emis2,
+            conductivity,
+        })
+    }
+
+    /// Creates the [`Normal`] substance with the properties of this pane
+    pub fn substance(&self) -> Normal {
+        let mut sub = Normal::new(&self.name);
+        sub.set_thermal_conductivity(self.conductivity)
+            .set_density(GLASS_DENSITY)
+            .set_specific_heat_capacity(GLASS_SPECIFIC_HEAT)
+            .set_solar_transmittance(self.solar_transmittance)
+            .set_front_solar_absorbtance(
+                (1. - self.solar_transmittance - self.front_solar_reflectance).max(0.),
+            )
+            .set_back_solar_absorbtance(
+                (1. - self.solar_transmittance - self.back_solar_reflectance).max(0.),
+            )
+            .set_visible_transmissivity(self.visible_transmittance)
+            .set_front_visible_reflectance(self.front_visible_reflectance)
+            .set_back_visible_reflectance(self.back_visible_reflectance)
+            .set_front_thermal_absorbtance(self.front_emissivity)
+            .set_back_thermal_absorbtance(self.back_emissivity);
+        sub
+    }
+}
+
+/// The gas-filled cavity between two [`Pane`]s
+#[derive(Debug, Clone)]
+pub struct Gap {
+    /// The gas filling the cavity
+    pub gas: GasSpecification,
+
+    /// The thickness, in m
+    pub thickness: Float,
+}
+
+impl Gap {
+    /// Parses a gap from a row of a WINDOW report: `Gap` (optionally followed by
+    /// its number), the name of the gas (e.g., `Air` or `Argon`), and then its
+    /// thickness (in mm). Mixtures are represented by the heaviest
+    /// gas in them (e.g., `Air 10%/Argon 90%` becomes Argon), with a warning.
+    pub fn from_record(record: &str) -> Result<Self, SimpleError> {
+        let mut tokens = record.split_whitespace().skip(1).peekable();
+        // Gap number, when separated from 'Gap'
+        if let Some(t) = tokens.peek() {
+            if t.parse::<usize>().is_ok() {
+                tokens.next();
+            }
+        }
+        let mut gas_name = Vec::new();
+        let mut thickness = None;
+        for t in tokens {
+            match t.parse::<Float>() {
+                Ok(v) => {
+                    thickness = Some(v);
+                    break;
+                }
+                Err(_) => gas_name.push(t),
+            }
+        }
+        let gas_name = gas_name.join(" ");
+        let thickness = thickness
+            .ok_or_else(|| SimpleError::Parse(format!("No thickness found in gap '{}'", record)))?;
+
+        let lower = gas_name.to_lowercase();
+        let gases = [
+            ("xenon", GasSpecification::Xenon),
+            ("krypton", GasSpecification::Krypton),
+            ("argon", GasSpecification::Argon),
+            ("air", GasSpecification::Air),
+        ];
+        let gas = gases
+            .iter()
+            .find(|(name, _)| lower.contains(name))
+            .map(|(_, gas)| *gas)
+            .ok_or_else(|| {
+                SimpleError::Parse(format!(
+                    "Unsupported gas '{}' in gap '{}'",
+                    gas_name, record
+                ))
+            })?;
+        if lower.contains('%') || lower.contains('/') {
+            print_warning_no_module(format!(
+                "Gas mixture '{}' is not supported... using {:?}",
+                gas_name, gas
+            ));
+        }
+
+        Ok(Self {
+            gas,
+            thickness: thickness / 1000.,
+        })
+    }
+
+    /// The name of the substance of the gap
+    fn substance_name(&self) -> &'static str {
+        match self.gas {
+            GasSpecification::Air => "Air",
+            GasSpecification::Argon => "Argon",
+            GasSpecification::Krypton => "Krypton",
+            GasSpecification::Xenon => "Xenon",
+        }
+    }
+}
+
+/// A glazing system (i.e., panes separated by gas-filled gaps) as
+/// designed in LBNL's WINDOW, which can be added to a [`Model`] as a
+/// [`Construction`] with its [`Substance`](crate::Substance)s and [`Material`]s
+///
+/// ```
+/// use model::glazing_system::GlazingSystem;
+/// use model::Model;
+///
+/// let report = "
+///     Name  : Double Clear Air
+///
+///     ID      Name          D(mm) Tsol Rsol1 Rsol2 Tvis Rvis1 Rvis2 Tir  Emis1 Emis2 Keff
+///     Outside
+///     103     CLEAR_6.DAT   5.7   .771  .070  .070 .884  .080  .080 .000  .840  .840 1.00
+///     Gap1    Air           12.7                                                        .024
+///     103     CLEAR_6.DAT   5.7   .771  .070  .070 .884  .080  .080 .000  .840  .840 1.00
+///     Inside
+/// ";
+/// let system = GlazingSystem::from_window_report(report).unwrap();
+///
+/// let mut model = Model::default();
+/// let construction = system.add_to_model(&mut model);
+/// assert_eq!(construction.name, "Double Clear Air");
+/// assert_eq!(construction.materials.len(), 3);
+/// ```
+#[derive(Debug, Clone)]
+pub struct GlazingSystem {
+    /// The name of the system, used for its [`Construction`]
+    pub name: String,
+
+    /// The panes, from the outside (front) to the inside (back)
+    pub panes: Vec<Pane>,
+
+    /// The gaps between the panes, from the outside (front) to the inside (back)
+    pub gaps: Vec<Gap>,
+}
+
+impl GlazingSystem {
+    /// Reads a glazing system from a report of LBNL's WINDOW. See [`Self::from_window_report`]
+    pub fn from_file<P: AsRef<Path> + Display>(filename: P) -> Result<Self, SimpleError> {
+        let src = fs::read_to_string(&filename)
+            .map_err(|_| SimpleError::Io(format!("Could not read WINDOW report '{}'", filename)))?;
+        Self::from_window_report(&src)
+    }
+
+    /// Parses the layers of a glazing system from a report of LBNL's WINDOW.
+    ///
+    /// The layers are listed between a line containing only `Outside` and one
+    /// containing only `Inside`, alternating panes (see [`Pane::from_record`]) and
+    /// gaps (see [`Gap::from_record`]). The rest of the report is ignored, except for a
+    /// `Name : ...` line, which gives the name of the system.
+    pub fn from_window_report(src: &str) -> Result<Self, SimpleError> {
+        let mut name = "WINDOW Glazing System".to_string();
+        let mut panes = Vec::new();
+        let mut gaps = Vec::new();
+        let mut in_layers = false;
+        let mut finished = false;
+        for (i, line) in src.lines().enumerate() {
+            let line = line.trim();
+            let lower = line.to_lowercase();
+            if lower == "outside" {
+                in_layers = true;
+                continue;
+            } else if lower == "inside" {
+                finished = in_layers;
+                break;
+            }
+
+            if !in_layers {
+                if let Some((key, value)) = line.split_once(':') {
+                    if key.trim().to_lowercase() == "name" && !value.trim().is_empty() {
+                        name = value.trim().to_string();
+                    }
+                }
+                continue;
+            }
+
+            if line.is_empty() || line.starts_with('-') {
+                continue;
+            }
+            let err = |e: SimpleError| SimpleError::Parse(format!("Line {}: {}", i + 1, e));
+            if lower.starts_with("gap") {
+                if gaps.len() + 1 != panes.len() {
+                    return Err(SimpleError::Parse(format!(
+                        "Line {}: gaps must be between two panes",
+                        i + 1
+                    )));
+                }
+                gaps.push(Gap::from_record(line).map_err(err)?);
+            } else {
+                if panes.len() != gaps.len() {
+                    return Err(SimpleError::Parse(format!(
+                        "Line {}: panes must be separated by gaps",
+                        i + 1
+                    )));
+                }
+                panes.push(Pane::from_record(line).map_err(err)?);
+            }
+        }
+
+        if !finished {
+            return Err(SimpleError::Parse(
+                "The WINDOW report has no layers between 'Outside' and 'Inside'".to_string(),
+            ));
+        }
+        if panes.is_empty() || gaps.len() + 1 != panes.len() {
+            return Err(SimpleError::Parse(
+                "A glazing system must start and end with a pane".to_string(),
+            ));
+        }
+        Ok(Self { name, panes, gaps })
+    }
+
+    /// Adds the [`Construction`] of this system to a [`Model`], together with the
+    /// substances and materials of its layers (unless the model already has
+    /// substances or materials with the same names, which are then reused).
+    pub fn add_to_model(&self, model: &mut Model) -> Arc<Construction> {
+        let material = |model: &mut Model, substance: &str, thickness: Float| -> String {
+            let name = format!("{} {:.1}mm", substance, thickness * 1000.);
+            if model.get_material(&name).is_err() {
+                model.add_material(Material::new(name.clone(), substance.to_string(), thickness));
+            }
+            name
+        };
+
+        let mut construction = Construction::new(&self.name);
+        for (i, pane) in self.panes.iter().enumerate() {
+            if i > 0 {
+                let gap = &self.gaps[i - 1];
+                let substance = gap.substance_name();
+                if model.get_substance(substance).is_err() {
+                    let mut gas = Gas::new(substance);
+                    gas.set_gas(gap.gas);
+                    model.add_substance(gas.wrap());
+                }
+                let name = material(model, substance, gap.thickness);
+                construction.materials.push(name);
+            }
+            if model.get_substance(&pane.name).is_err() {
+                model.add_substance(pane.substance().wrap());
+            }
+            let name = material(model, &pane.name, pane.thickness);
+            construction.materials.push(name);
+        }
+        model.add_construction(construction)
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use crate::Substance;
+
+    const REPORT: &str = "WINDOW 7.8 Glazing System Report
+
+ID    : 12
+Name  : Triple Low-e Argon
+Tilt  : 90
+
+Layer Data for Glazing System 'Triple Low-e Argon'
+ID      Name                D(mm) Tsol Rsol1 Rsol2 Tvis Rvis1 Rvis2 Tir  Emis1 Emis2 Keff
+------  ------------------  ----- ---- ----- ----- ---- ----- ----- ---- ----- ----- ----
+Outside
+103     CLEAR_6.DAT           5.7 .771  .070  .070 .884  .080  .080 .000  .840  .840 1.00
+Gap1    Argon                12.0                                                    .016
+2011    LowE 4mm (coated)     3.9 .472  .285  .241 .809  .044  .051 .000  .037  .840 1.00
+Gap 2   Air 10%/Argon 90%    12.0                                                    .017
+103     CLEAR_6.DAT           5.7 .771  .070  .070 .884  .080  .080 .000  .840  .840 1.00
+Inside
+
+Environmental Conditions: NFRC 100-2010
+";
+
+    #[test]
+    fn test_pane() -> Result<(), String> {
+        let pane = Pane::from_record(
+            "2011  LowE 4mm (coated)  3.9 .472  .285  .241 .809  .044  .051 .000  .037  .840 1.00",
+        )?;
+        assert_eq!(pane.id, "2011");
+        assert_eq!(pane.name, "LowE 4mm (coated)");
+        assert!((pane.thickness - 0.0039).abs() < 1e-9);
+        assert!((pane.front_emissivity - 0.037).abs() < 1e-9);
+
+        let sub = pane.substance();
+        assert!((sub.front_solar_absorbtance()? - 0.243).abs() < 1e-9);
+        assert!((sub.back_solar_absorbtance()? - 0.287).abs() < 1e-9);
+        assert!((sub.visible_transmissivity()? - 0.809).abs() < 1e-9);
+
+        // Missing values
+        assert!(Pane::from_record("103 CLEAR_6.DAT 5.7 .771").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_gap() -> Result<(), String> {
+        let gap = Gap::from_record("Gap1    Air   12.7   .024")?;
+        assert!(matches!(gap.gas, GasSpecification::Air));
+        assert!((gap.thickness - 0.0127).abs() < 1e-9);
+
+        let gap = Gap::from_record("Gap 2   Air 10%/Argon 90%    12.0")?;
+        assert!(matches!(gap.gas, GasSpecification::Argon));
+
+        assert!(Gap::from_record("Gap1 SF6 12.0").is_err());
+        assert!(Gap::from_record("Gap1 Air").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_report() -> Result<(), String> {
+        let system = GlazingSystem::from_window_report(REPORT)?;
+        assert_eq!(system.name, "Triple Low-e Argon");
+        assert_eq!(system.panes.len(), 3);
+        assert_eq!(system.gaps.len(), 2);
+
+        let mut model = Model::default();
+        let construction = system.add_to_model(&mut model);
+        assert_eq!(
+            construction.materials,
+            vec![
+                "CLEAR_6.DAT 5.7mm",
+                "Argon 12.0mm",
+                "LowE 4mm (coated) 3.9mm",
+                "Argon 12.0mm",
+                "CLEAR_6.DAT 5.7mm"
+            ]
+        );
+        // Repeated layers are added once
+        assert_eq!(model.substances.len(), 3);
+        assert_eq!(model.materials.len(), 3);
+        if let Substance::Normal(s) = model.get_substance("CLEAR_6.DAT")? {
+            assert!((s.solar_transmittance()? - 0.771).abs() < 1e-9);
+        } else {
+            panic!("Expecting a Normal substance")
+        }
+
+        // Adding again reuses everything but the construction
+        system.add_to_model(&mut model);
+        assert_eq!(model.substances.len(), 3);
+        assert_eq!(model.constructions.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_report_errors() {
+        // No layers
+        assert!(GlazingSystem::from_window_report("Name: Nothing").is_err());
+        // Two panes without a gap
+        let src = "Outside
+103 CLEAR_6.DAT 5.7 .771 .070 .070 .884 .080 .080 .000 .840 .840 1.00
+103 CLEAR_6.DAT 5.7 .771 .070 .070 .884 .080 .080 .000 .840 .840 1.00
+Inside";
+        assert!(GlazingSystem::from_window_report(src).is_err());
+        // Ends with a gap
+        let src = "Outside
+103 CLEAR_6.DAT 5.7 .771 .070 .070 .884 .080 .080 .000 .840 .840 1.00
+Gap1 Air 12.7
+Inside";
+        assert!(GlazingSystem::from_window_report(src).is_err());
+    }
+}
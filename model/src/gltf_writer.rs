@@ -0,0 +1,351 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::{Float, Model, Object};
+use geometry::{Point3D, Polygon3D, Triangulation3D};
+use serde_json::{json, Value};
+
+/// The `target` of a `bufferView` containing vertex attributes
+const ARRAY_BUFFER: usize = 34962;
+
+/// The `target` of a `bufferView` containing indices
+const ELEMENT_ARRAY_BUFFER: usize = 34963;
+
+/// The `componentType` of a `f32` accessor
+const FLOAT: usize = 5126;
+
+/// The `componentType` of a `u32` accessor
+const UNSIGNED_INT: usize = 5125;
+
+/// The materials used for each kind of element, as
+/// (name, RGBA colour)
+const MATERIALS: [(&str, [Float; 4]); 4] = [
+    ("Surface", [0.8, 0.8, 0.8, 1.0]),
+    ("Fenestration", [0.4, 0.6, 0.9, 0.4]),
+    ("ShadingSurface", [0.5, 0.5, 0.5, 1.0]),
+    ("Object", [0.7, 0.5, 0.3, 1.0]),
+];
+
+/// Encodes some bytes in base64, so they can be embedded in the file
+fn base64(data: &[u8]) -> String {
+    const CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(4 * data.len() / 3 + 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (b[0] as usize) << 16 | (b[1] as usize) << 8 | b[2] as usize;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(CHARS[(n >> (18 - 6 * i)) & 63] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Transforms a point from SIMPLE's coordinates (i.e., Z is up) into
+/// glTF's (i.e., Y is up)
+fn to_gltf_coordinates(p: Point3D) -> [f32; 3] {
+    [p.x as f32, p.z as f32, -p.y as f32]
+}
+
+/// Collects the meshes of the scene and the binary buffer containing
+/// their vertices and indices
+#[derive(Default)]
+struct GltfBuilder {
+    buffer: Vec<u8>,
+    buffer_views: Vec<Value>,
+    accessors: Vec<Value>,
+    meshes: Vec<Value>,
+    nodes: Vec<Value>,
+}
+
+impl GltfBuilder {
+    /// Adds a `bufferView` with some bytes to the buffer, returning its index
+    fn push_buffer_view(&mut self, data: &[u8], target: usize) -> usize {
+        self.buffer_views.push(json!({
+            "buffer": 0,
+            "byteOffset": self.buffer.len(),
+            "byteLength": data.len(),
+            "target": target,
+        }));
+        self.buffer.extend_from_slice(data);
+        self.buffer_views.len() - 1
+    }
+
+    /// Adds a mesh—and the node that places it in the scene—made of
+    /// some `positions` and the `indices` of its triangles
+    fn push_mesh(
+        &mut self,
+        name: &str,
+        material: usize,
+        positions: &[[f32; 3]],
+        indices: &[u32],
+    ) -> Result<(), String> {
+        if positions.is_empty() || indices.is_empty() {
+            return Err(format!("Element '{}' has no triangles to export", name));
+        }
+        let mut min = [f32::MAX; 3];
+        let mut max = [f32::MIN; 3];
+        let mut data = Vec::with_capacity(12 * positions.len());
+        for p in positions {
+            for i in 0..3 {
+                min[i] = min[i].min(p[i]);
+                max[i] = max[i].max(p[i]);
+                data.extend_from_slice(&p[i].to_le_bytes());
+            }
+        }
+        let view = self.push_buffer_view(&data, ARRAY_BUFFER);
+        self.accessors.push(json!({
+            "bufferView": view,
+            "componentType": FLOAT,
+            "count": positions.len(),
+            "type": "VEC3",
+            "min": min,
+            "max": max,
+        }));
+        let position_accessor = self.accessors.len() - 1;
+
+        let data: Vec<u8> = indices.iter().flat_map(|i| i.to_le_bytes()).collect();
+        let view = self.push_buffer_view(&data, ELEMENT_ARRAY_BUFFER);
+        self.accessors.push(json!({
+            "bufferView": view,
+            "componentType": UNSIGNED_INT,
+            "count": indices.len(),
+            "type": "SCALAR",
+        }));
+        let index_accessor = self.accessors.len() - 1;
+
+        self.meshes.push(json!({
+            "name": name,
+            "primitives": [{
+                "attributes": { "POSITION": position_accessor },
+                "indices": index_accessor,
+                "material": material,
+            }],
+        }));
+        self.nodes.push(json!({
+            "name": name,
+            "mesh": self.meshes.len() - 1,
+            "extras": { "category": MATERIALS[material].0 },
+        }));
+        Ok(())
+    }
+
+    /// Triangulates a [`Polygon3D`] and adds it as a mesh
+    fn push_polygon(
+        &mut self,
+        name: &str,
+        material: usize,
+        polygon: &Polygon3D,
+    ) -> Result<(), String> {
+        let t: Triangulation3D = polygon
+            .try_into()
+            .map_err(|e| format!("Could not triangulate '{}': {}", name, e))?;
+        let mut positions = Vec::new();
+        for tri in t.get_trilist() {
+            positions.push(to_gltf_coordinates(tri.a()));
+            positions.push(to_gltf_coordinates(tri.b()));
+            positions.push(to_gltf_coordinates(tri.c()));
+        }
+        let indices: Vec<u32> = (0..positions.len() as u32).collect();
+        self.push_mesh(name, material, &positions, &indices)
+    }
+
+    /// Adds an [`Object`] as a box of its `dimensions`, centered at its `location`.
+    ///
+    /// The `dimensions` are interpreted as width (i.e., along the right of the object),
+    /// depth (i.e., along its `front`) and height (i.e., along its `up`)
+    fn push_object(&mut self, object: &Object, material: usize) -> Result<(), String> {
+        let up = object.up.get_normalized();
+        let right = object.front.cross(up).get_normalized();
+        let front = up.cross(right);
+        let half = object.dimensions * 0.5;
+
+        let mut positions = Vec::with_capacity(8);
+        for i in 0..8 {
+            let sx = if i & 1 == 0 { -half.x } else { half.x };
+            let sy = if i & 2 == 0 { -half.y } else { half.y };
+            let sz = if i & 4 == 0 { -half.z } else { half.z };
+            let p = object.location + right * sx + front * sy + up * sz;
+            positions.push(to_gltf_coordinates(p));
+        }
+        // Two triangles per face of the box
+        let indices: [u32; 36] = [
+            0, 2, 1, 1, 2, 3, // bottom
+            4, 5, 6, 5, 7, 6, // top
+            0, 1, 4, 1, 5, 4, // back
+            2, 6, 3, 3, 6, 7, // front
+            0, 4, 2, 2, 4, 6, // left
+            1, 3, 5, 3, 7, 5, // right
+        ];
+        self.push_mesh(&object.name, material, &positions, &indices)
+    }
+}
+
+/// Writes the geometry of a [`Model`] (i.e., its surfaces, fenestrations,
+/// shading surfaces and objects) as a glTF 2.0 scene, with the binary
+/// data embedded in the file.
+///
+/// Each element is exported as a node—named after the element—whose
+/// `extras` contain its `category` (e.g., `"Fenestration"`). Coordinates are
+/// transformed so that the Z axis of the model points up in glTF (i.e., Y).
+pub(crate) fn write_gltf(model: &Model) -> Result<String, String> {
+    let mut builder = GltfBuilder::default();
+
+    for s in model.surfaces.iter() {
+        builder.push_polygon(&s.name, 0, &s.vertices)?;
+    }
+    for fen in model.fenestrations.iter() {
+        builder.push_polygon(&fen.name, 1, &fen.vertices)?;
+    }
+    for s in model.shading_surfaces.iter() {
+        if s.vertices.area() < 1e-5 {
+            continue;
+        }
+        builder.push_polygon(&s.name, 2, &s.vertices)?;
+    }
+    for object in model.objects.iter() {
+        builder.push_object(object, 3)?;
+    }
+
+    let materials: Vec<Value> = MATERIALS
+        .iter()
+        .map(|(name, colour)| {
+            let mut material = json!({
+                "name": name,
+                "pbrMetallicRoughness": {
+                    "baseColorFactor": colour,
+                    "metallicFactor": 0.0,
+                    "roughnessFactor": 1.0,
+                },
+                "doubleSided": true,
+            });
+            if colour[3] < 1.0 {
+                material["alphaMode"] = json!("BLEND");
+            }
+            material
+        })
+        .collect();
+
+    let name = match &model.name {
+        Some(n) => n.as_str(),
+        None => "unnamed",
+    };
+    let mut gltf = json!({
+        "asset": { "version": "2.0", "generator": "SIMPLE" },
+        "scene": 0,
+        "scenes": [{
+            "name": name,
+            "nodes": (0..builder.nodes.len()).collect::<Vec<usize>>(),
+        }],
+        "nodes": builder.nodes,
+        "meshes": builder.meshes,
+        "materials": materials,
+    });
+    // Empty buffers are not allowed
+    if !builder.buffer.is_empty() {
+        gltf["buffers"] = json!([{
+            "byteLength": builder.buffer.len(),
+            "uri": format!("data:application/octet-stream;base64,{}", base64(&builder.buffer)),
+        }]);
+        gltf["bufferViews"] = json!(builder.buffer_views);
+        gltf["accessors"] = json!(builder.accessors);
+    }
+
+    serde_json::to_string_pretty(&gltf).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use crate::ObjectSpecs;
+    use geometry::Vector3D;
+
+    #[test]
+    fn test_base64() {
+        assert_eq!(base64(b""), "");
+        assert_eq!(base64(b"f"), "Zg==");
+        assert_eq!(base64(b"fo"), "Zm8=");
+        assert_eq!(base64(b"foo"), "Zm9v");
+        assert_eq!(base64(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn test_box_with_window() -> Result<(), String> {
+        let (model, ..) = Model::from_file("./tests/box_with_window.spl")?;
+        let gltf: Value = serde_json::from_str(&write_gltf(&model)?).map_err(|e| e.to_string())?;
+
+        let n = model.surfaces.len() + model.fenestrations.len();
+        assert_eq!(gltf["nodes"].as_array().unwrap().len(), n);
+        assert_eq!(gltf["meshes"].as_array().unwrap().len(), n);
+        assert_eq!(gltf["accessors"].as_array().unwrap().len(), 2 * n);
+        assert_eq!(gltf["nodes"][0]["name"], json!(model.surfaces[0].name));
+        assert_eq!(
+            gltf["nodes"][model.surfaces.len()]["extras"]["category"],
+            json!("Fenestration")
+        );
+
+        // The buffer views are contiguous and fill the buffer
+        let mut length = 0;
+        for view in gltf["bufferViews"].as_array().unwrap() {
+            assert_eq!(view["byteOffset"], json!(length));
+            length += view["byteLength"].as_u64().unwrap();
+        }
+        assert_eq!(gltf["buffers"][0]["byteLength"], json!(length));
+        Ok(())
+    }
+
+    #[test]
+    fn test_object() -> Result<(), String> {
+        let mut model = Model::default();
+        model.add_object(Object {
+            name: "the table".into(),
+            dimensions: Point3D::new(2., 1., 0.8),
+            location: Point3D::new(1., 2., 0.4),
+            up: Vector3D::new(0., 0., 1.),
+            // Rotated, so the table is 2m long in Y
+            front: Vector3D::new(1., 0., 0.),
+            specifications: ObjectSpecs::default(),
+            space: None,
+        })?;
+        let gltf: Value = serde_json::from_str(&write_gltf(&model)?).map_err(|e| e.to_string())?;
+
+        let positions = &gltf["accessors"][0];
+        assert_eq!(positions["count"], json!(8));
+        assert_eq!(gltf["accessors"][1]["count"], json!(36));
+
+        // glTF is Y-up, with Z pointing towards -Y of the model
+        let check = |v: &Value, exp: [Float; 3]| {
+            for (i, e) in exp.iter().enumerate() {
+                let found = v[i].as_f64().unwrap() as Float;
+                assert!((found - e).abs() < 1e-5, "{} vs {}", found, e);
+            }
+        };
+        check(&positions["min"], [0.5, 0., -3.]);
+        check(&positions["max"], [1.5, 0.8, -1.]);
+        Ok(())
+    }
+}
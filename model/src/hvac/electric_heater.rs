@@ -69,10 +69,23 @@ pub struct ElectricHeater {
     /// which is the one set by the user witht the simulation options)
     heating_setpoint: Option<Float>,
 
+    /// The name of the [`Schedule`](crate::Schedule) that defines when this
+    /// system is available. The system is turned off whenever the value
+    /// of this schedule is zero or less. If none is given, the system is
+    /// always available.
+    availability_schedule: Option<String>,
+
     /// The heating or cooling power consumption (not delivered to the `Space`)    
     #[operational("power_consumption")]
     #[serde(skip)]
     heating_cooling_consumption: StateElementField,
+
+    /// The heating setpoint currently in use, in C, which control
+    /// algorithms can change. This state only exists if the system
+    /// has a `heating_setpoint`, which is its initial value.
+    #[operational("heating_setpoint")]
+    #[serde(skip)]
+    current_heating_setpoint: StateElementField,
 }
 
 impl ElectricHeater {
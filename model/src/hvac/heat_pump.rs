@@ -0,0 +1,243 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::model::Model;
+use crate::simulation_state_element::StateElementField;
+use crate::Float;
+use serde::{Deserialize, Serialize};
+
+use derive::{GroupMemberAPI, ObjectIO};
+
+/// An air-to-air Heat Pump that can heat and cool a `target_space`.
+/// Like the other HVACs, it only has two states: On/Off.
+///
+/// Its electricity consumption is calculated from the heating or cooling
+/// power delivered to the `target_space` and a Coefficient of Performance
+/// (COP), which is the rated COP modified by two polynomial curves:
+///
+/// ```math
+/// COP = COP_{rated} \times f(T_{out}) \times g(PLR)
+/// ```
+///
+/// where $`T_{out}`$ is the outdoor dry bulb temperature (in C) and $`PLR`$
+/// is the Part Load Ratio (i.e., the delivered power divided by the maximum
+/// heating or cooling power). The curves are given as the coefficients of a
+/// polynomial, starting from the constant term. When no curve is given, the
+/// corresponding factor is 1.
+///
+/// ## Examples
+///
+/// #### `.spl`
+///
+/// ```json
+/// {{#include ../../../model/tests/scanner/hvac_heat_pump.spl}}
+/// ```
+///
+/// #### `.json`
+///
+/// ```json
+/// {{#include ../../../model/tests/scanner/hvac_heat_pump.json}}
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, ObjectIO, GroupMemberAPI)]
+#[serde(deny_unknown_fields)]
+pub struct HeatPump {
+    /// The name of the system
+    pub name: String,
+
+    /// The `Space` that this [`HeatPump`] heats and/or
+    /// cools
+    target_space: Option<String>,
+
+    /// Max heating power delivered to the `target_space`, in W
+    max_heating_power: Option<Float>,
+
+    /// Max cooling power removed from the `target_space`, in W
+    max_cooling_power: Option<Float>,
+
+    /// The temperature below which the heat pump starts heating.
+    ///
+    /// This tempareture is 'measured' in the `target_space`.
+    heating_setpoint: Option<Float>,
+
+    /// The temperature over which the heat pump starts cooling.
+    ///
+    /// This tempareture is 'measured' in the `target_space`.
+    cooling_setpoint: Option<Float>,
+
+    /// The COP when heating at rated conditions. Defaults to 3.
+    rated_heating_cop: Option<Float>,
+
+    /// The COP when cooling at rated conditions. Defaults to 3.
+    rated_cooling_cop: Option<Float>,
+
+    /// The coefficients of the polynomial of the outdoor dry bulb
+    /// temperature (in C) that multiplies the `rated_heating_cop`
+    heating_cop_curve: Option<Vec<Float>>,
+
+    /// The coefficients of the polynomial of the outdoor dry bulb
+    /// temperature (in C) that multiplies the `rated_cooling_cop`
+    cooling_cop_curve: Option<Vec<Float>>,
+
+    /// The coefficients of the polynomial of the Part Load Ratio
+    /// that multiplies the COP, both when heating and when cooling
+    part_load_curve: Option<Vec<Float>>,
+
+    /// The name of the [`Schedule`](crate::Schedule) that defines when this
+    /// system is available. The system is turned off whenever the value
+    /// of this schedule is zero or less. If none is given, the system is
+    /// always available.
+    availability_schedule: Option<String>,
+
+    /// The heating (positive) or cooling (negative) power delivered
+    /// to the `Space`
+    #[operational("power_consumption")]
+    #[serde(skip)]
+    heating_cooling_consumption: StateElementField,
+
+    /// The heating setpoint currently in use, in C, which control
+    /// algorithms can change. This state only exists if the system
+    /// has a `heating_setpoint`, which is its initial value.
+    #[operational("heating_setpoint")]
+    #[serde(skip)]
+    current_heating_setpoint: StateElementField,
+
+    /// The cooling setpoint currently in use, in C, which control
+    /// algorithms can change. This state only exists if the system
+    /// has a `cooling_setpoint`, which is its initial value.
+    #[operational("cooling_setpoint")]
+    #[serde(skip)]
+    current_cooling_setpoint: StateElementField,
+
+    /// The electricity consumed by the heat pump
+    #[physical]
+    #[serde(skip)]
+    electricity_consumption: StateElementField,
+}
+
+impl HeatPump {
+    /// Wraps the `HeatPump` in an `HVAC` enum
+    pub fn wrap(self) -> crate::hvac::HVAC {
+        crate::hvac::HVAC::HeatPump(std::sync::Arc::new(self))
+    }
+}
+
+impl super::SmallHVAC for HeatPump {
+    fn heating_setpoint(&self) -> Result<Float, String> {
+        match self.heating_setpoint() {
+            Ok(v) => Ok(*v),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn cooling_setpoint(&self) -> Result<Float, String> {
+        match self.cooling_setpoint() {
+            Ok(v) => Ok(*v),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn max_heating_power(&self) -> Result<Float, String> {
+        match self.max_heating_power() {
+            Ok(v) => Ok(*v),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn max_cooling_power(&self) -> Result<Float, String> {
+        match self.max_cooling_power() {
+            Ok(v) => Ok(*v),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn target_space(&self) -> Result<&String, String> {
+        self.target_space()
+    }
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+    use crate::HVAC;
+
+    #[test]
+    fn serde() -> Result<(), String> {
+        use json5;
+        use std::fs;
+
+        // Hardcode a reference
+        let mut hardcoded_ref = HeatPump::new("Living room heat pump");
+        hardcoded_ref
+            .set_target_space("Living room")
+            .set_max_heating_power(3500.)
+            .set_max_cooling_power(3000.)
+            .set_rated_heating_cop(3.6)
+            .set_heating_cop_curve(vec![0.8, 0.02])
+            .set_part_load_curve(vec![0.85, 0.15]);
+        let hardcoded_ref = hardcoded_ref.wrap();
+
+        // Deserialize from hardcoded string and check they are the same
+        let from_json5: HVAC = json5::from_str(
+            "{
+            type: 'HeatPump',
+            name: 'Living room heat pump',
+            target_space: 'Living room',
+            max_heating_power: 3500,
+            max_cooling_power: 3000,
+            rated_heating_cop: 3.6,
+            heating_cop_curve: [0.8, 0.02],
+            part_load_curve: [0.85, 0.15],
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert_eq!(format!("{:?}", hardcoded_ref), format!("{:?}", from_json5));
+
+        // Read json file (used in DOC), Deserialize, and compare
+        let filename = "./tests/scanner/hvac_heat_pump";
+        let json_file = format!("{}.json", filename);
+        let json_data = fs::read_to_string(json_file).map_err(|e| e.to_string())?;
+        let from_json_file: HVAC = serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_json_file)
+        );
+
+        // Serialize and deserialize again... check that everythin matches the pattern
+        let rust_json = serde_json::to_string(&hardcoded_ref).map_err(|e| e.to_string())?;
+        let from_serialized: HVAC = serde_json::from_str(&rust_json).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_serialized)
+        );
+
+        // check simple
+        let (model, ..) = Model::from_file("./tests/scanner/hvac_heat_pump.spl")?;
+        assert_eq!(model.hvacs.len(), 1);
+        if let HVAC::HeatPump(hvac) = &model.hvacs[0] {
+            assert_eq!("Living room heat pump", hvac.name());
+            assert_eq!("Living room", hvac.target_space()?);
+        } else {
+            panic!("Incorrect HVAC!")
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,166 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::model::Model;
+use crate::simulation_state_element::StateElementField;
+use crate::Float;
+use serde::{Deserialize, Serialize};
+
+use derive::{GroupMemberAPI, ObjectIO};
+
+/// A balanced mechanical ventilation system with heat recovery (HRV) or
+/// energy recovery (ERV). It extracts air from the `target_space` and
+/// supplies the same amount of outdoor air, which is preheated (or precooled)
+/// by the extracted air:
+///
+/// ```math
+/// T_{supply} = T_{out} + \varepsilon_{s} (T_{space} - T_{out})
+/// ```
+///
+/// If a `latent_effectiveness` is given, the humidity ratio of the supply air
+/// is calculated in the same way.
+///
+/// The air flow supplied is `air_flow` multiplied by the `flow_fraction`, which
+/// can be modified through the API (e.g., `hvac("HRV").flow_fraction = 0.5`) or
+/// by a `schedule`.
+///
+/// ## Examples
+///
+/// #### `.spl`
+///
+/// ```json
+/// {{#include ../../../model/tests/scanner/hvac_heat_recovery_ventilator.spl}}
+/// ```
+///
+/// #### `.json`
+///
+/// ```json
+/// {{#include ../../../model/tests/scanner/hvac_heat_recovery_ventilator.json}}
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, ObjectIO, GroupMemberAPI)]
+#[serde(deny_unknown_fields)]
+pub struct HeatRecoveryVentilator {
+    /// The name of the system
+    pub name: String,
+
+    /// The `Space` that this [`HeatRecoveryVentilator`] ventilates
+    target_space: Option<String>,
+
+    /// The maximum air flow supplied to the `target_space`, in m3/s
+    air_flow: Option<Float>,
+
+    /// The fraction of the temperature difference between the extracted
+    /// and outdoor air that is recovered (from 0 to 1). Defaults to 0.7.
+    sensible_effectiveness: Option<Float>,
+
+    /// The fraction of the humidity ratio difference between the extracted
+    /// and outdoor air that is recovered (from 0 to 1). Defaults to 0 (i.e.,
+    /// an HRV, not an ERV).
+    latent_effectiveness: Option<Float>,
+
+    /// The name of the [`Schedule`](crate::Schedule) that sets the
+    /// `flow_fraction` of this system. If none is given, the
+    /// `flow_fraction` is only modified by the control algorithms.
+    schedule: Option<String>,
+
+    /// The fraction of the `air_flow` being supplied (from 0 to 1)
+    #[operational("flow_fraction")]
+    #[serde(skip)]
+    air_flow_fraction: StateElementField,
+}
+
+impl HeatRecoveryVentilator {
+    /// Wraps the `HeatRecoveryVentilator` in an `HVAC` enum
+    pub fn wrap(self) -> crate::hvac::HVAC {
+        crate::hvac::HVAC::HeatRecoveryVentilator(std::sync::Arc::new(self))
+    }
+}
+
+impl super::SmallHVAC for HeatRecoveryVentilator {
+    fn target_space(&self) -> Result<&String, String> {
+        self.target_space()
+    }
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+    use crate::HVAC;
+
+    #[test]
+    fn serde() -> Result<(), String> {
+        use json5;
+        use std::fs;
+
+        // Hardcode a reference
+        let mut hardcoded_ref = HeatRecoveryVentilator::new("Bedroom ERV");
+        hardcoded_ref
+            .set_target_space("Bedroom")
+            .set_air_flow(0.02)
+            .set_sensible_effectiveness(0.8)
+            .set_latent_effectiveness(0.6);
+        let hardcoded_ref = hardcoded_ref.wrap();
+
+        // Deserialize from hardcoded string and check they are the same
+        let from_json5: HVAC = json5::from_str(
+            "{
+            type: 'HeatRecoveryVentilator',
+            name: 'Bedroom ERV',
+            target_space: 'Bedroom',
+            air_flow: 0.02,
+            sensible_effectiveness: 0.8,
+            latent_effectiveness: 0.6,
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert_eq!(format!("{:?}", hardcoded_ref), format!("{:?}", from_json5));
+
+        // Read json file (used in DOC), Deserialize, and compare
+        let filename = "./tests/scanner/hvac_heat_recovery_ventilator";
+        let json_file = format!("{}.json", filename);
+        let json_data = fs::read_to_string(json_file).map_err(|e| e.to_string())?;
+        let from_json_file: HVAC = serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_json_file)
+        );
+
+        // Serialize and deserialize again... check that everythin matches the pattern
+        let rust_json = serde_json::to_string(&hardcoded_ref).map_err(|e| e.to_string())?;
+        let from_serialized: HVAC = serde_json::from_str(&rust_json).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_serialized)
+        );
+
+        // check simple
+        let (model, ..) = Model::from_file("./tests/scanner/hvac_heat_recovery_ventilator.spl")?;
+        assert_eq!(model.hvacs.len(), 1);
+        if let HVAC::HeatRecoveryVentilator(hvac) = &model.hvacs[0] {
+            assert_eq!("Bedroom ERV", hvac.name());
+            assert_eq!("Bedroom", hvac.target_space()?);
+        } else {
+            panic!("Incorrect HVAC!")
+        }
+
+        Ok(())
+    }
+}
@@ -27,7 +27,10 @@ use serde::{Deserialize, Serialize};
 
 /// An ideal Heating and Cooling device, with a COP of 1.
 ///
-/// It only has two states: On/Off.
+/// It only has two states: On/Off. If humidifying or dehumidifying
+/// setpoints are given, it also controls the humidity of the `target_space`
+/// (similar to EnergyPlus' `ZoneHVAC:IdealLoadsAirSystem`), adding or
+/// removing moisture without affecting the temperature of the air.
 ///
 /// ## Example
 ///
@@ -82,10 +85,53 @@ pub struct IdealHeaterCooler {
     /// which is the one set by the user witht the simulation options)
     cooling_setpoint: Option<Float>,
 
+    /// The relative humidity (from 0 to 1) below which the system
+    /// starts humidifying the `target_space`.
+    humidifying_setpoint: Option<Float>,
+
+    /// The relative humidity (from 0 to 1) over which the system
+    /// starts dehumidifying the `target_space`.
+    dehumidifying_setpoint: Option<Float>,
+
+    /// Max latent power used for humidifying, in W
+    max_humidifying_power: Option<Float>,
+
+    /// Max latent power removed when dehumidifying, in W
+    max_dehumidifying_power: Option<Float>,
+
+    /// The name of the [`Schedule`](crate::Schedule) that defines when this
+    /// system is available. The system is turned off whenever the value
+    /// of this schedule is zero or less. If none is given, the system is
+    /// always available.
+    availability_schedule: Option<String>,
+
     /// The heating or cooling power consumption (not delivered to the `Space`)    
     #[operational("power_consumption")]
     #[serde(skip)]
     heating_cooling_consumption: StateElementField,
+
+    /// The heating setpoint currently in use, in C, which control
+    /// algorithms can change. This state only exists if the system
+    /// has a `heating_setpoint`, which is its initial value.
+    #[operational("heating_setpoint")]
+    #[serde(skip)]
+    current_heating_setpoint: StateElementField,
+
+    /// The cooling setpoint currently in use, in C, which control
+    /// algorithms can change. This state only exists if the system
+    /// has a `cooling_setpoint`, which is its initial value.
+    #[operational("cooling_setpoint")]
+    #[serde(skip)]
+    current_cooling_setpoint: StateElementField,
+
+    /// The latent heat added (positive) or removed (negative) from
+    /// the `Space` when humidifying or dehumidifying.
+    ///
+    /// This state only exists if the system has a `humidifying_setpoint`
+    /// or a `dehumidifying_setpoint`.
+    #[operational("latent_power_consumption")]
+    #[serde(skip)]
+    latent_heating_cooling_consumption: StateElementField,
 }
 
 impl IdealHeaterCooler {
@@ -93,6 +139,12 @@ impl IdealHeaterCooler {
     pub fn wrap(self) -> HVAC {
         crate::hvac::HVAC::IdealHeaterCooler(std::sync::Arc::new(self))
     }
+
+    /// Checks whether this system controls the humidity of its
+    /// `target_space`
+    pub fn controls_humidity(&self) -> bool {
+        self.humidifying_setpoint.is_some() || self.dehumidifying_setpoint.is_some()
+    }
 }
 
 impl super::SmallHVAC for IdealHeaterCooler {
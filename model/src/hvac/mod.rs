@@ -18,12 +18,19 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 
-use crate::Float;
+use crate::thermostat::{hvac_capacity, hvac_power, set_hvac_power};
+use crate::{Float, SimulationState};
 use serde::{Deserialize, Serialize};
 mod electric_heater;
+mod heat_pump;
+mod heat_recovery_ventilator;
 mod ideal_heater_cooler;
+mod radiant_surface_system;
 pub use crate::hvac::electric_heater::ElectricHeater;
+pub use crate::hvac::heat_pump::HeatPump;
+pub use crate::hvac::heat_recovery_ventilator::HeatRecoveryVentilator;
 pub use crate::hvac::ideal_heater_cooler::IdealHeaterCooler;
+pub use crate::hvac::radiant_surface_system::RadiantSurfaceSystem;
 use crate::model::Model;
 use derive::{GroupAPI, GroupIO};
 use std::sync::Arc;
@@ -55,6 +62,18 @@ pub enum HVAC {
     /// An electric heater, it can only
     /// heat.
     ElectricHeater(Arc<ElectricHeater>),
+
+    /// An air-to-air heat pump, whose COP depends
+    /// on the outdoor temperature and part load ratio
+    HeatPump(Arc<HeatPump>),
+
+    /// A radiant floor, wall or ceiling, which heats
+    /// or cools the construction of a surface
+    RadiantSurfaceSystem(Arc<RadiantSurfaceSystem>),
+
+    /// A balanced mechanical ventilation system with
+    /// heat (and, optionally, moisture) recovery
+    HeatRecoveryVentilator(Arc<HeatRecoveryVentilator>),
 }
 
 impl std::fmt::Display for HVAC {
@@ -90,6 +109,79 @@ pub trait SmallHVAC {
     fn target_space(&self) -> Result<&String, String>;
 }
 
+impl HVAC {
+    /// Clones this [`HVAC`], giving the copy its own indexes within
+    /// the [`SimulationState`] (see [`Model::deep_clone`](crate::Model::deep_clone))
+    pub fn deep_clone(&self) -> Result<Self, String> {
+        Ok(match self {
+            Self::IdealHeaterCooler(h) => Self::IdealHeaterCooler(Arc::new(h.deep_clone()?)),
+            Self::ElectricHeater(h) => Self::ElectricHeater(Arc::new(h.deep_clone()?)),
+            Self::HeatPump(h) => Self::HeatPump(Arc::new(h.deep_clone()?)),
+            Self::RadiantSurfaceSystem(h) => Self::RadiantSurfaceSystem(Arc::new(h.deep_clone()?)),
+            Self::HeatRecoveryVentilator(h) => {
+                Self::HeatRecoveryVentilator(Arc::new(h.deep_clone()?))
+            }
+        })
+    }
+
+    /// Gets the fraction of its capacity at which this [`HVAC`] is operating.
+    /// The fraction is positive when heating (i.e., relative to the maximum
+    /// heating power) and negative when cooling (i.e., relative to the
+    /// maximum cooling power).
+    pub fn part_load_fraction(&self, state: &SimulationState) -> Result<Float, String> {
+        let (max_heating, max_cooling) = hvac_capacity(self)?;
+        let power = hvac_power(self, state)
+            .ok_or_else(|| format!("HVAC '{}' has not been added to a model", self.name()))?;
+        let fraction = if power > 0. && max_heating > 0. {
+            power / max_heating
+        } else if power < 0. && max_cooling > 0. {
+            power / max_cooling
+        } else {
+            0.
+        };
+        Ok(fraction)
+    }
+
+    /// Makes this [`HVAC`] operate at a fraction of its capacity. Positive
+    /// fractions heat and negative ones cool; fractions beyond 1 (or -1)
+    /// are limited to the full capacity.
+    pub fn set_part_load_fraction(
+        &self,
+        state: &mut SimulationState,
+        fraction: Float,
+    ) -> Result<(), String> {
+        let (max_heating, max_cooling) = hvac_capacity(self)?;
+        let fraction = fraction.clamp(-1., 1.);
+        let power = if fraction >= 0. {
+            fraction * max_heating
+        } else {
+            fraction * max_cooling
+        };
+        set_hvac_power(self, state, power)
+    }
+
+    /// Checks whether this [`HVAC`] is delivering any heating or cooling
+    pub fn is_on(&self, state: &SimulationState) -> Result<bool, String> {
+        Ok(self.part_load_fraction(state)? != 0.)
+    }
+
+    /// Turns this [`HVAC`] off or on. Systems that are turned on while off
+    /// operate at their full heating capacity (or at their full cooling
+    /// capacity, if they cannot heat); systems that were already on keep
+    /// operating as they were.
+    pub fn set_on(&self, state: &mut SimulationState, on: bool) -> Result<(), String> {
+        if !on {
+            return set_hvac_power(self, state, 0.);
+        }
+        if self.is_on(state)? {
+            return Ok(());
+        }
+        let (max_heating, _) = hvac_capacity(self)?;
+        let fraction = if max_heating > 0. { 1. } else { -1. };
+        self.set_part_load_fraction(state, fraction)
+    }
+}
+
 /***********/
 /* TESTING */
 /***********/
@@ -211,4 +303,53 @@ mod testing {
 
         Ok(())
     }
+
+    #[test]
+    fn test_part_load_fraction() -> Result<(), String> {
+        let mut model = Model::default();
+        let mut hvac = IdealHeaterCooler::new("some hvac");
+        hvac.set_target_space("Bedroom")
+            .set_max_heating_power(1000.)
+            .set_max_cooling_power(2000.);
+        let hvac = model.add_hvac(hvac.wrap())?;
+        let mut state = model
+            .take_state()
+            .and_then(|mut header| header.take_values())
+            .ok_or("No state")?;
+
+        // Off by default
+        assert!(!hvac.is_on(&state)?);
+        assert_eq!(hvac.part_load_fraction(&state)?, 0.);
+
+        // Heating and cooling are relative to each capacity
+        hvac.set_part_load_fraction(&mut state, 0.5)?;
+        assert_eq!(hvac.part_load_fraction(&state)?, 0.5);
+        if let HVAC::IdealHeaterCooler(h) = &hvac {
+            assert_eq!(h.heating_cooling_consumption(&state), Some(500.));
+        }
+        hvac.set_part_load_fraction(&mut state, -2.)?;
+        assert_eq!(hvac.part_load_fraction(&state)?, -1.);
+        if let HVAC::IdealHeaterCooler(h) = &hvac {
+            assert_eq!(h.heating_cooling_consumption(&state), Some(-2000.));
+        }
+
+        // Turning on keeps the current operation
+        hvac.set_on(&mut state, true)?;
+        assert_eq!(hvac.part_load_fraction(&state)?, -1.);
+        hvac.set_on(&mut state, false)?;
+        assert!(!hvac.is_on(&state)?);
+        hvac.set_on(&mut state, true)?;
+        assert_eq!(hvac.part_load_fraction(&state)?, 1.);
+
+        // Ventilators cannot be modulated this way
+        let mut model = Model::default();
+        let hrv = model.add_hvac(HeatRecoveryVentilator::new("hrv").wrap())?;
+        let state = model
+            .take_state()
+            .and_then(|mut header| header.take_values())
+            .ok_or("No state")?;
+        assert!(hrv.part_load_fraction(&state).is_err());
+
+        Ok(())
+    }
 }
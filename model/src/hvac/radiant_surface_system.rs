@@ -0,0 +1,218 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::model::Model;
+use crate::simulation_state_element::StateElementField;
+use crate::Float;
+use serde::{Deserialize, Serialize};
+
+use derive::{GroupMemberAPI, ObjectIO};
+
+/// A radiant heating and/or cooling system (e.g., hydronic floor heating or
+/// a chilled ceiling) embedded in a [`Surface`](crate::Surface). Instead of
+/// delivering heat directly into the air of a space, it injects it into the
+/// construction of the `target_surface`, at the interface between the layer
+/// `source_layer` and the next one. Like the other HVACs, it only has two
+/// states: On/Off; and it has a COP of 1.
+///
+/// The thermostat that controls it—if any—is assumed to be in the
+/// `target_space`
+///
+/// ## Examples
+///
+/// #### `.spl`
+///
+/// ```json
+/// {{#include ../../../model/tests/scanner/hvac_radiant_surface_system.spl}}
+/// ```
+///
+/// #### `.json`
+///
+/// ```json
+/// {{#include ../../../model/tests/scanner/hvac_radiant_surface_system.json}}
+/// ```
+#[derive(Debug, Serialize, Deserialize, Clone, ObjectIO, GroupMemberAPI)]
+#[serde(deny_unknown_fields)]
+pub struct RadiantSurfaceSystem {
+    /// The name of the system
+    pub name: String,
+
+    /// The name of the `Surface` in which the heat is injected
+    target_surface: Option<String>,
+
+    /// The index of the layer (starting from 0, at the front of the
+    /// construction) after which the heat is injected. Defaults to 0.
+    source_layer: Option<usize>,
+
+    /// The `Space` whose temperature controls this system
+    target_space: Option<String>,
+
+    /// Max heating power, in W
+    max_heating_power: Option<Float>,
+
+    /// Max cooling power, in W
+    max_cooling_power: Option<Float>,
+
+    /// The temperature below which the system starts heating.
+    ///
+    /// This tempareture is 'measured' in the `target_space`.
+    heating_setpoint: Option<Float>,
+
+    /// The temperature over which the system starts cooling.
+    ///
+    /// This tempareture is 'measured' in the `target_space`.
+    cooling_setpoint: Option<Float>,
+
+    /// The name of the [`Schedule`](crate::Schedule) that defines when this
+    /// system is available. The system is turned off whenever the value
+    /// of this schedule is zero or less. If none is given, the system is
+    /// always available.
+    availability_schedule: Option<String>,
+
+    /// The heating (positive) or cooling (negative) power injected
+    /// into the `target_surface`
+    #[operational("power_consumption")]
+    #[serde(skip)]
+    heating_cooling_consumption: StateElementField,
+
+    /// The heating setpoint currently in use, in C, which control
+    /// algorithms can change. This state only exists if the system
+    /// has a `heating_setpoint`, which is its initial value.
+    #[operational("heating_setpoint")]
+    #[serde(skip)]
+    current_heating_setpoint: StateElementField,
+
+    /// The cooling setpoint currently in use, in C, which control
+    /// algorithms can change. This state only exists if the system
+    /// has a `cooling_setpoint`, which is its initial value.
+    #[operational("cooling_setpoint")]
+    #[serde(skip)]
+    current_cooling_setpoint: StateElementField,
+}
+
+impl RadiantSurfaceSystem {
+    /// Wraps the `RadiantSurfaceSystem` in an `HVAC` enum
+    pub fn wrap(self) -> crate::hvac::HVAC {
+        crate::hvac::HVAC::RadiantSurfaceSystem(std::sync::Arc::new(self))
+    }
+}
+
+impl super::SmallHVAC for RadiantSurfaceSystem {
+    fn heating_setpoint(&self) -> Result<Float, String> {
+        match self.heating_setpoint() {
+            Ok(v) => Ok(*v),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn cooling_setpoint(&self) -> Result<Float, String> {
+        match self.cooling_setpoint() {
+            Ok(v) => Ok(*v),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn max_heating_power(&self) -> Result<Float, String> {
+        match self.max_heating_power() {
+            Ok(v) => Ok(*v),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn max_cooling_power(&self) -> Result<Float, String> {
+        match self.max_cooling_power() {
+            Ok(v) => Ok(*v),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn target_space(&self) -> Result<&String, String> {
+        self.target_space()
+    }
+}
+
+#[cfg(test)]
+mod testing {
+
+    use super::*;
+    use crate::HVAC;
+
+    #[test]
+    fn serde() -> Result<(), String> {
+        use json5;
+        use std::fs;
+
+        // Hardcode a reference
+        let mut hardcoded_ref = RadiantSurfaceSystem::new("Floor heating");
+        hardcoded_ref
+            .set_target_surface("Living room floor")
+            .set_source_layer(1)
+            .set_target_space("Living room")
+            .set_max_heating_power(2000.)
+            .set_heating_setpoint(20.);
+        let hardcoded_ref = hardcoded_ref.wrap();
+
+        // Deserialize from hardcoded string and check they are the same
+        let from_json5: HVAC = json5::from_str(
+            "{
+            type: 'RadiantSurfaceSystem',
+            name: 'Floor heating',
+            target_surface: 'Living room floor',
+            source_layer: 1,
+            target_space: 'Living room',
+            max_heating_power: 2000,
+            heating_setpoint: 20,
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert_eq!(format!("{:?}", hardcoded_ref), format!("{:?}", from_json5));
+
+        // Read json file (used in DOC), Deserialize, and compare
+        let filename = "./tests/scanner/hvac_radiant_surface_system";
+        let json_file = format!("{}.json", filename);
+        let json_data = fs::read_to_string(json_file).map_err(|e| e.to_string())?;
+        let from_json_file: HVAC = serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_json_file)
+        );
+
+        // Serialize and deserialize again... check that everythin matches the pattern
+        let rust_json = serde_json::to_string(&hardcoded_ref).map_err(|e| e.to_string())?;
+        let from_serialized: HVAC = serde_json::from_str(&rust_json).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_serialized)
+        );
+
+        // check simple
+        let (model, ..) = Model::from_file("./tests/scanner/hvac_radiant_surface_system.spl")?;
+        assert_eq!(model.hvacs.len(), 1);
+        if let HVAC::RadiantSurfaceSystem(hvac) = &model.hvacs[0] {
+            assert_eq!("Floor heating", hvac.name());
+            assert_eq!("Living room floor", hvac.target_surface()?);
+            assert_eq!(1, *hvac.source_layer()?);
+        } else {
+            panic!("Incorrect HVAC!")
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,462 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::error_msgs::print_warning_no_module;
+use crate::substance::gas::GasSpecification;
+use crate::{
+    Boundary, Fenestration, FenestrationType, Float, Model, Substance, Surface, SurfaceType,
+};
+use geometry::{Loop3D, Point3D};
+use std::fmt::Write;
+
+/// Writes an IDF object, adding each field in a new line followed by its
+/// description (i.e., `!- The description`)
+fn write_object(out: &mut String, class: &str, fields: &[(String, &str)]) -> Result<(), String> {
+    writeln!(out, "  {},", class).map_err(|e| e.to_string())?;
+    let n = fields.len();
+    for (i, (value, description)) in fields.iter().enumerate() {
+        let sep = if i + 1 == n { ';' } else { ',' };
+        let value = format!("{}{}", value, sep);
+        writeln!(out, "    {:<25}!- {}", value, description).map_err(|e| e.to_string())?;
+    }
+    writeln!(out).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Adds the vertices of a [`Loop3D`] to the fields of an IDF object
+fn push_vertices(fields: &mut Vec<(String, &str)>, vertices: &[Point3D]) {
+    fields.push((format!("{}", vertices.len()), "Number of Vertices"));
+    for v in vertices {
+        fields.push((format!("{},{},{}", v.x, v.y, v.z), "X,Y,Z Vertex {m}"));
+    }
+}
+
+/// Gets the vertices of a [`Loop3D`], optionally reversing them
+fn vertices(l: &Loop3D, reverse: bool) -> Vec<Point3D> {
+    let mut ret = l.vertices().to_vec();
+    if reverse {
+        ret.reverse();
+    }
+    ret
+}
+
+/// Checks whether a [`Fenestration`] lies on a [`Surface`]; that is, whether its
+/// vertices are on the plane of the `Surface` (within a tolerance) and within
+/// its bounds. The hole for the `Fenestration` can be either an inner loop of
+/// the `Surface` or cut into its outer loop.
+fn hosts(surface: &Surface, fen: &Fenestration) -> Result<bool, String> {
+    const TOLERANCE: Float = 1e-3;
+
+    let normal = surface.vertices.normal().get_normalized();
+    if !normal.is_parallel(fen.vertices.normal()) {
+        return Ok(false);
+    }
+    let origin = surface.vertices.outer().vertices()[0];
+    // Projects a point onto the plane of the surface, if close enough
+    let project = |p: Point3D| {
+        let distance = normal * (p - origin);
+        (distance.abs() < TOLERANCE).then(|| p - normal * distance)
+    };
+
+    let centroid = match project(fen.vertices.outer_centroid()) {
+        Some(c) => c,
+        None => return Ok(false),
+    };
+    for j in 0..surface.vertices.n_inner_loops() {
+        if surface.vertices.inner(j)?.test_point(centroid)? {
+            return Ok(true);
+        }
+    }
+    for v in fen.vertices.outer().vertices() {
+        match project(*v) {
+            Some(v) if surface.vertices.outer().test_point(v)? => {}
+            _ => return Ok(false),
+        }
+    }
+    Ok(true)
+}
+
+/// Translates a [`Boundary`] into an IDF `Outside Boundary Condition`, its
+/// `Outside Boundary Condition Object`, the `Sun Exposure` and the `Wind Exposure`
+fn outside_boundary(
+    name: &str,
+    boundary: &Boundary,
+) -> Result<(String, String, &'static str, &'static str), String> {
+    match boundary {
+        Boundary::Outdoor => Ok((
+            "Outdoors".into(),
+            String::new(),
+            "SunExposed",
+            "WindExposed",
+        )),
+        Boundary::Ground => Ok(("Ground".into(), String::new(), "NoSun", "NoWind")),
+        Boundary::Adiabatic => Ok(("Adiabatic".into(), String::new(), "NoSun", "NoWind")),
+        Boundary::Space { space } => Ok(("Zone".into(), space.clone(), "NoSun", "NoWind")),
+        Boundary::AmbientTemperature { .. } => Err(format!(
+            "Surface '{}' has an AmbientTemperature boundary, which cannot be exported to IDF",
+            name
+        )),
+    }
+}
+
+/// Writes a [`Model`] in EnergyPlus' IDF format.
+///
+/// Only the description of the building is written (i.e., zones, materials, constructions,
+/// surfaces and fenestrations). The objects controlling the simulation
+/// (e.g., `Timestep` or `RunPeriod`) are not.
+pub(crate) fn write_idf(model: &Model) -> Result<String, String> {
+    let mut out = String::new();
+    let name = match &model.name {
+        Some(n) => n.as_str(),
+        None => "unnamed",
+    };
+    writeln!(out, "!- Model '{}' exported by SIMPLE\n", name).map_err(|e| e.to_string())?;
+
+    write_object(&mut out, "Version", &[("9.4".into(), "Version Identifier")])?;
+    write_object(
+        &mut out,
+        "GlobalGeometryRules",
+        &[
+            ("UpperLeftCorner".into(), "Starting Vertex Position"),
+            ("Counterclockwise".into(), "Vertex Entry Direction"),
+            ("World".into(), "Coordinate System"),
+        ],
+    )?;
+
+    /* ZONES */
+    for space in model.spaces.iter() {
+        let volume = match space.volume {
+            Some(v) => format!("{}", v),
+            None => "autocalculate".into(),
+        };
+        write_object(
+            &mut out,
+            "Zone",
+            &[
+                (space.name.clone(), "Name"),
+                ("0".into(), "Direction of Relative North {deg}"),
+                ("0".into(), "X Origin {m}"),
+                ("0".into(), "Y Origin {m}"),
+                ("0".into(), "Z Origin {m}"),
+                ("1".into(), "Type"),
+                ("1".into(), "Multiplier"),
+                ("autocalculate".into(), "Ceiling Height {m}"),
+                (volume, "Volume {m3}"),
+            ],
+        )?;
+    }
+
+    /* MATERIALS */
+    for material in model.materials.iter() {
+        let thickness = format!("{}", material.thickness);
+        match model.get_substance(&material.substance)? {
+            Substance::Normal(s) => {
+                let solar_transmittance = s.solar_transmittance_or("", 0.);
+                if solar_transmittance > 0. {
+                    let front_solar_reflectance =
+                        1. - solar_transmittance - s.front_solar_absorbtance_or("", 0.);
+                    let back_solar_reflectance =
+                        1. - solar_transmittance - s.back_solar_absorbtance_or("", 0.);
+                    write_object(
+                        &mut out,
+                        "WindowMaterial:Glazing",
+                        &[
+                            (material.name.clone(), "Name"),
+                            ("SpectralAverage".into(), "Optical Data Type"),
+                            (String::new(), "Window Glass Spectral Data Set Name"),
+                            (thickness, "Thickness {m}"),
+                            (
+                                format!("{}", solar_transmittance),
+                                "Solar Transmittance at Normal Incidence",
+                            ),
+                            (
+                                format!("{}", front_solar_reflectance),
+                                "Front Side Solar Reflectance at Normal Incidence",
+                            ),
+                            (
+                                format!("{}", back_solar_reflectance),
+                                "Back Side Solar Reflectance at Normal Incidence",
+                            ),
+                            (
+                                format!("{}", s.visible_transmissivity_or("", 0.)),
+                                "Visible Transmittance at Normal Incidence",
+                            ),
+                            (
+                                format!("{}", s.front_visible_reflectance_or("", 0.)),
+                                "Front Side Visible Reflectance at Normal Incidence",
+                            ),
+                            (
+                                format!("{}", s.back_visible_reflectance_or("", 0.)),
+                                "Back Side Visible Reflectance at Normal Incidence",
+                            ),
+                            ("0".into(), "Infrared Transmittance at Normal Incidence"),
+                            (
+                                format!("{}", s.front_thermal_absorbtance_or("", 0.84)),
+                                "Front Side Infrared Hemispherical Emissivity",
+                            ),
+                            (
+                                format!("{}", s.back_thermal_absorbtance_or("", 0.84)),
+                                "Back Side Infrared Hemispherical Emissivity",
+                            ),
+                            (
+                                format!("{}", s.thermal_conductivity()?),
+                                "Conductivity {W/m-K}",
+                            ),
+                        ],
+                    )?;
+                } else {
+                    let visible_absorbtance = 1. - s.front_visible_reflectance_or("", 0.3);
+                    write_object(
+                        &mut out,
+                        "Material",
+                        &[
+                            (material.name.clone(), "Name"),
+                            ("MediumRough".into(), "Roughness"),
+                            (thickness, "Thickness {m}"),
+                            (
+                                format!("{}", s.thermal_conductivity()?),
+                                "Conductivity {W/m-K}",
+                            ),
+                            (format!("{}", s.density()?), "Density {kg/m3}"),
+                            (
+                                format!("{}", s.specific_heat_capacity()?),
+                                "Specific Heat {J/kg-K}",
+                            ),
+                            (
+                                format!("{}", s.front_thermal_absorbtance_or("", 0.9)),
+                                "Thermal Absorptance",
+                            ),
+                            (
+                                format!("{}", s.front_solar_absorbtance_or("", 0.7)),
+                                "Solar Absorptance",
+                            ),
+                            (format!("{}", visible_absorbtance), "Visible Absorptance"),
+                        ],
+                    )?;
+                }
+            }
+            Substance::Gas(s) => {
+                let gas = match s.gas()? {
+                    GasSpecification::Air => "Air",
+                    GasSpecification::Argon => "Argon",
+                    GasSpecification::Krypton => "Krypton",
+                    GasSpecification::Xenon => "Xenon",
+                };
+                write_object(
+                    &mut out,
+                    "WindowMaterial:Gas",
+                    &[
+                        (material.name.clone(), "Name"),
+                        (gas.into(), "Gas Type"),
+                        (thickness, "Thickness {m}"),
+                    ],
+                )?;
+            }
+        }
+    }
+
+    /* CONSTRUCTIONS */
+    for construction in model.constructions.iter() {
+        let mut fields = vec![(construction.name.clone(), "Name")];
+        for (i, layer) in construction.materials.iter().enumerate() {
+            let description = if i == 0 { "Outside Layer" } else { "Layer" };
+            fields.push((layer.clone(), description));
+        }
+        write_object(&mut out, "Construction", &fields)?;
+    }
+
+    /* SURFACES */
+    // EnergyPlus surfaces belong to a Zone, and their normal points
+    // towards the outside.
+    let mut reversed = Vec::with_capacity(model.surfaces.len());
+    for s in model.surfaces.iter() {
+        let (zone, outside, reverse) = match (&s.front_boundary, &s.back_boundary) {
+            (_, Boundary::Space { space }) => (space.clone(), &s.front_boundary, false),
+            (Boundary::Space { space }, _) => (space.clone(), &s.back_boundary, true),
+            _ => {
+                print_warning_no_module(format!(
+                    "Surface '{}' does not face any Space... writing it as a shading surface",
+                    s.name
+                ));
+                let mut fields = vec![
+                    (s.name.clone(), "Name"),
+                    (String::new(), "Transmittance Schedule Name"),
+                ];
+                push_vertices(&mut fields, &vertices(s.vertices.outer(), false));
+                write_object(&mut out, "Shading:Building:Detailed", &fields)?;
+                reversed.push(false);
+                continue;
+            }
+        };
+        reversed.push(reverse);
+        let (condition, object, sun, wind) = outside_boundary(&s.name, outside)?;
+        let mut normal = s.vertices.normal();
+        if reverse {
+            normal *= -1.;
+        }
+        let surface_type = match s.category() {
+            Ok(SurfaceType::ExteriorWall) | Ok(SurfaceType::InteriorWall) => "Wall",
+            Ok(SurfaceType::GroundFloor)
+            | Ok(SurfaceType::ExteriorFloor)
+            | Ok(SurfaceType::InteriorFloor) => "Floor",
+            Ok(SurfaceType::Ceiling) => "Ceiling",
+            Ok(SurfaceType::Roof) => "Roof",
+            _ => {
+                const COS_TILT: Float = 0.7;
+                if normal.z > COS_TILT {
+                    if let Boundary::Outdoor = outside {
+                        "Roof"
+                    } else {
+                        "Ceiling"
+                    }
+                } else if normal.z < -COS_TILT {
+                    "Floor"
+                } else {
+                    "Wall"
+                }
+            }
+        };
+        let mut fields = vec![
+            (s.name.clone(), "Name"),
+            (surface_type.into(), "Surface Type"),
+            (s.construction.clone(), "Construction Name"),
+            (zone, "Zone Name"),
+            (condition, "Outside Boundary Condition"),
+            (object, "Outside Boundary Condition Object"),
+            (sun.into(), "Sun Exposure"),
+            (wind.into(), "Wind Exposure"),
+            ("autocalculate".into(), "View Factor to Ground"),
+        ];
+        push_vertices(&mut fields, &vertices(s.vertices.outer(), reverse));
+        write_object(&mut out, "BuildingSurface:Detailed", &fields)?;
+    }
+
+    /* FENESTRATIONS */
+    for fen in model.fenestrations.iter() {
+        let fenestration_type = match fen.category {
+            FenestrationType::Window => "Window",
+            FenestrationType::Door => "Door",
+            FenestrationType::Opening => {
+                print_warning_no_module(format!(
+                    "Fenestration '{}' is an Opening, which cannot be exported to IDF... ignoring it",
+                    fen.name
+                ));
+                continue;
+            }
+        };
+
+        // Find the parent
+        let mut parent = None;
+        for (i, s) in model.surfaces.iter().enumerate() {
+            let is_parent = match fen.parent_surface() {
+                Ok(p) => p == &s.name,
+                Err(_) => hosts(s, fen)?,
+            };
+            if is_parent {
+                parent = Some(i);
+                break;
+            }
+        }
+        let parent = match parent {
+            Some(i) => i,
+            None => {
+                return Err(format!(
+                    "Could not find the Surface containing Fenestration '{}'",
+                    fen.name
+                ))
+            }
+        };
+        let parent_surface = &model.surfaces[parent];
+        let mut parent_normal = parent_surface.vertices.normal();
+        if reversed[parent] {
+            parent_normal *= -1.;
+        }
+        let reverse = !fen.vertices.normal().is_same_direction(parent_normal);
+
+        let mut fields = vec![
+            (fen.name.clone(), "Name"),
+            (fenestration_type.into(), "Surface Type"),
+            (fen.construction.clone(), "Construction Name"),
+            (parent_surface.name.clone(), "Building Surface Name"),
+            (String::new(), "Outside Boundary Condition Object"),
+            ("autocalculate".into(), "View Factor to Ground"),
+            (String::new(), "Frame and Divider Name"),
+            ("1".into(), "Multiplier"),
+        ];
+        push_vertices(&mut fields, &vertices(fen.vertices.outer(), reverse));
+        write_object(&mut out, "FenestrationSurface:Detailed", &fields)?;
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use crate::SurfaceTrait;
+
+    #[test]
+    fn test_write_object() -> Result<(), String> {
+        let mut out = String::new();
+        write_object(
+            &mut out,
+            "Zone",
+            &[("The Zone".into(), "Name"), ("0".into(), "Direction")],
+        )?;
+        assert_eq!(
+            out,
+            "  Zone,\n    The Zone,                !- Name\n    0;                       !- Direction\n\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn idf_round_trip() -> Result<(), String> {
+        let (model, ..) = Model::from_idf_file("./tests/idf/box.idf")?;
+        let idf = write_idf(&model)?;
+        let (again, ..) = Model::from_idf(&idf)?;
+
+        assert_eq!(model.spaces.len(), again.spaces.len());
+        assert_eq!(model.spaces[0].volume, again.spaces[0].volume);
+        assert_eq!(model.substances.len(), again.substances.len());
+        assert_eq!(model.materials.len(), again.materials.len());
+        assert_eq!(model.constructions.len(), again.constructions.len());
+        assert_eq!(model.surfaces.len(), again.surfaces.len());
+        for (a, b) in model.surfaces.iter().zip(again.surfaces.iter()) {
+            assert_eq!(a.name, b.name);
+            assert!((a.area() - b.area()).abs() < 1e-6);
+            assert!(a.normal().is_same_direction(b.normal()));
+        }
+        assert_eq!(model.fenestrations.len(), again.fenestrations.len());
+        assert!((model.fenestrations[0].area() - again.fenestrations[0].area()).abs() < 1e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn idf_box_with_window() -> Result<(), String> {
+        // This model has no parent surfaces, but holes in the surfaces
+        let (model, ..) = Model::from_file("./tests/box_with_window.spl")?;
+        let idf = write_idf(&model)?;
+        let (again, ..) = Model::from_idf(&idf)?;
+        assert_eq!(model.surfaces.len(), again.surfaces.len());
+        assert_eq!(model.fenestrations.len(), again.fenestrations.len());
+        Ok(())
+    }
+}
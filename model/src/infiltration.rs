@@ -163,10 +163,37 @@ pub enum Infiltration {
     /// $$ Q = A_L \sqrt{\frac{2 \Delta P}{\rho}} $$
     ///
     /// Where $\rho$ is the air density of $1.2 kg/m^3$. So, the estimated $A_L$ would be $0.0496 m^2$
+    ///
+    /// ```json
+    /// {{#include ../../../model/tests/scanner/infiltration_effective_air_leakage_area.json}}
+    /// ```
+    ///
+    /// > **Note:** Following EnergyPlus' naming, this variant can also be declared
+    /// > as `EffectiveLeakageArea`.
+    #[serde(alias = "EffectiveLeakageArea")]
     EffectiveAirLeakageArea {
         /// The Effective Air Leakage Area (in m2)
         area: Float,
     },
+
+    /// Calculates the infiltration by solving the mass balance of an air flow
+    /// network. The nodes of this network are the `Space`s with this kind of
+    /// infiltration plus the outdoor environment, and the links are the
+    /// [`Crack`](crate::Crack)s in `Surface`s and `Fenestration`s, as well
+    /// as open `Fenestration`s.
+    ///
+    /// The pressure outside of each `Surface` or `Fenestration` is driven by the wind—through the
+    /// `wind_pressure_coefficients` in the `SiteDetails`—and by buoyancy, meaning
+    /// that the resulting infiltration and inter-zone flows respond to both
+    /// wind and temperature differences.
+    ///
+    /// Air coming from outside is reported as infiltration, while air coming from
+    /// other `Space`s is reported as ventilation.
+    ///
+    /// ```json
+    /// {{#include ../../../model/tests/scanner/infiltration_air_flow_network.json}}
+    /// ```
+    AirFlowNetwork,
 }
 
 /***********/
@@ -316,4 +343,82 @@ mod testing {
 
         Ok(())
     }
+
+    #[test]
+    fn serde_effective_air_leakage_area() -> Result<(), String> {
+        use json5;
+        use std::fs;
+
+        // Hardcode a reference
+        let hardcoded_ref = Infiltration::EffectiveAirLeakageArea { area: 0.05 };
+
+        // Deserialize from hardcoded string and check they are the same
+        let from_hardcoded_json: Infiltration = json5::from_str(
+            "{
+            type: 'EffectiveAirLeakageArea',
+            area: 0.05,
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert_eq!(hardcoded_ref, from_hardcoded_json);
+
+        // EnergyPlus' name is accepted as well
+        let from_eplus_name: Infiltration = json5::from_str(
+            "{
+            type: 'EffectiveLeakageArea',
+            area: 0.05,
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert_eq!(hardcoded_ref, from_eplus_name);
+
+        // Read json file (used in DOC), Deserialize, and compare
+        let json_data =
+            fs::read_to_string("./tests/scanner/infiltration_effective_air_leakage_area.json")
+                .map_err(|e| e.to_string())?;
+        let from_json_file: Infiltration =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(hardcoded_ref, from_json_file);
+
+        // Serialize and deserialize again... check that everythin matches the pattern
+        let rust_json = serde_json::to_string(&hardcoded_ref).map_err(|e| e.to_string())?;
+        let from_serialized: Infiltration =
+            serde_json::from_str(&rust_json).map_err(|e| e.to_string())?;
+        assert_eq!(hardcoded_ref, from_serialized);
+
+        Ok(())
+    }
+
+    #[test]
+    fn serde_air_flow_network() -> Result<(), String> {
+        use json5;
+        use std::fs;
+
+        // Hardcode a reference
+        let hardcoded_ref = Infiltration::AirFlowNetwork;
+
+        // Deserialize from hardcoded string and check they are the same
+        let from_hardcoded_json: Infiltration = json5::from_str(
+            "{
+            type: 'AirFlowNetwork',
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert_eq!(hardcoded_ref, from_hardcoded_json);
+
+        // Read json file (used in DOC), Deserialize, and compare
+        let json_data = fs::read_to_string("./tests/scanner/infiltration_air_flow_network.json")
+            .map_err(|e| e.to_string())?;
+        let from_json_file: Infiltration =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(hardcoded_ref, from_json_file);
+
+        // Serialize and deserialize again... check that everythin matches the pattern
+        let rust_json = serde_json::to_string(&hardcoded_ref).map_err(|e| e.to_string())?;
+        let from_serialized: Infiltration =
+            serde_json::from_str(&rust_json).map_err(|e| e.to_string())?;
+        assert_eq!(hardcoded_ref, from_serialized);
+
+        Ok(())
+    }
 }
@@ -130,6 +130,15 @@ pub mod rhai_api;
 /// The module containing the functions that allow parsing a Model from text files
 pub mod scanner;
 
+/// The module that writes a Model in EnergyPlus' IDF format
+mod idf_writer;
+
+/// The module that exports the geometry of a Model as a glTF scene
+mod gltf_writer;
+
+/// The module that writes the geometry of a Model in Wavefront's OBJ format
+mod obj_writer;
+
 /// Contains the structure that has all the data that changes throughout the simulation
 mod simulation_state;
 pub use simulation_state::{SimulationState, SimulationStateHeader};
@@ -151,6 +160,14 @@ pub use building::{Building, ShelterClass};
 mod construction;
 pub use construction::Construction;
 
+/// A library of common constructions, loadable by name
+mod construction_library;
+pub use construction_library::ConstructionPreset;
+
+/// The U-value, thermal mass and dynamic thermal properties of a [`Construction`]
+mod thermal_properties;
+pub use thermal_properties::{DynamicThermalProperties, R_SE, R_SI};
+
 /// A material; i.e., a Substance with a certain thickness
 mod material;
 pub use material::Material;
@@ -159,31 +176,97 @@ pub use material::Material;
 pub mod substance;
 pub use substance::Substance;
 
+/// Glazing systems (i.e., panes and gaps) imported from LBNL's WINDOW
+pub mod glazing_system;
+pub use glazing_system::GlazingSystem;
+
+/// Values that change over time (e.g., the power of a [`Luminaire`])
+mod schedule;
+pub use crate::schedule::{Schedule, ScheduleInterpolation, ScheduleProfile};
+
+/// A crack through which air leaks, for air flow network calculations
+mod crack;
+pub use crack::Crack;
+
+/// Linear thermal bridges (psi-values) along edges and junctions
+mod thermal_bridge;
+pub use thermal_bridge::ThermalBridge;
+
+/// Blinds and shades that can be deployed on a `Fenestration`
+mod shading_device;
+pub use shading_device::{ShadingDevice, ShadingPosition};
+
+/// Glazings whose optical properties change during the simulation (e.g., electrochromic)
+mod switchable_glazing;
+pub use switchable_glazing::SwitchableGlazing;
+
+/// The frames and dividers of a `Fenestration`
+mod window_frame;
+pub use window_frame::{FrameAreas, WindowFrame};
+
 /// Represents the boundary of a [`Surface`] (e.g. it can lead to the ground, outdoors or a space)
 mod boundary;
 pub use boundary::Boundary;
 
+/// The correlations used for calculating convection coefficients
+mod convection_model;
+pub use convection_model::ConvectionModel;
+
 /// A surface that can potentially be opened and closed.
 mod fenestration;
-pub use fenestration::{Fenestration, FenestrationPosition, FenestrationType};
+pub use fenestration::{Fenestration, FenestrationPosition, FenestrationType, OpeningPosition};
 
 /// A fixed (i.e., not movable) surface in the building (or surroundings). This can be of
 /// any Construction, transparent or not.
 mod surface;
 pub use surface::{Surface, SurfaceType};
 
+/// Pairs coincident surfaces at each side of a partition into
+/// a single interior surface
+mod surface_matching;
+pub use surface_matching::SurfaceMatchingOptions;
+
+/// Geometry that casts shadows but does not transfer heat
+/// (e.g., overhangs, fins or neighbouring buildings)
+mod shading_surface;
+pub use shading_surface::ShadingSurface;
+
+/// Photovoltaic panels that generate electricity from the sun
+mod photovoltaic;
+pub use photovoltaic::Photovoltaic;
+
+/// Solar thermal collectors heating domestic hot water
+mod solar_collector;
+pub use solar_collector::SolarCollector;
+
+/// Tanks storing domestic hot water
+mod water_tank;
+pub use water_tank::WaterTank;
+
 /// Some details of the site in which the building(s) is located
 mod site_details;
-pub use site_details::{SiteDetails, TerrainClass};
+pub use site_details::{GroundCoupling, GroundTemperature, SiteDetails, TerrainClass};
+
+/// Plug loads (e.g., computers, fridges) within a [`Space`]
+mod electric_equipment;
+pub use electric_equipment::ElectricEquipment;
 
 /// A Luminaire
 mod luminaire;
 pub use luminaire::Luminaire;
 
+/// A set of illuminance sensors (e.g., over a workplane)
+mod sensor_grid;
+pub use sensor_grid::SensorGrid;
+
 /// The module for requesting Outputs
 // mod output;
 pub use simulation_state_element::Output;
 
+/// Requests for reporting Outputs aggregated over time (e.g., monthly sums)
+mod report;
+pub use report::{Report, ReportAggregation, ReportFrequency};
+
 /// Represents a space within a building. This will
 /// often be a room, but it might also be half a room
 mod space;
@@ -197,18 +280,33 @@ pub use infiltration::Infiltration;
 pub mod hvac;
 pub use hvac::{SmallHVAC, HVAC};
 
+/// Thermostats controlling the heating and cooling systems
+mod thermostat;
+pub use thermostat::{Thermostat, ThermostatControlType};
+
 /// For setting options in simulations
 pub mod simulation_options;
-pub use simulation_options::SolarOptions;
+pub use simulation_options::{SolarOptions, ThermalOptions, TimestepOptions};
 
 /// For printing warning and error messages to the user
 pub mod error_msgs;
 pub use error_msgs::{print_error, print_warning};
+pub use utils::SimpleError;
 
 /// Common methods used for Surface-like elements (fenestration and surfaces)
 pub mod surface_trait;
 pub use surface_trait::{get_orientation, Orientation, SurfaceTrait};
 
+/// Moving, rotating and mirroring the elements in a Model
+mod transformable;
+pub use transformable::Transformable;
+
+/// Fluent constructors for generating the geometry of a Model
+pub mod builder;
+
+/// Generators of typical buildings, from bare shoeboxes to ready-to-simulate models
+pub mod archetypes;
+
 /// Appliances, furniture, etc.
 pub mod objects;
 pub use objects::{chair::*, sofa::*, storage::*, table::*, Object, ObjectSpecs};
@@ -57,10 +57,44 @@ pub struct Luminaire {
     #[serde(skip_serializing_if = "Option::is_none")]
     target_space: Option<String>,
 
+    /// The name of the [`Schedule`](crate::Schedule) that multiplies the
+    /// `max_power` of the luminaire. If none is given, the power consumption
+    /// is only modified by the control algorithms.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    schedule: Option<String>,
+
+    /// The name of the [`SensorGrid`](crate::SensorGrid) whose illuminance is
+    /// used for dimming this luminaire according to the available daylight.
+    /// If none is given, the luminaire is not dimmed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    daylight_sensor: Option<String>,
+
+    /// The illuminance (in lux) to be reached at the `daylight_sensor` by
+    /// adding artificial light to the daylight. Defaults to 500
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target_illuminance: Option<Float>,
+
+    /// The number of steps in which the luminaire can be dimmed (e.g., 2 means
+    /// it can be off, at half of its power, or at full power). If none is given,
+    /// the luminaire is dimmed continuously.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dimming_steps: Option<usize>,
+
+    /// The minimum fraction of the `max_power` consumed by a continuously dimmed
+    /// luminaire while it is on. Defaults to 0.1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_power_fraction: Option<Float>,
+
     /// The state of the luminaire    
     #[operational]
     #[serde(skip)]
     power_consumption: StateElementField,
+
+    /// The fraction of the `max_power` (from 0 to 1) that the luminaire is
+    /// dimmed to, if it has a `daylight_sensor`
+    #[operational]
+    #[serde(skip)]
+    dimming_level: StateElementField,
 }
 
 #[cfg(test)]
@@ -18,12 +18,20 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 use crate::error_msgs::print_warning_no_module;
-use crate::scanner::SimpleScanner;
+use crate::gltf_writer::write_gltf;
+use crate::idf_writer::write_idf;
+use crate::obj_writer::write_obj;
+use crate::scanner::{IdfScanner, SimpleScanner};
 use crate::simulation_state_element::SimulationStateElement;
+use crate::surface_matching::match_surfaces;
 use crate::{hvac::*, Boundary, FenestrationType, SolarOptions, SurfaceType};
-use crate::{Float, SiteDetails};
-use crate::{Object, SurfaceTrait};
-use crate::{Output, SimulationStateHeader};
+use crate::{
+    Float, SimpleError, SiteDetails, SurfaceMatchingOptions, ThermalOptions, TimestepOptions,
+};
+use crate::{Object, SurfaceTrait, Transformable};
+use crate::{Output, Report, SimulationState, SimulationStateHeader};
+use calendar::Date;
+use geometry::Transform;
 use serde::{self, de::Visitor};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -34,7 +42,11 @@ use std::io::prelude::*;
 use std::path::Path;
 use std::sync::Arc;
 
-use crate::{Building, Construction, Fenestration, Luminaire, Material, Space, Substance, Surface};
+use crate::{
+    Building, Construction, ElectricEquipment, Fenestration, Luminaire, Material, Photovoltaic,
+    Schedule, SensorGrid, ShadingSurface, SolarCollector, Space, Substance, Surface, Thermostat,
+    WaterTank,
+};
 
 /// A structure describing a set of built-environment objects.
 ///
@@ -49,6 +61,9 @@ pub struct Model {
     /// The [`Construction`]s in the model
     pub constructions: Vec<Arc<Construction>>,
 
+    /// The [`ElectricEquipment`] (i.e., plug loads) in the model
+    pub electric_equipment: Vec<Arc<ElectricEquipment>>,
+
     /// The windows and doors in the surface
     pub fenestrations: Vec<Arc<Fenestration>>,
 
@@ -75,11 +90,29 @@ pub struct Model {
     /// is missing (e.g., asking for temperature in node 192)
     pub outputs: Vec<Output>,
 
+    /// The [`Photovoltaic`] panels in the model
+    pub photovoltaics: Vec<Arc<Photovoltaic>>,
+
+    /// The [`Report`]s requested, aggregating [`Output`]s over time
+    pub reports: Vec<Report>,
+
+    /// The [`Schedule`]s in the model
+    pub schedules: Vec<Arc<Schedule>>,
+
+    /// The [`SensorGrid`]s in the model
+    pub sensor_grids: Vec<Arc<SensorGrid>>,
+
+    /// The [`ShadingSurface`]s in the model
+    pub shading_surfaces: Vec<Arc<ShadingSurface>>,
+
     /// Some information about the site in which the building(s) of the
     /// model are located.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub site_details: Option<SiteDetails>,
 
+    /// The [`SolarCollector`]s heating the [`WaterTank`]s in the model
+    pub solar_collectors: Vec<Arc<SolarCollector>>,
+
     /// The options for the Solar calculations
     #[serde(skip_serializing_if = "Option::is_none")]
     pub solar_options: Option<SolarOptions>,
@@ -93,6 +126,20 @@ pub struct Model {
     /// The [`Substance`]s in the model
     pub substances: Vec<Substance>,
 
+    /// The options for discretizing constructions in the thermal calculations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thermal_options: Option<ThermalOptions>,
+
+    /// The [`Thermostat`]s controlling the [`HVAC`]s in the model
+    pub thermostats: Vec<Arc<Thermostat>>,
+
+    /// The options controlling how often each physics module is marched
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timestep_options: Option<TimestepOptions>,
+
+    /// The domestic hot water [`WaterTank`]s in the model
+    pub water_tanks: Vec<Arc<WaterTank>>,
+
     /// Serde
     #[serde(skip)]
     simulation_state: Option<SimulationStateHeader>,
@@ -108,6 +155,10 @@ impl std::fmt::Display for Model {
             write!(f, "Construction {}", b)?;
         }
 
+        for b in self.electric_equipment.iter() {
+            write!(f, "ElectricEquipment {}", b)?;
+        }
+
         for b in self.fenestrations.iter() {
             write!(f, "Fenestration {}", b)?;
         }
@@ -132,10 +183,34 @@ impl std::fmt::Display for Model {
             write!(f, "Output {}\n\n", b)?;
         }
 
+        for b in self.photovoltaics.iter() {
+            write!(f, "Photovoltaic {}", b)?;
+        }
+
+        for b in self.reports.iter() {
+            write!(f, "Report {}", b)?;
+        }
+
+        for b in self.schedules.iter() {
+            write!(f, "Schedule {}", b)?;
+        }
+
+        for b in self.sensor_grids.iter() {
+            write!(f, "SensorGrid {}", b)?;
+        }
+
+        for b in self.shading_surfaces.iter() {
+            write!(f, "ShadingSurface {}", b)?;
+        }
+
         if let Some(s) = self.site_details.as_ref() {
             write!(f, "SiteDetails {}", s)?;
         }
 
+        for b in self.solar_collectors.iter() {
+            write!(f, "SolarCollector {}", b)?;
+        }
+
         for b in self.substances.iter() {
             write!(f, "Substance {}", b)?;
         }
@@ -148,6 +223,14 @@ impl std::fmt::Display for Model {
             write!(f, "Surface {}", b)?;
         }
 
+        for b in self.thermostats.iter() {
+            write!(f, "Thermostat {}", b)?;
+        }
+
+        for b in self.water_tanks.iter() {
+            write!(f, "WaterTank {}", b)?;
+        }
+
         Ok(())
     }
 }
@@ -158,16 +241,27 @@ impl std::default::Default for Model {
             buildings: Vec::default(),
             fenestrations: Vec::default(),
             constructions: Vec::default(),
+            electric_equipment: Vec::default(),
             hvacs: Vec::default(),
             luminaires: Vec::default(),
             materials: Vec::default(),
             objects: Vec::default(),
             outputs: Vec::default(),
+            photovoltaics: Vec::default(),
+            reports: Vec::default(),
+            schedules: Vec::default(),
+            sensor_grids: Vec::default(),
+            shading_surfaces: Vec::default(),
             site_details: None,
+            solar_collectors: Vec::default(),
             solar_options: None,
             spaces: Vec::default(),
             surfaces: Vec::default(),
             substances: Vec::default(),
+            thermal_options: None,
+            thermostats: Vec::default(),
+            timestep_options: None,
+            water_tanks: Vec::default(),
             simulation_state: Some(SimulationStateHeader::new()), // yeah... this is the only field that defaults to a non-default value.
         }
     }
@@ -188,87 +282,55 @@ impl<'de> Visitor<'de> for SimpleModelVisitor {
     {
         let mut model = Model::default();
 
+        // Objects are added once everything has been read, in an order that
+        // allows them to find the objects they depend on (e.g., a Fenestration
+        // needs its parent Surface)
+        let mut buildings: Vec<Building> = Vec::new();
+        let mut constructions: Vec<Construction> = Vec::new();
+        let mut electric_equipment: Vec<ElectricEquipment> = Vec::new();
+        let mut fenestrations: Vec<Fenestration> = Vec::new();
+        let mut hvacs: Vec<HVAC> = Vec::new();
+        let mut luminaires: Vec<Luminaire> = Vec::new();
+        let mut materials: Vec<Material> = Vec::new();
+        let mut objects: Vec<Object> = Vec::new();
+        let mut photovoltaics: Vec<Photovoltaic> = Vec::new();
+        let mut schedules: Vec<Schedule> = Vec::new();
+        let mut sensor_grids: Vec<SensorGrid> = Vec::new();
+        let mut shading_surfaces: Vec<ShadingSurface> = Vec::new();
+        let mut solar_collectors: Vec<SolarCollector> = Vec::new();
+        let mut spaces: Vec<Space> = Vec::new();
+        let mut surfaces: Vec<Surface> = Vec::new();
+        let mut substances: Vec<Substance> = Vec::new();
+        let mut thermostats: Vec<Thermostat> = Vec::new();
+        let mut water_tanks: Vec<WaterTank> = Vec::new();
+
         while let Some(key) = map.next_key::<&[u8]>()? {
             match key {
-                b"buildings" => {
-                    let objs: Vec<Building> = map.next_value()?;
-                    for o in objs.into_iter() {
-                        model.add_building(o);
-                    }
-                }
-                b"constructions" => {
-                    let objs: Vec<Construction> = map.next_value()?;
-                    for o in objs.into_iter() {
-                        model.add_construction(o);
-                    }
-                }
-                b"fenestrations" => {
-                    let objs: Vec<Fenestration> = map.next_value()?;
-                    for o in objs.into_iter() {
-                        model
-                            .add_fenestration(o)
-                            .map_err(serde::de::Error::custom)?;
-                    }
-                }
-                b"hvacs" => {
-                    let objs: Vec<HVAC> = map.next_value()?;
-                    for o in objs.into_iter() {
-                        model.add_hvac(o).map_err(serde::de::Error::custom)?;
-                    }
-                }
-                b"luminaires" => {
-                    let objs: Vec<Luminaire> = map.next_value()?;
-                    for o in objs.into_iter() {
-                        model.add_luminaire(o).map_err(serde::de::Error::custom)?;
-                    }
-                }
-                b"materials" => {
-                    let objs: Vec<Material> = map.next_value()?;
-                    for o in objs.into_iter() {
-                        model.add_material(o);
-                    }
-                }
-                b"name" => {
-                    model.name = map.next_value()?;
-                }
-                b"objects" => {
-                    let objs: Vec<Object> = map.next_value()?;
-                    for o in objs.into_iter() {
-                        // model.add_object(o).map_err(serde::de::Error::custom)?;
-                        model.objects.push(o);
-                    }
-                }
-                b"outputs" => {
-                    let objs: Vec<Output> = map.next_value()?;
-                    for o in objs.into_iter() {
-                        model.outputs.push(o);
-                    }
-                }
-                b"site_details" => {
-                    model.site_details = map.next_value()?;
-                }
-                b"solar_options" => {
-                    model.solar_options = map.next_value()?;
-                }
-                b"spaces" => {
-                    let objs: Vec<Space> = map.next_value()?;
-                    for o in objs.into_iter() {
-                        model.add_space(o);
-                    }
-                }
-                b"surfaces" => {
-                    let objs: Vec<Surface> = map.next_value()?;
-                    for o in objs.into_iter() {
-                        // model.add_surface(o).map_err(serde::de::Error::custom)?;
-                        model.surfaces.push(Arc::new(o));
-                    }
-                }
-                b"substances" => {
-                    let objs: Vec<Substance> = map.next_value()?;
-                    for o in objs.into_iter() {
-                        model.add_substance(o);
-                    }
-                }
+                b"buildings" => buildings = map.next_value()?,
+                b"constructions" => constructions = map.next_value()?,
+                b"electric_equipment" => electric_equipment = map.next_value()?,
+                b"fenestrations" => fenestrations = map.next_value()?,
+                b"hvacs" => hvacs = map.next_value()?,
+                b"luminaires" => luminaires = map.next_value()?,
+                b"materials" => materials = map.next_value()?,
+                b"name" => model.name = map.next_value()?,
+                b"objects" => objects = map.next_value()?,
+                b"outputs" => model.outputs = map.next_value()?,
+                b"photovoltaics" => photovoltaics = map.next_value()?,
+                b"reports" => model.reports = map.next_value()?,
+                b"schedules" => schedules = map.next_value()?,
+                b"sensor_grids" => sensor_grids = map.next_value()?,
+                b"shading_surfaces" => shading_surfaces = map.next_value()?,
+                b"site_details" => model.site_details = map.next_value()?,
+                b"solar_collectors" => solar_collectors = map.next_value()?,
+                b"solar_options" => model.solar_options = map.next_value()?,
+                b"spaces" => spaces = map.next_value()?,
+                b"surfaces" => surfaces = map.next_value()?,
+                b"substances" => substances = map.next_value()?,
+                b"thermal_options" => model.thermal_options = map.next_value()?,
+                b"thermostats" => thermostats = map.next_value()?,
+                b"timestep_options" => model.timestep_options = map.next_value()?,
+                b"water_tanks" => water_tanks = map.next_value()?,
                 _ => {
                     let k = std::str::from_utf8(key).map_err(serde::de::Error::custom)?;
                     Err(format!("Field '{}' in model is not serialized", k))
@@ -277,6 +339,70 @@ impl<'de> Visitor<'de> for SimpleModelVisitor {
             }
         }
 
+        // Same order as in the SimpleScanner
+        for o in schedules.into_iter() {
+            model.add_schedule(o);
+        }
+        for o in spaces.into_iter() {
+            model.add_space(o);
+        }
+        for o in substances.into_iter() {
+            model.add_substance(o);
+        }
+        for o in materials.into_iter() {
+            model.add_material(o);
+        }
+        for o in constructions.into_iter() {
+            model.add_construction(o);
+        }
+        for o in surfaces.into_iter() {
+            model.add_surface(o).map_err(serde::de::Error::custom)?;
+        }
+        for o in shading_surfaces.into_iter() {
+            model.add_shading_surface(o);
+        }
+        for o in photovoltaics.into_iter() {
+            model
+                .add_photovoltaic(o)
+                .map_err(serde::de::Error::custom)?;
+        }
+        for o in water_tanks.into_iter() {
+            model.add_water_tank(o).map_err(serde::de::Error::custom)?;
+        }
+        for o in solar_collectors.into_iter() {
+            model
+                .add_solar_collector(o)
+                .map_err(serde::de::Error::custom)?;
+        }
+        for o in fenestrations.into_iter() {
+            model
+                .add_fenestration(o)
+                .map_err(serde::de::Error::custom)?;
+        }
+        for o in buildings.into_iter() {
+            model.add_building(o);
+        }
+        for o in hvacs.into_iter() {
+            model.add_hvac(o).map_err(serde::de::Error::custom)?;
+        }
+        for o in thermostats.into_iter() {
+            model.add_thermostat(o).map_err(serde::de::Error::custom)?;
+        }
+        for o in luminaires.into_iter() {
+            model.add_luminaire(o).map_err(serde::de::Error::custom)?;
+        }
+        for o in electric_equipment.into_iter() {
+            model
+                .add_electric_equipment(o)
+                .map_err(serde::de::Error::custom)?;
+        }
+        for o in sensor_grids.into_iter() {
+            model.add_sensor_grid(o).map_err(serde::de::Error::custom)?;
+        }
+        for o in objects.into_iter() {
+            model.add_object(o).map_err(serde::de::Error::custom)?;
+        }
+
         Ok(model)
     }
 }
@@ -333,16 +459,265 @@ impl Model {
         Ok(())
     }
 
-    /// Prints the model into a file called 'filename' in JSON format
+    /// Clones the model, giving the copy its own indexes within the
+    /// [`SimulationState`]. Unlike `clone()`—whose objects share them with
+    /// the original—this allows building a second physics model (e.g., for
+    /// simulating in parallel) on the copy.
+    ///
+    /// ```rust
+    /// use model::Model;
+    ///
+    /// let (model, _header) = Model::from_file("./tests/box.spl").unwrap();
+    /// let copy = model.deep_clone().unwrap();
+    ///
+    /// // Indexes set on the copy are not set on the original
+    /// assert!(copy.surfaces[0].set_first_node_temperature_index(0).is_ok());
+    /// assert!(model.surfaces[0].first_node_temperature_index().is_none());
+    /// ```
+    pub fn deep_clone(&self) -> Result<Self, String> {
+        fn deep<T, F>(v: &[Arc<T>], f: F) -> Result<Vec<Arc<T>>, String>
+        where
+            F: Fn(&T) -> Result<T, String>,
+        {
+            v.iter().map(|x| Ok(Arc::new(f(x)?))).collect()
+        }
+
+        Ok(Self {
+            electric_equipment: deep(&self.electric_equipment, ElectricEquipment::deep_clone)?,
+            fenestrations: deep(&self.fenestrations, Fenestration::deep_clone)?,
+            hvacs: self
+                .hvacs
+                .iter()
+                .map(|h| h.deep_clone())
+                .collect::<Result<Vec<HVAC>, String>>()?,
+            luminaires: deep(&self.luminaires, Luminaire::deep_clone)?,
+            photovoltaics: deep(&self.photovoltaics, Photovoltaic::deep_clone)?,
+            sensor_grids: deep(&self.sensor_grids, SensorGrid::deep_clone)?,
+            solar_collectors: deep(&self.solar_collectors, SolarCollector::deep_clone)?,
+            spaces: deep(&self.spaces, Space::deep_clone)?,
+            surfaces: deep(&self.surfaces, Surface::deep_clone)?,
+            thermostats: deep(&self.thermostats, Thermostat::deep_clone)?,
+            water_tanks: deep(&self.water_tanks, WaterTank::deep_clone)?,
+            ..self.clone()
+        })
+    }
+
+    /// Serializes the whole model as a single JSON document, which can
+    /// be parsed again through [`Model::from_json`].
+    ///
+    /// Each field of the model becomes an array of objects (e.g., `"surfaces": [...]`),
+    /// written the same way as in the `.spl` format. Surfaces are written without
+    /// the holes cut by their fenestrations, and these keep their `parent_surface`
+    /// (which is otherwise not serialized), so the holes are cut again when parsing.
+    ///
+    /// ```rust
+    /// use model::Model;
+    ///
+    /// let (model, header) = Model::from_file("./tests/box_with_window.spl").unwrap();
+    /// let json = model.to_json().unwrap();
+    ///
+    /// let (again, again_header) = Model::from_json(&json).unwrap();
+    /// assert_eq!(model.surfaces.len(), again.surfaces.len());
+    /// assert_eq!(header.len(), again_header.len());
+    /// ```
+    pub fn to_json(&self) -> Result<String, String> {
+        let mut json = serde_json::to_value(self).map_err(|e| e.to_string())?;
+        for (j, fen) in self.fenestrations.iter().enumerate() {
+            if let Ok(parent) = fen.parent_surface() {
+                if let Some(i) = self.surfaces.iter().position(|s| s.name() == parent) {
+                    json["surfaces"][i]["vertices"] =
+                        serde_json::to_value(self.surfaces[i].vertices.outer())
+                            .map_err(|e| e.to_string())?;
+                    json["fenestrations"][j]["parent_surface"] = parent.as_str().into();
+                }
+            }
+        }
+        serde_json::to_string_pretty(&json).map_err(|e| e.to_string())
+    }
+
+    /// Prints the model into a file called 'filename' in JSON format.
+    /// See [`Model::to_json`]
     pub fn print_to_json_file(&self, filename: &str) -> Result<(), String> {
         let mut file = File::create(filename).map_err(|e| e.to_string())?;
         // Write a &str in the file (ignoring the result).
-        writeln!(
-            &mut file,
-            "{}",
-            serde_json::to_string(self).map_err(|e| e.to_string())?
-        )
-        .map_err(|e| e.to_string())?;
+        writeln!(&mut file, "{}", self.to_json()?).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Writes the model in EnergyPlus' IDF format.
+    ///
+    /// Only the description of the building is written (i.e., zones, materials,
+    /// constructions, surfaces and fenestrations), so simulation controls
+    /// (e.g., `RunPeriod` or `Timestep`) need to be added before running EnergyPlus.
+    ///
+    /// ```rust
+    /// use model::{Model, Space};
+    ///
+    /// let mut model = Model::default();
+    /// model.add_space(Space::new("The Zone"));
+    ///
+    /// let idf = model.to_idf().unwrap();
+    /// let (again, ..) = Model::from_idf(&idf).unwrap();
+    /// assert_eq!(again.spaces.len(), 1);
+    /// ```
+    pub fn to_idf(&self) -> Result<String, String> {
+        write_idf(self)
+    }
+
+    /// Prints the model into a file called 'filename' in EnergyPlus' IDF format.
+    /// See [`Model::to_idf`]
+    pub fn print_to_idf_file(&self, filename: &str) -> Result<(), String> {
+        let mut file = File::create(filename).map_err(|e| e.to_string())?;
+        file.write_all(self.to_idf()?.as_bytes())
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Exports the geometry of the model (i.e., surfaces, fenestrations,
+    /// shading surfaces and objects) as a glTF 2.0 scene, which can be
+    /// inspected in most 3D and web viewers.
+    ///
+    /// Each element becomes a node named after it. Objects are represented
+    /// as boxes of their `dimensions`.
+    ///
+    /// ```rust
+    /// use model::Model;
+    ///
+    /// let (model, ..) = Model::from_file("./tests/box_with_window.spl").unwrap();
+    /// let gltf = model.to_gltf().unwrap();
+    /// assert!(gltf.contains("\"version\": \"2.0\""));
+    /// ```
+    pub fn to_gltf(&self) -> Result<String, String> {
+        write_gltf(self)
+    }
+
+    /// Prints the geometry of the model into a file called 'filename' in
+    /// glTF format (i.e., a `.gltf` file). See [`Model::to_gltf`]
+    pub fn print_to_gltf_file(&self, filename: &str) -> Result<(), String> {
+        let mut file = File::create(filename).map_err(|e| e.to_string())?;
+        file.write_all(self.to_gltf()?.as_bytes())
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    /// Applies a [`Transform`] (e.g., a translation, rotation or mirror) to
+    /// all the geometry in the model: surfaces, fenestrations, shading surfaces,
+    /// photovoltaic panels, solar collectors, sensor grids and objects. See
+    /// [`Transformable`].
+    ///
+    /// The [`SiteDetails`] and all the other non-geometric elements are not
+    /// affected. This needs to be called before creating the simulation modules.
+    ///
+    /// ```rust
+    /// use model::{Model, SurfaceTrait};
+    /// use geometry::{Transform, Vector3D};
+    ///
+    /// let (mut model, ..) = Model::from_file("./tests/box.spl").unwrap();
+    /// let normal = model.surfaces[0].normal();
+    /// model.transform(&Transform::mirror(Vector3D::new(0., 0., 1.))).unwrap();
+    /// assert!(model.surfaces[0].normal().compare(normal * -1.));
+    /// ```
+    pub fn transform(&mut self, t: &Transform) -> Result<(), String> {
+        for s in self.surfaces.iter_mut() {
+            Arc::make_mut(s).transform(t)?;
+        }
+        for s in self.fenestrations.iter_mut() {
+            Arc::make_mut(s).transform(t)?;
+        }
+        for s in self.shading_surfaces.iter_mut() {
+            Arc::make_mut(s).transform(t)?;
+        }
+        for s in self.photovoltaics.iter_mut() {
+            Arc::make_mut(s).transform(t)?;
+        }
+        for s in self.solar_collectors.iter_mut() {
+            Arc::make_mut(s).transform(t)?;
+        }
+        for s in self.sensor_grids.iter_mut() {
+            Arc::make_mut(s).transform(t)?;
+        }
+        for o in self.objects.iter_mut() {
+            o.transform(t)?;
+        }
+        Ok(())
+    }
+
+    /// Rotates the whole model counterclockwise (i.e., from East to North)
+    /// around the vertical axis that passes through the origin, which is
+    /// useful for studying the effect of the orientation of a building. The
+    /// input is in degrees. See [`Model::transform`].
+    ///
+    /// ```rust
+    /// use model::{Model, SurfaceTrait};
+    ///
+    /// let (mut model, ..) = Model::from_file("./tests/box.spl").unwrap();
+    /// let normal = model.surfaces[0].normal();
+    /// for _ in 0..4 {
+    ///     model.rotate(90.).unwrap();
+    /// }
+    /// assert!(model.surfaces[0].normal().compare(normal));
+    /// ```
+    pub fn rotate(&mut self, degrees: Float) -> Result<(), String> {
+        self.transform(&Transform::rotate_z(degrees))
+    }
+
+    /// Finds pairs of coincident [`Surface`]s that separate two spaces—each
+    /// of them with one [`Boundary::Space`] and one [`Boundary::Outdoor`]—and
+    /// replaces each pair by a single surface that leads to both spaces.
+    ///
+    /// The surface that is kept is the one containing fenestrations, if any;
+    /// otherwise, the first one. Its category is updated accordingly (e.g.,
+    /// `ExteriorWall` becomes `InteriorWall`, and a roof matched with the floor
+    /// of the space above becomes an `InteriorFloor`). Returns the number of
+    /// pairs that were merged.
+    ///
+    /// This needs to be called before creating the simulation modules.
+    ///
+    /// ```rust
+    /// use model::{Model, SurfaceMatchingOptions};
+    ///
+    /// let (mut model, ..) = Model::from_file("./tests/box.spl").unwrap();
+    /// let n = model.match_surfaces(&SurfaceMatchingOptions::default()).unwrap();
+    /// assert_eq!(n, 0);
+    /// ```
+    pub fn match_surfaces(&mut self, options: &SurfaceMatchingOptions) -> Result<usize, String> {
+        match_surfaces(self, options)
+    }
+
+    /// Writes the surfaces and fenestrations of the model in Wavefront's OBJ
+    /// format, returning the content of the OBJ file and of the MTL file
+    /// it refers to as `mtllib`.
+    ///
+    /// Each element is written as a group of triangles named after it, and its
+    /// construction is used as its material, so each construction gets its own colour.
+    ///
+    /// ```rust
+    /// use model::Model;
+    ///
+    /// let (model, ..) = Model::from_file("./tests/box_with_window.spl").unwrap();
+    /// let (obj, mtl) = model.to_obj("box.mtl").unwrap();
+    /// assert!(obj.contains("mtllib box.mtl"));
+    /// assert!(mtl.contains("newmtl "));
+    /// ```
+    pub fn to_obj(&self, mtllib: &str) -> Result<(String, String), String> {
+        write_obj(self, mtllib)
+    }
+
+    /// Prints the surfaces and fenestrations of the model into a file called
+    /// 'filename' in OBJ format, and its materials into a file with the same name
+    /// but with an `.mtl` extension. See [`Model::to_obj`]
+    pub fn print_to_obj_file(&self, filename: &str) -> Result<(), String> {
+        let mtl_path = std::path::Path::new(filename).with_extension("mtl");
+        let mtllib = mtl_path
+            .file_name()
+            .and_then(|f| f.to_str())
+            .ok_or(format!("Invalid OBJ filename '{}'", filename))?;
+        let (obj, mtl) = self.to_obj(mtllib)?;
+
+        let mut file = File::create(filename).map_err(|e| e.to_string())?;
+        file.write_all(obj.as_bytes()).map_err(|e| e.to_string())?;
+        let mut file = File::create(&mtl_path).map_err(|e| e.to_string())?;
+        file.write_all(mtl.as_bytes()).map_err(|e| e.to_string())?;
         Ok(())
     }
 
@@ -384,7 +759,11 @@ impl Model {
         self.simulation_state.take()
     }
 
-    /// Parses a model from JSON
+    /// Parses a model from JSON (e.g., as produced by [`Model::to_json`]).
+    ///
+    /// Objects are added to the model in an order that allows them to
+    /// refer to the ones they depend on, regardless of the order of the
+    /// fields in the document.
     ///
     /// ```rust
     /// use model::Model;
@@ -400,10 +779,10 @@ impl Model {
     /// assert_eq!(header.len(), 0); // buildings don't have state
     /// assert_eq!(model.buildings.len(), 1);
     /// ```
-    pub fn from_json(json: &str) -> Result<(Self, SimulationStateHeader), String> {
+    pub fn from_json(json: &str) -> Result<(Self, SimulationStateHeader), SimpleError> {
         let mut model: Model = match serde_json::from_str(json) {
             Ok(m) => m,
-            Err(e) => return Err(e.to_string()),
+            Err(e) => return Err(SimpleError::Parse(e.to_string())),
         };
         let state = model
             .take_state()
@@ -435,10 +814,15 @@ impl Model {
     /// ```
     pub fn from_json_file<P: AsRef<Path> + Display>(
         filename: P,
-    ) -> Result<(Self, SimulationStateHeader), String> {
+    ) -> Result<(Self, SimulationStateHeader), SimpleError> {
         let jsonstring = match fs::read_to_string(&filename) {
             Ok(v) => v,
-            Err(_) => return Err(format!("Could not read JSON file '{}'", filename)),
+            Err(_) => {
+                return Err(SimpleError::Io(format!(
+                    "Could not read JSON file '{}'",
+                    filename
+                )))
+            }
         };
         Self::from_json(&jsonstring)
     }
@@ -459,9 +843,9 @@ impl Model {
     /// assert_eq!(header.len(), 0); // buildings don't have state
     /// assert_eq!(model.buildings.len(), 1);
     /// ```
-    pub fn from_bytes(bytes: &[u8]) -> Result<(Self, SimulationStateHeader), String> {
+    pub fn from_bytes(bytes: &[u8]) -> Result<(Self, SimulationStateHeader), SimpleError> {
         let mut scanner = SimpleScanner::new(bytes, 1);
-        scanner.parse_model()
+        scanner.parse_model().map_err(SimpleError::Parse)
     }
 
     /// Parses a `Model` from a text file
@@ -488,14 +872,75 @@ impl Model {
     /// ```
     pub fn from_file<P: AsRef<Path> + Display>(
         filename: P,
-    ) -> Result<(Self, SimulationStateHeader), String> {
+    ) -> Result<(Self, SimulationStateHeader), SimpleError> {
         let bytes = match fs::read(&filename) {
             Ok(v) => v,
-            Err(_) => return Err(format!("Could not read SIMPLE file '{}'", filename)),
+            Err(_) => {
+                return Err(SimpleError::Io(format!(
+                    "Could not read SIMPLE file '{}'",
+                    filename
+                )))
+            }
         };
         Self::from_bytes(&bytes)
     }
 
+    /// Parses a `Model` from the contents of an EnergyPlus IDF file.
+    ///
+    /// Only the objects that have an equivalent in SIMPLE are translated (i.e.,
+    /// zones, materials, constructions, surfaces and fenestrations); the rest
+    /// are ignored.
+    ///
+    /// ```rust
+    /// use model::Model;
+    ///
+    /// let s = r#"
+    ///     Version, 9.4;
+    ///     Zone, The Zone; !- A comment
+    /// "#;
+    ///
+    /// let (model, ..) = Model::from_idf(s).unwrap();
+    /// assert_eq!(model.spaces.len(), 1);
+    /// assert_eq!(model.spaces[0].name(), "The Zone");
+    /// ```
+    pub fn from_idf(idf: &str) -> Result<(Self, SimulationStateHeader), SimpleError> {
+        let scanner = IdfScanner::new(idf);
+        scanner.parse_model().map_err(SimpleError::Parse)
+    }
+
+    /// Parses a `Model` from an EnergyPlus IDF file. See [`Model::from_idf`]
+    ///
+    /// ```rust
+    /// use model::Model;
+    /// use std::fs;
+    /// use std::io::Write;
+    ///
+    /// let mut file = fs::File::create("./model.idf").unwrap();
+    /// let s = r#"
+    ///     Version, 9.4;
+    ///     Zone, The Zone;
+    /// "#;
+    /// write!(file, "{}", s).unwrap();
+    ///
+    /// let (model, ..) = Model::from_idf_file("./model.idf").unwrap();
+    /// assert_eq!(model.spaces.len(), 1);
+    /// fs::remove_file("./model.idf").unwrap()
+    /// ```
+    pub fn from_idf_file<P: AsRef<Path> + Display>(
+        filename: P,
+    ) -> Result<(Self, SimulationStateHeader), SimpleError> {
+        let idf = match fs::read_to_string(&filename) {
+            Ok(v) => v,
+            Err(_) => {
+                return Err(SimpleError::Io(format!(
+                    "Could not read IDF file '{}'",
+                    filename
+                )))
+            }
+        };
+        Self::from_idf(&idf)
+    }
+
     /// Adds an [`Object`] to the [`Model`]
     ///
     /// ```rust
@@ -586,14 +1031,14 @@ impl Model {
     /// // incorrect name
     /// assert!(model.get_substance("I do not exist").is_err());
     /// ```
-    pub fn get_substance<S: Into<String>>(&self, name: S) -> Result<Substance, String> {
+    pub fn get_substance<S: Into<String>>(&self, name: S) -> Result<Substance, SimpleError> {
         let name: String = name.into();
         for i in self.substances.iter() {
             if i.name() == &name {
                 return Ok(i.clone());
             }
         }
-        Err(format!("Could not find Substance '{}' in model", name))
+        Err(SimpleError::missing_reference("Substance", name))
     }
 
     /// Adds a reference (`Arc`) to a [`Material`] to the [`Model`]
@@ -637,14 +1082,14 @@ impl Model {
     /// assert!(model.get_material("Sweet Panel").is_ok());
     /// assert!(model.get_material("This inexistent Material").is_err());
     /// ```
-    pub fn get_material<S: Into<String>>(&self, name: S) -> Result<Arc<Material>, String> {
+    pub fn get_material<S: Into<String>>(&self, name: S) -> Result<Arc<Material>, SimpleError> {
         let name: String = name.into();
         for i in self.materials.iter() {
             if i.name() == &name {
                 return Ok(i.clone());
             }
         }
-        Err(format!("Could not find Material '{}' in model", name))
+        Err(SimpleError::missing_reference("Material", name))
     }
 
     /// Adds a [`Construction`] to the [`Model`]
@@ -689,14 +1134,17 @@ impl Model {
     /// assert!(model.get_construction("Cool Construction").is_ok());
     /// assert!(model.get_construction("Leaky Construction").is_err());
     /// ```
-    pub fn get_construction<S: Into<String>>(&self, name: S) -> Result<Arc<Construction>, String> {
+    pub fn get_construction<S: Into<String>>(
+        &self,
+        name: S,
+    ) -> Result<Arc<Construction>, SimpleError> {
         let name: String = name.into();
         for i in self.constructions.iter() {
             if i.name() == &name {
                 return Ok(i.clone());
             }
         }
-        Err(format!("Could not find Construction '{}' in model", name))
+        Err(SimpleError::missing_reference("Construction", name))
     }
 
     /// Adds a [`Surface`] to the [`Model`]
@@ -802,14 +1250,14 @@ impl Model {
     /// assert!(model.get_surface("the surface").is_ok());
     /// assert!(model.get_surface("nope... I am not here").is_err());
     /// ```
-    pub fn get_surface<S: Into<String>>(&self, name: S) -> Result<Arc<Surface>, String> {
+    pub fn get_surface<S: Into<String>>(&self, name: S) -> Result<Arc<Surface>, SimpleError> {
         let name: String = name.into();
         for i in self.surfaces.iter() {
             if i.name() == &name {
                 return Ok(i.clone());
             }
         }
-        Err(format!("Could not find Surface '{}' in model", name))
+        Err(SimpleError::missing_reference("Surface", name))
     }
 
     /// Adds a [`Space`] to the [`Model`]
@@ -828,10 +1276,17 @@ impl Model {
     /// model.add_space(space);
     /// assert_eq!(model.spaces.len(), 2);
     /// ```
-    pub fn add_space(&mut self, add: Space) -> Arc<Space> {
+    pub fn add_space(&mut self, mut add: Space) -> Arc<Space> {
         if self.get_space(add.name()).is_ok() {
             print_warning_no_module(format!("There is already a Space called '{}'", add.name()))
         }
+        if add.multiplier() == Ok(&0) {
+            print_warning_no_module(format!(
+                "Space '{}' has a multiplier of 0... assuming a value of 1",
+                add.name()
+            ));
+            add.set_multiplier(1);
+        }
         let add = Arc::new(add);
         self.spaces.push(Arc::clone(&add));
         add
@@ -849,14 +1304,14 @@ impl Model {
     /// assert!(model.get_space("Bedroom").is_ok());
     /// assert!(model.get_space("Walrus Enclosure").is_err());
     /// ```
-    pub fn get_space<S: Into<String>>(&self, name: S) -> Result<Arc<Space>, String> {
+    pub fn get_space<S: Into<String>>(&self, name: S) -> Result<Arc<Space>, SimpleError> {
         let name: String = name.into();
         for i in self.spaces.iter() {
             if i.name() == &name {
                 return Ok(i.clone());
             }
         }
-        Err(format!("Could not find Space '{}' in model", name))
+        Err(SimpleError::missing_reference("Space", name))
     }
 
     /// Adds a [`Building`] to the [`Model`]
@@ -900,14 +1355,14 @@ impl Model {
     /// assert!(model.get_building("Main Campus").is_ok());
     /// assert!(model.get_building("Bar With Free Beer and Coffee").is_err());
     /// ```
-    pub fn get_building<S: Into<String>>(&self, name: S) -> Result<Arc<Building>, String> {
+    pub fn get_building<S: Into<String>>(&self, name: S) -> Result<Arc<Building>, SimpleError> {
         let name: String = name.into();
         for i in self.buildings.iter() {
             if i.name() == &name {
                 return Ok(i.clone());
             }
         }
-        Err(format!("Could not find Building '{}' in model", name))
+        Err(SimpleError::missing_reference("Building", name))
     }
 
     /// Adds a [`Fenestration`] to the [`Model`]
@@ -1026,10 +1481,26 @@ impl Model {
         // Push the OpenFraction state, and map into the object
         let state_index = self.push_to_state(
             SimulationStateElement::FenestrationOpenFraction(fen_index),
-            0.,
+            add.initial_open_fraction()?,
         )?;
         add.set_open_fraction_index(state_index)?;
 
+        // Shades start fully retracted
+        if add.shading_device().is_ok() {
+            let state_index = self.push_to_state(
+                SimulationStateElement::FenestrationShadeOpenFraction(fen_index),
+                1.,
+            )?;
+            add.set_shade_open_fraction_index(state_index)?;
+        }
+
+        // Switchable glazings start clear
+        if add.switchable_glazing().is_ok() {
+            let state_index =
+                self.push_to_state(SimulationStateElement::FenestrationTintLevel(fen_index), 0.)?;
+            add.set_tint_level_index(state_index)?;
+        }
+
         // check the parent surface
         let mut parent: Option<Arc<Surface>> = None;
         if let Ok(parent_name) = add.parent_surface() {
@@ -1118,14 +1589,17 @@ impl Model {
     /// assert!(model.get_fenestration("Window 1").is_ok());
     /// assert!(model.get_fenestration("Huge window facing west that creates overheating").is_err());
     /// ```
-    pub fn get_fenestration<S: Into<String>>(&self, name: S) -> Result<Arc<Fenestration>, String> {
+    pub fn get_fenestration<S: Into<String>>(
+        &self,
+        name: S,
+    ) -> Result<Arc<Fenestration>, SimpleError> {
         let name: String = name.into();
         for i in self.fenestrations.iter() {
             if i.name() == &name {
                 return Ok(i.clone());
             }
         }
-        Err(format!("Could not find Fenestration '{}' in model", name))
+        Err(SimpleError::missing_reference("Fenestration", name))
     }
 
     /// Adds a [`HVAC`] to the [`Model`]
@@ -1158,6 +1632,63 @@ impl Model {
                     0.,
                 )?;
                 hvac.set_heating_cooling_consumption_index(state_index)?;
+                if let Ok(setpoint) = hvac.heating_setpoint() {
+                    let state_index = self.push_to_state(
+                        SimulationStateElement::HeatingSetpoint(obj_index),
+                        *setpoint,
+                    )?;
+                    hvac.set_current_heating_setpoint_index(state_index)?;
+                }
+            }
+            HVAC::HeatPump(hvac) => {
+                let state_index = self.push_to_state(
+                    SimulationStateElement::HeatingCoolingPowerConsumption(obj_index),
+                    0.,
+                )?;
+                hvac.set_heating_cooling_consumption_index(state_index)?;
+                if let Ok(setpoint) = hvac.heating_setpoint() {
+                    let state_index = self.push_to_state(
+                        SimulationStateElement::HeatingSetpoint(obj_index),
+                        *setpoint,
+                    )?;
+                    hvac.set_current_heating_setpoint_index(state_index)?;
+                }
+                if let Ok(setpoint) = hvac.cooling_setpoint() {
+                    let state_index = self.push_to_state(
+                        SimulationStateElement::CoolingSetpoint(obj_index),
+                        *setpoint,
+                    )?;
+                    hvac.set_current_cooling_setpoint_index(state_index)?;
+                }
+            }
+            HVAC::RadiantSurfaceSystem(hvac) => {
+                let state_index = self.push_to_state(
+                    SimulationStateElement::HeatingCoolingPowerConsumption(obj_index),
+                    0.,
+                )?;
+                hvac.set_heating_cooling_consumption_index(state_index)?;
+                if let Ok(setpoint) = hvac.heating_setpoint() {
+                    let state_index = self.push_to_state(
+                        SimulationStateElement::HeatingSetpoint(obj_index),
+                        *setpoint,
+                    )?;
+                    hvac.set_current_heating_setpoint_index(state_index)?;
+                }
+                if let Ok(setpoint) = hvac.cooling_setpoint() {
+                    let state_index = self.push_to_state(
+                        SimulationStateElement::CoolingSetpoint(obj_index),
+                        *setpoint,
+                    )?;
+                    hvac.set_current_cooling_setpoint_index(state_index)?;
+                }
+            }
+            HVAC::HeatRecoveryVentilator(hvac) => {
+                // On by default
+                let state_index = self.push_to_state(
+                    SimulationStateElement::VentilationAirFlowFraction(obj_index),
+                    1.,
+                )?;
+                hvac.set_air_flow_fraction_index(state_index)?;
             }
             HVAC::IdealHeaterCooler(hvac) => {
                 let state_index = self.push_to_state(
@@ -1165,6 +1696,27 @@ impl Model {
                     0.,
                 )?;
                 hvac.set_heating_cooling_consumption_index(state_index)?;
+                if let Ok(setpoint) = hvac.heating_setpoint() {
+                    let state_index = self.push_to_state(
+                        SimulationStateElement::HeatingSetpoint(obj_index),
+                        *setpoint,
+                    )?;
+                    hvac.set_current_heating_setpoint_index(state_index)?;
+                }
+                if let Ok(setpoint) = hvac.cooling_setpoint() {
+                    let state_index = self.push_to_state(
+                        SimulationStateElement::CoolingSetpoint(obj_index),
+                        *setpoint,
+                    )?;
+                    hvac.set_current_cooling_setpoint_index(state_index)?;
+                }
+                if hvac.controls_humidity() {
+                    let state_index = self.push_to_state(
+                        SimulationStateElement::LatentHeatingCoolingPowerConsumption(obj_index),
+                        0.,
+                    )?;
+                    hvac.set_latent_heating_cooling_consumption_index(state_index)?;
+                }
             }
         }
 
@@ -1192,52 +1744,186 @@ impl Model {
     ///
     /// assert!(model.get_hvac("15000 million fancy heating").is_err());
     /// ```
-    pub fn get_hvac<S: Into<String>>(&self, name: S) -> Result<HVAC, String> {
+    pub fn get_hvac<S: Into<String>>(&self, name: S) -> Result<HVAC, SimpleError> {
         let name: String = name.into();
         for i in self.hvacs.iter() {
             let hvac_name = match i {
                 HVAC::ElectricHeater(hvac) => hvac.name(),
                 HVAC::IdealHeaterCooler(hvac) => hvac.name(),
+                HVAC::HeatPump(hvac) => hvac.name(),
+                HVAC::RadiantSurfaceSystem(hvac) => hvac.name(),
+                HVAC::HeatRecoveryVentilator(hvac) => hvac.name(),
             };
 
             if hvac_name == &name {
                 return Ok(i.clone());
             }
         }
-        Err(format!("Could not find HVAC '{}' in model", name))
+        Err(SimpleError::missing_reference("HVAC", name))
     }
 
-    /// Adds a [`Luminaire`] to the [`Model`]
+    /// Adds a [`Thermostat`] to the [`Model`]. If it uses a `PID` controller,
+    /// its memory is pushed into the state.
     ///
     /// ```rust
-    /// use model::{Model, Luminaire};
-    ///
-    /// let luminaire = Luminaire::new("LED Lightbulb");
-    /// let mut model = Model::default();
-    /// assert!(model.luminaires.is_empty());
-    /// model.add_luminaire(luminaire);
-    /// assert_eq!(model.luminaires.len(), 1);
+    /// use model::{Model, Thermostat};
     ///
-    /// // Adding a new luminaire with the same name will print a warning but still work
-    /// let luminaire = Luminaire::new("LED Lightbulb");
-    /// model.add_luminaire(luminaire);
-    /// assert_eq!(model.luminaires.len(), 2);
+    /// let mut thermostat = Thermostat::new("Bedroom Thermostat", "Bedroom");
+    /// thermostat.hvacs.push("Bedroom Heater".into());
+    /// thermostat.set_heating_setpoint(18.);
     ///
+    /// let mut model = Model::default();
+    /// assert!(model.thermostats.is_empty());
+    /// model.add_thermostat(thermostat).unwrap();
+    /// assert_eq!(model.thermostats.len(), 1);
     /// ```
-    pub fn add_luminaire(&mut self, add: Luminaire) -> Result<Arc<Luminaire>, String> {
-        if self.get_luminaire(add.name()).is_ok() {
+    pub fn add_thermostat(&mut self, add: Thermostat) -> Result<Arc<Thermostat>, String> {
+        if self.get_thermostat(add.name()).is_ok() {
             print_warning_no_module(format!(
-                "There is already an Luminaire called '{}'",
+                "There is already a Thermostat called '{}'",
                 add.name()
             ))
         }
-        let obj_index = self.luminaires.len();
-        // Push the state, and map into the object
-        let state_index = self.push_to_state(
+        let obj_index = self.thermostats.len();
+        if add.is_pid() {
+            let state_index = self.push_to_state(
+                SimulationStateElement::ThermostatIntegralError(obj_index),
+                0.,
+            )?;
+            add.set_integral_error_index(state_index)?;
+
+            let state_index = self.push_to_state(
+                SimulationStateElement::ThermostatPreviousError(obj_index),
+                0.,
+            )?;
+            add.set_previous_error_index(state_index)?;
+        }
+
+        let add = Arc::new(add);
+        self.thermostats.push(Arc::clone(&add));
+        Ok(add)
+    }
+
+    /// Retrieves a reference (`Arc`) to a [`Thermostat`] based on its name, from the `thermostats`
+    /// field
+    ///
+    /// ```rust
+    /// use model::{Model, Thermostat};
+    ///
+    /// let thermostat = Thermostat::new("Bedroom Thermostat", "Bedroom");
+    /// let mut model = Model::default();
+    /// model.add_thermostat(thermostat).unwrap();
+    ///
+    /// assert!(model.get_thermostat("Bedroom Thermostat").is_ok());
+    /// assert!(model.get_thermostat("Kitchen Thermostat").is_err());
+    /// ```
+    pub fn get_thermostat<S: Into<String>>(&self, name: S) -> Result<Arc<Thermostat>, SimpleError> {
+        let name: String = name.into();
+        for i in self.thermostats.iter() {
+            if i.name() == &name {
+                return Ok(i.clone());
+            }
+        }
+        Err(SimpleError::missing_reference("Thermostat", name))
+    }
+
+    /// Adds an [`ElectricEquipment`] to the [`Model`]
+    ///
+    /// ```rust
+    /// use model::{Model, ElectricEquipment};
+    ///
+    /// let fridge = ElectricEquipment::new("Fridge");
+    /// let mut model = Model::default();
+    /// assert!(model.electric_equipment.is_empty());
+    /// model.add_electric_equipment(fridge);
+    /// assert_eq!(model.electric_equipment.len(), 1);
+    /// ```
+    pub fn add_electric_equipment(
+        &mut self,
+        add: ElectricEquipment,
+    ) -> Result<Arc<ElectricEquipment>, String> {
+        if self.get_electric_equipment(add.name()).is_ok() {
+            print_warning_no_module(format!(
+                "There is already an ElectricEquipment called '{}'",
+                add.name()
+            ))
+        }
+        let obj_index = self.electric_equipment.len();
+        // Push the state, and map into the object
+        let state_index = self.push_to_state(
+            SimulationStateElement::ElectricEquipmentPowerConsumption(obj_index),
+            0.,
+        )?;
+        add.set_power_consumption_index(state_index)?;
+
+        // Add to model, and return a reference
+        let add = Arc::new(add);
+        self.electric_equipment.push(Arc::clone(&add));
+        Ok(add)
+    }
+
+    /// Retrieves a reference (`Arc`) to an [`ElectricEquipment`] based on its name, from the
+    /// `electric_equipment` field
+    ///
+    /// ```rust
+    /// use model::{Model, ElectricEquipment};
+    ///
+    /// let fridge = ElectricEquipment::new("Fridge");
+    /// let mut model = Model::default();
+    /// model.add_electric_equipment(fridge);
+    ///
+    /// assert!(model.get_electric_equipment("Fridge").is_ok());
+    /// assert!(model.get_electric_equipment("Toaster").is_err());
+    /// ```
+    pub fn get_electric_equipment<S: Into<String>>(
+        &self,
+        name: S,
+    ) -> Result<Arc<ElectricEquipment>, SimpleError> {
+        let name: String = name.into();
+        for i in self.electric_equipment.iter() {
+            if i.name() == &name {
+                return Ok(i.clone());
+            }
+        }
+        Err(SimpleError::missing_reference("ElectricEquipment", name))
+    }
+
+    /// Adds a [`Luminaire`] to the [`Model`]
+    ///
+    /// ```rust
+    /// use model::{Model, Luminaire};
+    ///
+    /// let luminaire = Luminaire::new("LED Lightbulb");
+    /// let mut model = Model::default();
+    /// assert!(model.luminaires.is_empty());
+    /// model.add_luminaire(luminaire);
+    /// assert_eq!(model.luminaires.len(), 1);
+    ///
+    /// // Adding a new luminaire with the same name will print a warning but still work
+    /// let luminaire = Luminaire::new("LED Lightbulb");
+    /// model.add_luminaire(luminaire);
+    /// assert_eq!(model.luminaires.len(), 2);
+    ///
+    /// ```
+    pub fn add_luminaire(&mut self, add: Luminaire) -> Result<Arc<Luminaire>, String> {
+        if self.get_luminaire(add.name()).is_ok() {
+            print_warning_no_module(format!(
+                "There is already an Luminaire called '{}'",
+                add.name()
+            ))
+        }
+        let obj_index = self.luminaires.len();
+        // Push the state, and map into the object
+        let state_index = self.push_to_state(
             SimulationStateElement::LuminairePowerConsumption(obj_index),
             0.,
         )?;
         add.set_power_consumption_index(state_index)?;
+        if add.daylight_sensor().is_ok() {
+            let state_index =
+                self.push_to_state(SimulationStateElement::LuminaireDimmingLevel(obj_index), 1.)?;
+            add.set_dimming_level_index(state_index)?;
+        }
 
         // Add to model, and return a reference
         let add = Arc::new(add);
@@ -1258,14 +1944,378 @@ impl Model {
     /// assert!(model.get_luminaire("LED Lightbulb").is_ok());
     /// assert!(model.get_luminaire("Unnecessarily colourful smart lightbulb").is_err());
     /// ```
-    pub fn get_luminaire<S: Into<String>>(&self, name: S) -> Result<Arc<Luminaire>, String> {
+    pub fn get_luminaire<S: Into<String>>(&self, name: S) -> Result<Arc<Luminaire>, SimpleError> {
         let name: String = name.into();
         for i in self.luminaires.iter() {
             if i.name() == &name {
                 return Ok(i.clone());
             }
         }
-        Err(format!("Could not find Luminaire '{}' in model", name))
+        Err(SimpleError::missing_reference("Luminaire", name))
+    }
+
+    /// Adds a [`Schedule`] to the [`Model`]
+    ///
+    /// ```rust
+    /// use model::{Model, Schedule, ScheduleProfile};
+    ///
+    /// let schedule = Schedule::new("Always on", ScheduleProfile::Constant { value: 1. });
+    /// let mut model = Model::default();
+    /// model.add_schedule(schedule);
+    /// assert_eq!(model.schedules.len(), 1);
+    /// ```
+    pub fn add_schedule(&mut self, add: Schedule) -> Arc<Schedule> {
+        if self.get_schedule(add.name()).is_ok() {
+            print_warning_no_module(format!(
+                "There is already a Schedule called '{}'",
+                add.name()
+            ))
+        }
+        let add = Arc::new(add);
+        self.schedules.push(Arc::clone(&add));
+        add
+    }
+
+    /// Retrieves a reference (`Arc`) to a [`Schedule`] based on its name, from the `schedules`
+    /// field
+    ///
+    /// ```rust
+    /// use model::{Model, Schedule, ScheduleProfile};
+    ///
+    /// let schedule = Schedule::new("Always on", ScheduleProfile::Constant { value: 1. });
+    /// let mut model = Model::default();
+    /// model.add_schedule(schedule);
+    ///
+    /// assert!(model.get_schedule("Always on").is_ok());
+    /// assert!(model.get_schedule("Never on").is_err());
+    /// ```
+    pub fn get_schedule<S: Into<String>>(&self, name: S) -> Result<Arc<Schedule>, SimpleError> {
+        let name: String = name.into();
+        for i in self.schedules.iter() {
+            if i.name() == &name {
+                return Ok(i.clone());
+            }
+        }
+        Err(SimpleError::missing_reference("Schedule", name))
+    }
+
+    /// Adds a [`ShadingSurface`] to the [`Model`]
+    ///
+    /// ```rust
+    /// use model::{Model, ShadingSurface};
+    /// use geometry::{Loop3D, Point3D, Polygon3D};
+    ///
+    /// let mut the_loop = Loop3D::new();
+    /// the_loop.push(Point3D::new(0., 0., 2.)).unwrap();
+    /// the_loop.push(Point3D::new(1., 0., 2.)).unwrap();
+    /// the_loop.push(Point3D::new(1., -0.5, 2.)).unwrap();
+    /// the_loop.close().unwrap();
+    /// let overhang = ShadingSurface::new("Overhang", Polygon3D::new(the_loop).unwrap());
+    ///
+    /// let mut model = Model::default();
+    /// model.add_shading_surface(overhang);
+    /// assert_eq!(model.shading_surfaces.len(), 1);
+    /// ```
+    pub fn add_shading_surface(&mut self, add: ShadingSurface) -> Arc<ShadingSurface> {
+        if self.get_shading_surface(add.name()).is_ok() {
+            print_warning_no_module(format!(
+                "There is already a ShadingSurface called '{}'",
+                add.name()
+            ))
+        }
+        let add = Arc::new(add);
+        self.shading_surfaces.push(Arc::clone(&add));
+        add
+    }
+
+    /// Retrieves a reference (`Arc`) to a [`ShadingSurface`] based on its name, from
+    /// the `shading_surfaces` field
+    pub fn get_shading_surface<S: Into<String>>(
+        &self,
+        name: S,
+    ) -> Result<Arc<ShadingSurface>, SimpleError> {
+        let name: String = name.into();
+        for i in self.shading_surfaces.iter() {
+            if i.name() == &name {
+                return Ok(i.clone());
+            }
+        }
+        Err(SimpleError::missing_reference("ShadingSurface", name))
+    }
+
+    /// Adds a [`Photovoltaic`] panel to the [`Model`]. The panel must be
+    /// either installed on a `Surface` or described by its own `vertices`.
+    ///
+    /// ```rust
+    /// use model::{Model, Photovoltaic};
+    ///
+    /// let mut pv = Photovoltaic::new("Roof Panels");
+    /// pv.set_surface("Roof");
+    ///
+    /// let mut model = Model::default();
+    /// model.add_photovoltaic(pv).unwrap();
+    /// assert_eq!(model.photovoltaics.len(), 1);
+    /// ```
+    pub fn add_photovoltaic(&mut self, add: Photovoltaic) -> Result<Arc<Photovoltaic>, String> {
+        add.validate()?;
+        if self.get_photovoltaic(add.name()).is_ok() {
+            print_warning_no_module(format!(
+                "There is already a Photovoltaic called '{}'",
+                add.name()
+            ))
+        }
+        let add = Arc::new(add);
+        self.photovoltaics.push(Arc::clone(&add));
+        Ok(add)
+    }
+
+    /// Retrieves a reference (`Arc`) to a [`Photovoltaic`] panel based on its name, from
+    /// the `photovoltaics` field
+    pub fn get_photovoltaic<S: Into<String>>(
+        &self,
+        name: S,
+    ) -> Result<Arc<Photovoltaic>, SimpleError> {
+        let name: String = name.into();
+        for i in self.photovoltaics.iter() {
+            if i.name() == &name {
+                return Ok(i.clone());
+            }
+        }
+        Err(SimpleError::missing_reference("Photovoltaic", name))
+    }
+
+    /// Adds a [`SolarCollector`] to the [`Model`]. The collector must be
+    /// either installed on a `Surface` or described by its own `vertices`.
+    ///
+    /// ```rust
+    /// use model::{Model, SolarCollector};
+    ///
+    /// let mut collector = SolarCollector::new("Roof Collector", "Hot Water Tank");
+    /// collector.set_surface("Roof");
+    ///
+    /// let mut model = Model::default();
+    /// model.add_solar_collector(collector).unwrap();
+    /// assert_eq!(model.solar_collectors.len(), 1);
+    /// ```
+    pub fn add_solar_collector(
+        &mut self,
+        add: SolarCollector,
+    ) -> Result<Arc<SolarCollector>, String> {
+        add.validate()?;
+        if self.get_solar_collector(add.name()).is_ok() {
+            print_warning_no_module(format!(
+                "There is already a SolarCollector called '{}'",
+                add.name()
+            ))
+        }
+        let add = Arc::new(add);
+        self.solar_collectors.push(Arc::clone(&add));
+        Ok(add)
+    }
+
+    /// Retrieves a reference (`Arc`) to a [`SolarCollector`] based on its name, from
+    /// the `solar_collectors` field
+    pub fn get_solar_collector<S: Into<String>>(
+        &self,
+        name: S,
+    ) -> Result<Arc<SolarCollector>, SimpleError> {
+        let name: String = name.into();
+        for i in self.solar_collectors.iter() {
+            if i.name() == &name {
+                return Ok(i.clone());
+            }
+        }
+        Err(SimpleError::missing_reference("SolarCollector", name))
+    }
+
+    /// Adds a [`SensorGrid`] to the [`Model`]. The grid must be described
+    /// either by its `vertices` or by its `points`.
+    ///
+    /// ```rust
+    /// use model::{Model, SensorGrid};
+    ///
+    /// let mut grid = SensorGrid::new("Desk");
+    /// grid.set_points(vec![1., 1., 0.8, 2., 1., 0.8]);
+    ///
+    /// let mut model = Model::default();
+    /// model.add_sensor_grid(grid).unwrap();
+    /// assert_eq!(model.sensor_grids.len(), 1);
+    /// ```
+    pub fn add_sensor_grid(&mut self, add: SensorGrid) -> Result<Arc<SensorGrid>, String> {
+        add.validate()?;
+        if self.get_sensor_grid(add.name()).is_ok() {
+            print_warning_no_module(format!(
+                "There is already a SensorGrid called '{}'",
+                add.name()
+            ))
+        }
+        let add = Arc::new(add);
+        self.sensor_grids.push(Arc::clone(&add));
+        Ok(add)
+    }
+
+    /// Retrieves a reference (`Arc`) to a [`SensorGrid`] based on its name, from
+    /// the `sensor_grids` field
+    pub fn get_sensor_grid<S: Into<String>>(
+        &self,
+        name: S,
+    ) -> Result<Arc<SensorGrid>, SimpleError> {
+        let name: String = name.into();
+        for i in self.sensor_grids.iter() {
+            if i.name() == &name {
+                return Ok(i.clone());
+            }
+        }
+        Err(SimpleError::missing_reference("SensorGrid", name))
+    }
+
+    /// Adds a [`WaterTank`] to the [`Model`]
+    ///
+    /// ```rust
+    /// use model::{Model, WaterTank};
+    ///
+    /// let tank = WaterTank::new("Hot Water Tank", 0.2);
+    ///
+    /// let mut model = Model::default();
+    /// model.add_water_tank(tank).unwrap();
+    /// assert!(model.get_water_tank("Hot Water Tank").is_ok());
+    /// ```
+    pub fn add_water_tank(&mut self, add: WaterTank) -> Result<Arc<WaterTank>, String> {
+        if self.get_water_tank(add.name()).is_ok() {
+            print_warning_no_module(format!(
+                "There is already a WaterTank called '{}'",
+                add.name()
+            ))
+        }
+        if add.volume <= 0. {
+            return Err(format!(
+                "The volume of WaterTank '{}' must be greater than zero... found {}",
+                add.name(),
+                add.volume
+            ));
+        }
+        let obj_index = self.water_tanks.len();
+        // Push the state, and map into the object
+        let state_index =
+            self.push_to_state(SimulationStateElement::WaterTankDrawFlowRate(obj_index), 0.)?;
+        add.set_draw_flow_rate_index(state_index)?;
+
+        // Add to model, and return a reference
+        let add = Arc::new(add);
+        self.water_tanks.push(Arc::clone(&add));
+        Ok(add)
+    }
+
+    /// Retrieves a reference (`Arc`) to a [`WaterTank`] based on its name, from
+    /// the `water_tanks` field
+    pub fn get_water_tank<S: Into<String>>(&self, name: S) -> Result<Arc<WaterTank>, SimpleError> {
+        let name: String = name.into();
+        for i in self.water_tanks.iter() {
+            if i.name() == &name {
+                return Ok(i.clone());
+            }
+        }
+        Err(SimpleError::missing_reference("WaterTank", name))
+    }
+
+    /// Applies the [`Thermostat`]s in the model, setting the power of the [`HVAC`]s
+    /// they control based on the temperature of their `target_space`. The `dt` is
+    /// the timestep of the simulation, in seconds.
+    ///
+    /// This is meant to be called after the control algorithms and before
+    /// [`Model::apply_schedules`], so thermostats override whatever the control
+    /// algorithms decided for their [`HVAC`]s but cannot turn on an unavailable system.
+    pub fn apply_thermostats(
+        &self,
+        date: Date,
+        dt: Float,
+        state: &mut SimulationState,
+    ) -> Result<(), String> {
+        for thermostat in self.thermostats.iter() {
+            thermostat.control(self, date, dt, state)?;
+        }
+        Ok(())
+    }
+
+    /// Applies the [`Schedule`]s referenced by the objects in the model to the
+    /// [`SimulationState`], at a certain [`Date`].
+    ///
+    /// * The power consumption of a [`Luminaire`] or [`ElectricEquipment`] with a `schedule` is its `max_power` multiplied by the value of the schedule (and by the dimming level of the [`Luminaire`], if it is dimmed according to daylight)
+    /// * [`HVAC`]s whose `availability_schedule` is zero or less are turned off
+    /// * The `flow_fraction` of a [`HeatRecoveryVentilator`](hvac::HeatRecoveryVentilator) with a `schedule` is the value of the schedule
+    /// * The hot water drawn from a [`WaterTank`] with a `draw_schedule` is its `max_draw_flow_rate` multiplied by the value of the schedule
+    ///
+    /// This is meant to be called after the control algorithms, so schedules constrain
+    /// whatever these decided.
+    pub fn apply_schedules(&self, date: Date, state: &mut SimulationState) -> Result<(), String> {
+        for lum in self.luminaires.iter() {
+            if let Ok(schedule) = lum.schedule() {
+                let v = self.get_schedule(schedule)?.value(date)?;
+                let dimming = lum.dimming_level(state).unwrap_or(1.);
+                let power = lum.max_power_or("", 0.) * v * dimming;
+                lum.set_power_consumption(state, power)?;
+            }
+        }
+
+        for eq in self.electric_equipment.iter() {
+            if let Ok(schedule) = eq.schedule() {
+                let v = self.get_schedule(schedule)?.value(date)?;
+                let power = eq.max_power_or("", 0.) * v;
+                eq.set_power_consumption(state, power)?;
+            }
+        }
+
+        for tank in self.water_tanks.iter() {
+            if let Ok(schedule) = tank.draw_schedule() {
+                let v = self.get_schedule(schedule)?.value(date)?;
+                let draw = tank.max_draw_flow_rate_or("", 0.) * v;
+                tank.set_draw_flow_rate(state, draw)?;
+            }
+        }
+
+        for hvac in self.hvacs.iter() {
+            match hvac {
+                HVAC::ElectricHeater(h) => {
+                    if let Ok(schedule) = h.availability_schedule() {
+                        if self.get_schedule(schedule)?.value(date)? <= 0. {
+                            h.set_heating_cooling_consumption(state, 0.)?;
+                        }
+                    }
+                }
+                HVAC::HeatPump(h) => {
+                    if let Ok(schedule) = h.availability_schedule() {
+                        if self.get_schedule(schedule)?.value(date)? <= 0. {
+                            h.set_heating_cooling_consumption(state, 0.)?;
+                        }
+                    }
+                }
+                HVAC::RadiantSurfaceSystem(h) => {
+                    if let Ok(schedule) = h.availability_schedule() {
+                        if self.get_schedule(schedule)?.value(date)? <= 0. {
+                            h.set_heating_cooling_consumption(state, 0.)?;
+                        }
+                    }
+                }
+                HVAC::HeatRecoveryVentilator(h) => {
+                    if let Ok(schedule) = h.schedule() {
+                        let v = self.get_schedule(schedule)?.value(date)?;
+                        h.set_air_flow_fraction(state, v)?;
+                    }
+                }
+                HVAC::IdealHeaterCooler(h) => {
+                    if let Ok(schedule) = h.availability_schedule() {
+                        if self.get_schedule(schedule)?.value(date)? <= 0. {
+                            h.set_heating_cooling_consumption(state, 0.)?;
+                            if h.controls_humidity() {
+                                h.set_latent_heating_cooling_consumption(state, 0.)?;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+
+        Ok(())
     }
 
     /// Retrieves a reference (`Arc`) to the [`Substance`] that comprises a [`Material`] called `mat_name`.
@@ -1298,7 +2348,7 @@ impl Model {
     pub fn get_material_substance<S: Into<String>>(
         &self,
         mat_name: S,
-    ) -> Result<Substance, String> {
+    ) -> Result<Substance, SimpleError> {
         let mat = self.get_material(mat_name)?;
         let name = &mat.substance;
         self.get_substance(name)
@@ -1346,6 +2396,31 @@ impl Model {
                         Err(_) => None,
                     };
 
+                    (hvac.name().clone(), heating, cooling)
+                }
+                HVAC::HeatPump(h) => {
+                    let heating = match h.heating_setpoint() {
+                        Ok(v) => Some(*v),
+                        Err(_) => None,
+                    };
+                    let cooling = match h.cooling_setpoint() {
+                        Ok(v) => Some(*v),
+                        Err(_) => None,
+                    };
+
+                    (hvac.name().clone(), heating, cooling)
+                }
+                HVAC::HeatRecoveryVentilator(_) => (hvac.name().clone(), None, None),
+                HVAC::RadiantSurfaceSystem(h) => {
+                    let heating = match h.heating_setpoint() {
+                        Ok(v) => Some(*v),
+                        Err(_) => None,
+                    };
+                    let cooling = match h.cooling_setpoint() {
+                        Ok(v) => Some(*v),
+                        Err(_) => None,
+                    };
+
                     (hvac.name().clone(), heating, cooling)
                 }
             };
@@ -1360,7 +2435,11 @@ impl Model {
     /// # NOTE:
     ///
     /// * It does not separate by building or anything
-    /// * It only accounts for surfaces labelled as
+    /// * It only accounts for surfaces labelled as floors (i.e., `GroundFloor`,
+    ///   `InteriorFloor` or `ExteriorFloor`). Surfaces without a `category` are
+    ///   considered floors if they are roughly horizontal.
+    /// * The areas of each space are those of a single copy of it, but the
+    ///   total area accounts for the `multiplier` of each [`Space`]
     ///
     /// ```
     /// use model::Model;
@@ -1368,8 +2447,6 @@ impl Model {
     /// let (total_area, areas) = model.get_space_sizes();
     /// ```
     pub fn get_space_sizes(&self) -> (Float, HashMap<String, Float>) {
-        let mut total_area = 0.0;
-
         let mut floor_areas = HashMap::with_capacity(self.spaces.len());
         self.spaces.iter().for_each(|s| {
             floor_areas.insert(s.name().clone(), 0.0);
@@ -1392,37 +2469,80 @@ impl Model {
         }
 
         for s in self.surfaces.iter() {
-            if let (Ok(cat), Some(space)) = (s.category(), get_space(s)) {
-                let area = s.area();
-                match cat {
-                    SurfaceType::GroundFloor => {
-                        total_area += area;
-                        let k = floor_areas
-                            .get_mut(&space)
-                            .unwrap_or_else(|| panic!("Unexpected space {}", space));
-                        *k += area;
-                    }
-                    SurfaceType::InteriorFloor => {
-                        total_area += area;
-                        let k = floor_areas
-                            .get_mut(&space)
-                            .unwrap_or_else(|| panic!("Unexpected space {}", space));
-                        *k += area;
-                    }
-                    SurfaceType::ExteriorFloor => {
-                        total_area += area;
-                        let k = floor_areas
-                            .get_mut(&space)
-                            .unwrap_or_else(|| panic!("Unexpected space {}", space));
-                        *k += area;
-                    }
-                    _ => {} // ignore
-                }
+            let is_floor = match s.category() {
+                Ok(SurfaceType::GroundFloor)
+                | Ok(SurfaceType::InteriorFloor)
+                | Ok(SurfaceType::ExteriorFloor) => true,
+                Ok(_) => false,
+                // get_space() returns the space above the surface, so
+                // any horizontal surface with a space above is its floor
+                Err(_) => s.normal().z.abs() > 0.7,
+            };
+            if let (true, Some(space)) = (is_floor, get_space(s)) {
+                let k = floor_areas
+                    .get_mut(&space)
+                    .unwrap_or_else(|| panic!("Unexpected space {}", space));
+                *k += s.area();
             }
         }
 
+        let total_area = floor_areas
+            .iter()
+            .map(|(space, area)| area * self.space_multiplier(space))
+            .sum();
+
         (total_area, floor_areas)
     }
+
+    /// Gets the multiplier of the [`Space`] called `name`—i.e., the number of
+    /// identical copies of it in the building. Returns 1 if the space
+    /// does not exist or has no multiplier.
+    pub fn space_multiplier<S: Into<String>>(&self, name: S) -> Float {
+        match self.get_space(name) {
+            Ok(space) => space.multiplier_or("Model", 1) as Float,
+            Err(_) => 1.,
+        }
+    }
+
+    /// Gets the factor by which the value of a [`SimulationStateElement`]
+    /// needs to be multiplied when reporting totals for the whole building.
+    ///
+    /// This is the multiplier of the target space for the power consumption
+    /// of luminaires, electric equipment, HVAC systems and the heaters of the
+    /// water tanks located in it, and 1 for everything else (e.g., temperatures
+    /// are the same in every copy of a space).
+    pub fn state_element_multiplier(&self, element: &SimulationStateElement) -> Float {
+        let target_space = match element {
+            SimulationStateElement::LuminairePowerConsumption(i) => self
+                .luminaires
+                .get(*i)
+                .and_then(|lum| lum.target_space().ok()),
+            SimulationStateElement::ElectricEquipmentPowerConsumption(i) => self
+                .electric_equipment
+                .get(*i)
+                .and_then(|eq| eq.target_space().ok()),
+            SimulationStateElement::WaterTankHeaterPowerConsumption(i) => {
+                self.water_tanks.get(*i).and_then(|tank| tank.space().ok())
+            }
+            SimulationStateElement::HeatingCoolingPowerConsumption(i)
+            | SimulationStateElement::LatentHeatingCoolingPowerConsumption(i)
+            | SimulationStateElement::HeatPumpElectricityConsumption(i) => {
+                match self.hvacs.get(*i) {
+                    Some(HVAC::IdealHeaterCooler(h)) => h.target_space().ok(),
+                    Some(HVAC::ElectricHeater(h)) => h.target_space().ok(),
+                    Some(HVAC::HeatPump(h)) => h.target_space().ok(),
+                    Some(HVAC::RadiantSurfaceSystem(h)) => h.target_space().ok(),
+                    Some(HVAC::HeatRecoveryVentilator(h)) => h.target_space().ok(),
+                    None => None,
+                }
+            }
+            _ => None,
+        };
+        match target_space {
+            Some(space) => self.space_multiplier(space),
+            None => 1.,
+        }
+    }
 }
 
 /***********/
@@ -1435,6 +2555,54 @@ mod testing {
     use super::*;
 
     use crate::substance::Normal;
+    use crate::ScheduleProfile;
+
+    #[test]
+    fn test_error_kinds() {
+        let model = Model::default();
+        assert!(matches!(
+            model.get_space("Nowhere"),
+            Err(SimpleError::MissingReference { .. })
+        ));
+        assert!(matches!(
+            Model::from_json("{ not json"),
+            Err(SimpleError::Parse(_))
+        ));
+        assert!(matches!(
+            Model::from_file("./tests/this_does_not_exist.spl"),
+            Err(SimpleError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_json_round_trip() -> Result<(), String> {
+        // This one has fenestrations with parent surfaces
+        let (model, state) = Model::from_file("./tests/cold_wellington_apartment.spl")?;
+        let json = model.to_json()?;
+        let (again, other_state) = Model::from_json(&json)?;
+
+        assert_eq!(state.len(), other_state.len());
+        assert_eq!(model.spaces.len(), again.spaces.len());
+        assert_eq!(model.constructions.len(), again.constructions.len());
+        assert_eq!(model.hvacs.len(), again.hvacs.len());
+        assert_eq!(model.surfaces.len(), again.surfaces.len());
+        for (a, b) in model.surfaces.iter().zip(again.surfaces.iter()) {
+            assert_eq!(a.name, b.name);
+            assert!((a.area() - b.area()).abs() < 1e-6);
+        }
+        assert_eq!(model.fenestrations.len(), again.fenestrations.len());
+        for (a, b) in model.fenestrations.iter().zip(again.fenestrations.iter()) {
+            assert_eq!(a.name, b.name);
+            assert_eq!(
+                format!("{:?}", a.front_boundary),
+                format!("{:?}", b.front_boundary)
+            );
+        }
+
+        // And it is stable
+        assert_eq!(json, again.to_json()?);
+        Ok(())
+    }
 
     #[test]
     fn serde() -> Result<(), String> {
@@ -1485,6 +2653,7 @@ mod testing {
             hvac, ChairArmType, ChairBackType, ChairLegType, ChairType, Object, ObjectSpecs,
             SofaType, SpacePurpose, StorageType, TableShape, TableType,
         };
+        use crate::{Report, ReportAggregation, ReportFrequency};
 
         let dir = "../docs/ioreference/src";
 
@@ -1519,6 +2688,8 @@ mod testing {
         ChairLegType::print_doc(&dir, &mut summary).map_err(|e| e.to_string())?;
         ChairType::print_doc(&dir, &mut summary).map_err(|e| e.to_string())?;
         Construction::print_doc(&dir, &mut summary).map_err(|e| e.to_string())?;
+        crate::ConstructionPreset::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
+        crate::Crack::print_doc(&dir, &mut summary).map_err(|e| e.to_string())?;
 
         /*****/
         /* D */
@@ -1527,6 +2698,8 @@ mod testing {
         /*****/
         /* E */
         /*****/
+        ElectricEquipment::print_doc(&dir, &mut summary).map_err(|e| e.to_string())?;
+        ElectricEquipment::print_api_doc(&dir, &mut summary).map_err(|e| e.to_string())?;
 
         /*****/
         /* F */
@@ -1539,6 +2712,8 @@ mod testing {
         /*****/
         /* G */
         /*****/
+        crate::GroundCoupling::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
+        crate::GroundTemperature::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
 
         /*****/
         /* H */
@@ -1552,6 +2727,19 @@ mod testing {
         hvac::IdealHeaterCooler::print_doc(&dir, &mut summary).map_err(|e| e.to_string())?;
         hvac::IdealHeaterCooler::print_api_doc(&dir, &mut summary).map_err(|e| e.to_string())?;
 
+        summary.push_str(&format!("\t"));
+        hvac::HeatPump::print_doc(&dir, &mut summary).map_err(|e| e.to_string())?;
+        hvac::HeatPump::print_api_doc(&dir, &mut summary).map_err(|e| e.to_string())?;
+
+        summary.push_str(&format!("\t"));
+        hvac::RadiantSurfaceSystem::print_doc(&dir, &mut summary).map_err(|e| e.to_string())?;
+        hvac::RadiantSurfaceSystem::print_api_doc(&dir, &mut summary).map_err(|e| e.to_string())?;
+
+        summary.push_str(&format!("\t"));
+        hvac::HeatRecoveryVentilator::print_doc(&dir, &mut summary).map_err(|e| e.to_string())?;
+        hvac::HeatRecoveryVentilator::print_api_doc(&dir, &mut summary)
+            .map_err(|e| e.to_string())?;
+
         /*****/
         /* I */
         /*****/
@@ -1586,11 +2774,14 @@ mod testing {
         /*****/
         Object::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
         ObjectSpecs::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
+        crate::OpeningPosition::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
         Output::print_doc(&dir, &mut summary).map_err(|e| e.to_string())?;
 
         /*****/
         /* P */
         /*****/
+        Photovoltaic::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
+        Photovoltaic::print_api_doc(dir, &mut summary).map_err(|e| e.to_string())?;
 
         /*****/
         /* Q */
@@ -1599,11 +2790,24 @@ mod testing {
         /*****/
         /* R */
         /*****/
+        Report::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
+        ReportAggregation::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
+        ReportFrequency::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
 
         /*****/
         /* S */
         /*****/
+        Schedule::print_doc(&dir, &mut summary).map_err(|e| e.to_string())?;
+        crate::ScheduleInterpolation::print_doc(&dir, &mut summary).map_err(|e| e.to_string())?;
+        crate::ScheduleProfile::print_doc(&dir, &mut summary).map_err(|e| e.to_string())?;
+        SensorGrid::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
+        SensorGrid::print_api_doc(dir, &mut summary).map_err(|e| e.to_string())?;
+        crate::ShadingDevice::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
+        crate::ShadingPosition::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
+        ShadingSurface::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
         SiteDetails::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
+        SolarCollector::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
+        SolarCollector::print_api_doc(dir, &mut summary).map_err(|e| e.to_string())?;
         SofaType::print_doc(&dir, &mut summary).map_err(|e| e.to_string())?;
         SolarOptions::print_doc(&dir, &mut summary).map_err(|e| e.to_string())?;
         Space::print_doc(&dir, &mut summary).map_err(|e| e.to_string())?;
@@ -1615,6 +2819,8 @@ mod testing {
         summary.push_str(&format!("\t"));
         substance::Normal::print_doc(&dir, &mut summary).map_err(|e| e.to_string())?;
         summary.push_str(&format!("\t"));
+        substance::normal::PhaseChange::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
+        summary.push_str(&format!("\t"));
         substance::Gas::print_doc(&dir, &mut summary).map_err(|e| e.to_string())?;
         summary.push_str(&format!("\t"));
         substance::gas::GasSpecification::print_doc(dir, &mut summary)
@@ -1625,6 +2831,7 @@ mod testing {
         Surface::print_doc(&dir, &mut summary).map_err(|e| e.to_string())?;
         Surface::print_api_doc(&dir, &mut summary).map_err(|e| e.to_string())?;
         crate::surface::SurfaceType::print_doc(&dir, &mut summary).map_err(|e| e.to_string())?;
+        crate::SwitchableGlazing::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
 
         /*****/
         /* T */
@@ -1632,6 +2839,12 @@ mod testing {
         TableShape::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
         TableType::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
         TerrainClass::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
+        crate::ThermalBridge::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
+        ThermalOptions::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
+        Thermostat::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
+        Thermostat::print_api_doc(dir, &mut summary).map_err(|e| e.to_string())?;
+        crate::ThermostatControlType::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
+        TimestepOptions::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
 
         /*****/
         /* U */
@@ -1644,6 +2857,9 @@ mod testing {
         /*****/
         /* W */
         /*****/
+        WaterTank::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
+        WaterTank::print_api_doc(dir, &mut summary).map_err(|e| e.to_string())?;
+        crate::WindowFrame::print_doc(dir, &mut summary).map_err(|e| e.to_string())?;
 
         /*****/
         /* X */
@@ -2015,6 +3231,136 @@ mod testing {
         Ok(())
     }
 
+    #[test]
+    fn test_api_co2_window() -> Result<(), String> {
+        let (model, mut header) = Model::from_file("./tests/box_with_window.spl")?;
+        let space = model.get_space("ZONE ONE")?;
+        let i = header.push(SimulationStateElement::SpaceCO2Concentration(0), 1200.)?;
+        space.set_co2_concentration_index(i)?;
+        let window = model.get_fenestration("Zn001:Wall001:Win001")?;
+
+        let state = header.take_values().ok_or("Could not get values")?;
+        let state = Arc::new(Mutex::new(state));
+        let model = Arc::new(model);
+        let mut engine = rhai::Engine::new();
+        register_control_api(&mut engine, &model, &state, false);
+
+        // Demand-controlled ventilation
+        let ast = engine
+            .compile(
+                "
+            let co2 = space(\"ZONE ONE\").co2_concentration;
+            let window = fenestration(\"Zn001:Wall001:Win001\");
+            if co2 > 1000.0 {
+                window.open_fraction = 1.0;
+            } else {
+                window.open_fraction = 0.0;
+            }
+        ",
+            )
+            .map_err(|e| e.to_string())?;
+
+        let _result: () = engine.eval_ast(&ast).map_err(|e| e.to_string())?;
+        let open = window
+            .open_fraction(&state.lock().unwrap())
+            .ok_or("No open fraction")?;
+        assert!((open - 1.).abs() < 1e-9);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_api_window_position() -> Result<(), String> {
+        let mut model = Model::default();
+        Construction::from_library("single-glazing", &mut model)?;
+        let window: Fenestration = json5::from_str(
+            "{
+            name: 'Window',
+            construction: 'single-glazing',
+            operation: {
+                type: 'Discrete',
+                positions: [
+                    { name: 'closed', open_fraction: 0 },
+                    { name: 'half', open_fraction: 0.5 },
+                ],
+            },
+            vertices: [0, 0, 0, 1, 0, 0, 1, 0, 1, 0, 0, 1],
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        let window = model.add_fenestration(window)?;
+        let state = model
+            .take_state()
+            .ok_or("No state")?
+            .take_values()
+            .ok_or("Could not get values")?;
+        let state = Arc::new(Mutex::new(state));
+        let model = Arc::new(model);
+        let mut engine = rhai::Engine::new();
+        register_control_api(&mut engine, &model, &state, false);
+
+        let position: String = engine
+            .eval("fenestration(\"Window\").position")
+            .map_err(|e| e.to_string())?;
+        assert_eq!(position, "closed");
+
+        engine
+            .run("let w = fenestration(\"Window\"); w.position = \"half\";")
+            .map_err(|e| e.to_string())?;
+        let open = window
+            .open_fraction(&state.lock().unwrap())
+            .ok_or("No open fraction")?;
+        assert!((open - 0.5).abs() < 1e-9);
+
+        // Unknown positions are errors
+        assert!(engine
+            .run("let w = fenestration(\"Window\"); w.position = \"ajar\";")
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_schedules() -> Result<(), String> {
+        let mut model = Model::default();
+
+        model.add_schedule(Schedule::new(
+            "Half",
+            ScheduleProfile::Constant { value: 0.5 },
+        ));
+        model.add_schedule(Schedule::new(
+            "Off",
+            ScheduleProfile::Constant { value: 0.0 },
+        ));
+
+        let mut lum = Luminaire::new("the light");
+        lum.set_max_power(100.).set_schedule("Half");
+        model.add_luminaire(lum)?;
+
+        let mut heater = ElectricHeater::new("the heater");
+        heater.set_availability_schedule("Off");
+        model.add_hvac(heater.wrap())?;
+
+        let mut state = model
+            .take_state()
+            .ok_or("Could not take state")?
+            .take_values()
+            .ok_or("Could not get values")?;
+        state[1] = 1500.;
+
+        let date = Date {
+            month: 1,
+            day: 1,
+            hour: 12.,
+            year: None,
+        };
+        model.apply_schedules(date, &mut state)?;
+        assert!((state[0] - 50.).abs() < 1e-5);
+        assert!(state[1].abs() < 1e-5);
+
+        Ok(())
+    }
+
     #[test]
     fn test_get_space_sizes() -> Result<(), String> {
         let (model, _) = Model::from_file("./tests/cold_wellington_apartment.spl")?;
@@ -2040,4 +3386,58 @@ mod testing {
 
         Ok(())
     }
+
+    #[test]
+    fn test_space_multiplier() -> Result<(), String> {
+        let mut model = Model::default();
+        let mut space = Space::new("Apartment");
+        space.set_multiplier(10);
+        model.add_space(space);
+
+        // A multiplier of 0 makes no sense
+        let mut space = Space::new("Nothing");
+        space.set_multiplier(0);
+        let space = model.add_space(space);
+        assert_eq!(*space.multiplier()?, 1);
+
+        let mut lights = Luminaire::new("Lights");
+        lights.set_target_space("Apartment");
+        model.add_luminaire(lights)?;
+        model.add_luminaire(Luminaire::new("Streetlight"))?;
+
+        let mut heat_pump = HeatPump::new("Heat pump");
+        heat_pump.set_target_space("Apartment");
+        model.add_hvac(heat_pump.wrap())?;
+        let mut tank = WaterTank::new("Tank", 0.2);
+        tank.set_space("Apartment");
+        model.add_water_tank(tank)?;
+
+        assert_eq!(model.space_multiplier("Apartment"), 10.);
+        assert_eq!(model.space_multiplier("Nowhere"), 1.);
+        assert_eq!(
+            model.state_element_multiplier(&SimulationStateElement::LuminairePowerConsumption(0)),
+            10.
+        );
+        assert_eq!(
+            model.state_element_multiplier(&SimulationStateElement::LuminairePowerConsumption(1)),
+            1.
+        );
+        assert_eq!(
+            model.state_element_multiplier(&SimulationStateElement::SpaceDryBulbTemperature(0)),
+            1.
+        );
+        assert_eq!(
+            model.state_element_multiplier(
+                &SimulationStateElement::HeatPumpElectricityConsumption(0)
+            ),
+            10.
+        );
+        assert_eq!(
+            model.state_element_multiplier(
+                &SimulationStateElement::WaterTankHeaterPowerConsumption(0)
+            ),
+            10.
+        );
+        Ok(())
+    }
 }
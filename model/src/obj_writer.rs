@@ -0,0 +1,190 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::{FenestrationType, Model};
+use geometry::{Polygon3D, Triangulation3D};
+use std::fmt::Write;
+
+/// The colours assigned to the constructions, in the order in which
+/// they are first found.
+const PALETTE: [[f32; 3]; 8] = [
+    [0.8, 0.8, 0.8],
+    [0.85, 0.65, 0.5],
+    [0.6, 0.75, 0.55],
+    [0.55, 0.65, 0.85],
+    [0.85, 0.8, 0.5],
+    [0.7, 0.55, 0.75],
+    [0.5, 0.75, 0.75],
+    [0.9, 0.55, 0.55],
+];
+
+/// The opacity (i.e., `d`) of the materials used by windows and doors
+const FENESTRATION_OPACITY: f32 = 0.4;
+
+/// OBJ and MTL names cannot contain whitespaces
+fn obj_name(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<&str>>().join("_")
+}
+
+/// Registers a construction used by an element, marking it as
+/// see-through if it is used by a window or a door
+fn register<'a>(constructions: &mut Vec<(&'a str, bool)>, name: &'a str, transparent: bool) {
+    match constructions.iter_mut().find(|(c, _)| *c == name) {
+        Some(c) => c.1 |= transparent,
+        None => constructions.push((name, transparent)),
+    }
+}
+
+/// Writes a named group with the triangles of a [`Polygon3D`], returning the
+/// number of vertices written (so the following groups can refer to theirs)
+fn write_group(
+    out: &mut String,
+    name: &str,
+    material: &str,
+    polygon: &Polygon3D,
+    first_vertex: usize,
+) -> Result<usize, String> {
+    let t: Triangulation3D = polygon
+        .try_into()
+        .map_err(|e| format!("Could not triangulate '{}': {}", name, e))?;
+    let triangles = t.get_trilist();
+
+    writeln!(out, "g {}", obj_name(name)).map_err(|e| e.to_string())?;
+    writeln!(out, "usemtl {}", obj_name(material)).map_err(|e| e.to_string())?;
+    for tri in triangles.iter() {
+        for v in [tri.a(), tri.b(), tri.c()] {
+            writeln!(out, "v {} {} {}", v.x, v.y, v.z).map_err(|e| e.to_string())?;
+        }
+    }
+    for i in 0..triangles.len() {
+        let a = first_vertex + 3 * i;
+        writeln!(out, "f {} {} {}", a, a + 1, a + 2).map_err(|e| e.to_string())?;
+    }
+    writeln!(out).map_err(|e| e.to_string())?;
+    Ok(3 * triangles.len())
+}
+
+/// Writes the [`Surface`](crate::Surface)s and [`Fenestration`](crate::Fenestration)s
+/// of a [`Model`] in Wavefront's OBJ format, returning the content of the OBJ file and
+/// that of its companion MTL file (which is referenced as `mtllib`).
+///
+/// Each element is written as a group of triangles named after it (whitespaces
+/// are replaced by underscores), using a material named after its construction.
+/// Each construction gets a different colour, and those used by fenestrations
+/// (other than openings) are semi-transparent.
+pub(crate) fn write_obj(model: &Model, mtllib: &str) -> Result<(String, String), String> {
+    let mut out = String::new();
+    let name = match &model.name {
+        Some(n) => n.as_str(),
+        None => "unnamed",
+    };
+    writeln!(out, "# Model '{}' exported by SIMPLE", name).map_err(|e| e.to_string())?;
+    writeln!(out, "mtllib {}\n", mtllib).map_err(|e| e.to_string())?;
+
+    // Constructions, in order of appearance, and whether they are see-through
+    let mut constructions: Vec<(&str, bool)> = Vec::new();
+
+    let mut n_vertices = 0;
+    for s in model.surfaces.iter() {
+        register(&mut constructions, &s.construction, false);
+        n_vertices += write_group(
+            &mut out,
+            &s.name,
+            &s.construction,
+            &s.vertices,
+            n_vertices + 1,
+        )?;
+    }
+    for fen in model.fenestrations.iter() {
+        let transparent = !matches!(fen.category, FenestrationType::Opening);
+        register(&mut constructions, &fen.construction, transparent);
+        n_vertices += write_group(
+            &mut out,
+            &fen.name,
+            &fen.construction,
+            &fen.vertices,
+            n_vertices + 1,
+        )?;
+    }
+
+    let mut mtl = String::new();
+    writeln!(mtl, "# Materials of model '{}' exported by SIMPLE\n", name)
+        .map_err(|e| e.to_string())?;
+    for (i, (construction, transparent)) in constructions.iter().enumerate() {
+        let [r, g, b] = PALETTE[i % PALETTE.len()];
+        writeln!(mtl, "newmtl {}", obj_name(construction)).map_err(|e| e.to_string())?;
+        writeln!(mtl, "Kd {} {} {}", r, g, b).map_err(|e| e.to_string())?;
+        if *transparent {
+            writeln!(mtl, "d {}", FENESTRATION_OPACITY).map_err(|e| e.to_string())?;
+        }
+        writeln!(mtl).map_err(|e| e.to_string())?;
+    }
+
+    Ok((out, mtl))
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn test_obj_name() {
+        assert_eq!(obj_name("Some  wall"), "Some_wall");
+        assert_eq!(obj_name("Window"), "Window");
+    }
+
+    #[test]
+    fn test_box_with_window() -> Result<(), String> {
+        let (model, ..) = Model::from_file("./tests/box_with_window.spl")?;
+        let (obj, mtl) = write_obj(&model, "box.mtl")?;
+
+        assert!(obj.contains("mtllib box.mtl\n"));
+        let groups: Vec<&str> = obj.lines().filter(|l| l.starts_with("g ")).collect();
+        assert_eq!(
+            groups.len(),
+            model.surfaces.len() + model.fenestrations.len()
+        );
+        assert_eq!(
+            groups[0],
+            format!("g {}", obj_name(&model.surfaces[0].name))
+        );
+
+        // All faces are triangles referring to existing vertices
+        let n_vertices = obj.lines().filter(|l| l.starts_with("v ")).count();
+        for face in obj.lines().filter(|l| l.starts_with("f ")) {
+            let indices: Vec<usize> = face
+                .split_whitespace()
+                .skip(1)
+                .map(|i| i.parse().unwrap())
+                .collect();
+            assert_eq!(indices.len(), 3);
+            assert!(indices.iter().all(|i| *i >= 1 && *i <= n_vertices));
+        }
+
+        // Every material used is defined
+        for usemtl in obj.lines().filter(|l| l.starts_with("usemtl ")) {
+            let name = usemtl.trim_start_matches("usemtl ");
+            assert!(mtl.contains(&format!("newmtl {}\n", name)));
+        }
+        // Windows are see-through
+        assert!(mtl.contains(&format!("d {}", FENESTRATION_OPACITY)));
+        Ok(())
+    }
+}
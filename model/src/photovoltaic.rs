@@ -0,0 +1,319 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use crate::model::Model;
+use crate::simulation_state_element::StateElementField;
+use crate::{Float, SurfaceTrait};
+use derive::{ObjectAPI, ObjectIO};
+use geometry::Polygon3D;
+use serde::{Deserialize, Serialize};
+
+/// A Photovoltaic panel that transforms the solar radiation
+/// it receives into electricity.
+///
+/// Panels can either be installed on the outdoor side of a `Surface` (e.g.,
+/// a roof), or be described by their own `vertices` (e.g., a panel on a
+/// rack, or in the garden). Exactly one of these must be given. Panels
+/// described by their own `vertices` are not part of the scene used for
+/// ray-tracing, so they do not cast shadows (use a `ShadingSurface`
+/// for that).
+///
+/// The generated power (in W) is calculated as follows:
+///
+/// ```math
+/// P = G \times A \times f_{area} \times \eta_{rated} \times f(G/1000) \times \eta_{inverter}
+/// ```
+///
+/// where $`G`$ is the incident solar irradiance (in W/m2), $`A`$ is the area of the
+/// panel or `Surface`, and $`f`$ is the `efficiency_curve`.
+///
+/// ## Examples
+///
+/// #### `.spl`
+///
+/// ```json
+/// {{#include ../../../model/tests/scanner/photovoltaic.spl}}
+/// ```
+///
+/// #### `.json`
+///
+/// ```json
+/// {{#include ../../../model/tests/scanner/photovoltaic.json}}
+/// ```
+#[derive(Debug, ObjectIO, ObjectAPI, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Photovoltaic {
+    /// The name of the Photovoltaic panel
+    pub name: String,
+
+    /// The name of the `Surface` on which the panel is installed. The
+    /// panel will receive the solar radiation incident on the
+    /// outdoor side of this surface.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    surface: Option<String>,
+
+    /// An array of Numbers representing the vertices of the
+    /// panel, if it is not installed on a `Surface`. The length of this
+    /// array must be divisible by 3. The panel faces the direction
+    /// of its normal.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vertices: Option<Polygon3D>,
+
+    /// The fraction of the area of the `surface` or `vertices`
+    /// that is covered by photovoltaic cells (from 0 to 1). Defaults to 1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    area_fraction: Option<Float>,
+
+    /// The efficiency of the panel at rated conditions
+    /// (i.e., 1000 W/m2). Defaults to 0.17
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rated_efficiency: Option<Float>,
+
+    /// The coefficients of the polynomial of the incident solar irradiance
+    /// relative to the rated conditions (i.e., $`G/1000`$) that multiplies the
+    /// `rated_efficiency`, starting from the constant term. If none is given,
+    /// the efficiency is constant.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    efficiency_curve: Option<Vec<Float>>,
+
+    /// The efficiency of the inverter transforming the direct current
+    /// produced by the panel into alternating current. Defaults to 0.96
+    #[serde(skip_serializing_if = "Option::is_none")]
+    inverter_efficiency: Option<Float>,
+
+    /// The solar irradiance incident on the panel, in W/m2
+    #[physical]
+    #[serde(skip)]
+    incident_solar_irradiance: StateElementField,
+
+    /// The electricity generated by the panel (after the inverter), in W
+    #[physical]
+    #[serde(skip)]
+    generated_power: StateElementField,
+}
+
+/// Checks that a panel (i.e., a [`Photovoltaic`] or a
+/// [`SolarCollector`](crate::SolarCollector)) is either installed on a
+/// `Surface` or described by its own `vertices`, but not both.
+pub(crate) fn validate_panel(
+    kind: &str,
+    name: &str,
+    surface: Result<&String, String>,
+    vertices: Result<&Polygon3D, String>,
+) -> Result<(), String> {
+    match (surface, vertices) {
+        (Ok(_), Ok(_)) => Err(format!(
+            "{} '{}' has both a 'surface' and 'vertices'... only one of them can be given",
+            kind, name
+        )),
+        (Err(_), Err(_)) => Err(format!(
+            "{} '{}' needs either a 'surface' or 'vertices'",
+            kind, name
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Calculates the area of a panel (i.e., a [`Photovoltaic`] or a
+/// [`SolarCollector`](crate::SolarCollector)), in m2. This is the area of
+/// its `surface` or `vertices` multiplied by its `area_fraction`.
+pub(crate) fn panel_area(
+    kind: &str,
+    name: &str,
+    model: &Model,
+    surface: Result<&String, String>,
+    vertices: Result<&Polygon3D, String>,
+    area_fraction: Float,
+) -> Result<Float, String> {
+    let gross_area = match (surface, vertices) {
+        (Ok(surface), _) => model.get_surface(surface)?.area(),
+        (Err(_), Ok(vertices)) => vertices.area(),
+        (Err(e), Err(_)) => return Err(e),
+    };
+    if !(0. ..=1.).contains(&area_fraction) {
+        return Err(format!(
+            "The 'area_fraction' of {} '{}' must be between 0 and 1... found {}",
+            kind, name, area_fraction
+        ));
+    }
+    Ok(gross_area * area_fraction)
+}
+
+impl Photovoltaic {
+    /// Checks that the panel is either installed on a `Surface` or
+    /// described by its own `vertices`, but not both.
+    pub fn validate(&self) -> Result<(), String> {
+        validate_panel("Photovoltaic", &self.name, self.surface(), self.vertices())
+    }
+
+    /// Calculates the area covered by photovoltaic cells, in m2
+    pub fn area(&self, model: &Model) -> Result<Float, String> {
+        panel_area(
+            "Photovoltaic",
+            &self.name,
+            model,
+            self.surface(),
+            self.vertices(),
+            self.area_fraction_or("", 1.),
+        )
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use crate::{Construction, Surface};
+    use geometry::{Loop3D, Point3D};
+
+    fn square(z: Float) -> Result<Polygon3D, String> {
+        let mut the_loop = Loop3D::new();
+        the_loop.push(Point3D::new(0., 0., z))?;
+        the_loop.push(Point3D::new(2., 0., z))?;
+        the_loop.push(Point3D::new(2., 2., z))?;
+        the_loop.push(Point3D::new(0., 2., z))?;
+        the_loop.close()?;
+        Polygon3D::new(the_loop)
+    }
+
+    #[test]
+    fn serde() -> Result<(), String> {
+        use json5;
+        use std::fs;
+
+        // Hardcode a reference
+        let mut hardcoded_ref = Photovoltaic::new("Roof Panels");
+        hardcoded_ref
+            .set_surface("Roof")
+            .set_area_fraction(0.8)
+            .set_rated_efficiency(0.2)
+            .set_efficiency_curve(vec![0.9, 0.1])
+            .set_inverter_efficiency(0.95);
+
+        // Deserialize from hardcoded string and check they are the same
+        let from_hardcoded_json: Photovoltaic = json5::from_str(
+            "{
+            name: 'Roof Panels',
+            surface: 'Roof',
+            area_fraction: 0.8,
+            rated_efficiency: 0.2,
+            efficiency_curve: [0.9, 0.1],
+            inverter_efficiency: 0.95
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_hardcoded_json)
+        );
+
+        // Read json file (used in DOC), Deserialize, and compare
+        let filename = "./tests/scanner/photovoltaic";
+        let json_file = format!("{}.json", filename);
+        let json_data = fs::read_to_string(json_file).map_err(|e| e.to_string())?;
+        let from_json_file: Photovoltaic =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_json_file)
+        );
+
+        // Serialize and deserialize again... check that everythin matches the pattern
+        let rust_json = serde_json::to_string(&hardcoded_ref).map_err(|e| e.to_string())?;
+        let from_serialized: Photovoltaic =
+            serde_json::from_str(&rust_json).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_serialized)
+        );
+
+        // check simple
+        let (model, ..) = Model::from_file(format!("{}.spl", filename))?;
+        assert_eq!(model.photovoltaics.len(), 1);
+        assert_eq!(model.photovoltaics[0].name(), "Roof Panels");
+
+        // Standalone geometry
+        let standalone: Photovoltaic = json5::from_str(
+            "{
+            name: 'Garden Panel',
+            vertices: [
+                0, 0, 0,
+                2, 0, 0,
+                2, 2, 0,
+                0, 2, 0
+            ]
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert!(standalone.validate().is_ok());
+        assert!(standalone.surface().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate() -> Result<(), String> {
+        let mut pv = Photovoltaic::new("Panel");
+        assert!(pv.validate().is_err());
+
+        pv.set_surface("Roof");
+        assert!(pv.validate().is_ok());
+
+        pv.set_vertices(square(0.)?);
+        assert!(pv.validate().is_err());
+
+        let mut model = Model::default();
+        assert!(model.add_photovoltaic(pv).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_area() -> Result<(), String> {
+        let mut model = Model::default();
+        model.add_construction(Construction::new("the construction"));
+        let roof = Surface::new(
+            "Roof",
+            square(3.)?,
+            "the construction",
+            crate::Boundary::Outdoor,
+            crate::Boundary::Outdoor,
+        );
+        model.add_surface(roof)?;
+
+        // On a surface
+        let mut pv = Photovoltaic::new("Roof Panels");
+        pv.set_surface("Roof").set_area_fraction(0.5);
+        assert!((pv.area(&model)? - 2.).abs() < 1e-9);
+
+        // Missing surface
+        let mut pv = Photovoltaic::new("Wall Panels");
+        pv.set_surface("Wall");
+        assert!(pv.area(&model).is_err());
+
+        // Standalone
+        let mut pv = Photovoltaic::new("Garden Panel");
+        pv.set_vertices(square(0.)?);
+        assert!((pv.area(&model)? - 4.).abs() < 1e-9);
+
+        // Wrong fraction
+        pv.set_area_fraction(1.2);
+        assert!(pv.area(&model).is_err());
+
+        Ok(())
+    }
+}
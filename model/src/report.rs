@@ -0,0 +1,160 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use crate::Output;
+use derive::ObjectIO;
+use serde::{Deserialize, Serialize};
+
+/// The time intervals over which a [`Report`] aggregates its [`Output`]
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, ObjectIO, Serialize, Deserialize)]
+#[inline_enum]
+pub enum ReportFrequency {
+    /// Every timestep of the simulation (i.e., no aggregation). This is the default.
+    #[default]
+    Timestep,
+    /// Every hour
+    Hourly,
+    /// Every day
+    Daily,
+    /// Every month
+    Monthly,
+    /// Once, for the whole simulation period
+    RunPeriod,
+}
+
+/// How the values of an [`Output`] are aggregated over the time
+/// intervals of a [`Report`]
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, ObjectIO, Serialize, Deserialize)]
+#[inline_enum]
+pub enum ReportAggregation {
+    /// The average of the values. This is the default.
+    #[default]
+    Average,
+    /// The sum of the values multiplied by the duration of the
+    /// timestep, in hours (e.g., it transforms a power in $W$ into
+    /// an energy in $Wh$)
+    Sum,
+    /// The minimum value
+    Minimum,
+    /// The maximum value
+    Maximum,
+}
+
+/// A request for reporting an [`Output`] of the simulation, aggregated over
+/// time (e.g., the hourly average of the temperature of a `Space`, or the
+/// monthly sum of the energy consumed by an `HVAC`).
+///
+/// When a model contains `Report`s, the results of the simulation contain
+/// one row per report and time interval—with the date at which the interval
+/// starts, the name of the report and its value—instead of one column per
+/// [`Output`].
+///
+/// ## Examples
+///
+/// #### `.spl`
+/// ```json
+/// {{#include ../../../model/tests/scanner/report.spl}}
+/// ```
+/// #### `.json`
+/// ```json
+/// {{#include ../../../model/tests/scanner/report.json}}
+/// ```
+#[derive(Debug, Clone, ObjectIO, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Report {
+    /// The output to report
+    pub output: Output,
+
+    /// The time intervals over which the output is aggregated.
+    /// Defaults to `Timestep`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    frequency: Option<ReportFrequency>,
+
+    /// How the output is aggregated. Defaults to `Average`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    aggregation: Option<ReportAggregation>,
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use crate::Model;
+
+    #[test]
+    fn serde() -> Result<(), String> {
+        use json5;
+        use std::fs;
+
+        // Hardcode a reference
+        let mut hardcoded_ref =
+            Report::new(Output::HeatingCoolingPowerConsumption("Heater".into()));
+        hardcoded_ref
+            .set_frequency(ReportFrequency::Monthly)
+            .set_aggregation(ReportAggregation::Sum);
+
+        // Deserialize from hardcoded string and check they are the same
+        let from_hardcoded_json: Report = json5::from_str(
+            "{
+            output: { HeatingCoolingPowerConsumption: 'Heater' },
+            frequency: 'Monthly',
+            aggregation: 'Sum'
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_hardcoded_json)
+        );
+
+        // Read json file (used in DOC), Deserialize, and compare
+        let filename = "./tests/scanner/report";
+        let json_file = format!("{}.json", filename);
+        let json_data = fs::read_to_string(json_file).map_err(|e| e.to_string())?;
+        let from_json_file: Report = serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_json_file)
+        );
+
+        // Serialize and deserialize again... check that everythin matches the pattern
+        let rust_json = serde_json::to_string(&hardcoded_ref).map_err(|e| e.to_string())?;
+        let from_serialized: Report =
+            serde_json::from_str(&rust_json).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_serialized)
+        );
+
+        // check simple
+        let (model, ..) = Model::from_file(format!("{}.spl", filename))?;
+        assert_eq!(model.reports.len(), 1);
+        assert_eq!(*model.reports[0].frequency()?, ReportFrequency::Monthly);
+
+        // Defaults
+        let report: Report = json5::from_str("{ output: { SpaceDryBulbTemperature: 'Kitchen' } }")
+            .map_err(|e| e.to_string())?;
+        assert!(report.frequency().is_err());
+        assert!(report.aggregation().is_err());
+        Ok(())
+    }
+}
@@ -19,10 +19,16 @@ SOFTWARE.
 */
 
 use crate::{
-    hvac::{ElectricHeater, IdealHeaterCooler, HVAC},
-    Fenestration, Luminaire, Model, SimulationState, Space, Surface,
+    hvac::{
+        ElectricHeater, HeatPump, HeatRecoveryVentilator, IdealHeaterCooler, RadiantSurfaceSystem,
+        HVAC,
+    },
+    ElectricEquipment, Fenestration, Luminaire, Model, Photovoltaic, SensorGrid, SimulationState,
+    SolarCollector, Space, Surface, Thermostat, WaterTank,
 };
+use calendar::Date;
 use std::sync::Mutex;
+use weather::CurrentWeather;
 
 use std::sync::Arc;
 
@@ -37,8 +43,280 @@ pub fn register_control_api(
     Surface::register_api(engine, model, state, research_mode);
     Fenestration::register_api(engine, model, state, research_mode);
     Luminaire::register_api(engine, model, state, research_mode);
+    ElectricEquipment::register_api(engine, model, state, research_mode);
+    Thermostat::register_api(engine, model, state, research_mode);
+    Photovoltaic::register_api(engine, model, state, research_mode);
+    SolarCollector::register_api(engine, model, state, research_mode);
+    WaterTank::register_api(engine, model, state, research_mode);
+    SensorGrid::register_api(engine, model, state, research_mode);
+    register_fenestration_positions(engine, state);
 
     HVAC::register_api(engine, model, state, research_mode);
     ElectricHeater::register_api(engine, model, state, research_mode);
     IdealHeaterCooler::register_api(engine, model, state, research_mode);
+    HeatPump::register_api(engine, model, state, research_mode);
+    RadiantSurfaceSystem::register_api(engine, model, state, research_mode);
+    HeatRecoveryVentilator::register_api(engine, model, state, research_mode);
+    register_hvac_modulation(engine, state, HVAC::ElectricHeater);
+    register_hvac_modulation(engine, state, HVAC::IdealHeaterCooler);
+    register_hvac_modulation(engine, state, HVAC::HeatPump);
+    register_hvac_modulation(engine, state, HVAC::RadiantSurfaceSystem);
+}
+
+/// Registers the `position` property of the [`Fenestration`]s, which allows
+/// reading and changing the current
+/// [`OpeningPosition`](crate::OpeningPosition) by name
+fn register_fenestration_positions(engine: &mut rhai::Engine, state: &Arc<Mutex<SimulationState>>) {
+    let new_state = Arc::clone(state);
+    engine.register_get(
+        "position",
+        move |this: &mut Arc<Fenestration>| -> Result<String, Box<rhai::EvalAltResult>> {
+            let state_ptr = &*new_state.lock().unwrap();
+            Ok(this.current_position(state_ptr)?.to_string())
+        },
+    );
+
+    let new_state = Arc::clone(state);
+    engine.register_set(
+        "position",
+        move |this: &mut Arc<Fenestration>,
+              name: rhai::ImmutableString|
+              -> Result<_, Box<rhai::EvalAltResult>> {
+            let state_ptr = &mut *new_state.lock().unwrap();
+            this.set_position(state_ptr, &name)?;
+            Ok(())
+        },
+    );
+}
+
+/// Registers the `part_load_fraction` and `is_on` properties of the heating
+/// and cooling systems wrapped by `wrap` (e.g., `HVAC::HeatPump`), which
+/// allow control scripts to modulate them directly. See
+/// [`HVAC::set_part_load_fraction`] and [`HVAC::set_on`].
+fn register_hvac_modulation<T: 'static>(
+    engine: &mut rhai::Engine,
+    state: &Arc<Mutex<SimulationState>>,
+    wrap: fn(Arc<T>) -> HVAC,
+) {
+    let new_state = Arc::clone(state);
+    engine.register_get(
+        "part_load_fraction",
+        move |this: &mut Arc<T>| -> Result<crate::Float, Box<rhai::EvalAltResult>> {
+            let state_ptr = &*new_state.lock().unwrap();
+            Ok(wrap(Arc::clone(this)).part_load_fraction(state_ptr)?)
+        },
+    );
+
+    let new_state = Arc::clone(state);
+    engine.register_set(
+        "part_load_fraction",
+        move |this: &mut Arc<T>, v: crate::Float| -> Result<_, Box<rhai::EvalAltResult>> {
+            let state_ptr = &mut *new_state.lock().unwrap();
+            wrap(Arc::clone(this)).set_part_load_fraction(state_ptr, v)?;
+            Ok(())
+        },
+    );
+
+    let new_state = Arc::clone(state);
+    engine.register_set(
+        "part_load_fraction",
+        move |this: &mut Arc<T>, v: rhai::INT| -> Result<_, Box<rhai::EvalAltResult>> {
+            let state_ptr = &mut *new_state.lock().unwrap();
+            wrap(Arc::clone(this)).set_part_load_fraction(state_ptr, v as crate::Float)?;
+            Ok(())
+        },
+    );
+
+    let new_state = Arc::clone(state);
+    engine.register_get(
+        "is_on",
+        move |this: &mut Arc<T>| -> Result<bool, Box<rhai::EvalAltResult>> {
+            let state_ptr = &*new_state.lock().unwrap();
+            Ok(wrap(Arc::clone(this)).is_on(state_ptr)?)
+        },
+    );
+
+    let new_state = Arc::clone(state);
+    engine.register_set(
+        "is_on",
+        move |this: &mut Arc<T>, on: bool| -> Result<_, Box<rhai::EvalAltResult>> {
+            let state_ptr = &mut *new_state.lock().unwrap();
+            wrap(Arc::clone(this)).set_on(state_ptr, on)?;
+            Ok(())
+        },
+    );
+}
+
+/// Registers the `date()` and `weather()` functions, which allow control
+/// scripts to read the current [`Date`] and [`CurrentWeather`] (e.g., for
+/// implementing weather-compensated or predictive control). The `weather`
+/// is meant to be updated by the simulation before every timestep.
+///
+/// ```
+/// use model::rhai_api::register_environment_api;
+/// use std::sync::{Arc, Mutex};
+/// use weather::CurrentWeather;
+///
+/// let weather = Arc::new(Mutex::new(CurrentWeather {
+///     dry_bulb_temperature: -2.,
+///     ..CurrentWeather::default()
+/// }));
+/// let mut engine = rhai::Engine::new();
+/// register_environment_api(&mut engine, &weather);
+///
+/// let cold: bool = engine.eval("weather().dry_bulb_temperature < 0.0").unwrap();
+/// assert!(cold);
+/// ```
+///
+/// The following properties are available:
+///
+/// | Object | Property | Description |
+/// |--------|----------|-------------|
+/// | `date()` | `month` | From 1 to 12 |
+/// | `date()` | `day` | From 1 to 31 |
+/// | `date()` | `hour` | From 0 to 24 |
+/// | `date()` | `day_of_year` | From 0 to 365, including the fraction of the day |
+/// | `date()` | `weekday` | `"Monday"` to `"Sunday"` |
+/// | `date()` | `day_type` | `"Weekday"` or `"Weekend"` |
+/// | `weather()` | `dry_bulb_temperature` | In C |
+/// | `weather()` | `dew_point_temperature` | In C |
+/// | `weather()` | `relative_humidity` | From 0 to 1 |
+/// | `weather()` | `pressure` | In Pa |
+/// | `weather()` | `global_horizontal_radiation` | In W/m2 |
+/// | `weather()` | `direct_normal_radiation` | In W/m2 |
+/// | `weather()` | `diffuse_horizontal_radiation` | In W/m2 |
+/// | `weather()` | `wind_speed` | In m/s |
+/// | `weather()` | `wind_direction` | In degrees, North is 0 and East is 90 |
+/// | `weather()` | `opaque_sky_cover` | From 0 to 1 |
+pub fn register_environment_api(engine: &mut rhai::Engine, weather: &Arc<Mutex<CurrentWeather>>) {
+    engine
+        .register_type_with_name::<Date>("Date")
+        .register_get("month", |d: &mut Date| d.month as rhai::INT)
+        .register_get("day", |d: &mut Date| d.day as rhai::INT)
+        .register_get("hour", |d: &mut Date| d.hour)
+        .register_get("day_of_year", |d: &mut Date| d.day_of_year())
+        .register_get("weekday", |d: &mut Date| format!("{:?}", d.weekday()))
+        .register_get("day_type", |d: &mut Date| format!("{:?}", d.day_type(&[])));
+
+    engine
+        .register_type_with_name::<CurrentWeather>("Weather")
+        .register_get("dry_bulb_temperature", |w: &mut CurrentWeather| {
+            w.dry_bulb_temperature
+        })
+        .register_get("dew_point_temperature", |w: &mut CurrentWeather| {
+            w.dew_point_temperature
+        })
+        .register_get("relative_humidity", |w: &mut CurrentWeather| {
+            w.relative_humidity
+        })
+        .register_get("pressure", |w: &mut CurrentWeather| w.pressure)
+        .register_get("global_horizontal_radiation", |w: &mut CurrentWeather| {
+            w.global_horizontal_radiation
+        })
+        .register_get("direct_normal_radiation", |w: &mut CurrentWeather| {
+            w.direct_normal_radiation
+        })
+        .register_get("diffuse_horizontal_radiation", |w: &mut CurrentWeather| {
+            w.diffuse_horizontal_radiation
+        })
+        .register_get("wind_speed", |w: &mut CurrentWeather| w.wind_speed)
+        .register_get("wind_direction", |w: &mut CurrentWeather| {
+            w.wind_direction.to_degrees()
+        })
+        .register_get("opaque_sky_cover", |w: &mut CurrentWeather| {
+            w.opaque_sky_cover
+        });
+
+    let new_weather = Arc::clone(weather);
+    engine.register_fn("date", move || new_weather.lock().unwrap().date);
+
+    let new_weather = Arc::clone(weather);
+    engine.register_fn("weather", move || *new_weather.lock().unwrap());
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn test_environment_api() -> Result<(), String> {
+        let weather = Arc::new(Mutex::new(CurrentWeather::default()));
+        let mut engine = rhai::Engine::new();
+        register_environment_api(&mut engine, &weather);
+        let ast = engine
+            .compile(
+                "
+            let d = date();
+            let w = weather();
+            `${d.month}/${d.day} ${d.hour} ${d.weekday} ${d.day_type} ${w.dry_bulb_temperature} ${w.wind_direction.round()}`
+        ",
+            )
+            .map_err(|e| e.to_string())?;
+
+        // Changes in the weather are seen by the script
+        for (day, expected) in [
+            (5, "1/5 14.5 Friday Weekday 12.5 90.0"),
+            (6, "1/6 14.5 Saturday Weekend 12.5 90.0"),
+        ] {
+            {
+                let mut w = weather.lock().map_err(|e| e.to_string())?;
+                w.date = Date {
+                    month: 1,
+                    day,
+                    hour: 14.5,
+                    year: None,
+                };
+                w.dry_bulb_temperature = 12.5;
+                w.wind_direction = (90. as crate::Float).to_radians();
+            }
+            let found: String = engine.eval_ast(&ast).map_err(|e| e.to_string())?;
+            assert_eq!(found, expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_hvac_modulation() -> Result<(), String> {
+        let mut model = Model::default();
+        let mut hvac = IdealHeaterCooler::new("Heater");
+        hvac.set_target_space("Bedroom")
+            .set_heating_setpoint(20.)
+            .set_cooling_setpoint(26.)
+            .set_max_heating_power(1000.)
+            .set_max_cooling_power(1000.);
+        model.add_hvac(hvac.wrap())?;
+        let state = model
+            .take_state()
+            .and_then(|mut header| header.take_values())
+            .ok_or("No state")?;
+        let state = Arc::new(Mutex::new(state));
+        let model = Arc::new(model);
+
+        let mut engine = rhai::Engine::new();
+        register_control_api(&mut engine, &model, &state, false);
+        let found: String = engine
+            .eval(
+                "
+            let h = hvac(\"Heater\");
+            let before = `${h.heating_setpoint} ${h.cooling_setpoint} ${h.is_on}`;
+            h.heating_setpoint = 18.5;
+            h.part_load_fraction = 0.25;
+            `${before} ${h.heating_setpoint} ${h.part_load_fraction} ${h.is_on}`
+        ",
+            )
+            .map_err(|e| e.to_string())?;
+        assert_eq!(found, "20.0 26.0 false 18.5 0.25 true");
+
+        // The changes are in the state
+        let state = state.lock().map_err(|e| e.to_string())?;
+        if let HVAC::IdealHeaterCooler(h) = &model.hvacs[0] {
+            assert_eq!(h.current_heating_setpoint(&state), Some(18.5));
+            assert_eq!(h.heating_cooling_consumption(&state), Some(250.));
+        }
+        Ok(())
+    }
 }
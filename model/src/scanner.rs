@@ -20,7 +20,15 @@ SOFTWARE.
 
 use std::{collections::HashMap, fmt::Display};
 
-use crate::{Model, SimulationStateHeader};
+use crate::error_msgs::print_warning_no_module;
+use crate::substance::gas::GasSpecification;
+use crate::substance::{Gas, Normal};
+use crate::{
+    Boundary, Construction, Fenestration, FenestrationType, Float, Material, Model, Schedule,
+    ScheduleProfile, SimulationStateHeader, Space, Surface, SurfaceType,
+};
+use calendar::Date;
+use geometry::{Loop3D, Point3D, Polygon3D};
 
 /// The scanner
 pub(crate) struct SimpleScanner<'a> {
@@ -256,19 +264,31 @@ impl<'a> SimpleScanner<'a> {
         // Now, build the model
         let mut model = Model::default();
         let read_order = vec![
+            "Schedule",
             "Space",
             "Substance",
             "Material",
             "Construction",
+            "ConstructionPreset",
             "Surface",
+            "ShadingSurface",
+            "Photovoltaic",
+            "WaterTank",
+            "SolarCollector",
             "Fenestration",
             "Building",
             "HVAC",
+            "Thermostat",
             "Luminaire",
+            "ElectricEquipment",
+            "SensorGrid",
             "Object",
             "Output",
+            "Report",
             "SiteDetails",
             "SolarOptions",
+            "ThermalOptions",
+            "TimestepOptions",
         ];
 
         for ident in read_order {
@@ -298,6 +318,18 @@ impl<'a> SimpleScanner<'a> {
                         };
                         model.add_construction(s);
                     }
+                    b"ConstructionPreset" => {
+                        let s: crate::ConstructionPreset = match json5::from_str(obj_str) {
+                            Ok(s) => s,
+                            Err(e) => {
+                                let errmsg = Self::make_error_msg(e.to_string(), *ln);
+                                return Err(errmsg);
+                            }
+                        };
+                        if let Err(e) = s.add_to_model(&mut model) {
+                            return Err(Self::make_error_msg(e, *ln));
+                        }
+                    }
                     b"Fenestration" => {
                         let s: crate::Fenestration = match json5::from_str(obj_str) {
                             Ok(s) => s,
@@ -324,6 +356,19 @@ impl<'a> SimpleScanner<'a> {
                             return Err(errmsg);
                         }
                     }
+                    b"Thermostat" => {
+                        let s: crate::Thermostat = match json5::from_str(obj_str) {
+                            Ok(s) => s,
+                            Err(e) => {
+                                let errmsg = Self::make_error_msg(e.to_string(), *ln);
+                                return Err(errmsg);
+                            }
+                        };
+                        if let Err(e) = model.add_thermostat(s) {
+                            let errmsg = Self::make_error_msg(e.to_string(), *ln);
+                            return Err(errmsg);
+                        }
+                    }
                     b"Luminaire" => {
                         let s: crate::Luminaire = match json5::from_str(obj_str) {
                             Ok(s) => s,
@@ -337,6 +382,19 @@ impl<'a> SimpleScanner<'a> {
                             return Err(errmsg);
                         }
                     }
+                    b"ElectricEquipment" => {
+                        let s: crate::ElectricEquipment = match json5::from_str(obj_str) {
+                            Ok(s) => s,
+                            Err(e) => {
+                                let errmsg = Self::make_error_msg(e.to_string(), *ln);
+                                return Err(errmsg);
+                            }
+                        };
+                        if let Err(e) = model.add_electric_equipment(s) {
+                            let errmsg = Self::make_error_msg(e.to_string(), *ln);
+                            return Err(errmsg);
+                        }
+                    }
                     b"Material" => {
                         let s: crate::Material = match json5::from_str(obj_str) {
                             Ok(s) => s,
@@ -370,6 +428,26 @@ impl<'a> SimpleScanner<'a> {
                         };
                         model.outputs.push(s);
                     }
+                    b"Report" => {
+                        let s: crate::Report = match json5::from_str(obj_str) {
+                            Ok(s) => s,
+                            Err(e) => {
+                                let errmsg = Self::make_error_msg(e.to_string(), *ln);
+                                return Err(errmsg);
+                            }
+                        };
+                        model.reports.push(s);
+                    }
+                    b"Schedule" => {
+                        let s: crate::Schedule = match json5::from_str(obj_str) {
+                            Ok(s) => s,
+                            Err(e) => {
+                                let errmsg = Self::make_error_msg(e.to_string(), *ln);
+                                return Err(errmsg);
+                            }
+                        };
+                        model.add_schedule(s);
+                    }
                     b"SiteDetails" => {
                         let s: crate::SiteDetails = match json5::from_str(obj_str) {
                             Ok(s) => s,
@@ -390,6 +468,26 @@ impl<'a> SimpleScanner<'a> {
                         };
                         model.solar_options = Some(s);
                     }
+                    b"ThermalOptions" => {
+                        let s: crate::ThermalOptions = match json5::from_str(obj_str) {
+                            Ok(s) => s,
+                            Err(e) => {
+                                let errmsg = Self::make_error_msg(e.to_string(), *ln);
+                                return Err(errmsg);
+                            }
+                        };
+                        model.thermal_options = Some(s);
+                    }
+                    b"TimestepOptions" => {
+                        let s: crate::TimestepOptions = match json5::from_str(obj_str) {
+                            Ok(s) => s,
+                            Err(e) => {
+                                let errmsg = Self::make_error_msg(e.to_string(), *ln);
+                                return Err(errmsg);
+                            }
+                        };
+                        model.timestep_options = Some(s);
+                    }
                     b"Space" => {
                         let s: crate::Space = match json5::from_str(obj_str) {
                             Ok(s) => s,
@@ -413,6 +511,68 @@ impl<'a> SimpleScanner<'a> {
                             return Err(errmsg);
                         }
                     }
+                    b"ShadingSurface" => {
+                        let s: crate::ShadingSurface = match json5::from_str(obj_str) {
+                            Ok(s) => s,
+                            Err(e) => {
+                                let errmsg = Self::make_error_msg(e.to_string(), *ln);
+                                return Err(errmsg);
+                            }
+                        };
+                        model.add_shading_surface(s);
+                    }
+                    b"WaterTank" => {
+                        let s: crate::WaterTank = match json5::from_str(obj_str) {
+                            Ok(s) => s,
+                            Err(e) => {
+                                let errmsg = Self::make_error_msg(e.to_string(), *ln);
+                                return Err(errmsg);
+                            }
+                        };
+                        if let Err(e) = model.add_water_tank(s) {
+                            let errmsg = Self::make_error_msg(e.to_string(), *ln);
+                            return Err(errmsg);
+                        }
+                    }
+                    b"SolarCollector" => {
+                        let s: crate::SolarCollector = match json5::from_str(obj_str) {
+                            Ok(s) => s,
+                            Err(e) => {
+                                let errmsg = Self::make_error_msg(e.to_string(), *ln);
+                                return Err(errmsg);
+                            }
+                        };
+                        if let Err(e) = model.add_solar_collector(s) {
+                            let errmsg = Self::make_error_msg(e.to_string(), *ln);
+                            return Err(errmsg);
+                        }
+                    }
+                    b"SensorGrid" => {
+                        let s: crate::SensorGrid = match json5::from_str(obj_str) {
+                            Ok(s) => s,
+                            Err(e) => {
+                                let errmsg = Self::make_error_msg(e.to_string(), *ln);
+                                return Err(errmsg);
+                            }
+                        };
+                        if let Err(e) = model.add_sensor_grid(s) {
+                            let errmsg = Self::make_error_msg(e.to_string(), *ln);
+                            return Err(errmsg);
+                        }
+                    }
+                    b"Photovoltaic" => {
+                        let s: crate::Photovoltaic = match json5::from_str(obj_str) {
+                            Ok(s) => s,
+                            Err(e) => {
+                                let errmsg = Self::make_error_msg(e.to_string(), *ln);
+                                return Err(errmsg);
+                            }
+                        };
+                        if let Err(e) = model.add_photovoltaic(s) {
+                            let errmsg = Self::make_error_msg(e.to_string(), *ln);
+                            return Err(errmsg);
+                        }
+                    }
                     b"Substance" => {
                         let s: crate::Substance = match json5::from_str(obj_str) {
                             Ok(s) => s,
@@ -439,6 +599,752 @@ impl<'a> SimpleScanner<'a> {
     }
 }
 
+/// A record (i.e., an object) in an IDF file. The first element of
+/// the `Vec` is the class of the object (e.g., `Zone`) and the rest are its
+/// fields. The `usize` is the line in which the record starts.
+type IdfRecord = (Vec<String>, usize);
+
+/// The thickness given to the `Material:NoMass` objects, which do not exist in
+/// SIMPLE. The conductivity is then calculated so that the R-value is kept.
+const IDF_NO_MASS_THICKNESS: Float = 0.1;
+
+/// The density given to the `Material:NoMass` objects. It is small so that
+/// SIMPLE considers these layers as massless
+const IDF_NO_MASS_DENSITY: Float = 30.;
+
+/// The specific heat capacity given to the `Material:NoMass` objects
+const IDF_NO_MASS_SPECIFIC_HEAT: Float = 800.;
+
+/// The density given to `WindowMaterial:Glazing` objects, as IDF files
+/// do not include this property
+const IDF_GLASS_DENSITY: Float = 2500.;
+
+/// The specific heat capacity given to `WindowMaterial:Glazing` objects, as IDF files
+/// do not include this property
+const IDF_GLASS_SPECIFIC_HEAT: Float = 840.;
+
+/// A scanner for EnergyPlus' IDF files.
+///
+/// It translates the objects that have an equivalent in SIMPLE, ignoring
+/// (with a warning, when relevant) the rest of them. The supported objects are:
+/// * `Zone`, which become a [`Space`](crate::Space)
+/// * `Material`, `Material:NoMass`, `WindowMaterial:Glazing` and `WindowMaterial:Gas`, which become a [`Substance`](crate::Substance) and a [`Material`](crate::Material) of the same name
+/// * `Construction`
+/// * `BuildingSurface:Detailed`, which become a [`Surface`](crate::Surface)
+/// * `FenestrationSurface:Detailed`, which become a [`Fenestration`](crate::Fenestration)
+/// * `Schedule:Constant` and `Schedule:Compact`, which become a [`Schedule`](crate::Schedule)
+pub(crate) struct IdfScanner<'a> {
+    source: &'a str,
+}
+
+impl<'a> IdfScanner<'a> {
+    /// Creates a new [`IdfScanner`]
+    pub(crate) fn new(source: &'a str) -> Self {
+        Self { source }
+    }
+
+    /// Splits the source into [`IdfRecord`]s, removing the comments.
+    fn records(&self) -> Result<Vec<IdfRecord>, String> {
+        let mut ret = Vec::new();
+        let mut fields: Vec<String> = Vec::new();
+        let mut current = String::new();
+        let mut start_line = 0;
+
+        for (i, line) in self.source.lines().enumerate() {
+            let ln = i + 1;
+            // Remove comments
+            let line = match line.find('!') {
+                Some(i) => &line[..i],
+                None => line,
+            };
+            for c in line.chars() {
+                if start_line == 0 && !c.is_whitespace() {
+                    start_line = ln;
+                }
+                match c {
+                    ',' => {
+                        fields.push(current.trim().to_string());
+                        current.clear();
+                    }
+                    ';' => {
+                        fields.push(current.trim().to_string());
+                        current.clear();
+                        ret.push((std::mem::take(&mut fields), start_line));
+                        start_line = 0;
+                    }
+                    _ => current.push(c),
+                }
+            }
+            current.push(' ');
+        }
+
+        if !fields.is_empty() || !current.trim().is_empty() {
+            let errmsg =
+                SimpleScanner::make_error_msg("Unterminated IDF object (missing ';')", start_line);
+            return Err(errmsg);
+        }
+        Ok(ret)
+    }
+
+    /// Gets the field `i` of a record (not counting the class), or
+    /// an empty `str` if it is not there.
+    fn field(rec: &IdfRecord, i: usize) -> &str {
+        rec.0.get(i + 1).map(|v| v.as_str()).unwrap_or("")
+    }
+
+    /// Parses the field `i` of a record as a number. Returns `None` if the
+    /// field is empty or autocalculated.
+    fn number(rec: &IdfRecord, i: usize) -> Result<Option<Float>, String> {
+        let v = Self::field(rec, i);
+        let lower = v.to_lowercase();
+        if v.is_empty() || lower == "autocalculate" || lower == "autosize" {
+            return Ok(None);
+        }
+        match v.parse::<Float>() {
+            Ok(v) => Ok(Some(v)),
+            Err(_) => Err(SimpleScanner::make_error_msg(
+                format!(
+                    "Expecting a number in field {} of '{}'... found '{}'",
+                    i + 1,
+                    rec.0[0],
+                    v
+                ),
+                rec.1,
+            )),
+        }
+    }
+
+    /// Parses the field `i` of a record as a number, returning an error
+    /// if it is not there.
+    fn required_number(rec: &IdfRecord, i: usize) -> Result<Float, String> {
+        match Self::number(rec, i)? {
+            Some(v) => Ok(v),
+            None => Err(SimpleScanner::make_error_msg(
+                format!("Field {} of '{}' is required", i + 1, rec.0[0]),
+                rec.1,
+            )),
+        }
+    }
+
+    /// Parses the field `i` of a record as a number, returning a `default`
+    /// value if it is not there.
+    fn number_or(rec: &IdfRecord, i: usize, default: Float) -> Result<Float, String> {
+        Ok(Self::number(rec, i)?.unwrap_or(default))
+    }
+
+    /// Builds a [`Polygon3D`] from the vertices in a record, starting from field `first`
+    fn polygon(
+        rec: &IdfRecord,
+        first: usize,
+        origin: Point3D,
+        clockwise: bool,
+    ) -> Result<Polygon3D, String> {
+        let coords = &rec.0[(first + 1).min(rec.0.len())..];
+        if coords.len() % 3 != 0 || coords.len() < 9 {
+            let errmsg = SimpleScanner::make_error_msg(
+                format!(
+                    "Object '{}' has an invalid number of vertices",
+                    Self::field(rec, 0)
+                ),
+                rec.1,
+            );
+            return Err(errmsg);
+        }
+        let mut points = Vec::with_capacity(coords.len() / 3);
+        for i in 0..coords.len() / 3 {
+            let x = Self::required_number(rec, first + 3 * i)?;
+            let y = Self::required_number(rec, first + 3 * i + 1)?;
+            let z = Self::required_number(rec, first + 3 * i + 2)?;
+            points.push(Point3D::new(x, y, z) + origin);
+        }
+        if clockwise {
+            points.reverse();
+        }
+
+        let mut outer = Loop3D::new();
+        for p in points {
+            outer
+                .push(p)
+                .map_err(|e| SimpleScanner::make_error_msg(e, rec.1))?;
+        }
+        outer
+            .close()
+            .map_err(|e| SimpleScanner::make_error_msg(e, rec.1))?;
+        Polygon3D::new(outer).map_err(|e| SimpleScanner::make_error_msg(e, rec.1))
+    }
+
+    /// Translates an IDF `Outside Boundary Condition` into a [`Boundary`]
+    fn boundary(
+        rec: &IdfRecord,
+        condition: &str,
+        object: &str,
+        surface_zones: &HashMap<String, String>,
+    ) -> Result<Boundary, String> {
+        let condition = condition.to_lowercase();
+        if condition == "outdoors" {
+            Ok(Boundary::Outdoor)
+        } else if condition.starts_with("ground") {
+            Ok(Boundary::Ground)
+        } else if condition == "adiabatic" {
+            Ok(Boundary::Adiabatic)
+        } else if condition == "zone" {
+            Ok(Boundary::Space {
+                space: object.to_string(),
+            })
+        } else if condition == "surface" {
+            match surface_zones.get(&object.to_lowercase()) {
+                Some(space) => Ok(Boundary::Space {
+                    space: space.clone(),
+                }),
+                None => Err(SimpleScanner::make_error_msg(
+                    format!(
+                        "Surface '{}' is adjacent to Surface '{}', which does not exist",
+                        Self::field(rec, 0),
+                        object
+                    ),
+                    rec.1,
+                )),
+            }
+        } else {
+            print_warning_no_module(format!(
+                "Outside Boundary Condition '{}' of Surface '{}' is not supported... using Adiabatic",
+                condition,
+                Self::field(rec, 0)
+            ));
+            Ok(Boundary::Adiabatic)
+        }
+    }
+
+    /// Parses the `Through: MM/DD` field `i` of a `Schedule:Compact`, returning
+    /// the number of days since January 1st until the end of that date
+    fn compact_through(rec: &IdfRecord, i: usize, date: &str) -> Result<usize, String> {
+        let err = || {
+            SimpleScanner::make_error_msg(
+                format!(
+                    "Invalid date '{}' in field {} of Schedule:Compact '{}'",
+                    date,
+                    i + 1,
+                    Self::field(rec, 0)
+                ),
+                rec.1,
+            )
+        };
+        let (month, day) = date.trim().split_once('/').ok_or_else(err)?;
+        let month = month.trim().parse::<u8>().map_err(|_| err())?;
+        let day = day.trim().parse::<u8>().map_err(|_| err())?;
+        let date = Date {
+            month,
+            day,
+            hour: 0.,
+            year: None,
+        };
+        if !(1..=12).contains(&month) || day == 0 || day > date.days_in_month() {
+            return Err(err());
+        }
+        Ok(date.day_of_year() as usize + 1)
+    }
+
+    /// Parses the `Until: HH:MM` field `i` of a `Schedule:Compact`, returning
+    /// the number of minutes since midnight
+    fn compact_until(rec: &IdfRecord, i: usize, time: &str) -> Result<usize, String> {
+        let err = || {
+            SimpleScanner::make_error_msg(
+                format!(
+                    "Invalid time '{}' in field {} of Schedule:Compact '{}'",
+                    time,
+                    i + 1,
+                    Self::field(rec, 0)
+                ),
+                rec.1,
+            )
+        };
+        let (hour, minute) = time.trim().split_once(':').ok_or_else(err)?;
+        let hour = hour.trim().parse::<usize>().map_err(|_| err())?;
+        let minute = minute.trim().parse::<usize>().map_err(|_| err())?;
+        let minutes = hour * 60 + minute;
+        if minute >= 60 || minutes == 0 || minutes > 24 * 60 {
+            return Err(err());
+        }
+        Ok(minutes)
+    }
+
+    /// Translates a `Schedule:Compact` into a [`ScheduleProfile`].
+    ///
+    /// The values of each day are evenly distributed, at the finest resolution
+    /// needed for representing all the `Until` times. Schedules with a single
+    /// `Through` period become `Constant`, `Daily` or `Weekly` profiles, and
+    /// the rest become `Annual` ones (in a year starting on a Monday). Holidays,
+    /// design days and custom days are not supported, so they are ignored.
+    fn compact_schedule(rec: &IdfRecord) -> Result<ScheduleProfile, String> {
+        const WEEKDAYS: [&str; 7] = [
+            "monday",
+            "tuesday",
+            "wednesday",
+            "thursday",
+            "friday",
+            "saturday",
+            "sunday",
+        ];
+        let name = Self::field(rec, 0);
+        let error = |msg: String| SimpleScanner::make_error_msg(msg, rec.1);
+
+        // The last day and the values of each day of the week, for each period
+        type Day = Vec<(usize, Float)>;
+        let mut periods: Vec<(usize, [Option<Day>; 7])> = Vec::new();
+        let mut days: Vec<usize> = Vec::new();
+
+        let mut i = 2;
+        while i + 1 < rec.0.len() {
+            let field = Self::field(rec, i);
+            let lower = field.to_lowercase();
+            if let Some(date) = lower.strip_prefix("through:") {
+                let last_day = Self::compact_through(rec, i, date)?;
+                if periods.last().is_some_and(|(prev, _)| *prev >= last_day) {
+                    return Err(error(format!(
+                        "The periods of Schedule:Compact '{}' are not in chronological order",
+                        name
+                    )));
+                }
+                periods.push((last_day, Default::default()));
+                days.clear();
+            } else if let Some(list) = lower.strip_prefix("for:") {
+                let defined = &periods
+                    .last()
+                    .ok_or_else(|| {
+                        error(format!(
+                            "Schedule:Compact '{}' has a 'For' field before any 'Through' field",
+                            name
+                        ))
+                    })?
+                    .1;
+                days.clear();
+                for day_type in list.split_whitespace() {
+                    match day_type {
+                        "alldays" => days.extend(0..7),
+                        "weekdays" => days.extend(0..5),
+                        "weekends" => days.extend(5..7),
+                        "allotherdays" => days.extend((0..7).filter(|d| defined[*d].is_none())),
+                        "holiday" | "holidays" | "summerdesignday" | "winterdesignday"
+                        | "customday1" | "customday2" => {}
+                        d => match WEEKDAYS.iter().position(|w| *w == d) {
+                            Some(d) => days.push(d),
+                            None => {
+                                return Err(error(format!(
+                                    "Unsupported day type '{}' in Schedule:Compact '{}'",
+                                    d, name
+                                )))
+                            }
+                        },
+                    }
+                }
+                // The values of these days are pushed by the 'Until' fields
+                let defined = &mut periods.last_mut().expect("checked above").1;
+                for d in days.iter() {
+                    defined[*d] = Some(Vec::new());
+                }
+            } else if let Some(interpolation) = lower.strip_prefix("interpolate:") {
+                if interpolation.trim() != "no" {
+                    print_warning_no_module(format!(
+                        "Schedule:Compact '{}' asks for '{}' interpolation, which is not supported... using 'No'",
+                        name,
+                        interpolation.trim()
+                    ));
+                }
+            } else if let Some(time) = lower.strip_prefix("until:") {
+                let minutes = Self::compact_until(rec, i, time)?;
+                let value = Self::required_number(rec, i + 1)?;
+                let defined = periods.last_mut().map(|(_, d)| d);
+                if days.is_empty() || defined.is_none() {
+                    return Err(error(format!(
+                        "Schedule:Compact '{}' has an 'Until' field before any 'For' field",
+                        name
+                    )));
+                }
+                let defined = defined.expect("checked above");
+                for d in days.iter() {
+                    let day = defined[*d].as_mut().expect("set by the 'For' field");
+                    if day.last().is_some_and(|(prev, _)| *prev >= minutes) {
+                        return Err(error(format!(
+                            "The 'Until' times of Schedule:Compact '{}' are not in chronological order",
+                            name
+                        )));
+                    }
+                    day.push((minutes, value));
+                }
+                i += 1;
+            } else if !field.is_empty() {
+                return Err(error(format!(
+                    "Unexpected field '{}' in Schedule:Compact '{}'",
+                    field, name
+                )));
+            }
+            i += 1;
+        }
+
+        // Check that everything is covered
+        match periods.last() {
+            Some((365, _)) => {}
+            _ => {
+                return Err(error(format!(
+                    "Schedule:Compact '{}' does not cover the whole year (the last 'Through' field should be 12/31)",
+                    name
+                )))
+            }
+        }
+        let mut step = 24 * 60;
+        for (_, week) in periods.iter() {
+            for (d, day) in week.iter().enumerate() {
+                match day {
+                    Some(day) if day.last().is_some_and(|(m, _)| *m == 24 * 60) => {
+                        for (minutes, _) in day.iter() {
+                            let (mut a, mut b) = (step, *minutes);
+                            while b != 0 {
+                                (a, b) = (b, a % b);
+                            }
+                            step = a;
+                        }
+                    }
+                    _ => {
+                        return Err(error(format!(
+                            "Schedule:Compact '{}' does not define every {} until 24:00",
+                            name, WEEKDAYS[d]
+                        )))
+                    }
+                }
+            }
+        }
+
+        // Evenly distribute the values
+        let n = 24 * 60 / step;
+        let sample = |day: &Option<Day>| -> Vec<Float> {
+            let day = day.as_ref().expect("checked above");
+            let mut j = 0;
+            (0..n)
+                .map(|k| {
+                    while day[j].0 <= k * step {
+                        j += 1;
+                    }
+                    day[j].1
+                })
+                .collect()
+        };
+        let profile = if periods.len() == 1 {
+            let days: Vec<Vec<Float>> = periods[0].1.iter().map(sample).collect();
+            if days.iter().all(|d| d == &days[0]) {
+                if days[0].iter().all(|v| *v == days[0][0]) {
+                    ScheduleProfile::Constant { value: days[0][0] }
+                } else {
+                    ScheduleProfile::Daily {
+                        values: days[0].clone(),
+                    }
+                }
+            } else {
+                ScheduleProfile::Weekly { days }
+            }
+        } else {
+            let weeks: Vec<Vec<Vec<Float>>> = periods
+                .iter()
+                .map(|(_, week)| week.iter().map(sample).collect())
+                .collect();
+            let mut values = Vec::with_capacity(365 * n);
+            let mut period = 0;
+            for d in 0..365 {
+                while periods[period].0 <= d {
+                    period += 1;
+                }
+                values.extend_from_slice(&weeks[period][d % 7]);
+            }
+            ScheduleProfile::Annual { values }
+        };
+        Ok(profile)
+    }
+
+    /// Parses a whole [`Model`] from an IDF file
+    pub(crate) fn parse_model(&self) -> Result<(Model, SimulationStateHeader), String> {
+        let records = self.records()?;
+
+        let mut data = HashMap::<String, Vec<&IdfRecord>>::new();
+        for rec in records.iter() {
+            let key = rec.0[0].to_lowercase();
+            if let Some(v) = data.get_mut(&key) {
+                v.push(rec);
+            } else {
+                data.insert(key, vec![rec]);
+            }
+        }
+        let get = |class: &str| -> Vec<&IdfRecord> { data.get(class).cloned().unwrap_or_default() };
+
+        // Zones got a new 'Space Name' field in BuildingSurface:Detailed
+        // objects in EnergyPlus 9.6
+        let mut has_space_field = true;
+        if let Some(rec) = get("version").first() {
+            let mut version = Self::field(rec, 0).split('.');
+            let major = version.next().and_then(|v| v.trim().parse::<usize>().ok());
+            let minor = version.next().and_then(|v| v.trim().parse::<usize>().ok());
+            if let (Some(major), Some(minor)) = (major, minor) {
+                has_space_field = (major, minor) >= (9, 6);
+            }
+        }
+
+        // Geometry rules
+        let mut clockwise = false;
+        let mut relative = true;
+        if let Some(rec) = get("globalgeometryrules").first() {
+            clockwise = Self::field(rec, 1).to_lowercase() == "clockwise";
+            relative = Self::field(rec, 2).to_lowercase() == "relative";
+        }
+
+        let mut model = Model::default();
+
+        /* SCHEDULES */
+        for rec in get("schedule:constant") {
+            let value = Self::required_number(rec, 2)?;
+            let profile = ScheduleProfile::Constant { value };
+            model.add_schedule(Schedule::new(Self::field(rec, 0), profile));
+        }
+        for rec in get("schedule:compact") {
+            let profile = Self::compact_schedule(rec)?;
+            model.add_schedule(Schedule::new(Self::field(rec, 0), profile));
+        }
+        for class in [
+            "schedule:year",
+            "schedule:week:daily",
+            "schedule:week:compact",
+            "schedule:day:hourly",
+            "schedule:day:interval",
+            "schedule:day:list",
+            "schedule:file",
+        ] {
+            for rec in get(class) {
+                print_warning_no_module(format!(
+                    "{} '{}' is not supported (only Schedule:Constant and Schedule:Compact are)... ignoring it",
+                    rec.0[0],
+                    Self::field(rec, 0)
+                ));
+            }
+        }
+
+        /* ZONES */
+        let mut origins = HashMap::<String, Point3D>::new();
+        for rec in get("zone") {
+            let name = Self::field(rec, 0);
+            let mut space = Space::new(name);
+            space.volume = Self::number(rec, 8)?;
+            if relative {
+                let x = Self::number_or(rec, 2, 0.)?;
+                let y = Self::number_or(rec, 3, 0.)?;
+                let z = Self::number_or(rec, 4, 0.)?;
+                origins.insert(name.to_lowercase(), Point3D::new(x, y, z));
+            }
+            model.add_space(space);
+        }
+
+        /* MATERIALS */
+        for rec in get("material") {
+            let name = Self::field(rec, 0);
+            let thermal_absorbtance = Self::number_or(rec, 6, 0.9)?;
+            let solar_absorbtance = Self::number_or(rec, 7, 0.7)?;
+            let visible_absorbtance = Self::number_or(rec, 8, 0.7)?;
+            let mut sub = Normal::new(name);
+            sub.set_thermal_conductivity(Self::required_number(rec, 3)?)
+                .set_density(Self::required_number(rec, 4)?)
+                .set_specific_heat_capacity(Self::required_number(rec, 5)?)
+                .set_front_thermal_absorbtance(thermal_absorbtance)
+                .set_back_thermal_absorbtance(thermal_absorbtance)
+                .set_front_solar_absorbtance(solar_absorbtance)
+                .set_back_solar_absorbtance(solar_absorbtance)
+                .set_front_visible_reflectance(1. - visible_absorbtance)
+                .set_back_visible_reflectance(1. - visible_absorbtance);
+            model.add_substance(sub.wrap());
+            let thickness = Self::required_number(rec, 2)?;
+            model.add_material(Material::new(name, name, thickness));
+        }
+        for rec in get("material:nomass") {
+            let name = Self::field(rec, 0);
+            let r_value = Self::required_number(rec, 2)?;
+            let thermal_absorbtance = Self::number_or(rec, 3, 0.9)?;
+            let solar_absorbtance = Self::number_or(rec, 4, 0.7)?;
+            let visible_absorbtance = Self::number_or(rec, 5, 0.7)?;
+            let mut sub = Normal::new(name);
+            sub.set_thermal_conductivity(IDF_NO_MASS_THICKNESS / r_value)
+                .set_density(IDF_NO_MASS_DENSITY)
+                .set_specific_heat_capacity(IDF_NO_MASS_SPECIFIC_HEAT)
+                .set_front_thermal_absorbtance(thermal_absorbtance)
+                .set_back_thermal_absorbtance(thermal_absorbtance)
+                .set_front_solar_absorbtance(solar_absorbtance)
+                .set_back_solar_absorbtance(solar_absorbtance)
+                .set_front_visible_reflectance(1. - visible_absorbtance)
+                .set_back_visible_reflectance(1. - visible_absorbtance);
+            model.add_substance(sub.wrap());
+            model.add_material(Material::new(name, name, IDF_NO_MASS_THICKNESS));
+        }
+        for rec in get("windowmaterial:glazing") {
+            let name = Self::field(rec, 0);
+            let tau = Self::number_or(rec, 4, 0.)?;
+            let front_rho = Self::number_or(rec, 5, 0.)?;
+            let back_rho = Self::number_or(rec, 6, front_rho)?;
+            let visible_tau = Self::number_or(rec, 7, 0.)?;
+            let front_visible_rho = Self::number_or(rec, 8, 0.)?;
+            let back_visible_rho = Self::number_or(rec, 9, front_visible_rho)?;
+            let front_emissivity = Self::number_or(rec, 11, 0.84)?;
+            let back_emissivity = Self::number_or(rec, 12, 0.84)?;
+            let mut sub = Normal::new(name);
+            sub.set_thermal_conductivity(Self::number_or(rec, 13, 0.9)?)
+                .set_density(IDF_GLASS_DENSITY)
+                .set_specific_heat_capacity(IDF_GLASS_SPECIFIC_HEAT)
+                .set_solar_transmittance(tau)
+                .set_front_solar_absorbtance(1. - tau - front_rho)
+                .set_back_solar_absorbtance(1. - tau - back_rho)
+                .set_visible_transmissivity(visible_tau)
+                .set_front_visible_reflectance(front_visible_rho)
+                .set_back_visible_reflectance(back_visible_rho)
+                .set_front_thermal_absorbtance(front_emissivity)
+                .set_back_thermal_absorbtance(back_emissivity);
+            model.add_substance(sub.wrap());
+            let thickness = Self::required_number(rec, 3)?;
+            model.add_material(Material::new(name, name, thickness));
+        }
+        for rec in get("windowmaterial:gas") {
+            let name = Self::field(rec, 0);
+            let gas = match Self::field(rec, 1).to_lowercase().as_str() {
+                "air" => GasSpecification::Air,
+                "argon" => GasSpecification::Argon,
+                "krypton" => GasSpecification::Krypton,
+                "xenon" => GasSpecification::Xenon,
+                g => {
+                    let errmsg = SimpleScanner::make_error_msg(
+                        format!(
+                            "Unsupported Gas Type '{}' in WindowMaterial:Gas '{}'",
+                            g, name
+                        ),
+                        rec.1,
+                    );
+                    return Err(errmsg);
+                }
+            };
+            let mut sub = Gas::new(name);
+            sub.set_gas(gas);
+            model.add_substance(sub.wrap());
+            let thickness = Self::required_number(rec, 2)?;
+            model.add_material(Material::new(name, name, thickness));
+        }
+
+        /* CONSTRUCTIONS */
+        for rec in get("construction") {
+            let mut c = Construction::new(Self::field(rec, 0));
+            for layer in rec.0.iter().skip(2) {
+                if !layer.is_empty() {
+                    c.materials.push(layer.clone());
+                }
+            }
+            model.add_construction(c);
+        }
+
+        /* SURFACES */
+        let surfaces = get("buildingsurface:detailed");
+        let offset = if has_space_field { 1 } else { 0 };
+        let mut surface_zones = HashMap::<String, String>::new();
+        for rec in surfaces.iter() {
+            surface_zones.insert(
+                Self::field(rec, 0).to_lowercase(),
+                Self::field(rec, 3).to_string(),
+            );
+        }
+        // E+ describes both sides of interzone surfaces, while SIMPLE
+        // uses a single one. The second ones are skipped, and mapped into the first ones.
+        let mut skipped = HashMap::<String, String>::new();
+        for rec in surfaces {
+            let name = Self::field(rec, 0);
+            let zone = Self::field(rec, 3);
+            let condition = Self::field(rec, 4 + offset);
+            let object = Self::field(rec, 5 + offset);
+            if condition.to_lowercase() == "surface" {
+                let other = object.to_lowercase();
+                if model.get_surface(object).is_ok() || skipped.contains_key(&other) {
+                    skipped.insert(name.to_lowercase(), object.to_string());
+                    continue;
+                }
+            }
+            let origin = origins
+                .get(&zone.to_lowercase())
+                .copied()
+                .unwrap_or(Point3D::new(0., 0., 0.));
+            let vertices = Self::polygon(rec, 10 + offset, origin, clockwise)?;
+            let front = Self::boundary(rec, condition, object, &surface_zones)?;
+            let interior = !matches!(front, Boundary::Outdoor | Boundary::Ground);
+            let category = match (Self::field(rec, 1).to_lowercase().as_str(), interior) {
+                ("wall", false) => SurfaceType::ExteriorWall,
+                ("wall", true) => SurfaceType::InteriorWall,
+                ("floor", false) => {
+                    if let Boundary::Ground = front {
+                        SurfaceType::GroundFloor
+                    } else {
+                        SurfaceType::ExteriorFloor
+                    }
+                }
+                ("floor", true) => SurfaceType::InteriorFloor,
+                ("roof", _) => SurfaceType::Roof,
+                ("ceiling", _) => SurfaceType::Ceiling,
+                _ => SurfaceType::Other,
+            };
+            let back = Boundary::Space {
+                space: zone.to_string(),
+            };
+            let mut s = Surface::new(name, vertices, Self::field(rec, 2), front, back);
+            s.set_category(category);
+            model
+                .add_surface(s)
+                .map_err(|e| SimpleScanner::make_error_msg(e, rec.1))?;
+        }
+
+        /* FENESTRATIONS */
+        let fenestrations = get("fenestrationsurface:detailed");
+        for rec in fenestrations {
+            let name = Self::field(rec, 0);
+            let object = Self::field(rec, 4);
+            if !object.is_empty() && model.get_fenestration(object).is_ok() {
+                continue;
+            }
+            let category = match Self::field(rec, 1).to_lowercase().as_str() {
+                "window" | "glassdoor" => FenestrationType::Window,
+                "door" => FenestrationType::Door,
+                t => {
+                    print_warning_no_module(format!(
+                        "Fenestration '{}' is of unsupported type '{}'... ignoring it",
+                        name, t
+                    ));
+                    continue;
+                }
+            };
+            let mut parent = Self::field(rec, 3).to_string();
+            if let Some(other) = skipped.get(&parent.to_lowercase()) {
+                parent = other.clone();
+            }
+            let origin = surface_zones
+                .get(&parent.to_lowercase())
+                .and_then(|zone| origins.get(&zone.to_lowercase()))
+                .copied()
+                .unwrap_or(Point3D::new(0., 0., 0.));
+            let vertices = Self::polygon(rec, 9, origin, clockwise)?;
+            let mut fen = Fenestration::new(
+                name,
+                vertices,
+                Self::field(rec, 2),
+                category,
+                Boundary::Outdoor,
+                Boundary::Outdoor,
+            );
+            fen.set_parent_surface(parent);
+            model
+                .add_fenestration(fen)
+                .map_err(|e| SimpleScanner::make_error_msg(e, rec.1))?;
+        }
+
+        let state = model.take_state().ok_or("Could not take state")?;
+        Ok((model, state))
+    }
+}
+
 #[cfg(test)]
 mod testing {
 
@@ -474,4 +1380,180 @@ mod testing {
         assert_eq!(object, b"{ data data }");
         Ok(())
     }
+
+    #[test]
+    fn idf_records() -> Result<(), String> {
+        let source = "Version,9.4;\n  Zone, The Zone, ! the name\n 0;\n";
+        let scan = IdfScanner::new(source);
+        let records = scan.records()?;
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].0, vec!["Version", "9.4"]);
+        assert_eq!(records[0].1, 1);
+        assert_eq!(records[1].0, vec!["Zone", "The Zone", "0"]);
+        assert_eq!(records[1].1, 2);
+        assert_eq!(IdfScanner::field(&records[1], 0), "The Zone");
+        assert_eq!(IdfScanner::field(&records[1], 12), "");
+
+        // Missing semicolon
+        let scan = IdfScanner::new("Zone, The Zone");
+        assert!(scan.records().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn idf_box() -> Result<(), String> {
+        use crate::SurfaceTrait;
+
+        let (model, ..) = Model::from_idf_file("./tests/idf/box.idf")?;
+
+        assert_eq!(model.schedules.len(), 1);
+        assert_eq!(model.spaces.len(), 1);
+        assert_eq!(model.spaces[0].volume, Some(48.));
+
+        assert_eq!(model.substances.len(), 4);
+        assert_eq!(model.materials.len(), 4);
+        let insulation = model.get_material("Insulation")?;
+        if let crate::Substance::Normal(s) = model.get_substance(&insulation.substance)? {
+            let r = insulation.thickness / s.thermal_conductivity()?;
+            assert!((r - 2.5).abs() < 1e-9);
+        } else {
+            panic!("Expecting a Normal substance")
+        }
+
+        assert_eq!(model.constructions.len(), 2);
+        assert_eq!(model.get_construction("Double Clear")?.materials.len(), 3);
+
+        assert_eq!(model.surfaces.len(), 6);
+        let south = model.get_surface("South Wall")?;
+        assert!((south.area() - 10.).abs() < 1e-6);
+        assert!(matches!(south.front_boundary, Boundary::Outdoor));
+        if let Boundary::Space { space } = &south.back_boundary {
+            assert_eq!(space, "Box Zone");
+        } else {
+            panic!("Expecting a Space boundary")
+        }
+        assert!(matches!(
+            model.get_surface("Floor")?.front_boundary,
+            Boundary::Ground
+        ));
+        assert_eq!(
+            *model.get_surface("Floor")?.category()?,
+            SurfaceType::GroundFloor
+        );
+
+        assert_eq!(model.fenestrations.len(), 1);
+        let window = &model.fenestrations[0];
+        assert!((window.area() - 2.).abs() < 1e-6);
+        Ok(())
+    }
+
+    #[test]
+    fn idf_schedules() -> Result<(), String> {
+        let source = "
+            Schedule:Constant, Always On, , 1;
+            Schedule:Compact, Office, Fraction,
+                Through: 12/31,
+                For: Weekdays SummerDesignDay,
+                Until: 08:00, 0.1,
+                Until: 18:30, 1,
+                Until: 24:00, 0.1,
+                For: AllOtherDays,
+                Until: 24:00, 0.1;
+            Schedule:Compact, Setpoint, Temperature,
+                Through: 12/31, For: AllDays, Until: 6:00, 16, Until: 24:00, 21;
+            Schedule:Compact, Flat, Fraction,
+                Through: 12/31, For: AllDays, Until: 24:00, 0.5;
+            Schedule:Compact, Seasonal, Fraction,
+                Through: 3/31, For: AllDays, Until: 24:00, 1,
+                Through: 12/31, For: Saturday Sunday, Until: 24:00, 0,
+                For: AllOtherDays, Until: 24:00, 0.5;
+            Schedule:Year, Unsupported, Fraction, Week, 1, 1, 12, 31;
+        ";
+        let (model, ..) = Model::from_idf(source)?;
+        assert_eq!(model.schedules.len(), 5);
+
+        let office = &model.get_schedule("Office")?.profile;
+        if let ScheduleProfile::Weekly { days } = office {
+            assert_eq!(days.len(), 7);
+            // Half-hourly values
+            assert_eq!(days[0].len(), 48);
+            assert_eq!(days[0][15], 0.1);
+            assert_eq!(days[0][16], 1.);
+            assert_eq!(days[0][36], 1.);
+            assert_eq!(days[0][37], 0.1);
+            assert_eq!(days[4], days[0]);
+            assert_eq!(days[5], vec![0.1; 48]);
+            assert_eq!(days[6], vec![0.1; 48]);
+        } else {
+            panic!("Expecting a Weekly profile... found {:?}", office)
+        }
+
+        // 6-hourly values are enough
+        assert_eq!(
+            model.get_schedule("Setpoint")?.profile,
+            ScheduleProfile::Daily {
+                values: vec![16., 21., 21., 21.]
+            }
+        );
+        assert_eq!(
+            model.get_schedule("Flat")?.profile,
+            ScheduleProfile::Constant { value: 0.5 }
+        );
+
+        if let ScheduleProfile::Annual { values } = &model.get_schedule("Seasonal")?.profile {
+            assert_eq!(values.len(), 365);
+            // March 31st
+            assert_eq!(values[89], 1.);
+            // April 1st is a Sunday, in a year starting on a Monday
+            assert_eq!(values[90], 0.);
+            assert_eq!(values[91], 0.5);
+            assert_eq!(values[96], 0.);
+            assert_eq!(values[97], 0.);
+            assert_eq!(values[98], 0.5);
+        } else {
+            panic!("Expecting an Annual profile")
+        }
+
+        // Errors
+        let missing_days = "Schedule:Compact, S, , Through: 12/31, For: Weekdays, Until: 24:00, 1;";
+        assert!(Model::from_idf(missing_days).is_err());
+        let short_year = "Schedule:Compact, S, , Through: 6/30, For: AllDays, Until: 24:00, 1;";
+        assert!(Model::from_idf(short_year).is_err());
+        let short_day = "Schedule:Compact, S, , Through: 12/31, For: AllDays, Until: 18:00, 1;";
+        assert!(Model::from_idf(short_day).is_err());
+        let bad_date = "Schedule:Compact, S, , Through: 2/30, For: AllDays, Until: 24:00, 1;";
+        assert!(Model::from_idf(bad_date).is_err());
+        let bad_order =
+            "Schedule:Compact, S, , Through: 12/31, For: AllDays, Until: 18:00, 1, Until: 8:00, 0;";
+        assert!(Model::from_idf(bad_order).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn idf_interzone() -> Result<(), String> {
+        // E+ 9.6 and later have a 'Space Name' field in surfaces
+        let source = "
+            Version, 22.1;
+            Zone, Zone A;
+            Zone, Zone B;
+            Material:NoMass, Insulation, Rough, 2.;
+            Construction, Wall, Insulation;
+            BuildingSurface:Detailed, A to B, Wall, Wall, Zone A, , Surface, B to A, NoSun, NoWind, 0, 4,
+                1,0,3, 1,0,0, 1,1,0, 1,1,3;
+            BuildingSurface:Detailed, B to A, Wall, Wall, Zone B, , Surface, A to B, NoSun, NoWind, 0, 4,
+                1,1,3, 1,1,0, 1,0,0, 1,0,3;
+        ";
+        let (model, ..) = Model::from_idf(source)?;
+        assert_eq!(model.spaces.len(), 2);
+        assert_eq!(model.surfaces.len(), 1);
+        let s = &model.surfaces[0];
+        assert_eq!(s.name(), "A to B");
+        assert_eq!(*s.category()?, SurfaceType::InteriorWall);
+        if let Boundary::Space { space } = &s.front_boundary {
+            assert_eq!(space, "Zone B");
+        } else {
+            panic!("Expecting a Space boundary")
+        }
+        Ok(())
+    }
 }
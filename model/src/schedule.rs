@@ -0,0 +1,484 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::Float;
+use calendar::{Date, DayType};
+use derive::ObjectIO;
+use serde::{Deserialize, Serialize};
+
+/// The way in which the values of a [`Schedule`] are calculated in between
+/// the instants that are explicitly given.
+///
+/// # Example
+///
+/// #### `.json`
+/// ```json
+/// {{#include ../../../model/tests/scanner/schedule_interpolation.json}}
+/// ```
+#[derive(Debug, Default, Copy, Clone, ObjectIO, Serialize, Deserialize, PartialEq, Eq)]
+#[inline_enum]
+pub enum ScheduleInterpolation {
+    /// Each value is kept until the next one starts (e.g., in an hourly
+    /// profile, the value given for 8:00 is used until 8:59)
+    #[default]
+    Step,
+
+    /// The values are linearly interpolated between the instant in
+    /// which one starts and the instant in which the next one starts
+    Linear,
+}
+
+/// The values that a [`Schedule`] takes over time.
+///
+/// The values in each profile are evenly distributed over its period. So,
+/// for instance, a `Daily` profile with 24 values is an hourly profile
+/// starting at midnight, while one with 2 values will change at midday.
+///
+/// # Examples
+///
+/// #### `.json`
+/// ```json
+/// {{#include ../../../model/tests/scanner/schedule_profile_weekly.json}}
+/// ```
+#[derive(Debug, Clone, PartialEq, ObjectIO, Serialize, Deserialize)]
+#[serde(tag = "type")]
+#[serde(deny_unknown_fields)]
+pub enum ScheduleProfile {
+    /// The same value all the time
+    Constant {
+        /// The value
+        value: Float,
+    },
+
+    /// The same profile every day
+    Daily {
+        /// The values throughout the day, starting at midnight
+        values: Vec<Float>,
+    },
+
+    /// A different profile for each day of the week.
+    ///
+    /// > Note: Dates without a year are assumed to be in a year
+    /// starting on a Monday.
+    Weekly {
+        /// Seven daily profiles, from Monday to Sunday
+        days: Vec<Vec<Float>>,
+    },
+
+    /// A different profile for weekdays, weekends and holidays (see
+    /// [`DayType`]).
+    ///
+    /// > Note: Dates without a year are assumed to be in a year
+    /// starting on a Monday.
+    ///
+    /// #### `.json`
+    /// ```json
+    /// {{#include ../../../model/tests/scanner/schedule_profile_day_types.json}}
+    /// ```
+    DayTypes {
+        /// The values throughout the days from Monday to Friday
+        weekday: Vec<Float>,
+
+        /// The values throughout Saturdays and Sundays
+        weekend: Vec<Float>,
+
+        /// The values throughout holidays. If none are given, the
+        /// `weekend` values are used.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        holiday: Option<Vec<Float>>,
+
+        /// The dates of the holidays (e.g., `"--12-25"` every year, or
+        /// `"2024-04-25"` only in 2024). Hours are ignored.
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        holidays: Vec<Date>,
+    },
+
+    /// A profile covering the whole (non-leap) year. For
+    /// instance, 8760 values represent an hourly profile starting on
+    /// midnight of January 1st.
+    Annual {
+        /// The values throughout the year
+        values: Vec<Float>,
+    },
+}
+
+/// A value that changes over time, which can be referenced by name by
+/// other objects (e.g., the power of a [`Luminaire`](crate::Luminaire)).
+///
+/// ## Examples
+///
+/// #### `.spl`
+/// ```json
+/// {{#include ../../../model/tests/scanner/schedule.spl}}
+/// ```
+///
+/// #### `.json`
+/// ```json
+/// {{#include ../../../model/tests/scanner/schedule.json}}
+/// ```
+#[derive(Debug, Clone, ObjectIO, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Schedule {
+    /// The name of the schedule
+    pub name: String,
+
+    /// The values of the schedule over time
+    pub profile: ScheduleProfile,
+
+    /// How the values are calculated between the instants explicitly
+    /// given in the `profile`. Defaults to `Step`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    interpolation: Option<ScheduleInterpolation>,
+}
+
+/// Samples a (cyclic) set of evenly distributed `values` at a
+/// fraction `x` (from 0 to 1) of its period. `next` is the value
+/// that comes after the last one (e.g., the first value of the next day)
+fn sample(
+    values: &[Float],
+    x: Float,
+    next: Float,
+    interpolation: ScheduleInterpolation,
+) -> Result<Float, String> {
+    if values.is_empty() {
+        return Err("Schedule profiles need at least one value".to_string());
+    }
+    let n = values.len();
+    let pos = x * n as Float;
+    let i = (pos.floor() as usize).min(n - 1);
+    match interpolation {
+        ScheduleInterpolation::Step => Ok(values[i]),
+        ScheduleInterpolation::Linear => {
+            let after = if i + 1 < n { values[i + 1] } else { next };
+            let frac = pos - i as Float;
+            Ok(values[i] + frac * (after - values[i]))
+        }
+    }
+}
+
+impl Schedule {
+    /// Gets the value of the schedule at a certain [`Date`]
+    ///
+    /// ```rust
+    /// use model::{Schedule, ScheduleProfile};
+    /// use calendar::Date;
+    ///
+    /// let profile = ScheduleProfile::Daily {
+    ///     values: vec![0., 1.],
+    /// };
+    /// let schedule = Schedule::new("half day", profile);
+    ///
+    /// let morning = Date { month: 2, day: 3, hour: 8., year: None };
+    /// assert_eq!(schedule.value(morning).unwrap(), 0.);
+    ///
+    /// let evening = Date { month: 2, day: 3, hour: 20., year: None };
+    /// assert_eq!(schedule.value(evening).unwrap(), 1.);
+    /// ```
+    pub fn value(&self, date: Date) -> Result<Float, String> {
+        let interpolation = match self.interpolation() {
+            Ok(i) => *i,
+            Err(_) => ScheduleInterpolation::default(),
+        };
+        let day_of_year = date.day_of_year();
+        let hour_fraction = date.hour / 24.;
+        let ret = match &self.profile {
+            ScheduleProfile::Constant { value } => Ok(*value),
+            ScheduleProfile::Daily { values } => {
+                let next = *values.first().unwrap_or(&0.);
+                sample(values, hour_fraction, next, interpolation)
+            }
+            ScheduleProfile::Weekly { days } => {
+                if days.len() != 7 {
+                    return Err(format!(
+                        "Weekly profile of Schedule '{}' should have 7 days... found {}",
+                        self.name,
+                        days.len()
+                    ));
+                }
+                let weekday = date.weekday().index();
+                let tomorrow = &days[(weekday + 1) % 7];
+                let next = *tomorrow.first().unwrap_or(&0.);
+                sample(&days[weekday], hour_fraction, next, interpolation)
+            }
+            ScheduleProfile::DayTypes {
+                weekday,
+                weekend,
+                holiday,
+                holidays,
+            } => {
+                let values = |d: Date| match d.day_type(holidays) {
+                    DayType::Weekday => weekday,
+                    DayType::Weekend => weekend,
+                    DayType::Holiday => holiday.as_ref().unwrap_or(weekend),
+                };
+                let mut tomorrow = date;
+                tomorrow.add_days(1);
+                let next = *values(tomorrow).first().unwrap_or(&0.);
+                sample(values(date), hour_fraction, next, interpolation)
+            }
+            ScheduleProfile::Annual { values } => {
+                let next = *values.first().unwrap_or(&0.);
+                sample(values, day_of_year / 365., next, interpolation)
+            }
+        };
+        ret.map_err(|e| format!("{} (in Schedule '{}')", e, self.name))
+    }
+}
+
+impl ::schedule::Schedule<Float> for Schedule {
+    fn get(&self, date: Date) -> Option<Float> {
+        self.value(date).ok()
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use crate::model::Model;
+
+    #[test]
+    fn serde() -> Result<(), String> {
+        use json5;
+        use std::fs;
+
+        // Hardcode a reference
+        let profile = ScheduleProfile::Daily {
+            values: vec![0., 0., 1., 1.],
+        };
+        let mut hardcoded_ref = Schedule::new("Lights", profile);
+        hardcoded_ref.set_interpolation(ScheduleInterpolation::Linear);
+
+        // Deserialize from hardcoded string and check they are the same
+        let from_hardcoded_json: Schedule = json5::from_str(
+            "{
+            name: 'Lights',
+            profile: {
+                type: 'Daily',
+                values: [0, 0, 1, 1]
+            },
+            interpolation: 'Linear'
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_hardcoded_json)
+        );
+
+        // Read json file (used in DOC), Deserialize, and compare
+        let filename = "./tests/scanner/schedule";
+        let json_file = format!("{}.json", filename);
+        let json_data = fs::read_to_string(json_file).map_err(|e| e.to_string())?;
+        let from_json_file: Schedule =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_json_file)
+        );
+
+        // Serialize and deserialize again... check that everythin matches the pattern
+        let rust_json = serde_json::to_string(&hardcoded_ref).map_err(|e| e.to_string())?;
+        let from_serialized: Schedule =
+            serde_json::from_str(&rust_json).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_serialized)
+        );
+
+        // check simple
+        let (model, ..) = Model::from_file("./tests/scanner/schedule.spl")?;
+        assert_eq!(model.schedules.len(), 1);
+        assert_eq!(model.schedules[0].name(), "Lights");
+
+        // Other files used in docs
+        let json_data = fs::read_to_string("./tests/scanner/schedule_profile_weekly.json")
+            .map_err(|e| e.to_string())?;
+        let weekly: ScheduleProfile =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert!(matches!(weekly, ScheduleProfile::Weekly { .. }));
+
+        let json_data = fs::read_to_string("./tests/scanner/schedule_profile_day_types.json")
+            .map_err(|e| e.to_string())?;
+        let day_types: ScheduleProfile =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        if let ScheduleProfile::DayTypes { holidays, .. } = &day_types {
+            assert_eq!(holidays.len(), 3);
+            assert_eq!(holidays[2].year, Some(2024));
+        } else {
+            return Err("Expecting a DayTypes profile".to_string());
+        }
+
+        let json_data = fs::read_to_string("./tests/scanner/schedule_interpolation.json")
+            .map_err(|e| e.to_string())?;
+        let interpolation: ScheduleInterpolation =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(interpolation, ScheduleInterpolation::Linear);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_daily() -> Result<(), String> {
+        let profile = ScheduleProfile::Daily {
+            values: vec![0., 0., 1., 1.],
+        };
+        let mut schedule = Schedule::new("Lights", profile);
+        let date = |hour: Float| Date {
+            month: 5,
+            day: 12,
+            hour,
+            year: None,
+        };
+
+        // Step
+        assert_eq!(schedule.value(date(0.))?, 0.);
+        assert_eq!(schedule.value(date(11.9))?, 0.);
+        assert_eq!(schedule.value(date(12.))?, 1.);
+        assert_eq!(schedule.value(date(23.9))?, 1.);
+
+        // Linear
+        schedule.set_interpolation(ScheduleInterpolation::Linear);
+        assert!((schedule.value(date(6.))? - 0.).abs() < 1e-9);
+        assert!((schedule.value(date(9.))? - 0.5).abs() < 1e-9);
+        assert!((schedule.value(date(12.))? - 1.).abs() < 1e-9);
+        // Goes back to the beginning of the day
+        assert!((schedule.value(date(21.))? - 0.5).abs() < 1e-9);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_weekly() -> Result<(), String> {
+        let mut days = vec![vec![1.]; 5];
+        days.push(vec![0.]);
+        days.push(vec![0.]);
+        let schedule = Schedule::new("Weekdays", ScheduleProfile::Weekly { days });
+
+        // January 1st is a Monday
+        for day in 1..=5 {
+            let date = Date {
+                month: 1,
+                day,
+                hour: 10.,
+                year: None,
+            };
+            assert_eq!(schedule.value(date)?, 1.);
+        }
+        for day in 6..=7 {
+            let date = Date {
+                month: 1,
+                day,
+                hour: 10.,
+                year: None,
+            };
+            assert_eq!(schedule.value(date)?, 0.);
+        }
+        let date = Date {
+            month: 1,
+            day: 8,
+            hour: 10.,
+            year: None,
+        };
+        assert_eq!(schedule.value(date)?, 1.);
+
+        // Wrong number of days
+        let schedule = Schedule::new(
+            "Wrong",
+            ScheduleProfile::Weekly {
+                days: vec![vec![1.]],
+            },
+        );
+        assert!(schedule.value(date).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_day_types() -> Result<(), String> {
+        let date = |year, month, day, hour| Date {
+            month,
+            day,
+            hour,
+            year,
+        };
+        let profile = ScheduleProfile::DayTypes {
+            weekday: vec![1.],
+            weekend: vec![0.5],
+            holiday: Some(vec![0.]),
+            holidays: vec![date(None, 12, 25, 0.)],
+        };
+        let mut schedule = Schedule::new("Office", profile);
+
+        // Friday, Saturday and Christmas
+        assert_eq!(schedule.value(date(Some(2026), 10, 16, 10.))?, 1.);
+        assert_eq!(schedule.value(date(Some(2026), 10, 17, 10.))?, 0.5);
+        assert_eq!(schedule.value(date(Some(2026), 12, 25, 10.))?, 0.);
+        // Without a year, the year starts on Monday
+        assert_eq!(schedule.value(date(None, 1, 6, 10.))?, 0.5);
+
+        // Interpolates towards the next day type
+        schedule.set_interpolation(ScheduleInterpolation::Linear);
+        assert!((schedule.value(date(Some(2026), 12, 24, 12.))? - 0.5).abs() < 1e-9);
+
+        // Holidays default to weekends
+        let profile = ScheduleProfile::DayTypes {
+            weekday: vec![1.],
+            weekend: vec![0.5],
+            holiday: None,
+            holidays: vec![date(None, 12, 25, 0.)],
+        };
+        let schedule = Schedule::new("Shop", profile);
+        assert_eq!(schedule.value(date(Some(2026), 12, 25, 10.))?, 0.5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_annual() -> Result<(), String> {
+        let values: Vec<Float> = (0..365).map(|i| i as Float).collect();
+        let schedule = Schedule::new("Days", ScheduleProfile::Annual { values });
+        let date = Date {
+            month: 2,
+            day: 1,
+            hour: 13.,
+            year: None,
+        };
+        assert_eq!(schedule.value(date)?, 31.);
+
+        let empty = Schedule::new("Empty", ScheduleProfile::Annual { values: vec![] });
+        assert!(empty.value(date).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_trait() {
+        use ::schedule::Schedule as ScheduleTrait;
+        let schedule = Schedule::new("Constant", ScheduleProfile::Constant { value: 2. });
+        let date = Date {
+            month: 2,
+            day: 1,
+            hour: 13.,
+            year: None,
+        };
+        assert_eq!(schedule.get(date), Some(2.));
+    }
+}
@@ -0,0 +1,297 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use crate::model::Model;
+use crate::simulation_state_element::StateElementField;
+use crate::Float;
+use derive::{ObjectAPI, ObjectIO};
+use geometry::{Point3D, Polygon3D, Ray3D, Vector3D};
+use serde::{Deserialize, Serialize};
+
+/// A set of illuminance sensors (e.g., over a workplane), whose average
+/// illuminance is calculated by the lighting module on every timestep.
+///
+/// The sensors can either be distributed over a polygon given by its `vertices`
+/// (in which case they are placed on a square grid of `spacing` metres,
+/// `height_offset` metres away from the polygon, looking towards its normal)
+/// or be given explicitly through `points` (in which case they look upwards).
+/// Exactly one of these must be given.
+///
+/// ## Examples
+///
+/// #### `.spl`
+///
+/// ```json
+/// {{#include ../../../model/tests/scanner/sensor_grid.spl}}
+/// ```
+///
+/// #### `.json`
+///
+/// ```json
+/// {{#include ../../../model/tests/scanner/sensor_grid.json}}
+/// ```
+#[derive(Debug, ObjectIO, ObjectAPI, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SensorGrid {
+    /// The name of the sensor grid
+    pub name: String,
+
+    /// An array of Numbers representing the vertices of the polygon
+    /// over which the sensors are distributed (e.g., the floor of a room).
+    /// The length of this array must be divisible by 3. The sensors
+    /// look towards the normal of the polygon.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vertices: Option<Polygon3D>,
+
+    /// The distance between sensors distributed over the `vertices`, in m.
+    /// Defaults to 0.5
+    #[serde(skip_serializing_if = "Option::is_none")]
+    spacing: Option<Float>,
+
+    /// The distance between the `vertices` and the sensors, measured
+    /// along the normal of the polygon, in m. Defaults to 0.8 (i.e., the
+    /// height of a desk, if the `vertices` describe the floor)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    height_offset: Option<Float>,
+
+    /// An array of Numbers representing the positions of the sensors, if
+    /// they are not distributed over `vertices`. The length of this
+    /// array must be divisible by 3.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    points: Option<Vec<Float>>,
+
+    /// The average illuminance over the sensors, in lux
+    #[physical]
+    #[serde(skip)]
+    illuminance: StateElementField,
+}
+
+impl SensorGrid {
+    /// Checks that the grid is described either by its `vertices` or
+    /// by its `points` (but not both), and that it contains sensors.
+    pub fn validate(&self) -> Result<(), String> {
+        match (self.vertices(), self.points()) {
+            (Ok(_), Ok(_)) => Err(format!(
+                "SensorGrid '{}' has both 'vertices' and 'points'... only one of them can be given",
+                self.name
+            )),
+            (Err(_), Err(_)) => Err(format!(
+                "SensorGrid '{}' needs either 'vertices' or 'points'",
+                self.name
+            )),
+            _ => Ok(()),
+        }?;
+        if self.sensors()?.is_empty() {
+            return Err(format!("SensorGrid '{}' contains no sensors", self.name));
+        }
+        Ok(())
+    }
+
+    /// Gets the sensors of the grid. The origin of each [`Ray3D`] is the
+    /// position of the sensor, and its direction is the normal of the
+    /// (virtual) illuminance meter.
+    pub fn sensors(&self) -> Result<Vec<Ray3D>, String> {
+        if let Ok(points) = self.points() {
+            if points.len() % 3 != 0 {
+                return Err(format!(
+                    "The length of the 'points' of SensorGrid '{}' must be divisible by 3... found {}",
+                    self.name,
+                    points.len()
+                ));
+            }
+            let up = Vector3D::new(0., 0., 1.);
+            return Ok(points
+                .chunks(3)
+                .map(|p| Ray3D {
+                    origin: Point3D::new(p[0], p[1], p[2]),
+                    direction: up,
+                })
+                .collect());
+        }
+
+        let polygon = self.vertices()?;
+        let spacing = self.spacing_or("", 0.5);
+        if spacing <= 0. {
+            return Err(format!(
+                "The 'spacing' of SensorGrid '{}' must be greater than zero... found {}",
+                self.name, spacing
+            ));
+        }
+        let offset = self.height_offset_or("", 0.8);
+
+        // Local axes over the plane of the polygon
+        let normal = polygon.normal();
+        let vertices = polygon.outer().vertices();
+        let origin = vertices[0];
+        let e1 = (vertices[1] - origin).get_normalized();
+        let e2 = normal.cross(e1);
+
+        let (mut min_u, mut max_u, mut min_v, mut max_v) = (0., 0., 0., 0.);
+        for v in vertices.iter() {
+            let d = *v - origin;
+            let (u, v) = (d * e1, d * e2);
+            min_u = Float::min(min_u, u);
+            max_u = Float::max(max_u, u);
+            min_v = Float::min(min_v, v);
+            max_v = Float::max(max_v, v);
+        }
+
+        let mut ret = Vec::new();
+        let mut u = min_u + spacing / 2.;
+        while u < max_u {
+            let mut v = min_v + spacing / 2.;
+            while v < max_v {
+                let p = origin + e1 * u + e2 * v;
+                if polygon.test_point(p)? {
+                    ret.push(Ray3D {
+                        origin: p + normal * offset,
+                        direction: normal,
+                    });
+                }
+                v += spacing;
+            }
+            u += spacing;
+        }
+        Ok(ret)
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use geometry::Loop3D;
+
+    fn rectangle(width: Float, depth: Float) -> Result<Polygon3D, String> {
+        let mut the_loop = Loop3D::new();
+        the_loop.push(Point3D::new(0., 0., 0.))?;
+        the_loop.push(Point3D::new(width, 0., 0.))?;
+        the_loop.push(Point3D::new(width, depth, 0.))?;
+        the_loop.push(Point3D::new(0., depth, 0.))?;
+        the_loop.close()?;
+        Polygon3D::new(the_loop)
+    }
+
+    #[test]
+    fn serde() -> Result<(), String> {
+        use json5;
+        use std::fs;
+
+        // Hardcode a reference
+        let mut hardcoded_ref = SensorGrid::new("Desk");
+        hardcoded_ref
+            .set_vertices(rectangle(4., 3.)?)
+            .set_spacing(1.)
+            .set_height_offset(0.75);
+
+        // Deserialize from hardcoded string and check they are the same
+        let from_hardcoded_json: SensorGrid = json5::from_str(
+            "{
+            name: 'Desk',
+            vertices: [
+                0, 0, 0,
+                4, 0, 0,
+                4, 3, 0,
+                0, 3, 0
+            ],
+            spacing: 1.0,
+            height_offset: 0.75
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_hardcoded_json)
+        );
+
+        // Read json file (used in DOC), Deserialize, and compare
+        let filename = "./tests/scanner/sensor_grid";
+        let json_file = format!("{}.json", filename);
+        let json_data = fs::read_to_string(json_file).map_err(|e| e.to_string())?;
+        let from_json_file: SensorGrid =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_json_file)
+        );
+
+        // Serialize and deserialize again... check that everythin matches the pattern
+        let rust_json = serde_json::to_string(&hardcoded_ref).map_err(|e| e.to_string())?;
+        let from_serialized: SensorGrid =
+            serde_json::from_str(&rust_json).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_serialized)
+        );
+
+        // check simple
+        let (model, ..) = Model::from_file(format!("{}.spl", filename))?;
+        assert_eq!(model.sensor_grids.len(), 1);
+        assert_eq!(model.sensor_grids[0].name(), "Desk");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_sensors() -> Result<(), String> {
+        // Over a polygon
+        let mut grid = SensorGrid::new("Desk");
+        grid.set_vertices(rectangle(4., 3.)?)
+            .set_spacing(1.)
+            .set_height_offset(0.75);
+        assert!(grid.validate().is_ok());
+        let sensors = grid.sensors()?;
+        assert_eq!(sensors.len(), 12);
+        for s in sensors.iter() {
+            assert!((s.origin.z - 0.75).abs() < 1e-9);
+            assert!(s.direction.compare(Vector3D::new(0., 0., 1.)));
+            assert!(s.origin.x > 0. && s.origin.x < 4.);
+            assert!(s.origin.y > 0. && s.origin.y < 3.);
+        }
+
+        // Explicit points
+        let grid: SensorGrid = json5::from_str(
+            "{
+            name: 'Points',
+            points: [1, 2, 0.8, 3, 2, 0.8]
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert!(grid.validate().is_ok());
+        let sensors = grid.sensors()?;
+        assert_eq!(sensors.len(), 2);
+        assert!(sensors[1].origin.compare(Point3D::new(3., 2., 0.8)));
+
+        // Default spacing
+        let mut grid = SensorGrid::new("Small desk");
+        grid.set_vertices(rectangle(1., 1.)?);
+        assert_eq!(grid.sensors()?.len(), 4);
+
+        // Wrong
+        let grid: SensorGrid = json5::from_str("{ name: 'Nothing' }").map_err(|e| e.to_string())?;
+        assert!(grid.validate().is_err());
+
+        let grid: SensorGrid =
+            json5::from_str("{ name: 'Bad points', points: [1, 2] }").map_err(|e| e.to_string())?;
+        assert!(grid.validate().is_err());
+
+        let mut model = Model::default();
+        assert!(model.add_sensor_grid(grid).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,181 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use crate::Float;
+use derive::ObjectIO;
+use serde::{Deserialize, Serialize};
+
+/// Defines on which side of a [`Fenestration`](crate::Fenestration) a
+/// [`ShadingDevice`] is installed.
+///
+/// ## Example
+///
+/// #### `.json`
+/// ```json
+/// {{#include ../../../model/tests/scanner/shading_position.json}}
+/// ```
+///
+/// > **Note**: This object cannot be declared by itself in a `SIMPLE` model,
+/// as it is always embeded on a `ShadingDevice`
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, ObjectIO, Serialize, Deserialize)]
+#[inline_enum]
+#[serde(deny_unknown_fields)]
+pub enum ShadingPosition {
+    /// The device is inside (e.g., a roller shade or an indoor venetian blind).
+    /// This is the default.
+    #[default]
+    Interior,
+
+    /// The device is outside (e.g., an external blind or an awning-like shade), so
+    /// it also blocks the sun that would reach the glazing.
+    Exterior,
+}
+
+/// A blind or a shade that can be deployed in front of a
+/// [`Fenestration`](crate::Fenestration).
+///
+/// How much the device is deployed is given by the `shade_open_fraction`
+/// of the `Fenestration`, which goes from 0 (i.e., fully deployed) to 1
+/// (i.e., fully retracted, the default) and can be changed through the
+/// Rhai API. The fraction of the radiation that goes through
+/// the shaded area is
+///
+/// ```math
+/// f = \text{open} + (1 - \text{open}) \tau
+/// ```
+///
+/// where $`\tau`$ is the solar or visible transmittance of the device.
+/// Radiation that does not go through the device is assumed to be reflected
+/// back to the exterior.
+///
+/// ## Example
+///
+/// #### `.json`
+/// ```json
+/// {{#include ../../../model/tests/scanner/shading_device.json}}
+/// ```
+///
+/// > **Note**: This object cannot be declared by itself in a `SIMPLE` model,
+/// as it is always embeded on a `Fenestration`
+#[derive(Debug, Clone, ObjectIO, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ShadingDevice {
+    /// The fraction of the solar radiation that goes through
+    /// the device when fully deployed (from 0 to 1)
+    pub solar_transmittance: Float,
+
+    /// The fraction of the visible light that goes through the
+    /// device when fully deployed (from 0 to 1). If none is given, the
+    /// `solar_transmittance` is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    visible_transmittance: Option<Float>,
+
+    /// Whether the device is installed on the inside or the outside
+    /// of the `Fenestration`. If none is given, it is assumed to be `Interior`.
+    #[serde(default)]
+    pub position: ShadingPosition,
+}
+
+impl ShadingDevice {
+    /// Calculates the fraction of the solar radiation that goes through
+    /// the device, given how open it is (from 0 to 1)
+    pub fn solar_factor(&self, open_fraction: Float) -> Float {
+        let open = open_fraction.clamp(0., 1.);
+        open + (1. - open) * self.solar_transmittance
+    }
+
+    /// Calculates the fraction of the visible light that goes through
+    /// the device, given how open it is (from 0 to 1)
+    pub fn visible_factor(&self, open_fraction: Float) -> Float {
+        let open = open_fraction.clamp(0., 1.);
+        let tau = self.visible_transmittance_or("", self.solar_transmittance);
+        open + (1. - open) * tau
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn serde() -> Result<(), String> {
+        use json5;
+        use std::fs;
+
+        // Hardcode a reference
+        let mut hardcoded_ref = ShadingDevice::new(0.1, ShadingPosition::Exterior);
+        hardcoded_ref.set_visible_transmittance(0.05);
+
+        // Deserialize from hardcoded string and check they are the same
+        let from_hardcoded_json: ShadingDevice = json5::from_str(
+            "{
+            solar_transmittance: 0.1,
+            visible_transmittance: 0.05,
+            position: 'Exterior'
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_hardcoded_json)
+        );
+
+        // Read json file (used in DOC), Deserialize, and compare
+        let json_data =
+            fs::read_to_string("./tests/scanner/shading_device.json").map_err(|e| e.to_string())?;
+        let from_json_file: ShadingDevice =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_json_file)
+        );
+
+        // Serialize and deserialize again... check that everythin matches the pattern
+        let rust_json = serde_json::to_string(&hardcoded_ref).map_err(|e| e.to_string())?;
+        let from_serialized: ShadingDevice =
+            serde_json::from_str(&rust_json).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_serialized)
+        );
+
+        // Position
+        let json_data = fs::read_to_string("./tests/scanner/shading_position.json")
+            .map_err(|e| e.to_string())?;
+        let position: ShadingPosition =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(position, ShadingPosition::Exterior);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_factors() {
+        let mut shade = ShadingDevice::new(0.2, ShadingPosition::Interior);
+        assert!((shade.solar_factor(1.) - 1.).abs() < 1e-6);
+        assert!((shade.solar_factor(0.) - 0.2).abs() < 1e-6);
+        assert!((shade.solar_factor(0.5) - 0.6).abs() < 1e-6);
+        // Defaults to solar
+        assert!((shade.visible_factor(0.) - 0.2).abs() < 1e-6);
+
+        shade.set_visible_transmittance(0.1);
+        assert!((shade.visible_factor(0.) - 0.1).abs() < 1e-6);
+        assert!((shade.visible_factor(0.5) - 0.55).abs() < 1e-6);
+    }
+}
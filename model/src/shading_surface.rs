@@ -0,0 +1,127 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use crate::Float;
+use derive::ObjectIO;
+use geometry::Polygon3D;
+use serde::{Deserialize, Serialize};
+
+/// A surface that casts shadows and reflects the sun, but that does not
+/// transfer heat (e.g., an overhang, a fin, or a neighbouring building).
+///
+/// `ShadingSurface`s are only geometry. They have no `Construction` and no
+/// thermal mass, so they are only used by the solar ray tracing and
+/// view factor calculations.
+///
+/// ## Examples
+///
+/// #### `.spl`
+/// ```json
+/// {{#include ../../../model/tests/scanner/shading_surface.spl}}
+/// ```
+///
+/// #### `.json`
+/// ```json
+/// {{#include ../../../model/tests/scanner/shading_surface.json}}
+/// ```
+#[derive(Debug, ObjectIO, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ShadingSurface {
+    /// The name of the shading surface
+    pub name: String,
+
+    /// An array of Numbers representing the vertices of the
+    /// surface. The length of this array must be divisible by 3.
+    pub vertices: Polygon3D,
+
+    /// The fraction of the solar radiation reflected by
+    /// both sides of the surface (from 0 to 1). If none is given,
+    /// a value of $0.3$ is assumed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    solar_reflectance: Option<Float>,
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use crate::Model;
+
+    #[test]
+    fn serde() -> Result<(), String> {
+        use geometry::{Loop3D, Point3D};
+        use json5;
+        use std::fs;
+
+        // Hardcode a reference
+        let mut the_loop = Loop3D::new();
+        the_loop.push(Point3D::new(0., 0., 2.))?;
+        the_loop.push(Point3D::new(1., 0., 2.))?;
+        the_loop.push(Point3D::new(1., -0.5, 2.))?;
+        the_loop.push(Point3D::new(0., -0.5, 2.))?;
+        the_loop.close()?;
+        let mut hardcoded_ref = ShadingSurface::new("Overhang", Polygon3D::new(the_loop)?);
+        hardcoded_ref.set_solar_reflectance(0.2);
+
+        // Deserialize from hardcoded string and check they are the same
+        let from_hardcoded_json: ShadingSurface = json5::from_str(
+            "{
+            name: 'Overhang',
+            vertices: [
+                0, 0, 2,
+                1, 0, 2,
+                1, -0.5, 2,
+                0, -0.5, 2
+            ],
+            solar_reflectance: 0.2
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_hardcoded_json)
+        );
+
+        // Read json file (used in DOC), Deserialize, and compare
+        let filename = "./tests/scanner/shading_surface";
+        let json_file = format!("{}.json", filename);
+        let json_data = fs::read_to_string(json_file).map_err(|e| e.to_string())?;
+        let from_json_file: ShadingSurface =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_json_file)
+        );
+
+        // Serialize and deserialize again... check that everythin matches the pattern
+        let rust_json = serde_json::to_string(&hardcoded_ref).map_err(|e| e.to_string())?;
+        let from_serialized: ShadingSurface =
+            serde_json::from_str(&rust_json).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_serialized)
+        );
+
+        // check simple
+        let (model, ..) = Model::from_file(format!("{}.spl", filename))?;
+        assert_eq!(model.shading_surfaces.len(), 1);
+        assert_eq!(model.shading_surfaces[0].name(), "Overhang");
+
+        Ok(())
+    }
+}
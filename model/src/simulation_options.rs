@@ -18,6 +18,7 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 
+use crate::{ConvectionModel, Float};
 use derive::ObjectIO;
 use serde::{Deserialize, Serialize};
 
@@ -74,6 +75,130 @@ pub struct SolarOptions {
     /// saving time    
     #[serde(skip_serializing_if = "Option::is_none")]
     optical_data_path: Option<String>,
+
+    /// A directory where Daylight Coefficient matrices are cached.
+    ///
+    /// Each matrix is stored in a file named after a hash of the geometry,
+    /// materials and options used for calculating it; so simulating the same
+    /// model again (e.g., with a different weather file or control strategy)
+    /// skips the ray-tracing. Unlike `optical_data_path`, changing the model
+    /// does not require removing the cached data.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    daylight_coefficients_cache: Option<String>,
+
+    /// Number of points sampled over each exterior fenestration when tracing
+    /// the beam solar radiation that enters the spaces.
+    ///
+    /// If given, the direct sun transmitted through the fenestrations is
+    /// traced—on every timestep—onto the interior surfaces it actually hits.
+    /// Otherwise, the sun is represented by the patches of the discretized
+    /// sky, as for the rest of the solar radiation. This matters mostly in
+    /// spaces with large glazed areas.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    n_interior_solar_points: Option<usize>,
+}
+
+/// The options controlling how constructions are discretized (i.e.,
+/// subdivided into elements and timesteps) by the thermal solver
+///
+/// Massive layers are subdivided into elements no thicker than
+/// `max_element_thickness`; then, the model timestep is subdivided until
+/// the resulting Fourier number keeps the explicit scheme stable. Layers
+/// that cannot be made stable within `max_timestep_subdivisions` (or
+/// without going below `min_timestep`) are treated as having no mass.
+/// Coarser options lead to faster but less accurate simulations.
+///
+/// ## Examples
+/// ##### `.spl`
+/// ```json
+/// {{#include ../../../model/tests/scanner/thermal_options.spl}}
+/// ```
+/// ##### `.json`
+/// ```json
+/// {{#include ../../../model/tests/scanner/thermal_options.json}}
+/// ```
+#[derive(Default, Debug, Clone, ObjectIO, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThermalOptions {
+    /// The maximum thickness of each of the elements in which the
+    /// massive layers are subdivided, in m. Defaults to 0.05.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_element_thickness: Option<Float>,
+
+    /// The maximum number of elements in each layer. Thick layers
+    /// that would require more elements than this (according to
+    /// `max_element_thickness`) are subdivided into this number of
+    /// thicker elements instead. Unlimited by default.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_elements_per_layer: Option<usize>,
+
+    /// The minimum timestep used for solving the heat transfer
+    /// through constructions, in seconds. Defaults to 60.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    min_timestep: Option<Float>,
+
+    /// The maximum number of subdivisions of the model timestep
+    /// used for ensuring the stability of the solution. Defaults to 13.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_timestep_subdivisions: Option<usize>,
+
+    /// The number of rays sent from each interior surface when calculating
+    /// the view factors between the surfaces of each space. When given, the
+    /// surfaces exchange long-wave radiation with each other (accounting for
+    /// their emissivities and inter-reflections). Otherwise, they exchange
+    /// it with the air of the space.
+    ///
+    /// A larger number of rays leads to more accurate view factors, but it
+    /// increases the time required for creating the model.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    n_view_factor_rays: Option<usize>,
+
+    /// The correlation used for calculating the convection coefficients of
+    /// the sides of the surfaces that do not face the outdoors. Defaults to
+    /// [`ConvectionModel::Tarp`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    interior_convection_model: Option<ConvectionModel>,
+
+    /// The correlation used for calculating the convection coefficients of
+    /// the sides of the surfaces that face the outdoors. Defaults to
+    /// [`ConvectionModel::Tarp`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exterior_convection_model: Option<ConvectionModel>,
+}
+
+/// The options controlling how often each physics module is marched,
+/// relative to the main timestep of the simulation (i.e., the `n`
+/// timesteps per hour)
+///
+/// Modules that do not integrate over time (e.g., the solar radiation)
+/// can be updated less often for speed, while fast dynamics (e.g., air
+/// flows) can be sub-stepped for accuracy. Either way, the ratio between
+/// the timesteps of a module and the main one must be a whole number, so
+/// that they remain synchronized.
+///
+/// ## Examples
+/// ##### `.spl`
+/// ```json
+/// {{#include ../../../model/tests/scanner/timestep_options.spl}}
+/// ```
+/// ##### `.json`
+/// ```json
+/// {{#include ../../../model/tests/scanner/timestep_options.json}}
+/// ```
+#[derive(Default, Debug, Clone, ObjectIO, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TimestepOptions {
+    /// The number of times per hour that the solar radiation (and the
+    /// electricity generated with it) is calculated. For instance, 1
+    /// means hourly. Defaults to once per timestep.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    solar_timesteps_per_hour: Option<usize>,
+
+    /// The number of times per hour that air flows, humidity and
+    /// contaminants are calculated. For instance, 60 means every
+    /// minute. Defaults to once per timestep.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    air_flow_timesteps_per_hour: Option<usize>,
 }
 
 /***********/
@@ -95,13 +220,15 @@ mod testing {
         hardcoded_ref.set_n_solar_irradiance_points(30);
         hardcoded_ref.set_solar_sky_discretization(2);
         hardcoded_ref.set_optical_data_path("data.json");
+        hardcoded_ref.set_n_interior_solar_points(100);
 
         // Deserialize from hardcoded string and check they are the same
         let from_hardcoded_json: SolarOptions = json5::from_str(
             "{            
             n_solar_irradiance_points: 30,
             solar_sky_discretization: 2,
-            optical_data_path: 'data.json'
+            optical_data_path: 'data.json',
+            n_interior_solar_points: 100
         }",
         )
         .map_err(|e| e.to_string())?;
@@ -136,6 +263,7 @@ mod testing {
             assert_eq!(30, *ops.n_solar_irradiance_points()?);
             assert_eq!(2, *ops.solar_sky_discretization()?);
             assert_eq!("data.json", ops.optical_data_path()?);
+            assert_eq!(100, *ops.n_interior_solar_points()?);
         }
 
         Ok(())
@@ -152,4 +280,71 @@ mod testing {
 
         Ok(())
     }
+
+    #[test]
+    fn serde_thermal_options() -> Result<(), String> {
+        use std::fs;
+
+        let mut hardcoded_ref = ThermalOptions::new();
+        hardcoded_ref
+            .set_max_element_thickness(0.1)
+            .set_max_elements_per_layer(4)
+            .set_max_timestep_subdivisions(20)
+            .set_n_view_factor_rays(3000);
+
+        // Read json file (used in DOC), Deserialize, and compare
+        let json_data = fs::read_to_string("./tests/scanner/thermal_options.json")
+            .map_err(|e| e.to_string())?;
+        let from_json_file: ThermalOptions =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_json_file)
+        );
+
+        // Serialize and deserialize again
+        let rust_json = serde_json::to_string(&hardcoded_ref).map_err(|e| e.to_string())?;
+        let from_serialized: ThermalOptions =
+            serde_json::from_str(&rust_json).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_serialized)
+        );
+
+        // From SPL
+        let (model, ..) = Model::from_file("./tests/scanner/thermal_options.spl")?;
+        let ops = model.thermal_options.ok_or("No thermal options")?;
+        assert_eq!(4, *ops.max_elements_per_layer()?);
+        assert!(ops.min_timestep().is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn serde_timestep_options() -> Result<(), String> {
+        use std::fs;
+
+        let mut hardcoded_ref = TimestepOptions::new();
+        hardcoded_ref
+            .set_solar_timesteps_per_hour(1)
+            .set_air_flow_timesteps_per_hour(60);
+
+        // Read json file (used in DOC), Deserialize, and compare
+        let json_data = fs::read_to_string("./tests/scanner/timestep_options.json")
+            .map_err(|e| e.to_string())?;
+        let from_json_file: TimestepOptions =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_json_file)
+        );
+
+        // From SPL
+        let (model, ..) = Model::from_file("./tests/scanner/timestep_options.spl")?;
+        let ops = model.timestep_options.ok_or("No timestep options")?;
+        assert_eq!(1, *ops.solar_timesteps_per_hour()?);
+        assert_eq!(60, *ops.air_flow_timesteps_per_hour()?);
+
+        Ok(())
+    }
 }
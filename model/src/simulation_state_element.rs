@@ -41,6 +41,20 @@ pub enum SimulationStateElement {
     #[references("Fenestration")]
     FenestrationOpenFraction(usize),
 
+    /// Represents how open (i.e., retracted) is the shading device of
+    /// a fenestration, from 0 (fully deployed) to 1 (fully retracted).
+    /// Contains the Index of fenestration
+    #[operational]
+    #[references("Fenestration")]
+    FenestrationShadeOpenFraction(usize),
+
+    /// Represents how tinted is the switchable glazing of a
+    /// fenestration, from 0 (clear) to 1 (fully tinted).
+    /// Contains the Index of fenestration
+    #[operational]
+    #[references("Fenestration")]
+    FenestrationTintLevel(usize),
+
     /// Represents the heating/cooling energy consumption of a Heating/Cooling system,
     /// in Watts
     ///
@@ -50,12 +64,99 @@ pub enum SimulationStateElement {
     #[references("HVAC")]
     HeatingCoolingPowerConsumption(usize),
 
+    /// Represents the latent heat added (positive) or removed (negative)
+    /// by a Heating/Cooling system when humidifying or dehumidifying
+    /// the air, in Watts
+    ///
+    /// Contains the index of the HVAC in the building's vector,
+    /// and the power.
+    #[operational]
+    #[references("HVAC")]
+    LatentHeatingCoolingPowerConsumption(usize),
+
+    /// Represents the heating setpoint currently used by a
+    /// Heating/Cooling system, in C. It starts as the `heating_setpoint`
+    /// of the system, and can be changed by control algorithms.
+    ///
+    /// Contains the index of the HVAC in the building's vector
+    #[operational]
+    #[references("HVAC")]
+    HeatingSetpoint(usize),
+
+    /// Represents the cooling setpoint currently used by a
+    /// Heating/Cooling system, in C. It starts as the `cooling_setpoint`
+    /// of the system, and can be changed by control algorithms.
+    ///
+    /// Contains the index of the HVAC in the building's vector
+    #[operational]
+    #[references("HVAC")]
+    CoolingSetpoint(usize),
+
+    /// Represents the fraction of the maximum air flow being supplied
+    /// by a ventilation system (from 0 to 1)
+    ///
+    /// Contains the index of the HVAC in the building's vector
+    #[operational]
+    #[references("HVAC")]
+    VentilationAirFlowFraction(usize),
+
     /// Represents the power being consumed by
     /// a Luminaire object, in Watts (luminaire index, power)
     #[operational]
     #[references("Luminaire")]
     LuminairePowerConsumption(usize),
 
+    /// The fraction of its maximum power (from 0 to 1) to which a
+    /// Luminaire is dimmed according to the available daylight
+    #[operational]
+    #[references("Luminaire")]
+    LuminaireDimmingLevel(usize),
+
+    /// Represents the time integral of the error of a
+    /// PID Thermostat, in K s
+    ///
+    /// Contains the index of the Thermostat in the model's vector
+    #[operational]
+    #[references("Thermostat")]
+    ThermostatIntegralError(usize),
+
+    /// Represents the error of a PID Thermostat in the
+    /// previous timestep, in K
+    ///
+    /// Contains the index of the Thermostat in the model's vector
+    #[operational]
+    #[references("Thermostat")]
+    ThermostatPreviousError(usize),
+
+    /// Represents the flow of hot water drawn from a
+    /// WaterTank, in m3/s
+    ///
+    /// Contains the index of the WaterTank in the model's vector
+    #[operational]
+    #[references("WaterTank")]
+    WaterTankDrawFlowRate(usize),
+
+    /// Represents the power being consumed by
+    /// an ElectricEquipment object, in Watts (equipment index, power)
+    #[operational]
+    #[references("ElectricEquipment")]
+    ElectricEquipmentPowerConsumption(usize),
+
+    /// The insulation of the clothing worn by the occupants of
+    /// a space, in clo
+    ///
+    /// Contains the index of the Space in the model's vector
+    #[operational]
+    #[references("Space")]
+    SpaceClothing(usize),
+
+    /// The metabolic rate of the occupants of a space, in met
+    ///
+    /// Contains the index of the Space in the model's vector
+    #[operational]
+    #[references("Space")]
+    SpaceMetabolicRate(usize),
+
     /* SOLAR */
     // Space
     //SpaceTotalSolarHeatGain(usize),
@@ -160,6 +261,42 @@ pub enum SimulationStateElement {
     #[references("Fenestration")]
     FenestrationBackIRIrradiance(usize),
 
+    /// Incident solar irradiance on a Photovoltaic panel, in W/m2
+    #[physical]
+    #[references("Photovoltaic")]
+    PhotovoltaicSolarIrradiance(usize),
+
+    /// The electricity generated by a Photovoltaic panel, in W
+    #[physical]
+    #[references("Photovoltaic")]
+    PhotovoltaicGeneratedPower(usize),
+
+    /// Incident solar irradiance on a SolarCollector, in W/m2
+    #[physical]
+    #[references("SolarCollector")]
+    SolarCollectorSolarIrradiance(usize),
+
+    /// The useful heat delivered by a SolarCollector to its
+    /// WaterTank, in W
+    #[physical]
+    #[references("SolarCollector")]
+    SolarCollectorHeatGain(usize),
+
+    /// The average illuminance over the sensors of a SensorGrid, in lux
+    #[physical]
+    #[references("SensorGrid")]
+    SensorGridIlluminance(usize),
+
+    /// The temperature of the water in a WaterTank, in C
+    #[physical]
+    #[references("WaterTank")]
+    WaterTankWaterTemperature(usize),
+
+    /// The power consumed by the backup heater of a WaterTank, in W
+    #[physical]
+    #[references("WaterTank")]
+    WaterTankHeaterPowerConsumption(usize),
+
     /// Space Air Temperature in C... The elements
     /// are the index of the Space in the Building mode
     /// and the temperature
@@ -191,6 +328,49 @@ pub enum SimulationStateElement {
     #[references("Space")]
     SpaceVentilationTemperature(usize),
 
+    /// The concentration of CO2 in the air of a space, in ppm
+    #[physical]
+    #[references("Space")]
+    SpaceCO2Concentration(usize),
+
+    /// The concentration of a generic contaminant in the air of
+    /// a space, in ppm
+    #[physical]
+    #[references("Space")]
+    SpaceGenericContaminantConcentration(usize),
+
+    /// The humidity ratio of the air in a space, in kg of water
+    /// per kg of dry air
+    #[physical]
+    #[references("Space")]
+    SpaceHumidityRatio(usize),
+
+    /// The mean radiant temperature of a space, in C, calculated by the
+    /// thermal module from the temperatures of its surfaces and fenestrations,
+    /// weighted by the view factors from the centre of the space
+    #[physical]
+    #[references("Space")]
+    SpaceMeanRadiantTemperature(usize),
+
+    /// The Predicted Mean Vote (PMV) of the occupants of a space, from
+    /// -3 (cold) to +3 (hot)
+    #[physical]
+    #[references("Space")]
+    SpacePredictedMeanVote(usize),
+
+    /// The Predicted Percentage of Dissatisfied (PPD) occupants
+    /// of a space, in %
+    #[physical]
+    #[references("Space")]
+    SpacePredictedPercentageDissatisfied(usize),
+
+    /// The electricity consumed by a heat pump, in Watts
+    ///
+    /// Contains the index of the HVAC in the building's vector
+    #[physical]
+    #[references("HVAC")]
+    HeatPumpElectricityConsumption(usize),
+
     /// The volume of air that is moving from one space to another in
     /// a controlled way. In m3/s
     #[physical]
@@ -97,6 +97,96 @@ impl TerrainClass {
     }
 }
 
+/// The source of the undisturbed ground temperature that surfaces
+/// with a [`Boundary::Ground`](crate::Boundary) are exposed to
+///
+/// ## Examples
+///
+/// #### `.json`
+/// ```json
+/// {{#include ../../../model/tests/scanner/ground_temperature_kusuda.json}}
+/// ```
+///
+/// ```json
+/// {{#include ../../../model/tests/scanner/ground_temperature_weather_file.json}}
+/// ```
+///
+/// > **Note**: This object cannot be declared by itself in a `SIMPLE` model,
+/// as it is always embeded on the `SiteDetails`
+#[derive(Debug, PartialEq, Clone, ObjectIO, Serialize, Deserialize)]
+#[serde(tag = "type")]
+#[serde(deny_unknown_fields)]
+pub enum GroundTemperature {
+    /// Interpolates the monthly average ground temperatures reported in the
+    /// weather file (i.e., the `GROUND TEMPERATURES` section of EPW files).
+    /// Values are interpolated linearly in time and depth.
+    WeatherFile {
+        /// The depth, in $`m`$
+        depth: Float,
+    },
+
+    /// The Kusuda and Achenbach (1965) model, which estimates the
+    /// temperature $`T`$ at a depth $`z`$ (in $`m`$) and day of the year $`t`$
+    /// as a sinusoidal wave that is damped and delayed as it penetrates the soil:
+    ///
+    /// ```math
+    /// T = T_{mean} - A e^{-z\sqrt{\frac{\pi}{365\alpha}}} \cos\left( \frac{2\pi}{365}\left(t - t_{0} - \frac{z}{2}\sqrt{\frac{365}{\pi \alpha}} \right) \right)
+    /// ```
+    ///
+    /// where $`\alpha`$ is the `soil_diffusivity` expressed in $`m^2/day`$.
+    Kusuda {
+        /// The annual mean temperature of the soil surface ($`T_{mean}`$, in $`C`$)
+        mean_temperature: Float,
+
+        /// The amplitude of the annual variation of the soil surface
+        /// temperature ($`A`$, in $`C`$)
+        amplitude: Float,
+
+        /// The day of the year (starting at 0 on January 1st) at which the soil
+        /// surface is the coldest ($`t_0`$)
+        coldest_day: Float,
+
+        /// The depth ($`z`$, in $`m`$)
+        depth: Float,
+
+        /// The thermal diffusivity of the soil in $`m^2/s`$
+        soil_diffusivity: Float,
+    },
+}
+
+/// The way in which surfaces with a [`Boundary::Ground`](crate::Boundary)
+/// exchange heat with the [`GroundTemperature`]
+///
+/// ## Examples
+///
+/// #### `.json`
+/// ```json
+/// {{#include ../../../model/tests/scanner/ground_coupling_soil_layer.json}}
+/// ```
+///
+/// > **Note**: This object cannot be declared by itself in a `SIMPLE` model,
+/// as it is always embeded on the `SiteDetails`
+#[derive(Debug, PartialEq, Clone, ObjectIO, Serialize, Deserialize)]
+#[serde(tag = "type")]
+#[serde(deny_unknown_fields)]
+pub enum GroundCoupling {
+    /// The exterior face of the surface exchanges heat by natural
+    /// convection with an environment at the ground temperature, just
+    /// like an `AmbientTemperature` boundary. This is the default.
+    Convective,
+
+    /// The exterior face of the surface is connected to the ground
+    /// temperature through a layer of soil with no thermal mass. That is to
+    /// say, the surface coefficient is $`k/L`$.
+    SoilLayer {
+        /// The thickness of the soil layer ($`L`$, in $`m`$)
+        thickness: Float,
+
+        /// The thermal conductivity of the soil ($`k`$, in $`W/m.K`$)
+        conductivity: Float,
+    },
+}
+
 /// Some information about the site in which the building(s) are located
 ///
 /// # Examples
@@ -146,6 +236,36 @@ pub struct SiteDetails {
     /// synthetic weathers or HVAC sizing, or other applications.
     #[serde(skip_serializing_if = "Option::is_none")]
     standard_meridian: Option<Float>,
+
+    /// The wind pressure coefficients ($`C_p`$) of the exterior surfaces
+    /// of the building(s), used by the [`Infiltration::AirFlowNetwork`](crate::Infiltration).
+    ///
+    /// These are given for equally spaced wind incidence angles, starting at
+    /// $`0^o`$ (i.e., the wind blowing perpendicular to the surface) and covering
+    /// the whole circle. E.g., `[0.6, 0.4, -0.3, -0.5, -0.3, 0.4]` are the coefficients
+    /// every $`60^o`$. Values in between are interpolated linearly.
+    ///
+    /// If none are given, the coefficients are calculated through the
+    /// Swami and Chandra correlation for low-rise buildings of square plan (as described in EnergyPlus'
+    /// Engineering Reference).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    wind_pressure_coefficients: Option<Vec<Float>>,
+
+    /// The concentration of CO2 in the outdoor air, in ppm. If none
+    /// is given, a value of $`400 ppm`$ is assumed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    outdoor_co2_concentration: Option<Float>,
+
+    /// The temperature of the ground in contact with surfaces that have a
+    /// `Ground` boundary. If none is given, the shallowest ground temperature
+    /// in the weather file is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ground_temperature: Option<GroundTemperature>,
+
+    /// How surfaces with a `Ground` boundary exchange heat with the
+    /// `ground_temperature`. Defaults to `Convective`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ground_coupling: Option<GroundCoupling>,
 }
 
 impl SiteDetails {
@@ -188,6 +308,49 @@ impl SiteDetails {
             TerrainClass::default().wind_speed_modifier(height)
         }
     }
+
+    /// Calculates the wind pressure coefficient ($`C_p`$) of a surface for a certain
+    /// wind incidence angle (in degrees), measured between the direction from which
+    /// the wind blows and the outward normal of the surface.
+    ///
+    /// If no `wind_pressure_coefficients` have been set, it uses the Swami and Chandra
+    /// correlation for low-rise buildings with a side ratio of $`1`$:
+    ///
+    /// ```math
+    /// C_p = 0.6 \ln\left(1.248 - 0.703 \sin(\theta/2) + 0.769 \cos(\theta/2) + 0.717 \cos^2(\theta/2) \right)
+    /// ```
+    ///
+    /// ```
+    /// use model::SiteDetails;
+    ///
+    /// let mut details = SiteDetails::default();
+    /// // windward is positive, leeward is negative
+    /// assert!(details.wind_pressure_coefficient(0.) > 0.);
+    /// assert!(details.wind_pressure_coefficient(180.) < 0.);
+    ///
+    /// details.set_wind_pressure_coefficients(vec![0.6, -0.3]);
+    /// assert!((details.wind_pressure_coefficient(90.) - 0.15).abs() < 1e-5);
+    /// ```
+    pub fn wind_pressure_coefficient(&self, incidence_angle: Float) -> Float {
+        let angle = incidence_angle.rem_euclid(360.);
+        match &self.wind_pressure_coefficients {
+            Some(cps) if !cps.is_empty() => {
+                let delta = 360. / cps.len() as Float;
+                let x = angle / delta;
+                let i = (x.floor() as usize) % cps.len();
+                let next = (i + 1) % cps.len();
+                let w = x - x.floor();
+                cps[i] * (1. - w) + cps[next] * w
+            }
+            _ => {
+                // The correlation is symmetric, defined between 0 and 180 degrees
+                let angle = if angle > 180. { 360. - angle } else { angle };
+                let half = (angle / 2.).to_radians();
+                let (s, c) = (half.sin(), half.cos());
+                0.6 * (1.248 - 0.703 * s + 0.769 * c + 0.717 * c * c).ln()
+            }
+        }
+    }
 }
 
 /***********/
@@ -200,6 +363,28 @@ mod testing {
 
     use crate::Model;
 
+    #[test]
+    fn test_wind_pressure_coefficient() {
+        // Swami and Chandra
+        let details = SiteDetails::default();
+        assert!((details.wind_pressure_coefficient(0.) - 0.603).abs() < 1e-3);
+        assert!((details.wind_pressure_coefficient(180.) + 0.364).abs() < 1e-3);
+        assert!(
+            (details.wind_pressure_coefficient(90.) - details.wind_pressure_coefficient(-90.))
+                .abs()
+                < 1e-9
+        );
+
+        // Interpolated
+        let mut details = SiteDetails::default();
+        details.set_wind_pressure_coefficients(vec![0.6, 0.4, -0.3, -0.5, -0.3, 0.4]);
+        assert!((details.wind_pressure_coefficient(0.) - 0.6).abs() < 1e-5);
+        assert!((details.wind_pressure_coefficient(30.) - 0.5).abs() < 1e-5);
+        assert!((details.wind_pressure_coefficient(330.) - 0.5).abs() < 1e-5);
+        assert!((details.wind_pressure_coefficient(-30.) - 0.5).abs() < 1e-5);
+        assert!((details.wind_pressure_coefficient(180.) + 0.5).abs() < 1e-5);
+    }
+
     #[test]
     fn serde_site_details() -> Result<(), String> {
         use json5;
@@ -209,12 +394,19 @@ mod testing {
         let mut hardcoded_ref = SiteDetails::new();
         hardcoded_ref.set_altitude(123.);
         hardcoded_ref.set_terrain(TerrainClass::Urban);
+        hardcoded_ref.set_ground_temperature(GroundTemperature::WeatherFile { depth: 0.5 });
+        hardcoded_ref.set_ground_coupling(GroundCoupling::SoilLayer {
+            thickness: 0.5,
+            conductivity: 1.5,
+        });
 
         // Deserialize from hardcoded string and check they are the same
         let from_hardcoded_json: SiteDetails = json5::from_str(
             "{
             altitude: 123.0,
-            terrain: 'Urban'
+            terrain: 'Urban',
+            ground_temperature: { type: 'WeatherFile', depth: 0.5 },
+            ground_coupling: { type: 'SoilLayer', thickness: 0.5, conductivity: 1.5 },
         }",
         )
         .map_err(|e| e.to_string())?;
@@ -259,6 +451,94 @@ mod testing {
         Ok(())
     }
 
+    #[test]
+    fn serde_ground_temperature() -> Result<(), String> {
+        use json5;
+        use std::fs;
+
+        // Hardcode a reference
+        let hardcoded_ref = GroundTemperature::Kusuda {
+            mean_temperature: 12.5,
+            amplitude: 8.,
+            coldest_day: 20.,
+            depth: 1.,
+            soil_diffusivity: 5e-7,
+        };
+
+        // Deserialize from hardcoded string and check they are the same
+        let from_hardcoded_json: GroundTemperature = json5::from_str(
+            "{
+            type: 'Kusuda',
+            mean_temperature: 12.5,
+            amplitude: 8.0,
+            coldest_day: 20.0,
+            depth: 1.0,
+            soil_diffusivity: 5e-7,
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert_eq!(hardcoded_ref, from_hardcoded_json);
+
+        // Read json files (used in DOC), Deserialize, and compare
+        let json_data = fs::read_to_string("./tests/scanner/ground_temperature_kusuda.json")
+            .map_err(|e| e.to_string())?;
+        let from_json_file: GroundTemperature =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(hardcoded_ref, from_json_file);
+
+        let json_data = fs::read_to_string("./tests/scanner/ground_temperature_weather_file.json")
+            .map_err(|e| e.to_string())?;
+        let from_json_file: GroundTemperature =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(
+            GroundTemperature::WeatherFile { depth: 0.5 },
+            from_json_file
+        );
+
+        // Serialize and deserialize again... check that everythin matches the pattern
+        let rust_json = serde_json::to_string(&hardcoded_ref).map_err(|e| e.to_string())?;
+        let from_serialized: GroundTemperature =
+            serde_json::from_str(&rust_json).map_err(|e| e.to_string())?;
+        assert_eq!(hardcoded_ref, from_serialized);
+
+        Ok(())
+    }
+
+    #[test]
+    fn serde_ground_coupling() -> Result<(), String> {
+        use json5;
+        use std::fs;
+
+        // Hardcode a reference
+        let hardcoded_ref = GroundCoupling::SoilLayer {
+            thickness: 0.5,
+            conductivity: 1.5,
+        };
+
+        // Read json file (used in DOC), Deserialize, and compare
+        let json_data = fs::read_to_string("./tests/scanner/ground_coupling_soil_layer.json")
+            .map_err(|e| e.to_string())?;
+        let from_json_file: GroundCoupling =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(hardcoded_ref, from_json_file);
+
+        // Unit variant
+        let convective: GroundCoupling =
+            json5::from_str("{type: 'Convective'}").map_err(|e| e.to_string())?;
+        assert_eq!(convective, GroundCoupling::Convective);
+
+        // check simple
+        let (model, ..) = Model::from_file("./tests/scanner/site_details.spl")?;
+        let details = model.site_details.ok_or("No site details!")?;
+        assert_eq!(details.ground_coupling()?, &hardcoded_ref);
+        assert_eq!(
+            details.ground_temperature()?,
+            &GroundTemperature::WeatherFile { depth: 0.5 }
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn site_details_from_file() -> Result<(), String> {
         let (model, ..) = Model::from_file("./tests/box_with_window.spl")?;
@@ -0,0 +1,198 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use crate::model::Model;
+use crate::photovoltaic::{panel_area, validate_panel};
+use crate::simulation_state_element::StateElementField;
+use crate::Float;
+use derive::{ObjectAPI, ObjectIO};
+use geometry::Polygon3D;
+use serde::{Deserialize, Serialize};
+
+/// A solar thermal collector that heats the water in a [`WaterTank`](crate::WaterTank).
+///
+/// Like a [`Photovoltaic`](crate::Photovoltaic) panel, collectors can either be
+/// installed on the outdoor side of a `Surface`, or be described by their own `vertices`
+/// (which do not cast shadows). Exactly one of these must be given.
+///
+/// The useful heat delivered to the `water_tank` (in W) is calculated
+/// based on a linear collector efficiency model:
+///
+/// ```math
+/// Q = A \times f_{area} \left( \eta_0 G - U (T_{tank} - T_{out}) \right)
+/// ```
+///
+/// where $`G`$ is the incident solar irradiance (in W/m2), $`A`$ is the area of the
+/// collector or `Surface`, $`\eta_0`$ is the `optical_efficiency`, $`U`$ is the
+/// `heat_loss_coefficient`, $`T_{tank}`$ is the temperature of the water in the tank and
+/// $`T_{out}`$ is the outdoor dry bulb temperature. The circulation
+/// pump only runs when this value is positive, so the collector never cools the tank.
+///
+/// ## Examples
+///
+/// #### `.spl`
+///
+/// ```json
+/// {{#include ../../../model/tests/scanner/solar_collector.spl}}
+/// ```
+///
+/// #### `.json`
+///
+/// ```json
+/// {{#include ../../../model/tests/scanner/solar_collector.json}}
+/// ```
+#[derive(Debug, ObjectIO, ObjectAPI, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SolarCollector {
+    /// The name of the collector
+    pub name: String,
+
+    /// The name of the [`WaterTank`](crate::WaterTank) heated by this collector
+    pub water_tank: String,
+
+    /// The name of the `Surface` on which the collector is installed. The
+    /// collector will receive the solar radiation incident on the
+    /// outdoor side of this surface.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    surface: Option<String>,
+
+    /// An array of Numbers representing the vertices of the
+    /// collector, if it is not installed on a `Surface`. The length of this
+    /// array must be divisible by 3. The collector faces the direction
+    /// of its normal.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vertices: Option<Polygon3D>,
+
+    /// The fraction of the area of the `surface` or `vertices`
+    /// that is covered by the absorber (from 0 to 1). Defaults to 1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    area_fraction: Option<Float>,
+
+    /// The efficiency of the collector when its temperature
+    /// equals the outdoor temperature. Defaults to 0.75
+    #[serde(skip_serializing_if = "Option::is_none")]
+    optical_efficiency: Option<Float>,
+
+    /// The heat lost by the collector per unit of area and per degree of
+    /// difference between the water and the outdoor air, in W/m2K. Defaults to 3.5
+    #[serde(skip_serializing_if = "Option::is_none")]
+    heat_loss_coefficient: Option<Float>,
+
+    /// The solar irradiance incident on the collector, in W/m2
+    #[physical]
+    #[serde(skip)]
+    incident_solar_irradiance: StateElementField,
+
+    /// The useful heat delivered by the collector to the water tank, in W
+    #[physical]
+    #[serde(skip)]
+    heat_gain: StateElementField,
+}
+
+impl SolarCollector {
+    /// Checks that the collector is either installed on a `Surface` or
+    /// described by its own `vertices`, but not both.
+    pub fn validate(&self) -> Result<(), String> {
+        validate_panel(
+            "SolarCollector",
+            &self.name,
+            self.surface(),
+            self.vertices(),
+        )
+    }
+
+    /// Calculates the area of the absorber, in m2
+    pub fn area(&self, model: &Model) -> Result<Float, String> {
+        panel_area(
+            "SolarCollector",
+            &self.name,
+            model,
+            self.surface(),
+            self.vertices(),
+            self.area_fraction_or("", 1.),
+        )
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn serde() -> Result<(), String> {
+        use json5;
+        use std::fs;
+
+        // Hardcode a reference
+        let mut hardcoded_ref = SolarCollector::new("Roof Collector", "Hot Water Tank");
+        hardcoded_ref
+            .set_surface("Roof")
+            .set_area_fraction(0.5)
+            .set_optical_efficiency(0.8)
+            .set_heat_loss_coefficient(4.);
+
+        // Deserialize from hardcoded string and check they are the same
+        let from_hardcoded_json: SolarCollector = json5::from_str(
+            "{
+            name: 'Roof Collector',
+            water_tank: 'Hot Water Tank',
+            surface: 'Roof',
+            area_fraction: 0.5,
+            optical_efficiency: 0.8,
+            heat_loss_coefficient: 4
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_hardcoded_json)
+        );
+
+        // Read json file (used in DOC), Deserialize, and compare
+        let filename = "./tests/scanner/solar_collector";
+        let json_file = format!("{}.json", filename);
+        let json_data = fs::read_to_string(json_file).map_err(|e| e.to_string())?;
+        let from_json_file: SolarCollector =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_json_file)
+        );
+
+        // Serialize and deserialize again... check that everythin matches the pattern
+        let rust_json = serde_json::to_string(&hardcoded_ref).map_err(|e| e.to_string())?;
+        let from_serialized: SolarCollector =
+            serde_json::from_str(&rust_json).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_serialized)
+        );
+
+        // check simple
+        let (model, ..) = Model::from_file(format!("{}.spl", filename))?;
+        assert_eq!(model.solar_collectors.len(), 1);
+        assert_eq!(model.solar_collectors[0].name(), "Roof Collector");
+
+        // Needs geometry
+        let collector = SolarCollector::new("Collector", "Hot Water Tank");
+        assert!(collector.validate().is_err());
+
+        Ok(())
+    }
+}
@@ -101,6 +101,23 @@ pub struct Space {
     #[serde(skip_serializing_if = "Option::is_none")]
     infiltration: Option<Infiltration>,
 
+    /// The name of the [`Schedule`](crate::Schedule) that multiplies the
+    /// flow of the `infiltration`. If none is given, the infiltration is
+    /// not modified.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    infiltration_schedule: Option<String>,
+
+    /// The name of the [`Schedule`](crate::Schedule) containing the number
+    /// of people in the space over time
+    #[serde(skip_serializing_if = "Option::is_none")]
+    occupancy_schedule: Option<String>,
+
+    /// The rate at which a generic contaminant is released into the
+    /// air of the space, in $`m^3/s`$. If none is given, the concentration
+    /// of generic contaminants is not calculated.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    generic_contaminant_generation: Option<Float>,
+
     // The importance of this space over time
     // importance : Option<Box<dyn Schedule<Float>>>,
     /// The building in which this `Space` is inserted
@@ -112,12 +129,28 @@ pub struct Space {
     #[serde(skip_serializing_if = "Option::is_none")]
     storey: Option<usize>,
 
+    /// The number of identical copies of this space in the building
+    /// (e.g., the same apartment repeated on several floors of a tower).
+    /// The space is simulated only once, but its floor area and the
+    /// energy consumed by its luminaires, electric equipment and HVAC
+    /// systems are reported multiplied by this number. Defaults to 1.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    multiplier: Option<usize>,
+
     /// The purposes in a room. It can have multiple
     /// purposes (e.g., a Living/Dining/Kithen space)
     #[serde(skip_serializing_if = "Vec::is_empty")]
     #[serde(default)]
     pub purposes: Vec<SpacePurpose>,
 
+    #[operational]
+    #[serde(skip)]
+    clothing: StateElementField,
+
+    #[operational]
+    #[serde(skip)]
+    metabolic_rate: StateElementField,
+
     #[physical]
     #[serde(skip)]
     dry_bulb_temperature: StateElementField,
@@ -145,6 +178,30 @@ pub struct Space {
     #[physical]
     #[serde(skip)]
     ventilation_temperature: StateElementField,
+
+    #[physical]
+    #[serde(skip)]
+    co2_concentration: StateElementField,
+
+    #[physical]
+    #[serde(skip)]
+    generic_contaminant_concentration: StateElementField,
+
+    #[physical]
+    #[serde(skip)]
+    humidity_ratio: StateElementField,
+
+    #[physical]
+    #[serde(skip)]
+    mean_radiant_temperature: StateElementField,
+
+    #[physical]
+    #[serde(skip)]
+    predicted_mean_vote: StateElementField,
+
+    #[physical]
+    #[serde(skip)]
+    predicted_percentage_dissatisfied: StateElementField,
 }
 
 /***********/
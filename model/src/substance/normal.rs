@@ -22,12 +22,47 @@ use crate::Float;
 use derive::ObjectIO;
 use serde::{Deserialize, Serialize};
 
+/// The latent heat storage of a Phase Change Material (PCM), described
+/// by the relationship between its specific enthalpy and its temperature.
+///
+/// Within the phase change range (i.e., between `min_temperature` and
+/// `max_temperature`) the apparent specific heat capacity of the substance is
+/// the derivative of the enthalpy curve, $`c_p = dh/dT`$. Outside of that range,
+/// the `specific_heat_capacity` of the [`Normal`] substance is used.
+///
+/// ## Example
+///
+/// #### `.json`
+///
+/// ```json
+/// {{#include ../../../model/tests/scanner/phase_change.json}}
+/// ```
+///
+/// > **Note**: This object cannot be declared by itself in a `SIMPLE` model,
+/// as it is always embeded on a `Normal` substance
+#[derive(Clone, Debug, ObjectIO, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PhaseChange {
+    /// The coefficients of the polynomial $`h(T) = a_0 + a_1 T + a_2 T^2 + ...`$
+    /// describing the specific enthalpy of the substance (in J/kg) as a function
+    /// of its temperature (in C), starting from the constant term. A maximum of
+    /// 12 coefficients can be given.
+    pub enthalpy: Vec<Float>,
+
+    /// The temperature (in C) at which the phase change starts
+    pub min_temperature: Float,
+
+    /// The temperature (in C) at which the phase change ends
+    pub max_temperature: Float,
+}
+
 /// Represents a physical material
 /// with common physical properties (e.g.,
 /// timber, concrete, brick, glass).  In other words,
 /// it cannot change its thermal/optical properties based
-/// on its internal state (e.g., it cannot change its specific heat
-/// based on temperature, like Gas or Phase Change Materials).
+/// on its internal state (e.g., it cannot change its conductivity
+/// based on temperature, like Gas). The exception is the specific heat
+/// capacity of Phase Change Materials, which can be given by a [`PhaseChange`].
 ///
 /// ## Examples
 ///
@@ -62,6 +97,11 @@ pub struct Normal {
     #[serde(skip_serializing_if = "Option::is_none")]
     density: Option<Float>,
 
+    /// The latent heat storage of the substance, if it is a Phase
+    /// Change Material (e.g., a PCM wallboard)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    phase_change: Option<PhaseChange>,
+
     /* SOLAR RADIATION PROPERTIES */
     /// Solar absorbtance (from 0 to 1) at the front side
     /// (Front being the side closer to the first material in a construction)
@@ -128,6 +168,26 @@ pub struct Normal {
     #[serde(skip_serializing_if = "Option::is_none")]
     visible_transmissivity: Option<Float>,
 
+    /* SPECTRAL PROPERTIES */
+    /// The front reflectance (from 0 to 1) at several wavelength bands of
+    /// equal width, covering the visible range (i.e., from 380 to 780 nm).
+    /// Any number of bands can be given (e.g., 40 values would mean
+    /// bands of 10 nm).
+    ///
+    /// This is only used for spectral lighting calculations. If
+    /// not given, `front_visible_reflectance` is used for all bands.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    front_spectral_reflectance: Option<Vec<Float>>,
+
+    /// The transmittance at normal incidence (from 0 to 1) at several
+    /// wavelength bands of equal width, covering the visible
+    /// range (i.e., from 380 to 780 nm), as measured by a spectrometer.
+    ///
+    /// This is only used for spectral lighting calculations. If
+    /// not given, `visible_transmissivity` is used for all bands.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    spectral_transmittance: Option<Vec<Float>>,
+
     /* INFRARED RADIATION PROPERTIES */
     /// Front thermal absorbtance (i.e., emissitivy; from 0 to 1)
     /// (Front being the side closer to the first material in a construction)
@@ -214,6 +274,58 @@ mod testing {
         Ok(())
     }
 
+    #[test]
+    fn serde_phase_change() -> Result<(), String> {
+        use std::fs;
+
+        // Deserialize from hardcoded string and check they are the same
+        let mut hardcoded_ref = PhaseChange::new(21., 25.);
+        hardcoded_ref.enthalpy = vec![-1.1e5, 5000., 100.];
+        let from_hardcoded_json: PhaseChange = json5::from_str(
+            "{
+            enthalpy: [-1.1e5, 5000, 100],
+            min_temperature: 21,
+            max_temperature: 25,
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_hardcoded_json)
+        );
+
+        // Read json file (used in DOC), Deserialize, and compare
+        let json_data =
+            fs::read_to_string("./tests/scanner/phase_change.json").map_err(|e| e.to_string())?;
+        let from_json_file: PhaseChange =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_json_file)
+        );
+
+        // Within a Normal substance
+        let normal: Normal = json5::from_str(
+            "{
+            name: 'PCM board',
+            thermal_conductivity: 0.2,
+            density: 800,
+            specific_heat_capacity: 2000,
+            phase_change: {
+                enthalpy: [-1.1e5, 5000, 100],
+                min_temperature: 21,
+                max_temperature: 25,
+            }
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        let pc = normal.phase_change()?;
+        assert_eq!(pc.enthalpy.len(), 3);
+        assert!((pc.max_temperature - 25.).abs() < 1e-9);
+
+        Ok(())
+    }
+
     #[test]
     fn test_substance_basic() -> Result<(), String> {
         let s_name = "The Normal".to_string();
@@ -17,7 +17,7 @@ LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
 OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
-use crate::{Boundary, Model};
+use crate::{Boundary, ConvectionModel, Crack, Model, ThermalBridge};
 use crate::{Float, SurfaceTrait};
 use crate::{SimulationState, SimulationStateElement, SimulationStateHeader};
 use derive::{ObjectAPI, ObjectIO};
@@ -120,6 +120,19 @@ pub struct Surface {
     #[serde(skip_serializing_if = "Option::is_none")]
     category: Option<SurfaceType>,
 
+    /// The [`Crack`](crate::Crack) through which air leaks across this
+    /// surface. This is only used when the [`Space`](crate::Space)s at
+    /// each side of it have an [`Infiltration::AirFlowNetwork`](crate::Infiltration).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    crack: Option<Crack>,
+
+    /// The linear [`ThermalBridge`](crate::ThermalBridge)s along the edges and
+    /// junctions of this surface (e.g., the perimeter of a slab). These are added
+    /// to the ones declared in its `Construction`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    #[serde(default)]
+    pub thermal_bridges: Vec<ThermalBridge>,
+
     /// The front convection coefficient, in `W/m2K`
     ///
     /// This value fixes the value, so the automatic calculations
@@ -134,6 +147,16 @@ pub struct Surface {
     #[serde(skip_serializing_if = "Option::is_none")]
     precalculated_back_convection_coef: Option<Float>,
 
+    /// The correlation used for calculating the front convection coefficient,
+    /// overriding the one set in the [`ThermalOptions`](crate::ThermalOptions)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    front_convection_model: Option<ConvectionModel>,
+
+    /// The correlation used for calculating the back convection coefficient,
+    /// overriding the one set in the [`ThermalOptions`](crate::ThermalOptions)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    back_convection_model: Option<ConvectionModel>,
+
     /* STATE */
     #[physical("front_temperature")]
     #[serde(skip)]
@@ -454,6 +477,27 @@ impl SurfaceTrait for Surface {
     }
 }
 
+impl Surface {
+    /// Calculates the heat transfer coefficient (in $`W/K`$) of all the
+    /// [`ThermalBridge`]s in this surface and in its `Construction`
+    pub fn thermal_bridges_heat_transfer_coefficient(
+        &self,
+        model: &Model,
+    ) -> Result<Float, String> {
+        let construction = model.get_construction(&self.construction)?;
+        let area = self.area();
+
+        let mut ret = 0.0;
+        for bridge in self.thermal_bridges.iter() {
+            ret += bridge.heat_transfer_coefficient(1.)?;
+        }
+        for bridge in construction.thermal_bridges.iter() {
+            ret += bridge.heat_transfer_coefficient(area)?;
+        }
+        Ok(ret)
+    }
+}
+
 /***********/
 /* TESTING */
 /***********/
@@ -558,6 +602,46 @@ mod testing {
         Ok(())
     }
 
+    #[test]
+    fn test_thermal_bridges() -> Result<(), String> {
+        let mut model = Model::default();
+        let mut construction = crate::Construction::new("the construction");
+        construction
+            .thermal_bridges
+            .push(ThermalBridge::new(0.02, 1.5));
+        model.add_construction(construction);
+
+        let mut outer = Loop3D::new();
+        outer.push(Point3D::new(0., 0., 0.))?;
+        outer.push(Point3D::new(2., 0., 0.))?;
+        outer.push(Point3D::new(2., 2., 0.))?;
+        outer.push(Point3D::new(0., 2., 0.))?;
+        outer.close()?;
+        let polygon = Polygon3D::new(outer)?;
+
+        let mut surf = Surface::new(
+            "Slab",
+            polygon,
+            "the construction",
+            Boundary::Ground,
+            Boundary::Outdoor,
+        );
+        // Only the construction: 0.02 * 1.5 * 4
+        assert!((surf.thermal_bridges_heat_transfer_coefficient(&model)? - 0.12).abs() < 1e-5);
+
+        // Add the perimeter
+        surf.thermal_bridges.push(ThermalBridge::new(0.5, 8.));
+        assert!((surf.thermal_bridges_heat_transfer_coefficient(&model)? - 4.12).abs() < 1e-5);
+
+        // Wrong lengths are caught
+        surf.thermal_bridges.push(ThermalBridge::new(0.5, -8.));
+        assert!(surf
+            .thermal_bridges_heat_transfer_coefficient(&model)
+            .is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn test_surface_basic() -> Result<(), String> {
         let construction = "the construction".to_string();
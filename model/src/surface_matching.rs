@@ -0,0 +1,345 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::error_msgs::print_warning_no_module;
+use crate::{Boundary, Float, Model, Surface, SurfaceTrait, SurfaceType};
+use geometry::{Loop3D, Vector3D};
+use std::sync::Arc;
+
+/// The tolerances used for deciding whether two [`Surface`]s
+/// are coincident, when matching them (see [`Model::match_surfaces`])
+#[derive(Debug, Clone, Copy)]
+pub struct SurfaceMatchingOptions {
+    /// The maximum distance between the matching vertices of two
+    /// surfaces, in `m`
+    pub distance_tolerance: Float,
+
+    /// The maximum difference between the areas of two surfaces,
+    /// as a fraction of the largest of them
+    pub area_tolerance: Float,
+}
+
+impl Default for SurfaceMatchingOptions {
+    fn default() -> Self {
+        Self {
+            distance_tolerance: 0.01,
+            area_tolerance: 0.01,
+        }
+    }
+}
+
+/// The side of a [`Surface`] that leads to a space, and the side that
+/// has not been connected to anything yet (i.e., it is `Outdoor`)
+struct Sides {
+    /// The name of the space at one side of the surface
+    space: String,
+
+    /// Whether the `Outdoor` boundary is the front one
+    free_is_front: bool,
+}
+
+impl Sides {
+    /// Gets the sides of a surface that can be matched... returns `None`
+    /// if the surface does not have exactly one `Space` boundary and an
+    /// `Outdoor` one
+    fn new(s: &Surface) -> Option<Self> {
+        match (&s.front_boundary, &s.back_boundary) {
+            (Boundary::Outdoor, Boundary::Space { space }) => Some(Self {
+                space: space.clone(),
+                free_is_front: true,
+            }),
+            (Boundary::Space { space }, Boundary::Outdoor) => Some(Self {
+                space: space.clone(),
+                free_is_front: false,
+            }),
+            _ => None,
+        }
+    }
+
+    /// The direction towards which the free side of the surface is facing
+    fn free_direction(&self, normal: Vector3D) -> Vector3D {
+        if self.free_is_front {
+            normal
+        } else {
+            normal * -1.
+        }
+    }
+}
+
+/// Checks whether every vertex in `a` is within `distance` of a vertex in `b`,
+/// regardless of the order of the vertices
+fn same_vertices(a: &Loop3D, b: &Loop3D, distance: Float) -> bool {
+    let d2 = distance * distance;
+    a.len() == b.len()
+        && a.vertices()
+            .iter()
+            .all(|p| b.vertices().iter().any(|q| p.squared_distance(*q) <= d2))
+}
+
+/// Checks whether `a` and `b` are the two faces of the same partition—i.e., they
+/// overlap and the free side of each of them faces the space of the other one.
+fn coincident(
+    a: &Surface,
+    a_sides: &Sides,
+    b: &Surface,
+    b_sides: &Sides,
+    options: &SurfaceMatchingOptions,
+) -> bool {
+    let (area_a, area_b) = (a.area(), b.area());
+    if (area_a - area_b).abs() > options.area_tolerance * area_a.max(area_b) {
+        return false;
+    }
+    if a.vertices.n_inner_loops() != b.vertices.n_inner_loops()
+        || !same_vertices(a.outer(), b.outer(), options.distance_tolerance)
+    {
+        return false;
+    }
+    // The free side of 'a' faces the space of 'b', and vice versa
+    let a_free = a_sides.free_direction(a.normal());
+    let b_free = b_sides.free_direction(b.normal());
+    a_free * b_free < 0.0
+}
+
+/// The category of a surface that, after being matched, connects two spaces
+fn interior_category(category: &SurfaceType) -> SurfaceType {
+    match category {
+        SurfaceType::ExteriorWall => SurfaceType::InteriorWall,
+        SurfaceType::ExteriorFloor | SurfaceType::GroundFloor => SurfaceType::InteriorFloor,
+        SurfaceType::Roof => SurfaceType::Ceiling,
+        other => other.clone(),
+    }
+}
+
+/// Finds pairs of coincident surfaces at each side of a partition between two
+/// spaces, and replaces each pair by a single surface whose boundaries lead
+/// to both spaces. Returns the number of pairs that were merged.
+pub(crate) fn match_surfaces(
+    model: &mut Model,
+    options: &SurfaceMatchingOptions,
+) -> Result<usize, String> {
+    if options.distance_tolerance < 0.0 || options.area_tolerance < 0.0 {
+        return Err("Surface matching tolerances cannot be negative".to_string());
+    }
+
+    let is_parent = |s: &Surface| {
+        model
+            .fenestrations
+            .iter()
+            .any(|f| f.parent_surface().map(|p| p == s.name()).unwrap_or(false))
+    };
+
+    let sides: Vec<Option<Sides>> = model.surfaces.iter().map(|s| Sides::new(s)).collect();
+    let mut replaced: Vec<Option<Arc<Surface>>> = vec![None; model.surfaces.len()];
+    let mut removed = vec![false; model.surfaces.len()];
+    let mut n_pairs = 0;
+
+    for i in 0..model.surfaces.len() {
+        let a_sides = match &sides[i] {
+            Some(v) if !removed[i] && replaced[i].is_none() => v,
+            _ => continue,
+        };
+        let a = &model.surfaces[i];
+        for j in i + 1..model.surfaces.len() {
+            let b_sides = match &sides[j] {
+                Some(v) if !removed[j] && replaced[j].is_none() => v,
+                _ => continue,
+            };
+            let b = &model.surfaces[j];
+            if a_sides.space == b_sides.space || !coincident(a, a_sides, b, b_sides, options) {
+                continue;
+            }
+
+            // Keep the surface that contains fenestrations, if any
+            let (keep, remove, keep_sides, other_sides) = match (is_parent(a), is_parent(b)) {
+                (_, false) => (i, j, a_sides, b_sides),
+                (false, true) => (j, i, b_sides, a_sides),
+                (true, true) => {
+                    print_warning_no_module(format!(
+                        "Surfaces '{}' and '{}' are coincident, but they cannot be matched because both of them contain fenestrations",
+                        a.name(),
+                        b.name()
+                    ));
+                    continue;
+                }
+            };
+            let (kept, other) = (&model.surfaces[keep], &model.surfaces[remove]);
+            if kept.construction != other.construction {
+                print_warning_no_module(format!(
+                    "Matching surfaces '{}' and '{}', which have different constructions... using '{}'",
+                    kept.name(),
+                    other.name(),
+                    kept.construction
+                ));
+            }
+
+            let mut merged = (**kept).clone();
+            let boundary = Boundary::Space {
+                space: other_sides.space.clone(),
+            };
+            if keep_sides.free_is_front {
+                merged.front_boundary = boundary;
+            } else {
+                merged.back_boundary = boundary;
+            }
+            // The floor of a space on top of another one is an interior floor,
+            // even if the surface we keep is the roof of the space below
+            let is_floor = |s: &Surface| {
+                matches!(
+                    s.category(),
+                    Ok(SurfaceType::GroundFloor
+                        | SurfaceType::ExteriorFloor
+                        | SurfaceType::InteriorFloor)
+                )
+            };
+            if is_floor(other) {
+                merged.set_category(SurfaceType::InteriorFloor);
+            } else if let Ok(category) = merged.category() {
+                let category = interior_category(category);
+                merged.set_category(category);
+            }
+
+            replaced[keep] = Some(Arc::new(merged));
+            removed[remove] = true;
+            n_pairs += 1;
+            break;
+        }
+    }
+
+    let surfaces = std::mem::take(&mut model.surfaces);
+    model.surfaces = surfaces
+        .into_iter()
+        .zip(replaced)
+        .zip(removed)
+        .filter_map(|((s, r), rm)| if rm { None } else { Some(r.unwrap_or(s)) })
+        .collect();
+
+    Ok(n_pairs)
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use crate::{Construction, Space};
+    use geometry::{Point3D, Polygon3D};
+
+    fn wall(name: &str, space: &str, pts: [(Float, Float, Float); 4]) -> Result<Surface, String> {
+        let mut outer = Loop3D::new();
+        for (x, y, z) in pts {
+            outer.push(Point3D::new(x, y, z))?;
+        }
+        outer.close()?;
+        let mut s = Surface::new(
+            name,
+            Polygon3D::new(outer)?,
+            "the construction",
+            Boundary::Outdoor,
+            Boundary::Space {
+                space: space.to_string(),
+            },
+        );
+        s.set_category(SurfaceType::ExteriorWall);
+        Ok(s)
+    }
+
+    fn model() -> Result<Model, String> {
+        let mut model = Model::default();
+        model.add_construction(Construction::new("the construction"));
+        model.add_space(Space::new("Kitchen"));
+        model.add_space(Space::new("Living"));
+
+        // The normals of both walls point away from their own space
+        let a = [(1., 0., 0.), (1., 1., 0.), (1., 1., 3.), (1., 0., 3.)];
+        model.add_surface(wall("Kitchen East", "Kitchen", a)?)?;
+        // slightly off, but within the default tolerance
+        let b = [
+            (1.001, 1., 0.),
+            (1.001, 0., 0.),
+            (1.001, 0., 3.),
+            (1.001, 1., 3.),
+        ];
+        model.add_surface(wall("Living West", "Living", b)?)?;
+
+        let c = [(0., 0., 0.), (0., 0., 3.), (0., 1., 3.), (0., 1., 0.)];
+        model.add_surface(wall("Kitchen West", "Kitchen", c)?)?;
+        Ok(model)
+    }
+
+    #[test]
+    fn test_match_surfaces() -> Result<(), String> {
+        let mut model = model()?;
+        let n = model.match_surfaces(&SurfaceMatchingOptions::default())?;
+        assert_eq!(n, 1);
+        assert_eq!(model.surfaces.len(), 2);
+
+        let s = model.get_surface("Kitchen East")?;
+        assert_eq!(*s.category()?, SurfaceType::InteriorWall);
+        assert!(matches!(&s.back_boundary, Boundary::Space{space} if space == "Kitchen"));
+        assert!(matches!(&s.front_boundary, Boundary::Space{space} if space == "Living"));
+        assert!(model.get_surface("Living West").is_err());
+
+        // Nothing else to match
+        assert!(matches!(
+            model.get_surface("Kitchen West")?.front_boundary,
+            Boundary::Outdoor
+        ));
+        assert_eq!(model.match_surfaces(&SurfaceMatchingOptions::default())?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_tolerance() -> Result<(), String> {
+        let mut model = model()?;
+        let options = SurfaceMatchingOptions {
+            distance_tolerance: 1e-4,
+            ..Default::default()
+        };
+        assert_eq!(model.match_surfaces(&options)?, 0);
+        assert_eq!(model.surfaces.len(), 3);
+
+        let options = SurfaceMatchingOptions {
+            distance_tolerance: -1.,
+            ..Default::default()
+        };
+        assert!(model.match_surfaces(&options).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_same_side() -> Result<(), String> {
+        // Both spaces are at the same side of the partition
+        let mut model = Model::default();
+        model.add_construction(Construction::new("the construction"));
+        model.add_space(Space::new("Kitchen"));
+        model.add_space(Space::new("Living"));
+        let a = [(1., 0., 0.), (1., 1., 0.), (1., 1., 3.), (1., 0., 3.)];
+        let b = [(1., 1., 0.), (1., 0., 0.), (1., 0., 3.), (1., 1., 3.)];
+        model.add_surface(wall("Kitchen East", "Kitchen", a)?)?;
+        let mut living = wall("Living West", "Living", b)?;
+        living.front_boundary = living.back_boundary.clone();
+        living.back_boundary = Boundary::Outdoor;
+        model.add_surface(living)?;
+        assert_eq!(model.match_surfaces(&SurfaceMatchingOptions::default())?, 0);
+        Ok(())
+    }
+}
@@ -0,0 +1,149 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use crate::Float;
+use derive::ObjectIO;
+use serde::{Deserialize, Serialize};
+
+/// A glazing whose optical properties can be changed during the
+/// simulation (e.g., electrochromic glass), installed on a
+/// [`Fenestration`](crate::Fenestration).
+///
+/// How tinted the glazing is is given by the `tint_level` of the
+/// `Fenestration`, which goes from 0 (i.e., clear, the default) to 1
+/// (i.e., fully tinted) and can be changed through the Rhai API. The
+/// optical properties of the clear state are those of the `construction`
+/// of the `Fenestration`, and the fully tinted state is described relative
+/// to it. The fraction of the radiation that goes through the clear
+/// glazing that still goes through it is
+///
+/// ```math
+/// f = 1 - \text{tint} (1 - \tau_{tinted})
+/// ```
+///
+/// where $`\tau_{tinted}`$ is the solar or visible `tinted_transmittance`.
+/// Radiation that does not go through the glazing is assumed to be absorbed
+/// by it, so the glass still receives the sun on its outer side.
+///
+/// ## Example
+///
+/// #### `.json`
+/// ```json
+/// {{#include ../../../model/tests/scanner/switchable_glazing.json}}
+/// ```
+///
+/// > **Note**: This object cannot be declared by itself in a `SIMPLE` model,
+/// as it is always embeded on a `Fenestration`
+#[derive(Debug, Clone, ObjectIO, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SwitchableGlazing {
+    /// The fraction of the solar radiation transmitted by the clear
+    /// glazing that is still transmitted when fully tinted (from 0 to 1)
+    pub tinted_solar_transmittance: Float,
+
+    /// The fraction of the visible light transmitted by the clear
+    /// glazing that is still transmitted when fully tinted (from 0 to 1).
+    /// If none is given, the `tinted_solar_transmittance` is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tinted_visible_transmittance: Option<Float>,
+}
+
+impl SwitchableGlazing {
+    /// Calculates the fraction of the solar radiation transmitted by
+    /// the clear glazing that goes through it, given its tint level
+    /// (from 0 to 1)
+    pub fn solar_factor(&self, tint_level: Float) -> Float {
+        let tint = tint_level.clamp(0., 1.);
+        1. - tint * (1. - self.tinted_solar_transmittance)
+    }
+
+    /// Calculates the fraction of the visible light transmitted by
+    /// the clear glazing that goes through it, given its tint level
+    /// (from 0 to 1)
+    pub fn visible_factor(&self, tint_level: Float) -> Float {
+        let tint = tint_level.clamp(0., 1.);
+        let tau = self.tinted_visible_transmittance_or("", self.tinted_solar_transmittance);
+        1. - tint * (1. - tau)
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn serde() -> Result<(), String> {
+        use json5;
+        use std::fs;
+
+        // Hardcode a reference
+        let mut hardcoded_ref = SwitchableGlazing::new(0.1);
+        hardcoded_ref.set_tinted_visible_transmittance(0.02);
+
+        // Deserialize from hardcoded string and check they are the same
+        let from_hardcoded_json: SwitchableGlazing = json5::from_str(
+            "{
+            tinted_solar_transmittance: 0.1,
+            tinted_visible_transmittance: 0.02,
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_hardcoded_json)
+        );
+
+        // Read json file (used in DOC), Deserialize, and compare
+        let json_data = fs::read_to_string("./tests/scanner/switchable_glazing.json")
+            .map_err(|e| e.to_string())?;
+        let from_json_file: SwitchableGlazing =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_json_file)
+        );
+
+        // Serialize and deserialize again... check that everythin matches the pattern
+        let rust_json = serde_json::to_string(&hardcoded_ref).map_err(|e| e.to_string())?;
+        let from_serialized: SwitchableGlazing =
+            serde_json::from_str(&rust_json).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_serialized)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_factors() {
+        let mut glazing = SwitchableGlazing::new(0.2);
+        assert!((glazing.solar_factor(0.) - 1.).abs() < 1e-6);
+        assert!((glazing.solar_factor(1.) - 0.2).abs() < 1e-6);
+        assert!((glazing.solar_factor(0.5) - 0.6).abs() < 1e-6);
+        // Out of range
+        assert!((glazing.solar_factor(2.) - 0.2).abs() < 1e-6);
+        // Defaults to solar
+        assert!((glazing.visible_factor(1.) - 0.2).abs() < 1e-6);
+
+        glazing.set_tinted_visible_transmittance(0.1);
+        assert!((glazing.visible_factor(1.) - 0.1).abs() < 1e-6);
+        assert!((glazing.visible_factor(0.5) - 0.55).abs() < 1e-6);
+    }
+}
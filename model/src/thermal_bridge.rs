@@ -0,0 +1,135 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use crate::Float;
+use derive::ObjectIO;
+use serde::{Deserialize, Serialize};
+
+/// A linear thermal bridge; i.e., a steady conductive path along an
+/// edge or junction (e.g., where a slab meets a wall, or around a window)
+/// that bypasses the layers of the `Construction`.
+///
+/// The heat flow (in $W$) through the thermal bridge is calculated as
+///
+/// $$ Q = \psi L \Delta T $$
+///
+/// where $\psi$ is the linear thermal transmittance of the junction (i.e.,
+/// the `psi` value), $L$ is its `length` and $\Delta T$ is the difference
+/// between the temperatures at each side of the `Surface`.
+///
+/// When declared on a [`Surface`](crate::Surface), the `length` is given in $m$.
+/// When declared on a [`Construction`](crate::Construction), it is given per square
+/// meter of surface (i.e., in $m/m^2$), so it is multiplied by the area of each
+/// `Surface` that uses such `Construction`.
+///
+/// ## Example
+///
+/// #### `.json`
+/// ```json
+/// {{#include ../../../model/tests/scanner/thermal_bridge.json}}
+/// ```
+///
+/// > **Note**: This object cannot be declared by itself in a `SIMPLE` model,
+/// as it is always embeded on a `Surface` or a `Construction`
+#[derive(Debug, Clone, ObjectIO, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThermalBridge {
+    /// The linear thermal transmittance of the junction, in $W/m.K$
+    pub psi: Float,
+
+    /// The length of the junction, in $m$ (or $m/m^2$, if declared
+    /// on a `Construction`)
+    pub length: Float,
+}
+
+impl ThermalBridge {
+    /// Calculates the heat transfer coefficient (i.e., $\psi L$, in $W/K$)
+    /// of the thermal bridge. The length is scaled by `length_multiplier`,
+    /// which is the area of the `Surface` when the bridge is declared on
+    /// a `Construction`.
+    pub fn heat_transfer_coefficient(&self, length_multiplier: Float) -> Result<Float, String> {
+        if self.length < 0. {
+            return Err(format!(
+                "The length of a ThermalBridge cannot be negative... found {}",
+                self.length
+            ));
+        }
+        Ok(self.psi * self.length * length_multiplier)
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn serde() -> Result<(), String> {
+        use json5;
+        use std::fs;
+
+        // Hardcode a reference
+        let hardcoded_ref = ThermalBridge::new(0.45, 12.);
+
+        // Deserialize from hardcoded string and check they are the same
+        let from_hardcoded_json: ThermalBridge = json5::from_str(
+            "{
+            psi: 0.45,
+            length: 12.0
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_hardcoded_json)
+        );
+
+        // Read json file (used in DOC), Deserialize, and compare
+        let filename = "./tests/scanner/thermal_bridge";
+        let json_file = format!("{}.json", filename);
+        let json_data = fs::read_to_string(json_file).map_err(|e| e.to_string())?;
+        let from_json_file: ThermalBridge =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_json_file)
+        );
+
+        // Serialize and deserialize again... check that everythin matches the pattern
+        let rust_json = serde_json::to_string(&hardcoded_ref).map_err(|e| e.to_string())?;
+        let from_serialized: ThermalBridge =
+            serde_json::from_str(&rust_json).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_serialized)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_heat_transfer_coefficient() -> Result<(), String> {
+        let bridge = ThermalBridge::new(0.45, 12.);
+        assert!((bridge.heat_transfer_coefficient(1.)? - 5.4).abs() < 1e-5);
+        assert!((bridge.heat_transfer_coefficient(2.)? - 10.8).abs() < 1e-5);
+
+        let bridge = ThermalBridge::new(0.45, -1.);
+        assert!(bridge.heat_transfer_coefficient(1.).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,478 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::substance::gas::{Gas, GasSpecification};
+use crate::{Construction, Float, Model, Substance};
+use std::ops::{Add, Div, Mul, Sub};
+
+/// The interior surface resistance for horizontal heat flow given
+/// by ISO-6946, in $`m^2 K/W`$
+pub const R_SI: Float = 0.13;
+
+/// The exterior surface resistance given by ISO-6946, in $`m^2 K/W`$
+pub const R_SE: Float = 0.04;
+
+/// The emissivity assumed for the surfaces of a gas cavity when
+/// none is given
+const DEFAULT_EMISSIVITY: Float = 0.84;
+
+/// The mean temperature of the air layers (10C), in $`K`$, used for
+/// calculating their radiative heat transfer coefficient
+const AIR_LAYER_TEMPERATURE: Float = 283.15;
+
+/// The thermal conductivity of air assumed by ISO-6946, in $`W/m K`$
+const AIR_CONDUCTIVITY: Float = 0.025;
+
+/// The Stefan-Boltzmann constant, in $`W m^{-2} K^{-4}`$
+const SIGMA: Float = 5.670374419e-8;
+
+/// The thermal conductivity (in $`W/m K`$) of a gas at [`AIR_LAYER_TEMPERATURE`],
+/// following the coefficients of ISO-15099
+fn gas_conductivity(gas: GasSpecification) -> Float {
+    let (a, b) = match gas {
+        GasSpecification::Air => (2.873e-3, 7.760e-5),
+        GasSpecification::Argon => (2.285e-3, 5.149e-5),
+        GasSpecification::Krypton => (9.443e-4, 2.826e-5),
+        GasSpecification::Xenon => (4.538e-4, 1.723e-5),
+    };
+    a + b * AIR_LAYER_TEMPERATURE
+}
+
+/// A bare-bones complex number, for the matrices of ISO-13786
+#[derive(Debug, Clone, Copy)]
+struct Complex {
+    re: Float,
+    im: Float,
+}
+
+impl Complex {
+    const ONE: Self = Self::new(1., 0.);
+
+    const fn new(re: Float, im: Float) -> Self {
+        Self { re, im }
+    }
+
+    fn abs(self) -> Float {
+        self.re.hypot(self.im)
+    }
+
+    fn arg(self) -> Float {
+        self.im.atan2(self.re)
+    }
+
+    fn scale(self, v: Float) -> Self {
+        Self::new(self.re * v, self.im * v)
+    }
+}
+
+impl Add for Complex {
+    type Output = Self;
+    fn add(self, other: Self) -> Self {
+        Self::new(self.re + other.re, self.im + other.im)
+    }
+}
+
+impl Sub for Complex {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self::new(self.re - other.re, self.im - other.im)
+    }
+}
+
+impl Mul for Complex {
+    type Output = Self;
+    fn mul(self, other: Self) -> Self {
+        Self::new(
+            self.re * other.re - self.im * other.im,
+            self.re * other.im + self.im * other.re,
+        )
+    }
+}
+
+impl Div for Complex {
+    type Output = Self;
+    fn div(self, other: Self) -> Self {
+        let d = other.re * other.re + other.im * other.im;
+        Self::new(
+            (self.re * other.re + self.im * other.im) / d,
+            (self.im * other.re - self.re * other.im) / d,
+        )
+    }
+}
+
+/// A heat transfer matrix, as defined in ISO-13786
+type HeatTransferMatrix = [[Complex; 2]; 2];
+
+/// Multiplies two heat transfer matrices
+fn matmul(a: &HeatTransferMatrix, b: &HeatTransferMatrix) -> HeatTransferMatrix {
+    let mut ret = [[Complex::new(0., 0.); 2]; 2];
+    for (i, row) in ret.iter_mut().enumerate() {
+        for (j, v) in row.iter_mut().enumerate() {
+            *v = a[i][0] * b[0][j] + a[i][1] * b[1][j];
+        }
+    }
+    ret
+}
+
+/// The heat transfer matrix of a layer with no thermal mass (e.g., a
+/// surface resistance or an air layer) with a certain resistance
+fn resistance_matrix(r: Float) -> HeatTransferMatrix {
+    [
+        [Complex::ONE, Complex::new(-r, 0.)],
+        [Complex::new(0., 0.), Complex::ONE],
+    ]
+}
+
+/// The heat transfer matrix of a homogeneous layer, as given by ISO-13786.
+/// The `period` is in seconds.
+fn layer_matrix(
+    thickness: Float,
+    conductivity: Float,
+    density: Float,
+    heat_capacity: Float,
+    period: Float,
+) -> HeatTransferMatrix {
+    let pi = std::f64::consts::PI as Float;
+    let delta = (conductivity * period / (pi * density * heat_capacity)).sqrt();
+    let xi = thickness / delta;
+    let (sh, ch) = (xi.sinh(), xi.cosh());
+    let (s, c) = xi.sin_cos();
+
+    let z11 = Complex::new(ch * c, sh * s);
+    let z12 = Complex::new(sh * c + ch * s, ch * s - sh * c).scale(-delta / (2. * conductivity));
+    let z21 = Complex::new(sh * c - ch * s, sh * c + ch * s).scale(-conductivity / delta);
+    [[z11, z12], [z21, z11]]
+}
+
+/// The dynamic thermal characteristics of a [`Construction`] subject to
+/// periodic (e.g., daily) temperature variations, as defined by ISO-13786.
+///
+/// The front of the `Construction` is assumed to face the exterior, and its
+/// back the interior.
+#[derive(Debug, Clone, Copy)]
+pub struct DynamicThermalProperties {
+    /// The period of the variations, in hours
+    pub period: Float,
+
+    /// The steady-state U-value, in $`W/m^2K`$
+    pub u_value: Float,
+
+    /// The periodic thermal transmittance (i.e., the amplitude of the heat
+    /// flow at the interior per unit of amplitude of the exterior temperature),
+    /// in $`W/m^2K`$
+    pub periodic_thermal_transmittance: Float,
+
+    /// The ratio between the `periodic_thermal_transmittance` and the `u_value`
+    pub decrement_factor: Float,
+
+    /// The time by which the heat flow at the interior lags behind the
+    /// exterior temperature, in hours
+    pub time_shift: Float,
+
+    /// The areal heat capacity at the interior side, in $`J/m^2K`$
+    pub internal_areal_heat_capacity: Float,
+
+    /// The areal heat capacity at the exterior side, in $`J/m^2K`$
+    pub external_areal_heat_capacity: Float,
+}
+
+impl Construction {
+    /// Gets the thickness and substance of each layer, from front to back
+    fn layers(&self, model: &Model) -> Result<Vec<(Float, Substance)>, String> {
+        if self.materials.is_empty() {
+            return Err(format!("Construction '{}' has no layers", self.name));
+        }
+        self.materials
+            .iter()
+            .map(|name| {
+                let material = model.get_material(name)?;
+                let substance = model.get_substance(&material.substance)?;
+                Ok((material.thickness, substance))
+            })
+            .collect()
+    }
+
+    /// Calculates the thermal resistance (in $`m^2K/W`$) of a gas layer, based
+    /// on the emissivities of the layers around it. As in ISO-6946, the gas is
+    /// assumed to be still, but the conductive and convective heat transfer
+    /// are scaled by the conductivity of the gas relative to that of air.
+    fn gas_resistance(
+        &self,
+        layers: &[(Float, Substance)],
+        i: usize,
+        gas: &Gas,
+    ) -> Result<Float, String> {
+        let neighbour = |j: Option<usize>| match j.and_then(|j| layers.get(j)) {
+            Some((_, Substance::Normal(s))) => Ok(s.clone()),
+            _ => Err(format!(
+                "The gas layers of Construction '{}' must be enclosed by two solid layers",
+                self.name
+            )),
+        };
+        let front = neighbour(i.checked_sub(1))?;
+        let back = neighbour(Some(i + 1))?;
+        let e1 = front.back_thermal_absorbtance_or("", DEFAULT_EMISSIVITY);
+        let e2 = back.front_thermal_absorbtance_or("", DEFAULT_EMISSIVITY);
+
+        let thickness = layers[i].0;
+        let ratio = gas.gas().map_or(1., |g| {
+            gas_conductivity(*g) / gas_conductivity(GasSpecification::Air)
+        });
+        let h_a = ratio * (AIR_CONDUCTIVITY / thickness).max(1.25);
+        let h_r = 4. * SIGMA * AIR_LAYER_TEMPERATURE.powi(3) / (1. / e1 + 1. / e2 - 1.);
+        Ok(1. / (h_a + h_r))
+    }
+
+    /// Calculates the steady-state U-value (in $`W/m^2K`$) of the `Construction`,
+    /// following ISO-6946. This includes the surface resistances [`R_SI`] and [`R_SE`]
+    /// and—if any—its [`ThermalBridge`](crate::ThermalBridge)s.
+    ///
+    /// Gas layers are treated as unventilated layers with horizontal heat flow,
+    /// with their heat transfer scaled by the conductivity of the gas, so the
+    /// results of glazings filled with gases other than air are only indicative.
+    ///
+    /// ```
+    /// use model::{Construction, Material, Model, substance::Normal, R_SE, R_SI};
+    ///
+    /// let mut model = Model::default();
+    /// let mut concrete = Normal::new("Concrete");
+    /// concrete.set_thermal_conductivity(1.4);
+    /// model.add_substance(concrete.wrap());
+    /// model.add_material(Material::new("Slab", "Concrete", 0.14));
+    /// let mut slab = Construction::new("Slab");
+    /// slab.materials.push("Slab".into());
+    ///
+    /// let u = slab.u_value(&model).unwrap();
+    /// assert!((u - 1. / (R_SI + 0.1 + R_SE)).abs() < 1e-6);
+    /// ```
+    pub fn u_value(&self, model: &Model) -> Result<Float, String> {
+        let layers = self.layers(model)?;
+        let mut r = R_SI + R_SE;
+        for (i, (thickness, substance)) in layers.iter().enumerate() {
+            r += match substance {
+                Substance::Normal(s) => thickness / s.thermal_conductivity()?,
+                Substance::Gas(g) => self.gas_resistance(&layers, i, g)?,
+            };
+        }
+        let mut u = 1. / r;
+        for bridge in self.thermal_bridges.iter() {
+            // their length is given per square meter
+            u += bridge.heat_transfer_coefficient(1.)?;
+        }
+        Ok(u)
+    }
+
+    /// Calculates the thermal mass of the `Construction`; that is, the sum of the
+    /// areal heat capacity of its layers (in $`J/m^2K`$). Gas layers have none,
+    /// and the latent heat of Phase Change Materials is not considered.
+    pub fn thermal_mass(&self, model: &Model) -> Result<Float, String> {
+        let mut ret = 0.;
+        for (thickness, substance) in self.layers(model)? {
+            if let Substance::Normal(s) = substance {
+                ret += thickness * s.density()? * s.specific_heat_capacity()?;
+            }
+        }
+        Ok(ret)
+    }
+
+    /// Calculates the [`DynamicThermalProperties`] of the `Construction` under
+    /// periodic variations of a certain `period` (in hours, usually 24), following
+    /// ISO-13786. Gas layers are treated as thermal resistances, as in
+    /// [`Construction::u_value`], and thermal bridges are ignored.
+    pub fn dynamic_thermal_properties(
+        &self,
+        model: &Model,
+        period: Float,
+    ) -> Result<DynamicThermalProperties, String> {
+        if period <= 0. {
+            return Err(format!(
+                "The period for calculating dynamic thermal properties must be positive... found {}",
+                period
+            ));
+        }
+        let layers = self.layers(model)?;
+        let seconds = period * 3600.;
+
+        // From the exterior to the interior.
+        let mut z = resistance_matrix(R_SE);
+        let mut r = R_SI + R_SE;
+        for (i, (thickness, substance)) in layers.iter().enumerate() {
+            let layer = match substance {
+                Substance::Normal(s) => {
+                    let lambda = *s.thermal_conductivity()?;
+                    r += thickness / lambda;
+                    layer_matrix(
+                        *thickness,
+                        lambda,
+                        *s.density()?,
+                        *s.specific_heat_capacity()?,
+                        seconds,
+                    )
+                }
+                Substance::Gas(g) => {
+                    let rg = self.gas_resistance(&layers, i, g)?;
+                    r += rg;
+                    resistance_matrix(rg)
+                }
+            };
+            z = matmul(&z, &layer);
+        }
+        let z = matmul(&z, &resistance_matrix(R_SI));
+
+        let u_value = 1. / r;
+        let y12 = Complex::new(-1., 0.) / z[0][1];
+        let periodic_thermal_transmittance = y12.abs();
+        let omega = 2. * std::f64::consts::PI as Float / seconds;
+        let time_shift = (-y12.arg() / omega / 3600.).rem_euclid(period);
+
+        Ok(DynamicThermalProperties {
+            period,
+            u_value,
+            periodic_thermal_transmittance,
+            decrement_factor: periodic_thermal_transmittance / u_value,
+            time_shift,
+            internal_areal_heat_capacity: ((z[0][0] - Complex::ONE) / z[0][1]).abs() / omega,
+            external_areal_heat_capacity: ((z[1][1] - Complex::ONE) / z[0][1]).abs() / omega,
+        })
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use crate::substance::{gas::GasSpecification, Gas, Normal};
+    use crate::Material;
+
+    fn add_normal(model: &mut Model, name: &str, lambda: Float, rho: Float, cp: Float) {
+        let mut s = Normal::new(name);
+        s.set_thermal_conductivity(lambda)
+            .set_density(rho)
+            .set_specific_heat_capacity(cp);
+        model.add_substance(s.wrap());
+    }
+
+    fn concrete_wall(insulated: bool) -> (Model, Construction) {
+        let mut model = Model::default();
+        add_normal(&mut model, "concrete", 2.0, 2400., 1000.);
+        add_normal(&mut model, "EPS", 0.04, 30., 1400.);
+        model.add_material(Material::new("200mm concrete", "concrete", 0.2));
+        model.add_material(Material::new("100mm EPS", "EPS", 0.1));
+        let mut c = Construction::new("wall");
+        if insulated {
+            c.materials.push("100mm EPS".into());
+        }
+        c.materials.push("200mm concrete".into());
+        (model, c)
+    }
+
+    #[test]
+    fn test_complex() {
+        let a = Complex::new(1., 2.);
+        let b = Complex::new(3., -1.);
+        let c = a * b;
+        assert!((c.re - 5.).abs() < 1e-9 && (c.im - 5.).abs() < 1e-9);
+        let d = c / b;
+        assert!((d.re - 1.).abs() < 1e-9 && (d.im - 2.).abs() < 1e-9);
+        assert!((Complex::new(3., 4.).abs() - 5.).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_u_value_and_mass() -> Result<(), String> {
+        let (model, c) = concrete_wall(true);
+        let u = c.u_value(&model)?;
+        assert!((1. / u - (R_SI + R_SE + 0.1 + 2.5)).abs() < 1e-9);
+        let mass = c.thermal_mass(&model)?;
+        assert!((mass - (0.2 * 2400. * 1000. + 0.1 * 30. * 1400.)).abs() < 1e-6);
+
+        // Thermal bridges are added in parallel
+        let (model, mut c) = concrete_wall(true);
+        c.thermal_bridges.push(crate::ThermalBridge::new(0.5, 0.2));
+        assert!((c.u_value(&model)? - u - 0.1).abs() < 1e-9);
+
+        // Errors
+        let mut c = Construction::new("nothing");
+        assert!(c.u_value(&model).is_err());
+        c.materials.push("does not exist".into());
+        assert!(c.thermal_mass(&model).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_double_glazing() -> Result<(), String> {
+        let mut model = Model::default();
+        let mut glass = Normal::new("glass");
+        glass.set_thermal_conductivity(1.);
+        model.add_substance(glass.wrap());
+        let mut air = Gas::new("air");
+        air.set_gas(GasSpecification::Air);
+        model.add_substance(air.wrap());
+        model.add_material(Material::new("pane", "glass", 0.006));
+        model.add_material(Material::new("gap", "air", 0.012));
+
+        let mut c = Construction::new("double glazing");
+        c.materials = vec!["pane".into(), "gap".into(), "pane".into()];
+        // Clear double glazing is usually around 2.8 W/m2K
+        let u = c.u_value(&model)?;
+        assert!((u - 2.82).abs() < 0.05, "u = {}", u);
+
+        // Gases need to be enclosed
+        c.materials = vec!["pane".into(), "gap".into()];
+        assert!(c.u_value(&model).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_dynamic_properties() -> Result<(), String> {
+        // A thin layer does not delay anything
+        let mut model = Model::default();
+        add_normal(&mut model, "thin", 1.0, 1000., 1000.);
+        model.add_material(Material::new("1mm", "thin", 0.001));
+        let mut c = Construction::new("thin");
+        c.materials.push("1mm".into());
+        let p = c.dynamic_thermal_properties(&model, 24.)?;
+        assert!((p.decrement_factor - 1.).abs() < 1e-3);
+        assert!(p.time_shift < 0.05);
+
+        // 200mm of concrete... checked against an independent implementation
+        let (model, c) = concrete_wall(false);
+        let p = c.dynamic_thermal_properties(&model, 24.)?;
+        assert!((p.u_value - 1. / 0.27).abs() < 1e-9);
+        assert!((p.periodic_thermal_transmittance - 1.9531).abs() < 1e-3);
+        assert!((p.decrement_factor - 0.5273).abs() < 1e-3);
+        assert!((p.time_shift - 5.475).abs() < 1e-2);
+        assert!((p.internal_areal_heat_capacity - 86420.).abs() < 10.);
+        assert!((p.external_areal_heat_capacity - 175878.).abs() < 10.);
+
+        // External insulation delays and dampens more, and keeps the
+        // mass on the inside
+        let (model, c) = concrete_wall(true);
+        let p = c.dynamic_thermal_properties(&model, 24.)?;
+        assert!(p.decrement_factor < 0.2);
+        assert!((p.time_shift - 7.793).abs() < 1e-2);
+        assert!(p.internal_areal_heat_capacity > 10. * p.external_areal_heat_capacity);
+
+        assert!(c.dynamic_thermal_properties(&model, 0.).is_err());
+        Ok(())
+    }
+}
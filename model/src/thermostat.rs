@@ -0,0 +1,588 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use crate::model::Model;
+use crate::simulation_state_element::StateElementField;
+use crate::{Float, SimulationState, HVAC};
+use calendar::Date;
+use derive::{ObjectAPI, ObjectIO};
+use serde::{Deserialize, Serialize};
+
+/// The default width of the deadband of a [`Thermostat`], in C
+pub const DEFAULT_DEADBAND: Float = 1.0;
+
+/// The default proportional gain of a [`Thermostat`] with `PID` control, in 1/K
+pub const DEFAULT_PROPORTIONAL_GAIN: Float = 1.0;
+
+/// The algorithm used by a [`Thermostat`] to decide how much power
+/// the [`HVAC`]s it controls should deliver.
+///
+/// ## Examples
+///
+/// #### `.json`
+///
+/// ```json
+/// {{#include ../../../model/tests/scanner/thermostat_control_type.json}}
+/// ```
+/// > **Note**: This object cannot be declared by itself in a `SIMPLE` model,
+/// as it is always embeded on a `Thermostat` object
+#[derive(Copy, Clone, ObjectIO, Debug, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+#[inline_enum]
+pub enum ThermostatControlType {
+    /// The [`HVAC`]s are either off or working at full power.
+    ///
+    /// Heating is turned on when the temperature falls below the heating
+    /// setpoint minus half the `deadband`, and turned off when it rises
+    /// above the heating setpoint plus half the `deadband`. Cooling works
+    /// the other way around. Within the deadband, the [`HVAC`]s keep doing
+    /// whatever they were doing in the previous timestep.
+    #[default]
+    OnOff,
+
+    /// The fraction of the maximum power delivered by the [`HVAC`]s is
+    /// calculated by a Proportional-Integral-Derivative controller, based on
+    /// the difference between the setpoint and the temperature of
+    /// the `target_space`.
+    PID,
+}
+
+/// A thermostat that controls one or more [`HVAC`]s based on the
+/// dry bulb temperature measured in a `target_space`.
+///
+/// Thermostats are applied by the simulation at every timestep, after
+/// the control algorithms (e.g., a Rhai script) and before the
+/// [`Schedule`](crate::Schedule)s. This means that they override whatever the
+/// control algorithms decided for the [`HVAC`]s they control, while still
+/// respecting their `availability_schedule`.
+///
+/// The setpoints can be fixed or given by a [`Schedule`](crate::Schedule).
+/// When both are given, the schedule is used.
+///
+/// ## Examples
+///
+/// #### `.spl`
+///
+/// ```json
+/// {{#include ../../../model/tests/scanner/thermostat.spl}}
+/// ```
+///
+/// #### `.json`
+///
+/// ```json
+/// {{#include ../../../model/tests/scanner/thermostat.json}}
+/// ```
+#[derive(Debug, ObjectIO, ObjectAPI, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Thermostat {
+    /// The name of the Thermostat
+    pub name: String,
+
+    /// The name of the `Space` in which the thermostat
+    /// is located (i.e., where the temperature is measured)
+    pub target_space: String,
+
+    /// The names of the [`HVAC`]s controlled by this thermostat. They
+    /// must be able to heat or cool (i.e., a
+    /// [`HeatRecoveryVentilator`](crate::hvac::HeatRecoveryVentilator)
+    /// cannot be controlled by a thermostat)
+    pub hvacs: Vec<String>,
+
+    /// The temperature below which the [`HVAC`]s start heating, in C
+    #[serde(skip_serializing_if = "Option::is_none")]
+    heating_setpoint: Option<Float>,
+
+    /// The temperature over which the [`HVAC`]s start cooling, in C
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cooling_setpoint: Option<Float>,
+
+    /// The name of the [`Schedule`](crate::Schedule) containing the heating
+    /// setpoint, in C. If given, the `heating_setpoint` is ignored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    heating_setpoint_schedule: Option<String>,
+
+    /// The name of the [`Schedule`](crate::Schedule) containing the cooling
+    /// setpoint, in C. If given, the `cooling_setpoint` is ignored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cooling_setpoint_schedule: Option<String>,
+
+    /// The width of the band around each setpoint within which
+    /// an `OnOff` thermostat does not change the state of the [`HVAC`]s, in C.
+    /// Defaults to 1.0
+    #[serde(skip_serializing_if = "Option::is_none")]
+    deadband: Option<Float>,
+
+    /// The algorithm used for controlling the [`HVAC`]s. Defaults to `OnOff`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    control_type: Option<ThermostatControlType>,
+
+    /// The proportional gain of a `PID` thermostat, in 1/K. This is the fraction
+    /// of the maximum power delivered per degree of difference between the
+    /// setpoint and the temperature. Defaults to 1.0
+    #[serde(skip_serializing_if = "Option::is_none")]
+    proportional_gain: Option<Float>,
+
+    /// The integral gain of a `PID` thermostat, in 1/(K s). Defaults to 0.0
+    #[serde(skip_serializing_if = "Option::is_none")]
+    integral_gain: Option<Float>,
+
+    /// The derivative gain of a `PID` thermostat, in s/K. Defaults to 0.0
+    #[serde(skip_serializing_if = "Option::is_none")]
+    derivative_gain: Option<Float>,
+
+    /// The time integral of the error of a `PID` thermostat, in K s
+    #[operational]
+    #[serde(skip)]
+    integral_error: StateElementField,
+
+    /// The error of a `PID` thermostat in the previous timestep, in K
+    #[operational]
+    #[serde(skip)]
+    previous_error: StateElementField,
+}
+
+/// Gets the maximum heating and cooling power (both positive) of an [`HVAC`]
+/// that can be controlled by a [`Thermostat`]
+pub(crate) fn hvac_capacity(hvac: &HVAC) -> Result<(Float, Float), String> {
+    let capacity = match hvac {
+        HVAC::ElectricHeater(h) => (h.max_heating_power_or("", 0.), 0.),
+        HVAC::IdealHeaterCooler(h) => (
+            h.max_heating_power_or("", 0.),
+            h.max_cooling_power_or("", 0.),
+        ),
+        HVAC::HeatPump(h) => (
+            h.max_heating_power_or("", 0.),
+            h.max_cooling_power_or("", 0.),
+        ),
+        HVAC::RadiantSurfaceSystem(h) => (
+            h.max_heating_power_or("", 0.),
+            h.max_cooling_power_or("", 0.),
+        ),
+        HVAC::HeatRecoveryVentilator(h) => {
+            return Err(format!(
+                "HeatRecoveryVentilator '{}' cannot be controlled by a Thermostat",
+                h.name
+            ))
+        }
+    };
+    Ok(capacity)
+}
+
+/// Gets the heating (positive) or cooling (negative) power being
+/// delivered by an [`HVAC`]
+pub(crate) fn hvac_power(hvac: &HVAC, state: &SimulationState) -> Option<Float> {
+    match hvac {
+        HVAC::ElectricHeater(h) => h.heating_cooling_consumption(state),
+        HVAC::IdealHeaterCooler(h) => h.heating_cooling_consumption(state),
+        HVAC::HeatPump(h) => h.heating_cooling_consumption(state),
+        HVAC::RadiantSurfaceSystem(h) => h.heating_cooling_consumption(state),
+        HVAC::HeatRecoveryVentilator(_) => None,
+    }
+}
+
+/// Sets the heating (positive) or cooling (negative) power
+/// delivered by an [`HVAC`]
+pub(crate) fn set_hvac_power(
+    hvac: &HVAC,
+    state: &mut SimulationState,
+    power: Float,
+) -> Result<(), String> {
+    match hvac {
+        HVAC::ElectricHeater(h) => {
+            h.set_heating_cooling_consumption(state, power)?;
+        }
+        HVAC::IdealHeaterCooler(h) => {
+            h.set_heating_cooling_consumption(state, power)?;
+        }
+        HVAC::HeatPump(h) => {
+            h.set_heating_cooling_consumption(state, power)?;
+        }
+        HVAC::RadiantSurfaceSystem(h) => {
+            h.set_heating_cooling_consumption(state, power)?;
+        }
+        HVAC::HeatRecoveryVentilator(h) => {
+            return Err(format!(
+                "HeatRecoveryVentilator '{}' cannot be controlled by a Thermostat",
+                h.name
+            ))
+        }
+    }
+    Ok(())
+}
+
+impl Thermostat {
+    /// Checks whether this thermostat uses a `PID` controller
+    pub fn is_pid(&self) -> bool {
+        matches!(self.control_type, Some(ThermostatControlType::PID))
+    }
+
+    /// Gets the heating and cooling setpoints of the thermostat at a certain
+    /// [`Date`], prioritizing the schedules over the fixed values.
+    pub fn setpoints(
+        &self,
+        model: &Model,
+        date: Date,
+    ) -> Result<(Option<Float>, Option<Float>), String> {
+        let heating = match &self.heating_setpoint_schedule {
+            Some(s) => Some(model.get_schedule(s)?.value(date)?),
+            None => self.heating_setpoint,
+        };
+        let cooling = match &self.cooling_setpoint_schedule {
+            Some(s) => Some(model.get_schedule(s)?.value(date)?),
+            None => self.cooling_setpoint,
+        };
+        Ok((heating, cooling))
+    }
+
+    /// Sets the power of the [`HVAC`]s controlled by this thermostat, based on
+    /// the temperature of its `target_space`. The `dt` is the timestep, in seconds,
+    /// and it is only used by `PID` thermostats.
+    pub fn control(
+        &self,
+        model: &Model,
+        date: Date,
+        dt: Float,
+        state: &mut SimulationState,
+    ) -> Result<(), String> {
+        let space = model.get_space(&self.target_space)?;
+        let temp = space.dry_bulb_temperature(state).ok_or_else(|| {
+            format!(
+                "Could not get the temperature of Thermostat '{}' target_space",
+                self.name
+            )
+        })?;
+        let (heating, cooling) = self.setpoints(model, date)?;
+
+        // A positive fraction means heating, a negative one cooling
+        let pid_fraction = if self.is_pid() {
+            Some(self.pid_fraction(temp, heating, cooling, dt, state)?)
+        } else {
+            None
+        };
+
+        for hvac_name in self.hvacs.iter() {
+            let hvac = model.get_hvac(hvac_name)?;
+            let (max_heating, max_cooling) = hvac_capacity(&hvac)?;
+            let fraction = match pid_fraction {
+                Some(f) => f,
+                None => {
+                    let current = hvac_power(&hvac, state).unwrap_or(0.);
+                    self.on_off_fraction(temp, heating, cooling, current)
+                }
+            };
+            let power = if fraction > 0. {
+                fraction * max_heating
+            } else {
+                fraction * max_cooling
+            };
+            set_hvac_power(&hvac, state, power)?;
+        }
+        Ok(())
+    }
+
+    /// Calculates the fraction of the maximum power of an `OnOff` thermostat,
+    /// which is 1 (heating), -1 (cooling) or 0. The `current` power
+    /// is used for deciding what to do within the deadband.
+    fn on_off_fraction(
+        &self,
+        temp: Float,
+        heating: Option<Float>,
+        cooling: Option<Float>,
+        current: Float,
+    ) -> Float {
+        let half_band = self.deadband_or("", DEFAULT_DEADBAND) / 2.;
+        if let Some(setpoint) = heating {
+            let is_on = current > 0.;
+            if temp < setpoint - half_band || (is_on && temp <= setpoint + half_band) {
+                return 1.;
+            }
+        }
+        if let Some(setpoint) = cooling {
+            let is_on = current < 0.;
+            if temp > setpoint + half_band || (is_on && temp >= setpoint - half_band) {
+                return -1.;
+            }
+        }
+        0.
+    }
+
+    /// Calculates the fraction of the maximum power of a `PID` thermostat, from -1
+    /// (full cooling) to 1 (full heating), and updates its memory in the state.
+    ///
+    /// The error is measured against the closest setpoint.
+    fn pid_fraction(
+        &self,
+        temp: Float,
+        heating: Option<Float>,
+        cooling: Option<Float>,
+        dt: Float,
+        state: &mut SimulationState,
+    ) -> Result<Float, String> {
+        let error = match (heating, cooling) {
+            (Some(h), Some(c)) => {
+                if temp < h {
+                    h - temp
+                } else if temp > c {
+                    c - temp
+                } else {
+                    0.
+                }
+            }
+            (Some(h), None) => h - temp,
+            (None, Some(c)) => c - temp,
+            (None, None) => 0.,
+        };
+        let kp = self.proportional_gain_or("", DEFAULT_PROPORTIONAL_GAIN);
+        let ki = self.integral_gain_or("", 0.);
+        let kd = self.derivative_gain_or("", 0.);
+
+        let err_msg = || {
+            format!(
+                "PID Thermostat '{}' has no memory in the state... was it added to the Model?",
+                self.name
+            )
+        };
+        let mut integral = self.integral_error(state).ok_or_else(err_msg)? + error * dt;
+        let previous = self.previous_error(state).ok_or_else(err_msg)?;
+
+        // Anti-windup: the integral term alone cannot exceed the full power
+        if ki > 0. {
+            let max_integral = 1. / ki;
+            integral = integral.clamp(-max_integral, max_integral);
+        }
+        self.set_integral_error(state, integral)?;
+        self.set_previous_error(state, error)?;
+
+        let u = kp * error + ki * integral + kd * (error - previous) / dt;
+        let mut fraction = u.clamp(-1., 1.);
+        if heating.is_none() {
+            fraction = fraction.min(0.);
+        }
+        if cooling.is_none() {
+            fraction = fraction.max(0.);
+        }
+        Ok(fraction)
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use crate::hvac::IdealHeaterCooler;
+    use crate::{Schedule, ScheduleProfile, SimulationStateElement, Space};
+    use std::sync::Arc;
+
+    #[test]
+    fn serde() -> Result<(), String> {
+        use json5;
+        use std::fs;
+
+        // Hardcode a reference
+        let mut hardcoded_ref = Thermostat::new("Living Thermostat", "Living Room");
+        hardcoded_ref.hvacs.push("Living Heat Pump".into());
+        hardcoded_ref.set_heating_setpoint(19.);
+        hardcoded_ref.set_cooling_setpoint(25.);
+        hardcoded_ref.set_deadband(0.5);
+
+        // Deserialize from hardcoded string and check they are the same
+        let from_hardcoded_json: Thermostat = json5::from_str(
+            "{
+            name: 'Living Thermostat',
+            target_space: 'Living Room',
+            hvacs: ['Living Heat Pump'],
+            heating_setpoint: 19,
+            cooling_setpoint: 25,
+            deadband: 0.5,
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_hardcoded_json)
+        );
+
+        // Read json file (used in DOC), Deserialize, and compare
+        let filename = "./tests/scanner/thermostat";
+        let json_file = format!("{}.json", filename);
+        let json_data = fs::read_to_string(json_file).map_err(|e| e.to_string())?;
+        let from_json_file: Thermostat =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_json_file)
+        );
+
+        // Serialize and deserialize again... check that everythin matches the pattern
+        let rust_json = serde_json::to_string(&hardcoded_ref).map_err(|e| e.to_string())?;
+        let from_serialized: Thermostat =
+            serde_json::from_str(&rust_json).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_serialized)
+        );
+
+        // check the control type
+        let json_data = fs::read_to_string("./tests/scanner/thermostat_control_type.json")
+            .map_err(|e| e.to_string())?;
+        let control: ThermostatControlType =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(control, ThermostatControlType::PID);
+
+        Ok(())
+    }
+
+    /// Builds a model with a space, an IdealHeaterCooler and the given thermostat,
+    /// and returns it with its state (including the space temperature)
+    fn get_model(thermostat: Thermostat) -> Result<(Model, SimulationState, Arc<Space>), String> {
+        let mut model = Model::default();
+        let space = model.add_space(Space::new("Room"));
+
+        let mut hvac = IdealHeaterCooler::new("Air Conditioner");
+        hvac.set_max_heating_power(1000.);
+        hvac.set_max_cooling_power(2000.);
+        model.add_hvac(hvac.wrap())?;
+
+        model.add_thermostat(thermostat)?;
+
+        let mut header = model.take_state().ok_or("No state")?;
+        let i = header.push(SimulationStateElement::SpaceDryBulbTemperature(0), 22.)?;
+        space.set_dry_bulb_temperature_index(i)?;
+        Ok((model, header.take_values().ok_or("No values")?, space))
+    }
+
+    fn power(model: &Model, state: &SimulationState) -> Result<Float, String> {
+        hvac_power(&model.get_hvac("Air Conditioner")?, state).ok_or("No power".to_string())
+    }
+
+    #[test]
+    fn test_on_off() -> Result<(), String> {
+        let mut thermostat = Thermostat::new("Thermostat", "Room");
+        thermostat.hvacs.push("Air Conditioner".into());
+        thermostat.set_heating_setpoint(20.);
+        thermostat.set_cooling_setpoint(25.);
+        let (model, mut state, space) = get_model(thermostat)?;
+        let date = Date {
+            month: 1,
+            day: 1,
+            hour: 12.,
+            year: None,
+        };
+
+        let mut check = |temp: Float, expected: Float| -> Result<(), String> {
+            space.set_dry_bulb_temperature(&mut state, temp)?;
+            model.apply_thermostats(date, 60., &mut state)?;
+            let found = power(&model, &state)?;
+            assert!(
+                (found - expected).abs() < 1e-5,
+                "at {temp}C expected {expected}W... found {found}W"
+            );
+            Ok(())
+        };
+
+        // Comfortable
+        check(22., 0.)?;
+        // Within the heating deadband, and off
+        check(19.7, 0.)?;
+        // Too cold
+        check(19.4, 1000.)?;
+        // Within the heating deadband, and on
+        check(20.3, 1000.)?;
+        // Too warm for heating
+        check(20.6, 0.)?;
+        // Within the cooling deadband, and off
+        check(25.3, 0.)?;
+        // Too hot
+        check(25.6, -2000.)?;
+        // Within the cooling deadband, and on
+        check(24.7, -2000.)?;
+        // Comfortable again
+        check(24.4, 0.)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_schedule() -> Result<(), String> {
+        let mut thermostat = Thermostat::new("Thermostat", "Room");
+        thermostat.hvacs.push("Air Conditioner".into());
+        thermostat.set_heating_setpoint(10.);
+        thermostat.set_heating_setpoint_schedule("Heating setpoint");
+        let (mut model, mut state, space) = get_model(thermostat)?;
+        model.add_schedule(Schedule::new(
+            "Heating setpoint",
+            ScheduleProfile::Constant { value: 23. },
+        ));
+        let date = Date {
+            month: 1,
+            day: 1,
+            hour: 12.,
+            year: None,
+        };
+
+        // The schedule, not the fixed setpoint, is used
+        space.set_dry_bulb_temperature(&mut state, 22.)?;
+        model.apply_thermostats(date, 60., &mut state)?;
+        assert!((power(&model, &state)? - 1000.).abs() < 1e-5);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_pid() -> Result<(), String> {
+        let mut thermostat = Thermostat::new("Thermostat", "Room");
+        thermostat.hvacs.push("Air Conditioner".into());
+        thermostat.set_heating_setpoint(20.);
+        thermostat.set_cooling_setpoint(25.);
+        thermostat.set_control_type(ThermostatControlType::PID);
+        thermostat.set_proportional_gain(0.5);
+        thermostat.set_integral_gain(0.01);
+        let (model, mut state, space) = get_model(thermostat)?;
+        let date = Date {
+            month: 1,
+            day: 1,
+            hour: 12.,
+            year: None,
+        };
+        let dt = 10.;
+
+        // Comfortable... nothing happens
+        space.set_dry_bulb_temperature(&mut state, 22.)?;
+        model.apply_thermostats(date, dt, &mut state)?;
+        assert!(power(&model, &state)?.abs() < 1e-5);
+
+        // 1 degree too cold: P = 0.5, I = 0.01 * 10 = 0.1
+        space.set_dry_bulb_temperature(&mut state, 19.)?;
+        model.apply_thermostats(date, dt, &mut state)?;
+        let found = power(&model, &state)?;
+        assert!((found - 600.).abs() < 1e-3, "found {found}");
+
+        // Same error again: the integral grows
+        model.apply_thermostats(date, dt, &mut state)?;
+        let found = power(&model, &state)?;
+        assert!((found - 700.).abs() < 1e-3, "found {found}");
+
+        // Way too hot: full cooling
+        space.set_dry_bulb_temperature(&mut state, 30.)?;
+        model.apply_thermostats(date, dt, &mut state)?;
+        let found = power(&model, &state)?;
+        assert!((found + 2000.).abs() < 1e-3, "found {found}");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,202 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::{
+    Fenestration, Float, Object, Photovoltaic, SensorGrid, ShadingSurface, SolarCollector, Surface,
+};
+use geometry::{Point3D, Transform};
+
+/// A trait shared by all the elements of a [`Model`](crate::Model) that
+/// have a position in space, allowing them to be moved around.
+pub trait Transformable {
+    /// Applies a [`Transform`] to the geometry of the element
+    fn transform(&mut self, t: &Transform) -> Result<(), String>;
+
+    /// Moves the element `x`, `y` and `z` metres along each axis
+    fn translate(&mut self, x: Float, y: Float, z: Float) -> Result<(), String> {
+        self.transform(&Transform::translate(x, y, z))
+    }
+
+    /// Rotates the element counterclockwise (i.e., from East to North)
+    /// around the vertical axis that passes through the origin. The input
+    /// is in degrees.
+    fn rotate(&mut self, degrees: Float) -> Result<(), String> {
+        self.transform(&Transform::rotate_z(degrees))
+    }
+}
+
+impl Transformable for Surface {
+    fn transform(&mut self, t: &Transform) -> Result<(), String> {
+        self.vertices.transform(t)
+    }
+}
+
+impl Transformable for Fenestration {
+    fn transform(&mut self, t: &Transform) -> Result<(), String> {
+        self.vertices.transform(t)
+    }
+}
+
+impl Transformable for ShadingSurface {
+    fn transform(&mut self, t: &Transform) -> Result<(), String> {
+        self.vertices.transform(t)
+    }
+}
+
+/// Panels placed over a `surface` follow it, so only
+/// their `vertices` (if any) are transformed
+impl Transformable for Photovoltaic {
+    fn transform(&mut self, t: &Transform) -> Result<(), String> {
+        if let Ok(vertices) = self.vertices() {
+            let mut vertices = vertices.clone();
+            vertices.transform(t)?;
+            self.set_vertices(vertices);
+        }
+        Ok(())
+    }
+}
+
+/// Collectors placed over a `surface` follow it, so only
+/// their `vertices` (if any) are transformed
+impl Transformable for SolarCollector {
+    fn transform(&mut self, t: &Transform) -> Result<(), String> {
+        if let Ok(vertices) = self.vertices() {
+            let mut vertices = vertices.clone();
+            vertices.transform(t)?;
+            self.set_vertices(vertices);
+        }
+        Ok(())
+    }
+}
+
+/// Sensors given by their `points` keep looking upwards
+impl Transformable for SensorGrid {
+    fn transform(&mut self, t: &Transform) -> Result<(), String> {
+        if let Ok(vertices) = self.vertices() {
+            let mut vertices = vertices.clone();
+            vertices.transform(t)?;
+            self.set_vertices(vertices);
+        }
+        if let Ok(points) = self.points() {
+            if points.len() % 3 != 0 {
+                return Err(format!(
+                    "The length of the 'points' of SensorGrid '{}' must be divisible by 3",
+                    self.name()
+                ));
+            }
+            let points = points
+                .chunks_exact(3)
+                .flat_map(|p| {
+                    let p = t.transform_pt(Point3D::new(p[0], p[1], p[2]));
+                    [p.x, p.y, p.z]
+                })
+                .collect();
+            self.set_points(points);
+        }
+        Ok(())
+    }
+}
+
+/// The `dimensions` of the object are not modified
+impl Transformable for Object {
+    fn transform(&mut self, t: &Transform) -> Result<(), String> {
+        self.location = t.transform_pt(self.location);
+        self.up = t.transform_vec(self.up).get_normalized();
+        self.front = t.transform_vec(self.front).get_normalized();
+        Ok(())
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use crate::{Boundary, SurfaceTrait};
+    use geometry::{Loop3D, Polygon3D, Vector3D};
+
+    fn polygon() -> Result<Polygon3D, String> {
+        let mut the_loop = Loop3D::new();
+        the_loop.push(Point3D::new(0., 0., 0.))?;
+        the_loop.push(Point3D::new(1., 0., 0.))?;
+        the_loop.push(Point3D::new(1., 0., 2.))?;
+        the_loop.push(Point3D::new(0., 0., 2.))?;
+        the_loop.close()?;
+        Polygon3D::new(the_loop)
+    }
+
+    #[test]
+    fn test_surface() -> Result<(), String> {
+        let mut s = Surface::new(
+            "South wall",
+            polygon()?,
+            "the construction",
+            Boundary::Outdoor,
+            Boundary::Outdoor,
+        );
+        assert!(s.normal().compare(Vector3D::new(0., -1., 0.)));
+
+        // Facing West
+        s.rotate(-90.)?;
+        assert!(s.normal().compare(Vector3D::new(-1., 0., 0.)));
+        assert!((s.area() - 2.).abs() < 1e-9);
+
+        s.translate(0., 0., 3.)?;
+        assert!(s
+            .outer()
+            .vertices()
+            .iter()
+            .all(|v| v.z >= 3. - 1e-9 && v.x.abs() < 1e-9));
+        Ok(())
+    }
+
+    #[test]
+    fn test_sensor_grid() -> Result<(), String> {
+        let mut grid = SensorGrid::new("the grid");
+        grid.set_points(vec![1., 0., 0.8, 2., 0., 0.8]);
+        grid.rotate(90.)?;
+        let sensors = grid.sensors()?;
+        assert!(sensors[0].origin.compare(Point3D::new(0., 1., 0.8)));
+        assert!(sensors[1].origin.compare(Point3D::new(0., 2., 0.8)));
+        assert!(sensors[1].direction.compare(Vector3D::new(0., 0., 1.)));
+
+        grid.set_points(vec![1., 2.]);
+        assert!(grid.rotate(90.).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_object() -> Result<(), String> {
+        let mut object = Object {
+            name: "the table".into(),
+            dimensions: Point3D::new(1., 1., 0.8),
+            location: Point3D::new(2., 0., 0.),
+            ..Default::default()
+        };
+        object.rotate(90.)?;
+        assert!(object.location.compare(Point3D::new(0., 2., 0.)));
+        assert!(object.front.compare(Vector3D::new(-1., 0., 0.)));
+        assert!(object.up.compare(Vector3D::new(0., 0., 1.)));
+        assert!(object.dimensions.compare(Point3D::new(1., 1., 0.8)));
+        Ok(())
+    }
+}
@@ -0,0 +1,219 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use crate::model::Model;
+use crate::simulation_state_element::StateElementField;
+use crate::Float;
+use derive::{ObjectAPI, ObjectIO};
+use serde::{Deserialize, Serialize};
+
+/// A tank storing domestic hot water, which can be heated by
+/// [`SolarCollector`](crate::SolarCollector)s and by a backup heater.
+///
+/// The water in the tank is assumed to be fully mixed (i.e., no stratification).
+/// Its temperature responds to the following heat balance:
+///
+/// ```math
+/// C \frac{dT}{dt} = Q_{solar} + Q_{heater} - UA(T - T_{amb}) - \rho c_p \dot{V} (T - T_{mains})
+/// ```
+///
+/// where $`C`$ is the heat capacity of the water in the tank, $`Q_{solar}`$ is the heat
+/// delivered by the collectors, $`UA`$ is the `heat_loss_coefficient`, $`T_{amb}`$ is
+/// the temperature around the tank and $`\dot{V}`$ is the hot water
+/// draw. The backup heater delivers whatever is needed (up to its
+/// `heater_power`) to keep the water at the `setpoint`.
+///
+/// The heat lost by a tank located in a `Space` is added to that `Space`.
+///
+/// ## Examples
+///
+/// #### `.spl`
+///
+/// ```json
+/// {{#include ../../../model/tests/scanner/water_tank.spl}}
+/// ```
+///
+/// #### `.json`
+///
+/// ```json
+/// {{#include ../../../model/tests/scanner/water_tank.json}}
+/// ```
+#[derive(Debug, ObjectIO, ObjectAPI, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WaterTank {
+    /// The name of the tank
+    pub name: String,
+
+    /// The volume of water in the tank, in m3
+    pub volume: Float,
+
+    /// The name of the `Space` in which the tank is located. If
+    /// none is given, the tank is assumed to be outdoors.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    space: Option<String>,
+
+    /// The heat lost by the tank per degree of difference between the
+    /// water and its surroundings, in W/K. Defaults to 2.0
+    #[serde(skip_serializing_if = "Option::is_none")]
+    heat_loss_coefficient: Option<Float>,
+
+    /// The temperature of the cold water replacing the hot
+    /// water drawn from the tank, in C. Defaults to 15.0
+    #[serde(skip_serializing_if = "Option::is_none")]
+    mains_water_temperature: Option<Float>,
+
+    /// The temperature the backup heater tries to keep, in C.
+    /// Defaults to 55.0
+    #[serde(skip_serializing_if = "Option::is_none")]
+    setpoint: Option<Float>,
+
+    /// The maximum power of the backup heater, in W. If none is
+    /// given, the tank has no backup heater.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    heater_power: Option<Float>,
+
+    /// The maximum flow of hot water drawn from the tank, in m3/s
+    #[serde(skip_serializing_if = "Option::is_none")]
+    max_draw_flow_rate: Option<Float>,
+
+    /// The name of the [`Schedule`](crate::Schedule) that multiplies the
+    /// `max_draw_flow_rate` of the tank. If none is given, the draw
+    /// is only modified by the control algorithms.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    draw_schedule: Option<String>,
+
+    /// The flow of hot water drawn from the tank, in m3/s
+    #[operational]
+    #[serde(skip)]
+    draw_flow_rate: StateElementField,
+
+    /// The temperature of the water in the tank, in C
+    #[physical]
+    #[serde(skip)]
+    water_temperature: StateElementField,
+
+    /// The power consumed by the backup heater, in W
+    #[physical]
+    #[serde(skip)]
+    heater_power_consumption: StateElementField,
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use crate::Model;
+
+    #[test]
+    fn serde() -> Result<(), String> {
+        use json5;
+        use std::fs;
+
+        // Hardcode a reference
+        let mut hardcoded_ref = WaterTank::new("Hot Water Tank", 0.2);
+        hardcoded_ref
+            .set_space("Laundry")
+            .set_heat_loss_coefficient(1.5)
+            .set_mains_water_temperature(12.)
+            .set_setpoint(60.)
+            .set_heater_power(2000.)
+            .set_max_draw_flow_rate(0.0002)
+            .set_draw_schedule("Hot Water Use");
+
+        // Deserialize from hardcoded string and check they are the same
+        let from_hardcoded_json: WaterTank = json5::from_str(
+            "{
+            name: 'Hot Water Tank',
+            volume: 0.2,
+            space: 'Laundry',
+            heat_loss_coefficient: 1.5,
+            mains_water_temperature: 12,
+            setpoint: 60,
+            heater_power: 2000,
+            max_draw_flow_rate: 0.0002,
+            draw_schedule: 'Hot Water Use'
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_hardcoded_json)
+        );
+
+        // Read json file (used in DOC), Deserialize, and compare
+        let filename = "./tests/scanner/water_tank";
+        let json_file = format!("{}.json", filename);
+        let json_data = fs::read_to_string(json_file).map_err(|e| e.to_string())?;
+        let from_json_file: WaterTank =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_json_file)
+        );
+
+        // Serialize and deserialize again... check that everythin matches the pattern
+        let rust_json = serde_json::to_string(&hardcoded_ref).map_err(|e| e.to_string())?;
+        let from_serialized: WaterTank =
+            serde_json::from_str(&rust_json).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_serialized)
+        );
+
+        // check simple
+        let (model, ..) = Model::from_file(format!("{}.spl", filename))?;
+        assert_eq!(model.water_tanks.len(), 1);
+        assert_eq!(model.water_tanks[0].name(), "Hot Water Tank");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_draw_schedule() -> Result<(), String> {
+        use crate::{Schedule, ScheduleProfile};
+        use calendar::Date;
+
+        let mut model = Model::default();
+        model.add_schedule(Schedule::new(
+            "the schedule",
+            ScheduleProfile::Constant { value: 0.5 },
+        ));
+
+        let mut tank = WaterTank::new("Hot Water Tank", 0.2);
+        tank.set_max_draw_flow_rate(0.0002)
+            .set_draw_schedule("the schedule");
+        let tank = model.add_water_tank(tank)?;
+
+        let mut state = model
+            .take_state()
+            .ok_or("No state")?
+            .take_values()
+            .ok_or("No values")?;
+        let date = Date {
+            month: 1,
+            day: 1,
+            hour: 8.,
+            year: None,
+        };
+        model.apply_schedules(date, &mut state)?;
+        let draw = tank.draw_flow_rate(&state).ok_or("No draw")?;
+        assert!((draw - 0.0001).abs() < 1e-12, "draw = {}", draw);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,261 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::{Float, R_SE, R_SI};
+use derive::ObjectIO;
+use serde::{Deserialize, Serialize};
+
+/// The frame (and, optionally, the dividers) of a
+/// [`Fenestration`](crate::Fenestration), which take part of its area and
+/// conduct heat in parallel with the glazing.
+///
+/// The `Fenestration` is treated as a rectangle of its same area and
+/// height, whose perimeter is covered by a frame of a certain `width`.
+/// Dividers split the remaining area into panes. Only the glazed area
+/// (i.e., the area of the `Fenestration` minus that of the frame and the
+/// dividers) is simulated with the `Construction` of the `Fenestration`,
+/// and only it lets the sun through. The frame and the dividers are a
+/// steady conductive path between the environments at each side, whose
+/// heat transfer coefficient (in $`W/K`$) is
+///
+/// ```math
+/// H = \frac{A_{frame}}{R_{si} + 1/C_{frame} + R_{se}} + \frac{A_{div}}{R_{si} + 1/C_{div} + R_{se}} + \psi_{eog} L_{glass}
+/// ```
+///
+/// where $`C`$ are the surface-to-surface conductances of the frame and
+/// dividers, $`\psi_{eog}`$ is the linear thermal transmittance of the edge
+/// of the glass (i.e., the extra heat lost through the spacers) and
+/// $`L_{glass}`$ is the visible perimeter of the panes.
+///
+/// ## Example
+///
+/// #### `.json`
+/// ```json
+/// {{#include ../../../model/tests/scanner/window_frame.json}}
+/// ```
+///
+/// > **Note**: This object cannot be declared by itself in a `SIMPLE` model,
+/// as it is always embeded on a `Fenestration`
+#[derive(Debug, Clone, ObjectIO, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WindowFrame {
+    /// The width of the frame, in $`m`$, measured from the edge of the
+    /// `Fenestration` inwards
+    pub width: Float,
+
+    /// The surface-to-surface thermal conductance of the frame, in $`W/m^2K`$
+    pub conductance: Float,
+
+    /// The linear thermal transmittance of the edge of the glass, in
+    /// $`W/m.K`$. If none is given, it is assumed to be zero.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    edge_of_glass_psi: Option<Float>,
+
+    /// The width of the dividers, in $`m`$. If none is given, it is
+    /// assumed to be equal to the `width` of the frame
+    #[serde(skip_serializing_if = "Option::is_none")]
+    divider_width: Option<Float>,
+
+    /// The surface-to-surface thermal conductance of the dividers, in
+    /// $`W/m^2K`$. If none is given, the `conductance` of the frame is used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    divider_conductance: Option<Float>,
+
+    /// The number of horizontal dividers. Defaults to zero.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    horizontal_dividers: Option<usize>,
+
+    /// The number of vertical dividers. Defaults to zero.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    vertical_dividers: Option<usize>,
+}
+
+/// The areas and lengths of a [`WindowFrame`] installed on a
+/// fenestration of certain dimensions
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FrameAreas {
+    /// The area of the frame, in $`m^2`$
+    pub frame: Float,
+
+    /// The area of the dividers, in $`m^2`$
+    pub dividers: Float,
+
+    /// The area of the glass, in $`m^2`$
+    pub glazing: Float,
+
+    /// The visible perimeter of all the panes of glass, in $`m`$
+    pub glazing_perimeter: Float,
+}
+
+/// The thermal transmittance of an element of a certain surface-to-surface
+/// `conductance`, including the surface resistances
+fn transmittance(conductance: Float) -> Float {
+    1. / (R_SI + 1. / conductance + R_SE)
+}
+
+impl WindowFrame {
+    /// Calculates the [`FrameAreas`] of this frame when installed on a
+    /// rectangular fenestration `width` by `height` metres
+    pub fn areas(&self, width: Float, height: Float) -> Result<FrameAreas, String> {
+        if self.width < 0. || self.divider_width.unwrap_or(0.) < 0. {
+            return Err("The width of a WindowFrame and its dividers cannot be negative".into());
+        }
+        let inner_width = width - 2. * self.width;
+        let inner_height = height - 2. * self.width;
+        if inner_width <= 0. || inner_height <= 0. {
+            return Err(format!(
+                "A WindowFrame {} m wide does not fit in a fenestration of {:.2} m by {:.2} m",
+                self.width, width, height
+            ));
+        }
+        let inner_area = inner_width * inner_height;
+
+        let d = self.divider_width.unwrap_or(self.width);
+        let n_h = self.horizontal_dividers.unwrap_or(0) as Float;
+        let n_v = self.vertical_dividers.unwrap_or(0) as Float;
+        let pane_width = inner_width - n_v * d;
+        let pane_height = inner_height - n_h * d;
+        if pane_width <= 0. || pane_height <= 0. {
+            return Err("The dividers of a WindowFrame do not leave space for the glass".into());
+        }
+        let dividers = d * (n_h * inner_width + n_v * inner_height) - n_h * n_v * d * d;
+
+        Ok(FrameAreas {
+            frame: width * height - inner_area,
+            dividers,
+            glazing: inner_area - dividers,
+            glazing_perimeter: 2. * ((n_h + 1.) * pane_width + (n_v + 1.) * pane_height),
+        })
+    }
+
+    /// Calculates the heat transfer coefficient (in $`W/K`$) of this frame,
+    /// its dividers and the edge of the glass, given its [`FrameAreas`]
+    pub fn heat_transfer_coefficient(&self, areas: &FrameAreas) -> Result<Float, String> {
+        let divider_conductance = self.divider_conductance.unwrap_or(self.conductance);
+        if self.conductance <= 0. || divider_conductance <= 0. {
+            return Err("The conductance of a WindowFrame must be positive".into());
+        }
+        Ok(areas.frame * transmittance(self.conductance)
+            + areas.dividers * transmittance(divider_conductance)
+            + areas.glazing_perimeter * self.edge_of_glass_psi.unwrap_or(0.))
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn serde() -> Result<(), String> {
+        use json5;
+        use std::fs;
+
+        // Hardcode a reference
+        let mut hardcoded_ref = WindowFrame::new(0.05, 5.);
+        hardcoded_ref
+            .set_edge_of_glass_psi(0.06)
+            .set_horizontal_dividers(1);
+
+        // Deserialize from hardcoded string and check they are the same
+        let from_hardcoded_json: WindowFrame = json5::from_str(
+            "{
+            width: 0.05,
+            conductance: 5.0,
+            edge_of_glass_psi: 0.06,
+            horizontal_dividers: 1,
+        }",
+        )
+        .map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_hardcoded_json)
+        );
+
+        // Read json file (used in DOC), Deserialize, and compare
+        let json_data =
+            fs::read_to_string("./tests/scanner/window_frame.json").map_err(|e| e.to_string())?;
+        let from_json_file: WindowFrame =
+            serde_json::from_str(&json_data).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_json_file)
+        );
+
+        // Serialize and deserialize again... check that everythin matches the pattern
+        let rust_json = serde_json::to_string(&hardcoded_ref).map_err(|e| e.to_string())?;
+        let from_serialized: WindowFrame =
+            serde_json::from_str(&rust_json).map_err(|e| e.to_string())?;
+        assert_eq!(
+            format!("{:?}", hardcoded_ref),
+            format!("{:?}", from_serialized)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_areas() -> Result<(), String> {
+        // A 1.2 x 1.5 window with a 5cm frame
+        let mut frame = WindowFrame::new(0.05, 5.);
+        let areas = frame.areas(1.2, 1.5)?;
+        assert!((areas.frame - (1.8 - 1.1 * 1.4)).abs() < 1e-6);
+        assert!(areas.dividers.abs() < 1e-6);
+        assert!((areas.glazing - 1.1 * 1.4).abs() < 1e-6);
+        assert!((areas.glazing_perimeter - (2. * (1.1 + 1.4))).abs() < 1e-6);
+
+        // Add a cross of 4cm dividers
+        frame
+            .set_divider_width(0.04)
+            .set_horizontal_dividers(1)
+            .set_vertical_dividers(1);
+        let areas = frame.areas(1.2, 1.5)?;
+        assert!((areas.dividers - (0.04 * (1.1 + 1.4) - 0.04 * 0.04)).abs() < 1e-6);
+        assert!((areas.frame + areas.dividers + areas.glazing - 1.8).abs() < 1e-6);
+        // Four panes of 0.53 x 0.68
+        assert!((areas.glazing_perimeter - (4. * 2. * (0.53 + 0.68))).abs() < 1e-6);
+
+        // Frames that do not fit
+        assert!(WindowFrame::new(0.7, 5.).areas(1.2, 1.5).is_err());
+        frame.set_vertical_dividers(30);
+        assert!(frame.areas(1.2, 1.5).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_heat_transfer_coefficient() -> Result<(), String> {
+        let mut frame = WindowFrame::new(0.05, 5.);
+        let areas = frame.areas(1.2, 1.5)?;
+        let u = 1. / (R_SI + 0.2 + R_SE);
+        assert!((frame.heat_transfer_coefficient(&areas)? - areas.frame * u).abs() < 1e-6);
+
+        frame.set_edge_of_glass_psi(0.06);
+        let expected = areas.frame * u + 0.06 * areas.glazing_perimeter;
+        assert!((frame.heat_transfer_coefficient(&areas)? - expected).abs() < 1e-6);
+
+        assert!(WindowFrame::new(0.05, 0.)
+            .heat_transfer_coefficient(&areas)
+            .is_err());
+        Ok(())
+    }
+}
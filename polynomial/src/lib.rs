@@ -123,6 +123,23 @@ impl Polynomial {
 
         y
     }
+
+    /// Calculates the derivative of the polynomial (i.e., a new
+    /// polynomial with one coefficient less)
+    ///
+    /// ```
+    ///     use polynomial::*;
+    ///     // p = 1.0 + 2.0*x + 3.0*x^2 ==> dp/dx = 2.0 + 6.0*x
+    ///     let p = poly![1.0, 2.0, 3.0];
+    ///     assert_eq!(p.derivative().eval(2.0), 14.0);
+    /// ```
+    pub fn derivative(&self) -> Self {
+        let mut ret = Self::new();
+        for i in 1..self.len {
+            ret.push(self.coefficients[i] * i as Float);
+        }
+        ret
+    }
 }
 
 /// A convenient way of defining a `Polynomial`.
@@ -252,4 +269,21 @@ mod testing {
             assert_eq!(v, 0.0);
         }
     }
+
+    #[test]
+    fn test_derivative() {
+        // A constant
+        let p = poly![6.0];
+        assert!(p.derivative().is_empty());
+        assert_eq!(p.derivative().eval(3.0), 0.0);
+
+        // 1 + x + 2x^2 + 3x^3 ==> 1 + 4x + 9x^2
+        let p = poly![1.0, 1.0, 2.0, 3.0];
+        let d = p.derivative();
+        assert_eq!(d.len(), 3);
+        assert_eq!(d.coefficients[0], 1.0);
+        assert_eq!(d.coefficients[1], 4.0);
+        assert_eq!(d.coefficients[2], 9.0);
+        assert_eq!(d.eval(2.0), 1.0 + 8.0 + 36.0);
+    }
 }
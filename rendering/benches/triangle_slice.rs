@@ -0,0 +1,56 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use rendering::rand::{RandGen, Rng, SeedableRng};
+use rendering::triangle::{simd, simple_intersect_triangle_slice, Triangle};
+use rendering::Float;
+
+// Intersects the triangles of a (full) BVH leaf one at a time, as the
+// scalar path does, and several at a time, as the `simd` feature does.
+// Run with `cargo bench -p rendering --features simd --bench triangle_slice`
+pub fn triangle_slice(c: &mut Criterion) {
+    const N_TRIANGLES: usize = 24;
+    const N_RAYS: usize = 256;
+
+    let mut rng = RandGen::seed_from_u64(1);
+    let mut coordinate = || -> Float { rng.gen::<Float>() * 10. - 5. };
+
+    let mut scene = rendering::Scene::new();
+    for _ in 0..N_TRIANGLES {
+        let t: Triangle = std::array::from_fn(|_| coordinate());
+        scene.triangles.push(t);
+    }
+    let rays: Vec<geometry::Ray3D> = (0..N_RAYS)
+        .map(|_| geometry::Ray3D {
+            origin: geometry::Point3D::new(coordinate(), coordinate(), coordinate()),
+            direction: geometry::Vector3D::new(coordinate(), coordinate(), coordinate())
+                .get_normalized(),
+        })
+        .collect();
+    let scene = black_box(scene);
+    let rays = black_box(rays);
+    let lanes = black_box(simd::Lanes::pack(&scene.triangles));
+
+    let mut group = c.benchmark_group("triangle_slice");
+    group.bench_function("scalar", |b| {
+        b.iter(|| {
+            for ray in rays.iter() {
+                black_box(simple_intersect_triangle_slice(
+                    &scene,
+                    *ray,
+                    0,
+                    N_TRIANGLES,
+                ));
+            }
+        })
+    });
+    group.bench_function("lanes", |b| {
+        b.iter(|| {
+            for ray in rays.iter() {
+                black_box(simd::intersect_lanes(&lanes, N_TRIANGLES, *ray, 0));
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, triangle_slice);
+criterion_main!(benches);
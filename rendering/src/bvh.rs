@@ -25,6 +25,8 @@ https://pbr-book.org/3ed-2018/Primitives_and_Intersection_Acceleration/Bounding_
  */
 
 use crate::scene::Scene;
+#[cfg(feature = "simd")]
+use crate::triangle::simd::Lanes;
 use crate::triangle::*;
 use crate::Float;
 use geometry::{BBox3D, BBoxAxis, Point3D, Ray3D, Vector3D};
@@ -415,6 +417,10 @@ struct FlatNode {
     /// what in PBR is called `primitivesOffset` for Leafs, and
     /// `secondChildOffset` for Interior nodes
     next: i32,
+    /// The position of the first of the [`Lanes`] in which the
+    /// triangles of a leaf are packed. Not used in interior nodes.
+    #[cfg(feature = "simd")]
+    first_lane: u32,
 }
 
 impl FlatNode {
@@ -426,6 +432,11 @@ impl FlatNode {
 #[derive(Debug, Default, Clone)]
 pub struct BoundingVolumeTree {
     nodes: Vec<FlatNode>,
+
+    /// The triangles of every leaf, packed for testing
+    /// them several at a time
+    #[cfg(feature = "simd")]
+    lanes: Vec<Lanes>,
 }
 
 impl BoundingVolumeTree {
@@ -483,8 +494,101 @@ impl BoundingVolumeTree {
         let mut nodes: Vec<FlatNode> = Vec::with_capacity(total_nodes);
         Self::flatten_node(&root, &mut nodes);
 
+        #[allow(unused_mut)]
+        let mut tree = Self {
+            nodes,
+            #[cfg(feature = "simd")]
+            lanes: Vec::new(),
+        };
+        #[cfg(feature = "simd")]
+        tree.pack_lanes(scene);
+
         // return
-        (Self { nodes }, ordered_mapping)
+        (tree, ordered_mapping)
+    }
+
+    /// Packs the triangles of each leaf into [`Lanes`]
+    #[cfg(feature = "simd")]
+    fn pack_lanes(&mut self, scene: &Scene) {
+        self.lanes.clear();
+        for node in self.nodes.iter_mut().filter(|n| n.is_leaf()) {
+            let ini = node.next as usize;
+            let fin = ini + node.n_prims as usize;
+            node.first_lane = self.lanes.len() as u32;
+            self.lanes.extend(Lanes::pack(&scene.triangles[ini..fin]));
+        }
+    }
+
+    /// Finds the closest triangle in a leaf `node` hit by a ray
+    #[cfg_attr(feature = "simd", allow(unused_variables))]
+    fn intersect_leaf(
+        &self,
+        scene: &Scene,
+        node: &FlatNode,
+        ray: Ray3D,
+    ) -> Option<(usize, Point3D)> {
+        let ini = node.next as usize;
+        let n = node.n_prims as usize;
+
+        #[cfg(feature = "simd")]
+        return crate::triangle::simd::intersect_lanes(
+            &self.lanes[node.first_lane as usize..],
+            n,
+            ray,
+            ini,
+        );
+        #[cfg(not(feature = "simd"))]
+        simple_intersect_triangle_slice(scene, ray, ini, ini + n)
+    }
+
+    /// The bounds of everything in the tree, or `None` if it is empty
+    pub fn bounds(&self) -> Option<BBox3D> {
+        self.nodes.first().map(|n| n.bounds)
+    }
+
+    /// Updates the bounds of every node after the triangles of the `scene`
+    /// have moved (e.g., a door that opens), keeping the structure of the
+    /// tree. This is much faster than building a new tree, but the tree
+    /// becomes less efficient if triangles move too much.
+    ///
+    /// Returns an error if the number of triangles has changed.
+    pub fn refit(&mut self, scene: &Scene) -> Result<(), String> {
+        let n_prims: usize = self
+            .nodes
+            .iter()
+            .filter(|n| n.is_leaf())
+            .map(|n| n.n_prims as usize)
+            .sum();
+        if n_prims != scene.triangles.len() {
+            return Err(format!(
+                "Cannot refit a BoundingVolumeTree of {} triangles into a scene with {} triangles",
+                n_prims,
+                scene.triangles.len()
+            ));
+        }
+
+        // Children are always after their parents
+        for i in (0..self.nodes.len()).rev() {
+            let node = &self.nodes[i];
+            let bounds = if node.is_leaf() {
+                let ini = node.next as usize;
+                let fin = ini + node.n_prims as usize;
+                scene.triangles[ini..fin]
+                    .iter()
+                    .map(world_bounds)
+                    .reduce(|a, b| BBox3D::from_union(&a, &b))
+                    .expect("Leaves should never be empty")
+            } else {
+                BBox3D::from_union(
+                    &self.nodes[i + 1].bounds,
+                    &self.nodes[node.next as usize].bounds,
+                )
+            };
+            self.nodes[i].bounds = bounds;
+        }
+        #[cfg(feature = "simd")]
+        self.pack_lanes(scene);
+        Ok(())
     }
 
     fn flatten_node(node: &Node, nodes: &mut Vec<FlatNode>) -> usize {
@@ -496,6 +600,8 @@ impl BoundingVolumeTree {
                     n_prims: l.n_prims as i16,
                     next: l.first_prim_offset as i32,
                     axis: BBoxAxis::X, // We won't use this
+                    #[cfg(feature = "simd")]
+                    first_lane: 0, // We will fill this when packing the lanes
                 });
             }
             Node::Interior(i) => {
@@ -510,6 +616,8 @@ impl BoundingVolumeTree {
                     n_prims: 0,
                     next: 0, // We will patch this
                     axis: i.split_axis,
+                    #[cfg(feature = "simd")]
+                    first_lane: 0,
                 });
                 Self::flatten_node(child1, nodes);
                 // Patch second offset
@@ -573,13 +681,8 @@ impl BoundingVolumeTree {
             let node = &self.nodes[current_node];
             if node.bounds.intersect(ray, &inv_dir) {
                 if node.is_leaf() {
-                    let offset = node.next;
-
                     // Check all the objects in this Node
-                    let ini = offset as usize;
-                    let fin = ini + node.n_prims as usize;
-
-                    if let Some((i, p)) = simple_intersect_triangle_slice(scene, ray, ini, fin) {
+                    if let Some((i, p)) = self.intersect_leaf(scene, node, ray) {
                         // If hit, check the distance.
                         let this_t_squared = (p - ray.origin).length_squared();
                         // if the distance is less than the prevous one, update the info
@@ -658,15 +761,8 @@ impl BoundingVolumeTree {
             let node = &self.nodes[current_node];
             if node.bounds.intersect(ray, &inv_dir) {
                 if node.is_leaf() {
-                    let offset = node.next;
-
                     // Check all the objects in this Node
-                    let ini = offset as usize;
-                    let fin = ini + node.n_prims as usize;
-
-                    /* NON_SIMD */
-
-                    if let Some((_, p)) = simple_triangle_intersect(scene, ray, ini, fin) {
+                    if let Some((_, p)) = self.intersect_leaf(scene, node, ray) {
                         // If hit, check the distance.
                         let this_t_squared = (p - ray.origin).length_squared();
 
@@ -848,6 +944,43 @@ mod tests {
         assert_eq!(node.axis, BBoxAxis::Z);
     }
 
+    #[test]
+    fn test_refit() -> Result<(), String> {
+        let mut scene = get_horizontal_scene();
+        let (mut bvh, _) = BoundingVolumeTree::new(&mut scene);
+        let ray = Ray3D {
+            origin: Point3D::new(-1., 0., 10.),
+            direction: Vector3D::new(0., 0., -1.),
+        };
+        let mut aux = [0; 32];
+        assert!(bvh.intersect(&scene, ray, &mut aux).is_some());
+
+        // Move everything 5m up
+        for t in scene.triangles.iter_mut() {
+            t[2] += 5.;
+            t[5] += 5.;
+            t[8] += 5.;
+        }
+        bvh.refit(&scene)?;
+        let bounds = bvh.bounds().unwrap();
+        assert!((bounds.min.z - 4.5).abs() < 0.05);
+        assert!((bounds.max.z - 5.5).abs() < 0.05);
+        let (_, p) = bvh.intersect(&scene, ray, &mut aux).unwrap();
+        assert!((p.z - 5.5).abs() < 0.05);
+
+        // Rays that used to hit, now miss
+        let ray = Ray3D {
+            origin: Point3D::new(-1., 0., 2.),
+            direction: Vector3D::new(0., 0., -1.),
+        };
+        assert!(bvh.intersect(&scene, ray, &mut aux).is_none());
+
+        // Different number of triangles
+        scene.triangles.pop();
+        assert!(bvh.refit(&scene).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_intersect_horizontal() {
         let original_scene = get_horizontal_scene();
@@ -53,6 +53,20 @@ impl View {
         self.view_up.normalize();
     }
 
+    /// Writes the view in Radiance's format (e.g., `-vtv -vp 0 0 0 ...`), as
+    /// used in the `VIEW` line of HDR images. The vertical angle
+    /// is calculated from the `aspect_ratio` (i.e., height/width) of the image,
+    /// the same way a [`Pinhole`](crate::camera::Pinhole) camera does.
+    pub fn radiance_view(&self, aspect_ratio: Float) -> String {
+        let half_h = (self.field_of_view.to_radians() / 2.).tan();
+        let vertical = 2. * (aspect_ratio * half_h).atan().to_degrees();
+        let (p, d, u) = (self.view_point, self.view_direction, self.view_up);
+        format!(
+            "-vtv -vp {} {} {} -vd {} {} {} -vu {} {} {} -vh {} -vv {}",
+            p.x, p.y, p.z, d.x, d.y, d.z, u.x, u.y, u.z, self.field_of_view, vertical
+        )
+    }
+
     pub fn view_right(&mut self) -> Vector3D {
         let view_right = self.view_direction.cross(self.view_up);
         self.view_up = view_right.cross(self.view_direction);
@@ -29,8 +29,9 @@ pub type ColourMatrix = GenericMatrix<Spectrum>;
 pub fn average_matrix(dc: &Matrix) -> Matrix {
     let (nrows, _ncols) = dc.size();
     let average_operator = Matrix::new(1. / nrows as Float, 1, nrows);
-    // return
-    &average_operator * dc
+    average_operator
+        .fast_prod(dc)
+        .expect("The averaging operator has as many columns as the DC matrix has rows")
 }
 
 pub fn colour_matrix_to_radiance(cm: &ColourMatrix) -> Matrix {
@@ -19,7 +19,7 @@ SOFTWARE.
 */
 
 use crate::colour::Spectrum;
-use crate::colour_matrix::ColourMatrix;
+use crate::colour_matrix::{read_colour_matrix, save_colour_matrix, ColourMatrix};
 use crate::rand::*;
 
 use crate::samplers::sample_cosine_weighted_horizontal_hemisphere;
@@ -28,12 +28,73 @@ use crate::Float;
 use geometry::intersection::SurfaceSide;
 use geometry::Vector3D;
 use geometry::{Point3D, Ray3D};
+use std::hash::{Hash, Hasher};
+use std::path::PathBuf;
 use utils::ProgressBar;
 use weather::solar::ReinhartSky;
 
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
+/// Changing this invalidates all the Daylight Coefficients cached
+/// by previous versions (e.g., because the algorithm changed)
+const CACHE_VERSION: u64 = 1;
+
+/// A [FNV-1a](https://en.wikipedia.org/wiki/Fowler%E2%80%93Noll%E2%80%93Vo_hash_function)
+/// hasher. Unlike the `DefaultHasher` of the standard library, its results do
+/// not change between versions of Rust, so they can be used as keys of
+/// files stored on disk.
+struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= *b as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+/// Feeds a `Float` into a hasher
+fn hash_float<H: Hasher>(v: Float, hasher: &mut H) {
+    v.to_bits().hash(hasher)
+}
+
+/// Feeds the geometry and materials of a [`Scene`]—including
+/// those of its instances—into a hasher
+fn hash_scene<H: Hasher>(scene: &Scene, hasher: &mut H) {
+    scene.triangles.len().hash(hasher);
+    for t in scene.triangles.iter() {
+        t.iter().for_each(|v| hash_float(*v, hasher));
+    }
+    for (a, b, c) in scene.normals.iter() {
+        for v in [a.x, a.y, a.z, b.x, b.y, b.z, c.x, c.y, c.z] {
+            hash_float(v, hasher);
+        }
+    }
+    scene.front_material_indexes.hash(hasher);
+    scene.back_material_indexes.hash(hasher);
+    for m in scene.materials.iter() {
+        format!("{:?}", m).hash(hasher);
+    }
+
+    scene.instances().len().hash(hasher);
+    for instance in scene.instances() {
+        format!("{:?}", instance.transform()).hash(hasher);
+        hash_scene(instance.mesh(), hasher);
+    }
+}
+
 /// A structure meant to calculate DC matrices
 /// for Climate Daylight Simulations.
 #[derive(Debug)]
@@ -41,6 +102,14 @@ pub struct DCFactory {
     pub reinhart: ReinhartSky,
     pub max_depth: usize,
     pub n_ambient_samples: usize,
+
+    /// A directory where the calculated Daylight Coefficients are stored.
+    ///
+    /// If given, each DC matrix is saved in a file named after a hash of
+    /// the scene, the rays and the options of this `DCFactory`; so
+    /// calculating the same matrix again reads it from there instead of
+    /// tracing rays.
+    pub cache_dir: Option<PathBuf>,
 }
 
 impl Default for DCFactory {
@@ -49,16 +118,81 @@ impl Default for DCFactory {
             reinhart: ReinhartSky::new(1),
             max_depth: 190, // russian roulette takes care of this
             n_ambient_samples: 300,
+            cache_dir: None,
         }
     }
 }
 
 impl DCFactory {
+    /// Calculates the Daylight Coefficients of a set of `rays` in a `scene`; that is,
+    /// a matrix with one row per ray and one column per sky patch.
+    ///
+    /// If this `DCFactory` has a `cache_dir`, the matrix is read from there
+    /// when it has been calculated before, and stored there otherwise.
     pub fn calc_dc(
         &self,
         rays: &[Ray3D],
         scene: &Scene,
         progress_bar: Option<&ProgressBar>,
+    ) -> ColourMatrix {
+        let cache_dir = match &self.cache_dir {
+            Some(d) => d,
+            None => return self.trace_dc(rays, scene, progress_bar),
+        };
+        let filename = cache_dir.join(format!("dc_{:016x}.mtx", self.cache_key(rays, scene)));
+
+        if filename.is_file() {
+            if let Ok(dc) = read_colour_matrix(&filename) {
+                if dc.size() == (rays.len(), self.reinhart.n_bins) {
+                    return dc;
+                }
+            }
+        }
+
+        let dc = self.trace_dc(rays, scene, progress_bar);
+        let saved = std::fs::create_dir_all(cache_dir)
+            .map_err(|e| e.to_string())
+            .and_then(|_| save_colour_matrix(&dc, &filename));
+        if let Err(e) = saved {
+            eprintln!(
+                "Warning: Could not cache Daylight Coefficients in '{}': {}",
+                filename.display(),
+                e
+            );
+        }
+        dc
+    }
+
+    /// A hash of everything that affects the Daylight Coefficients
+    /// of some `rays` in a `scene`, used for naming cached matrices.
+    pub fn cache_key(&self, rays: &[Ray3D], scene: &Scene) -> u64 {
+        let mut hasher = StableHasher::default();
+        CACHE_VERSION.hash(&mut hasher);
+
+        // Options
+        self.reinhart.mf.hash(&mut hasher);
+        self.max_depth.hash(&mut hasher);
+        self.n_ambient_samples.hash(&mut hasher);
+
+        // Rays
+        rays.len().hash(&mut hasher);
+        for ray in rays {
+            let (o, d) = (ray.origin, ray.direction);
+            for v in [o.x, o.y, o.z, d.x, d.y, d.z] {
+                hash_float(v, &mut hasher);
+            }
+        }
+
+        hash_scene(scene, &mut hasher);
+        hasher.finish()
+    }
+
+    /// Calculates the Daylight Coefficients by tracing rays
+    fn trace_dc(
+        &self,
+        rays: &[Ray3D],
+        scene: &Scene,
+        progress_bar: Option<&ProgressBar>,
     ) -> ColourMatrix {
         // Initialize matrix
         let n_bins = self.reinhart.n_bins;
@@ -183,17 +317,15 @@ impl DCFactory {
 
             let (triangle_index, mut interaction) = intersect.unwrap();
             let material = match interaction.geometry_shading.side {
-                SurfaceSide::Front => {
-                    &scene.materials[scene.front_material_indexes[triangle_index]]
-                }
-                SurfaceSide::Back => &scene.materials[scene.back_material_indexes[triangle_index]],
+                SurfaceSide::Front => scene.front_material(triangle_index),
+                SurfaceSide::Back => scene.back_material(triangle_index),
                 SurfaceSide::NonApplicable => {
                     // Hit parallel to the surface...
                     break;
                 }
             };
             #[cfg(feature = "textures")]
-            interaction.interpolate_normal(scene.normals[triangle_index]);
+            interaction.interpolate_normal(scene.vertex_normals(triangle_index));
 
             // reached limit.
             depth += 1;
@@ -230,4 +362,83 @@ impl DCFactory {
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+    use crate::material::{Material, Plastic};
+    use crate::primitive::Primitive;
+    use geometry::Triangle3D;
+
+    fn scene() -> Scene {
+        let mut scene = Scene::new();
+        let plastic = scene.push_material(Material::Plastic(Plastic {
+            colour: Spectrum::gray(0.5),
+            specularity: 0.0,
+            roughness: 0.0,
+        }));
+        let triangle = Triangle3D::new(
+            Point3D::new(-1., -1., 1.),
+            Point3D::new(1., -1., 1.),
+            Point3D::new(0., 1., 1.),
+        )
+        .unwrap();
+        scene.push_object(plastic, plastic, Primitive::Triangle(triangle));
+        scene.build_accelerator();
+        scene
+    }
+
+    #[test]
+    fn test_cache_key() {
+        let factory = DCFactory::default();
+        let rays = vec![Ray3D {
+            origin: Point3D::new(0., 0., 0.),
+            direction: Vector3D::new(0., 0., 1.),
+        }];
+        let scene = scene();
+        let key = factory.cache_key(&rays, &scene);
+        assert_eq!(key, factory.cache_key(&rays, &scene));
+
+        // Different options
+        let other = DCFactory {
+            n_ambient_samples: 10,
+            ..DCFactory::default()
+        };
+        assert_ne!(key, other.cache_key(&rays, &scene));
+
+        // Different rays
+        let moved = vec![Ray3D {
+            origin: Point3D::new(0., 0., 0.5),
+            direction: Vector3D::new(0., 0., 1.),
+        }];
+        assert_ne!(key, factory.cache_key(&moved, &scene));
+
+        // Different scene
+        assert_ne!(key, factory.cache_key(&rays, &Scene::new()));
+    }
+
+    #[test]
+    fn test_cache() {
+        let cache_dir = std::env::temp_dir().join("simple_dc_cache_test");
+        let _ = std::fs::remove_dir_all(&cache_dir);
+        let factory = DCFactory {
+            n_ambient_samples: 10,
+            cache_dir: Some(cache_dir.clone()),
+            ..DCFactory::default()
+        };
+        let rays = vec![Ray3D {
+            origin: Point3D::new(0., 0., 0.),
+            direction: Vector3D::new(0., 0., 1.),
+        }];
+        let scene = scene();
+
+        let dc = factory.calc_dc(&rays, &scene, None);
+        let filename = cache_dir.join(format!("dc_{:016x}.mtx", factory.cache_key(&rays, &scene)));
+        assert!(filename.is_file());
+
+        // Read from the cache... random numbers would give a different matrix
+        let cached = factory.calc_dc(&rays, &scene, None);
+        assert_eq!(dc.size(), cached.size());
+        assert_eq!(format!("{}", dc), format!("{}", cached));
+
+        let _ = std::fs::remove_dir_all(&cache_dir);
+    }
+}
@@ -21,11 +21,11 @@ SOFTWARE.
 use crate::colour::Spectrum;
 use crate::Float;
 
-use crate::material::{Dielectric, Diffuse, Glass, Light, Metal, Mirror, Plastic};
+use crate::material::{Dielectric, Diffuse, Glass, Klems, Light, Metal, Mirror, Plastic};
 
 use crate::material::Material;
 use crate::primitive::Primitive;
-use crate::scene::Scene;
+use crate::scene::{Scene, Wavelengths};
 
 use geometry::{DistantSource3D, Loop3D, Point3D, Polygon3D, Sphere3D, Triangulation3D, Vector3D};
 
@@ -173,6 +173,7 @@ impl RadianceReader {
             b"mirror" => self.consume_mirror(source, scene, &modifier, &name),
             b"dielectric" => self.consume_dielectric(source, scene, &modifier, &name),
             b"glass" => self.consume_glass(source, scene, &modifier, &name),
+            b"BSDF" => self.consume_bsdf(source, scene, &modifier, &name),
 
             // objects
             b"sphere" => self.consume_sphere(source, scene, &modifier, &name),
@@ -476,6 +477,69 @@ impl RadianceReader {
         Ok(())
     }
 
+    /// Consumes a BSDF material, which reads an LBNL/WINDOW XML file.
+    ///
+    /// Only the thickness, file and up direction are read; the
+    /// thickness must be zero, and the function file, transform and
+    /// diffuse components are not supported.
+    fn consume_bsdf(
+        &mut self,
+        source: &[u8],
+        scene: &mut Scene,
+        _modifier: &str,
+        name: &str,
+    ) -> Result<(), String> {
+        let n_strings = self
+            .consume_token(source)?
+            .parse::<usize>()
+            .map_err(|e| e.to_string())?;
+        if n_strings < 6 {
+            self.error_here(format!(
+                "Incorrect BSDF definition... expected at least 6 string arguments; found '{}'",
+                n_strings
+            ));
+        }
+        let thickness = self
+            .consume_token(source)?
+            .parse::<Float>()
+            .map_err(|e| e.to_string())?;
+        if thickness != 0.0 {
+            self.error_here(format!(
+                "Only BSDF materials with zero thickness are supported; found '{}'",
+                thickness
+            ));
+        }
+        let filename = self.consume_token(source)?;
+        let mut up = [0.0; 3];
+        for v in up.iter_mut() {
+            *v = self
+                .consume_token(source)?
+                .parse::<Float>()
+                .map_err(|e| e.to_string())?;
+        }
+        // function file and transforms
+        for _ in 5..n_strings {
+            self.consume_token(source)?;
+        }
+        let t = self.consume_token(source)?;
+        assert_eq!(t, "0".to_string());
+        let n_reals = self
+            .consume_token(source)?
+            .parse::<usize>()
+            .map_err(|e| e.to_string())?;
+        if n_reals != 0 {
+            self.error_here("Diffuse components of BSDF materials are not supported".to_string());
+        }
+
+        let up = Vector3D::new(up[0], up[1], up[2]);
+        let bsdf = Klems::from_file(&filename, &Wavelengths::Visible, up)?;
+
+        self.modifiers.push(name.to_string());
+        scene.push_material(Material::Klems(bsdf));
+
+        Ok(())
+    }
+
     /// Consumes a sphere
     fn consume_sphere(
         &mut self,
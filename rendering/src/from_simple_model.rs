@@ -19,12 +19,14 @@ SOFTWARE.
 */
 
 use geometry::Triangulation3D;
+use model::substance::Normal;
 use model::{FenestrationType, Model, Substance};
 
 use crate::colour::Spectrum;
 use crate::material::{Glass, Material, Plastic};
 use crate::primitive::Primitive;
 use crate::scene::{Scene, Wavelengths};
+use crate::spectral::SampledSpectrum;
 
 /// An auxiliar structure only meant to create a Scene from a Model
 #[derive(Default)]
@@ -45,8 +47,14 @@ pub enum SceneElement {
     Surface,
     /// Leads to a Fenestration
     Fenestration,
+    /// Leads to a ShadingSurface
+    ShadingSurface,
 }
 
+/// The solar reflectance of a [`ShadingSurface`](model::ShadingSurface) with no
+/// `solar_reflectance`
+const DEFAULT_SHADING_REFLECTANCE: crate::Float = 0.3;
+
 impl SimpleModelReader {
     /// Builds a scene, returning a [`Scene`] object and also a vector
     /// mapping each triangle in the scene to a surface
@@ -55,10 +63,6 @@ impl SimpleModelReader {
         model: &Model,
         wavelength: &Wavelengths,
     ) -> Result<(Scene, Vec<(SceneElement, usize)>), String> {
-        if matches!(wavelength, Wavelengths::Visible) {
-            unimplemented!()
-        }
-
         let mut scene = Scene::new();
         let mut triangle_map = Vec::with_capacity(model.surfaces.len() * 3);
 
@@ -156,6 +160,26 @@ impl SimpleModelReader {
             }
         }
 
+        // Add shading surfaces, which are only geometry
+        for (shade_i, s) in model.shading_surfaces.iter().enumerate() {
+            let polygon = &s.vertices;
+            if polygon.area() < 1e-5 {
+                continue;
+            }
+            let rho = s.solar_reflectance_or("", DEFAULT_SHADING_REFLECTANCE);
+            let mat_index = scene.push_material(Material::Plastic(Plastic {
+                colour: Spectrum::gray(rho),
+                specularity: 0.0,
+                roughness: 0.0,
+            }));
+
+            let t: Triangulation3D = polygon.try_into()?;
+            for tri in t.get_trilist() {
+                scene.push_object(mat_index, mat_index, Primitive::Triangle(tri));
+                triangle_map.push((SceneElement::ShadingSurface, shade_i));
+            }
+        }
+
         Ok((scene, triangle_map))
     }
 
@@ -193,49 +217,55 @@ impl SimpleModelReader {
     fn substance_to_material(substance: &Substance, wavelength: &Wavelengths) -> Option<Material> {
         match substance {
             Substance::Normal(s) => {
-                let alpha = match *wavelength {
-                    Wavelengths::Solar => match s.front_solar_absorbtance() {
-                        Ok(v) => *v,
-                        Err(_) => {
-                            // eprintln!("Substance '{}' does not have a Solar Absorbtance... assuming value of {}", s.name, v);
-                            0.7
-                        }
-                    },
-                    Wavelengths::Visible => match s.front_visible_reflectance() {
-                        Ok(v) => *v,
-                        Err(_) => {
-                            // eprintln!("Substance '{}' does not have a Solar Absorbtance... assuming value of {}", s.name, v);
-                            0.7
-                        }
-                    },
-                };
-                let rho = 1. - alpha;
-                let tau = match *wavelength {
-                    Wavelengths::Solar => match s.solar_transmittance() {
-                        Ok(v) => transmittance_to_transmissivity(*v),
-                        Err(_) => {
-                            // eprintln!("Substance '{}' does not have a Solar Absorbtance... assuming value of {}", s.name, v);
-                            0.
-                        }
-                    },
-                    Wavelengths::Visible => match s.visible_transmissivity() {
-                        Ok(v) => *v,
-                        Err(_) => {
-                            // eprintln!("Substance '{}' does not have a Solar Absorbtance... assuming value of {}", s.name, v);
-                            0.
-                        }
-                    },
+                let (rho, tau) = match *wavelength {
+                    Wavelengths::Spectral(pass) => Self::spectral_properties(s, pass),
+                    Wavelengths::Solar | Wavelengths::Visible => {
+                        let rho = match *wavelength {
+                            Wavelengths::Solar => match s.front_solar_absorbtance() {
+                                Ok(v) => 1. - *v,
+                                Err(_) => {
+                                    // eprintln!("Substance '{}' does not have a Solar Absorbtance... assuming value of {}", s.name, v);
+                                    0.3
+                                }
+                            },
+                            // Visible properties are given as reflectance
+                            _ => match s.front_visible_reflectance() {
+                                Ok(v) => *v,
+                                Err(_) => {
+                                    // eprintln!("Substance '{}' does not have a Visible Reflectance... assuming value of {}", s.name, v);
+                                    0.3
+                                }
+                            },
+                        };
+                        let tau = match *wavelength {
+                            Wavelengths::Solar => match s.solar_transmittance() {
+                                Ok(v) => transmittance_to_transmissivity(*v),
+                                Err(_) => {
+                                    // eprintln!("Substance '{}' does not have a Solar Absorbtance... assuming value of {}", s.name, v);
+                                    0.
+                                }
+                            },
+                            _ => match s.visible_transmissivity() {
+                                Ok(v) => *v,
+                                Err(_) => {
+                                    // eprintln!("Substance '{}' does not have a Solar Absorbtance... assuming value of {}", s.name, v);
+                                    0.
+                                }
+                            },
+                        };
+                        (Spectrum::gray(rho), Spectrum::gray(tau))
+                    }
                 };
 
                 // return
-                if tau > 0.0 {
+                if !tau.is_black() {
                     Some(Material::Glass(Glass {
-                        colour: Spectrum::gray(tau),
+                        colour: tau,
                         refraction_index: 1.52,
                     }))
                 } else {
                     Some(Material::Plastic(Plastic {
-                        colour: Spectrum::gray(rho),
+                        colour: rho,
                         specularity: 0.0,
                         roughness: 0.0,
                     }))
@@ -244,6 +274,29 @@ impl SimpleModelReader {
             Substance::Gas(_) => None,
         }
     }
+
+    /// Gets the reflectance and transmissivity of a substance in the bands
+    /// of a pass of a spectral rendering. Substances without spectral data
+    /// have the same visible properties in all bands.
+    fn spectral_properties(s: &Normal, pass: usize) -> (Spectrum, Spectrum) {
+        let rho = match s.front_spectral_reflectance() {
+            Ok(v) => SampledSpectrum(v.clone()).pass(pass),
+            Err(_) => Spectrum::gray(*s.front_visible_reflectance().unwrap_or(&0.3)),
+        };
+        let tau = match s.spectral_transmittance() {
+            Ok(v) => {
+                let mut tau = SampledSpectrum(v.clone()).pass(pass);
+                for t in tau.0.iter_mut() {
+                    if *t > 0. {
+                        *t = transmittance_to_transmissivity(*t);
+                    }
+                }
+                tau
+            }
+            Err(_) => Spectrum::gray(*s.visible_transmissivity().unwrap_or(&0.)),
+        };
+        (rho, tau)
+    }
 }
 
 #[cfg(test)]
@@ -253,7 +306,7 @@ mod tests {
     use crate::material::Light;
     use crate::Float;
     use geometry::{DistantSource3D, Loop3D, Point3D, Polygon3D, Vector3D};
-    use model::{Construction, Fenestration, Surface};
+    use model::{Construction, Fenestration, ShadingSurface, Surface};
     use validate::assert_close;
 
     #[test]
@@ -261,6 +314,41 @@ mod tests {
         assert_close!(0.96, transmittance_to_transmissivity(0.88), 1e-2)
     }
 
+    #[test]
+    fn test_spectral_materials() -> Result<(), String> {
+        use crate::spectral::N_SPECTRAL_BANDS;
+
+        // Reddish, with spectral data
+        let mut paint = Normal::new("paint");
+        paint.set_front_spectral_reflectance(
+            (0..N_SPECTRAL_BANDS)
+                .map(|i| 0.1 * (i + 1) as Float)
+                .collect(),
+        );
+        let paint = paint.wrap();
+        let material = SimpleModelReader::substance_to_material(&paint, &Wavelengths::Spectral(1))
+            .ok_or("No material")?;
+        if let Material::Plastic(p) = material {
+            assert_close!(p.colour.0[0], 0.4, 1e-9);
+            assert_close!(p.colour.0[2], 0.6, 1e-9);
+        } else {
+            return Err("Expecting Plastic".into());
+        }
+
+        // Without spectral data
+        let mut glass = Normal::new("glass");
+        glass.set_visible_transmissivity(0.8);
+        let material =
+            SimpleModelReader::substance_to_material(&glass.wrap(), &Wavelengths::Spectral(0))
+                .ok_or("No material")?;
+        if let Material::Glass(g) = material {
+            assert_eq!(g.colour, Spectrum::gray(0.8));
+        } else {
+            return Err("Expecting Glass".into());
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_scene_from_model() -> Result<(), String> {
         // BUILD SCENE
@@ -432,6 +520,26 @@ mod tests {
         assert_eq!(element_type, SceneElement::Fenestration);
         assert_eq!(0, index);
 
+        /* ... AND A SHADING SURFACE */
+        let mut the_l = Loop3D::with_capacity(3);
+        the_l.push(Point3D::new(0., 0., 2.))?;
+        the_l.push(Point3D::new(1., 0., 2.))?;
+        the_l.push(Point3D::new(0., 1., 2.))?;
+        the_l.close()?;
+
+        let poly = Polygon3D::new(the_l)?;
+        model.add_shading_surface(ShadingSurface::new("some overhang", poly));
+
+        let mut r = SimpleModelReader::default();
+        let (scene, map) = r.build_scene(&model, &Wavelengths::Solar)?;
+
+        assert_eq!(map.len(), scene.triangles.len());
+        assert_eq!(map.len(), 6);
+
+        let (element_type, index) = map[5];
+        assert_eq!(element_type, SceneElement::ShadingSurface);
+        assert_eq!(0, index);
+
         Ok(())
     }
 }
@@ -28,6 +28,7 @@ SOFTWARE.
  written by Bruce Walter  (bjw@graphics.cornell.edu)  5/26/95
  based on code written by Greg Ward
 */
+use crate::camera::View;
 use crate::colour::Spectrum;
 use crate::colourmap::Colourmap;
 use crate::Float;
@@ -78,6 +79,84 @@ fn colour_to_rgbe(red: Float, green: Float, blue: Float) -> [u8; 4] {
     }
 }
 
+/// The minimum length of a run worth encoding when
+/// writing run-length encoded scanlines
+const MIN_RUN_LENGTH: usize = 4;
+
+/// Writes the bytes of one component of a scanline, run-length encoded
+fn write_bytes_rle(data: &[u8], out: &mut Vec<u8>) {
+    let n = data.len();
+    let mut cur = 0;
+    while cur < n {
+        let mut beg_run = cur;
+        let mut run_count = 0;
+        let mut old_run_count = 0;
+        // Find the next run of (at least) MIN_RUN_LENGTH bytes
+        while run_count < MIN_RUN_LENGTH && beg_run < n {
+            beg_run += run_count;
+            old_run_count = run_count;
+            run_count = 1;
+            while beg_run + run_count < n
+                && run_count < 127
+                && data[beg_run] == data[beg_run + run_count]
+            {
+                run_count += 1;
+            }
+        }
+        // A short run right before the long one
+        if old_run_count > 1 && old_run_count == beg_run - cur {
+            out.push(128 + old_run_count as u8);
+            out.push(data[cur]);
+            cur = beg_run;
+        }
+        // Non-run bytes
+        while cur < beg_run {
+            let nonrun_count = (beg_run - cur).min(128);
+            out.push(nonrun_count as u8);
+            out.extend_from_slice(&data[cur..cur + nonrun_count]);
+            cur += nonrun_count;
+        }
+        // The run itself
+        if run_count >= MIN_RUN_LENGTH {
+            out.push(128 + run_count as u8);
+            out.push(data[beg_run]);
+            cur += run_count;
+        }
+    }
+}
+
+/// Reads the bytes of one component of a run-length encoded scanline,
+/// returning the number of bytes consumed from `content`
+fn read_bytes_rle(content: &[u8], data: &mut [u8]) -> Result<usize, String> {
+    let errmsg = "Bad run-length encoded scanline";
+    let mut i = 0;
+    let mut ptr = 0;
+    while ptr < data.len() {
+        let count = *content.get(i).ok_or(errmsg)? as usize;
+        i += 1;
+        if count > 128 {
+            // A run
+            let count = count - 128;
+            if ptr + count > data.len() {
+                return Err(errmsg.to_string());
+            }
+            let value = *content.get(i).ok_or(errmsg)?;
+            i += 1;
+            data[ptr..ptr + count].fill(value);
+            ptr += count;
+        } else {
+            // A non-run
+            if count == 0 || ptr + count > data.len() || i + count > content.len() {
+                return Err(errmsg.to_string());
+            }
+            data[ptr..ptr + count].copy_from_slice(&content[i..i + count]);
+            i += count;
+            ptr += count;
+        }
+    }
+    Ok(i)
+}
+
 fn rgbe_to_colour(r: u8, g: u8, b: u8, e: u8) -> Spectrum {
     if e == 0 {
         return Spectrum::BLACK;
@@ -164,24 +243,143 @@ impl ImageBuffer {
         }
     }
 
-    /// Saves the image in HDRE format
+    /// Saves the image in HDRE format (i.e., Radiance's `.hdr`)
     pub fn save_hdre(&self, filename: &Path) -> Result<(), String> {
-        // Create the file
         let mut file = std::fs::File::create(filename).map_err(|e| e.to_string())?;
+        self.write_hdre(&mut file, None)
+    }
+
+    /// Saves the image in HDRE format (i.e., Radiance's `.hdr`), including
+    /// the `VIEW` from which it was rendered in the header. This is required
+    /// by some Radiance programs (e.g., `evalglare`)
+    pub fn save_hdre_with_view(&self, filename: &Path, view: &View) -> Result<(), String> {
+        let mut file = std::fs::File::create(filename).map_err(|e| e.to_string())?;
+        self.write_hdre(&mut file, Some(view))
+    }
+
+    /// Writes the image in HDRE format, with run-length encoded
+    /// scanlines whenever the width of the image allows it
+    fn write_hdre<W: Write>(&self, out: &mut W, view: Option<&View>) -> Result<(), String> {
         // Write header
-        // let gamma = 1.0;
-        // let exposure = 1.0;
-        file.write_all(b"#?RGBE\n").map_err(|e| e.to_string())?;
-        // file.write_all(format!("GAMMA={}\n", gamma).as_bytes()).map_err(|e| e.to_string())?;
-        // file.write_all(format!("EXPOSURE={}\n", exposure).as_bytes()).map_err(|e| e.to_string())?;
-        file.write_all(b"FORMAT=32-bit_rle_rgbe\n\n")
-            .map_err(|e| e.to_string())?;
-        file.write_all(format!("-Y {} +X {}\n", self.height, self.width).as_bytes())
+        let mut header = String::from("#?RADIANCE\nSOFTWARE=SIMPLE\n");
+        if let Some(view) = view {
+            let aspect_ratio = self.height as Float / self.width as Float;
+            header += &format!("VIEW= {}\n", view.radiance_view(aspect_ratio));
+        }
+        header += "FORMAT=32-bit_rle_rgbe\n\n";
+        header += &format!("-Y {} +X {}\n", self.height, self.width);
+        out.write_all(header.as_bytes())
             .map_err(|e| e.to_string())?;
 
-        for pixel in self.pixels.iter() {
-            file.write_all(&colour_to_rgbe(pixel.0[0], pixel.0[1], pixel.0[2]))
-                .map_err(|e| e.to_string())?;
+        let rgbe: Vec<[u8; 4]> = self
+            .pixels
+            .iter()
+            .map(|pixel| colour_to_rgbe(pixel.0[0], pixel.0[1], pixel.0[2]))
+            .collect();
+
+        // Scanlines that are too short or too long cannot be encoded
+        if self.width < 8 || self.width > 0x7fff {
+            for pixel in rgbe.iter() {
+                out.write_all(pixel).map_err(|e| e.to_string())?;
+            }
+            return Ok(());
+        }
+
+        let mut buffer = Vec::with_capacity(4 * self.width);
+        let mut component = vec![0; self.width];
+        for scanline in rgbe.chunks_exact(self.width) {
+            buffer.clear();
+            buffer.extend_from_slice(&[2, 2, (self.width >> 8) as u8, (self.width & 0xff) as u8]);
+            for i in 0..4 {
+                for (c, pixel) in component.iter_mut().zip(scanline.iter()) {
+                    *c = pixel[i];
+                }
+                write_bytes_rle(&component, &mut buffer);
+            }
+            out.write_all(&buffer).map_err(|e| e.to_string())?;
+        }
+
+        Ok(())
+    }
+
+    /// Saves the image in OpenEXR format, as uncompressed 32-bit
+    /// floating point RGB channels
+    pub fn save_exr(&self, filename: &Path) -> Result<(), String> {
+        let mut file = std::fs::File::create(filename).map_err(|e| e.to_string())?;
+        self.write_exr(&mut file)
+    }
+
+    /// Writes the image in OpenEXR format (scanline, one scanline
+    /// per chunk, no compression)
+    fn write_exr<W: Write>(&self, out: &mut W) -> Result<(), String> {
+        fn attribute(header: &mut Vec<u8>, name: &str, kind: &str, value: &[u8]) {
+            header.extend_from_slice(name.as_bytes());
+            header.push(0);
+            header.extend_from_slice(kind.as_bytes());
+            header.push(0);
+            header.extend_from_slice(&(value.len() as i32).to_le_bytes());
+            header.extend_from_slice(value);
+        }
+
+        // Magic number and version 2 (single-part scanline file)
+        let mut header: Vec<u8> = vec![0x76, 0x2f, 0x31, 0x01, 2, 0, 0, 0];
+
+        // Channels, in alphabetical order, as FLOAT (i.e., 2)
+        let mut channels = Vec::new();
+        for name in ["B", "G", "R"] {
+            channels.extend_from_slice(name.as_bytes());
+            channels.push(0);
+            channels.extend_from_slice(&2i32.to_le_bytes());
+            channels.extend_from_slice(&[0, 0, 0, 0]); // pLinear and reserved
+            channels.extend_from_slice(&1i32.to_le_bytes()); // xSampling
+            channels.extend_from_slice(&1i32.to_le_bytes()); // ySampling
+        }
+        channels.push(0);
+        attribute(&mut header, "channels", "chlist", &channels);
+        attribute(&mut header, "compression", "compression", &[0]);
+
+        let mut window = Vec::new();
+        for v in [0, 0, self.width as i32 - 1, self.height as i32 - 1] {
+            window.extend_from_slice(&v.to_le_bytes());
+        }
+        attribute(&mut header, "dataWindow", "box2i", &window);
+        attribute(&mut header, "displayWindow", "box2i", &window);
+        attribute(&mut header, "lineOrder", "lineOrder", &[0]);
+        attribute(
+            &mut header,
+            "pixelAspectRatio",
+            "float",
+            &1f32.to_le_bytes(),
+        );
+        attribute(&mut header, "screenWindowCenter", "v2f", &[0; 8]);
+        attribute(
+            &mut header,
+            "screenWindowWidth",
+            "float",
+            &1f32.to_le_bytes(),
+        );
+        header.push(0);
+
+        // Offset table
+        let chunk_size = 8 + 3 * 4 * self.width;
+        let first_chunk = header.len() + 8 * self.height;
+        for y in 0..self.height {
+            header.extend_from_slice(&((first_chunk + y * chunk_size) as u64).to_le_bytes());
+        }
+        out.write_all(&header).map_err(|e| e.to_string())?;
+
+        // Scanlines
+        let mut chunk = Vec::with_capacity(chunk_size);
+        for (y, scanline) in self.pixels.chunks_exact(self.width).enumerate() {
+            chunk.clear();
+            chunk.extend_from_slice(&(y as i32).to_le_bytes());
+            chunk.extend_from_slice(&((chunk_size - 8) as i32).to_le_bytes());
+            for channel in [2, 1, 0] {
+                for pixel in scanline.iter() {
+                    chunk.extend_from_slice(&(pixel.0[channel] as f32).to_le_bytes());
+                }
+            }
+            out.write_all(&chunk).map_err(|e| e.to_string())?;
         }
 
         Ok(())
@@ -200,10 +398,16 @@ impl ImageBuffer {
                 ))
             }
         };
-        let mut content = content.as_slice();
         let filename = filename
             .to_str()
             .ok_or("Could not transform filename into string")?;
+        Self::from_hdre_bytes(&content, filename)
+    }
+
+    /// Creates a new [`ImageBuffer`] from the content of an HDRE
+    /// file (flat or run-length encoded)
+    fn from_hdre_bytes(content: &[u8], filename: &str) -> Result<Self, String> {
+        let mut content = content;
 
         // READ HEADER
         let height: Option<usize>;
@@ -271,14 +475,47 @@ impl ImageBuffer {
         let width = width.ok_or("no width?")?;
         let height = height.ok_or("no height")?;
 
-        // Read body
-        let pixels = content
-            .chunks_exact(4)
-            .map(|x| {
-                let (r, g, b, e) = (x[0], x[1], x[2], x[3]);
-                rgbe_to_colour(r, g, b, e)
-            })
-            .collect();
+        // Read body... flat files do not start with a run-length
+        // encoded scanline marker
+        let is_rle = (8..=0x7fff).contains(&width)
+            && content.len() >= 4
+            && content[0] == 2
+            && content[1] == 2
+            && content[2] & 0x80 == 0;
+        let pixels = if is_rle {
+            let mut pixels = Vec::with_capacity(width * height);
+            let mut components: [Vec<u8>; 4] = std::array::from_fn(|_| vec![0; width]);
+            for _ in 0..height {
+                if content.len() < 4
+                    || content[0] != 2
+                    || content[1] != 2
+                    || ((content[2] as usize) << 8 | content[3] as usize) != width
+                {
+                    return Err(format!(
+                        "When reading file '{}' : Wrong scanline width",
+                        filename
+                    ));
+                }
+                content = &content[4..];
+                for c in components.iter_mut() {
+                    let n = read_bytes_rle(content, c)?;
+                    content = &content[n..];
+                }
+                let [r, g, b, e] = &components;
+                for (((r, g), b), e) in r.iter().zip(g).zip(b).zip(e) {
+                    pixels.push(rgbe_to_colour(*r, *g, *b, *e));
+                }
+            }
+            pixels
+        } else {
+            content
+                .chunks_exact(4)
+                .map(|x| {
+                    let (r, g, b, e) = (x[0], x[1], x[2], x[3]);
+                    rgbe_to_colour(r, g, b, e)
+                })
+                .collect()
+        };
 
         // return
         Ok(Self {
@@ -286,7 +523,7 @@ impl ImageBuffer {
             height,
             pixels,
         })
-    } // end of from_file()
+    } // end of from_hdre_bytes()
 
     /// Creates a new version of an image, but in (log10) falsecolour
     pub fn save_log_falsecolour(
@@ -568,6 +805,93 @@ mod tests {
         Ok(())
     }
 
+    fn test_image(width: usize, height: usize) -> ImageBuffer {
+        let pixels = (0..width * height)
+            .map(|i| {
+                // Some repeated values, for testing the runs
+                let v = (i / 5) as Float;
+                Spectrum([v, 2. * v + 0.3, 0.5])
+            })
+            .collect();
+        ImageBuffer::from_pixels(width, height, pixels)
+    }
+
+    #[test]
+    fn test_rle() -> Result<(), String> {
+        let data: Vec<u8> = vec![1, 2, 3, 3, 3, 3, 3, 3, 4, 4, 5, 5, 5, 5, 9, 8, 8];
+        let mut encoded = Vec::new();
+        write_bytes_rle(&data, &mut encoded);
+        assert!(encoded.len() < data.len());
+
+        let mut decoded = vec![0; data.len()];
+        let n = read_bytes_rle(&encoded, &mut decoded)?;
+        assert_eq!(n, encoded.len());
+        assert_eq!(data, decoded);
+        Ok(())
+    }
+
+    #[test]
+    fn test_hdre_roundtrip() -> Result<(), String> {
+        // Width 4 is written flat; 20 is run-length encoded
+        for width in [4, 20] {
+            let image = test_image(width, 3);
+            let mut content = Vec::new();
+            image.write_hdre(&mut content, None)?;
+            let read = ImageBuffer::from_hdre_bytes(&content, "test")?;
+            assert_eq!(read.width, width);
+            assert_eq!(read.height, 3);
+            assert_eq!(read.pixels.len(), image.pixels.len());
+            for (a, b) in image.pixels.iter().zip(read.pixels.iter()) {
+                for i in 0..3 {
+                    assert!((a.0[i] - b.0[i]).abs() <= 0.01 * a.max());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_hdre_view() -> Result<(), String> {
+        let image = test_image(20, 10);
+        let mut content = Vec::new();
+        let view = View::default();
+        image.write_hdre(&mut content, Some(&view))?;
+        let header = String::from_utf8_lossy(&content[0..100]);
+        assert!(header.starts_with("#?RADIANCE\n"));
+        assert!(header.contains("VIEW= -vtv -vp 0 0 0 -vd 0 1 0 -vu 0 0 1 -vh 60 -vv "));
+
+        // It can still be read
+        ImageBuffer::from_hdre_bytes(&content, "test")?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_exr() -> Result<(), String> {
+        let (width, height) = (7, 3);
+        let image = test_image(width, height);
+        let mut content = Vec::new();
+        image.write_exr(&mut content)?;
+
+        assert_eq!(&content[0..8], &[0x76, 0x2f, 0x31, 0x01, 2, 0, 0, 0]);
+        let chunk_size = 8 + 3 * 4 * width;
+
+        // The header ends with a null byte, followed by the offset table
+        let first_chunk = content.len() - height * chunk_size;
+        let table = &content[first_chunk - 8 * height..first_chunk];
+        assert_eq!(content[first_chunk - 8 * height - 1], 0);
+        for y in 0..height {
+            let offset = u64::from_le_bytes(table[8 * y..8 * y + 8].try_into().unwrap()) as usize;
+            assert_eq!(offset, first_chunk + y * chunk_size);
+            let chunk_y = i32::from_le_bytes(content[offset..offset + 4].try_into().unwrap());
+            assert_eq!(chunk_y, y as i32);
+
+            // First value is the Blue channel of the first pixel in the scanline
+            let blue = f32::from_le_bytes(content[offset + 8..offset + 12].try_into().unwrap());
+            assert!((blue as Float - image[(0, y)].0[2]).abs() < 1e-6);
+        }
+        Ok(())
+    }
+
     // #[test]
     // #[ignore]
     // fn test_from_file() {
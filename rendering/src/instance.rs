@@ -0,0 +1,159 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::scene::Scene;
+use crate::triangle::Triangle;
+use crate::Float;
+use geometry::{BBox3D, Point3D, Ray3D, Transform, Vector3D};
+use std::sync::Arc;
+
+/// A [`Scene`] placed—once or many times—within another [`Scene`] (e.g., the
+/// same piece of furniture repeated in every apartment of a building).
+///
+/// All the instances of a mesh share its triangles and its acceleration
+/// structure; each of them only stores its [`Transform`] (from the coordinates
+/// of the mesh into those of the containing scene). Moving an instance
+/// does not require rebuilding anything.
+///
+/// Light sources and skies of the instanced scenes are ignored.
+#[derive(Clone)]
+pub struct Instance {
+    /// The instanced geometry, in its own coordinates
+    pub(crate) mesh: Arc<Scene>,
+
+    /// Transforms the mesh into the coordinates of the containing scene
+    pub(crate) transform: Transform,
+
+    /// The bounds of the instance, in the coordinates of the containing scene
+    pub(crate) bounds: BBox3D,
+
+    /// The index of the first material of the mesh among the
+    /// materials of the containing scene
+    pub(crate) material_offset: usize,
+
+    /// The index of the first triangle of this instance, counting
+    /// the triangles of all the previous instances
+    pub(crate) first_triangle: usize,
+}
+
+impl Instance {
+    /// Creates a new `Instance`. Returns an error if the mesh has no
+    /// acceleration structure.
+    pub(crate) fn new(
+        mesh: Arc<Scene>,
+        transform: Transform,
+        material_offset: usize,
+        first_triangle: usize,
+    ) -> Result<Self, String> {
+        let mesh_bounds = match &mesh.accelerator {
+            Some(a) => a.bounds(),
+            None => return Err("Instanced scenes need an acceleration structure".to_string()),
+        };
+        // Empty meshes have no bounds... place them nowhere
+        let bounds = match mesh_bounds {
+            Some(b) => transform.transform_bbox(b),
+            None => BBox3D::from_point(Point3D::new(Float::MAX, Float::MAX, Float::MAX)),
+        };
+        Ok(Self {
+            mesh,
+            transform,
+            bounds,
+            material_offset,
+            first_triangle,
+        })
+    }
+
+    /// The instanced [`Scene`]
+    pub fn mesh(&self) -> &Arc<Scene> {
+        &self.mesh
+    }
+
+    /// The [`Transform`] placing the mesh in the containing scene
+    pub fn transform(&self) -> &Transform {
+        &self.transform
+    }
+
+    /// The bounds of the instance, in the coordinates of the containing scene
+    pub fn bounds(&self) -> BBox3D {
+        self.bounds
+    }
+
+    /// The number of triangles in the instance
+    pub fn n_triangles(&self) -> usize {
+        self.mesh.triangles.len()
+    }
+
+    /// Moves the instance, updating its bounds
+    pub(crate) fn set_transform(&mut self, transform: Transform) -> Result<(), String> {
+        *self = Self::new(
+            self.mesh.clone(),
+            transform,
+            self.material_offset,
+            self.first_triangle,
+        )?;
+        Ok(())
+    }
+
+    /// Finds the closest triangle of the mesh hit by a `ray` given in the
+    /// coordinates of the containing scene. Returns the index of the triangle
+    /// within the mesh, and the point of intersection in the coordinates
+    /// of the containing scene.
+    pub(crate) fn intersect<const N: usize>(
+        &self,
+        ray: Ray3D,
+        inv_dir: &Vector3D,
+        node_aux: &mut [usize; N],
+    ) -> Option<(usize, Point3D)> {
+        if !self.bounds.intersect(ray, inv_dir) {
+            return None;
+        }
+        let local_ray = Ray3D {
+            origin: self.transform.inv_transform_pt(ray.origin),
+            direction: self
+                .transform
+                .inv_transform_vec(ray.direction)
+                .get_normalized(),
+        };
+        let accelerator = self.mesh.accelerator.as_ref()?;
+        let (i, p) = accelerator.intersect(&self.mesh, local_ray, node_aux)?;
+        Some((i, self.transform.transform_pt(p)))
+    }
+
+    /// A triangle of the mesh, in the coordinates of the containing scene
+    pub(crate) fn world_triangle(&self, index: usize) -> Triangle {
+        let t = &self.mesh.triangles[index];
+        let mut ret = [0.0; 9];
+        for (from, to) in t.chunks_exact(3).zip(ret.chunks_exact_mut(3)) {
+            let p = self
+                .transform
+                .transform_pt(Point3D::new(from[0], from[1], from[2]));
+            to.copy_from_slice(&[p.x, p.y, p.z]);
+        }
+        ret
+    }
+
+    /// The normals at the vertices of a triangle of the mesh, in
+    /// the coordinates of the containing scene
+    pub(crate) fn world_normals(&self, index: usize) -> (Vector3D, Vector3D, Vector3D) {
+        let (a, b, c) = self.mesh.normals[index];
+        let t = |n: Vector3D| self.transform.transform_normal(n).get_normalized();
+        (t(a), t(b), t(c))
+    }
+}
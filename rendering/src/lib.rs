@@ -46,7 +46,9 @@ mod colour;
 pub use colour::Spectrum;
 pub mod colourmap;
 pub mod image;
+mod instance;
 pub mod interaction;
+pub use instance::Instance;
 pub mod material;
 
 pub mod primitive;
@@ -56,6 +58,7 @@ pub mod rand;
 pub mod samplers;
 mod scene;
 pub use scene::{Scene, Wavelengths};
+pub mod spectral;
 
 pub mod triangle;
 
@@ -73,7 +76,10 @@ pub use from_simple_model::{SceneElement, SimpleModelReader};
 
 // Ray-tracer
 mod ray_tracer;
-pub use ray_tracer::RayTracer;
+pub use ray_tracer::{Integrator, RayTracer};
+
+pub mod photon_map;
+pub use photon_map::PhotonMap;
 
 // mod backward_metropolis;
 // pub use crate::backward_metropolis::{
@@ -0,0 +1,683 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use crate::colour::Spectrum;
+use crate::material::bsdf_sample::BSDFSample;
+use crate::material::mat_trait::{MatFlag, TransFlag};
+use crate::rand::*;
+use crate::scene::Wavelengths;
+use crate::{Float, PI};
+use geometry::Vector3D;
+
+/// A ring of patches in a [`KlemsBasis`]: all the patches between two
+/// polar angles, divided evenly in azimuth
+#[derive(Debug, Clone)]
+struct KlemsRing {
+    /// The lower polar angle, in radians
+    lower: Float,
+
+    /// The upper polar angle, in radians
+    upper: Float,
+
+    /// The number of patches in the ring
+    n_phis: usize,
+}
+
+impl KlemsRing {
+    /// The projected solid angle of each patch in the ring
+    fn projected_solid_angle(&self) -> Float {
+        PI * (self.upper.sin().powi(2) - self.lower.sin().powi(2)) / self.n_phis as Float
+    }
+}
+
+/// The discretization of the hemisphere used by the matrices in a
+/// [`Klems`] BSDF. It is made of rings of patches, starting from the
+/// normal (i.e., patch 0 is always normal incidence). The first patch of
+/// each ring is centred at an azimuth of zero, and azimuths grow
+/// counterclockwise.
+#[derive(Debug, Clone)]
+pub struct KlemsBasis {
+    /// The rings
+    rings: Vec<KlemsRing>,
+
+    /// The number of patches before each ring
+    acc_patches: Vec<usize>,
+}
+
+impl KlemsBasis {
+    /// Creates a new basis from a list of rings, each one given as the
+    /// lower and upper polar angles (in degrees) and the number of patches.
+    pub fn new(rings: &[(Float, Float, usize)]) -> Result<Self, String> {
+        if rings.is_empty() {
+            return Err("A Klems basis needs at least one ring".to_string());
+        }
+        let mut acc = 0;
+        let mut acc_patches = Vec::with_capacity(rings.len());
+        let mut ret = Vec::with_capacity(rings.len());
+        for (lower, upper, n_phis) in rings.iter() {
+            if *n_phis == 0 || upper <= lower {
+                return Err(format!(
+                    "Invalid ring in Klems basis: from {} to {} degrees, with {} patches",
+                    lower, upper, n_phis
+                ));
+            }
+            acc_patches.push(acc);
+            acc += n_phis;
+            ret.push(KlemsRing {
+                lower: lower.to_radians(),
+                upper: upper.to_radians(),
+                n_phis: *n_phis,
+            });
+        }
+        Ok(Self {
+            rings: ret,
+            acc_patches,
+        })
+    }
+
+    /// The standard `LBNL/Klems Full` basis, with 145 patches
+    pub fn full() -> Self {
+        let bounds = [0., 5., 15., 25., 35., 45., 55., 65., 75., 90.];
+        let n_phis = [1, 8, 16, 20, 24, 24, 24, 16, 12];
+        let rings: Vec<(Float, Float, usize)> = n_phis
+            .iter()
+            .enumerate()
+            .map(|(i, n)| (bounds[i], bounds[i + 1], *n))
+            .collect();
+        Self::new(&rings).expect("The Klems Full basis is valid")
+    }
+
+    /// The number of patches in the basis
+    pub fn n_patches(&self) -> usize {
+        let last = self.rings.len() - 1;
+        self.acc_patches[last] + self.rings[last].n_phis
+    }
+
+    /// Finds the ring of a patch, returning it and the index of the patch within it
+    fn ring(&self, patch: usize) -> (&KlemsRing, usize) {
+        let i = self
+            .acc_patches
+            .iter()
+            .rposition(|acc| *acc <= patch)
+            .unwrap_or(0);
+        (&self.rings[i], patch - self.acc_patches[i])
+    }
+
+    /// The projected solid angle (i.e., the solid angle weighted by the
+    /// cosine of the polar angle) of a patch
+    pub fn projected_solid_angle(&self, patch: usize) -> Float {
+        self.ring(patch).0.projected_solid_angle()
+    }
+
+    /// Gets the patch containing a direction, in local coordinates (i.e.,
+    /// Z is the normal). The polar angle is measured from
+    /// the closest side of the surface (i.e., directions `(x, y, z)`
+    /// and `(x, y, -z)` are in the same patch).
+    pub fn patch(&self, dir: Vector3D) -> usize {
+        let theta = dir.z.abs().min(1.).acos();
+        let i = self
+            .rings
+            .iter()
+            .position(|r| theta < r.upper)
+            .unwrap_or(self.rings.len() - 1);
+        let ring = &self.rings[i];
+        let mut phi = dir.y.atan2(dir.x);
+        if phi < 0. {
+            phi += 2. * PI;
+        }
+        let width = 2. * PI / ring.n_phis as Float;
+        let k = ((phi + width / 2.) / width).floor() as usize % ring.n_phis;
+        self.acc_patches[i] + k
+    }
+
+    /// Samples a direction within a patch, uniformly in projected solid
+    /// angle. The returned direction has a positive Z component.
+    fn sample_patch(&self, patch: usize, u: (Float, Float)) -> Vector3D {
+        let (ring, k) = self.ring(patch);
+        let (sin_l, sin_u) = (ring.lower.sin().powi(2), ring.upper.sin().powi(2));
+        let sin_theta = (sin_l + u.0 * (sin_u - sin_l)).sqrt();
+        let cos_theta = (1. - sin_theta * sin_theta).max(0.).sqrt();
+        let width = 2. * PI / ring.n_phis as Float;
+        let phi = (k as Float - 0.5 + u.1) * width;
+        Vector3D::new(sin_theta * phi.cos(), sin_theta * phi.sin(), cos_theta)
+    }
+}
+
+/// Gets the contents of all the elements called `tag` in an XML document
+fn xml_elements<'a>(src: &'a str, tag: &str) -> Vec<&'a str> {
+    let open = format!("<{}", tag);
+    let close = format!("</{}>", tag);
+    let mut ret = Vec::new();
+    let mut rest = src;
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        // Avoid matching tags that start with the same name
+        match after.chars().next() {
+            Some(c) if c == '>' || c.is_whitespace() => {}
+            _ => {
+                rest = after;
+                continue;
+            }
+        }
+        let content_start = match after.find('>') {
+            Some(i) => i + 1,
+            None => break,
+        };
+        let content = &after[content_start..];
+        let end = match content.find(&close) {
+            Some(i) => i,
+            None => break,
+        };
+        ret.push(&content[..end]);
+        rest = &content[end + close.len()..];
+    }
+    ret
+}
+
+/// Gets the (trimmed) content of the first element called `tag` in an XML document
+fn xml_element<'a>(src: &'a str, tag: &str) -> Option<&'a str> {
+    xml_elements(src, tag).first().map(|s| s.trim())
+}
+
+/// Parses a number in the contents of an element
+fn xml_number(src: &str, tag: &str) -> Result<Float, String> {
+    let v = xml_element(src, tag).ok_or(format!("BSDF file has no '{}' element", tag))?;
+    v.parse()
+        .map_err(|_| format!("Invalid value '{}' for '{}' in BSDF file", v, tag))
+}
+
+/// A Bidirectional Scattering Distribution Function measured or
+/// calculated over a [`KlemsBasis`] (e.g., by LBNL's WINDOW or
+/// `genBSDF`), for modelling complex fenestration systems such as blinds
+/// between panes or fabrics.
+///
+/// The BSDF values (in $`sr^{-1}`$) of each matrix are stored as
+/// `[outgoing * n_patches + incoming]`. Following the conventions of
+/// WINDOW, "front" is the side towards which the normal of the surface
+/// points and the transmission and reflection are named after the side on
+/// which the light arrives. Patches are identified by the direction in which
+/// light travels (see [`KlemsBasis::patch`]), so light that is not
+/// scattered (i.e., goes straight through or is reflected specularly)
+/// remains in the same patch.
+#[derive(Debug, Clone)]
+pub struct Klems {
+    /// The basis of the matrices
+    pub basis: KlemsBasis,
+
+    /// Transmission of light arriving at the front
+    pub front_transmission: Vec<Float>,
+
+    /// Transmission of light arriving at the back
+    pub back_transmission: Vec<Float>,
+
+    /// Reflection of light arriving at the front
+    pub front_reflection: Vec<Float>,
+
+    /// Reflection of light arriving at the back
+    pub back_reflection: Vec<Float>,
+
+    /// The direction (in world coordinates) towards which the
+    /// azimuth of 90 degrees of the basis points (i.e., the "up"
+    /// direction of the window, as in Radiance's `BSDF` material).
+    pub up: Vector3D,
+}
+
+impl Klems {
+    /// Reads a BSDF from an LBNL/WINDOW XML document, using the data of the
+    /// `Visible` or `Solar` wavelength (spectral renderings use `Visible`).
+    ///
+    /// Documents whose rows and columns have different bases are not supported.
+    /// Reflection data is optional; if the back transmission is missing,
+    /// the front one is used for both sides.
+    pub fn from_xml(src: &str, wavelength: &Wavelengths, up: Vector3D) -> Result<Self, String> {
+        // Basis
+        let basis_src =
+            xml_element(src, "AngleBasis").ok_or("BSDF file has no 'AngleBasis' element")?;
+        let mut rings = Vec::new();
+        for block in xml_elements(basis_src, "AngleBasisBlock") {
+            rings.push((
+                xml_number(block, "LowerTheta")?,
+                xml_number(block, "UpperTheta")?,
+                xml_number(block, "nPhis")? as usize,
+            ));
+        }
+        let basis = KlemsBasis::new(&rings)?;
+        let n = basis.n_patches();
+
+        let incident_rows = xml_element(src, "IncidentDataStructure") == Some("Rows");
+        let wavelength_name = match wavelength {
+            Wavelengths::Solar => "Solar",
+            Wavelengths::Visible | Wavelengths::Spectral(_) => "Visible",
+        };
+
+        let mut ret = Self {
+            basis,
+            front_transmission: Vec::new(),
+            back_transmission: Vec::new(),
+            front_reflection: vec![0.0; n * n],
+            back_reflection: vec![0.0; n * n],
+            up,
+        };
+        for data in xml_elements(src, "WavelengthData") {
+            if xml_element(data, "Wavelength") != Some(wavelength_name) {
+                continue;
+            }
+            for block in xml_elements(data, "WavelengthDataBlock") {
+                let direction = xml_element(block, "WavelengthDataDirection")
+                    .ok_or("BSDF data block with no 'WavelengthDataDirection'")?;
+                let values = xml_element(block, "ScatteringData")
+                    .ok_or("BSDF data block with no 'ScatteringData'")?
+                    .split(|c: char| c == ',' || c.is_whitespace())
+                    .filter(|v| !v.is_empty())
+                    .map(|v| {
+                        v.parse::<Float>()
+                            .map_err(|_| format!("Invalid value '{}' in BSDF data", v))
+                    })
+                    .collect::<Result<Vec<Float>, String>>()?;
+                if values.len() != n * n {
+                    return Err(format!(
+                        "BSDF data for '{}' has {} values, but {} were expected for a basis of {} patches",
+                        direction,
+                        values.len(),
+                        n * n,
+                        n
+                    ));
+                }
+                let mut matrix = vec![0.0; n * n];
+                for (k, v) in values.into_iter().enumerate() {
+                    let (outgoing, incoming) = if incident_rows {
+                        (k % n, k / n)
+                    } else {
+                        (k / n, k % n)
+                    };
+                    matrix[outgoing * n + incoming] = v;
+                }
+                match direction {
+                    "Transmission Front" => ret.front_transmission = matrix,
+                    "Transmission Back" => ret.back_transmission = matrix,
+                    "Reflection Front" => ret.front_reflection = matrix,
+                    "Reflection Back" => ret.back_reflection = matrix,
+                    _ => return Err(format!("Unknown BSDF data direction '{}'", direction)),
+                }
+            }
+        }
+
+        if ret.front_transmission.is_empty() {
+            return Err(format!(
+                "BSDF file has no front transmission data for '{}' wavelength",
+                wavelength_name
+            ));
+        }
+        if ret.back_transmission.is_empty() {
+            ret.back_transmission = ret.front_transmission.clone();
+        }
+        Ok(ret)
+    }
+
+    /// Reads a BSDF from an LBNL/WINDOW XML file. See [`Self::from_xml`].
+    pub fn from_file(
+        filename: &str,
+        wavelength: &Wavelengths,
+        up: Vector3D,
+    ) -> Result<Self, String> {
+        let src = std::fs::read_to_string(filename)
+            .map_err(|e| format!("Could not read BSDF file '{}': {}", filename, e))?;
+        Self::from_xml(&src, wavelength, up)
+    }
+
+    /// The fraction of the light arriving (from the front or the back) at a
+    /// certain patch that is transmitted to the other side
+    pub fn hemispherical_transmittance(&self, front: bool, incoming: usize) -> Float {
+        let matrix = if front {
+            &self.front_transmission
+        } else {
+            &self.back_transmission
+        };
+        self.hemispherical(matrix, incoming)
+    }
+
+    /// The fraction of the light arriving (from the front or the back) at a
+    /// certain patch that is reflected
+    pub fn hemispherical_reflectance(&self, front: bool, incoming: usize) -> Float {
+        let matrix = if front {
+            &self.front_reflection
+        } else {
+            &self.back_reflection
+        };
+        self.hemispherical(matrix, incoming)
+    }
+
+    fn hemispherical(&self, matrix: &[Float], incoming: usize) -> Float {
+        let n = self.basis.n_patches();
+        (0..n)
+            .map(|o| matrix[o * n + incoming] * self.basis.projected_solid_angle(o))
+            .sum()
+    }
+
+    /// The transmittance at normal incidence, used as the colour of the material
+    pub fn colour(&self) -> Spectrum {
+        Spectrum::gray(self.hemispherical_transmittance(true, 0))
+    }
+
+    /// Gets the X and Y axes of the local coordinate system of the basis,
+    /// given the `normal` of the surface (i.e., its Z axis)
+    pub fn local_axes(&self, normal: Vector3D) -> (Vector3D, Vector3D) {
+        let mut y = self.up - normal * (self.up * normal);
+        if y.length() < 1e-9 {
+            // Up is parallel to the normal... pick anything
+            y = normal
+                .get_perpendicular()
+                .unwrap_or(Vector3D::new(0., 1., 0.));
+        }
+        let y = y.get_normalized();
+        let x = y.cross(normal);
+        (x, y)
+    }
+
+    /// The matrices that scatter light towards an observer on the
+    /// `front` (or back) side: the reflection of that side and
+    /// the transmission of the other one
+    fn matrices(&self, front: bool) -> (&[Float], &[Float]) {
+        if front {
+            (&self.front_reflection, &self.back_transmission)
+        } else {
+            (&self.back_reflection, &self.front_transmission)
+        }
+    }
+
+    /// Samples the direction from which light arrives, given the
+    /// direction `wo` in which a ray travels towards the surface (in local
+    /// coordinates, where the normal points towards the side from which the
+    /// ray comes, which is the front if `front`).
+    pub fn sample_bsdf(
+        &self,
+        wo: Vector3D,
+        front: bool,
+        rng: &mut RandGen,
+        trans_flags: TransFlag,
+    ) -> Option<BSDFSample> {
+        let n = self.basis.n_patches();
+        let (reflection, transmission) = self.matrices(front);
+        // The light leaves the surface towards the observer
+        let outgoing = self.basis.patch(wo * -1.);
+
+        let weight = |matrix: &[Float], incoming: usize| {
+            matrix[outgoing * n + incoming] * self.basis.projected_solid_angle(incoming)
+        };
+        let use_reflection = trans_flags & TransFlag::Reflection;
+        let use_transmission = trans_flags & TransFlag::Transmission;
+        let mut total = 0.0;
+        for incoming in 0..n {
+            if use_reflection {
+                total += weight(reflection, incoming);
+            }
+            if use_transmission {
+                total += weight(transmission, incoming);
+            }
+        }
+        if total < 1e-19 {
+            return None;
+        }
+
+        // Choose a patch
+        let mut target = rng.gen::<Float>() * total;
+        let mut chosen = None;
+        let candidates = [
+            (true, reflection, use_reflection),
+            (false, transmission, use_transmission),
+        ];
+        'outer: for (is_reflection, matrix, used) in candidates {
+            if !used {
+                continue;
+            }
+            for incoming in 0..n {
+                let w = weight(matrix, incoming);
+                if w > 0. && target <= w {
+                    chosen = Some((is_reflection, incoming, matrix[outgoing * n + incoming]));
+                    break 'outer;
+                }
+                target -= w;
+            }
+        }
+        let (is_reflection, incoming, value) = chosen?;
+
+        // Light travels towards the surface (i.e., against the normal)
+        // when reflected, and crosses it (i.e., along the normal) when
+        // transmitted. The ray goes the opposite way.
+        let u = self.basis.sample_patch(incoming, (rng.gen(), rng.gen()));
+        let (wi, flags) = if is_reflection {
+            (Vector3D::new(-u.x, -u.y, u.z), MatFlag::GlossyReflection)
+        } else {
+            (Vector3D::new(-u.x, -u.y, -u.z), MatFlag::GlossyTransmission)
+        };
+        let pdf = value * u.z / total;
+        Some(BSDFSample::new(Spectrum::gray(value), wi, pdf, flags))
+    }
+
+    /// Evaluates the BSDF for light arriving from direction `wi` and
+    /// leaving towards `wo` (both pointing away from the surface, in local
+    /// coordinates where the normal points towards `wo`, which is the front
+    /// if `front`)
+    pub fn eval_bsdf(&self, wo: Vector3D, wi: Vector3D, front: bool) -> Spectrum {
+        let n = self.basis.n_patches();
+        let (reflection, transmission) = self.matrices(front);
+        let outgoing = self.basis.patch(wo);
+        let incoming = self.basis.patch(wi * -1.);
+        let matrix = if wi.z > 0. { reflection } else { transmission };
+        Spectrum::gray(matrix[outgoing * n + incoming])
+    }
+
+    /// The probability (per unit solid angle) of [`Self::sample_bsdf`]
+    /// returning `wi` (pointing away from the surface) when observed
+    /// from `wo` (also pointing away from the surface)
+    pub fn pdf(&self, wo: Vector3D, wi: Vector3D, front: bool) -> Float {
+        let n = self.basis.n_patches();
+        let (reflection, transmission) = self.matrices(front);
+        let outgoing = self.basis.patch(wo);
+        let total: Float = (0..n)
+            .map(|i| {
+                (reflection[outgoing * n + i] + transmission[outgoing * n + i])
+                    * self.basis.projected_solid_angle(i)
+            })
+            .sum();
+        if total < 1e-19 {
+            return 0.;
+        }
+        self.eval_bsdf(wo, wi, front).0[0] * wi.z.abs() / total
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use validate::assert_close;
+
+    /// A basis with 5 patches, and a clear glazing that transmits 60% and
+    /// reflects 10% specularly, but only 20% from the back
+    fn xml() -> String {
+        let basis = KlemsBasis::new(&[(0., 45., 1), (45., 90., 4)]).unwrap();
+        let diagonal = |v: Float| -> String {
+            let mut rows = Vec::new();
+            for o in 0..5 {
+                let row: Vec<String> = (0..5)
+                    .map(|i| {
+                        if i == o {
+                            format!("{}", v / basis.projected_solid_angle(i))
+                        } else {
+                            "0".to_string()
+                        }
+                    })
+                    .collect();
+                rows.push(row.join(", "));
+            }
+            rows.join(",\n")
+        };
+        let block = |direction: &str, v: Float| {
+            format!(
+                "<WavelengthDataBlock>
+                    <WavelengthDataDirection>{}</WavelengthDataDirection>
+                    <ColumnAngleBasis>Test</ColumnAngleBasis>
+                    <RowAngleBasis>Test</RowAngleBasis>
+                    <ScatteringDataType>BTDF</ScatteringDataType>
+                    <ScatteringData>{}</ScatteringData>
+                </WavelengthDataBlock>",
+                direction,
+                diagonal(v)
+            )
+        };
+        format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<WindowElement>
+<Optical>
+<Layer>
+    <DataDefinition>
+        <IncidentDataStructure>Columns</IncidentDataStructure>
+        <AngleBasis>
+            <AngleBasisName>Test</AngleBasisName>
+            <AngleBasisBlock>
+                <Theta>0</Theta>
+                <nPhis>1</nPhis>
+                <ThetaBounds><LowerTheta>0</LowerTheta><UpperTheta>45</UpperTheta></ThetaBounds>
+            </AngleBasisBlock>
+            <AngleBasisBlock>
+                <Theta>67.5</Theta>
+                <nPhis>4</nPhis>
+                <ThetaBounds><LowerTheta>45</LowerTheta><UpperTheta>90</UpperTheta></ThetaBounds>
+            </AngleBasisBlock>
+        </AngleBasis>
+    </DataDefinition>
+    <WavelengthData>
+        <LayerNumber>System</LayerNumber>
+        <Wavelength unit=\"Integral\">Visible</Wavelength>
+        {}{}{}
+    </WavelengthData>
+</Layer>
+</Optical>
+</WindowElement>",
+            block("Transmission Front", 0.6),
+            block("Reflection Front", 0.1),
+            block("Reflection Back", 0.2),
+        )
+    }
+
+    #[test]
+    fn test_basis() {
+        let basis = KlemsBasis::full();
+        assert_eq!(basis.n_patches(), 145);
+        assert_eq!(basis.patch(Vector3D::new(0., 0., 1.)), 0);
+        assert_eq!(basis.patch(Vector3D::new(0., 0., -1.)), 0);
+        // First patch of the second ring
+        let dir = Vector3D::new((10. as Float).to_radians().sin(), 0., 1.);
+        assert_eq!(basis.patch(dir.get_normalized()), 1);
+        // Grazing, slightly clockwise from the start
+        assert_eq!(basis.patch(Vector3D::new(1., -0.01, 0.)), 144 - 11);
+
+        // The projected solid angles add up to PI
+        let total: Float = (0..145).map(|i| basis.projected_solid_angle(i)).sum();
+        assert_close!(total, PI, 1e-9);
+
+        // Samples fall within their patch
+        let mut rng = get_rng();
+        for patch in 0..145 {
+            let dir = basis.sample_patch(patch, (rng.gen(), rng.gen()));
+            assert_eq!(basis.patch(dir), patch);
+        }
+    }
+
+    #[test]
+    fn test_xml_elements() {
+        let src = "<a><WavelengthDataBlock>block</WavelengthDataBlock><Wavelength unit=\"x\"> Visible </Wavelength></a>";
+        assert_eq!(xml_element(src, "Wavelength"), Some("Visible"));
+        assert_eq!(xml_element(src, "WavelengthDataBlock"), Some("block"));
+        assert_eq!(xml_elements(src, "b").len(), 0);
+    }
+
+    #[test]
+    fn test_from_xml() -> Result<(), String> {
+        let up = Vector3D::new(0., 0., 1.);
+        let bsdf = Klems::from_xml(&xml(), &Wavelengths::Visible, up)?;
+        assert_eq!(bsdf.basis.n_patches(), 5);
+        for i in 0..5 {
+            assert_close!(bsdf.hemispherical_transmittance(true, i), 0.6, 1e-9);
+            // Same as the front
+            assert_close!(bsdf.hemispherical_transmittance(false, i), 0.6, 1e-9);
+            assert_close!(bsdf.hemispherical_reflectance(true, i), 0.1, 1e-9);
+            assert_close!(bsdf.hemispherical_reflectance(false, i), 0.2, 1e-9);
+        }
+        assert_close!(bsdf.colour().0[0], 0.6, 1e-9);
+
+        // No solar data
+        assert!(Klems::from_xml(&xml(), &Wavelengths::Solar, up).is_err());
+        // Bad data
+        assert!(Klems::from_xml(&xml().replace("0, 0,", "0,"), &Wavelengths::Visible, up).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sample() -> Result<(), String> {
+        let bsdf = Klems::from_xml(&xml(), &Wavelengths::Visible, Vector3D::new(0., 1., 0.))?;
+        let mut rng = get_rng();
+
+        // A ray coming from the front
+        let wo = Vector3D::new(1.5, 0.2, -1.).get_normalized();
+        let mut transmitted = 0.0;
+        let mut reflected = 0.0;
+        let n = 10000;
+        for _ in 0..n {
+            let sample = bsdf
+                .sample_bsdf(wo, true, &mut rng, TransFlag::All)
+                .ok_or("No sample")?;
+            // Specular... the patch does not change
+            assert_eq!(bsdf.basis.patch(sample.wi), bsdf.basis.patch(wo));
+            let pdf = bsdf.pdf(wo * -1., sample.wi, true);
+            assert_close!(pdf, sample.pdf, 1e-9);
+            let f = bsdf.eval_bsdf(wo * -1., sample.wi, true);
+            assert_close!(f.0[0], sample.spectrum.0[0], 1e-9);
+
+            let throughput = sample.spectrum.0[0] * sample.wi.z.abs() / sample.pdf;
+            if sample.is_transmission() {
+                assert!(sample.wi.z < 0.);
+                transmitted += throughput;
+            } else {
+                assert!(sample.wi.z > 0.);
+                reflected += throughput;
+            }
+        }
+        // Transmission from the back (i.e., same as front), reflection from the front
+        assert_close!(transmitted / n as Float, 0.6, 0.03);
+        assert_close!(reflected / n as Float, 0.1, 0.03);
+        assert_close!((transmitted + reflected) / n as Float, 0.7, 1e-6);
+
+        // Only reflection
+        let sample = bsdf
+            .sample_bsdf(wo, false, &mut rng, TransFlag::Reflection)
+            .ok_or("No sample")?;
+        assert!(sample.is_reflection());
+        assert_close!(
+            sample.spectrum.0[0] * sample.wi.z.abs() / sample.pdf,
+            0.2,
+            1e-9
+        );
+        Ok(())
+    }
+}
@@ -51,6 +51,9 @@ pub use glass::Glass;
 mod specular;
 pub use specular::*;
 
+mod klems;
+pub use klems::{Klems, KlemsBasis};
+
 pub mod bsdf_sample;
 mod local_coordinates_utils;
 
@@ -66,6 +69,7 @@ pub enum Material {
     Mirror(Mirror),
     Dielectric(Dielectric),
     Glass(Glass),
+    Klems(Klems),
 }
 
 impl Material {
@@ -79,6 +83,7 @@ impl Material {
             Self::Mirror(m) => m.id(),
             Self::Dielectric(m) => m.id(),
             Self::Glass(m) => m.id(),
+            Self::Klems(_) => "klems",
         }
     }
 
@@ -94,6 +99,7 @@ impl Material {
             Self::Mirror(m) => m.colour(),
             Self::Dielectric(m) => m.colour(),
             Self::Glass(m) => m.colour(),
+            Self::Klems(m) => m.colour(),
         }
     }
 
@@ -146,7 +152,10 @@ impl Material {
         rng: &mut RandGen,
     ) -> Option<BSDFSample> {
         // world to local
-        let (intersection_pt, normal, e1, e2) = interaction.get_triad();
+        let (intersection_pt, normal, mut e1, mut e2) = interaction.get_triad();
+        if let Self::Klems(m) = self {
+            (e1, e2) = m.local_axes(normal);
+        }
         let wo = self.to_local(normal, e1, e2, wo);
 
         let transport_mode = TransportMode::default();
@@ -172,6 +181,10 @@ impl Material {
                 ret
             }
             Self::Glass(m) => m.sample_bsdf(wo, *eta, rng, transport_mode, trans_flags),
+            Self::Klems(m) => {
+                let front = interaction.geometry_shading.side == SurfaceSide::Front;
+                m.sample_bsdf(wo, front, rng, trans_flags)
+            }
         };
 
         if let Some(sample) = &mut ret {
@@ -196,6 +209,13 @@ impl Material {
         vout: Vector3D,
         eta: Float,
     ) -> Spectrum {
+        // The triad is right-handed on the front of the surface
+        let front = e1.cross(e2) * normal > 0.;
+        let (e1, e2) = match self {
+            Self::Klems(m) => m.local_axes(normal),
+            _ => (e1, e2),
+        };
+
         // convert ray into local
         vin = self.to_local(normal, e1, e2, vin);
         let vout = self.to_local(normal, e1, e2, vout);
@@ -204,6 +224,7 @@ impl Material {
             Self::Diffuse(m) => m.eval_bsdf(vin, vout, eta, TransportMode::default()),
             Self::Plastic(m) => m.eval_bsdf(vin, vout, eta, TransportMode::default()),
             Self::Metal(m) => m.eval_bsdf(vin, vout, eta, TransportMode::default()),
+            Self::Klems(m) => m.eval_bsdf(vin, vout, front),
             Self::Light(_) | Self::Mirror(_) | Self::Dielectric(_) | Self::Glass(_) => {
                 Spectrum::BLACK
             }
@@ -220,6 +241,13 @@ impl Material {
         vout: Vector3D,
         eta: Float,
     ) -> Float {
+        // The triad is right-handed on the front of the surface
+        let front = e1.cross(e2) * normal > 0.;
+        let (e1, e2) = match self {
+            Self::Klems(m) => m.local_axes(normal),
+            _ => (e1, e2),
+        };
+
         // convert ray into local
         vin = self.to_local(normal, e1, e2, vin);
         let vout = self.to_local(normal, e1, e2, vout);
@@ -228,6 +256,8 @@ impl Material {
             Self::Diffuse(m) => m.pdf(vin, vout, eta, TransportMode::default()),
             Self::Plastic(m) => m.pdf(vin, vout, eta, TransportMode::default()),
             Self::Metal(m) => m.pdf(vin, vout, eta, TransportMode::default()),
+            // This function receives the directions the other way around
+            Self::Klems(m) => m.pdf(vout, vin, front),
             Self::Light(_) | Self::Mirror(_) | Self::Dielectric(_) | Self::Glass(_) => 0.,
         }
     }
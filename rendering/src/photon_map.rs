@@ -0,0 +1,397 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! A caustic photon map, used for estimating the light that reaches diffuse
+//! surfaces after bouncing off mirrors or going through glass (i.e., the
+//! paths that unidirectional path tracing finds with very low probability).
+//!
+//! Photons are emitted from the light sources in the scene (both area and
+//! distant ones), traced through specular surfaces, and stored at the first
+//! non-specular surface they hit. Photons that hit a non-specular surface
+//! directly are discarded, as direct lighting is sampled explicitly by the
+//! [`RayTracer`](crate::RayTracer). The sky is not emitted.
+
+use crate::colour::Spectrum;
+use crate::interaction::Interaction;
+use crate::material::Material;
+use crate::primitive::Primitive;
+use crate::primitive_samplers::{sample_sphere_surface, sample_triangle_surface};
+use crate::rand::*;
+use crate::samplers::{
+    local_to_world, sample_cosine_weighted_horizontal_hemisphere, uniform_sample_tilted_disc,
+};
+use crate::scene::Scene;
+use crate::{Float, PI};
+use geometry::intersection::SurfaceSide;
+use geometry::{BBox3D, Point3D, Ray3D, Vector3D};
+use std::collections::HashMap;
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// A packet of light stored on a non-specular surface
+#[derive(Debug, Clone, Copy)]
+struct Photon {
+    /// The point where the photon hit the surface
+    position: Point3D,
+
+    /// The direction the photon came from (i.e., pointing away
+    /// from the surface)
+    wi: Vector3D,
+
+    /// The flux carried by the photon
+    power: Spectrum,
+}
+
+/// A set of caustic photons, indexed in a uniform grid whose cells
+/// are as large as the gathering radius.
+#[derive(Debug, Clone, Default)]
+pub struct PhotonMap {
+    photons: Vec<Photon>,
+    radius: Float,
+    cells: HashMap<[i64; 3], Vec<usize>>,
+}
+
+/// Samples a point on a light source and a direction in which a photon
+/// leaves it. Returns the ray followed by the photon and its power, assuming
+/// that `n_photons` will be emitted in total. `bounds` are the centre and
+/// radius of a sphere containing the scene, which is required for emitting
+/// photons from distant lights.
+fn emit_photon(
+    scene: &Scene,
+    bounds: Option<(Point3D, Float)>,
+    n_photons: usize,
+    rng: &mut RandGen,
+) -> Option<(Ray3D, Spectrum)> {
+    let (light, p_light) = scene.sample_light_uniform(rng)?;
+    let front = &scene.materials[light.front_material_index];
+    let (material, side) = if front.emits_light() {
+        (front, 1.)
+    } else {
+        (&scene.materials[light.back_material_index], -1.)
+    };
+    let p = p_light * n_photons as Float;
+
+    let (point, normal, area) = match &light.primitive {
+        Primitive::Triangle(t) => (sample_triangle_surface(t, rng), t.normal() * side, t.area()),
+        Primitive::Sphere(s) => {
+            let point = sample_sphere_surface(s, rng);
+            (point, (point - s.centre()).get_normalized(), s.area())
+        }
+        Primitive::Source(s) => {
+            // Photons come from a disc perpendicular to the source,
+            // just outside of the scene
+            let (centre, radius) = bounds?;
+            let direction = light.primitive.sample_direction(rng, centre);
+            let normal = s.direction.get_normalized();
+            let disc_centre = centre + normal * (2. * radius);
+            let origin = uniform_sample_tilted_disc(rng.gen(), radius, disc_centre, normal);
+            let power = material.colour() * s.omega * PI * radius * radius / p;
+            let ray = Ray3D {
+                origin,
+                direction: direction * -1.,
+            };
+            return Some((ray, power));
+        }
+        Primitive::Cylinder(_) => return None,
+    };
+
+    // Cosine-weighted emission, as lights are Lambertian
+    let local = sample_cosine_weighted_horizontal_hemisphere(rng.gen());
+    let e2 = normal.get_perpendicular().unwrap();
+    let e1 = e2.cross(normal);
+    let (x, y, z) = local_to_world(
+        e1,
+        e2,
+        normal,
+        Point3D::new(0., 0., 0.),
+        local.x,
+        local.y,
+        local.z,
+    );
+    let ray = Ray3D {
+        origin: point + normal * 0.001, // prevent self-intersection
+        direction: Vector3D::new(x, y, z),
+    };
+    let power = material.colour() * area * PI / p;
+    Some((ray, power))
+}
+
+/// Follows a photon through the scene, returning it if it reaches a
+/// non-specular surface after at least one specular bounce
+fn trace_photon<const N: usize>(
+    scene: &Scene,
+    mut ray: Ray3D,
+    mut power: Spectrum,
+    max_depth: usize,
+    rng: &mut RandGen,
+    aux: &mut [usize; N],
+) -> Option<Photon> {
+    let mut refraction_coefficient = 1.0;
+    for depth in 0..=max_depth {
+        let (triangle_index, mut interaction) = scene.cast_ray(ray, aux)?;
+        let material = match interaction.geometry_shading.side {
+            SurfaceSide::Front => scene.front_material(triangle_index),
+            SurfaceSide::Back => scene.back_material(triangle_index),
+            SurfaceSide::NonApplicable => return None,
+        };
+        if material.emits_light() {
+            return None;
+        }
+
+        #[cfg(feature = "textures")]
+        interaction.interpolate_normal(scene.vertex_normals(triangle_index));
+
+        if !material.specular_only() {
+            if depth == 0 {
+                // Direct lighting is sampled by the ray-tracer
+                return None;
+            }
+            return Some(Photon {
+                position: interaction.point,
+                wi: ray.direction * -1.,
+                power,
+            });
+        }
+
+        let sample = material.sample_bsdf(
+            ray.direction,
+            &mut interaction,
+            &mut refraction_coefficient,
+            rng,
+        )?;
+        let cos_theta = (interaction.geometry_shading.normal * sample.wi).abs();
+        power *= sample.spectrum * cos_theta / sample.pdf;
+        if power.is_black() {
+            return None;
+        }
+        ray = Ray3D {
+            direction: sample.wi,
+            origin: interaction.point,
+        };
+    }
+    None
+}
+
+/// The centre and radius of a sphere containing the scene, including its instances
+fn scene_sphere(scene: &Scene) -> Option<(Point3D, Float)> {
+    let mut bounds = scene.accelerator.as_ref().and_then(|a| a.bounds());
+    for instance in scene.instances() {
+        bounds = Some(match bounds {
+            Some(b) => BBox3D::from_union(&b, &instance.bounds()),
+            None => instance.bounds(),
+        });
+    }
+    let bounds = bounds?;
+    let diagonal = bounds.max - bounds.min;
+    Some((bounds.min + diagonal * 0.5, diagonal.length() * 0.5))
+}
+
+impl PhotonMap {
+    /// Emits `n_photons` from the light sources in the `scene`, storing the
+    /// ones that reach a non-specular surface after bouncing off specular
+    /// ones (up to `max_depth` times). The radiance is later estimated from
+    /// the photons within `radius` of each point.
+    ///
+    /// The `scene` needs to have an acceleration structure.
+    pub fn new(scene: &Scene, n_photons: usize, radius: Float, max_depth: usize) -> Self {
+        assert!(radius > 0., "The radius of a PhotonMap must be positive");
+        let bounds = scene_sphere(scene);
+
+        let chunk_len = 1024;
+        let n_chunks = n_photons.div_ceil(chunk_len);
+
+        #[cfg(not(feature = "parallel"))]
+        let chunks = 0..n_chunks;
+
+        #[cfg(feature = "parallel")]
+        let chunks = (0..n_chunks).into_par_iter();
+
+        let photons: Vec<Vec<Photon>> = chunks
+            .map(|chunk| {
+                let mut rng = get_rng();
+                let mut aux = [0; 32];
+                let n = chunk_len.min(n_photons - chunk * chunk_len);
+                (0..n)
+                    .filter_map(|_| {
+                        let (ray, power) = emit_photon(scene, bounds, n_photons, &mut rng)?;
+                        trace_photon(scene, ray, power, max_depth, &mut rng, &mut aux)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let mut ret = Self {
+            photons: photons.into_iter().flatten().collect(),
+            radius,
+            cells: HashMap::new(),
+        };
+        for (i, photon) in ret.photons.iter().enumerate() {
+            let cell = ret.cell(photon.position);
+            ret.cells.entry(cell).or_default().push(i);
+        }
+        ret
+    }
+
+    /// The number of photons stored in the map
+    pub fn len(&self) -> usize {
+        self.photons.len()
+    }
+
+    /// Checks whether the map has no photons
+    pub fn is_empty(&self) -> bool {
+        self.photons.is_empty()
+    }
+
+    /// The radius within which photons are gathered
+    pub fn radius(&self) -> Float {
+        self.radius
+    }
+
+    /// The cell of the grid that contains a point
+    fn cell(&self, p: Point3D) -> [i64; 3] {
+        [
+            (p.x / self.radius).floor() as i64,
+            (p.y / self.radius).floor() as i64,
+            (p.z / self.radius).floor() as i64,
+        ]
+    }
+
+    /// Estimates the radiance reflected towards `interaction.wo` by a
+    /// `material`, due to the photons around `interaction.point`
+    pub fn estimate(&self, material: &Material, interaction: &Interaction, eta: Float) -> Spectrum {
+        let mut ret = Spectrum::BLACK;
+        if self.photons.is_empty() {
+            return ret;
+        }
+        let (point, normal, e1, e2) = interaction.get_triad();
+        let r2 = self.radius * self.radius;
+        let [cx, cy, cz] = self.cell(point);
+        for x in cx - 1..=cx + 1 {
+            for y in cy - 1..=cy + 1 {
+                for z in cz - 1..=cz + 1 {
+                    let indexes = match self.cells.get(&[x, y, z]) {
+                        Some(i) => i,
+                        None => continue,
+                    };
+                    for photon in indexes.iter().map(|i| &self.photons[*i]) {
+                        if (photon.position - point).length_squared() > r2 {
+                            continue;
+                        }
+                        let bsdf =
+                            material.eval_bsdf(normal, e1, e2, interaction.wo, photon.wi, eta);
+                        ret += bsdf * photon.power;
+                    }
+                }
+            }
+        }
+        ret / (PI * r2)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::material::{Diffuse, Light, Mirror};
+    use geometry::Triangle3D;
+
+    /// A small light at z = 1 facing up, under a mirror at z = 2 and above
+    /// a floor at z = 0. The only light reaching the floor bounces off the mirror.
+    fn mirror_scene() -> Result<Scene, String> {
+        let mut scene = Scene::new();
+        let floor = scene.push_material(Material::Diffuse(Diffuse {
+            colour: Spectrum::gray(0.5),
+        }));
+        let black = scene.push_material(Material::Diffuse(Diffuse {
+            colour: Spectrum::BLACK,
+        }));
+        let mirror = scene.push_material(Material::Mirror(Mirror(Spectrum::gray(0.9))));
+        let light = scene.push_material(Material::Light(Light(Spectrum::gray(100.))));
+
+        let floor_triangle = Triangle3D::new(
+            Point3D::new(-10., -10., 0.),
+            Point3D::new(10., -10., 0.),
+            Point3D::new(0., 10., 0.),
+        )?;
+        scene.push_object(floor, floor, Primitive::Triangle(floor_triangle));
+        let mirror_triangle = Triangle3D::new(
+            Point3D::new(-10., -10., 2.),
+            Point3D::new(0., 10., 2.),
+            Point3D::new(10., -10., 2.),
+        )?;
+        scene.push_object(mirror, mirror, Primitive::Triangle(mirror_triangle));
+        let light_triangle = Triangle3D::new(
+            Point3D::new(-0.5, -0.5, 1.),
+            Point3D::new(0.5, -0.5, 1.),
+            Point3D::new(0., 0.5, 1.),
+        )?;
+        scene.push_object(light, black, Primitive::Triangle(light_triangle));
+        scene.build_accelerator();
+        Ok(scene)
+    }
+
+    #[test]
+    fn test_only_caustics() -> Result<(), String> {
+        let mut scene = mirror_scene()?;
+        let map = PhotonMap::new(&scene, 10000, 0.5, 10);
+        assert!(!map.is_empty());
+        // Photons land on the floor or on the back of the light
+        assert!(map
+            .photons
+            .iter()
+            .all(|p| p.position.z.abs() < 1e-3 || (p.position.z - 1.).abs() < 1e-3));
+
+        // Without specular surfaces, there are no caustics
+        scene.materials[2] = Material::Diffuse(Diffuse {
+            colour: Spectrum::gray(0.9),
+        });
+        let map = PhotonMap::new(&scene, 10000, 0.5, 10);
+        assert!(map.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_estimate() -> Result<(), String> {
+        let scene = mirror_scene()?;
+        let map = PhotonMap::new(&scene, 200000, 0.5, 10);
+
+        // Look at the floor at (3, 0, 0) from above
+        let ray = Ray3D {
+            origin: Point3D::new(3., 0., 0.5),
+            direction: Vector3D::new(0., 0., -1.),
+        };
+        let mut aux = [0; 32];
+        let (i, interaction) = scene.cast_ray(ray, &mut aux).unwrap();
+        let estimate = map.estimate(scene.front_material(i), &interaction, 1.);
+
+        // The mirrored light is at (0, 0, 3): the irradiance is L * A * cos^2 / d^2
+        let cos = 3. / (18. as Float).sqrt();
+        let irradiance = 0.9 * 100. * 0.5 * cos * cos / 18.;
+        let expected = 0.5 / PI * irradiance;
+        let found = estimate.0[0];
+        assert!(
+            (found - expected).abs() / expected < 0.15,
+            "expected {}, found {}",
+            expected,
+            found
+        );
+        Ok(())
+    }
+}
@@ -23,6 +23,7 @@ use crate::colour::Spectrum;
 use crate::image::ImageBuffer;
 use crate::interaction::Interaction;
 use crate::material::Material;
+use crate::photon_map::PhotonMap;
 use crate::rand::*;
 
 use crate::scene::{Object, Scene};
@@ -33,11 +34,32 @@ use geometry::Ray3D;
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
+/// The algorithm used by a [`RayTracer`] for rendering
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Integrator {
+    /// Unidirectional path tracing
+    #[default]
+    PathTracing,
+
+    /// Path tracing in which caustics (i.e., light reaching diffuse surfaces
+    /// after bouncing off mirrors or going through glass) are estimated from
+    /// a [`PhotonMap`]. This converges much faster in scenes with light pipes,
+    /// mirrors and glazing lit by small sources such as the sun.
+    PhotonMapping {
+        /// The number of photons emitted from the light sources
+        n_photons: usize,
+
+        /// The radius within which photons are gathered
+        radius: Float,
+    },
+}
+
 #[derive(Debug, Clone)]
 pub struct RayTracer {
     pub max_depth: usize,
     pub n_shadow_samples: usize,
     pub n_ambient_samples: usize,
+    pub integrator: Integrator,
 }
 
 impl Default for RayTracer {
@@ -46,6 +68,7 @@ impl Default for RayTracer {
             max_depth: 100,
             n_shadow_samples: 1,
             n_ambient_samples: 70,
+            integrator: Integrator::default(),
         }
     }
 }
@@ -71,11 +94,26 @@ fn balance_heuristic(na: usize, pdfa: Float, nb: usize, pdfb: Float) -> Float {
 impl RayTracer {
     /// Recursively traces  ray
     pub fn trace_ray<const N: usize>(
+        &self,
+        rng: &mut RandGen,
+        scene: &Scene,
+        ray: Ray3D,
+        aux: &mut [usize; N],
+    ) -> Spectrum {
+        self.trace_path(rng, scene, ray, aux, None)
+    }
+
+    /// Traces a path. If a `photon_map` is given, the caustics are
+    /// gathered from it at every non-specular surface, and the paths that
+    /// reach a light source through specular surfaces after such gathering
+    /// are not counted (they are already in the map).
+    fn trace_path<const N: usize>(
         &self,
         rng: &mut RandGen,
         scene: &Scene,
         mut ray: Ray3D,
         aux: &mut [usize; N],
+        photon_map: Option<&PhotonMap>,
     ) -> Spectrum {
         let mut ray_prob = 1.0;
         let mut beta = Spectrum::ONE;
@@ -83,11 +121,16 @@ impl RayTracer {
         let mut refraction_coefficient = 1.0;
         let mut specular_bounce = true;
         let mut spectrum = Spectrum::BLACK;
+        let mut gathered_caustics = false;
+        let mut specular_since_gather = false;
         loop {
+            // Light reaching the last non-specular surface through specular
+            // ones was already gathered from the photon map
+            let in_photon_map = gathered_caustics && specular_since_gather;
             let intersect = scene.cast_ray(ray, aux);
             if intersect.is_none() {
                 // Check distant lights
-                for light in &scene.distant_lights {
+                for light in scene.distant_lights.iter().filter(|_| !in_photon_map) {
                     if let Some((light_colour, light_pdf)) = intersect_light(scene, light, ray, aux)
                     {
                         if light_pdf > 1e-18 {
@@ -113,10 +156,8 @@ impl RayTracer {
 
             let (triangle_index, mut interaction) = intersect.unwrap();
             let material = match interaction.geometry_shading.side {
-                SurfaceSide::Front => {
-                    &scene.materials[scene.front_material_indexes[triangle_index]]
-                }
-                SurfaceSide::Back => &scene.materials[scene.back_material_indexes[triangle_index]],
+                SurfaceSide::Front => scene.front_material(triangle_index),
+                SurfaceSide::Back => scene.back_material(triangle_index),
                 SurfaceSide::NonApplicable => {
                     // Hit parallel to the surface...
                     break;
@@ -126,14 +167,14 @@ impl RayTracer {
             // We hit a light... lights do not reflect,
             // so break
             if material.emits_light() {
-                if specular_bounce {
+                if specular_bounce && !in_photon_map {
                     spectrum += beta * material.colour();
                 }
                 break;
             }
 
             #[cfg(feature = "textures")]
-            interaction.interpolate_normal(scene.normals[triangle_index]);
+            interaction.interpolate_normal(scene.vertex_normals(triangle_index));
 
             // Direct lighting
             if !material.specular_only() {
@@ -148,6 +189,14 @@ impl RayTracer {
                     aux,
                 );
                 spectrum += beta * local;
+
+                if let Some(map) = photon_map {
+                    spectrum += beta * map.estimate(material, &interaction, refraction_coefficient);
+                    gathered_caustics = true;
+                    specular_since_gather = false;
+                }
+            } else {
+                specular_since_gather = true;
             }
 
             // reached limit.
@@ -186,13 +235,26 @@ impl RayTracer {
         spectrum
     }
 
-    /// Recursively traces a single ray
+    /// Recursively traces a single ray. This always uses path tracing, as the
+    /// [`PhotonMap`] required by other integrators is only built by `render()`
     pub fn trace_all_rays<const N: usize>(
         &self,
         rng: &mut RandGen,
         scene: &Scene,
         ray: Ray3D,
         aux: &mut [usize; N],
+    ) -> Spectrum {
+        self.trace_all_paths(rng, scene, ray, aux, None)
+    }
+
+    /// Averages `n_ambient_samples` paths starting with the same ray
+    fn trace_all_paths<const N: usize>(
+        &self,
+        rng: &mut RandGen,
+        scene: &Scene,
+        ray: Ray3D,
+        aux: &mut [usize; N],
+        photon_map: Option<&PhotonMap>,
     ) -> Spectrum {
         // let original_ray = ray;
         let mut spectrum = Spectrum::BLACK;
@@ -205,7 +267,7 @@ impl RayTracer {
         };
 
         for _ in 0..n_ambient_samples {
-            spectrum += self.trace_ray(rng, scene, ray, aux);
+            spectrum += self.trace_path(rng, scene, ray, aux, photon_map);
         }
 
         spectrum / n_ambient_samples as Float
@@ -272,6 +334,16 @@ impl RayTracer {
         local_illum / n_shadow_samples
     }
 
+    /// Builds the [`PhotonMap`] required by the `integrator`, if any
+    pub fn build_photon_map(&self, scene: &Scene) -> Option<PhotonMap> {
+        match self.integrator {
+            Integrator::PathTracing => None,
+            Integrator::PhotonMapping { n_photons, radius } => {
+                Some(PhotonMap::new(scene, n_photons, radius, self.max_depth))
+            }
+        }
+    }
+
     pub fn render(self, scene: &Scene, camera: &dyn Camera) -> ImageBuffer {
         let (width, height) = camera.film_resolution();
         let total_pixels = width * height;
@@ -289,6 +361,9 @@ impl RayTracer {
         height: usize,
         pixels: &mut [Spectrum],
     ) {
+        let photon_map = self.build_photon_map(scene);
+        let photon_map = photon_map.as_ref();
+
         let chunk_len = 128;
         let i: Vec<&mut [Spectrum]> = pixels.chunks_mut(chunk_len).collect();
 
@@ -310,7 +385,7 @@ impl RayTracer {
                 let y = (pindex as Float / width as Float).floor() as usize;
                 let x = pindex - y * width;
                 let (ray, _weight) = camera.gen_ray(&CameraSample { p_film: (x, y) });
-                *pixel = self.trace_all_rays(&mut rng, scene, ray, &mut aux);
+                *pixel = self.trace_all_paths(&mut rng, scene, ray, &mut aux, photon_map);
 
                 progress.tic();
                 pindex += 1;
@@ -367,5 +442,39 @@ pub fn intersect_light<const N: usize>(
 
 #[cfg(test)]
 mod tests {
-    // use super::*;
+    use super::*;
+    use crate::material::Diffuse;
+    use crate::primitive::Primitive;
+    use geometry::{Point3D, Triangle3D};
+
+    #[test]
+    fn test_build_photon_map() -> Result<(), String> {
+        let mut scene = Scene::new();
+        let gray = scene.push_material(Material::Diffuse(Diffuse {
+            colour: Spectrum::gray(0.5),
+        }));
+        let triangle = Triangle3D::new(
+            Point3D::new(0., 0., 0.),
+            Point3D::new(1., 0., 0.),
+            Point3D::new(0., 1., 0.),
+        )?;
+        scene.push_object(gray, gray, Primitive::Triangle(triangle));
+        scene.build_accelerator();
+
+        let tracer = RayTracer::default();
+        assert!(tracer.build_photon_map(&scene).is_none());
+
+        let tracer = RayTracer {
+            integrator: Integrator::PhotonMapping {
+                n_photons: 100,
+                radius: 0.1,
+            },
+            ..RayTracer::default()
+        };
+        let map = tracer.build_photon_map(&scene).unwrap();
+        // There are no lights
+        assert!(map.is_empty());
+        assert!((map.radius() - 0.1).abs() < 1e-9);
+        Ok(())
+    }
 }
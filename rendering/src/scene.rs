@@ -22,6 +22,7 @@ SOFTWARE.
 use crate::bvh::BoundingVolumeTree;
 use crate::colour::Spectrum;
 use crate::from_simple_model::SimpleModelReader;
+use crate::instance::Instance;
 use crate::interaction::Interaction;
 use crate::material::{Light, Material};
 use crate::primitive::Primitive;
@@ -30,9 +31,10 @@ use crate::triangle::Triangle;
 use crate::Float;
 use calendar::Date;
 
-use geometry::{Ray3D, Vector3D};
+use geometry::{Ray3D, Transform, Vector3D};
 use model::Model;
 use rand::Rng;
+use std::sync::Arc;
 
 #[derive(Clone, Default)]
 pub struct Object {
@@ -76,12 +78,21 @@ pub struct Scene {
     /// A function returning the diffuse Sky brightness (i.e., without the sun)
     /// The sun should be added separately.
     /// Alternatively, you can use the `add_perez_sky` function
-    pub sky: Option<Box<dyn Fn(Vector3D) -> Float + Sync>>,
+    pub sky: Option<Box<dyn Fn(Vector3D) -> Float + Send + Sync>>,
+
+    /// Other scenes placed within this one. Their triangles come after
+    /// those in `triangles` (i.e., the first triangle of the first instance
+    /// has index `triangles.len()`).
+    pub(crate) instances: Vec<Instance>,
 }
 
 pub enum Wavelengths {
     Solar,
     Visible,
+    /// One of the passes of a spectral rendering, containing the index of
+    /// the pass (smaller than [`N_SPECTRAL_PASSES`](crate::spectral::N_SPECTRAL_PASSES)).
+    /// See [`SampledSpectrum`](crate::spectral::SampledSpectrum).
+    Spectral(usize),
 }
 
 impl Scene {
@@ -246,27 +257,44 @@ impl Scene {
         ray: Ray3D,
         node_aux: &mut [usize; N],
     ) -> Option<(usize, Interaction)> {
-        if let Some(accelerator) = &self.accelerator {
-            let aux = accelerator.intersect(self, ray, node_aux);
-
-            match aux {
-                Some((i, ..)) => {
-                    // update ray
-                    let t = &self.triangles[i];
-                    let (point, u, v) = crate::triangle::baricentric_coordinates(ray, t).unwrap();
-                    let interaction = Interaction {
-                        point,
-                        wo: ray.direction * -1.0,
-                        geometry_shading: crate::triangle::new_info(t, point, u, v, ray.direction),
-                    };
-
-                    Some((i, interaction))
+        let accelerator = match &self.accelerator {
+            Some(a) => a,
+            None => panic!("Trying to cast_ray() in a scene without an acceleration structure"),
+        };
+        let mut hit = accelerator.intersect(self, ray, node_aux);
+
+        if !self.instances.is_empty() {
+            let mut t_squared = hit
+                .map(|(_, p)| (p - ray.origin).length_squared())
+                .unwrap_or(Float::MAX);
+            let inv_dir = Vector3D::new(
+                1. / ray.direction.x,
+                1. / ray.direction.y,
+                1. / ray.direction.z,
+            );
+            for instance in self.instances.iter() {
+                if let Some((i, p)) = instance.intersect(ray, &inv_dir, node_aux) {
+                    let this_t_squared = (p - ray.origin).length_squared();
+                    if this_t_squared < t_squared {
+                        t_squared = this_t_squared;
+                        hit = Some((self.triangles.len() + instance.first_triangle + i, p));
+                    }
                 }
-                None => None,
             }
-        } else {
-            panic!("Trying to cast_ray() in a scene without an acceleration structure")
         }
+
+        let (i, p) = hit?;
+        let t = self.world_triangle(i);
+        // Triangles of instances are hit in their own coordinates, so
+        // this can (rarely) fail due to rounding errors
+        let (point, u, v) =
+            crate::triangle::baricentric_coordinates(ray, &t).unwrap_or((p, 0.0, 0.0));
+        let interaction = Interaction {
+            point,
+            wo: ray.direction * -1.0,
+            geometry_shading: crate::triangle::new_info(&t, point, u, v, ray.direction),
+        };
+        Some((i, interaction))
     }
 
     /// Checks whether a [`Ray3D`] can travel a certain distance without hitting any surface
@@ -276,13 +304,155 @@ impl Scene {
         distance_squared: Float,
         node_aux: &mut [usize; N],
     ) -> bool {
-        if let Some(a) = &self.accelerator {
-            a.unobstructed_distance(self, ray, distance_squared, node_aux)
-        } else {
-            panic!("Trying to check if unobstructed_distance() in a scene without an acceleration structure")
+        let accelerator = match &self.accelerator {
+            Some(a) => a,
+            None => panic!("Trying to check if unobstructed_distance() in a scene without an acceleration structure"),
+        };
+        if !accelerator.unobstructed_distance(self, ray, distance_squared, node_aux) {
+            return false;
+        }
+
+        // Same criteria as BoundingVolumeTree::unobstructed_distance()
+        const MIN_T: Float = 0.000001;
+        let inv_dir = Vector3D::new(
+            1. / ray.direction.x,
+            1. / ray.direction.y,
+            1. / ray.direction.z,
+        );
+        !self.instances.iter().any(
+            |instance| match instance.intersect(ray, &inv_dir, node_aux) {
+                Some((_, p)) => {
+                    let t_squared = (p - ray.origin).length_squared();
+                    t_squared > MIN_T
+                        && t_squared + MIN_T < distance_squared
+                        && (distance_squared - t_squared).abs() > 0.0001
+                }
+                None => false,
+            },
+        )
+    }
+
+    /// Finds the instance containing the triangle with index `i` (if it
+    /// is not in `triangles`), and the index of the triangle within it
+    fn locate_instance(&self, i: usize) -> Option<(&Instance, usize)> {
+        let i = i.checked_sub(self.triangles.len())?;
+        let pos = self
+            .instances
+            .partition_point(|instance| instance.first_triangle <= i);
+        let instance = &self.instances[pos.checked_sub(1)?];
+        Some((instance, i - instance.first_triangle))
+    }
+
+    /// The triangle with index `i`, in the coordinates of the scene. This
+    /// includes the triangles of the instances.
+    fn world_triangle(&self, i: usize) -> Triangle {
+        match self.locate_instance(i) {
+            Some((instance, local)) => instance.world_triangle(local),
+            None => self.triangles[i],
+        }
+    }
+
+    /// The [`Material`] at the front of the triangle with index `i`, as
+    /// returned by [`Scene::cast_ray`]
+    pub fn front_material(&self, i: usize) -> &Material {
+        match self.locate_instance(i) {
+            Some((instance, local)) => {
+                &self.materials
+                    [instance.material_offset + instance.mesh.front_material_indexes[local]]
+            }
+            None => &self.materials[self.front_material_indexes[i]],
+        }
+    }
+
+    /// The [`Material`] at the back of the triangle with index `i`, as
+    /// returned by [`Scene::cast_ray`]
+    pub fn back_material(&self, i: usize) -> &Material {
+        match self.locate_instance(i) {
+            Some((instance, local)) => {
+                &self.materials
+                    [instance.material_offset + instance.mesh.back_material_indexes[local]]
+            }
+            None => &self.materials[self.back_material_indexes[i]],
+        }
+    }
+
+    /// The normals at the vertices of the triangle with index `i`, as
+    /// returned by [`Scene::cast_ray`]
+    pub fn vertex_normals(&self, i: usize) -> (Vector3D, Vector3D, Vector3D) {
+        match self.locate_instance(i) {
+            Some((instance, local)) => instance.world_normals(local),
+            None => self.normals[i],
+        }
+    }
+
+    /// Places a `mesh` (i.e., another [`Scene`], whose acceleration structure
+    /// needs to be built) within this scene, returning the index of the new
+    /// [`Instance`].
+    ///
+    /// The triangles and acceleration structure of the mesh are not copied,
+    /// so placing the same mesh many times takes very little memory. Its
+    /// materials are copied only the first time it is placed.
+    pub fn push_instance(
+        &mut self,
+        mesh: &Arc<Scene>,
+        transform: Transform,
+    ) -> Result<usize, String> {
+        let material_offset = match self
+            .instances
+            .iter()
+            .find(|instance| Arc::ptr_eq(&instance.mesh, mesh))
+        {
+            Some(instance) => instance.material_offset,
+            None => {
+                let offset = self.materials.len();
+                self.materials.extend(mesh.materials.iter().cloned());
+                offset
+            }
+        };
+        let first_triangle = self
+            .instances
+            .last()
+            .map(|i| i.first_triangle + i.n_triangles())
+            .unwrap_or(0);
+        let instance = Instance::new(mesh.clone(), transform, material_offset, first_triangle)?;
+        self.instances.push(instance);
+        Ok(self.instances.len() - 1)
+    }
+
+    /// The [`Instance`] objects placed in this scene
+    pub fn instances(&self) -> &[Instance] {
+        &self.instances
+    }
+
+    /// Moves the [`Instance`] with index `i`. Unlike moving triangles,
+    /// this does not require updating the acceleration structure.
+    pub fn set_instance_transform(&mut self, i: usize, transform: Transform) -> Result<(), String> {
+        match self.instances.get_mut(i) {
+            Some(instance) => instance.set_transform(transform),
+            None => Err(format!(
+                "Instance {} does not exist... the scene has {} instances",
+                i,
+                self.instances.len()
+            )),
         }
     }
 
+    /// Updates the acceleration structure after moving some triangles, without
+    /// rebuilding it (see [`BoundingVolumeTree::refit`])
+    pub fn refit_accelerator(&mut self) -> Result<(), String> {
+        let mut accelerator = match self.accelerator.take() {
+            Some(a) => a,
+            None => {
+                return Err(
+                    "Trying to refit the acceleration structure of a scene without one".to_string(),
+                )
+            }
+        };
+        let ret = accelerator.refit(self);
+        self.accelerator = Some(accelerator);
+        ret
+    }
+
     /// Pushes a [`Material`] to the [`Scene`] and return its
     /// position in the `materials` Vector.
     pub fn push_material(&mut self, material: Material) -> usize {
@@ -359,6 +529,121 @@ impl Scene {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use crate::colour::Spectrum;
+    use crate::material::Plastic;
+    use geometry::{Point3D, Sphere3D};
+
+    /// A scene with a sphere of radius 0.5 at the origin
+    fn sphere() -> Arc<Scene> {
+        let mut mesh = Scene::new();
+        let plastic = mesh.push_material(Material::Plastic(Plastic {
+            colour: Spectrum::gray(0.5),
+            specularity: 0.,
+            roughness: 0.,
+        }));
+        let sphere = Sphere3D::new(0.5, Point3D::new(0., 0., 0.));
+        mesh.push_object(plastic, plastic, Primitive::Sphere(sphere));
+        mesh.build_accelerator();
+        Arc::new(mesh)
+    }
+
+    #[test]
+    fn test_instances() -> Result<(), String> {
+        let mut scene = Scene::new();
+        let mirror = scene.push_material(Material::Mirror(crate::material::Mirror(
+            Spectrum::gray(0.9),
+        )));
+        let floor = geometry::Triangle3D::new(
+            Point3D::new(-10., -10., -1.),
+            Point3D::new(10., -10., -1.),
+            Point3D::new(0., 10., -1.),
+        )?;
+        scene.push_object(mirror, mirror, Primitive::Triangle(floor));
+
+        let mesh = sphere();
+        assert!(scene
+            .push_instance(&Arc::new(Scene::new()), Transform::new())
+            .is_err());
+        scene.push_instance(&mesh, Transform::translate(-2., 0., 0.))?;
+        scene.push_instance(&mesh, Transform::translate(2., 0., 0.))?;
+        // Materials are shared by all instances of the same mesh
+        assert_eq!(scene.materials.len(), 2);
+        assert_eq!(scene.instances().len(), 2);
+        scene.build_accelerator();
+        let n = scene.triangles.len();
+        let n_sphere = mesh.triangles.len();
+
+        let mut aux = [0; 32];
+        let down = Vector3D::new(0., 0., -1.);
+        let cast = |scene: &Scene, x: Float, aux: &mut [usize; 32]| {
+            let ray = Ray3D {
+                origin: Point3D::new(x, 0., 10.),
+                direction: down,
+            };
+            scene.cast_ray(ray, aux)
+        };
+
+        // Hits the floor
+        let (i, interaction) = cast(&scene, 0., &mut aux).unwrap();
+        assert!(i < n);
+        assert!((interaction.point.z + 1.).abs() < 1e-5);
+        assert!(matches!(scene.front_material(i), Material::Mirror(_)));
+
+        // Hits the top of the spheres
+        for (x, first) in [(-2., n), (2., n + n_sphere)] {
+            let (i, interaction) = cast(&scene, x, &mut aux).unwrap();
+            assert!(i >= first && i < first + n_sphere);
+            assert!((interaction.point.z - 0.5).abs() < 0.05);
+            assert!((interaction.point.x - x).abs() < 0.05);
+            assert!(matches!(scene.front_material(i), Material::Plastic(_)));
+            let (a, ..) = scene.vertex_normals(i);
+            assert!(a.z > 0.5);
+        }
+
+        // The sphere casts a shadow
+        let ray = Ray3D {
+            origin: Point3D::new(2., 0., -0.99),
+            direction: Vector3D::new(0., 0., 1.),
+        };
+        assert!(!scene.unobstructed_distance(ray, 100., &mut aux));
+
+        // Move it away, without rebuilding anything
+        scene.set_instance_transform(1, Transform::translate(5., 0., 0.))?;
+        assert!(scene.unobstructed_distance(ray, 100., &mut aux));
+        let (i, _) = cast(&scene, 5., &mut aux).unwrap();
+        assert!(i >= n + n_sphere);
+        assert!(scene.set_instance_transform(2, Transform::new()).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_refit_accelerator() -> Result<(), String> {
+        let mut scene = Scene::new();
+        assert!(scene.refit_accelerator().is_err());
+        let mesh = sphere();
+        scene.materials = mesh.materials.clone();
+        scene.triangles = mesh.triangles.clone();
+        scene.normals = mesh.normals.clone();
+        scene.front_material_indexes = mesh.front_material_indexes.clone();
+        scene.back_material_indexes = mesh.back_material_indexes.clone();
+        scene.build_accelerator();
+
+        for t in scene.triangles.iter_mut() {
+            t[0] += 3.;
+            t[3] += 3.;
+            t[6] += 3.;
+        }
+        scene.refit_accelerator()?;
+        let ray = Ray3D {
+            origin: Point3D::new(3., 0., 10.),
+            direction: Vector3D::new(0., 0., -1.),
+        };
+        let mut aux = [0; 32];
+        let (_, interaction) = scene.cast_ray(ray, &mut aux).unwrap();
+        assert!((interaction.point.x - 3.).abs() < 0.05);
+        Ok(())
+    }
 
     // #[test]
     // fn test_push_material() {
@@ -0,0 +1,347 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use crate::colour::Spectrum;
+use crate::{Float, N_CHANNELS};
+use serde::{Deserialize, Serialize};
+
+/// The shortest wavelength covered by spectral data, in nm
+pub const MIN_WAVELENGTH: Float = 380.;
+
+/// The longest wavelength covered by spectral data, in nm
+pub const MAX_WAVELENGTH: Float = 780.;
+
+/// The number of passes of the ray-tracer required for spectral rendering.
+/// Each pass traces [`N_CHANNELS`] bands.
+pub const N_SPECTRAL_PASSES: usize = 3;
+
+/// The number of wavelength bands used for spectral rendering
+pub const N_SPECTRAL_BANDS: usize = N_SPECTRAL_PASSES * N_CHANNELS;
+
+/// The number of samples taken within each band when resampling
+const RESAMPLING_SAMPLES: usize = 10;
+
+/// The interval between the wavelengths of the tabulated
+/// functions below, in nm
+const TABLE_STEP: Float = 10.;
+
+/// The CIE 1924 photopic luminous efficiency function $`V(\lambda)`$,
+/// from 380 to 780 nm every [`TABLE_STEP`]
+const PHOTOPIC_EFFICIENCY: [Float; 41] = [
+    0.000039, 0.00012, 0.000396, 0.00121, 0.004, 0.0116, 0.023, 0.038, 0.06, 0.09098, 0.13902,
+    0.20802, 0.323, 0.503, 0.71, 0.862, 0.954, 0.99495, 0.995, 0.952, 0.87, 0.757, 0.631, 0.503,
+    0.381, 0.265, 0.175, 0.107, 0.061, 0.032, 0.017, 0.00821, 0.004102, 0.002091, 0.001047,
+    0.00052, 0.000249, 0.00012, 0.00006, 0.00003, 0.000015,
+];
+
+/// The melanopic efficiency function $`s_{mel}(\lambda)`$ (i.e., the
+/// sensitivity of the ipRGCs, including the filtering of the lens, peaking
+/// at 490 nm as in CIE S 026), from 380 to 780 nm every [`TABLE_STEP`]
+const MELANOPIC_EFFICIENCY: [Float; 41] = [
+    0.00404, 0.01054, 0.02878, 0.07373, 0.16234, 0.28270, 0.42709, 0.57979, 0.73706, 0.87447,
+    0.96328, 1.00000, 0.94663, 0.81956, 0.65322, 0.47244, 0.31188, 0.18609, 0.09973, 0.04731,
+    0.02085, 0.00881, 0.00366, 0.00153, 0.00064, 0.00028, 0.00012, 0.00005, 0.00003, 0.00001,
+    0.00001, 0., 0., 0., 0., 0., 0., 0., 0., 0., 0.,
+];
+
+/// The relative spectral power distribution of the CIE standard
+/// illuminant D65 (i.e., average daylight), from 380 to 780 nm
+/// every [`TABLE_STEP`]
+const D65: [Float; 41] = [
+    49.98, 54.65, 82.75, 91.49, 93.43, 86.68, 104.86, 117.01, 117.81, 114.86, 115.92, 108.81,
+    109.35, 107.80, 104.79, 107.69, 104.41, 104.05, 100.0, 96.33, 95.79, 88.69, 90.01, 89.60,
+    87.70, 83.29, 83.70, 80.03, 80.21, 82.28, 78.28, 69.72, 71.61, 74.35, 61.60, 69.89, 75.09,
+    63.59, 46.42, 66.81, 63.38,
+];
+
+/// The ratio between the Equivalent Melanopic Lux (EML, as used in
+/// WELL v1) and the melanopic Equivalent Daylight Illuminance (M-EDI,
+/// as defined in CIE S 026). That is, `EML = M-EDI * EML_PER_MELANOPIC_EDI`.
+pub const EML_PER_MELANOPIC_EDI: Float = 1. / 0.9058;
+
+/// Interpolates a function tabulated from [`MIN_WAVELENGTH`] every [`TABLE_STEP`]
+fn tabulated_value(table: &[Float], wavelength: Float) -> Float {
+    let x = ((wavelength - MIN_WAVELENGTH) / TABLE_STEP).max(0.);
+    let i = x.floor() as usize;
+    if i + 1 >= table.len() {
+        return table[table.len() - 1];
+    }
+    let t = x - i as Float;
+    table[i] * (1. - t) + table[i + 1] * t
+}
+
+/// A spectral distribution (e.g., of reflectance, transmittance or radiance),
+/// given as the average value of several bands of equal width covering the
+/// visible range (i.e., from [`MIN_WAVELENGTH`] to [`MAX_WAVELENGTH`]).
+///
+/// The ray-tracer handles [`N_CHANNELS`] channels at a time, so spectral
+/// rendering is done in [`N_SPECTRAL_PASSES`] passes: each one traces a
+/// [`Scene`](crate::Scene) built with
+/// [`Wavelengths::Spectral`](crate::Wavelengths::Spectral), whose materials
+/// (and sky) have the values of the corresponding bands (see [`Self::pass`]).
+/// The results of all passes can then be put together
+/// through [`Self::from_passes`].
+///
+/// ```
+/// use rendering::spectral::{SampledSpectrum, N_SPECTRAL_BANDS};
+///
+/// // Reflectance measured at 10 nm intervals
+/// let measured = SampledSpectrum((0..40).map(|i| 0.2 + 0.01 * i as f64).collect());
+/// let bands = measured.resample(N_SPECTRAL_BANDS);
+/// assert_eq!(bands.0.len(), N_SPECTRAL_BANDS);
+/// let first_pass = bands.pass(0);
+/// assert!(first_pass.0[0] < 0.3);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SampledSpectrum(pub Vec<Float>);
+
+impl SampledSpectrum {
+    /// Creates a spectral distribution with `n_bands` bands, all of them
+    /// with the same value `v`
+    pub fn gray(v: Float, n_bands: usize) -> Self {
+        Self(vec![v; n_bands])
+    }
+
+    /// Creates a spectral distribution with `n_bands` bands, whose values
+    /// are the average of function `f`—which receives a wavelength in
+    /// nm—over each band
+    pub fn from_fn<F: Fn(Float) -> Float>(n_bands: usize, f: F) -> Self {
+        let width = (MAX_WAVELENGTH - MIN_WAVELENGTH) / n_bands as Float;
+        let step = width / RESAMPLING_SAMPLES as Float;
+        let data = (0..n_bands)
+            .map(|i| {
+                let start = MIN_WAVELENGTH + i as Float * width;
+                (0..RESAMPLING_SAMPLES)
+                    .map(|j| f(start + (j as Float + 0.5) * step))
+                    .sum::<Float>()
+                    / RESAMPLING_SAMPLES as Float
+            })
+            .collect();
+        Self(data)
+    }
+
+    /// The relative spectral power distribution of the CIE standard
+    /// illuminant D65 (i.e., average daylight), normalized to 1 at 560 nm
+    pub fn d65(n_bands: usize) -> Self {
+        Self::from_fn(n_bands, |l| tabulated_value(&D65, l) / 100.)
+    }
+
+    /// The relative spectral power distribution of a black body at a
+    /// certain `temperature` (in K), normalized to 1 at 560 nm. This can be used
+    /// for approximating light sources from their Correlated Colour Temperature.
+    pub fn black_body(temperature: Float, n_bands: usize) -> Self {
+        // Second radiation constant, in nm.K
+        const C2: Float = 1.4388e7;
+        let planck = |l: Float| 1. / (l.powi(5) * ((C2 / (l * temperature)).exp() - 1.));
+        let reference = planck(560.);
+        Self::from_fn(n_bands, |l| planck(l) / reference)
+    }
+
+    /// The width of each band, in nm
+    pub fn band_width(&self) -> Float {
+        (MAX_WAVELENGTH - MIN_WAVELENGTH) / self.0.len() as Float
+    }
+
+    /// The wavelength at the centre of band `i`, in nm
+    pub fn band_centre(&self, i: usize) -> Float {
+        MIN_WAVELENGTH + (i as Float + 0.5) * self.band_width()
+    }
+
+    /// The value at a certain wavelength (in nm), interpolated linearly
+    /// between the centres of the bands. Wavelengths outside of the range
+    /// take the value of the closest band.
+    pub fn value_at(&self, wavelength: Float) -> Float {
+        let n = self.0.len();
+        if n == 0 {
+            return 0.0;
+        }
+        // Position in "band" units, relative to the centre of the first band
+        let x = (wavelength - MIN_WAVELENGTH) / self.band_width() - 0.5;
+        if x <= 0. {
+            return self.0[0];
+        }
+        let i = x.floor() as usize;
+        if i + 1 >= n {
+            return self.0[n - 1];
+        }
+        let t = x - i as Float;
+        self.0[i] * (1. - t) + self.0[i + 1] * t
+    }
+
+    /// Transforms this distribution into one with `n_bands` bands. The value of
+    /// each new band is the average over its width.
+    pub fn resample(&self, n_bands: usize) -> Self {
+        if n_bands == self.0.len() {
+            return self.clone();
+        }
+        Self::from_fn(n_bands, |l| self.value_at(l))
+    }
+
+    /// Integrates this distribution, weighted by a tabulated function
+    fn weighted_integral(&self, table: &[Float]) -> Float {
+        let step = self.band_width() / RESAMPLING_SAMPLES as Float;
+        self.0
+            .iter()
+            .enumerate()
+            .map(|(i, v)| {
+                let start = MIN_WAVELENGTH + i as Float * self.band_width();
+                let weight: Float = (0..RESAMPLING_SAMPLES)
+                    .map(|j| tabulated_value(table, start + (j as Float + 0.5) * step))
+                    .sum();
+                v * weight * step
+            })
+            .sum()
+    }
+
+    /// The integral of this distribution weighted by the photopic
+    /// luminous efficiency function $`V(\lambda)`$. Multiplying this by
+    /// 683 lm/W transforms spectral irradiance (in W/m2.nm) into illuminance.
+    pub fn photopic(&self) -> Float {
+        self.weighted_integral(&PHOTOPIC_EFFICIENCY)
+    }
+
+    /// The integral of this distribution weighted by the melanopic
+    /// efficiency function $`s_{mel}(\lambda)`$
+    pub fn melanopic(&self) -> Float {
+        self.weighted_integral(&MELANOPIC_EFFICIENCY)
+    }
+
+    /// The melanopic Daylight Efficacy Ratio (i.e., melanopic DER, as
+    /// defined in CIE S 026) of a light source with this spectral
+    /// distribution: the ratio between its melanopic and photopic content,
+    /// relative to that of D65. Multiplying an illuminance (in lux) by
+    /// this value yields the melanopic Equivalent Daylight Illuminance
+    /// (M-EDI, in lux).
+    ///
+    /// ```
+    /// use rendering::spectral::SampledSpectrum;
+    ///
+    /// let daylight = SampledSpectrum::d65(40);
+    /// assert!((daylight.melanopic_daylight_efficacy_ratio() - 1.).abs() < 1e-9);
+    ///
+    /// // A warm light source is less stimulating
+    /// let incandescent = SampledSpectrum::black_body(2700., 40);
+    /// assert!(incandescent.melanopic_daylight_efficacy_ratio() < 0.6);
+    /// ```
+    pub fn melanopic_daylight_efficacy_ratio(&self) -> Float {
+        let photopic = self.photopic();
+        if photopic <= 0. {
+            return 0.;
+        }
+        let d65 = Self::d65(self.0.len());
+        (self.melanopic() / photopic) / (d65.melanopic() / d65.photopic())
+    }
+
+    /// Gets the bands traced in pass `pass` of a spectral rendering
+    /// (i.e., bands from `pass * N_CHANNELS`) as a [`Spectrum`]. The
+    /// distribution is resampled into [`N_SPECTRAL_BANDS`] if needed.
+    pub fn pass(&self, pass: usize) -> Spectrum {
+        assert!(
+            pass < N_SPECTRAL_PASSES,
+            "Pass {} is out of range... only {} passes are used for spectral rendering",
+            pass,
+            N_SPECTRAL_PASSES
+        );
+        let bands = self.resample(N_SPECTRAL_BANDS);
+        let mut ret = Spectrum::BLACK;
+        ret.0
+            .copy_from_slice(&bands.0[pass * N_CHANNELS..(pass + 1) * N_CHANNELS]);
+        ret
+    }
+
+    /// Puts together the results of the passes of a spectral rendering
+    /// (e.g., the radiance of a pixel or the irradiance on a sensor),
+    /// in order.
+    pub fn from_passes(passes: &[Spectrum]) -> Self {
+        Self(passes.iter().flat_map(|s| s.0).collect())
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use validate::assert_close;
+
+    #[test]
+    fn test_bands() {
+        let s = SampledSpectrum(vec![1., 2., 3., 4.]);
+        assert_close!(s.band_width(), 100.);
+        assert_close!(s.band_centre(0), 430.);
+        assert_close!(s.band_centre(3), 730.);
+
+        assert_close!(s.value_at(430.), 1.);
+        assert_close!(s.value_at(480.), 1.5);
+        assert_close!(s.value_at(700.), 3.7);
+        // Out of range
+        assert_close!(s.value_at(300.), 1.);
+        assert_close!(s.value_at(770.), 4.);
+    }
+
+    #[test]
+    fn test_resample() {
+        // Constant values remain constant
+        let s = SampledSpectrum::gray(0.5, 40).resample(N_SPECTRAL_BANDS);
+        assert_eq!(s.0.len(), N_SPECTRAL_BANDS);
+        assert!(s.0.iter().all(|v| (v - 0.5).abs() < 1e-9));
+
+        // Linear values keep their average
+        let s = SampledSpectrum((0..40).map(|i| i as Float).collect());
+        let r = s.resample(4);
+        assert_eq!(r.0.len(), 4);
+        assert_close!(r.0[1], 14.5, 1e-9);
+        assert_close!(r.0[2], 24.5, 1e-9);
+    }
+
+    #[test]
+    fn test_melanopic() {
+        // Tabulated values
+        assert_close!(tabulated_value(&MELANOPIC_EFFICIENCY, 490.), 1.);
+        assert_close!(tabulated_value(&PHOTOPIC_EFFICIENCY, 555.), 0.994975, 1e-9);
+
+        // An equal-energy spectrum has as much melanopic as photopic content
+        // (the areas under both functions are similar)
+        let white = SampledSpectrum::gray(1., 40);
+        let ratio = white.melanopic() / white.photopic();
+        assert!(ratio > 0.8 && ratio < 1.2, "ratio = {}", ratio);
+
+        // The number of bands does not matter much
+        for n in [N_SPECTRAL_BANDS, 40, 400] {
+            let d65 = SampledSpectrum::d65(n);
+            assert_close!(d65.melanopic_daylight_efficacy_ratio(), 1., 1e-9);
+
+            // Blue skies are more stimulating than warm lights
+            let blue_sky = SampledSpectrum::black_body(10000., n);
+            let warm = SampledSpectrum::black_body(3000., n);
+            assert!(blue_sky.melanopic_daylight_efficacy_ratio() > 1.);
+            assert!(warm.melanopic_daylight_efficacy_ratio() < 0.7);
+        }
+    }
+
+    #[test]
+    fn test_passes() {
+        let s = SampledSpectrum((0..N_SPECTRAL_BANDS).map(|i| i as Float).collect());
+        let passes: Vec<Spectrum> = (0..N_SPECTRAL_PASSES).map(|p| s.pass(p)).collect();
+        assert_eq!(passes[1].0[0], N_CHANNELS as Float);
+        assert_eq!(SampledSpectrum::from_passes(&passes), s);
+    }
+}
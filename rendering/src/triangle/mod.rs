@@ -7,6 +7,10 @@ use geometry::{
 pub mod fallback;
 pub use fallback::baricentric_coordinates;
 
+/// Intersects rays with several triangles at once
+#[cfg(feature = "simd")]
+pub mod simd;
+
 pub(crate) const LEAF_SIZE: usize = 24;
 
 /// The smallest definition of a Triangle I could think of
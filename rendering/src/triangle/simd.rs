@@ -0,0 +1,194 @@
+use crate::Float;
+use geometry::{Point3D, Ray3D};
+
+use super::Triangle;
+
+/// The number of triangles tested against a ray at once
+pub const LANES: usize = 8;
+
+/// The first vertex and the two edges leaving it of [`LANES`] triangles,
+/// stored as a structure of arrays (i.e., all the X coordinates of their
+/// first vertices together, then all the Y coordinates, and so on) so that
+/// every step of the intersection test is the same operation over whole arrays.
+///
+/// Unused lanes are left as zeroes, meaning that they are degenerate
+/// triangles that are never hit.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Lanes {
+    ax: [Float; LANES],
+    ay: [Float; LANES],
+    az: [Float; LANES],
+    e1x: [Float; LANES],
+    e1y: [Float; LANES],
+    e1z: [Float; LANES],
+    e2x: [Float; LANES],
+    e2y: [Float; LANES],
+    e2z: [Float; LANES],
+}
+
+impl Lanes {
+    /// Loads (at most [`LANES`]) triangles
+    fn new(triangles: &[Triangle]) -> Self {
+        let mut ret = Self::default();
+        for (l, t) in triangles.iter().take(LANES).enumerate() {
+            let [ax, ay, az, bx, by, bz, cx, cy, cz] = *t;
+            ret.ax[l] = ax;
+            ret.ay[l] = ay;
+            ret.az[l] = az;
+            ret.e1x[l] = bx - ax;
+            ret.e1y[l] = by - ay;
+            ret.e1z[l] = bz - az;
+            ret.e2x[l] = cx - ax;
+            ret.e2y[l] = cy - ay;
+            ret.e2z[l] = cz - az;
+        }
+        ret
+    }
+
+    /// Packs a slice of triangles into as many [`Lanes`] as needed
+    pub fn pack(triangles: &[Triangle]) -> Vec<Self> {
+        triangles.chunks(LANES).map(Self::new).collect()
+    }
+
+    /// Calculates the distance (along the ray) to each triangle, or infinity
+    /// for those that are not hit.
+    ///
+    /// The same Möller–Trumbore algorithm as
+    /// [`baricentric_coordinates`](super::baricentric_coordinates) is used,
+    /// but without branches, so that every lane goes through the same
+    /// operations regardless of whether its triangle is hit.
+    #[allow(clippy::needless_range_loop)]
+    fn distances(&self, ray: Ray3D) -> [Float; LANES] {
+        const TINY: Float = 1e-5;
+        let (dx, dy, dz) = (ray.direction.x, ray.direction.y, ray.direction.z);
+        let (ox, oy, oz) = (ray.origin.x, ray.origin.y, ray.origin.z);
+
+        let mut ret = [Float::INFINITY; LANES];
+        for l in 0..LANES {
+            // h = direction x edge2
+            let hx = dy * self.e2z[l] - dz * self.e2y[l];
+            let hy = dz * self.e2x[l] - dx * self.e2z[l];
+            let hz = dx * self.e2y[l] - dy * self.e2x[l];
+            let a = self.e1x[l] * hx + self.e1y[l] * hy + self.e1z[l] * hz;
+            let f = 1. / a;
+
+            let sx = ox - self.ax[l];
+            let sy = oy - self.ay[l];
+            let sz = oz - self.az[l];
+            let u = f * (sx * hx + sy * hy + sz * hz);
+
+            // q = s x edge1
+            let qx = sy * self.e1z[l] - sz * self.e1y[l];
+            let qy = sz * self.e1x[l] - sx * self.e1z[l];
+            let qz = sx * self.e1y[l] - sy * self.e1x[l];
+            let v = f * (dx * qx + dy * qy + dz * qz);
+            let t = f * (self.e2x[l] * qx + self.e2y[l] * qy + self.e2z[l] * qz);
+
+            // Non-short-circuiting, so there are no branches
+            let hit = (a.abs() >= TINY)
+                & (-Float::EPSILON..=1. + Float::EPSILON).contains(&u)
+                & (v >= -Float::EPSILON)
+                & (u + v <= 1. + Float::EPSILON)
+                & (t > TINY);
+            ret[l] = if hit { t } else { Float::INFINITY };
+        }
+        ret
+    }
+}
+
+/// Finds the closest triangle—among the `n` triangles packed in `lanes`
+/// (see [`Lanes::pack`]), the first of which has index `ini` within the
+/// scene—hit by a ray, returning its index and the point of intersection.
+///
+/// This is equivalent to
+/// [`simple_intersect_triangle_slice`](super::fallback::simple_intersect_triangle_slice),
+/// but tests [`LANES`] triangles at a time.
+pub fn intersect_lanes(
+    lanes: &[Lanes],
+    n: usize,
+    ray: Ray3D,
+    ini: usize,
+) -> Option<(usize, Point3D)> {
+    const MIN_T: Float = 0.0000001;
+    // The distances are compared along the ray, and only the
+    // closest one is projected
+    let d_squared = ray.direction.length_squared();
+    let mut best: Option<(usize, Float)> = None;
+
+    for (chunk, lanes) in lanes.iter().take(n.div_ceil(LANES)).enumerate() {
+        let distances = lanes.distances(ray);
+        let len = (n - chunk * LANES).min(LANES);
+        for (l, t) in distances.iter().enumerate().take(len) {
+            let closer = best.is_none_or(|(_, best_t)| *t < best_t);
+            if t.is_finite() && t * t * d_squared > MIN_T && closer {
+                best = Some((ini + chunk * LANES + l, *t));
+            }
+        }
+    }
+    best.map(|(i, t)| (i, ray.project(t)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rand::*;
+    use crate::triangle::fallback;
+    use crate::Scene;
+    use geometry::Vector3D;
+
+    #[test]
+    fn test_lanes() {
+        let t: Triangle = [0., 0., 0., 1., 0., 0., 0., 1., 0.];
+        let lanes = Lanes::new(&[t, t]);
+        assert_eq!(lanes.e1x[..3], [1., 1., 0.]);
+        assert_eq!(lanes.e2y[..3], [1., 1., 0.]);
+        assert_eq!(Lanes::pack(&[t; 9]).len(), 2);
+        assert!(Lanes::pack(&[]).is_empty());
+
+        // Only the loaded triangles can be hit
+        let ray = Ray3D {
+            origin: Point3D::new(0.2, 0.2, 1.),
+            direction: Vector3D::new(0., 0., -1.),
+        };
+        let distances = lanes.distances(ray);
+        assert!((distances[0] - 1.).abs() < 1e-9);
+        assert!((distances[1] - 1.).abs() < 1e-9);
+        assert!(distances[2..].iter().all(|t| t.is_infinite()));
+    }
+
+    #[test]
+    fn test_same_as_fallback() {
+        let mut rng = RandGen::seed_from_u64(1);
+        let mut coordinate = || -> Float { rng.gen::<Float>() * 10. - 5. };
+
+        let mut scene = Scene::new();
+        for _ in 0..53 {
+            scene.triangles.push(std::array::from_fn(|_| coordinate()));
+        }
+
+        let mut n_hits = 0;
+        for _ in 0..500 {
+            let origin = Point3D::new(coordinate(), coordinate(), coordinate());
+            let direction =
+                Vector3D::new(coordinate(), coordinate(), coordinate()).get_normalized();
+            let ray = Ray3D { origin, direction };
+
+            // Slices of different lengths, and not aligned to LANES
+            for (ini, fin) in [(0, 53), (3, 12), (5, 6), (7, 7)] {
+                let exp = fallback::simple_intersect_triangle_slice(&scene, ray, ini, fin);
+                let lanes = Lanes::pack(&scene.triangles[ini..fin]);
+                let found = intersect_lanes(&lanes, fin - ini, ray, ini);
+                match (exp, found) {
+                    (None, None) => {}
+                    (Some((i, p)), Some((j, q))) => {
+                        n_hits += 1;
+                        assert_eq!(i, j);
+                        assert!((p - q).length() < 1e-6);
+                    }
+                    _ => panic!("Expecting {:?}... found {:?}", exp, found),
+                }
+            }
+        }
+        assert!(n_hits > 0);
+    }
+}
@@ -267,6 +267,7 @@ fn sponza() -> Result<(), String> {
             month: 6,
             day: 1,
             hour: 12.,
+            year: None,
         },
         -33.,
         70.,
@@ -53,6 +53,7 @@ mod tests {
             month: 1,
             day: 1,
             hour: 13.,
+            year: None,
         };
 
         let v: usize = 1;
@@ -66,6 +67,7 @@ mod tests {
             month: 1,
             day: 1,
             hour: 13.,
+            year: None,
         };
 
         let v: char = 'a';
@@ -79,6 +81,7 @@ mod tests {
             month: 1,
             day: 1,
             hour: 13.,
+            year: None,
         };
 
         let v = 123.1;
@@ -0,0 +1,77 @@
+/*
+ * C interface to the SIMPLE building simulation engine.
+ *
+ * Functions returning an `int` return 0 on success and -1 on error. A
+ * description of the last error in the current thread can be retrieved
+ * with `simple_last_error()`.
+ *
+ * Example:
+ *
+ *     SimpleSimulation *sim = simple_simulation_new(
+ *         model, model_len, weather, weather_len, "epw", 4);
+ *     if (!sim) {
+ *         fprintf(stderr, "%s\n", simple_last_error());
+ *         return 1;
+ *     }
+ *     ptrdiff_t t = simple_simulation_index_of(
+ *         sim, "{\"SpaceDryBulbTemperature\":\"Kitchen\"}");
+ *     while (simple_simulation_step(sim) == 1) {
+ *         double v;
+ *         simple_simulation_get(sim, t, &v);
+ *     }
+ *     simple_simulation_free(sim);
+ */
+
+#ifndef SIMPLE_H
+#define SIMPLE_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* An opaque handle to a simulation */
+typedef struct SimpleSimulation SimpleSimulation;
+
+/* The last error in the current thread, or NULL. Owned by the library. */
+const char *simple_last_error(void);
+
+/* Creates a simulation from the contents of a model (.spl or JSON) and of a
+ * weather file ("epw", "csv" or "sw"), with `n` timesteps per hour. Returns
+ * NULL on error. */
+SimpleSimulation *simple_simulation_new(const uint8_t *model, size_t model_len,
+                                        const uint8_t *weather, size_t weather_len,
+                                        const char *weather_format, size_t n);
+
+/* Releases a simulation */
+void simple_simulation_free(SimpleSimulation *sim);
+
+/* Simulates the next timestep. Returns 1 if a timestep was simulated, 0 if
+ * the simulation was over, and -1 on error. */
+int simple_simulation_step(SimpleSimulation *sim);
+
+/* The date of the last simulated timestep (all 0 if none) */
+int simple_simulation_date(SimpleSimulation *sim, int *month, int *day, double *hour);
+
+/* The number of elements in the state, or -1 on error */
+ptrdiff_t simple_simulation_state_len(SimpleSimulation *sim);
+
+/* The position of the element of the state called `name`, or -1 */
+ptrdiff_t simple_simulation_index_of(SimpleSimulation *sim, const char *name);
+
+/* The name of the `i`th element of the state, or NULL. Owned by `sim`. */
+const char *simple_simulation_state_name(SimpleSimulation *sim, size_t i);
+
+/* Reads the `i`th element of the state */
+int simple_simulation_get(SimpleSimulation *sim, size_t i, double *value);
+
+/* Changes the `i`th element of the state */
+int simple_simulation_set(SimpleSimulation *sim, size_t i, double value);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* SIMPLE_H */
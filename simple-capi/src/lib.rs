@@ -0,0 +1,417 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+#![deny(missing_docs)]
+
+//! A C interface to `SIMPLE`, for embedding it in applications written in
+//! other languages (e.g., C++ or C#) and in co-simulation middleware. The
+//! declarations are in `include/simple.h`.
+//!
+//! Simulations are created from the contents of a model and of a weather
+//! file, and are then advanced one timestep at a time. The elements of the
+//! state are identified by their position (see `simple_simulation_index_of`),
+//! and can be read and changed in between timesteps.
+//!
+//! Functions returning an `int` return `0` on success and `-1` on error; a
+//! description of the last error in the current thread can be retrieved
+//! with `simple_last_error`. Numbers are always exchanged as `double`.
+
+use simple::run_simulation::weather_from_bytes;
+use simple::{Float, Model, Stepper};
+use std::cell::RefCell;
+use std::ffi::{c_char, c_double, c_int, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::ptr;
+
+thread_local! {
+    /// The last error that happened in this thread
+    static LAST_ERROR: RefCell<Option<CString>> = const { RefCell::new(None) };
+}
+
+/// Stores the last error of this thread
+fn set_last_error(msg: String) {
+    let msg = CString::new(msg.replace('\0', " ")).expect("Nul bytes were removed");
+    LAST_ERROR.with(|e| *e.borrow_mut() = Some(msg));
+}
+
+/// Runs `f`, storing its error (or panic) as the last error
+fn guard<T, F: FnOnce() -> Result<T, String>>(f: F) -> Option<T> {
+    match catch_unwind(AssertUnwindSafe(f)) {
+        Ok(Ok(v)) => Some(v),
+        Ok(Err(e)) => {
+            set_last_error(e);
+            None
+        }
+        Err(_) => {
+            set_last_error("Internal error: SIMPLE panicked".to_string());
+            None
+        }
+    }
+}
+
+/// Transforms the result of [`guard`] into a status code
+fn status(r: Option<()>) -> c_int {
+    match r {
+        Some(()) => 0,
+        None => -1,
+    }
+}
+
+/// Borrows a slice from a pointer and a length
+///
+/// # Safety
+///
+/// `data` must be valid for reading `len` bytes, or null if `len` is 0
+unsafe fn slice<'a>(data: *const u8, len: usize) -> Result<&'a [u8], String> {
+    if len == 0 {
+        return Ok(&[]);
+    }
+    if data.is_null() {
+        return Err("Received a null buffer".to_string());
+    }
+    Ok(std::slice::from_raw_parts(data, len))
+}
+
+/// Borrows a string from a nul-terminated pointer
+///
+/// # Safety
+///
+/// `s` must be null or point to a valid nul-terminated string
+unsafe fn string<'a>(s: *const c_char) -> Result<&'a str, String> {
+    if s.is_null() {
+        return Err("Received a null string".to_string());
+    }
+    CStr::from_ptr(s).to_str().map_err(|e| e.to_string())
+}
+
+/// Borrows the simulation behind a handle
+///
+/// # Safety
+///
+/// `sim` must be null or a handle returned by `simple_simulation_new`
+/// that has not been freed
+unsafe fn simulation<'a>(sim: *mut SimpleSimulation) -> Result<&'a mut SimpleSimulation, String> {
+    sim.as_mut()
+        .ok_or_else(|| "Received a null simulation".to_string())
+}
+
+/// A simulation, which is handed to C as an opaque pointer
+pub struct SimpleSimulation {
+    /// The simulation
+    stepper: Stepper,
+
+    /// The name of each element of the state, ready to be handed to C
+    header: Vec<CString>,
+}
+
+/// Returns a description of the last error that happened in the current
+/// thread, or null if there has been none. The string is owned by the
+/// library and remains valid until the next error in this thread.
+#[no_mangle]
+pub extern "C" fn simple_last_error() -> *const c_char {
+    LAST_ERROR.with(|e| match &*e.borrow() {
+        Some(msg) => msg.as_ptr(),
+        None => ptr::null(),
+    })
+}
+
+/// Creates a simulation of a model (the contents of a `.spl` or JSON file)
+/// with the contents of a weather file in a certain `weather_format` (i.e.,
+/// `"epw"`, `"csv"` or `"sw"`), with `n` timesteps per hour. Returns null
+/// on error. The simulation must be released with `simple_simulation_free`.
+///
+/// # Safety
+///
+/// `model` and `weather` must be valid for reading `model_len` and
+/// `weather_len` bytes, respectively; and `weather_format` must be a
+/// nul-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn simple_simulation_new(
+    model: *const u8,
+    model_len: usize,
+    weather: *const u8,
+    weather_len: usize,
+    weather_format: *const c_char,
+    n: usize,
+) -> *mut SimpleSimulation {
+    let sim = guard(|| {
+        let model = slice(model, model_len)?;
+        let weather = weather_from_bytes(slice(weather, weather_len)?, string(weather_format)?)?;
+        let (model, header) = if model.trim_ascii_start().starts_with(b"{") {
+            let json = std::str::from_utf8(model).map_err(|e| e.to_string())?;
+            Model::from_json(json)?
+        } else {
+            Model::from_bytes(model)?
+        };
+        let stepper = Stepper::new(model, header, weather, n)?;
+        let header = stepper
+            .header()
+            .iter()
+            .map(|h| CString::new(h.as_str()).map_err(|e| e.to_string()))
+            .collect::<Result<Vec<CString>, String>>()?;
+        Ok(SimpleSimulation { stepper, header })
+    });
+    match sim {
+        Some(sim) => Box::into_raw(Box::new(sim)),
+        None => ptr::null_mut(),
+    }
+}
+
+/// Releases a simulation
+///
+/// # Safety
+///
+/// `sim` must be null or a handle returned by `simple_simulation_new`
+/// that has not been freed yet
+#[no_mangle]
+pub unsafe extern "C" fn simple_simulation_free(sim: *mut SimpleSimulation) {
+    if !sim.is_null() {
+        drop(Box::from_raw(sim));
+    }
+}
+
+/// Simulates the next timestep. Returns `1` if a timestep was simulated,
+/// `0` if the whole simulation period had already been simulated, and
+/// `-1` on error.
+///
+/// # Safety
+///
+/// `sim` must be a handle returned by `simple_simulation_new`
+#[no_mangle]
+pub unsafe extern "C" fn simple_simulation_step(sim: *mut SimpleSimulation) -> c_int {
+    let stepped = guard(|| Ok(simulation(sim)?.stepper.step()?.is_some()));
+    match stepped {
+        Some(true) => 1,
+        Some(false) => 0,
+        None => -1,
+    }
+}
+
+/// Writes the month, day and hour of the last simulated timestep. All
+/// of them are 0 if nothing has been simulated yet.
+///
+/// # Safety
+///
+/// `sim` must be a handle returned by `simple_simulation_new`, and the
+/// other pointers must be valid for writing
+#[no_mangle]
+pub unsafe extern "C" fn simple_simulation_date(
+    sim: *mut SimpleSimulation,
+    month: *mut c_int,
+    day: *mut c_int,
+    hour: *mut c_double,
+) -> c_int {
+    status(guard(|| {
+        if month.is_null() || day.is_null() || hour.is_null() {
+            return Err("Received a null pointer".to_string());
+        }
+        let date = simulation(sim)?.stepper.date();
+        *month = date.map(|d| d.month as c_int).unwrap_or(0);
+        *day = date.map(|d| d.day as c_int).unwrap_or(0);
+        *hour = date.map(|d| d.hour as c_double).unwrap_or(0.);
+        Ok(())
+    }))
+}
+
+/// Returns the number of elements in the state of the simulation,
+/// or `-1` on error
+///
+/// # Safety
+///
+/// `sim` must be a handle returned by `simple_simulation_new`
+#[no_mangle]
+pub unsafe extern "C" fn simple_simulation_state_len(sim: *mut SimpleSimulation) -> isize {
+    guard(|| Ok(simulation(sim)?.header.len() as isize)).unwrap_or(-1)
+}
+
+/// Returns the position of the element of the state called `name` (e.g.,
+/// `{"SpaceDryBulbTemperature":"Kitchen"}`), or `-1` if there is none
+///
+/// # Safety
+///
+/// `sim` must be a handle returned by `simple_simulation_new`, and
+/// `name` a nul-terminated string
+#[no_mangle]
+pub unsafe extern "C" fn simple_simulation_index_of(
+    sim: *mut SimpleSimulation,
+    name: *const c_char,
+) -> isize {
+    guard(|| {
+        let sim = simulation(sim)?;
+        let name = string(name)?;
+        sim.stepper
+            .index_of(name)
+            .map(|i| i as isize)
+            .ok_or_else(|| format!("There is no element called '{}' in the state", name))
+    })
+    .unwrap_or(-1)
+}
+
+/// Returns the name of the `i`th element of the state, or null on error.
+/// The string is owned by the simulation.
+///
+/// # Safety
+///
+/// `sim` must be a handle returned by `simple_simulation_new`
+#[no_mangle]
+pub unsafe extern "C" fn simple_simulation_state_name(
+    sim: *mut SimpleSimulation,
+    i: usize,
+) -> *const c_char {
+    guard(|| {
+        let sim = simulation(sim)?;
+        sim.header
+            .get(i)
+            .map(|h| h.as_ptr())
+            .ok_or_else(|| format!("There is no element {} in the state", i))
+    })
+    .unwrap_or(ptr::null())
+}
+
+/// Writes the value of the `i`th element of the state into `value`
+///
+/// # Safety
+///
+/// `sim` must be a handle returned by `simple_simulation_new`, and
+/// `value` must be valid for writing
+#[no_mangle]
+pub unsafe extern "C" fn simple_simulation_get(
+    sim: *mut SimpleSimulation,
+    i: usize,
+    value: *mut c_double,
+) -> c_int {
+    status(guard(|| {
+        if value.is_null() {
+            return Err("Received a null pointer".to_string());
+        }
+        let v = simulation(sim)?
+            .stepper
+            .state()
+            .get(i)
+            .ok_or_else(|| format!("There is no element {} in the state", i))?;
+        *value = *v as c_double;
+        Ok(())
+    }))
+}
+
+/// Sets the value of the `i`th element of the state, which will be used
+/// when simulating the next timestep
+///
+/// # Safety
+///
+/// `sim` must be a handle returned by `simple_simulation_new`
+#[no_mangle]
+pub unsafe extern "C" fn simple_simulation_set(
+    sim: *mut SimpleSimulation,
+    i: usize,
+    value: c_double,
+) -> c_int {
+    status(guard(|| {
+        let v = simulation(sim)?
+            .stepper
+            .state_mut()
+            .get_mut(i)
+            .ok_or_else(|| format!("There is no element {} in the state", i))?;
+        *v = value as Float;
+        Ok(())
+    }))
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    /// Creates a simulation of the box model. Its `SolarOptions` are removed
+    /// (and it is passed as JSON) so that it is fast to simulate and no
+    /// optical data is written next to the model.
+    fn new_simulation() -> Result<*mut SimpleSimulation, String> {
+        let (mut model, _) =
+            Model::from_file(concat!(env!("CARGO_MANIFEST_DIR"), "/../tests/box/box.spl"))?;
+        model.solar_options = None;
+        let model = model.to_json()?;
+        let weather = std::fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../tests/wellington.epw"
+        ))
+        .map_err(|e| e.to_string())?;
+        let format = CString::new("epw").map_err(|e| e.to_string())?;
+        let sim = unsafe {
+            simple_simulation_new(
+                model.as_ptr(),
+                model.len(),
+                weather.as_ptr(),
+                weather.len(),
+                format.as_ptr(),
+                1,
+            )
+        };
+        Ok(sim)
+    }
+
+    #[test]
+    fn test_step() -> Result<(), String> {
+        let sim = new_simulation()?;
+        assert!(!sim.is_null());
+        unsafe {
+            let name = CString::new("{\"SpaceDryBulbTemperature\":\"ZONE ONE\"}").unwrap();
+            let i = simple_simulation_index_of(sim, name.as_ptr());
+            assert!(i >= 0);
+            assert!(simple_simulation_state_len(sim) > i);
+            let found = CStr::from_ptr(simple_simulation_state_name(sim, i as usize));
+            assert_eq!(found, name.as_c_str());
+
+            // Change the state, then simulate
+            assert_eq!(simple_simulation_set(sim, i as usize, 30.), 0);
+            assert_eq!(simple_simulation_step(sim), 1);
+            let mut v = 0.;
+            assert_eq!(simple_simulation_get(sim, i as usize, &mut v), 0);
+            assert!(v.is_finite() && v != 30.);
+
+            let (mut month, mut day, mut hour) = (0, 0, 0.);
+            assert_eq!(
+                simple_simulation_date(sim, &mut month, &mut day, &mut hour),
+                0
+            );
+            assert_eq!((month, day), (1, 1));
+
+            // Errors
+            assert_eq!(simple_simulation_get(sim, usize::MAX, &mut v), -1);
+            assert!(!simple_last_error().is_null());
+            let missing = CString::new("Nothing").unwrap();
+            assert_eq!(simple_simulation_index_of(sim, missing.as_ptr()), -1);
+
+            simple_simulation_free(sim);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_model() {
+        let format = CString::new("epw").unwrap();
+        let sim =
+            unsafe { simple_simulation_new(ptr::null(), 0, ptr::null(), 0, format.as_ptr(), 1) };
+        assert!(sim.is_null());
+        assert!(!simple_last_error().is_null());
+    }
+}
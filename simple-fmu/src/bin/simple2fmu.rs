@@ -0,0 +1,168 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use clap::Parser;
+use simple_fmu::description::{guid, model_description, MODEL_IDENTIFIER};
+use simple_fmu::fmu::{FmuConfig, CONFIG_FILE};
+use simple_fmu::Fmu;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Packages a SIMPLE model and a weather file as an FMI 2.0
+/// co-simulation FMU
+#[derive(Parser)]
+#[clap(author, version, about, long_about = None)]
+struct Inputs {
+    /// The model (.spl or .json)
+    #[clap(short, long)]
+    pub model: String,
+
+    /// The weather file (.epw, .csv or .sw)
+    #[clap(short, long)]
+    pub weather: String,
+
+    /// The number of timesteps per hour
+    #[clap(short, default_value_t = 4)]
+    pub n: usize,
+
+    /// The compiled simple_fmu library. Defaults to the one next
+    /// to this executable
+    #[clap(short, long)]
+    pub library: Option<String>,
+
+    /// The FMI name of the platform of the library (e.g., linux64,
+    /// win64 or darwin64). Defaults to the current platform
+    #[clap(short, long)]
+    pub platform: Option<String>,
+
+    /// The FMU to create
+    #[clap(short, long)]
+    pub output: String,
+}
+
+/// The FMI name of the current platform
+fn current_platform() -> Result<String, String> {
+    let os = match std::env::consts::OS {
+        "linux" => "linux",
+        "windows" => "win",
+        "macos" => "darwin",
+        os => return Err(format!("Unsupported platform '{}'", os)),
+    };
+    let bits = if cfg!(target_pointer_width = "64") {
+        64
+    } else {
+        32
+    };
+    Ok(format!("{}{}", os, bits))
+}
+
+/// The file name of a library for an FMI platform
+fn library_name(platform: &str) -> String {
+    let ext = if platform.starts_with("win") {
+        "dll"
+    } else if platform.starts_with("darwin") {
+        "dylib"
+    } else {
+        "so"
+    };
+    format!("{}.{}", MODEL_IDENTIFIER, ext)
+}
+
+/// The library next to this executable
+fn default_library() -> Result<PathBuf, String> {
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    let name = format!(
+        "{}{}{}",
+        std::env::consts::DLL_PREFIX,
+        MODEL_IDENTIFIER,
+        std::env::consts::DLL_SUFFIX
+    );
+    Ok(exe.with_file_name(name))
+}
+
+/// The name of a file, without its directories
+fn file_name(path: &str) -> Result<String, String> {
+    Path::new(path)
+        .file_name()
+        .map(|f| f.to_string_lossy().to_string())
+        .ok_or_else(|| format!("Invalid file '{}'", path))
+}
+
+/// Reads a whole file
+fn read<P: AsRef<Path>>(path: P) -> Result<Vec<u8>, String> {
+    let path = path.as_ref();
+    fs::read(path).map_err(|e| format!("Could not read '{}': {}", path.display(), e))
+}
+
+fn run(inputs: Inputs) -> Result<(), String> {
+    let config = FmuConfig {
+        model: file_name(&inputs.model)?,
+        weather: file_name(&inputs.weather)?,
+        n: inputs.n,
+    };
+    let model = read(&inputs.model)?;
+    let weather = read(&inputs.weather)?;
+
+    // Make sure it can be simulated
+    let fmu = Fmu::from_contents(&config, &model, &weather)?;
+    let description = model_description(&fmu, &guid(&model, &weather, inputs.n));
+
+    let platform = match inputs.platform {
+        Some(p) => p,
+        None => current_platform()?,
+    };
+    let library = match inputs.library {
+        Some(l) => PathBuf::from(l),
+        None => default_library()?,
+    };
+    let library = read(library)?;
+    let json = serde_json::to_string_pretty(&config).map_err(|e| e.to_string())?;
+
+    let file = File::create(&inputs.output)
+        .map_err(|e| format!("Could not create '{}': {}", inputs.output, e))?;
+    let mut zip = ZipWriter::new(file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let files = [
+        ("modelDescription.xml".to_string(), description.as_bytes()),
+        (
+            format!("binaries/{}/{}", platform, library_name(&platform)),
+            library.as_slice(),
+        ),
+        (format!("resources/{}", CONFIG_FILE), json.as_bytes()),
+        (format!("resources/{}", config.model), model.as_slice()),
+        (format!("resources/{}", config.weather), weather.as_slice()),
+    ];
+    for (name, contents) in files {
+        zip.start_file(name, options).map_err(|e| e.to_string())?;
+        zip.write_all(contents).map_err(|e| e.to_string())?;
+    }
+    zip.finish().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+fn main() {
+    if let Err(e) = run(Inputs::parse()) {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
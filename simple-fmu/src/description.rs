@@ -0,0 +1,173 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::fmu::{Causality, Fmu};
+use std::collections::BTreeSet;
+
+/// The name of the model, and of the shared library within the FMU
+pub const MODEL_IDENTIFIER: &str = "simple_fmu";
+
+/// Calculates a GUID that identifies the contents of an FMU (i.e., the
+/// model, the weather and the number of timesteps per hour), so that a
+/// `modelDescription.xml` cannot be used with different resources.
+///
+/// This is a 128-bit FNV-1a hash, which is stable across platforms
+/// and versions of Rust.
+pub fn guid(model: &[u8], weather: &[u8], n: usize) -> String {
+    const OFFSET: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013B;
+    let mut hash = OFFSET;
+    let n = n.to_le_bytes();
+    for byte in model.iter().chain(weather.iter()).chain(n.iter()) {
+        hash ^= *byte as u128;
+        hash = hash.wrapping_mul(PRIME);
+    }
+    let h = format!("{:032x}", hash);
+    format!(
+        "{{{}-{}-{}-{}-{}}}",
+        &h[0..8],
+        &h[8..12],
+        &h[12..16],
+        &h[16..20],
+        &h[20..32]
+    )
+}
+
+/// Escapes the characters that are not allowed in XML attributes
+fn escape(s: &str) -> String {
+    let mut ret = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => ret.push_str("&amp;"),
+            '<' => ret.push_str("&lt;"),
+            '>' => ret.push_str("&gt;"),
+            '"' => ret.push_str("&quot;"),
+            '\'' => ret.push_str("&apos;"),
+            _ => ret.push(c),
+        }
+    }
+    ret
+}
+
+/// Writes the `modelDescription.xml` of an FMU
+pub fn model_description(fmu: &Fmu, guid: &str) -> String {
+    let mut xml = String::new();
+    let mut w = |s: String| xml.push_str(&s);
+
+    w(r#"<?xml version="1.0" encoding="UTF-8"?>
+"#
+    .to_string());
+    w(format!(
+        r#"<fmiModelDescription fmiVersion="2.0" modelName="{MODEL_IDENTIFIER}" guid="{}" generationTool="SIMPLE" variableNamingConvention="flat" numberOfEventIndicators="0">
+"#,
+        escape(guid)
+    ));
+    w(format!(
+        r#"  <CoSimulation modelIdentifier="{MODEL_IDENTIFIER}" canHandleVariableCommunicationStepSize="true" canBeInstantiatedOnlyOncePerProcess="false" canNotUseMemoryManagementFunctions="true"/>
+"#
+    ));
+
+    // Units
+    let units: BTreeSet<&str> = fmu.variables().iter().map(|v| v.unit).collect();
+    w("  <UnitDefinitions>\n".to_string());
+    for unit in units {
+        w(format!("    <Unit name=\"{}\"/>\n", escape(unit)));
+    }
+    w("  </UnitDefinitions>\n".to_string());
+
+    w(format!(
+        "  <DefaultExperiment startTime=\"0\" stopTime=\"{}\" stepSize=\"{}\"/>\n",
+        fmu.stop_time(),
+        fmu.dt()
+    ));
+
+    // Variables
+    w("  <ModelVariables>\n".to_string());
+    for (vr, v) in fmu.variables().iter().enumerate() {
+        let real = match v.causality {
+            Causality::Input => format!(
+                "<Real unit=\"{}\" start=\"{}\"/>",
+                escape(v.unit),
+                fmu.get_real(vr).unwrap_or(0.)
+            ),
+            Causality::Output => format!("<Real unit=\"{}\"/>", escape(v.unit)),
+        };
+        let (causality, variability, initial) = match v.causality {
+            Causality::Input => ("input", "continuous", ""),
+            Causality::Output => ("output", "continuous", " initial=\"calculated\""),
+        };
+        w(format!(
+            "    <ScalarVariable name=\"{}\" valueReference=\"{}\" description=\"{}\" causality=\"{}\" variability=\"{}\"{}>\n      {}\n    </ScalarVariable>\n",
+            escape(&v.name),
+            vr,
+            escape(&v.description),
+            causality,
+            variability,
+            initial,
+            real
+        ));
+    }
+    w("  </ModelVariables>\n".to_string());
+
+    // Structure... FMI indices start at 1
+    let outputs: Vec<usize> = fmu
+        .variables()
+        .iter()
+        .enumerate()
+        .filter(|(_, v)| v.causality == Causality::Output)
+        .map(|(i, _)| i + 1)
+        .collect();
+    w("  <ModelStructure>\n".to_string());
+    for tag in ["Outputs", "InitialUnknowns"] {
+        w(format!("    <{}>\n", tag));
+        for i in outputs.iter() {
+            w(format!("      <Unknown index=\"{}\"/>\n", i));
+        }
+        w(format!("    </{}>\n", tag));
+    }
+    w("  </ModelStructure>\n".to_string());
+    w("</fmiModelDescription>\n".to_string());
+
+    xml
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn test_guid() {
+        let a = guid(b"model", b"weather", 4);
+        assert_eq!(a.len(), 38);
+        assert!(a.starts_with('{') && a.ends_with('}'));
+        assert_eq!(a, guid(b"model", b"weather", 4));
+        assert_ne!(a, guid(b"model", b"weather", 2));
+        assert_ne!(a, guid(b"Model", b"weather", 4));
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("a<b & \"c\""), "a&lt;b &amp; &quot;c&quot;");
+    }
+}
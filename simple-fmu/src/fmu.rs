@@ -0,0 +1,467 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use serde::{Deserialize, Serialize};
+use simple::hvac::HVAC;
+use simple::run_simulation::weather_from_bytes;
+use simple::{CurrentWeather, Date, Float, Model, Stepper, Weather, WeatherTrait};
+use std::ffi::c_double;
+use std::fs;
+use std::path::Path;
+
+/// The name of the file, within the `resources` of the FMU, describing
+/// what to simulate
+pub const CONFIG_FILE: &str = "simple.json";
+
+/// The contents of [`CONFIG_FILE`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FmuConfig {
+    /// The name of the model file (`.spl` or `.json`), within the resources
+    pub model: String,
+
+    /// The name of the weather file (`.epw`, `.csv` or `.sw`), within the resources
+    pub weather: String,
+
+    /// The number of timesteps per hour
+    pub n: usize,
+}
+
+/// Whether the co-simulation master reads or writes a variable
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Causality {
+    /// Set by the master before each step
+    Input,
+
+    /// Calculated by `SIMPLE`
+    Output,
+}
+
+/// The weather variables that can be given by the master, replacing
+/// the ones in the weather file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WeatherVariable {
+    /// In C
+    DryBulbTemperature,
+
+    /// In C
+    DewPointTemperature,
+
+    /// From 0 to 1
+    RelativeHumidity,
+
+    /// In W/m2
+    GlobalHorizontalRadiation,
+
+    /// In W/m2
+    DirectNormalRadiation,
+
+    /// In W/m2
+    DiffuseHorizontalRadiation,
+
+    /// In m/s
+    WindSpeed,
+
+    /// In degrees, North is 0 and East is 90
+    WindDirection,
+}
+
+impl WeatherVariable {
+    /// All the weather variables
+    const ALL: [Self; 8] = [
+        Self::DryBulbTemperature,
+        Self::DewPointTemperature,
+        Self::RelativeHumidity,
+        Self::GlobalHorizontalRadiation,
+        Self::DirectNormalRadiation,
+        Self::DiffuseHorizontalRadiation,
+        Self::WindSpeed,
+        Self::WindDirection,
+    ];
+
+    /// The name of the variable, and its unit
+    fn name_and_unit(&self) -> (&'static str, &'static str) {
+        match self {
+            Self::DryBulbTemperature => ("dry_bulb_temperature", "degC"),
+            Self::DewPointTemperature => ("dew_point_temperature", "degC"),
+            Self::RelativeHumidity => ("relative_humidity", "1"),
+            Self::GlobalHorizontalRadiation => ("global_horizontal_radiation", "W/m2"),
+            Self::DirectNormalRadiation => ("direct_normal_radiation", "W/m2"),
+            Self::DiffuseHorizontalRadiation => ("diffuse_horizontal_radiation", "W/m2"),
+            Self::WindSpeed => ("wind_speed", "m/s"),
+            Self::WindDirection => ("wind_direction", "deg"),
+        }
+    }
+
+    /// Reads this variable from a [`CurrentWeather`]
+    fn get(&self, w: &CurrentWeather) -> Float {
+        match self {
+            Self::DryBulbTemperature => w.dry_bulb_temperature,
+            Self::DewPointTemperature => w.dew_point_temperature,
+            Self::RelativeHumidity => w.relative_humidity,
+            Self::GlobalHorizontalRadiation => w.global_horizontal_radiation,
+            Self::DirectNormalRadiation => w.direct_normal_radiation,
+            Self::DiffuseHorizontalRadiation => w.diffuse_horizontal_radiation,
+            Self::WindSpeed => w.wind_speed,
+            Self::WindDirection => w.wind_direction.to_degrees(),
+        }
+    }
+
+    /// Writes this variable into a [`CurrentWeather`]
+    fn set(&self, w: &mut CurrentWeather, v: Float) {
+        match self {
+            Self::DryBulbTemperature => w.dry_bulb_temperature = v,
+            Self::DewPointTemperature => w.dew_point_temperature = v,
+            Self::RelativeHumidity => w.relative_humidity = v,
+            Self::GlobalHorizontalRadiation => w.global_horizontal_radiation = v,
+            Self::DirectNormalRadiation => w.direct_normal_radiation = v,
+            Self::DiffuseHorizontalRadiation => w.diffuse_horizontal_radiation = v,
+            Self::WindSpeed => w.wind_speed = v,
+            Self::WindDirection => w.wind_direction = v.to_radians(),
+        }
+    }
+}
+
+/// What an FMI variable represents
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// An element of the simulation state
+    State(usize),
+
+    /// A weather variable
+    Weather(WeatherVariable),
+}
+
+/// A variable exchanged with the co-simulation master. Its value
+/// reference is its position in [`Fmu::variables`].
+#[derive(Debug, Clone)]
+pub struct Variable {
+    /// The name of the variable
+    pub name: String,
+
+    /// A human-readable description
+    pub description: String,
+
+    /// The unit, as declared in the model description
+    pub unit: &'static str,
+
+    /// Whether the variable is an input or an output
+    pub causality: Causality,
+
+    /// What the variable represents
+    pub target: Target,
+}
+
+/// The weather of the file, with some of its values replaced by the
+/// inputs given by the master
+struct InputWeather<'a> {
+    /// The weather file
+    weather: &'a Weather,
+
+    /// The values given by the master
+    inputs: Vec<(WeatherVariable, Float)>,
+}
+
+impl WeatherTrait for InputWeather<'_> {
+    fn get_weather_data(&self, date: Date) -> CurrentWeather {
+        let mut w = self.weather.get_weather_data(date);
+        for (variable, v) in self.inputs.iter() {
+            variable.set(&mut w, *v);
+        }
+        w
+    }
+
+    fn get_ground_temperature(&self, date: Date, depth: Float) -> Option<Float> {
+        self.weather.get_ground_temperature(date, depth)
+    }
+}
+
+/// A `SIMPLE` simulation wrapped as an FMI 2.0 co-simulation slave: space
+/// temperatures are outputs, while the heating/cooling power of the HVAC
+/// systems and the weather are inputs.
+///
+/// Time is measured in seconds since the beginning of the weather data.
+/// Weather inputs that have never been set take the values in the
+/// weather file.
+pub struct Fmu {
+    /// The simulation
+    stepper: Stepper,
+
+    /// The weather file
+    weather: Weather,
+
+    /// The variables exchanged with the master
+    variables: Vec<Variable>,
+
+    /// The values given by the master to the weather variables
+    weather_inputs: Vec<Option<Float>>,
+
+    /// The current time, in seconds
+    time: c_double,
+}
+
+impl Fmu {
+    /// Wraps the simulation of a `model` with `n` timesteps per hour
+    pub fn new(model: Model, mut weather: Weather, n: usize) -> Result<Self, String> {
+        weather.sort_data();
+        // Parse again, as the objects can only be simulated once
+        let (model, header) = Model::from_json(&model.to_json()?)?;
+        let stepper = Stepper::new(model, header, weather.clone(), n)?;
+        let model = stepper.model();
+
+        let mut variables = Vec::new();
+        for space in model.spaces.iter() {
+            if let Some(i) = space.dry_bulb_temperature_index() {
+                variables.push(Variable {
+                    name: format!("{}.dry_bulb_temperature", space.name()),
+                    description: format!("Dry bulb temperature of space '{}'", space.name()),
+                    unit: "degC",
+                    causality: Causality::Output,
+                    target: Target::State(i),
+                });
+            }
+        }
+        for hvac in model.hvacs.iter() {
+            let i = match hvac {
+                HVAC::ElectricHeater(h) => h.heating_cooling_consumption_index(),
+                HVAC::IdealHeaterCooler(h) => h.heating_cooling_consumption_index(),
+                HVAC::HeatPump(h) => h.heating_cooling_consumption_index(),
+                HVAC::RadiantSurfaceSystem(h) => h.heating_cooling_consumption_index(),
+                HVAC::HeatRecoveryVentilator(_) => None,
+            };
+            if let Some(i) = i {
+                variables.push(Variable {
+                    name: format!("{}.heating_cooling_power", hvac.name()),
+                    description: format!(
+                        "Heating (positive) or cooling (negative) power of HVAC '{}'",
+                        hvac.name()
+                    ),
+                    unit: "W",
+                    causality: Causality::Input,
+                    target: Target::State(i),
+                });
+            }
+        }
+        for variable in WeatherVariable::ALL {
+            let (name, unit) = variable.name_and_unit();
+            variables.push(Variable {
+                name: format!("weather.{}", name),
+                description: format!(
+                    "Outdoor {}. Taken from the weather file until set",
+                    name.replace('_', " ")
+                ),
+                unit,
+                causality: Causality::Input,
+                target: Target::Weather(variable),
+            });
+        }
+
+        Ok(Self {
+            weather_inputs: vec![None; variables.len()],
+            stepper,
+            weather,
+            variables,
+            time: 0.,
+        })
+    }
+
+    /// Wraps a simulation described by an [`FmuConfig`], given the contents
+    /// of the model and weather files it refers to
+    pub fn from_contents(config: &FmuConfig, model: &[u8], weather: &[u8]) -> Result<Self, String> {
+        let (model, _) = if config.model.ends_with(".json") {
+            let json = std::str::from_utf8(model).map_err(|e| e.to_string())?;
+            Model::from_json(json)?
+        } else {
+            Model::from_bytes(model)?
+        };
+        let format = config.weather.rsplit('.').next().unwrap_or("");
+        let weather = weather_from_bytes(weather, format)?;
+        Self::new(model, weather, config.n)
+    }
+
+    /// The variables exchanged with the master
+    pub fn variables(&self) -> &[Variable] {
+        &self.variables
+    }
+
+    /// The duration of each timestep of the simulation, in seconds
+    pub fn dt(&self) -> c_double {
+        self.stepper.dt() as c_double
+    }
+
+    /// The duration of the weather data, in seconds
+    pub fn stop_time(&self) -> c_double {
+        self.weather.data.len() as c_double * 3600.
+    }
+
+    /// The current time, in seconds
+    pub fn time(&self) -> c_double {
+        self.time
+    }
+
+    /// Gets a variable by its value reference
+    fn variable(&self, vr: usize) -> Result<&Variable, String> {
+        self.variables
+            .get(vr)
+            .ok_or_else(|| format!("Unknown value reference {}", vr))
+    }
+
+    /// Reads the value of a variable
+    pub fn get_real(&self, vr: usize) -> Result<c_double, String> {
+        let v = match self.variable(vr)?.target {
+            Target::State(i) => self.stepper.state()[i],
+            Target::Weather(variable) => match self.weather_inputs[vr] {
+                Some(v) => v,
+                None => variable.get(&self.stepper.current_weather()),
+            },
+        };
+        Ok(v as c_double)
+    }
+
+    /// Sets the value of an input
+    pub fn set_real(&mut self, vr: usize, v: c_double) -> Result<(), String> {
+        let variable = self.variable(vr)?;
+        if variable.causality != Causality::Input {
+            return Err(format!("Variable '{}' is not an input", variable.name));
+        }
+        match variable.target {
+            Target::State(i) => self.stepper.state_mut()[i] = v as Float,
+            Target::Weather(_) => self.weather_inputs[vr] = Some(v as Float),
+        }
+        Ok(())
+    }
+
+    /// Simulates from `current` (which must be the current time) to
+    /// `current + h`. The step must be a multiple of [`Fmu::dt`].
+    pub fn do_step(&mut self, current: c_double, h: c_double) -> Result<(), String> {
+        if (current - self.time).abs() > 1e-6 {
+            return Err(format!(
+                "Expecting a step from time {}, but got one from {}",
+                self.time, current
+            ));
+        }
+        let dt = self.dt();
+        let n = (h / dt).round();
+        if n < 1. || (n * dt - h).abs() > 1e-6 {
+            return Err(format!(
+                "The communication step ({} s) must be a multiple of the timestep ({} s)",
+                h, dt
+            ));
+        }
+
+        let inputs = self
+            .variables
+            .iter()
+            .zip(self.weather_inputs.iter())
+            .filter_map(|(variable, value)| match (variable.target, value) {
+                (Target::Weather(w), Some(v)) => Some((w, *v)),
+                _ => None,
+            })
+            .collect();
+        let weather = InputWeather {
+            weather: &self.weather,
+            inputs,
+        };
+        for _ in 0..n as usize {
+            if self.stepper.step_with_weather(&weather)?.is_none() {
+                return Err("Reached the end of the weather data".to_string());
+            }
+        }
+        self.time += h;
+        Ok(())
+    }
+}
+
+/// Reads the [`FmuConfig`], and the contents of the model and
+/// weather files, from the `resources` of an FMU
+pub fn read_resources(resources: &Path) -> Result<(FmuConfig, Vec<u8>, Vec<u8>), String> {
+    let read = |name: &str| {
+        let path = resources.join(name);
+        fs::read(&path).map_err(|e| format!("Could not read '{}': {}", path.display(), e))
+    };
+    let config: FmuConfig =
+        serde_json::from_slice(&read(CONFIG_FILE)?).map_err(|e| e.to_string())?;
+    let model = read(&config.model)?;
+    let weather = read(&config.weather)?;
+    Ok((config, model, weather))
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+
+    /// The box model as JSON. Its `SolarOptions` are removed so that it is
+    /// fast to simulate and no optical data is written next to the model.
+    pub(crate) fn box_model() -> Result<Vec<u8>, String> {
+        let (mut model, _) =
+            Model::from_file(concat!(env!("CARGO_MANIFEST_DIR"), "/../tests/box/box.spl"))?;
+        model.solar_options = None;
+        Ok(model.to_json()?.into_bytes())
+    }
+
+    /// The contents of the Wellington EPW file
+    pub(crate) fn wellington() -> Result<Vec<u8>, String> {
+        fs::read(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/../tests/wellington.epw"
+        ))
+        .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn test_do_step() -> Result<(), String> {
+        let config = FmuConfig {
+            model: "box.json".to_string(),
+            weather: "wellington.epw".to_string(),
+            n: 2,
+        };
+        let model = box_model()?;
+        let weather = wellington()?;
+        let mut fmu = Fmu::from_contents(&config, &model, &weather)?;
+        assert_eq!(fmu.dt(), 1800.);
+
+        let position = |name: &str| {
+            fmu.variables()
+                .iter()
+                .position(|v| v.name == name)
+                .ok_or(format!("No variable '{}'", name))
+        };
+        let temperature = position("ZONE ONE.dry_bulb_temperature")?;
+        let outdoor = position("weather.dry_bulb_temperature")?;
+
+        // Outputs cannot be set
+        assert!(fmu.set_real(temperature, 20.).is_err());
+
+        fmu.set_real(outdoor, -40.)?;
+        assert_eq!(fmu.get_real(outdoor)?, -40.);
+        let before = fmu.get_real(temperature)?;
+        fmu.do_step(0., 3600.)?;
+        assert_eq!(fmu.time(), 3600.);
+        assert!(fmu.get_real(temperature)? < before);
+
+        // Steps must be consistent
+        assert!(fmu.do_step(0., 3600.).is_err());
+        assert!(fmu.do_step(3600., 1000.).is_err());
+        Ok(())
+    }
+}
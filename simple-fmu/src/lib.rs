@@ -0,0 +1,704 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+#![deny(missing_docs)]
+// The names of the exported functions are given by the FMI standard
+#![allow(non_snake_case)]
+
+//! Exports `SIMPLE` models as FMI 2.0 Functional Mock-up Units (FMUs)
+//! for co-simulation, so that buildings can be simulated together with
+//! (for instance) Modelica models of HVAC plants.
+//!
+//! An FMU is a zip file containing a `modelDescription.xml`, this library
+//! (compiled for each supported platform) and some `resources`—in this
+//! case, the model, the weather file and a [`fmu::FmuConfig`]. These are
+//! put together by the `simple2fmu` binary.
+//!
+//! The dry bulb temperature of each space is an output; the heating or
+//! cooling power of each HVAC and the weather are inputs (see
+//! [`fmu::Fmu`]). Only real variables are exchanged, and the state of the
+//! FMU cannot be saved and restored.
+
+/// Writes the `modelDescription.xml`
+pub mod description;
+
+/// The simulation behind the FMI functions
+pub mod fmu;
+pub use fmu::Fmu;
+
+use std::ffi::{c_char, c_double, c_int, c_uint, c_void, CStr, CString};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+/// `fmi2Status`
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// All well
+    Ok = 0,
+    /// Something is not right, but the computation can continue
+    Warning = 1,
+    /// The result of a step cannot be used
+    Discard = 2,
+    /// The instance cannot be used anymore, but it can be freed or reset
+    Error = 3,
+    /// No instance can be used anymore
+    Fatal = 4,
+    /// An asynchronous step is running
+    Pending = 5,
+}
+
+/// `fmi2CoSimulation`, the only `fmi2Type` supported
+const CO_SIMULATION: c_int = 1;
+
+/// `fmi2CallbackLogger`
+pub type Logger = unsafe extern "C" fn(
+    environment: *mut c_void,
+    instance_name: *const c_char,
+    status: Status,
+    category: *const c_char,
+    message: *const c_char,
+    ...
+);
+
+/// `fmi2CallbackFunctions`
+#[repr(C)]
+pub struct CallbackFunctions {
+    /// Reports messages to the master
+    pub logger: Option<Logger>,
+    /// Not used
+    pub allocate_memory: Option<unsafe extern "C" fn(usize, usize) -> *mut c_void>,
+    /// Not used
+    pub free_memory: Option<unsafe extern "C" fn(*mut c_void)>,
+    /// Not used, as steps are never asynchronous
+    pub step_finished: Option<unsafe extern "C" fn(*mut c_void, Status)>,
+    /// Passed back to the logger
+    pub component_environment: *mut c_void,
+}
+
+/// An instance of the FMU, which is handed to the master as an
+/// opaque `fmi2Component`
+pub struct Instance {
+    /// The name given by the master
+    name: CString,
+
+    /// The GUID given by the master
+    guid: String,
+
+    /// The `resources` directory of the FMU
+    resources: PathBuf,
+
+    /// The logger given by the master
+    logger: Option<Logger>,
+
+    /// Passed back to the logger
+    environment: *mut c_void,
+
+    /// Whether to report debug messages
+    logging_on: bool,
+
+    /// The simulation
+    fmu: Fmu,
+}
+
+impl Instance {
+    /// Sends a message to the master, if it has given a logger
+    fn log(&self, status: Status, msg: &str) {
+        if status == Status::Ok && !self.logging_on {
+            return;
+        }
+        log(self.logger, self.environment, &self.name, status, msg)
+    }
+}
+
+/// Sends a message through a logger, if any
+fn log(logger: Option<Logger>, environment: *mut c_void, name: &CStr, status: Status, msg: &str) {
+    if let Some(logger) = logger {
+        let category = if status == Status::Ok {
+            c"logAll"
+        } else {
+            c"logStatusError"
+        };
+        let msg = CString::new(msg.replace('\0', " ")).expect("Nul bytes were removed");
+        // Never pass the message as the format, as it might contain '%'
+        unsafe {
+            logger(
+                environment,
+                name.as_ptr(),
+                status,
+                category.as_ptr(),
+                c"%s".as_ptr(),
+                msg.as_ptr(),
+            )
+        }
+    }
+}
+
+/// Parses the URI of the `resources` directory given by the master
+/// (e.g., `file:///C:/my%20fmu/resources`) into a path
+pub fn resources_path(uri: &str) -> Result<PathBuf, String> {
+    let rest = uri.strip_prefix("file:").ok_or_else(|| {
+        format!(
+            "Only 'file:' resource locations are supported... got '{}'",
+            uri
+        )
+    })?;
+    // Skip the authority (e.g., 'localhost'), if any
+    let rest = match rest.strip_prefix("//") {
+        Some(r) => &r[r.find('/').unwrap_or(r.len())..],
+        None => rest,
+    };
+
+    let mut bytes = Vec::with_capacity(rest.len());
+    let mut chars = rest.bytes();
+    while let Some(b) = chars.next() {
+        if b == b'%' {
+            let hex = [chars.next().unwrap_or(0), chars.next().unwrap_or(0)];
+            let v = std::str::from_utf8(&hex)
+                .ok()
+                .and_then(|h| u8::from_str_radix(h, 16).ok())
+                .ok_or_else(|| format!("Invalid escape sequence in '{}'", uri))?;
+            bytes.push(v);
+        } else {
+            bytes.push(b);
+        }
+    }
+    let mut path = String::from_utf8(bytes).map_err(|e| e.to_string())?;
+
+    // Remove the slash before Windows drive letters (e.g., '/C:/')
+    if cfg!(windows) && path.len() > 2 && path.as_bytes()[2] == b':' {
+        path.remove(0);
+    }
+    Ok(PathBuf::from(path))
+}
+
+/// Reads the resources of the FMU, checking that they match the GUID
+/// of the `modelDescription.xml`
+fn load(resources: &Path, guid: &str) -> Result<Fmu, String> {
+    let (config, model, weather) = fmu::read_resources(resources)?;
+    let expected = description::guid(&model, &weather, config.n);
+    if expected != guid {
+        return Err(format!(
+            "GUID '{}' does not match the resources of the FMU (expecting '{}')",
+            guid, expected
+        ));
+    }
+    Fmu::from_contents(&config, &model, &weather)
+}
+
+/// Runs `f` on an instance, reporting its errors (and panics) to the master
+///
+/// # Safety
+///
+/// `c` must be null or an instance returned by `fmi2Instantiate` that has
+/// not been freed
+unsafe fn with_instance<F: FnOnce(&mut Instance) -> Result<(), String>>(
+    c: *mut c_void,
+    f: F,
+) -> Status {
+    let instance = match (c as *mut Instance).as_mut() {
+        Some(i) => i,
+        None => return Status::Error,
+    };
+    let msg = match catch_unwind(AssertUnwindSafe(|| f(&mut *instance))) {
+        Ok(Ok(())) => return Status::Ok,
+        Ok(Err(e)) => e,
+        Err(_) => "Internal error: SIMPLE panicked".to_string(),
+    };
+    instance.log(Status::Error, &msg);
+    Status::Error
+}
+
+/// Borrows a slice from a pointer and a length
+///
+/// # Safety
+///
+/// `data` must be valid for `len` elements, or null if `len` is 0
+unsafe fn slice<'a, T>(data: *const T, len: usize) -> Result<&'a [T], String> {
+    if len == 0 {
+        return Ok(&[]);
+    }
+    if data.is_null() {
+        return Err("Received a null array".to_string());
+    }
+    Ok(std::slice::from_raw_parts(data, len))
+}
+
+/// Borrows a string from a nul-terminated pointer
+///
+/// # Safety
+///
+/// `s` must be null or point to a valid nul-terminated string
+unsafe fn string<'a>(s: *const c_char) -> Result<&'a str, String> {
+    if s.is_null() {
+        return Err("Received a null string".to_string());
+    }
+    CStr::from_ptr(s).to_str().map_err(|e| e.to_string())
+}
+
+/// Returns `"default"`, as the types in the FMI headers are used
+#[no_mangle]
+pub extern "C" fn fmi2GetTypesPlatform() -> *const c_char {
+    c"default".as_ptr()
+}
+
+/// Returns `"2.0"`
+#[no_mangle]
+pub extern "C" fn fmi2GetVersion() -> *const c_char {
+    c"2.0".as_ptr()
+}
+
+/// Enables or disables the debug messages. Log categories are ignored.
+///
+/// # Safety
+///
+/// `c` must be an instance returned by `fmi2Instantiate`
+#[no_mangle]
+pub unsafe extern "C" fn fmi2SetDebugLogging(
+    c: *mut c_void,
+    logging_on: c_int,
+    _n_categories: usize,
+    _categories: *const *const c_char,
+) -> Status {
+    with_instance(c, |i| {
+        i.logging_on = logging_on != 0;
+        Ok(())
+    })
+}
+
+/// Creates an instance of the FMU, reading the model and the weather from
+/// its resources. Returns null on error.
+///
+/// # Safety
+///
+/// The strings must be valid nul-terminated strings, and `functions` must
+/// be null or point to valid callback functions
+#[no_mangle]
+pub unsafe extern "C" fn fmi2Instantiate(
+    instance_name: *const c_char,
+    fmu_type: c_int,
+    fmu_guid: *const c_char,
+    fmu_resource_location: *const c_char,
+    functions: *const CallbackFunctions,
+    _visible: c_int,
+    logging_on: c_int,
+) -> *mut c_void {
+    let (logger, environment) = match functions.as_ref() {
+        Some(f) => (f.logger, f.component_environment),
+        None => (None, ptr::null_mut()),
+    };
+    let name = match string(instance_name) {
+        Ok(n) => CString::new(n).expect("Came from a nul-terminated string"),
+        Err(_) => CString::default(),
+    };
+
+    let instance = catch_unwind(AssertUnwindSafe(|| -> Result<Instance, String> {
+        if fmu_type != CO_SIMULATION {
+            return Err("This FMU only supports co-simulation".to_string());
+        }
+        let guid = string(fmu_guid)?.to_string();
+        let resources = resources_path(string(fmu_resource_location)?)?;
+        let fmu = load(&resources, &guid)?;
+        Ok(Instance {
+            name: name.clone(),
+            guid,
+            resources,
+            logger,
+            environment,
+            logging_on: logging_on != 0,
+            fmu,
+        })
+    }));
+    match instance {
+        Ok(Ok(i)) => Box::into_raw(Box::new(i)) as *mut c_void,
+        Ok(Err(e)) => {
+            log(logger, environment, &name, Status::Error, &e);
+            ptr::null_mut()
+        }
+        Err(_) => {
+            let msg = "Internal error: SIMPLE panicked";
+            log(logger, environment, &name, Status::Fatal, msg);
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Releases an instance. Null instances are ignored.
+///
+/// # Safety
+///
+/// `c` must be null or an instance returned by `fmi2Instantiate` that has
+/// not been freed
+#[no_mangle]
+pub unsafe extern "C" fn fmi2FreeInstance(c: *mut c_void) {
+    if !c.is_null() {
+        drop(Box::from_raw(c as *mut Instance));
+    }
+}
+
+/// Sets up the experiment. The tolerance and stop time are ignored; if the
+/// start time is not zero, the simulation is advanced (with the default
+/// inputs) until then.
+///
+/// # Safety
+///
+/// `c` must be an instance returned by `fmi2Instantiate`
+#[no_mangle]
+pub unsafe extern "C" fn fmi2SetupExperiment(
+    c: *mut c_void,
+    _tolerance_defined: c_int,
+    _tolerance: c_double,
+    start_time: c_double,
+    _stop_time_defined: c_int,
+    _stop_time: c_double,
+) -> Status {
+    with_instance(c, |i| {
+        let time = i.fmu.time();
+        if start_time > time {
+            i.log(
+                Status::Ok,
+                &format!("Advancing the simulation to the start time {}", start_time),
+            );
+            i.fmu.do_step(time, start_time - time)?;
+        }
+        Ok(())
+    })
+}
+
+/// Nothing needs to be done when entering the initialization mode
+///
+/// # Safety
+///
+/// `c` must be an instance returned by `fmi2Instantiate`
+#[no_mangle]
+pub unsafe extern "C" fn fmi2EnterInitializationMode(c: *mut c_void) -> Status {
+    with_instance(c, |_| Ok(()))
+}
+
+/// Nothing needs to be done when exiting the initialization mode
+///
+/// # Safety
+///
+/// `c` must be an instance returned by `fmi2Instantiate`
+#[no_mangle]
+pub unsafe extern "C" fn fmi2ExitInitializationMode(c: *mut c_void) -> Status {
+    with_instance(c, |_| Ok(()))
+}
+
+/// Nothing needs to be done when terminating
+///
+/// # Safety
+///
+/// `c` must be an instance returned by `fmi2Instantiate`
+#[no_mangle]
+pub unsafe extern "C" fn fmi2Terminate(c: *mut c_void) -> Status {
+    with_instance(c, |_| Ok(()))
+}
+
+/// Takes the instance back to the state right after `fmi2Instantiate`
+///
+/// # Safety
+///
+/// `c` must be an instance returned by `fmi2Instantiate`
+#[no_mangle]
+pub unsafe extern "C" fn fmi2Reset(c: *mut c_void) -> Status {
+    with_instance(c, |i| {
+        i.fmu = load(&i.resources, &i.guid)?;
+        Ok(())
+    })
+}
+
+/// Reads the values of some real variables
+///
+/// # Safety
+///
+/// `c` must be an instance returned by `fmi2Instantiate`, and `vr` and
+/// `value` must be valid for `nvr` elements
+#[no_mangle]
+pub unsafe extern "C" fn fmi2GetReal(
+    c: *mut c_void,
+    vr: *const c_uint,
+    nvr: usize,
+    value: *mut c_double,
+) -> Status {
+    with_instance(c, |i| {
+        let vr = slice(vr, nvr)?;
+        if nvr > 0 && value.is_null() {
+            return Err("Received a null array".to_string());
+        }
+        for (j, r) in vr.iter().enumerate() {
+            *value.add(j) = i.fmu.get_real(*r as usize)?;
+        }
+        Ok(())
+    })
+}
+
+/// Sets the values of some real inputs
+///
+/// # Safety
+///
+/// `c` must be an instance returned by `fmi2Instantiate`, and `vr` and
+/// `value` must be valid for `nvr` elements
+#[no_mangle]
+pub unsafe extern "C" fn fmi2SetReal(
+    c: *mut c_void,
+    vr: *const c_uint,
+    nvr: usize,
+    value: *const c_double,
+) -> Status {
+    with_instance(c, |i| {
+        let vr = slice(vr, nvr)?;
+        let value = slice(value, nvr)?;
+        for (r, v) in vr.iter().zip(value.iter()) {
+            i.fmu.set_real(*r as usize, *v)?;
+        }
+        Ok(())
+    })
+}
+
+/// Advances the simulation from `current` by `h` seconds, which must be a
+/// multiple of the timestep
+///
+/// # Safety
+///
+/// `c` must be an instance returned by `fmi2Instantiate`
+#[no_mangle]
+pub unsafe extern "C" fn fmi2DoStep(
+    c: *mut c_void,
+    current: c_double,
+    h: c_double,
+    _no_set_prior_state: c_int,
+) -> Status {
+    with_instance(c, |i| i.fmu.do_step(current, h))
+}
+
+/// Reports that a feature is not supported by this FMU
+///
+/// # Safety
+///
+/// `c` must be null or an instance returned by `fmi2Instantiate`
+unsafe fn unsupported(c: *mut c_void, what: &str) -> Status {
+    with_instance(c, |_| Err(format!("This FMU does not support {}", what)))
+}
+
+/// Succeeds only when no variables are requested, as there are no
+/// variables of this type
+///
+/// # Safety
+///
+/// `c` must be null or an instance returned by `fmi2Instantiate`
+unsafe fn no_variables(c: *mut c_void, nvr: usize, kind: &str) -> Status {
+    with_instance(c, |_| {
+        if nvr == 0 {
+            Ok(())
+        } else {
+            Err(format!("This FMU has no {} variables", kind))
+        }
+    })
+}
+
+/// Declares FMI functions that are not supported by this FMU
+macro_rules! unsupported {
+    ($($name:ident($($arg:ident: $ty:ty),*) => $what:literal;)*) => {
+        $(
+            #[doc = concat!("Not supported: fails with `fmi2Error`, as this FMU does not support ", $what)]
+            ///
+            /// # Safety
+            ///
+            /// `c` must be null or an instance returned by `fmi2Instantiate`
+            #[no_mangle]
+            pub unsafe extern "C" fn $name(c: *mut c_void, $(_: $ty),*) -> Status {
+                unsupported(c, $what)
+            }
+        )*
+    };
+}
+
+/// Declares FMI functions that access variables of types this FMU does not have
+macro_rules! no_variables {
+    ($($name:ident($ty:ty) => $kind:literal;)*) => {
+        $(
+            #[doc = concat!("Fails unless `nvr` is 0, as this FMU has no ", $kind, " variables")]
+            ///
+            /// # Safety
+            ///
+            /// `c` must be null or an instance returned by `fmi2Instantiate`
+            #[no_mangle]
+            pub unsafe extern "C" fn $name(
+                c: *mut c_void,
+                _vr: *const c_uint,
+                nvr: usize,
+                _value: $ty,
+            ) -> Status {
+                no_variables(c, nvr, $kind)
+            }
+        )*
+    };
+}
+
+no_variables! {
+    fmi2GetInteger(*mut c_int) => "integer";
+    fmi2SetInteger(*const c_int) => "integer";
+    fmi2GetBoolean(*mut c_int) => "boolean";
+    fmi2SetBoolean(*const c_int) => "boolean";
+    fmi2GetString(*mut *const c_char) => "string";
+    fmi2SetString(*const *const c_char) => "string";
+}
+
+unsupported! {
+    fmi2GetFMUstate(s: *mut *mut c_void) => "saving its state";
+    fmi2SetFMUstate(s: *mut c_void) => "restoring its state";
+    fmi2FreeFMUstate(s: *mut *mut c_void) => "saving its state";
+    fmi2SerializedFMUstateSize(s: *mut c_void, size: *mut usize) => "serializing its state";
+    fmi2SerializeFMUstate(s: *mut c_void, data: *mut c_char, size: usize) => "serializing its state";
+    fmi2DeSerializeFMUstate(data: *const c_char, size: usize, s: *mut *mut c_void) => "serializing its state";
+    fmi2GetDirectionalDerivative(u: *const c_uint, nu: usize, z: *const c_uint, nz: usize, dv: *const c_double, dz: *mut c_double) => "directional derivatives";
+    fmi2SetRealInputDerivatives(vr: *const c_uint, nvr: usize, order: *const c_int, value: *const c_double) => "interpolating inputs";
+    fmi2GetRealOutputDerivatives(vr: *const c_uint, nvr: usize, order: *const c_int, value: *mut c_double) => "output derivatives";
+    fmi2CancelStep() => "asynchronous steps";
+    fmi2GetStatus(kind: c_int, value: *mut Status) => "asynchronous steps";
+    fmi2GetRealStatus(kind: c_int, value: *mut c_double) => "asynchronous steps";
+    fmi2GetIntegerStatus(kind: c_int, value: *mut c_int) => "asynchronous steps";
+    fmi2GetBooleanStatus(kind: c_int, value: *mut c_int) => "asynchronous steps";
+    fmi2GetStringStatus(kind: c_int, value: *mut *const c_char) => "asynchronous steps";
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_resources_path() -> Result<(), String> {
+        assert_eq!(
+            resources_path("file:///tmp/my%20fmu/resources")?,
+            PathBuf::from("/tmp/my fmu/resources")
+        );
+        assert_eq!(
+            resources_path("file://localhost/tmp/resources")?,
+            PathBuf::from("/tmp/resources")
+        );
+        assert_eq!(resources_path("file:/tmp")?, PathBuf::from("/tmp"));
+        assert!(resources_path("http://example.com/resources").is_err());
+        assert!(resources_path("file:///tmp/%zz").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_fmi() -> Result<(), String> {
+        // Build the resources of an FMU
+        let dir = std::env::temp_dir().join(format!("simple_fmu_test_{}", std::process::id()));
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        let model = fmu::testing::box_model()?;
+        let weather = fmu::testing::wellington()?;
+        let config = fmu::FmuConfig {
+            model: "box.json".to_string(),
+            weather: "wellington.epw".to_string(),
+            n: 1,
+        };
+        fs::write(dir.join("box.json"), &model).map_err(|e| e.to_string())?;
+        fs::write(dir.join("wellington.epw"), &weather).map_err(|e| e.to_string())?;
+        let json = serde_json::to_string(&config).map_err(|e| e.to_string())?;
+        fs::write(dir.join(fmu::CONFIG_FILE), json).map_err(|e| e.to_string())?;
+
+        let guid =
+            CString::new(description::guid(&model, &weather, 1)).map_err(|e| e.to_string())?;
+        let uri = CString::new(format!("file://{}", dir.display())).map_err(|e| e.to_string())?;
+        unsafe {
+            // Wrong GUID or type
+            let c = fmi2Instantiate(
+                c"box".as_ptr(),
+                CO_SIMULATION,
+                c"{nope}".as_ptr(),
+                uri.as_ptr(),
+                ptr::null(),
+                0,
+                0,
+            );
+            assert!(c.is_null());
+            let c = fmi2Instantiate(
+                c"box".as_ptr(),
+                0,
+                guid.as_ptr(),
+                uri.as_ptr(),
+                ptr::null(),
+                0,
+                0,
+            );
+            assert!(c.is_null());
+
+            let c = fmi2Instantiate(
+                c"box".as_ptr(),
+                CO_SIMULATION,
+                guid.as_ptr(),
+                uri.as_ptr(),
+                ptr::null(),
+                0,
+                0,
+            );
+            assert!(!c.is_null());
+            assert_eq!(fmi2SetupExperiment(c, 0, 0., 0., 0, 0.), Status::Ok);
+            assert_eq!(fmi2EnterInitializationMode(c), Status::Ok);
+            assert_eq!(fmi2ExitInitializationMode(c), Status::Ok);
+
+            let temperature = (*(c as *mut Instance))
+                .fmu
+                .variables()
+                .iter()
+                .position(|v| v.name == "ZONE ONE.dry_bulb_temperature")
+                .unwrap() as c_uint;
+            let temperature = [temperature];
+            let mut value = [0.];
+            assert_eq!(
+                fmi2GetReal(c, temperature.as_ptr(), 1, value.as_mut_ptr()),
+                Status::Ok
+            );
+            assert_eq!(fmi2DoStep(c, 0., 3600., 1), Status::Ok);
+            assert_eq!(fmi2DoStep(c, 0., 3600., 1), Status::Error);
+
+            // Outputs cannot be set, and unknown variables fail
+            assert_eq!(
+                fmi2SetReal(c, temperature.as_ptr(), 1, [1.].as_ptr()),
+                Status::Error
+            );
+            assert_eq!(
+                fmi2GetReal(c, [9999].as_ptr(), 1, value.as_mut_ptr()),
+                Status::Error
+            );
+            assert_eq!(
+                fmi2GetInteger(c, ptr::null(), 0, ptr::null_mut()),
+                Status::Ok
+            );
+
+            // Reset takes the time back to 0
+            assert_eq!(fmi2Reset(c), Status::Ok);
+            assert_eq!(fmi2DoStep(c, 0., 3600., 1), Status::Ok);
+            assert_eq!(fmi2Terminate(c), Status::Ok);
+            fmi2FreeInstance(c);
+        }
+        fs::remove_dir_all(&dir).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
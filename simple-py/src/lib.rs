@@ -0,0 +1,446 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+#![deny(missing_docs)]
+
+//! Python bindings for `SIMPLE`, allowing Python users to load models and
+//! weather files, run simulations and analyse their results as `numpy`
+//! arrays.
+//!
+//! ```python
+//! import simple_py
+//!
+//! model = simple_py.Model.from_file("./tests/box/box.spl")
+//! results = simple_py.simulate(model, "./tests/wellington.epw", n=4)
+//!
+//! temperature = results.space_temperature("ZONE ONE")  # a numpy array
+//! everything = results.to_numpy()  # one row per timestep
+//! ```
+
+use numpy::ndarray::Array2;
+use numpy::{IntoPyArray, PyArray1, PyArray2};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use simple::run_simulation::{load_weather, run_collect, SimOptions};
+use simple::{
+    CurrentWeather, Date, DaylightDimming, Float, Model, OccupantBehaviour, SimpleError,
+    SimulationResults, SimulationStateHeader, VoidControl, Weather,
+};
+
+/// Transforms the errors of `SIMPLE` into Python exceptions
+fn to_py_err<E: ToString>(e: E) -> PyErr {
+    PyValueError::new_err(e.to_string())
+}
+
+/// Reads a model from a `.spl` or a `.json` file
+fn load_model(path: &str) -> Result<(Model, SimulationStateHeader), String> {
+    let loaded = if path.ends_with(".spl") {
+        Model::from_file(path)
+    } else if path.ends_with(".json") {
+        Model::from_json_file(path)
+    } else {
+        return Err(format!(
+            "Unkown kind of file '{}'... expecting .json or .spl",
+            path
+        ));
+    };
+    loaded.map_err(|e| e.to_string())
+}
+
+/// Simulates a copy of `model` and collects the whole state at every
+/// timestep. The `control` can be `None`, `"people"` or `"daylight"`
+/// (i.e., the same built-in routines available in the command line).
+fn run(
+    model: &Model,
+    options: &SimOptions,
+    control: Option<&str>,
+) -> Result<SimulationResults, SimpleError> {
+    // The objects of a model can be simulated only once, so
+    // we simulate a fresh copy.
+    let (model, mut header) = Model::from_json(&model.to_json()?)?;
+    match control {
+        None => run_collect(&model, &mut header, options, VoidControl {}),
+        Some("people") => {
+            let controller = OccupantBehaviour::new(&model)?;
+            run_collect(&model, &mut header, options, controller)
+        }
+        Some("daylight") => {
+            let controller = DaylightDimming::new(&model)?;
+            run_collect(&model, &mut header, options, controller)
+        }
+        Some(other) => Err(SimpleError::Other(format!(
+            "Unknown control '{}'... expecting 'people' or 'daylight'",
+            other
+        ))),
+    }
+}
+
+/// Transforms a `(month, day)` tuple into the [`Date`] at midnight
+fn to_date(month_day: Option<(u8, u8)>) -> Option<Date> {
+    month_day.map(|(month, day)| Date {
+        month,
+        day,
+        hour: 0.,
+        year: None,
+    })
+}
+
+/// The `(month, day, hour)` of a [`Date`]
+fn from_date(date: &Date) -> (u8, u8, Float) {
+    (date.month, date.day, date.hour)
+}
+
+/// A building model
+#[pyclass(name = "Model")]
+struct PyModel {
+    /// The model
+    model: Model,
+}
+
+#[pymethods]
+impl PyModel {
+    /// Reads a model from a `.spl` or a `.json` file
+    #[staticmethod]
+    fn from_file(path: &str) -> PyResult<Self> {
+        let (model, _) = load_model(path).map_err(to_py_err)?;
+        Ok(Self { model })
+    }
+
+    /// Parses a model in the `.spl` format
+    #[staticmethod]
+    fn from_spl(src: &str) -> PyResult<Self> {
+        let (model, _) = Model::from_bytes(src.as_bytes()).map_err(to_py_err)?;
+        Ok(Self { model })
+    }
+
+    /// Parses a model in JSON format
+    #[staticmethod]
+    fn from_json(src: &str) -> PyResult<Self> {
+        let (model, _) = Model::from_json(src).map_err(to_py_err)?;
+        Ok(Self { model })
+    }
+
+    /// Serializes the model in JSON format
+    fn to_json(&self) -> PyResult<String> {
+        self.model.to_json().map_err(to_py_err)
+    }
+
+    /// Serializes the model in the `.spl` format
+    fn __str__(&self) -> String {
+        format!("{}", self.model)
+    }
+
+    /// The names of the spaces in the model
+    #[getter]
+    fn spaces(&self) -> Vec<String> {
+        self.model.spaces.iter().map(|s| s.name().clone()).collect()
+    }
+
+    /// The names of the surfaces in the model
+    #[getter]
+    fn surfaces(&self) -> Vec<String> {
+        self.model
+            .surfaces
+            .iter()
+            .map(|s| s.name().clone())
+            .collect()
+    }
+
+    /// The names of the fenestrations in the model
+    #[getter]
+    fn fenestrations(&self) -> Vec<String> {
+        self.model
+            .fenestrations
+            .iter()
+            .map(|f| f.name().clone())
+            .collect()
+    }
+
+    /// The names of the HVAC systems in the model
+    #[getter]
+    fn hvacs(&self) -> Vec<String> {
+        self.model.hvacs.iter().map(|h| h.name().clone()).collect()
+    }
+
+    /// The names of the luminaires in the model
+    #[getter]
+    fn luminaires(&self) -> Vec<String> {
+        self.model
+            .luminaires
+            .iter()
+            .map(|l| l.name().clone())
+            .collect()
+    }
+}
+
+/// Weather data, as read from an EPW, TMY3, CSV or `.sw` file
+#[pyclass(name = "Weather")]
+struct PyWeather {
+    /// The weather
+    weather: Weather,
+}
+
+impl PyWeather {
+    /// A series with one value per line of weather data
+    fn series<'py>(
+        &self,
+        py: Python<'py>,
+        f: fn(&CurrentWeather) -> Float,
+    ) -> Bound<'py, PyArray1<Float>> {
+        let v: Vec<Float> = self.weather.data.iter().map(f).collect();
+        v.into_pyarray_bound(py)
+    }
+}
+
+#[pymethods]
+impl PyWeather {
+    /// Reads a weather file, whose format is identified by its extension
+    #[staticmethod]
+    fn from_file(path: &str) -> PyResult<Self> {
+        let weather = load_weather(path).map_err(to_py_err)?;
+        Ok(Self { weather })
+    }
+
+    /// The number of lines of weather data
+    fn __len__(&self) -> usize {
+        self.weather.data.len()
+    }
+
+    /// The name of the city
+    #[getter]
+    fn city(&self) -> String {
+        self.weather.location.city.clone()
+    }
+
+    /// The latitude, in degrees (South is negative)
+    #[getter]
+    fn latitude(&self) -> Float {
+        self.weather.location.latitude.to_degrees()
+    }
+
+    /// The longitude, in degrees (West is negative)
+    #[getter]
+    fn longitude(&self) -> Float {
+        self.weather.location.longitude.to_degrees()
+    }
+
+    /// The `(month, day, hour)` of each line of weather data
+    #[getter]
+    fn dates(&self) -> Vec<(u8, u8, Float)> {
+        self.weather
+            .data
+            .iter()
+            .map(|w| from_date(&w.date))
+            .collect()
+    }
+
+    /// The dry bulb temperature, in C
+    fn dry_bulb_temperature<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<Float>> {
+        self.series(py, |w| w.dry_bulb_temperature)
+    }
+
+    /// The dew point temperature, in C
+    fn dew_point_temperature<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<Float>> {
+        self.series(py, |w| w.dew_point_temperature)
+    }
+
+    /// The relative humidity, from 0 to 1
+    fn relative_humidity<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<Float>> {
+        self.series(py, |w| w.relative_humidity)
+    }
+
+    /// The global horizontal radiation, in W/m2
+    fn global_horizontal_radiation<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<Float>> {
+        self.series(py, |w| w.global_horizontal_radiation)
+    }
+
+    /// The direct normal radiation, in W/m2
+    fn direct_normal_radiation<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<Float>> {
+        self.series(py, |w| w.direct_normal_radiation)
+    }
+
+    /// The diffuse horizontal radiation, in W/m2
+    fn diffuse_horizontal_radiation<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<Float>> {
+        self.series(py, |w| w.diffuse_horizontal_radiation)
+    }
+
+    /// The wind speed, in m/s
+    fn wind_speed<'py>(&self, py: Python<'py>) -> Bound<'py, PyArray1<Float>> {
+        self.series(py, |w| w.wind_speed)
+    }
+}
+
+/// The results of a simulation, with one value per timestep
+/// for every element of the simulation state
+#[pyclass(name = "SimulationResults")]
+struct PySimulationResults {
+    /// The results
+    results: SimulationResults,
+}
+
+/// Transforms an optional series into a `numpy` array, or `None`
+fn to_array(py: Python<'_>, series: Option<Vec<Float>>) -> Option<Bound<'_, PyArray1<Float>>> {
+    series.map(|v| v.into_pyarray_bound(py))
+}
+
+#[pymethods]
+impl PySimulationResults {
+    /// The number of timesteps
+    fn __len__(&self) -> usize {
+        self.results.len()
+    }
+
+    /// The name of each column of the results
+    #[getter]
+    fn header(&self) -> Vec<String> {
+        self.results.header().to_vec()
+    }
+
+    /// The `(month, day, hour)` of each timestep
+    #[getter]
+    fn dates(&self) -> Vec<(u8, u8, Float)> {
+        self.results.dates().map(|d| from_date(&d)).collect()
+    }
+
+    /// All the results, as a 2D array with one row per
+    /// timestep and one column per element of the `header`
+    fn to_numpy<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyArray2<Float>>> {
+        let recorder = self.results.recorder();
+        let data = recorder
+            .timesteps()
+            .iter()
+            .flat_map(|t| t.values.iter().copied())
+            .collect();
+        let shape = (recorder.timesteps().len(), recorder.header().len());
+        let array = Array2::from_shape_vec(shape, data).map_err(to_py_err)?;
+        Ok(array.into_pyarray_bound(py))
+    }
+
+    /// The results in the column called `name`, or `None` if there
+    /// is no such column
+    fn column<'py>(&self, py: Python<'py>, name: &str) -> Option<Bound<'py, PyArray1<Float>>> {
+        let recorder = self.results.recorder();
+        let i = recorder.header().iter().position(|h| h == name)?;
+        to_array(py, recorder.series(i))
+    }
+
+    /// The dry bulb temperature of a space, in C
+    fn space_temperature<'py>(
+        &self,
+        py: Python<'py>,
+        space: &str,
+    ) -> Option<Bound<'py, PyArray1<Float>>> {
+        to_array(py, self.results.space_temperature(space))
+    }
+
+    /// The heating (positive) or cooling (negative) power of an HVAC, in W
+    fn hvac_power<'py>(&self, py: Python<'py>, hvac: &str) -> Option<Bound<'py, PyArray1<Float>>> {
+        to_array(py, self.results.hvac_power(hvac))
+    }
+
+    /// The energy consumed by an HVAC during the whole simulation, in kWh
+    fn hvac_energy(&self, hvac: &str) -> Option<Float> {
+        self.results.hvac_energy(hvac)
+    }
+
+    /// The energy consumed by a luminaire during the whole simulation, in kWh
+    fn luminaire_energy(&self, luminaire: &str) -> Option<Float> {
+        self.results.luminaire_energy(luminaire)
+    }
+}
+
+/// Simulates a model and returns its results. The `start` and `end` of
+/// the simulation are `(month, day)` tuples (the `end` is exclusive); the
+/// whole weather file is simulated if they are not given. The `control`
+/// can be `"people"` or `"daylight"`.
+#[pyfunction]
+#[pyo3(signature = (model, weather_file, n=1, control=None, start=None, end=None, warmup_days=0))]
+#[allow(clippy::too_many_arguments)]
+fn simulate(
+    py: Python<'_>,
+    model: &PyModel,
+    weather_file: String,
+    n: usize,
+    control: Option<String>,
+    start: Option<(u8, u8)>,
+    end: Option<(u8, u8)>,
+    warmup_days: usize,
+) -> PyResult<PySimulationResults> {
+    let options = SimOptions {
+        weather_file: Some(weather_file),
+        n,
+        start_date: to_date(start),
+        end_date: to_date(end),
+        warmup_days,
+        quiet: true,
+        ..SimOptions::default()
+    };
+    let results = py
+        .allow_threads(|| run(&model.model, &options, control.as_deref()))
+        .map_err(to_py_err)?;
+    Ok(PySimulationResults { results })
+}
+
+/// The `simple_py` Python module
+#[pymodule]
+fn simple_py(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_class::<PyModel>()?;
+    m.add_class::<PyWeather>()?;
+    m.add_class::<PySimulationResults>()?;
+    m.add_function(wrap_pyfunction!(simulate, m)?)?;
+    Ok(())
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn test_load_model() {
+        assert!(load_model("../tests/box/box.spl").is_ok());
+        assert!(load_model("../tests/box/box.idf").is_err());
+    }
+
+    #[test]
+    fn test_run() -> Result<(), SimpleError> {
+        let (model, _) = load_model("../tests/box/box.spl")?;
+        let options = SimOptions {
+            weather_file: Some("../tests/wellington.epw".into()),
+            n: 1,
+            start_date: to_date(Some((1, 1))),
+            end_date: to_date(Some((1, 3))),
+            quiet: true,
+            ..SimOptions::default()
+        };
+
+        // The same model can be simulated more than once
+        let first = run(&model, &options, None)?;
+        let second = run(&model, &options, None)?;
+        assert_eq!(first.len(), 48);
+        assert_eq!(first.header(), second.header());
+
+        assert!(run(&model, &options, Some("magic")).is_err());
+        Ok(())
+    }
+}
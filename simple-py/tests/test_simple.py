@@ -0,0 +1,36 @@
+# Run with `maturin develop && pytest tests` from the simple-py directory
+import numpy as np
+import pytest
+import simple_py
+
+
+def test_model():
+    model = simple_py.Model.from_file("../tests/box/box.spl")
+    assert len(model.spaces) == 1
+    again = simple_py.Model.from_json(model.to_json())
+    assert again.spaces == model.spaces
+
+
+def test_weather():
+    weather = simple_py.Weather.from_file("../tests/wellington.epw")
+    assert len(weather) == 8760
+    assert weather.dry_bulb_temperature().shape == (8760,)
+    assert weather.latitude < 0
+
+
+def test_simulate():
+    model = simple_py.Model.from_file("../tests/box/box.spl")
+    results = simple_py.simulate(
+        model, "../tests/wellington.epw", start=(1, 1), end=(1, 3)
+    )
+    assert len(results) == 48
+    matrix = results.to_numpy()
+    assert matrix.shape == (48, len(results.header))
+
+    space = model.spaces[0]
+    temperature = results.space_temperature(space)
+    assert np.all(np.isfinite(temperature))
+    assert results.space_temperature("Nowhere") is None
+
+    with pytest.raises(ValueError):
+        simple_py.simulate(model, "../tests/wellington.epw", control="magic")
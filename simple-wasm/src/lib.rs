@@ -0,0 +1,147 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+#![deny(missing_docs)]
+
+//! A WebAssembly wrapper around `SIMPLE`, for powering in-browser tools.
+//! Everything is passed in and out as strings and bytes, as there is no
+//! file system.
+//!
+//! ```js
+//! import init, { Simulation } from "./pkg/simple_wasm.js";
+//!
+//! await init();
+//! const model = await (await fetch("box.spl")).text();
+//! const weather = new Uint8Array(await (await fetch("wellington.epw")).arrayBuffer());
+//!
+//! const sim = new Simulation(model, weather, "epw", 4);
+//! const i = sim.index_of('{"SpaceDryBulbTemperature":"ZONE ONE"}');
+//! while (sim.step()) {
+//!     console.log(sim.month, sim.day, sim.hour, sim.get(i));
+//! }
+//! ```
+
+use simple::{Float, Model, SimulationStateHeader, Stepper};
+use wasm_bindgen::prelude::*;
+
+/// Parses a model, either in JSON or in the `.spl` format
+fn parse_model(src: &str) -> Result<(Model, SimulationStateHeader), String> {
+    let parsed = if src.trim_start().starts_with('{') {
+        Model::from_json(src)
+    } else {
+        Model::from_bytes(src.as_bytes())
+    };
+    parsed.map_err(|e| e.to_string())
+}
+
+/// A simulation that is advanced one timestep at a time
+#[wasm_bindgen]
+pub struct Simulation {
+    /// The simulation
+    stepper: Stepper,
+}
+
+#[wasm_bindgen]
+impl Simulation {
+    /// Prepares the simulation of a `model` (in JSON or `.spl` format)
+    /// with the contents of a weather file in a certain `weather_format`
+    /// (i.e., `"epw"`, `"csv"` or `"sw"`), with `n` timesteps per hour
+    #[wasm_bindgen(constructor)]
+    pub fn new(
+        model: &str,
+        weather: &[u8],
+        weather_format: &str,
+        n: usize,
+    ) -> Result<Simulation, JsError> {
+        let (model, header) = parse_model(model).map_err(|e| JsError::new(&e))?;
+        let weather = simple::run_simulation::weather_from_bytes(weather, weather_format)
+            .map_err(|e| JsError::new(&e.to_string()))?;
+        let stepper = Stepper::new(model, header, weather, n).map_err(|e| JsError::new(&e))?;
+        Ok(Self { stepper })
+    }
+
+    /// Simulates the next timestep. Returns `false` if the whole
+    /// simulation period had already been simulated.
+    pub fn step(&mut self) -> Result<bool, JsError> {
+        let date = self.stepper.step().map_err(|e| JsError::new(&e))?;
+        Ok(date.is_some())
+    }
+
+    /// The month of the last simulated timestep (or 0, if
+    /// nothing has been simulated)
+    #[wasm_bindgen(getter)]
+    pub fn month(&self) -> u8 {
+        self.stepper.date().map(|d| d.month).unwrap_or(0)
+    }
+
+    /// The day of the last simulated timestep (or 0, if
+    /// nothing has been simulated)
+    #[wasm_bindgen(getter)]
+    pub fn day(&self) -> u8 {
+        self.stepper.date().map(|d| d.day).unwrap_or(0)
+    }
+
+    /// The hour of the last simulated timestep (or 0, if
+    /// nothing has been simulated)
+    #[wasm_bindgen(getter)]
+    pub fn hour(&self) -> Float {
+        self.stepper.date().map(|d| d.hour).unwrap_or(0.)
+    }
+
+    /// The name of each element of the state
+    pub fn header(&self) -> Vec<String> {
+        self.stepper.header().to_vec()
+    }
+
+    /// The position within the state of the element called `name`
+    /// in the header, or `undefined` if there is no such element
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.stepper.index_of(name)
+    }
+
+    /// A copy of the whole state
+    pub fn state(&self) -> Vec<Float> {
+        self.stepper.state().clone()
+    }
+
+    /// Gets the `i`th value of the state
+    pub fn get(&self, i: usize) -> Result<Float, JsError> {
+        self.stepper
+            .state()
+            .get(i)
+            .copied()
+            .ok_or_else(|| JsError::new(&format!("There is no element {} in the state", i)))
+    }
+
+    /// Sets the `i`th value of the state, which will be used
+    /// when simulating the next timestep
+    pub fn set(&mut self, i: usize, value: Float) -> Result<(), JsError> {
+        match self.stepper.state_mut().get_mut(i) {
+            Some(v) => {
+                *v = value;
+                Ok(())
+            }
+            None => Err(JsError::new(&format!(
+                "There is no element {} in the state",
+                i
+            ))),
+        }
+    }
+}
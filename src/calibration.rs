@@ -0,0 +1,496 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Calibrating a model against measured data, following ASHRAE
+//! Guideline 14.
+//!
+//! The agreement between a simulation and some [`Measurement`]s is given
+//! by the Normalized Mean Bias Error (NMBE) and the Coefficient of Variation
+//! of the Root Mean Squared Error (CV(RMSE)), which are compared against the
+//! limits of Guideline 14 for hourly or monthly data (see [`Resolution`]).
+//!
+//! The tunable parameters are those of a [`ParametricStudy`], and a
+//! [`calibration`] is an [`Optimization`] that minimizes the CV(RMSE) of
+//! all the measurements subject to the limits of the guideline.
+//!
+//! ```no_run
+//! use simple::calibration::{calibrated_parameters, calibration, Measurement, Resolution};
+//! use simple::optimization::GeneticAlgorithm;
+//! use simple::parametrics::{Parameter, ParametricStudy};
+//! use simple::run_simulation::SimOptions;
+//! use simple::{Model, Output};
+//!
+//! let (base, _) = Model::from_file("./tests/box/box.spl").unwrap();
+//! let study = ParametricStudy::new(&base)
+//!     .unwrap()
+//!     .parameter(Parameter::material_thickness("R13LAYER", vec![0.02, 0.05, 0.1, 0.15]));
+//!
+//! let measured = Measurement::from_file(
+//!     "./measured.csv",
+//!     "Bedroom temperature",
+//!     Output::SpaceDryBulbTemperature("Bedroom".into()),
+//! )
+//! .unwrap();
+//! let optimization = calibration(study.clone(), vec![measured], Resolution::Hourly).unwrap();
+//!
+//! let options = SimOptions {
+//!     weather_file: Some("./tests/wellington.epw".into()),
+//!     quiet: true,
+//!     ..SimOptions::default()
+//! };
+//! let results = optimization
+//!     .run(&mut GeneticAlgorithm::new(8, 5, 0), &options)
+//!     .unwrap();
+//! match calibrated_parameters(&study, &results) {
+//!     Some(parameters) => println!("Calibrated: {:?}", parameters),
+//!     None => println!("The model could not be calibrated"),
+//! }
+//! ```
+
+use crate::optimization::{Constraint, Objective, Optimization, OptimizationResults};
+use crate::parametrics::ParametricStudy;
+use crate::simulation_results::SimulationResults;
+use crate::Float;
+use calendar::Date;
+use model::Output;
+use std::collections::HashMap;
+
+/// The resolution of the measured data, which determines the limits that
+/// a calibrated model has to satisfy according to ASHRAE Guideline 14
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+    /// Each measurement is compared against the simulated value at the
+    /// same time. NMBE within ±10% and CV(RMSE) below 30%.
+    Hourly,
+
+    /// The measurements and the simulated values are added up for each
+    /// month before comparing them. NMBE within ±5% and CV(RMSE) below 15%.
+    Monthly,
+}
+
+impl Resolution {
+    /// The maximum absolute value of the NMBE, in %
+    pub fn nmbe_limit(&self) -> Float {
+        match self {
+            Self::Hourly => 10.,
+            Self::Monthly => 5.,
+        }
+    }
+
+    /// The maximum value of the CV(RMSE), in %
+    pub fn cv_rmse_limit(&self) -> Float {
+        match self {
+            Self::Hourly => 30.,
+            Self::Monthly => 15.,
+        }
+    }
+}
+
+/// The date of a measurement or a simulated value, rounded to the minute,
+/// so that both can be matched regardless of the year or of small
+/// differences in their hours
+fn date_key(date: Date) -> (u8, u8, i64) {
+    let minutes = (date.hour * 60.).round();
+    let mut d = Date {
+        hour: 0.,
+        year: None,
+        ..date
+    };
+    d.add_minutes(minutes);
+    (d.month, d.day, (d.hour * 60.).round() as i64)
+}
+
+/// A measured time series of an [`Output`] of the model
+#[derive(Debug, Clone)]
+pub struct Measurement {
+    /// The name of the measurement
+    name: String,
+
+    /// The simulated output that corresponds to the measurement
+    output: Output,
+
+    /// The measured values, and when they were measured
+    data: Vec<(Date, Float)>,
+}
+
+impl Measurement {
+    /// Creates a `Measurement` of an `output` out of some `data`
+    pub fn new<S: Into<String>>(name: S, output: Output, data: Vec<(Date, Float)>) -> Self {
+        Self {
+            name: name.into(),
+            output,
+            data,
+        }
+    }
+
+    /// Reads the measurements in a `column` of some CSV data, whose first
+    /// line is a header and whose first column contains dates in ISO 8601
+    /// format (e.g., `2024-01-15 13:00` or `--01-15T13:00`). Empty cells
+    /// (i.e., missing data) are skipped.
+    pub fn from_csv(src: &str, column: &str, output: Output) -> Result<Self, String> {
+        let mut lines = src
+            .lines()
+            .enumerate()
+            .map(|(i, l)| (i + 1, l.trim()))
+            .filter(|(_, l)| !l.is_empty());
+        let (_, header) = lines.next().ok_or("The measured data is empty")?;
+        let i = header
+            .split(',')
+            .position(|h| h.trim().trim_matches('"') == column)
+            .ok_or_else(|| format!("The measured data has no '{}' column", column))?;
+        if i == 0 {
+            return Err("The first column of the measured data must contain dates".to_string());
+        }
+
+        let mut data = Vec::new();
+        for (ln, line) in lines {
+            let fields: Vec<&str> = line.split(',').map(|f| f.trim()).collect();
+            let value = match fields.get(i) {
+                Some(v) if !v.is_empty() => v
+                    .parse::<Float>()
+                    .map_err(|_| format!("Line {}: invalid value '{}'", ln, v))?,
+                _ => continue,
+            };
+            let date = Date::parse_iso8601(fields[0]).map_err(|e| format!("Line {}: {}", ln, e))?;
+            data.push((date, value));
+        }
+        Ok(Self::new(column, output, data))
+    }
+
+    /// Reads the measurements in a `column` of a CSV file. See
+    /// [`Measurement::from_csv`]
+    pub fn from_file(path: &str, column: &str, output: Output) -> Result<Self, String> {
+        let src = std::fs::read_to_string(path)
+            .map_err(|e| format!("Could not read measured data '{}': {}", path, e))?;
+        Self::from_csv(&src, column, output)
+    }
+
+    /// The name of the measurement
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The measured values that were simulated, along with the simulated
+    /// values at the same time, aggregated according to a [`Resolution`]
+    pub fn compare(
+        &self,
+        results: &SimulationResults,
+        resolution: Resolution,
+    ) -> Result<(Vec<Float>, Vec<Float>), String> {
+        let simulated = results.output(&self.output).ok_or_else(|| {
+            format!(
+                "The simulation has no results for measurement '{}'",
+                self.name
+            )
+        })?;
+        let simulated: HashMap<(u8, u8, i64), Float> = results
+            .dates()
+            .map(date_key)
+            .zip(simulated)
+            .collect();
+        let pairs: Vec<(Date, Float, Float)> = self
+            .data
+            .iter()
+            .filter_map(|(date, measured)| {
+                simulated
+                    .get(&date_key(*date))
+                    .map(|s| (*date, *measured, *s))
+            })
+            .collect();
+        Ok(aggregate(&pairs, resolution))
+    }
+}
+
+/// Separates some pairs of measured and simulated values (adding them up
+/// for each month, if the resolution is monthly)
+fn aggregate(pairs: &[(Date, Float, Float)], resolution: Resolution) -> (Vec<Float>, Vec<Float>) {
+    match resolution {
+        Resolution::Hourly => pairs.iter().map(|(_, m, s)| (*m, *s)).unzip(),
+        Resolution::Monthly => {
+            let mut months: Vec<(u8, Float, Float)> = Vec::new();
+            for (date, m, s) in pairs {
+                match months.last_mut() {
+                    Some((month, sum_m, sum_s)) if *month == date.month => {
+                        *sum_m += m;
+                        *sum_s += s;
+                    }
+                    _ => months.push((date.month, *m, *s)),
+                }
+            }
+            months.iter().map(|(_, m, s)| (*m, *s)).unzip()
+        }
+    }
+}
+
+/// How well some simulated values fit some measured ones, according
+/// to ASHRAE Guideline 14
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GoodnessOfFit {
+    /// The Normalized Mean Bias Error, in %. Positive values mean that
+    /// the simulation underestimates the measurements.
+    pub nmbe: Float,
+
+    /// The Coefficient of Variation of the Root Mean Squared Error, in %
+    pub cv_rmse: Float,
+
+    /// The number of values compared
+    pub n: usize,
+}
+
+impl GoodnessOfFit {
+    /// Compares some `simulated` values against the `measured` ones.
+    /// Following Guideline 14, both errors are divided by `n - 1`.
+    pub fn new(measured: &[Float], simulated: &[Float]) -> Result<Self, String> {
+        if measured.len() != simulated.len() {
+            return Err(format!(
+                "Cannot compare {} measured values against {} simulated ones",
+                measured.len(),
+                simulated.len()
+            ));
+        }
+        let n = measured.len();
+        if n < 2 {
+            return Err(format!(
+                "At least two values are needed to assess the goodness of fit... found {}",
+                n
+            ));
+        }
+        let mean = measured.iter().sum::<Float>() / n as Float;
+        if mean == 0. {
+            return Err("Cannot normalize errors by a mean measured value of zero".to_string());
+        }
+        let dof = (n - 1) as Float;
+        let bias: Float = measured.iter().zip(simulated).map(|(m, s)| m - s).sum();
+        let squares: Float = measured
+            .iter()
+            .zip(simulated)
+            .map(|(m, s)| (m - s).powi(2))
+            .sum();
+        Ok(Self {
+            nmbe: 100. * bias / (dof * mean),
+            cv_rmse: 100. * (squares / dof).sqrt() / mean,
+            n,
+        })
+    }
+
+    /// Whether the fit is within the limits of Guideline 14
+    pub fn is_calibrated(&self, resolution: Resolution) -> bool {
+        self.nmbe.abs() <= resolution.nmbe_limit() && self.cv_rmse <= resolution.cv_rmse_limit()
+    }
+}
+
+/// The statistic of a [`GoodnessOfFit`] used by a [`Fit`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Statistic {
+    /// The absolute value of the NMBE
+    Nmbe,
+
+    /// The CV(RMSE)
+    CvRmse,
+}
+
+/// An [`Objective`] measuring how well a simulation fits a [`Measurement`]
+#[derive(Debug, Clone)]
+pub struct Fit {
+    /// The measurement
+    pub measurement: Measurement,
+
+    /// The statistic used
+    pub statistic: Statistic,
+
+    /// The resolution at which values are compared
+    pub resolution: Resolution,
+}
+
+impl Objective for Fit {
+    fn name(&self) -> String {
+        let statistic = match self.statistic {
+            Statistic::Nmbe => "|NMBE|",
+            Statistic::CvRmse => "CV(RMSE)",
+        };
+        format!("{} of {} (%)", statistic, self.measurement.name)
+    }
+
+    fn evaluate(&self, results: &SimulationResults) -> Result<Float, String> {
+        let (measured, simulated) = self.measurement.compare(results, self.resolution)?;
+        let fit = GoodnessOfFit::new(&measured, &simulated)?;
+        Ok(match self.statistic {
+            Statistic::Nmbe => fit.nmbe.abs(),
+            Statistic::CvRmse => fit.cv_rmse,
+        })
+    }
+}
+
+/// The sum of the CV(RMSE) of several measurements
+struct TotalCvRmse(Vec<Fit>);
+
+impl Objective for TotalCvRmse {
+    fn name(&self) -> String {
+        "Total CV(RMSE) (%)".to_string()
+    }
+
+    fn evaluate(&self, results: &SimulationResults) -> Result<Float, String> {
+        self.0.iter().map(|f| f.evaluate(results)).sum()
+    }
+}
+
+/// Creates the [`Optimization`] that calibrates the parameters of a
+/// [`ParametricStudy`] against some measurements: it minimizes the sum of
+/// their CV(RMSE), subject to the NMBE and CV(RMSE) of each of them being
+/// within the limits of Guideline 14 for a certain [`Resolution`].
+pub fn calibration(
+    study: ParametricStudy,
+    measurements: Vec<Measurement>,
+    resolution: Resolution,
+) -> Result<Optimization, String> {
+    if measurements.is_empty() {
+        return Err("A calibration needs at least one measurement".to_string());
+    }
+    let fit = |measurement: &Measurement, statistic| Fit {
+        measurement: measurement.clone(),
+        statistic,
+        resolution,
+    };
+    let objective = TotalCvRmse(
+        measurements
+            .iter()
+            .map(|m| fit(m, Statistic::CvRmse))
+            .collect(),
+    );
+    let mut optimization = Optimization::new(study, objective);
+    for m in measurements.iter() {
+        optimization = optimization
+            .constraint(Constraint::at_most(
+                fit(m, Statistic::Nmbe),
+                resolution.nmbe_limit(),
+            ))
+            .constraint(Constraint::at_most(
+                fit(m, Statistic::CvRmse),
+                resolution.cv_rmse_limit(),
+            ));
+    }
+    Ok(optimization)
+}
+
+/// The name and value of each parameter of the best design found by a
+/// [`calibration`], if it satisfies the limits of Guideline 14
+pub fn calibrated_parameters(
+    study: &ParametricStudy,
+    results: &OptimizationResults,
+) -> Option<Vec<(String, Float)>> {
+    let best = results.best_feasible()?;
+    Some(
+        study
+            .parameters()
+            .iter()
+            .zip(best.parameters.iter())
+            .map(|(p, v)| (p.name().to_string(), *v))
+            .collect(),
+    )
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use validate::assert_close;
+
+    #[test]
+    fn test_goodness_of_fit() -> Result<(), String> {
+        let measured = [10., 12., 14., 12.];
+        let fit = GoodnessOfFit::new(&measured, &measured)?;
+        assert_close!(fit.nmbe, 0.);
+        assert_close!(fit.cv_rmse, 0.);
+        assert!(fit.is_calibrated(Resolution::Monthly));
+
+        // Underestimates everything by 1.2 (i.e., 10% of the mean)
+        let simulated: Vec<Float> = measured.iter().map(|m| m - 1.2).collect();
+        let fit = GoodnessOfFit::new(&measured, &simulated)?;
+        assert_close!(fit.nmbe, 100. * 4. * 1.2 / (3. * 12.));
+        assert_close!(fit.cv_rmse, 100. * (4. * 1.44 / 3. as Float).sqrt() / 12.);
+        assert!(!fit.is_calibrated(Resolution::Hourly));
+        assert_eq!(fit.n, 4);
+
+        assert!(GoodnessOfFit::new(&measured, &simulated[1..]).is_err());
+        assert!(GoodnessOfFit::new(&[1.], &[1.]).is_err());
+        assert!(GoodnessOfFit::new(&[1., -1.], &[1., 1.]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_csv() -> Result<(), String> {
+        let src = "Date,Bedroom temperature,Kitchen temperature
+2024-01-15 13:00,21.5,20.1
+2024-01-15 14:00,,20.3
+--01-15T15:00,22.0,
+";
+        let output = Output::SpaceDryBulbTemperature("Bedroom".into());
+        let m = Measurement::from_csv(src, "Bedroom temperature", output.clone())?;
+        assert_eq!(m.name(), "Bedroom temperature");
+        assert_eq!(m.data.len(), 2);
+        assert_eq!(m.data[1].0.hour, 15.);
+        assert_close!(m.data[1].1, 22.);
+
+        assert!(Measurement::from_csv(src, "Nothing", output.clone()).is_err());
+        assert!(Measurement::from_csv(src, "Date", output.clone()).is_err());
+        assert!(Measurement::from_csv("Date,T\n01/15,20", "T", output).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_date_key() {
+        let date = |month, day, hour| Date {
+            month,
+            day,
+            hour,
+            year: None,
+        };
+        assert_eq!(date_key(date(1, 15, 12.9999)), (1, 15, 13 * 60));
+        assert_eq!(
+            date_key(Date {
+                year: Some(2021),
+                ..date(1, 15, 13.)
+            }),
+            (1, 15, 13 * 60)
+        );
+        // The end of a day is the beginning of the next one
+        assert_eq!(date_key(date(1, 31, 23.999999)), (2, 1, 0));
+    }
+
+    #[test]
+    fn test_aggregate() {
+        let date = |month| Date {
+            month,
+            day: 1,
+            hour: 0.,
+            year: None,
+        };
+        let pairs = [(date(1), 1., 2.), (date(1), 3., 4.), (date(2), 5., 6.)];
+        let (m, s) = aggregate(&pairs, Resolution::Hourly);
+        assert_eq!(m, vec![1., 3., 5.]);
+        assert_eq!(s, vec![2., 4., 6.]);
+        let (m, s) = aggregate(&pairs, Resolution::Monthly);
+        assert_eq!(m, vec![4., 5.]);
+        assert_eq!(s, vec![6., 6.]);
+    }
+}
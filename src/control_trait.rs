@@ -20,8 +20,12 @@ SOFTWARE.
 
 use model::{Model, SimulationState};
 
-use crate::MultiphysicsModel;
+use crate::stepper::march;
+use crate::{Float, MultiphysicsModel};
+use calendar::Date;
+use communication::SimulationModel;
 use std::borrow::Borrow;
+use weather::{CurrentWeather, WeatherTrait};
 
 /// A trait that allows controling
 pub trait SimpleControl {
@@ -33,3 +37,192 @@ pub trait SimpleControl {
         state: &mut SimulationState,
     ) -> Result<(), String>;
 }
+
+/// The weather over the horizon of a [`PredictiveControl`], one entry
+/// per timestep. It never reveals the weather after the horizon: later
+/// dates get the weather of the last timestep.
+pub struct Forecast<'a, W: WeatherTrait> {
+    /// The actual weather
+    weather: &'a W,
+
+    /// The dates of the timesteps within the horizon
+    dates: Vec<Date>,
+}
+
+impl<'a, W: WeatherTrait> Forecast<'a, W> {
+    /// Creates a forecast of some `weather` over some `dates`, which
+    /// must be sorted and cannot be empty
+    pub fn new(weather: &'a W, dates: Vec<Date>) -> Self {
+        debug_assert!(!dates.is_empty(), "A forecast needs at least one date");
+        Self { weather, dates }
+    }
+
+    /// The dates of the timesteps within the horizon
+    pub fn dates(&self) -> &[Date] {
+        &self.dates
+    }
+
+    /// The weather at each timestep within the horizon
+    pub fn data(&self) -> Vec<CurrentWeather> {
+        self.dates
+            .iter()
+            .map(|d| self.get_weather_data(*d))
+            .collect()
+    }
+}
+
+impl<W: WeatherTrait> WeatherTrait for Forecast<'_, W> {
+    fn get_weather_data(&self, date: Date) -> CurrentWeather {
+        let last = self.dates[self.dates.len() - 1];
+        let date = if date > last { last } else { date };
+        CurrentWeather {
+            date,
+            ..self.weather.get_weather_data(date)
+        }
+    }
+
+    fn get_ground_temperature(&self, date: Date, depth: Float) -> Option<Float> {
+        let last = self.dates[self.dates.len() - 1];
+        let date = if date > last { last } else { date };
+        self.weather.get_ground_temperature(date, depth)
+    }
+}
+
+/// What a [`PredictiveControl`] knows when deciding the actuator values
+/// for the next timestep
+pub struct PredictionContext<'a, W: WeatherTrait> {
+    /// The date of the timestep about to be simulated
+    pub date: Date,
+
+    /// The duration of each timestep, in seconds
+    pub dt: Float,
+
+    /// The model being simulated
+    pub model: &'a Model,
+
+    /// The physics of the model
+    pub physics: &'a MultiphysicsModel,
+
+    /// A snapshot of the state before the timestep
+    pub state: &'a SimulationState,
+
+    /// The weather over the horizon of the controller, starting
+    /// at `date`
+    pub forecast: Forecast<'a, W>,
+}
+
+impl<W: WeatherTrait> PredictionContext<'_, W> {
+    /// Simulates the whole horizon from a copy of the current state,
+    /// without affecting the actual simulation. Before each timestep,
+    /// `actuate` is called with the position of the timestep within the
+    /// horizon, its date and the predicted state, so it can set the
+    /// actuator values being evaluated.
+    ///
+    /// Returns the predicted state after each timestep.
+    pub fn simulate<F>(&self, mut actuate: F) -> Result<Vec<SimulationState>, String>
+    where
+        F: FnMut(usize, Date, &mut SimulationState) -> Result<(), String>,
+    {
+        // All the internal state of the physics is in the SimulationState,
+        // so a copy of it is all we need
+        let mut state = self.state.clone();
+        let mut memory = self.physics.allocate_memory(&state)?;
+        let mut ret = Vec::with_capacity(self.forecast.dates.len());
+        for (i, date) in self.forecast.dates.iter().enumerate() {
+            actuate(i, *date, &mut state)?;
+            march(
+                self.model,
+                self.physics,
+                &self.forecast,
+                *date,
+                self.dt,
+                &mut state,
+                &mut memory,
+            )?;
+            ret.push(state.clone());
+        }
+        Ok(ret)
+    }
+}
+
+/// A controller that looks ahead—e.g., for Model Predictive Control—by
+/// simulating the model forward over a horizon before setting the
+/// actuators of the actual timestep.
+///
+/// Controllers are used through [`Stepper::step_with_controller`](crate::Stepper::step_with_controller).
+pub trait PredictiveControl {
+    /// The number of timesteps the controller looks ahead (including
+    /// the one about to be simulated)
+    fn horizon(&self) -> usize;
+
+    /// Sets the actuators in `state` for the timestep at `context.date`
+    fn control<W: WeatherTrait>(
+        &self,
+        context: &PredictionContext<W>,
+        state: &mut SimulationState,
+    ) -> Result<(), String>;
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use crate::run_simulation::load_weather;
+    use crate::Stepper;
+    use std::cell::RefCell;
+
+    /// Does not actuate anything, but records its predictions
+    struct Observer {
+        predictions: RefCell<Vec<Vec<SimulationState>>>,
+    }
+
+    impl PredictiveControl for Observer {
+        fn horizon(&self) -> usize {
+            3
+        }
+
+        fn control<W: WeatherTrait>(
+            &self,
+            context: &PredictionContext<W>,
+            _state: &mut SimulationState,
+        ) -> Result<(), String> {
+            assert_eq!(context.forecast.dates().len(), 3);
+            assert_eq!(context.forecast.dates()[0], context.date);
+
+            // The forecast is bounded by the horizon
+            let last = context.forecast.data()[2];
+            let mut later = context.date;
+            later.add_days(10);
+            let beyond = context.forecast.get_weather_data(later);
+            assert_eq!(beyond.dry_bulb_temperature, last.dry_bulb_temperature);
+
+            let before = context.state.clone();
+            let predicted = context.simulate(|_, _, _| Ok(()))?;
+            assert_eq!(predicted.len(), 3);
+            assert_eq!(&before, context.state);
+            self.predictions.borrow_mut().push(predicted);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_predictive_control() -> Result<(), String> {
+        let (model, header) = Model::from_file("./tests/box/box.spl")?;
+        let weather = load_weather("./tests/wellington.epw")?;
+        let mut stepper = Stepper::new(model, header, weather, 2)?;
+        let controller = Observer {
+            predictions: RefCell::new(Vec::new()),
+        };
+        for _ in 0..4 {
+            stepper.step_with_controller(&controller)?;
+            // The first predicted timestep is what actually happened
+            let predictions = controller.predictions.borrow();
+            let predicted = &predictions[predictions.len() - 1][0];
+            assert_eq!(predicted, stepper.state());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,225 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use model::{Luminaire, Model, SensorGrid, SimulationState};
+
+use crate::control_trait::SimpleControl;
+use crate::Float;
+use crate::MultiphysicsModel;
+use std::borrow::Borrow;
+use std::sync::Arc;
+
+/// The illuminance (in lux) that dimmed luminaires aim for when
+/// no `target_illuminance` is given
+const DEFAULT_TARGET_ILLUMINANCE: Float = 500.;
+
+/// The minimum fraction of their `max_power` that continuously dimmed
+/// luminaires consume while on, when no `min_power_fraction` is given
+const DEFAULT_MIN_POWER_FRACTION: Float = 0.1;
+
+/// A control routine that dims the [`Luminaire`]s with a `daylight_sensor`
+/// so that, together with the daylight, they reach their `target_illuminance`
+/// at the [`SensorGrid`] they refer to.
+///
+/// Luminaires with `dimming_steps` are dimmed to the lowest step that
+/// provides the missing illuminance; the others are dimmed continuously,
+/// but never below their `min_power_fraction` unless they are switched off.
+/// It is assumed that, at full power, a luminaire provides the `target_illuminance`.
+pub struct DaylightDimming {
+    /// The dimmed luminaires and the grids where their
+    /// sensors are located
+    luminaires: Vec<(Arc<Luminaire>, Arc<SensorGrid>)>,
+}
+
+impl DaylightDimming {
+    /// Creates a new `DaylightDimming` controller, checking that the
+    /// `daylight_sensor` of every luminaire exists in the model
+    pub fn new(model: &Model) -> Result<Self, String> {
+        let mut luminaires = Vec::new();
+        for lum in model.luminaires.iter() {
+            if let Ok(sensor) = lum.daylight_sensor() {
+                if let Ok(&0) = lum.dimming_steps() {
+                    return Err(format!("Luminaire '{}' has 0 dimming_steps", lum.name()));
+                }
+                let grid = model.get_sensor_grid(sensor)?;
+                luminaires.push((Arc::clone(lum), grid));
+            }
+        }
+        Ok(Self { luminaires })
+    }
+}
+
+/// Calculates the fraction of the `max_power` of a luminaire required
+/// for reaching the `target` illuminance when the daylight provides
+/// `illuminance`
+fn dimming_level(
+    illuminance: Float,
+    target: Float,
+    steps: Option<usize>,
+    min_power_fraction: Float,
+) -> Float {
+    let missing = ((target - illuminance) / target).clamp(0., 1.);
+    if missing <= 0. {
+        return 0.;
+    }
+    match steps {
+        Some(n) => (missing * n as Float).ceil() / n as Float,
+        None => missing.max(min_power_fraction),
+    }
+}
+
+impl SimpleControl for DaylightDimming {
+    fn control<M: Borrow<Model>>(
+        &self,
+        _model: M,
+        _physics_model: &MultiphysicsModel,
+        state: &mut SimulationState,
+    ) -> Result<(), String> {
+        for (lum, grid) in self.luminaires.iter() {
+            let illuminance = grid
+                .illuminance(state)
+                .ok_or("Could not get the illuminance of the Luminaire's daylight_sensor")?;
+            let level = dimming_level(
+                illuminance,
+                lum.target_illuminance_or("", DEFAULT_TARGET_ILLUMINANCE),
+                lum.dimming_steps().ok().copied(),
+                lum.min_power_fraction_or("", DEFAULT_MIN_POWER_FRACTION),
+            );
+            lum.set_dimming_level(state, level)?;
+            // Scheduled luminaires are multiplied by the dimming level
+            // when applying the schedules
+            if lum.schedule().is_err() {
+                lum.set_power_consumption(state, lum.max_power_or("", 0.) * level)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+
+    use communication::{MetaOptions, SimulationModel};
+    use geometry::{Loop3D, Point3D, Polygon3D};
+    use model::{SimulationStateElement, SimulationStateHeader};
+    use validate::assert_close;
+
+    use super::*;
+
+    #[test]
+    fn test_dimming_level() {
+        // Continuous
+        assert_close!(dimming_level(0., 500., None, 0.1), 1.);
+        assert_close!(dimming_level(250., 500., None, 0.1), 0.5);
+        assert_close!(dimming_level(480., 500., None, 0.1), 0.1);
+        assert_close!(dimming_level(600., 500., None, 0.1), 0.);
+
+        // Stepped
+        assert_close!(dimming_level(0., 500., Some(2), 0.1), 1.);
+        assert_close!(dimming_level(200., 500., Some(2), 0.1), 1.);
+        assert_close!(dimming_level(300., 500., Some(2), 0.1), 0.5);
+        assert_close!(dimming_level(500., 500., Some(2), 0.1), 0.);
+    }
+
+    #[test]
+    fn test_control() -> Result<(), String> {
+        let mut model = Model::default();
+
+        let mut the_loop = Loop3D::new();
+        the_loop.push(Point3D::new(0., 0., 0.))?;
+        the_loop.push(Point3D::new(4., 0., 0.))?;
+        the_loop.push(Point3D::new(4., 3., 0.))?;
+        the_loop.push(Point3D::new(0., 3., 0.))?;
+        the_loop.close()?;
+        let mut grid = SensorGrid::new("Desk");
+        grid.set_vertices(Polygon3D::new(the_loop)?);
+        let grid = model.add_sensor_grid(grid)?;
+
+        let mut lum = Luminaire::new("Dimmed");
+        lum.set_max_power(100.)
+            .set_daylight_sensor("Desk")
+            .set_target_illuminance(400.);
+        let dimmed = model.add_luminaire(lum)?;
+
+        let mut lum = Luminaire::new("Stepped");
+        lum.set_max_power(100.)
+            .set_daylight_sensor("Desk")
+            .set_target_illuminance(400.)
+            .set_dimming_steps(4);
+        let stepped = model.add_luminaire(lum)?;
+
+        let mut lum = Luminaire::new("Not dimmed");
+        lum.set_max_power(100.);
+        let not_dimmed = model.add_luminaire(lum)?;
+        assert!(not_dimmed.dimming_level_index().is_none());
+
+        // The illuminance is usually calculated by the light module
+        let mut state_header = model.take_state().ok_or("Could not take state")?;
+        let i = state_header.push(SimulationStateElement::SensorGridIlluminance(0), 0.)?;
+        grid.set_illuminance_index(i)?;
+
+        // Create a model... we don't use it, but we need it as an input.
+        let meta_option = MetaOptions::default();
+        let physics_model = MultiphysicsModel::new(
+            &meta_option,
+            (),
+            &Model::default(),
+            &mut SimulationStateHeader::new(),
+            1,
+        )?;
+
+        let mut state = state_header.take_values().ok_or("Could not take state")?;
+        let controller = DaylightDimming::new(&model)?;
+
+        // Some daylight
+        grid.set_illuminance(&mut state, 100.)?;
+        controller.control(&model, &physics_model, &mut state)?;
+        assert_close!(dimmed.dimming_level(&state).unwrap(), 0.75);
+        assert_close!(dimmed.power_consumption(&state).unwrap(), 75.);
+        assert_close!(stepped.power_consumption(&state).unwrap(), 75.);
+
+        grid.set_illuminance(&mut state, 250.)?;
+        controller.control(&model, &physics_model, &mut state)?;
+        assert_close!(dimmed.power_consumption(&state).unwrap(), 37.5);
+        assert_close!(stepped.power_consumption(&state).unwrap(), 50.);
+
+        // Plenty of daylight
+        grid.set_illuminance(&mut state, 1000.)?;
+        controller.control(&model, &physics_model, &mut state)?;
+        assert_close!(dimmed.power_consumption(&state).unwrap(), 0.);
+        assert_close!(stepped.power_consumption(&state).unwrap(), 0.);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_sensor() -> Result<(), String> {
+        let mut model = Model::default();
+        let mut lum = Luminaire::new("Dimmed");
+        lum.set_max_power(100.).set_daylight_sensor("Nowhere");
+        model.add_luminaire(lum)?;
+        assert!(DaylightDimming::new(&model).is_err());
+        Ok(())
+    }
+}
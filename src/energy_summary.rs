@@ -0,0 +1,387 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::Float;
+use calendar::Date;
+use model::{Model, SimulationState, SimulationStateElement, SimulationStateHeader, HVAC};
+use serde::Serialize;
+
+/// The name of the months, for the reports
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// The name given to the energy consumed by objects that
+/// do not serve any space (e.g., exterior luminaires)
+const UNASSIGNED: &str = "(no space)";
+
+/// The energy consumed by each end use, in $`kWh`$
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct EndUseEnergy {
+    /// The energy delivered by the heating systems
+    pub heating: Float,
+
+    /// The energy removed by the cooling systems
+    pub cooling: Float,
+
+    /// The energy consumed by the luminaires
+    pub lighting: Float,
+
+    /// The energy consumed by the electric equipment
+    pub equipment: Float,
+}
+
+impl EndUseEnergy {
+    /// The sum of all the end uses
+    pub fn total(&self) -> Float {
+        self.heating + self.cooling + self.lighting + self.equipment
+    }
+
+    /// Adds up two `EndUseEnergy`
+    fn add(&self, other: &Self) -> Self {
+        Self {
+            heating: self.heating + other.heating,
+            cooling: self.cooling + other.cooling,
+            lighting: self.lighting + other.lighting,
+            equipment: self.equipment + other.equipment,
+        }
+    }
+
+    /// Divides all the end uses by an area, if it is larger than zero
+    fn per_area(&self, area: Float) -> Option<Self> {
+        if area > 0. {
+            Some(Self {
+                heating: self.heating / area,
+                cooling: self.cooling / area,
+                lighting: self.lighting / area,
+                equipment: self.equipment / area,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+/// The energy consumption of a [`Space`](model::Space) (including all
+/// of its copies, if it has a multiplier) throughout a simulation
+#[derive(Debug, Clone, Serialize)]
+pub struct SpaceEnergy {
+    /// The name of the space
+    pub name: String,
+
+    /// The floor area of the space—including all its copies—in $`m^2`$
+    pub floor_area: Float,
+
+    /// The energy consumed in each month of the year
+    pub monthly: [EndUseEnergy; 12],
+}
+
+impl SpaceEnergy {
+    /// The energy consumed in the whole simulation
+    pub fn total(&self) -> EndUseEnergy {
+        self.monthly
+            .iter()
+            .fold(EndUseEnergy::default(), |acc, m| acc.add(m))
+    }
+}
+
+/// The end use in which a [`SimulationStateElement`] is accounted for
+#[derive(Debug, Clone, Copy)]
+enum EndUse {
+    /// Heating or cooling, depending on the sign of the power
+    HeatingCooling,
+    /// Lighting
+    Lighting,
+    /// Equipment
+    Equipment,
+}
+
+/// Accumulates the heating, cooling, lighting and equipment energy
+/// consumed by each space of a [`Model`] in each month, so that users
+/// do not need to integrate the power columns of the results themselves.
+///
+/// The heating and cooling energy is the one delivered to (or removed from)
+/// the spaces by the HVAC systems, which are considered to be heating when
+/// their power is positive and cooling when it is negative.
+#[derive(Debug, Clone)]
+pub struct EnergySummary {
+    /// The spaces in the model, plus an extra one for the objects
+    /// that do not serve any space (if any)
+    pub spaces: Vec<SpaceEnergy>,
+
+    /// The elements of the state to integrate: their index in the
+    /// [`SimulationState`], their end use, the space they serve and their
+    /// multiplier
+    elements: Vec<(usize, EndUse, usize, Float)>,
+}
+
+impl EnergySummary {
+    /// Creates a new `EnergySummary`, finding the power consumption of the
+    /// luminaires, electric equipment and HVAC systems in the
+    /// [`SimulationStateHeader`]
+    pub fn new(model: &Model, state_header: &SimulationStateHeader) -> Self {
+        let (_total, floor_areas) = model.get_space_sizes();
+        let mut spaces: Vec<SpaceEnergy> = model
+            .spaces
+            .iter()
+            .map(|s| SpaceEnergy {
+                name: s.name().clone(),
+                floor_area: floor_areas.get(s.name()).copied().unwrap_or(0.)
+                    * model.space_multiplier(s.name()),
+                monthly: [EndUseEnergy::default(); 12],
+            })
+            .collect();
+
+        let mut elements = Vec::new();
+        for (state_index, element) in state_header.elements.iter().enumerate() {
+            let (end_use, target_space) = match element {
+                SimulationStateElement::HeatingCoolingPowerConsumption(i) => {
+                    let target = match &model.hvacs[*i] {
+                        HVAC::IdealHeaterCooler(h) => h.target_space(),
+                        HVAC::ElectricHeater(h) => h.target_space(),
+                        HVAC::HeatPump(h) => h.target_space(),
+                        HVAC::RadiantSurfaceSystem(h) => h.target_space(),
+                        HVAC::HeatRecoveryVentilator(h) => h.target_space(),
+                    };
+                    (EndUse::HeatingCooling, target.ok())
+                }
+                SimulationStateElement::LuminairePowerConsumption(i) => {
+                    (EndUse::Lighting, model.luminaires[*i].target_space().ok())
+                }
+                SimulationStateElement::ElectricEquipmentPowerConsumption(i) => (
+                    EndUse::Equipment,
+                    model.electric_equipment[*i].target_space().ok(),
+                ),
+                _ => continue,
+            };
+            let space_index = match target_space
+                .and_then(|name| model.spaces.iter().position(|s| s.name() == name))
+            {
+                Some(i) => i,
+                None => {
+                    if spaces.len() == model.spaces.len() {
+                        spaces.push(SpaceEnergy {
+                            name: UNASSIGNED.to_string(),
+                            floor_area: 0.,
+                            monthly: [EndUseEnergy::default(); 12],
+                        });
+                    }
+                    model.spaces.len()
+                }
+            };
+            let multiplier = model.state_element_multiplier(element);
+            elements.push((state_index, end_use, space_index, multiplier));
+        }
+
+        Self { spaces, elements }
+    }
+
+    /// Accounts for a timestep of `dt` seconds, ending at `date`
+    pub fn record(&mut self, date: Date, dt: Float, state: &SimulationState) {
+        // W during dt seconds, into kWh
+        let factor = dt / 3600. / 1000.;
+        let month = (date.month as usize).clamp(1, 12) - 1;
+        for (state_index, end_use, space_index, multiplier) in self.elements.iter() {
+            let energy = state[*state_index] * multiplier * factor;
+            let m = &mut self.spaces[*space_index].monthly[month];
+            match end_use {
+                EndUse::HeatingCooling if energy >= 0. => m.heating += energy,
+                EndUse::HeatingCooling => m.cooling -= energy,
+                EndUse::Lighting => m.lighting += energy,
+                EndUse::Equipment => m.equipment += energy,
+            }
+        }
+    }
+
+    /// The floor area of all the spaces, in $`m^2`$
+    pub fn floor_area(&self) -> Float {
+        self.spaces.iter().map(|s| s.floor_area).sum()
+    }
+
+    /// The energy consumed by all the spaces in each month
+    pub fn monthly(&self) -> [EndUseEnergy; 12] {
+        let mut ret = [EndUseEnergy::default(); 12];
+        for s in self.spaces.iter() {
+            for (r, m) in ret.iter_mut().zip(s.monthly.iter()) {
+                *r = r.add(m);
+            }
+        }
+        ret
+    }
+
+    /// The energy consumed by all the spaces in the whole simulation
+    pub fn total(&self) -> EndUseEnergy {
+        self.spaces
+            .iter()
+            .fold(EndUseEnergy::default(), |acc, s| acc.add(&s.total()))
+    }
+
+    /// Writes the summary in JSON format. Energy is given in $`kWh`$
+    /// and intensities in $`kWh/m^2`$.
+    pub fn write_json<T: std::io::Write>(&self, out: T) -> Result<(), String> {
+        #[derive(Serialize)]
+        struct Totals<'a> {
+            name: &'a str,
+            floor_area: Float,
+            monthly: [EndUseEnergy; 12],
+            total: EndUseEnergy,
+            total_per_area: Option<EndUseEnergy>,
+        }
+
+        #[derive(Serialize)]
+        struct Report<'a> {
+            building: Totals<'a>,
+            spaces: Vec<Totals<'a>>,
+        }
+
+        let totals = |name, floor_area, monthly, total: EndUseEnergy| Totals {
+            name,
+            floor_area,
+            monthly,
+            total,
+            total_per_area: total.per_area(floor_area),
+        };
+        let report = Report {
+            building: totals("Building", self.floor_area(), self.monthly(), self.total()),
+            spaces: self
+                .spaces
+                .iter()
+                .map(|s| totals(&s.name, s.floor_area, s.monthly, s.total()))
+                .collect(),
+        };
+        serde_json::to_writer_pretty(out, &report).map_err(|e| e.to_string())
+    }
+
+    /// Writes the summary as an HTML page, with a table per space
+    pub fn write_html<T: std::io::Write>(&self, mut out: T) -> std::io::Result<()> {
+        fn table<T: std::io::Write>(
+            out: &mut T,
+            name: &str,
+            floor_area: Float,
+            monthly: &[EndUseEnergy; 12],
+            total: EndUseEnergy,
+        ) -> std::io::Result<()> {
+            let row = |label: &str, e: &EndUseEnergy, decimals: usize| {
+                format!(
+                    "<tr><td>{}</td><td>{:.*}</td><td>{:.*}</td><td>{:.*}</td><td>{:.*}</td><td>{:.*}</td></tr>",
+                    label,
+                    decimals,
+                    e.heating,
+                    decimals,
+                    e.cooling,
+                    decimals,
+                    e.lighting,
+                    decimals,
+                    e.equipment,
+                    decimals,
+                    e.total()
+                )
+            };
+            writeln!(out, "<h2>{} ({:.1} m&sup2;)</h2>", name, floor_area)?;
+            writeln!(out, "<table>")?;
+            writeln!(out, "<tr><th>kWh</th><th>Heating</th><th>Cooling</th><th>Lighting</th><th>Equipment</th><th>Total</th></tr>")?;
+            for (month, e) in MONTHS.iter().zip(monthly.iter()) {
+                writeln!(out, "{}", row(month, e, 1))?;
+            }
+            writeln!(out, "{}", row("<b>Total</b>", &total, 1))?;
+            if let Some(e) = total.per_area(floor_area) {
+                writeln!(out, "{}", row("<b>kWh/m&sup2;</b>", &e, 2))?;
+            }
+            writeln!(out, "</table>")
+        }
+
+        writeln!(out, "<!DOCTYPE html>")?;
+        writeln!(
+            out,
+            "<html><head><meta charset=\"utf-8\"><title>Energy summary</title>"
+        )?;
+        writeln!(out, "<style>table {{ border-collapse: collapse; }} td, th {{ border: 1px solid #ccc; padding: 4px 8px; text-align: right; }}</style>")?;
+        writeln!(out, "</head><body>")?;
+        writeln!(out, "<h1>Energy summary</h1>")?;
+        table(
+            &mut out,
+            "Building",
+            self.floor_area(),
+            &self.monthly(),
+            self.total(),
+        )?;
+        for s in self.spaces.iter() {
+            table(&mut out, &s.name, s.floor_area, &s.monthly, s.total())?;
+        }
+        writeln!(out, "</body></html>")
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use model::{ElectricEquipment, Luminaire, Space};
+    use validate::assert_close;
+
+    #[test]
+    fn test_summary() -> Result<(), String> {
+        let mut model = Model::default();
+        model.add_space(Space::new("Kitchen"));
+        let mut luminaire = Luminaire::new("Light");
+        luminaire.set_target_space("Kitchen");
+        let luminaire = model.add_luminaire(luminaire)?;
+        let fridge = model.add_electric_equipment(ElectricEquipment::new("Fridge"))?;
+        let header = model.take_state().ok_or("No state")?;
+
+        let mut summary = EnergySummary::new(&model, &header);
+        assert_eq!(summary.spaces.len(), 2); // Kitchen and unassigned
+
+        let mut state = vec![0.; header.elements.len()];
+        luminaire.set_power_consumption(&mut state, 100.)?;
+        fridge.set_power_consumption(&mut state, 50.)?;
+
+        // Ten hours in January, and one in February
+        let mut date = Date {
+            month: 1,
+            day: 31,
+            hour: 14.,
+            year: None,
+        };
+        for _ in 0..11 {
+            summary.record(date, 3600., &state);
+            date.add_hours(1.);
+        }
+
+        assert_close!(summary.spaces[0].monthly[0].lighting, 1.);
+        assert_close!(summary.spaces[0].monthly[1].lighting, 0.1);
+        assert_close!(summary.spaces[1].monthly[0].equipment, 0.5);
+        assert_close!(summary.total().total(), 1.65);
+
+        let mut json = Vec::new();
+        summary.write_json(&mut json)?;
+        let json = String::from_utf8(json).map_err(|e| e.to_string())?;
+        assert!(json.contains("\"Kitchen\""));
+
+        let mut html = Vec::new();
+        summary.write_html(&mut html).map_err(|e| e.to_string())?;
+        let html = String::from_utf8(html).map_err(|e| e.to_string())?;
+        assert!(html.contains("<h2>Kitchen"));
+        Ok(())
+    }
+}
@@ -58,9 +58,64 @@ pub use rhai_script_controller::RhaiControlScript;
 pub mod occupant_behaviour;
 pub use occupant_behaviour::OccupantBehaviour;
 
+/// A default control routine that dims luminaires according
+/// to the available daylight
+pub mod daylight_dimming;
+pub use daylight_dimming::DaylightDimming;
+
 /// A module with some useful functions to run a simulation
 pub mod run_simulation;
 
+/// Reporting the progress of a simulation, and cancelling it
+pub mod progress;
+pub use progress::{CancellationToken, ProgressReporter, SimulationProgress};
+
+/// Summarizing and checking a model without simulating it
+pub mod model_summary;
+pub use model_summary::ModelSummary;
+
+/// Monthly heating, cooling, lighting and equipment energy per space
+pub mod energy_summary;
+pub use energy_summary::EnergySummary;
+
+/// Peak heating and cooling loads per space, for sizing HVAC systems
+pub mod peak_loads;
+pub use peak_loads::PeakLoads;
+
+/// Writing the [`Report`]s requested in a model, aggregated over time
+pub mod reporting;
+pub use reporting::ReportWriter;
+
+/// Recording time series of the simulation state in memory
+pub mod recorder;
+pub use recorder::Recorder;
+
+/// The results of a simulation, queried by what they represent
+pub mod simulation_results;
+pub use simulation_results::SimulationResults;
+
+/// Simulating several variants of a model and collecting their results
+pub mod parametrics;
+pub use parametrics::ParametricStudy;
+
+/// Sampling the parameters of a model and analysing their influence
+pub mod sensitivity;
+
+/// Searching for the variant of a model that best meets some objectives
+pub mod optimization;
+
+/// Calibrating a model against measured data
+pub mod calibration;
+
+/// Simulating a model one timestep at a time
+pub mod stepper;
+pub use stepper::Stepper;
+
+/// Publishing the state and receiving commands over MQTT, for
+/// hardware-in-the-loop experiments
+#[cfg(feature = "mqtt")]
+pub mod telemetry;
+
 // Re-exports
 pub use calendar::{Date, Period};
 pub use communication::{MetaOptions, SimulationModel};
@@ -76,7 +131,8 @@ pub use rendering::{
     rand::{get_rng, Rng},
 };
 
-pub use schedule::*;
+pub use schedule;
+pub use schedule::{EmptySchedule, ScheduleConstant};
 pub use weather::{
     CurrentWeather, EPWWeather, EPWWeatherLine, Location, PerezSky, SkyUnits, Solar,
     SyntheticWeather, Time, Weather, WeatherTrait,
@@ -0,0 +1,316 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::Float;
+use model::{Boundary, FenestrationType, Model, Orientation, SurfaceTrait, HVAC};
+
+/// The orientations in which the glazing ratios are reported
+const ORIENTATIONS: [Orientation; 8] = [
+    Orientation::North,
+    Orientation::NorthEast,
+    Orientation::East,
+    Orientation::SouthEast,
+    Orientation::South,
+    Orientation::SouthWest,
+    Orientation::West,
+    Orientation::NorthWest,
+];
+
+/// The summary of a single [`Space`](model::Space)
+#[derive(Debug, Clone)]
+pub struct SpaceSummary {
+    /// The name of the space
+    pub name: String,
+
+    /// The volume of the space, if given, in $`m^3`$
+    pub volume: Option<Float>,
+
+    /// The floor area of a single copy of the space, in $`m^2`$
+    pub floor_area: Float,
+
+    /// The number of copies of the space
+    pub multiplier: Float,
+}
+
+/// The summary of the façade of the model facing an [`Orientation`]
+#[derive(Debug, Clone)]
+pub struct GlazingSummary {
+    /// The orientation of the façade
+    pub orientation: Orientation,
+
+    /// The gross area (i.e., including the windows) of the
+    /// exterior walls, in $`m^2`$
+    pub wall_area: Float,
+
+    /// The area of the exterior windows, in $`m^2`$
+    pub window_area: Float,
+}
+
+impl GlazingSummary {
+    /// The window-to-wall ratio of the façade
+    pub fn ratio(&self) -> Float {
+        if self.wall_area > 0. {
+            self.window_area / self.wall_area
+        } else {
+            0.
+        }
+    }
+}
+
+/// A human-readable summary of a [`Model`]—its spaces, the U-values of its
+/// constructions, its glazing ratios and its HVAC systems—that can be produced
+/// without running a simulation.
+///
+/// ```
+/// use simple::model_summary::ModelSummary;
+/// use simple::Model;
+///
+/// let (model, _header) = Model::from_file("./tests/box/box.spl").unwrap();
+/// let summary = ModelSummary::new(&model).unwrap();
+/// assert_eq!(summary.spaces.len(), 1);
+/// println!("{}", summary);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ModelSummary {
+    /// The name of the model
+    pub name: Option<String>,
+
+    /// The spaces in the model
+    pub spaces: Vec<SpaceSummary>,
+
+    /// The total floor area of the model, accounting for the
+    /// multiplier of the spaces, in $`m^2`$
+    pub total_floor_area: Float,
+
+    /// The name of each [`Construction`](model::Construction) and its
+    /// U-value, in $`W/m^2K`$
+    pub constructions: Vec<(String, Float)>,
+
+    /// The glazing on each orientation that has exterior walls or windows
+    pub glazing: Vec<GlazingSummary>,
+
+    /// The name, kind and target space of each [`HVAC`]
+    pub hvacs: Vec<(String, &'static str, String)>,
+}
+
+impl ModelSummary {
+    /// Summarizes a [`Model`], returning an error if it contains
+    /// broken references (e.g., to materials that do not exist)
+    pub fn new(model: &Model) -> Result<Self, String> {
+        let (total_floor_area, floor_areas) = model.get_space_sizes();
+        let spaces = model
+            .spaces
+            .iter()
+            .map(|s| SpaceSummary {
+                name: s.name().clone(),
+                volume: s.volume,
+                floor_area: floor_areas.get(s.name()).copied().unwrap_or(0.),
+                multiplier: model.space_multiplier(s.name()),
+            })
+            .collect();
+
+        let constructions = model
+            .constructions
+            .iter()
+            .map(|c| Ok((c.name.clone(), c.u_value(model)?)))
+            .collect::<Result<Vec<_>, String>>()?;
+
+        // Windows are holes in their parent walls, so they are added
+        // to the wall area as well
+        let mut glazing: Vec<GlazingSummary> = ORIENTATIONS
+            .iter()
+            .map(|o| GlazingSummary {
+                orientation: *o,
+                wall_area: 0.,
+                window_area: 0.,
+            })
+            .collect();
+        let mut add = |orientation: Option<Orientation>, area: Float, is_window: bool| {
+            if let Some(g) = glazing
+                .iter_mut()
+                .find(|g| Some(g.orientation) == orientation)
+            {
+                g.wall_area += area;
+                if is_window {
+                    g.window_area += area;
+                }
+            }
+        };
+        for s in model.surfaces.iter() {
+            add(s.outside_orientation(), s.area(), false);
+        }
+        for f in model.fenestrations.iter() {
+            let is_window = f.category == FenestrationType::Window;
+            add(f.outside_orientation(), f.area(), is_window);
+        }
+        glazing.retain(|g| g.wall_area > 0.);
+
+        let hvacs = model
+            .hvacs
+            .iter()
+            .map(|hvac| {
+                let (kind, target) = match hvac {
+                    HVAC::IdealHeaterCooler(h) => ("Ideal Heater/Cooler", h.target_space()),
+                    HVAC::ElectricHeater(h) => ("Electric Heater", h.target_space()),
+                    HVAC::HeatPump(h) => ("Heat Pump", h.target_space()),
+                    HVAC::RadiantSurfaceSystem(h) => ("Radiant Surface System", h.target_space()),
+                    HVAC::HeatRecoveryVentilator(h) => {
+                        ("Heat Recovery Ventilator", h.target_space())
+                    }
+                };
+                let target = target.cloned().unwrap_or_else(|_| "-".to_string());
+                (hvac.name().clone(), kind, target)
+            })
+            .collect();
+
+        Ok(Self {
+            name: model.name.clone(),
+            spaces,
+            total_floor_area,
+            constructions,
+            glazing,
+            hvacs,
+        })
+    }
+}
+
+impl std::fmt::Display for ModelSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(name) = &self.name {
+            writeln!(f, "Model '{}'", name)?;
+        }
+
+        writeln!(f, "Spaces ({}):", self.spaces.len())?;
+        for s in self.spaces.iter() {
+            let volume = match s.volume {
+                Some(v) => format!("{:.1} m3", v),
+                None => "unknown volume".to_string(),
+            };
+            write!(f, "  - {}: {:.1} m2, {}", s.name, s.floor_area, volume)?;
+            if s.multiplier != 1. {
+                write!(f, " (x{})", s.multiplier)?;
+            }
+            writeln!(f)?;
+        }
+        writeln!(f, "Total floor area: {:.1} m2", self.total_floor_area)?;
+
+        writeln!(f, "Constructions ({}):", self.constructions.len())?;
+        for (name, u) in self.constructions.iter() {
+            writeln!(f, "  - {}: U = {:.3} W/m2K", name, u)?;
+        }
+
+        writeln!(f, "Glazing ratios:")?;
+        for g in self.glazing.iter() {
+            writeln!(
+                f,
+                "  - {}: {:.1}% ({:.1} m2 of windows in {:.1} m2 of walls)",
+                g.orientation,
+                100. * g.ratio(),
+                g.window_area,
+                g.wall_area
+            )?;
+        }
+
+        writeln!(f, "HVAC systems ({}):", self.hvacs.len())?;
+        for (name, kind, target) in self.hvacs.iter() {
+            writeln!(f, "  - {} ({}), serving '{}'", name, kind, target)?;
+        }
+        Ok(())
+    }
+}
+
+/// Checks that a [`Boundary`] leads to a space that exists in the model
+fn check_boundary(model: &Model, owner: &str, boundary: &Boundary) -> Result<(), String> {
+    if let Boundary::Space { space } = boundary {
+        if model.get_space(space).is_err() {
+            return Err(format!(
+                "'{}' leads to space '{}', which does not exist",
+                owner, space
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Checks the references in a [`Model`] that are not verified when
+/// reading it—i.e., the constructions and boundaries of surfaces and
+/// fenestrations—returning all the problems found.
+pub fn lint(model: &Model) -> Vec<String> {
+    let mut problems = Vec::new();
+    for s in model.surfaces.iter() {
+        if let Err(e) = model.get_construction(&s.construction) {
+            problems.push(format!("Surface '{}': {}", s.name, e));
+        }
+        for b in [&s.front_boundary, &s.back_boundary] {
+            if let Err(e) = check_boundary(model, &s.name, b) {
+                problems.push(format!("Surface {}", e));
+            }
+        }
+    }
+    for s in model.fenestrations.iter() {
+        if let Err(e) = model.get_construction(&s.construction) {
+            problems.push(format!("Fenestration '{}': {}", s.name, e));
+        }
+        for b in [&s.front_boundary, &s.back_boundary] {
+            if let Err(e) = check_boundary(model, &s.name, b) {
+                problems.push(format!("Fenestration {}", e));
+            }
+        }
+    }
+    problems
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use model::Construction;
+    use validate::assert_close;
+
+    #[test]
+    fn test_box() -> Result<(), String> {
+        let (model, _header) = Model::from_file("./tests/box/box.spl")?;
+        let summary = ModelSummary::new(&model)?;
+        assert_eq!(summary.spaces.len(), 1);
+        assert!(summary.total_floor_area > 0.);
+        assert_eq!(summary.constructions.len(), model.constructions.len());
+        for g in summary.glazing.iter() {
+            assert!(g.ratio() >= 0. && g.ratio() <= 1.);
+        }
+        assert!(lint(&model).is_empty());
+
+        let s = format!("{}", summary);
+        assert!(s.contains("Spaces (1)"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_u_value() -> Result<(), String> {
+        let (model, _header) = Model::from_file("./tests/box/box.spl")?;
+        let mut c = Construction::new("Single layer");
+        c.materials.push("R13LAYER".into());
+        let u = c.u_value(&model)?;
+        assert_close!(1. / u, model::R_SI + model::R_SE + 2.290965, 1e-3);
+        Ok(())
+    }
+}
@@ -34,10 +34,14 @@ use model::{Model, SimulationState, SimulationStateHeader, SolarOptions};
 
 use air::air_model::{AirFlowModel, AirFlowModelMemory};
 use calendar::Date;
-use communication::{ErrorHandling, MetaOptions, SimulationModel};
+use comfort::comfort_model::{ComfortModel, ComfortModelMemory};
+use communication::{ErrorHandling, MetaOptions, SimulationModel, SubStepping};
+use electrical::electrical_model::{ElectricalModel, ElectricalModelMemory};
 use heat::heat_model::{ThermalModel, ThermalModelMemory};
 use light::solar_model::{SolarModel, SolarModelMemory};
+use serde::{Deserialize, Serialize};
 use std::borrow::Borrow;
+use std::path::Path;
 use weather::WeatherTrait;
 // use acoustic_model::model::AcousticModel;
 
@@ -47,6 +51,35 @@ pub struct MultiphysicsModelMemory {
     thermal: ThermalModelMemory,
     solar: SolarModelMemory,
     air: AirFlowModelMemory,
+    electrical: ElectricalModelMemory,
+    comfort: ComfortModelMemory,
+
+    /// The number of timesteps marched so far, used for
+    /// synchronizing the modules
+    timestep: usize,
+}
+
+/// A snapshot of a simulation at a certain timestep, from which
+/// it can be resumed (see [`MultiphysicsModel::save_checkpoint`]).
+///
+/// The internal state of all the modules is contained in the
+/// [`SimulationState`] (e.g., the temperatures of the nodes within
+/// the walls), and their memory is allocated from it, so this is
+/// all that is needed for restarting a simulation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Checkpoint {
+    /// The date of the last simulated timestep
+    pub date: Date,
+
+    /// The number of timesteps per hour of the simulation
+    pub dt_subdivisions: usize,
+
+    /// The name of each element in the `state`, used for checking
+    /// that the checkpoint belongs to the same model
+    pub header: Vec<String>,
+
+    /// The values of the [`SimulationState`]
+    pub state: SimulationState,
 }
 
 /// The structure that connects all the SIMPLE simulation modules.
@@ -64,7 +97,15 @@ pub struct MultiphysicsModel {
     thermal_model: ThermalModel,
     air_flow_model: AirFlowModel,
     solar_model: SolarModel,
+    electrical_model: ElectricalModel,
+    comfort_model: ComfortModel,
     // acoustic_model: AcousticModel,
+    /// How the solar and electrical models are synchronized
+    /// with the main timestep
+    solar_stepping: SubStepping,
+
+    /// How the air flow model is synchronized with the main timestep
+    air_flow_stepping: SubStepping,
 }
 
 impl ErrorHandling for MultiphysicsModel {
@@ -84,11 +125,18 @@ impl SimulationModel for MultiphysicsModel {
         let solar = self.solar_model.allocate_memory(state)?;
         #[allow(clippy::let_unit_value)]
         let air = self.air_flow_model.allocate_memory(state)?;
+        #[allow(clippy::let_unit_value)]
+        let electrical = self.electrical_model.allocate_memory(state)?;
+        #[allow(clippy::let_unit_value)]
+        let comfort = self.comfort_model.allocate_memory(state)?;
 
         let ret = MultiphysicsModelMemory {
             thermal,
             solar,
             air,
+            electrical,
+            comfort,
+            timestep: 0,
         };
 
         Ok(ret)
@@ -102,16 +150,43 @@ impl SimulationModel for MultiphysicsModel {
         state: &mut SimulationStateHeader,
         n: usize,
     ) -> Result<Self::OutputType, String> {
-        let thermal_model = match ThermalModel::new(meta_options, (), model.borrow(), state, n) {
+        // The comfort model pushes operational elements (i.e., the clothing and
+        // metabolic rate of the occupants), so it needs to go first.
+        let comfort_model = match ComfortModel::new(meta_options, (), model.borrow(), state, n) {
+            Ok(v) => v,
+            Err(e) => return MultiphysicsModel::user_error(e),
+        };
+
+        // Negotiate the timestep of each module
+        let (solar_n, air_flow_n) = match &model.borrow().timestep_options {
+            Some(options) => (
+                options.solar_timesteps_per_hour().ok().copied(),
+                options.air_flow_timesteps_per_hour().ok().copied(),
+            ),
+            None => (None, None),
+        };
+        let solar_stepping = match SubStepping::negotiate(n, solar_n.unwrap_or(n)) {
+            Ok(v) => v,
+            Err(e) => return MultiphysicsModel::user_error(e),
+        };
+        let air_flow_stepping = match SubStepping::negotiate(n, air_flow_n.unwrap_or(n)) {
             Ok(v) => v,
             Err(e) => return MultiphysicsModel::user_error(e),
         };
+        let solar_n = solar_stepping.timesteps_per_hour(n);
+        let air_flow_n = air_flow_stepping.timesteps_per_hour(n);
 
-        let air_flow_model = match AirFlowModel::new(meta_options, (), model.borrow(), state, n) {
+        let thermal_model = match ThermalModel::new(meta_options, (), model.borrow(), state, n) {
             Ok(v) => v,
             Err(e) => return MultiphysicsModel::user_error(e),
         };
 
+        let air_flow_model =
+            match AirFlowModel::new(meta_options, (), model.borrow(), state, air_flow_n) {
+                Ok(v) => v,
+                Err(e) => return MultiphysicsModel::user_error(e),
+            };
+
         // let acoustic_model = match AcousticModel::new(building, state, n){
         //     Ok(v)=>v,
         //     Err(e)=>return MultiphysicsModel::user_error(e),
@@ -131,7 +206,13 @@ impl SimulationModel for MultiphysicsModel {
         };
 
         let solar_model =
-            match SolarModel::new(meta_options, solar_options, model.borrow(), state, n) {
+            match SolarModel::new(meta_options, solar_options, model.borrow(), state, solar_n) {
+                Ok(v) => v,
+                Err(e) => return MultiphysicsModel::user_error(e),
+            };
+
+        let electrical_model =
+            match ElectricalModel::new(meta_options, (), model.borrow(), state, solar_n) {
                 Ok(v) => v,
                 Err(e) => return MultiphysicsModel::user_error(e),
             };
@@ -141,6 +222,10 @@ impl SimulationModel for MultiphysicsModel {
             // acoustic_model,
             solar_model,
             air_flow_model,
+            electrical_model,
+            comfort_model,
+            solar_stepping,
+            air_flow_stepping,
 
             dt_subdivisions: n,
             dt: 60. * 60. / n as Float,
@@ -155,30 +240,144 @@ impl SimulationModel for MultiphysicsModel {
         state: &mut SimulationState,
         alloc: &mut MultiphysicsModelMemory,
     ) -> Result<(), String> {
-        // First solar,
-        self.solar_model
-            .march(date, weather, model.borrow(), state, &mut alloc.solar)?;
+        let timestep = alloc.timestep;
+        alloc.timestep = alloc.timestep.wrapping_add(1);
+
+        // First solar, and then the electricity generated with the sun
+        self.sub_step(self.solar_stepping, date, timestep, |date| {
+            self.solar_model
+                .march(date, weather, model.borrow(), state, &mut alloc.solar)?;
+            self.electrical_model
+                .march(date, weather, model.borrow(), state, &mut alloc.electrical)
+        })?;
 
         // Then noise
         // self.acoustic_model.march(date, weather, building, state)?;
 
         // Then air flow
-        self.air_flow_model
-            .march(date, weather, model.borrow(), state, &mut alloc.air)?;
+        self.sub_step(self.air_flow_stepping, date, timestep, |date| {
+            self.air_flow_model
+                .march(date, weather, model.borrow(), state, &mut alloc.air)
+        })?;
 
         // Then temperature
         self.thermal_model
             .march(date, weather, model.borrow(), state, &mut alloc.thermal)?;
 
+        // And finally, how people feel about it
+        self.comfort_model
+            .march(date, weather, model.borrow(), state, &mut alloc.comfort)?;
+
         Ok(())
     }
 }
 
 impl MultiphysicsModel {
+    /// Calls `march` as many times as a module synchronized according to
+    /// `stepping` needs within the main timestep number `timestep` (which
+    /// starts at `date`): several times, at the dates of its substeps; or
+    /// once every few timesteps, at the middle of those timesteps.
+    fn sub_step<F>(
+        &self,
+        stepping: SubStepping,
+        date: Date,
+        timestep: usize,
+        mut march: F,
+    ) -> Result<(), String>
+    where
+        F: FnMut(Date) -> Result<(), String>,
+    {
+        match stepping {
+            SubStepping::Substeps(k) => {
+                for i in 0..k {
+                    let mut d = date;
+                    d.add_seconds(i as Float * self.dt / k as Float);
+                    march(d)?;
+                }
+                Ok(())
+            }
+            SubStepping::Every(k) => {
+                if timestep % k != 0 {
+                    return Ok(());
+                }
+                let mut d = date;
+                d.add_seconds((k - 1) as Float * self.dt / 2.);
+                march(d)
+            }
+        }
+    }
+
     /// Retrieves the thermal model
     pub fn thermal_model(&self) -> &ThermalModel {
         &self.thermal_model
     }
+
+    /// Writes a [`Checkpoint`] in JSON format, containing the `state` of
+    /// the simulation after simulating `date`.
+    pub fn save_checkpoint<P: AsRef<Path>>(
+        &self,
+        path: P,
+        date: Date,
+        model: &Model,
+        state_header: &SimulationStateHeader,
+        state: &SimulationState,
+    ) -> Result<(), String> {
+        let checkpoint = Checkpoint {
+            date,
+            dt_subdivisions: self.dt_subdivisions,
+            header: state_header
+                .elements
+                .iter()
+                .map(|e| e.stringify(model))
+                .collect(),
+            state: state.clone(),
+        };
+        let json = serde_json::to_string(&checkpoint).map_err(|e| e.to_string())?;
+        std::fs::write(path.as_ref(), json).map_err(|e| {
+            format!(
+                "Could not write checkpoint '{}': {}",
+                path.as_ref().display(),
+                e
+            )
+        })
+    }
+
+    /// Reads a [`Checkpoint`] written by [`MultiphysicsModel::save_checkpoint`],
+    /// returning the date of its last simulated timestep and the [`SimulationState`]
+    /// from which the simulation should continue.
+    ///
+    /// Returns an error if the checkpoint was saved from a different model
+    /// or with a different timestep.
+    pub fn resume<P: AsRef<Path>>(
+        &self,
+        path: P,
+        model: &Model,
+        state_header: &SimulationStateHeader,
+    ) -> Result<(Date, SimulationState), String> {
+        let json = std::fs::read_to_string(path.as_ref()).map_err(|e| {
+            format!(
+                "Could not read checkpoint '{}': {}",
+                path.as_ref().display(),
+                e
+            )
+        })?;
+        let checkpoint: Checkpoint = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+        if checkpoint.dt_subdivisions != self.dt_subdivisions {
+            return Err(format!(
+                "Checkpoint was saved with {} timesteps per hour, but the simulation has {}",
+                checkpoint.dt_subdivisions, self.dt_subdivisions
+            ));
+        }
+        let header: Vec<String> = state_header
+            .elements
+            .iter()
+            .map(|e| e.stringify(model))
+            .collect();
+        if checkpoint.header != header || checkpoint.state.len() != header.len() {
+            return Err("Checkpoint does not correspond to this model".to_string());
+        }
+        Ok((checkpoint.date, checkpoint.state))
+    }
 }
 
 /***********/
@@ -186,4 +385,84 @@ impl MultiphysicsModel {
 /***********/
 
 #[cfg(test)]
-mod testing {}
+mod testing {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint() -> Result<(), String> {
+        let meta_options = MetaOptions::default();
+        let (model, mut header) = Model::from_file("./tests/box/box.spl")?;
+        let physics = MultiphysicsModel::new(&meta_options, (), &model, &mut header, 2)?;
+        let mut state = header.take_values().ok_or("No values")?;
+        state
+            .iter_mut()
+            .enumerate()
+            .for_each(|(i, v)| *v = i as Float);
+
+        let date = Date {
+            month: 3,
+            day: 4,
+            hour: 12.5,
+            year: None,
+        };
+        let path = std::env::temp_dir().join("simple_test_checkpoint.json");
+        physics.save_checkpoint(&path, date, &model, &header, &state)?;
+        let (found_date, found_state) = physics.resume(&path, &model, &header)?;
+        assert_eq!(found_date, date);
+        assert_eq!(found_state, state);
+
+        // Different timestep
+        let (model, mut other_header) = Model::from_file("./tests/box/box.spl")?;
+        let other = MultiphysicsModel::new(&meta_options, (), &model, &mut other_header, 4)?;
+        assert!(other.resume(&path, &model, &other_header).is_err());
+
+        // Different model
+        let mut short_header = header.clone();
+        short_header.elements.pop();
+        assert!(physics.resume(&path, &model, &short_header).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sub_stepping() -> Result<(), String> {
+        use crate::run_simulation::load_weather;
+        use model::TimestepOptions;
+
+        let meta_options = MetaOptions::default();
+        let weather = load_weather("./tests/wellington.epw")?;
+
+        // Cannot be synchronized
+        let (mut model, mut header) = Model::from_file("./tests/box/box.spl")?;
+        let mut options = TimestepOptions::new();
+        options.set_solar_timesteps_per_hour(3);
+        model.timestep_options = Some(options);
+        assert!(MultiphysicsModel::new(&meta_options, (), &model, &mut header, 4).is_err());
+
+        // Hourly sun, and air flows every minute
+        let (mut model, mut header) = Model::from_file("./tests/box/box.spl")?;
+        let mut options = TimestepOptions::new();
+        options
+            .set_solar_timesteps_per_hour(1)
+            .set_air_flow_timesteps_per_hour(60);
+        model.timestep_options = Some(options);
+        let physics = MultiphysicsModel::new(&meta_options, (), &model, &mut header, 4)?;
+        assert_eq!(physics.solar_stepping, SubStepping::Every(4));
+        assert_eq!(physics.air_flow_stepping, SubStepping::Substeps(15));
+
+        let mut state = header.take_values().ok_or("No values")?;
+        let mut memory = physics.allocate_memory(&state)?;
+        let mut date = Date {
+            month: 1,
+            day: 1,
+            hour: 0.,
+            year: None,
+        };
+        for _ in 0..8 {
+            physics.march(date, &weather, &model, &mut state, &mut memory)?;
+            date.add_seconds(physics.dt);
+        }
+        assert_eq!(memory.timestep, 8);
+        assert!(state.iter().all(|v| v.is_finite()));
+        Ok(())
+    }
+}
@@ -18,9 +18,11 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 
+use air::humidity::{relative_humidity, STANDARD_PRESSURE};
 use model::{Model, SimulationState, HVAC};
 
 use crate::control_trait::SimpleControl;
+use crate::Float;
 use crate::MultiphysicsModel;
 use std::borrow::Borrow;
 
@@ -30,6 +32,7 @@ use std::borrow::Borrow;
 /// For now it:
 ///
 /// * Turns the heating/cooling systems in each zone depending on the tempreature of the space where its thermostat is located.
+/// * Turns the humidifying/dehumidifying of the [`IdealHeaterCooler`](model::hvac::IdealHeaterCooler)s on and off depending on the relative humidity of their target space.
 pub struct OccupantBehaviour {}
 
 impl OccupantBehaviour {
@@ -39,6 +42,29 @@ impl OccupantBehaviour {
     }
 }
 
+/// Calculates the power of an On/Off system that heats (positive) whenever
+/// the temperature is below the heating setpoint and cools (negative) whenever
+/// it is over the cooling setpoint. Each tuple contains a setpoint and the
+/// corresponding max power.
+fn on_off_power(
+    space_temp: Float,
+    heating: (Result<&Float, String>, Result<&Float, String>),
+    cooling: (Result<&Float, String>, Result<&Float, String>),
+) -> Float {
+    let mut power = 0.0;
+    if let (Ok(setpoint), Ok(max_heating)) = heating {
+        if space_temp < *setpoint {
+            power = *max_heating;
+        }
+    }
+    if let (Ok(setpoint), Ok(max_cooling)) = cooling {
+        if space_temp > *setpoint {
+            power = -*max_cooling;
+        }
+    }
+    power
+}
+
 impl SimpleControl for OccupantBehaviour {
     fn control<M: Borrow<Model>>(
         &self,
@@ -99,8 +125,65 @@ impl SimpleControl for OccupantBehaviour {
                                 hvac.set_heating_cooling_consumption(state, 0.0)?;
                             }
                         }
+
+                        // Deal with humidity
+                        if hvac.controls_humidity() {
+                            if let Some(w) = space.humidity_ratio(state) {
+                                let rh = relative_humidity(space_temp, w, STANDARD_PRESSURE);
+                                let mut latent = 0.0;
+                                if let (Ok(setpoint), Ok(power)) =
+                                    (hvac.humidifying_setpoint(), hvac.max_humidifying_power())
+                                {
+                                    if rh < *setpoint {
+                                        latent = *power;
+                                    }
+                                }
+                                if let (Ok(setpoint), Ok(power)) = (
+                                    hvac.dehumidifying_setpoint(),
+                                    hvac.max_dehumidifying_power(),
+                                ) {
+                                    if rh > *setpoint {
+                                        latent = -*power;
+                                    }
+                                }
+                                hvac.set_latent_heating_cooling_consumption(state, latent)?;
+                            }
+                        }
+                    }
+                }
+                HVAC::HeatPump(hvac) => {
+                    if let Ok(space_name) = hvac.target_space() {
+                        let space: std::sync::Arc<model::Space> =
+                            model.borrow().get_space(space_name)?;
+                        let space_temp = space
+                            .dry_bulb_temperature(state)
+                            .ok_or("Could not get HeatPump's target_space temperature ")?;
+
+                        let power = on_off_power(
+                            space_temp,
+                            (hvac.heating_setpoint(), hvac.max_heating_power()),
+                            (hvac.cooling_setpoint(), hvac.max_cooling_power()),
+                        );
+                        hvac.set_heating_cooling_consumption(state, power)?;
+                    }
+                }
+                HVAC::RadiantSurfaceSystem(hvac) => {
+                    if let Ok(space_name) = hvac.target_space() {
+                        let space: std::sync::Arc<model::Space> =
+                            model.borrow().get_space(space_name)?;
+                        let space_temp = space.dry_bulb_temperature(state).ok_or(
+                            "Could not get RadiantSurfaceSystem's target_space temperature ",
+                        )?;
+                        let power = on_off_power(
+                            space_temp,
+                            (hvac.heating_setpoint(), hvac.max_heating_power()),
+                            (hvac.cooling_setpoint(), hvac.max_cooling_power()),
+                        );
+                        hvac.set_heating_cooling_consumption(state, power)?;
                     }
                 }
+                // Controlled through its schedule or the API
+                HVAC::HeatRecoveryVentilator(_) => {}
             }
         }
         Ok(())
@@ -416,4 +499,85 @@ mod testing {
 
         Ok(())
     }
+
+    #[test]
+    fn test_control_ideal_humidity() -> Result<(), String> {
+        let mut model = Model::default();
+
+        // add a space
+        let mut space = Space::new("The space");
+        space.set_volume(212.0);
+        let space = model.add_space(space);
+
+        // Add an ideal system that only controls humidity
+        let humidifying_setpoint = 0.3;
+        let dehumidifying_setpoint = 0.6;
+        let max_humidifying_power = 500.0;
+        let max_dehumidifying_power = 800.0;
+        let mut heater = IdealHeaterCooler::new("Ideal loads");
+        heater
+            .set_humidifying_setpoint(humidifying_setpoint)
+            .set_dehumidifying_setpoint(dehumidifying_setpoint)
+            .set_max_humidifying_power(max_humidifying_power)
+            .set_max_dehumidifying_power(max_dehumidifying_power)
+            .set_target_space(space.name().clone());
+        let hvac = model.add_hvac(heater.wrap())?;
+
+        // Get state
+        let mut state_header = model.take_state().ok_or("Could not take state")?;
+        let meta_option = MetaOptions::default();
+        let physics_model = MultiphysicsModel::new(&meta_option, (), &model, &mut state_header, 1)?;
+        let mut state = state_header.take_values().ok_or("Could not take state")?;
+        let controller = OccupantBehaviour::new(&model)?;
+
+        let heater = match &hvac {
+            HVAC::IdealHeaterCooler(heater) => heater,
+            _ => panic!("Expecting an IdealHeaterCooler"),
+        };
+        let temp = 20.0;
+        space.set_dry_bulb_temperature(&mut state, temp)?;
+
+        // Test 1: too dry
+        let w = air::humidity::humidity_ratio(temp, 0.2, STANDARD_PRESSURE);
+        space.set_humidity_ratio(&mut state, w)?;
+        controller.control(&model, &physics_model, &mut state)?;
+        assert_close!(
+            max_humidifying_power,
+            heater
+                .latent_heating_cooling_consumption(&state)
+                .ok_or("Could not get latent consumption")?
+        );
+
+        // Test 2: comfortable
+        let w = air::humidity::humidity_ratio(temp, 0.45, STANDARD_PRESSURE);
+        space.set_humidity_ratio(&mut state, w)?;
+        controller.control(&model, &physics_model, &mut state)?;
+        assert_close!(
+            0.0,
+            heater
+                .latent_heating_cooling_consumption(&state)
+                .ok_or("Could not get latent consumption")?
+        );
+
+        // Test 3: too humid
+        let w = air::humidity::humidity_ratio(temp, 0.8, STANDARD_PRESSURE);
+        space.set_humidity_ratio(&mut state, w)?;
+        controller.control(&model, &physics_model, &mut state)?;
+        assert_close!(
+            -max_dehumidifying_power,
+            heater
+                .latent_heating_cooling_consumption(&state)
+                .ok_or("Could not get latent consumption")?
+        );
+
+        // Sensible heating/cooling was never touched
+        assert_close!(
+            0.0,
+            heater
+                .heating_cooling_consumption(&state)
+                .ok_or("Could not get heating/cooling consumption")?
+        );
+
+        Ok(())
+    }
 }
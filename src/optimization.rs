@@ -0,0 +1,574 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Design optimization: searching for the variant of a model that
+//! minimizes an [`Objective`] while satisfying some [`Constraint`]s.
+//!
+//! The design space is given by the [`Parameter`]s of a [`ParametricStudy`],
+//! and each design is a choice of one of the values of each of them. The
+//! designs to be simulated are proposed by an [`Optimizer`], such as a
+//! [`RandomSearch`] or a [`GeneticAlgorithm`].
+//!
+//! ```no_run
+//! use simple::optimization::{Constraint, GeneticAlgorithm, Optimization};
+//! use simple::parametrics::{Metric, Parameter, ParametricStudy};
+//! use simple::run_simulation::SimOptions;
+//! use simple::Model;
+//!
+//! let (base, _) = Model::from_file("./tests/box/box.spl").unwrap();
+//! let study = ParametricStudy::new(&base)
+//!     .unwrap()
+//!     .parameter(Parameter::material_thickness("R13LAYER", vec![0.05, 0.1, 0.15, 0.2]))
+//!     .parameter(Parameter::orientation(vec![0., 45., 90., 135., 180.]));
+//!
+//! let optimization = Optimization::new(study, Metric::hvac_energy("Heater"))
+//!     .constraint(Constraint::at_most(Metric::hours_above("Bedroom", 26.), 100.));
+//!
+//! let options = SimOptions {
+//!     weather_file: Some("./tests/wellington.epw".into()),
+//!     quiet: true,
+//!     ..SimOptions::default()
+//! };
+//! let mut optimizer = GeneticAlgorithm::new(8, 5, 0);
+//! let results = optimization.run(&mut optimizer, &options).unwrap();
+//! println!("{:?}", results.best().map(|e| &e.parameters));
+//! ```
+
+use crate::parametrics::{Metric, ParametricStudy};
+use crate::run_simulation::{run_collect, SimOptions};
+use crate::simulation_results::SimulationResults;
+use crate::{Float, VoidControl};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use rendering::rand::{RandGen, Rng, SeedableRng};
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// A value calculated out of the results of a simulation, to be minimized
+/// (or bounded, when used in a [`Constraint`])
+pub trait Objective: Send + Sync {
+    /// The name of the objective
+    fn name(&self) -> String;
+
+    /// Calculates the objective out of the results of a simulation
+    fn evaluate(&self, results: &SimulationResults) -> Result<Float, String>;
+}
+
+impl Objective for Metric {
+    fn name(&self) -> String {
+        Metric::name(self).to_string()
+    }
+
+    fn evaluate(&self, results: &SimulationResults) -> Result<Float, String> {
+        Metric::evaluate(self, results)
+    }
+}
+
+/// The limit imposed by a [`Constraint`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Bound {
+    /// The value cannot be larger than this
+    AtMost(Float),
+
+    /// The value cannot be smaller than this
+    AtLeast(Float),
+}
+
+/// A limit to the value of an [`Objective`] that feasible designs must respect
+pub struct Constraint {
+    /// The constrained value
+    objective: Box<dyn Objective>,
+
+    /// The limit
+    bound: Bound,
+}
+
+impl Constraint {
+    /// The `objective` cannot be larger than `max`
+    pub fn at_most<O: Objective + 'static>(objective: O, max: Float) -> Self {
+        Self {
+            objective: Box::new(objective),
+            bound: Bound::AtMost(max),
+        }
+    }
+
+    /// The `objective` cannot be smaller than `min`
+    pub fn at_least<O: Objective + 'static>(objective: O, min: Float) -> Self {
+        Self {
+            objective: Box::new(objective),
+            bound: Bound::AtLeast(min),
+        }
+    }
+
+    /// By how much a `value` of the constrained objective exceeds the
+    /// limit (i.e., zero if the constraint is satisfied)
+    pub fn violation(&self, value: Float) -> Float {
+        match self.bound {
+            Bound::AtMost(max) => (value - max).max(0.),
+            Bound::AtLeast(min) => (min - value).max(0.),
+        }
+    }
+}
+
+/// The results of simulating a design
+#[derive(Debug, Clone, PartialEq)]
+pub struct Evaluation {
+    /// The index of the value chosen for each parameter
+    pub design: Vec<usize>,
+
+    /// The value of each parameter
+    pub parameters: Vec<Float>,
+
+    /// The value of the objective
+    pub objective: Float,
+
+    /// The value of each of the constrained objectives
+    pub constraints: Vec<Float>,
+
+    /// The sum of the violations of all the constraints
+    pub violation: Float,
+}
+
+impl Evaluation {
+    /// Whether the design satisfies all the constraints
+    pub fn is_feasible(&self) -> bool {
+        self.violation <= 0.
+    }
+
+    /// Ranks two evaluations: feasible designs come before infeasible ones,
+    /// which are ranked by how much they violate the constraints. Feasible
+    /// designs are ranked by their objective.
+    pub fn compare(&self, other: &Self) -> Ordering {
+        self.violation
+            .total_cmp(&other.violation)
+            .then(self.objective.total_cmp(&other.objective))
+    }
+}
+
+/// Proposes the designs to be evaluated by an [`Optimization`]
+pub trait Optimizer {
+    /// Proposes the next designs to evaluate—as the index of the value
+    /// chosen for each parameter—given the number of values of each
+    /// parameter and the evaluations of all the designs proposed so far, in
+    /// the order in which they were proposed. Proposing no designs ends
+    /// the optimization.
+    fn propose(&mut self, dimensions: &[usize], history: &[Evaluation]) -> Vec<Vec<usize>>;
+}
+
+/// A random design
+fn random_design(rng: &mut RandGen, dimensions: &[usize]) -> Vec<usize> {
+    dimensions.iter().map(|n| rng.gen_range(0..*n)).collect()
+}
+
+/// Evaluates a number of random designs, all at once
+pub struct RandomSearch {
+    /// The number of designs to evaluate
+    samples: usize,
+
+    /// The random number generator
+    rng: RandGen,
+}
+
+impl RandomSearch {
+    /// Creates a search over a number of random `samples`, reproducible
+    /// through a `seed`
+    pub fn new(samples: usize, seed: u64) -> Self {
+        Self {
+            samples,
+            rng: RandGen::seed_from_u64(seed),
+        }
+    }
+}
+
+impl Optimizer for RandomSearch {
+    fn propose(&mut self, dimensions: &[usize], history: &[Evaluation]) -> Vec<Vec<usize>> {
+        (history.len()..self.samples)
+            .map(|_| random_design(&mut self.rng, dimensions))
+            .collect()
+    }
+}
+
+/// A basic genetic algorithm. Each generation is made of the best design
+/// of the previous one, plus children of parents chosen by tournament
+/// (between two random designs), through uniform crossover and mutation.
+pub struct GeneticAlgorithm {
+    /// The number of designs in each generation
+    population: usize,
+
+    /// The number of generations
+    generations: usize,
+
+    /// The probability of each parameter of a child being changed at random
+    mutation_rate: Float,
+
+    /// The number of generations proposed so far
+    generation: usize,
+
+    /// The random number generator
+    rng: RandGen,
+}
+
+impl GeneticAlgorithm {
+    /// Creates a genetic algorithm that evolves a `population` over a number
+    /// of `generations`, reproducible through a `seed`. The mutation rate
+    /// is 0.1 by default.
+    pub fn new(population: usize, generations: usize, seed: u64) -> Self {
+        Self {
+            population,
+            generations,
+            mutation_rate: 0.1,
+            generation: 0,
+            rng: RandGen::seed_from_u64(seed),
+        }
+    }
+
+    /// Sets the probability of each parameter of a child being
+    /// changed at random
+    pub fn mutation_rate(mut self, rate: Float) -> Self {
+        self.mutation_rate = rate;
+        self
+    }
+
+    /// Chooses the better of two random designs of the previous generation
+    fn tournament<'a>(&mut self, parents: &'a [Evaluation]) -> &'a Evaluation {
+        let a = &parents[self.rng.gen_range(0..parents.len())];
+        let b = &parents[self.rng.gen_range(0..parents.len())];
+        match a.compare(b) {
+            Ordering::Greater => b,
+            _ => a,
+        }
+    }
+}
+
+impl Optimizer for GeneticAlgorithm {
+    fn propose(&mut self, dimensions: &[usize], history: &[Evaluation]) -> Vec<Vec<usize>> {
+        if self.generation >= self.generations || self.population == 0 {
+            return Vec::new();
+        }
+        self.generation += 1;
+
+        let parents = &history[history.len().saturating_sub(self.population)..];
+        if parents.is_empty() {
+            return (0..self.population)
+                .map(|_| random_design(&mut self.rng, dimensions))
+                .collect();
+        }
+
+        // Elitism
+        let best = parents
+            .iter()
+            .min_by(|a, b| a.compare(b))
+            .expect("There should be parents");
+        let mut children = vec![best.design.clone()];
+        while children.len() < self.population {
+            let a = self.tournament(parents);
+            let b = self.tournament(parents);
+            let child = a
+                .design
+                .iter()
+                .zip(b.design.iter())
+                .zip(dimensions.iter())
+                .map(|((a, b), n)| {
+                    if self.rng.gen::<Float>() < self.mutation_rate {
+                        self.rng.gen_range(0..*n)
+                    } else if self.rng.gen::<bool>() {
+                        *a
+                    } else {
+                        *b
+                    }
+                })
+                .collect();
+            children.push(child);
+        }
+        children
+    }
+}
+
+/// Asks an `optimizer` for designs until it proposes none, evaluating
+/// each of the new ones—in parallel, if the `parallel` feature is
+/// enabled—through `evaluate`. Designs that were already evaluated are
+/// not evaluated again. Returns the history of the optimization.
+fn drive<P, F>(
+    optimizer: &mut P,
+    dimensions: &[usize],
+    evaluate: F,
+) -> Result<Vec<Evaluation>, String>
+where
+    P: Optimizer + ?Sized,
+    F: Fn(&[usize]) -> Result<Evaluation, String> + Sync,
+{
+    if dimensions.contains(&0) {
+        return Err("Every parameter of an optimization needs at least one value".to_string());
+    }
+    let mut history: Vec<Evaluation> = Vec::new();
+    let mut evaluated: HashMap<Vec<usize>, Evaluation> = HashMap::new();
+    loop {
+        let designs = optimizer.propose(dimensions, &history);
+        if designs.is_empty() {
+            return Ok(history);
+        }
+        if let Some(d) = designs
+            .iter()
+            .find(|d| d.len() != dimensions.len() || d.iter().zip(dimensions).any(|(i, n)| i >= n))
+        {
+            return Err(format!("The optimizer proposed an invalid design {:?}", d));
+        }
+
+        let mut new: Vec<&Vec<usize>> = designs
+            .iter()
+            .filter(|d| !evaluated.contains_key(*d))
+            .collect();
+        new.sort();
+        new.dedup();
+
+        #[cfg(feature = "parallel")]
+        let iter = new.into_par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let iter = new.into_iter();
+
+        let evaluations = iter
+            .map(|d| evaluate(d))
+            .collect::<Result<Vec<Evaluation>, String>>()?;
+        for e in evaluations {
+            evaluated.insert(e.design.clone(), e);
+        }
+        history.extend(designs.iter().map(|d| evaluated[d].clone()));
+    }
+}
+
+/// The search for the design that minimizes an [`Objective`]
+/// subject to some [`Constraint`]s
+pub struct Optimization {
+    /// The model and the parameters that define the design space
+    study: ParametricStudy,
+
+    /// The value to minimize
+    objective: Box<dyn Objective>,
+
+    /// The constraints that feasible designs must satisfy
+    constraints: Vec<Constraint>,
+}
+
+impl Optimization {
+    /// Creates an optimization over the designs given by the parameters
+    /// of a [`ParametricStudy`] (whose metrics are ignored)
+    pub fn new<O: Objective + 'static>(study: ParametricStudy, objective: O) -> Self {
+        Self {
+            study,
+            objective: Box::new(objective),
+            constraints: Vec::new(),
+        }
+    }
+
+    /// Adds a constraint
+    pub fn constraint(mut self, constraint: Constraint) -> Self {
+        self.constraints.push(constraint);
+        self
+    }
+
+    /// The number of values of each parameter
+    pub fn dimensions(&self) -> Vec<usize> {
+        self.study
+            .parameters()
+            .iter()
+            .map(|p| p.values().len())
+            .collect()
+    }
+
+    /// Simulates a design with some [`SimOptions`] (of which the
+    /// `input_file` is ignored), calculating its objective and constraints
+    pub fn evaluate(&self, design: &[usize], options: &SimOptions) -> Result<Evaluation, String> {
+        let parameters = self
+            .study
+            .parameters()
+            .iter()
+            .zip(design.iter())
+            .map(|(p, i)| {
+                p.values()
+                    .get(*i)
+                    .copied()
+                    .ok_or_else(|| format!("Parameter '{}' has no value {}", p.name(), i))
+            })
+            .collect::<Result<Vec<Float>, String>>()?;
+        let (model, mut header) = self.study.variant(&parameters)?;
+        let results = run_collect(&model, &mut header, options, VoidControl {})?;
+
+        let objective = self.objective.evaluate(&results)?;
+        let constraints = self
+            .constraints
+            .iter()
+            .map(|c| c.objective.evaluate(&results))
+            .collect::<Result<Vec<Float>, String>>()?;
+        let violation = self
+            .constraints
+            .iter()
+            .zip(constraints.iter())
+            .map(|(c, v)| c.violation(*v))
+            .sum();
+        Ok(Evaluation {
+            design: design.to_vec(),
+            parameters,
+            objective,
+            constraints,
+            violation,
+        })
+    }
+
+    /// Runs the optimization, simulating the designs proposed by an
+    /// [`Optimizer`] with some [`SimOptions`] (of which the `input_file`
+    /// is ignored)
+    pub fn run<P: Optimizer + ?Sized>(
+        &self,
+        optimizer: &mut P,
+        options: &SimOptions,
+    ) -> Result<OptimizationResults, String> {
+        let history = drive(optimizer, &self.dimensions(), |design| {
+            self.evaluate(design, options)
+                .map_err(|e| format!("Design {:?} failed: {}", design, e))
+        })?;
+        Ok(OptimizationResults {
+            objective: self.objective.name(),
+            constraints: self
+                .constraints
+                .iter()
+                .map(|c| c.objective.name())
+                .collect(),
+            history,
+        })
+    }
+}
+
+/// The designs evaluated during an [`Optimization`]
+#[derive(Debug, Clone)]
+pub struct OptimizationResults {
+    /// The name of the objective
+    pub objective: String,
+
+    /// The name of the constrained objectives
+    pub constraints: Vec<String>,
+
+    /// The evaluation of each of the designs proposed, in the order in
+    /// which they were proposed (designs proposed more than once appear
+    /// more than once)
+    pub history: Vec<Evaluation>,
+}
+
+impl OptimizationResults {
+    /// The best design found, ranked as in [`Evaluation::compare`]
+    pub fn best(&self) -> Option<&Evaluation> {
+        self.history.iter().min_by(|a, b| a.compare(b))
+    }
+
+    /// The best design found that satisfies all the constraints, if any
+    pub fn best_feasible(&self) -> Option<&Evaluation> {
+        self.best().filter(|e| e.is_feasible())
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    /// The objective is the distance to (3, 7); the sum of both
+    /// indexes cannot be larger than 8
+    fn toy(design: &[usize]) -> Result<Evaluation, String> {
+        let objective = (design[0] as Float - 3.).abs() + (design[1] as Float - 7.).abs();
+        let sum = (design[0] + design[1]) as Float;
+        Ok(Evaluation {
+            design: design.to_vec(),
+            parameters: design.iter().map(|i| *i as Float).collect(),
+            objective,
+            constraints: vec![sum],
+            violation: Constraint::at_most(Metric::new("sum", |_| Ok(0.)), 8.).violation(sum),
+        })
+    }
+
+    #[test]
+    fn test_constraint() {
+        let c = Constraint::at_most(Metric::new("x", |_| Ok(0.)), 2.);
+        assert_eq!(c.violation(1.), 0.);
+        assert_eq!(c.violation(3.), 1.);
+        let c = Constraint::at_least(Metric::new("x", |_| Ok(0.)), 2.);
+        assert_eq!(c.violation(3.), 0.);
+        assert_eq!(c.violation(0.5), 1.5);
+    }
+
+    #[test]
+    fn test_compare() -> Result<(), String> {
+        let a = toy(&[3, 5])?; // feasible, objective 2
+        let b = toy(&[3, 6])?; // infeasible, objective 1
+        let c = toy(&[2, 5])?; // feasible, objective 3
+        assert!(a.is_feasible() && !b.is_feasible());
+        assert_eq!(a.compare(&b), Ordering::Less);
+        assert_eq!(a.compare(&c), Ordering::Less);
+        assert_eq!(b.compare(&c), Ordering::Greater);
+        Ok(())
+    }
+
+    #[test]
+    fn test_random_search() -> Result<(), String> {
+        let mut optimizer = RandomSearch::new(20, 1);
+        let history = drive(&mut optimizer, &[10, 10], toy)?;
+        assert_eq!(history.len(), 20);
+        assert!(history.iter().all(|e| e.design[0] < 10 && e.design[1] < 10));
+        Ok(())
+    }
+
+    #[test]
+    fn test_genetic_algorithm() -> Result<(), String> {
+        let mut optimizer = GeneticAlgorithm::new(10, 30, 2).mutation_rate(0.2);
+        let history = drive(&mut optimizer, &[10, 10], toy)?;
+        assert_eq!(history.len(), 300);
+        let results = OptimizationResults {
+            objective: "distance".into(),
+            constraints: vec!["sum".into()],
+            history,
+        };
+
+        // The optimum is (2, 6) or (3, 5), with a distance of 2
+        let best = results.best_feasible().ok_or("No feasible design")?;
+        assert_eq!(best.objective, 2.);
+
+        // Elitism: the best design of each generation is never lost
+        let best_of = |g: &[Evaluation]| g.iter().min_by(|a, b| a.compare(b)).cloned();
+        let generations: Vec<&[Evaluation]> = results.history.chunks(10).collect();
+        for w in generations.windows(2) {
+            let (a, b) = (best_of(w[0]).unwrap(), best_of(w[1]).unwrap());
+            assert_ne!(b.compare(&a), Ordering::Greater);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_design() {
+        struct Invalid;
+        impl Optimizer for Invalid {
+            fn propose(&mut self, _: &[usize], _: &[Evaluation]) -> Vec<Vec<usize>> {
+                vec![vec![20, 0]]
+            }
+        }
+        assert!(drive(&mut Invalid, &[10, 10], toy).is_err());
+        assert!(drive(&mut RandomSearch::new(5, 0), &[10, 0], toy).is_err());
+    }
+}
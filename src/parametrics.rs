@@ -0,0 +1,537 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Parametric studies, in which several variants of a model—each of them
+//! with a different combination of the values of some parameters—are
+//! simulated, and some metrics of each of them are collected in a table.
+//!
+//! Variants are created by applying each [`Parameter`] to a copy of a base
+//! model, in the order in which they were declared. Parameters can move the
+//! model around (e.g., [`Parameter::orientation`]), change its properties
+//! (e.g., [`Parameter::material_thickness`]) or add new elements to it
+//! through the [`SpaceBuilder`](model::builder::SpaceBuilder).
+//!
+//! ```no_run
+//! use simple::parametrics::{Metric, Parameter, ParametricStudy};
+//! use simple::run_simulation::SimOptions;
+//! use simple::builder::SpaceBuilder;
+//! use simple::Model;
+//!
+//! let (base, _) = Model::from_file("./tests/box/box.spl").unwrap();
+//!
+//! let study = ParametricStudy::new(&base)
+//!     .unwrap()
+//!     .parameter(Parameter::new("wwr", vec![0.2, 0.4, 0.6], |model, wwr| {
+//!         SpaceBuilder::rectangular(4., 5., 3.)
+//!             .name("Office")
+//!             .window_to_wall_ratio(wwr)
+//!             .construction("R13WALL")
+//!             .window_construction("DoubleClear")
+//!             .build(model)?;
+//!         Ok(())
+//!     }))
+//!     .parameter(Parameter::material_thickness("R13LAYER", vec![0.05, 0.1]))
+//!     .parameter(Parameter::orientation(vec![0., 90., 180., 270.]))
+//!     .metric(Metric::mean_space_temperature("Office"));
+//!
+//! let options = SimOptions {
+//!     weather_file: Some("./tests/wellington.epw".into()),
+//!     quiet: true,
+//!     ..SimOptions::default()
+//! };
+//! let results = study.run(&options).unwrap();
+//! assert_eq!(results.rows().len(), 3 * 2 * 4);
+//! results.write_csv(std::io::stdout()).unwrap();
+//! ```
+
+use crate::run_simulation::{run_collect, SimOptions};
+use crate::simulation_results::SimulationResults;
+use crate::{Float, VoidControl};
+use model::{Material, Model, SimulationStateHeader};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use std::sync::Arc;
+
+/// A function that modifies a [`Model`] according to the value of a [`Parameter`]
+type Modifier = Arc<dyn Fn(&mut Model, Float) -> Result<(), String> + Send + Sync>;
+
+/// A function that calculates a [`Metric`] out of the results of a simulation
+type Evaluation = Arc<dyn Fn(&SimulationResults) -> Result<Float, String> + Send + Sync>;
+
+/// A dimension of a [`ParametricStudy`]: a set of values, and how
+/// to modify a model according to each of them
+#[derive(Clone)]
+pub struct Parameter {
+    /// The name of the parameter, used as the header of its column
+    name: String,
+
+    /// The values taken by the parameter
+    values: Vec<Float>,
+
+    /// Modifies a model according to one of the values
+    apply: Modifier,
+}
+
+impl std::fmt::Debug for Parameter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Parameter")
+            .field("name", &self.name)
+            .field("values", &self.values)
+            .finish()
+    }
+}
+
+impl Parameter {
+    /// Creates a `Parameter` that modifies a model by calling `apply` with
+    /// each of its `values`
+    pub fn new<S, F>(name: S, values: Vec<Float>, apply: F) -> Self
+    where
+        S: Into<String>,
+        F: Fn(&mut Model, Float) -> Result<(), String> + Send + Sync + 'static,
+    {
+        Self {
+            name: name.into(),
+            values,
+            apply: Arc::new(apply),
+        }
+    }
+
+    /// Rotates the whole model counterclockwise by each of the `values`,
+    /// in degrees. See [`Model::rotate`].
+    pub fn orientation(values: Vec<Float>) -> Self {
+        Self::new("orientation", values, |model, degrees| {
+            model.rotate(degrees)
+        })
+    }
+
+    /// Sets the thickness of a [`Material`] to each of the `values`, in m.
+    /// Every construction made of such material is affected.
+    pub fn material_thickness<S: Into<String>>(material: S, values: Vec<Float>) -> Self {
+        let material = material.into();
+        let name = format!("{} thickness", material);
+        Self::new(name, values, move |model, thickness| {
+            let m = model
+                .materials
+                .iter_mut()
+                .find(|m| m.name == material)
+                .ok_or_else(|| format!("There is no Material called '{}'", material))?;
+            *m = Arc::new(Material {
+                name: m.name.clone(),
+                substance: m.substance.clone(),
+                thickness,
+            });
+            Ok(())
+        })
+    }
+
+    /// The name of the parameter
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The values taken by the parameter
+    pub fn values(&self) -> &[Float] {
+        &self.values
+    }
+}
+
+/// A value that summarizes the results of the simulation of each of
+/// the variants of a [`ParametricStudy`]
+#[derive(Clone)]
+pub struct Metric {
+    /// The name of the metric, used as the header of its column
+    name: String,
+
+    /// Calculates the metric
+    evaluate: Evaluation,
+}
+
+impl std::fmt::Debug for Metric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Metric").field("name", &self.name).finish()
+    }
+}
+
+impl Metric {
+    /// Creates a `Metric` calculated by `evaluate`
+    pub fn new<S, F>(name: S, evaluate: F) -> Self
+    where
+        S: Into<String>,
+        F: Fn(&SimulationResults) -> Result<Float, String> + Send + Sync + 'static,
+    {
+        Self {
+            name: name.into(),
+            evaluate: Arc::new(evaluate),
+        }
+    }
+
+    /// The energy consumed by an HVAC over the whole simulation, in kWh
+    pub fn hvac_energy<S: Into<String>>(hvac: S) -> Self {
+        let hvac = hvac.into();
+        Self::new(format!("{} energy (kWh)", hvac), move |results| {
+            results
+                .hvac_energy(&hvac)
+                .ok_or_else(|| format!("There are no results for HVAC '{}'", hvac))
+        })
+    }
+
+    /// The energy consumed by a luminaire over the whole simulation, in kWh
+    pub fn luminaire_energy<S: Into<String>>(luminaire: S) -> Self {
+        let luminaire = luminaire.into();
+        Self::new(format!("{} energy (kWh)", luminaire), move |results| {
+            results
+                .luminaire_energy(&luminaire)
+                .ok_or_else(|| format!("There are no results for Luminaire '{}'", luminaire))
+        })
+    }
+
+    /// The average dry bulb temperature of a space over the whole
+    /// simulation, in C
+    pub fn mean_space_temperature<S: Into<String>>(space: S) -> Self {
+        let space = space.into();
+        Self::new(format!("{} mean temperature (C)", space), move |results| {
+            let temperatures = results
+                .space_temperature(&space)
+                .ok_or_else(|| format!("There are no results for Space '{}'", space))?;
+            if temperatures.is_empty() {
+                return Err("Cannot average the temperature of an empty simulation".to_string());
+            }
+            Ok(temperatures.iter().sum::<Float>() / temperatures.len() as Float)
+        })
+    }
+
+    /// The maximum power consumed by an HVAC at any timestep (i.e., its
+    /// peak load), in W
+    pub fn peak_hvac_power<S: Into<String>>(hvac: S) -> Self {
+        let hvac = hvac.into();
+        Self::new(format!("{} peak power (W)", hvac), move |results| {
+            let power = results
+                .hvac_power(&hvac)
+                .ok_or_else(|| format!("There are no results for HVAC '{}'", hvac))?;
+            Ok(power.into_iter().fold(0., Float::max))
+        })
+    }
+
+    /// The number of hours in which the dry bulb temperature of a space is
+    /// above a certain `temperature` (in C), as a measure of discomfort
+    pub fn hours_above<S: Into<String>>(space: S, temperature: Float) -> Self {
+        let space = space.into();
+        let name = format!("{} hours above {}C", space, temperature);
+        Self::new(name, move |results| {
+            let mut hours = 0.;
+            for t in results.timesteps() {
+                let t_space = t
+                    .space_temperature(&space)
+                    .ok_or_else(|| format!("There are no results for Space '{}'", space))?;
+                if t_space > temperature {
+                    hours += t.dt() / 3600.;
+                }
+            }
+            Ok(hours)
+        })
+    }
+
+    /// The name of the metric
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Evaluates the metric on the results of a simulation
+    pub fn evaluate(&self, results: &SimulationResults) -> Result<Float, String> {
+        (self.evaluate)(results)
+    }
+}
+
+/// A sweep over every combination of the values of some [`Parameter`]s
+#[derive(Debug, Clone)]
+pub struct ParametricStudy {
+    /// The base model, in JSON format
+    base: String,
+
+    /// The dimensions of the study
+    parameters: Vec<Parameter>,
+
+    /// The metrics collected for each variant
+    metrics: Vec<Metric>,
+}
+
+impl ParametricStudy {
+    /// Creates a study over variants of a `base` model
+    pub fn new(base: &Model) -> Result<Self, String> {
+        Ok(Self {
+            base: base.to_json()?,
+            parameters: Vec::new(),
+            metrics: Vec::new(),
+        })
+    }
+
+    /// Adds a dimension to the study
+    pub fn parameter(mut self, parameter: Parameter) -> Self {
+        self.parameters.push(parameter);
+        self
+    }
+
+    /// The dimensions of the study
+    pub fn parameters(&self) -> &[Parameter] {
+        &self.parameters
+    }
+
+    /// Adds a metric to be collected for each variant
+    pub fn metric(mut self, metric: Metric) -> Self {
+        self.metrics.push(metric);
+        self
+    }
+
+    /// The values of the parameters of each variant of the study (i.e.,
+    /// every combination of them). The first parameter is the one that
+    /// changes least often.
+    pub fn variants(&self) -> Vec<Vec<Float>> {
+        self.parameters
+            .iter()
+            .fold(vec![Vec::new()], |variants, parameter| {
+                variants
+                    .iter()
+                    .flat_map(|v| {
+                        parameter.values.iter().map(move |x| {
+                            let mut v = v.clone();
+                            v.push(*x);
+                            v
+                        })
+                    })
+                    .collect()
+            })
+    }
+
+    /// Creates the variant of the base model given by some `values`
+    /// of the parameters (see [`ParametricStudy::variants`])
+    pub fn variant(&self, values: &[Float]) -> Result<(Model, SimulationStateHeader), String> {
+        if values.len() != self.parameters.len() {
+            return Err(format!(
+                "The parametric study has {} parameters, but {} values were given",
+                self.parameters.len(),
+                values.len()
+            ));
+        }
+        let mut model: Model = serde_json::from_str(&self.base).map_err(|e| e.to_string())?;
+        for (parameter, value) in self.parameters.iter().zip(values.iter()) {
+            (parameter.apply)(&mut model, *value).map_err(|e| {
+                format!(
+                    "Could not set parameter '{}' to {}: {}",
+                    parameter.name, value, e
+                )
+            })?;
+        }
+        let header = model
+            .take_state()
+            .ok_or("Could not take the state of the variant")?;
+        Ok((model, header))
+    }
+
+    /// Simulates a variant, calculating its metrics
+    fn simulate(&self, values: &[Float], options: &SimOptions) -> Result<Vec<Float>, String> {
+        let (model, mut header) = self.variant(values)?;
+        let results = run_collect(&model, &mut header, options, VoidControl {})?;
+        self.metrics.iter().map(|m| m.evaluate(&results)).collect()
+    }
+
+    /// Simulates every variant—in parallel, if the `parallel` feature is
+    /// enabled—with some [`SimOptions`] (of which the `input_file` is
+    /// ignored), and collects their metrics. Returns an error as soon as
+    /// any of the variants fails.
+    pub fn run(&self, options: &SimOptions) -> Result<ParametricResults, String> {
+        self.run_samples(self.variants(), options)
+    }
+
+    /// Like [`ParametricStudy::run`], but simulating the variants given by
+    /// some arbitrary values of the parameters (e.g., random samples of
+    /// them) instead of every combination of their values
+    pub fn run_samples(
+        &self,
+        samples: Vec<Vec<Float>>,
+        options: &SimOptions,
+    ) -> Result<ParametricResults, String> {
+        #[cfg(feature = "parallel")]
+        let iter = samples.into_par_iter();
+        #[cfg(not(feature = "parallel"))]
+        let iter = samples.into_iter();
+
+        let rows = iter
+            .map(|parameters| {
+                let metrics = self
+                    .simulate(&parameters, options)
+                    .map_err(|e| format!("Variant {:?} failed: {}", parameters, e))?;
+                Ok(ParametricRow {
+                    parameters,
+                    metrics,
+                })
+            })
+            .collect::<Result<Vec<ParametricRow>, String>>()?;
+
+        Ok(ParametricResults {
+            parameters: self.parameters.iter().map(|p| p.name.clone()).collect(),
+            metrics: self.metrics.iter().map(|m| m.name.clone()).collect(),
+            rows,
+        })
+    }
+}
+
+/// The values of the parameters and the metrics of a variant
+/// of a [`ParametricStudy`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParametricRow {
+    /// The values of the parameters, in the order in which
+    /// they were declared
+    pub parameters: Vec<Float>,
+
+    /// The values of the metrics, in the order in which
+    /// they were declared
+    pub metrics: Vec<Float>,
+}
+
+/// The results of a [`ParametricStudy`], as a table with one row per
+/// variant and one column per parameter and metric
+#[derive(Debug, Clone)]
+pub struct ParametricResults {
+    /// The name of the parameters
+    parameters: Vec<String>,
+
+    /// The name of the metrics
+    metrics: Vec<String>,
+
+    /// The results of each variant
+    rows: Vec<ParametricRow>,
+}
+
+impl ParametricResults {
+    /// The names of the columns: first the parameters, then the metrics
+    pub fn header(&self) -> Vec<&str> {
+        self.parameters
+            .iter()
+            .chain(self.metrics.iter())
+            .map(|s| s.as_str())
+            .collect()
+    }
+
+    /// The results of each variant
+    pub fn rows(&self) -> &[ParametricRow] {
+        &self.rows
+    }
+
+    /// The values of a metric, by name, for each variant
+    pub fn metric(&self, name: &str) -> Option<Vec<Float>> {
+        let i = self.metrics.iter().position(|m| m == name)?;
+        Some(self.rows.iter().map(|r| r.metrics[i]).collect())
+    }
+
+    /// Writes the table in CSV format
+    pub fn write_csv<T: std::io::Write>(&self, mut out: T) -> std::io::Result<()> {
+        let quote = |s: &str| {
+            if s.contains(',') || s.contains('"') {
+                format!("\"{}\"", s.replace('"', "\"\""))
+            } else {
+                s.to_string()
+            }
+        };
+        let header: Vec<String> = self.header().iter().map(|s| quote(s)).collect();
+        writeln!(out, "{}", header.join(","))?;
+        for row in self.rows.iter() {
+            let values: Vec<String> = row
+                .parameters
+                .iter()
+                .map(|v| format!("{}", v))
+                .chain(row.metrics.iter().map(|v| format!("{:.3}", v)))
+                .collect();
+            writeln!(out, "{}", values.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use model::SurfaceTrait;
+
+    #[test]
+    fn test_variants() -> Result<(), String> {
+        let (base, _) = Model::from_file("./tests/box/box.spl")?;
+        let study = ParametricStudy::new(&base)?
+            .parameter(Parameter::material_thickness("R13LAYER", vec![0.05, 0.1]))
+            .parameter(Parameter::orientation(vec![0., 90., 180.]));
+
+        let variants = study.variants();
+        assert_eq!(variants.len(), 6);
+        assert_eq!(variants[0], vec![0.05, 0.]);
+        assert_eq!(variants[1], vec![0.05, 90.]);
+        assert_eq!(variants[5], vec![0.1, 180.]);
+
+        let (model, header) = study.variant(&[0.1, 180.])?;
+        assert!((model.get_material("R13LAYER")?.thickness - 0.1).abs() < 1e-9);
+        let (n0, n) = (base.surfaces[0].normal(), model.surfaces[0].normal());
+        assert!((n.x + n0.x).abs() < 1e-6);
+        assert!((n.y + n0.y).abs() < 1e-6);
+        assert!((n.z - n0.z).abs() < 1e-6);
+        assert!(!header.elements.is_empty());
+
+        assert!(study.variant(&[0.1]).is_err());
+        let study = study.parameter(Parameter::material_thickness("Nothing", vec![1.]));
+        assert!(study.variant(&[0.1, 0., 1.]).is_err());
+
+        // No parameters... just the base model
+        assert_eq!(
+            ParametricStudy::new(&base)?.variants(),
+            vec![Vec::<Float>::new()]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_write_csv() -> Result<(), String> {
+        let results = ParametricResults {
+            parameters: vec!["orientation".into()],
+            metrics: vec!["Heater energy (kWh)".into(), "a, b".into()],
+            rows: vec![
+                ParametricRow {
+                    parameters: vec![0.],
+                    metrics: vec![1., 2.],
+                },
+                ParametricRow {
+                    parameters: vec![90.],
+                    metrics: vec![3., 4.5],
+                },
+            ],
+        };
+        assert_eq!(results.metric("a, b"), Some(vec![2., 4.5]));
+        assert!(results.metric("nothing").is_none());
+
+        let mut out = Vec::new();
+        results.write_csv(&mut out).map_err(|e| e.to_string())?;
+        let out = String::from_utf8(out).map_err(|e| e.to_string())?;
+        assert_eq!(
+            out,
+            "orientation,Heater energy (kWh),\"a, b\"\n0,1.000,2.000\n90,3.000,4.500\n"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,233 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::Float;
+use calendar::Date;
+use model::{Model, SimulationState, SimulationStateElement, SimulationStateHeader, HVAC};
+
+/// The factor by which peak loads are multiplied when suggesting
+/// the capacity of the heating and cooling systems
+pub const SIZING_FACTOR: Float = 1.25;
+
+/// The peak heating and cooling loads of a single copy of a
+/// [`Space`](model::Space)
+#[derive(Debug, Clone, Default)]
+pub struct SpacePeakLoad {
+    /// The name of the space
+    pub name: String,
+
+    /// The floor area of the space, in $`m^2`$
+    pub floor_area: Float,
+
+    /// The peak heating load, in $`W`$
+    pub heating: Float,
+
+    /// The timestep in which the peak heating load happened, if any
+    pub heating_date: Option<Date>,
+
+    /// The peak cooling load (as a positive number), in $`W`$
+    pub cooling: Float,
+
+    /// The timestep in which the peak cooling load happened, if any
+    pub cooling_date: Option<Date>,
+}
+
+impl SpacePeakLoad {
+    /// The suggested `max_heating_power` of the systems heating
+    /// this space: the peak heating load times the [`SIZING_FACTOR`]
+    pub fn suggested_heating_power(&self) -> Float {
+        self.heating * SIZING_FACTOR
+    }
+
+    /// The suggested `max_cooling_power` of the systems cooling
+    /// this space: the peak cooling load times the [`SIZING_FACTOR`]
+    pub fn suggested_cooling_power(&self) -> Float {
+        self.cooling * SIZING_FACTOR
+    }
+}
+
+/// Tracks the peak heating and cooling loads of each space of a [`Model`]
+/// throughout a simulation—their magnitude and the time at which they
+/// happen—so that they can be used for sizing the HVAC systems.
+///
+/// The load of a space is the sum of the power of the HVAC systems
+/// serving it, positive when heating and negative when cooling. Loads are
+/// only meaningful for sizing when the systems are able to meet the
+/// setpoints (e.g., `IdealHeaterCooler`s without capacity limits). Design
+/// days (e.g., a synthetic weather file) can be simulated for this purpose.
+#[derive(Debug, Clone)]
+pub struct PeakLoads {
+    /// The peak loads of each space
+    pub spaces: Vec<SpacePeakLoad>,
+
+    /// The index of the power of each HVAC system in the
+    /// [`SimulationState`], and the space it serves
+    elements: Vec<(usize, usize)>,
+
+    /// The load of each space in the current timestep
+    loads: Vec<Float>,
+}
+
+impl PeakLoads {
+    /// Creates a new `PeakLoads`, finding the power of the HVAC
+    /// systems serving each space in the [`SimulationStateHeader`]
+    pub fn new(model: &Model, state_header: &SimulationStateHeader) -> Self {
+        let (_total, floor_areas) = model.get_space_sizes();
+        let spaces: Vec<SpacePeakLoad> = model
+            .spaces
+            .iter()
+            .map(|s| SpacePeakLoad {
+                name: s.name().clone(),
+                floor_area: floor_areas.get(s.name()).copied().unwrap_or(0.),
+                ..SpacePeakLoad::default()
+            })
+            .collect();
+
+        let elements = state_header
+            .elements
+            .iter()
+            .enumerate()
+            .filter_map(|(state_index, element)| {
+                let hvac = match element {
+                    SimulationStateElement::HeatingCoolingPowerConsumption(i) => &model.hvacs[*i],
+                    _ => return None,
+                };
+                let target = match hvac {
+                    HVAC::IdealHeaterCooler(h) => h.target_space(),
+                    HVAC::ElectricHeater(h) => h.target_space(),
+                    HVAC::HeatPump(h) => h.target_space(),
+                    HVAC::RadiantSurfaceSystem(h) => h.target_space(),
+                    HVAC::HeatRecoveryVentilator(h) => h.target_space(),
+                }
+                .ok()?;
+                let space_index = model.spaces.iter().position(|s| s.name() == target)?;
+                Some((state_index, space_index))
+            })
+            .collect();
+
+        Self {
+            loads: vec![0.; spaces.len()],
+            spaces,
+            elements,
+        }
+    }
+
+    /// Accounts for the timestep ending at `date`
+    pub fn record(&mut self, date: Date, state: &SimulationState) {
+        self.loads.iter_mut().for_each(|l| *l = 0.);
+        for (state_index, space_index) in self.elements.iter() {
+            self.loads[*space_index] += state[*state_index];
+        }
+        for (space, load) in self.spaces.iter_mut().zip(self.loads.iter()) {
+            if *load > space.heating {
+                space.heating = *load;
+                space.heating_date = Some(date);
+            } else if -*load > space.cooling {
+                space.cooling = -*load;
+                space.cooling_date = Some(date);
+            }
+        }
+    }
+
+    /// Writes the peak loads of all the spaces, in CSV format
+    pub fn write_report<T: std::io::Write>(&self, mut out: T) -> std::io::Result<()> {
+        writeln!(
+            out,
+            "Space,Floor area (m2),Peak heating (W),Peak heating (W/m2),Peak heating date,Suggested heating power (W),Peak cooling (W),Peak cooling (W/m2),Peak cooling date,Suggested cooling power (W)"
+        )?;
+        let per_area = |v: Float, area: Float| if area > 0. { v / area } else { 0. };
+        let date = |d: Option<Date>| d.map(|d| d.to_string()).unwrap_or_else(|| "-".into());
+        for s in self.spaces.iter() {
+            writeln!(
+                out,
+                "{},{:.1},{:.1},{:.1},{},{:.1},{:.1},{:.1},{},{:.1}",
+                s.name,
+                s.floor_area,
+                s.heating,
+                per_area(s.heating, s.floor_area),
+                date(s.heating_date),
+                s.suggested_heating_power(),
+                s.cooling,
+                per_area(s.cooling, s.floor_area),
+                date(s.cooling_date),
+                s.suggested_cooling_power(),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use model::hvac::IdealHeaterCooler;
+    use model::Space;
+    use validate::assert_close;
+
+    #[test]
+    fn test_peaks() -> Result<(), String> {
+        let mut model = Model::default();
+        model.add_space(Space::new("Kitchen"));
+        model.add_space(Space::new("Bedroom"));
+        for name in ["Kitchen heater", "Kitchen cooler"] {
+            let mut hvac = IdealHeaterCooler::new(name);
+            hvac.set_target_space("Kitchen");
+            model.add_hvac(hvac.wrap())?;
+        }
+        let header = model.take_state().ok_or("No state")?;
+
+        let mut peaks = PeakLoads::new(&model, &header);
+        let mut state = vec![0.; header.elements.len()];
+        let mut date = Date {
+            month: 7,
+            day: 1,
+            hour: 0.,
+            year: None,
+        };
+        // (heater, cooler) at each hour
+        for (heater, cooler) in [(500., 0.), (800., -100.), (0., -900.), (0., -300.)] {
+            state[0] = heater;
+            state[1] = cooler;
+            peaks.record(date, &state);
+            date.add_hours(1.);
+        }
+
+        let kitchen = &peaks.spaces[0];
+        assert_close!(kitchen.heating, 700.);
+        assert_eq!(kitchen.heating_date.map(|d| d.hour), Some(1.));
+        assert_close!(kitchen.cooling, 900.);
+        assert_eq!(kitchen.cooling_date.map(|d| d.hour), Some(2.));
+        assert_close!(kitchen.suggested_heating_power(), 875.);
+
+        // Nothing serves the bedroom
+        assert!(peaks.spaces[1].heating_date.is_none());
+        assert!(peaks.spaces[1].cooling_date.is_none());
+
+        let mut csv = Vec::new();
+        peaks.write_report(&mut csv).map_err(|e| e.to_string())?;
+        let csv = String::from_utf8(csv).map_err(|e| e.to_string())?;
+        assert_eq!(csv.lines().count(), 3);
+        Ok(())
+    }
+}
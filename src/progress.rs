@@ -0,0 +1,276 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::Float;
+use calendar::{Date, Period};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+#[cfg(not(feature = "wasm"))]
+use std::time::Instant;
+
+/// The progress of a simulation, as given to a [`ProgressReporter`]
+/// after every timestep
+#[derive(Debug, Clone, Copy)]
+pub struct SimulationProgress {
+    /// The date that has just been simulated
+    pub date: Date,
+
+    /// The number of timesteps simulated so far
+    pub done: usize,
+
+    /// The number of timesteps in the simulation
+    pub total: usize,
+
+    /// The time elapsed since the simulation started
+    pub elapsed: Duration,
+}
+
+impl SimulationProgress {
+    /// The fraction of the simulation that has been completed, from 0 to 1
+    pub fn fraction(&self) -> Float {
+        self.done as Float / self.total.max(1) as Float
+    }
+
+    /// Estimates the time remaining until the end of the simulation,
+    /// assuming that all timesteps take the same time. Returns `None`
+    /// if no timestep has been simulated yet.
+    pub fn eta(&self) -> Option<Duration> {
+        if self.done == 0 {
+            return None;
+        }
+        let remaining = self.total.saturating_sub(self.done) as u32;
+        Some(self.elapsed / self.done as u32 * remaining)
+    }
+}
+
+/// A trait for receiving the progress of a simulation (e.g., for
+/// showing it in a GUI or sending it to a client).
+///
+/// It is implemented for closures, so a callback can be used directly:
+///
+/// ```
+/// use simple::progress::{ProgressReporter, SimulationProgress};
+///
+/// let mut last = 0.;
+/// let mut reporter = |p: &SimulationProgress| last = p.fraction();
+/// # let progress = SimulationProgress {
+/// #     date: simple::Date { month: 1, day: 1, hour: 0., year: None },
+/// #     done: 1,
+/// #     total: 4,
+/// #     elapsed: std::time::Duration::from_secs(1),
+/// # };
+/// reporter.report(&progress);
+/// ```
+pub trait ProgressReporter {
+    /// Receives the progress after each simulated timestep
+    fn report(&mut self, progress: &SimulationProgress);
+
+    /// Called once the simulation is over
+    fn finish(&mut self) {}
+}
+
+impl<F: FnMut(&SimulationProgress)> ProgressReporter for F {
+    fn report(&mut self, progress: &SimulationProgress) {
+        self(progress)
+    }
+}
+
+/// Reports the progress of a simulation through STDERR, every time
+/// it reaches a new percentage. This is what
+/// [`run`](crate::run_simulation::run) uses.
+#[derive(Debug, Clone)]
+pub struct StderrProgress {
+    /// The last percentage reported
+    percent: usize,
+
+    /// Whether to keep quiet
+    quiet: bool,
+}
+
+impl StderrProgress {
+    /// Creates a new `StderrProgress`, which prints nothing if `quiet`
+    pub fn new(quiet: bool) -> Self {
+        Self {
+            percent: usize::MAX,
+            quiet,
+        }
+    }
+}
+
+impl ProgressReporter for StderrProgress {
+    fn report(&mut self, progress: &SimulationProgress) {
+        let percent = (100. * progress.fraction()) as usize;
+        if !self.quiet && percent != self.percent {
+            self.percent = percent;
+            let eta = match progress.eta() {
+                Some(eta) => format!("{}s left", eta.as_secs()),
+                None => "...".to_string(),
+            };
+            eprint!(
+                "\r  ... Simulating {:02}/{:02} [{:>3}%] {:<12}",
+                progress.date.month, progress.date.day, percent, eta
+            );
+        }
+    }
+
+    fn finish(&mut self) {
+        if !self.quiet {
+            eprintln!();
+        }
+    }
+}
+
+/// A token that can be shared with another thread (e.g., the one running
+/// a GUI) to abort a simulation cleanly. The simulation checks it before
+/// every timestep and returns an error once it has been cancelled.
+///
+/// ```
+/// use simple::progress::CancellationToken;
+///
+/// let token = CancellationToken::default();
+/// let other = token.clone();
+/// assert!(token.check().is_ok());
+/// other.cancel();
+/// assert!(token.check().is_err());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Cancels the simulation
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Checks whether the simulation has been cancelled
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Returns an error if the simulation has been cancelled
+    pub fn check(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            Err("The simulation was cancelled".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Keeps track of the timesteps simulated, feeding a [`ProgressReporter`]
+pub(crate) struct ProgressTracker<'a, P: ProgressReporter + ?Sized> {
+    /// The number of timesteps in the simulation
+    total: usize,
+
+    /// The number of timesteps simulated so far
+    done: usize,
+
+    /// The moment in which the simulation started (clocks are
+    /// not available in WebAssembly)
+    #[cfg(not(feature = "wasm"))]
+    start: Instant,
+
+    /// The reporter
+    reporter: &'a mut P,
+}
+
+impl<'a, P: ProgressReporter + ?Sized> ProgressTracker<'a, P> {
+    pub(crate) fn new(period: Period, reporter: &'a mut P) -> Self {
+        Self {
+            total: period.count().max(1),
+            done: 0,
+            #[cfg(not(feature = "wasm"))]
+            start: Instant::now(),
+            reporter,
+        }
+    }
+
+    /// Registers a simulated timestep
+    pub(crate) fn step(&mut self, date: Date) {
+        self.done += 1;
+        #[cfg(not(feature = "wasm"))]
+        let elapsed = self.start.elapsed();
+        #[cfg(feature = "wasm")]
+        let elapsed = Duration::ZERO;
+        self.reporter.report(&SimulationProgress {
+            date,
+            done: self.done,
+            total: self.total,
+            elapsed,
+        });
+    }
+
+    /// Ends the progress report
+    pub(crate) fn finish(&mut self) {
+        self.reporter.finish();
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    #[test]
+    fn test_tracker() {
+        let start = Date {
+            month: 1,
+            day: 1,
+            hour: 0.,
+            year: None,
+        };
+        let end = Date {
+            month: 1,
+            day: 1,
+            hour: 4.,
+            year: None,
+        };
+        // Four timesteps, ending at 1:00, 2:00, 3:00 and 4:00
+        let period = Period::new(start, end, 3600.);
+
+        let mut fractions = Vec::new();
+        let mut reporter = |p: &SimulationProgress| fractions.push(p.fraction());
+        let mut tracker = ProgressTracker::new(period, &mut reporter);
+        for date in period {
+            tracker.step(date);
+        }
+        tracker.finish();
+        assert_eq!(fractions, vec![0.25, 0.5, 0.75, 1.]);
+    }
+
+    #[test]
+    fn test_eta() {
+        let mut progress = SimulationProgress {
+            date: Date::default(),
+            done: 0,
+            total: 4,
+            elapsed: Duration::from_secs(0),
+        };
+        assert!(progress.eta().is_none());
+
+        progress.done = 1;
+        progress.elapsed = Duration::from_secs(2);
+        assert_eq!(progress.eta(), Some(Duration::from_secs(6)));
+    }
+}
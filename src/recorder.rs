@@ -0,0 +1,334 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::Float;
+use calendar::Date;
+use matrix::Matrix;
+use model::{Model, Output, SimulationState, SimulationStateHeader};
+
+/// The values recorded by a [`Recorder`] at the end of a timestep
+#[derive(Debug, Clone)]
+pub struct RecordedTimestep {
+    /// The date of the timestep, as it would be reported in the results
+    pub date: Date,
+
+    /// The duration of the timestep, in seconds
+    pub dt: Float,
+
+    /// The recorded values, in the same order as [`Recorder::header`]
+    pub values: Vec<Float>,
+}
+
+/// Accumulates time series of some elements of the [`SimulationState`]
+/// in memory, during [`run_with_recorder`](crate::run_simulation::run_with_recorder),
+/// so that the results of a simulation can be used programmatically (e.g.,
+/// in an optimization loop) without writing and parsing a CSV file.
+///
+/// The recorded values are the same that would be written by
+/// [`run`](crate::run_simulation::run): power consumptions are reported
+/// for all the copies of a space, and nothing is recorded during the warm-up.
+///
+/// ```no_run
+/// use simple::recorder::Recorder;
+/// use simple::run_simulation::{run_with_recorder, SimOptions};
+/// use simple::{Model, Output, VoidControl};
+///
+/// let options = SimOptions {
+///     input_file: "./tests/box/box.spl".into(),
+///     weather_file: Some("./tests/wellington.epw".into()),
+///     n: 1,
+///     quiet: true,
+///     ..SimOptions::default()
+/// };
+/// let (model, mut header) = Model::from_file(&options.input_file).unwrap();
+///
+/// let mut recorder = Recorder::new(vec![Output::SpaceDryBulbTemperature("Bedroom".into())]);
+/// run_with_recorder(&model, &mut header, &options, VoidControl {}, &mut recorder).unwrap();
+///
+/// let temperatures = recorder.series(0).unwrap();
+/// assert_eq!(temperatures.len(), recorder.timesteps().len());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Recorder {
+    /// The outputs requested, if they were given as such
+    outputs: Vec<Output>,
+
+    /// Whether to record the whole state, regardless of the
+    /// outputs of the model
+    whole_state: bool,
+
+    /// The indexes of the recorded elements in the state
+    indexes: Vec<usize>,
+
+    /// The name of each of the recorded elements
+    header: Vec<String>,
+
+    /// The factor by which each of the recorded values is multiplied
+    multipliers: Vec<Float>,
+
+    /// The recorded values
+    timesteps: Vec<RecordedTimestep>,
+}
+
+impl Recorder {
+    /// Creates a `Recorder` of some [`Output`]s. If none are given, the
+    /// `outputs` of the model—or, if there are none, the whole state—are
+    /// recorded.
+    pub fn new(outputs: Vec<Output>) -> Self {
+        Self {
+            outputs,
+            ..Self::default()
+        }
+    }
+
+    /// Creates a `Recorder` of the elements in some positions of
+    /// the [`SimulationState`]
+    pub fn from_indexes(indexes: Vec<usize>) -> Self {
+        Self {
+            indexes,
+            ..Self::default()
+        }
+    }
+
+    /// Creates a `Recorder` of every element of the [`SimulationState`],
+    /// even if the model requests only some `outputs`
+    pub fn whole_state() -> Self {
+        Self {
+            whole_state: true,
+            ..Self::default()
+        }
+    }
+
+    /// Resolves the requested outputs against the elements of the
+    /// [`SimulationStateHeader`]—which are not all known until the
+    /// simulation model has been built—and removes any data recorded
+    /// before, returning an error if any of the requests cannot be found.
+    pub fn start(
+        &mut self,
+        model: &Model,
+        state_header: &SimulationStateHeader,
+    ) -> Result<(), String> {
+        let full_header: Vec<String> = state_header
+            .elements
+            .iter()
+            .map(|x| x.stringify(model))
+            .collect();
+
+        let outputs = if self.outputs.is_empty() && self.indexes.is_empty() {
+            &model.outputs
+        } else {
+            &self.outputs
+        };
+        if self.whole_state {
+            self.indexes = (0..full_header.len()).collect();
+        } else if !outputs.is_empty() {
+            self.indexes = outputs
+                .iter()
+                .map(|output| {
+                    let output = serde_json::to_string(output).map_err(|e| e.to_string())?;
+                    full_header
+                        .iter()
+                        .position(|x| x == &output)
+                        .ok_or_else(|| {
+                            format!(
+                                "Recorder asks for output {}, which is not in the simulation state",
+                                output
+                            )
+                        })
+                })
+                .collect::<Result<Vec<usize>, String>>()?;
+        } else if self.indexes.is_empty() {
+            self.indexes = (0..full_header.len()).collect();
+        }
+
+        if let Some(i) = self.indexes.iter().find(|i| **i >= full_header.len()) {
+            return Err(format!(
+                "Recorder asks for element {} of the simulation state, which has only {} elements",
+                i,
+                full_header.len()
+            ));
+        }
+
+        self.header = self
+            .indexes
+            .iter()
+            .map(|i| full_header[*i].clone())
+            .collect();
+        self.multipliers = self
+            .indexes
+            .iter()
+            .map(|i| model.state_element_multiplier(&state_header.elements[*i]))
+            .collect();
+        self.timesteps.clear();
+        Ok(())
+    }
+
+    /// Records the state at the end of a timestep of `dt` seconds
+    pub fn record(&mut self, date: Date, dt: Float, state: &SimulationState) {
+        let values = self
+            .indexes
+            .iter()
+            .zip(self.multipliers.iter())
+            .map(|(i, m)| state[*i] * m)
+            .collect();
+        self.timesteps.push(RecordedTimestep { date, dt, values });
+    }
+
+    /// Appends the timesteps recorded by another `Recorder` of the same
+    /// elements (e.g., of a later part of the same simulation)
+    pub(crate) fn append(&mut self, other: Recorder) -> Result<(), String> {
+        if self.header != other.header {
+            return Err(
+                "Cannot append the timesteps of a Recorder of different elements".to_string(),
+            );
+        }
+        self.timesteps.extend(other.timesteps);
+        Ok(())
+    }
+
+    /// The name of each of the recorded elements (i.e., the columns
+    /// of the results), as they would be written in the header of a CSV file
+    pub fn header(&self) -> &[String] {
+        &self.header
+    }
+
+    /// The values recorded at each timestep
+    pub fn timesteps(&self) -> &[RecordedTimestep] {
+        &self.timesteps
+    }
+
+    /// The time series of the `i`th recorded element, or `None` if
+    /// there are not so many elements
+    pub fn series(&self, i: usize) -> Option<Vec<Float>> {
+        if i >= self.header.len() {
+            return None;
+        }
+        Some(self.timesteps.iter().map(|t| t.values[i]).collect())
+    }
+
+    /// The recorded values as a [`Matrix`] with one row per
+    /// timestep and one column per recorded element
+    pub fn to_matrix(&self) -> Matrix {
+        let data = self
+            .timesteps
+            .iter()
+            .flat_map(|t| t.values.iter().copied())
+            .collect();
+        Matrix::from_data(self.timesteps.len(), self.header.len(), data)
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use model::{SimulationStateElement, Space};
+
+    fn get_model() -> Result<(Model, SimulationStateHeader), String> {
+        let mut model = Model::default();
+        for name in ["Kitchen", "Bedroom"] {
+            model.add_space(Space::new(name));
+        }
+        let mut header = SimulationStateHeader::new();
+        for (i, space) in model.spaces.iter().enumerate() {
+            let index = header.push(SimulationStateElement::SpaceDryBulbTemperature(i), 0.)?;
+            space.set_dry_bulb_temperature_index(index)?;
+        }
+        Ok((model, header))
+    }
+
+    #[test]
+    fn test_record() -> Result<(), String> {
+        let (model, mut header) = get_model()?;
+        let mut recorder = Recorder::new(vec![Output::SpaceDryBulbTemperature("Bedroom".into())]);
+        recorder.start(&model, &header)?;
+        assert_eq!(recorder.header().len(), 1);
+
+        let mut state = header.take_values().ok_or("Could not take values")?;
+        let mut date = Date {
+            month: 1,
+            day: 1,
+            hour: 0.,
+            year: None,
+        };
+        for i in 0..3 {
+            state[0] = -1.;
+            state[1] = i as Float;
+            recorder.record(date, 3600., &state);
+            date.add_hours(1.);
+        }
+        assert_eq!(recorder.timesteps().len(), 3);
+        assert_eq!(recorder.series(0), Some(vec![0., 1., 2.]));
+        assert!(recorder.series(1).is_none());
+
+        let matrix = recorder.to_matrix();
+        assert_eq!(matrix.size(), (3, 1));
+        assert_eq!(matrix.get(2, 0)?, 2.);
+
+        // Starting again removes the data
+        recorder.start(&model, &header)?;
+        assert!(recorder.timesteps().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_whole_state() -> Result<(), String> {
+        let (model, header) = get_model()?;
+        let mut recorder = Recorder::default();
+        recorder.start(&model, &header)?;
+        assert_eq!(recorder.header().len(), 2);
+
+        let mut model = model;
+        model
+            .outputs
+            .push(Output::SpaceDryBulbTemperature("Kitchen".into()));
+        let mut recorder = Recorder::default();
+        recorder.start(&model, &header)?;
+        assert_eq!(recorder.header().len(), 1);
+        let mut recorder = Recorder::whole_state();
+        recorder.start(&model, &header)?;
+        assert_eq!(recorder.header().len(), 2);
+
+        let mut recorder = Recorder::from_indexes(vec![1]);
+        recorder.start(&model, &header)?;
+        assert_eq!(
+            recorder.header(),
+            &header.elements[1..2]
+                .iter()
+                .map(|e| e.stringify(&model))
+                .collect::<Vec<String>>()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors() -> Result<(), String> {
+        let (model, header) = get_model()?;
+        let mut recorder = Recorder::new(vec![Output::SpaceDryBulbTemperature("Garage".into())]);
+        assert!(recorder.start(&model, &header).is_err());
+
+        let mut recorder = Recorder::from_indexes(vec![2]);
+        assert!(recorder.start(&model, &header).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,297 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::Float;
+use calendar::Date;
+use model::{Model, ReportAggregation, ReportFrequency, SimulationState, SimulationStateHeader};
+
+/// A [`Report`](model::Report) resolved against the
+/// [`SimulationStateHeader`], and the values accumulated
+/// during its current time interval
+#[derive(Debug, Clone)]
+struct ReportAccumulator {
+    /// The name of the report, as written in the results
+    name: String,
+
+    /// The index of the reported element in the [`SimulationState`]
+    index: usize,
+
+    /// The multiplier of the reported element (e.g., for spaces
+    /// that are repeated several times)
+    multiplier: Float,
+
+    /// The intervals in which values are aggregated
+    frequency: ReportFrequency,
+
+    /// How values are aggregated
+    aggregation: ReportAggregation,
+
+    /// The date at which the current interval started
+    start: Option<Date>,
+
+    /// The aggregated value in the current interval
+    value: Float,
+
+    /// The number of timesteps recorded in the current interval
+    count: usize,
+}
+
+impl ReportAccumulator {
+    /// The date at which the interval containing the timestep of `dt` seconds
+    /// that ends at `date` starts. Timesteps within the same interval return
+    /// the same value.
+    fn interval_start(&self, date: Date, dt: Float) -> Date {
+        // Timesteps are located by their middle, which never falls on the
+        // boundary between intervals (e.g., a timestep ending at 1:00 belongs
+        // to the hour starting at 0:00)
+        let middle = Date::from_year_and_day(date.year, date.day_of_year() - 0.5 * dt / 86400.);
+        match self.frequency {
+            ReportFrequency::Timestep => date,
+            ReportFrequency::Hourly => Date {
+                hour: middle.hour.floor(),
+                ..middle
+            },
+            ReportFrequency::Daily => Date { hour: 0., ..middle },
+            ReportFrequency::Monthly => Date {
+                day: 1,
+                hour: 0.,
+                ..middle
+            },
+            ReportFrequency::RunPeriod => self.start.unwrap_or(Date {
+                hour: (middle.hour - 0.5 * dt / 3600.).max(0.),
+                ..middle
+            }),
+        }
+    }
+
+    /// Adds a value to the current interval
+    fn add(&mut self, value: Float, dt: Float) {
+        if self.count == 0 {
+            self.value = match self.aggregation {
+                ReportAggregation::Minimum | ReportAggregation::Maximum => value,
+                _ => 0.,
+            }
+        }
+        match self.aggregation {
+            ReportAggregation::Average => self.value += value,
+            ReportAggregation::Sum => self.value += value * dt / 3600.,
+            ReportAggregation::Minimum => self.value = self.value.min(value),
+            ReportAggregation::Maximum => self.value = self.value.max(value),
+        }
+        self.count += 1;
+    }
+
+    /// Writes the current interval—if any values were recorded in it—and
+    /// resets the accumulator
+    fn flush<T: std::io::Write>(&mut self, out: &mut T) -> Result<(), String> {
+        if let (Some(start), true) = (self.start, self.count > 0) {
+            let value = match self.aggregation {
+                ReportAggregation::Average => self.value / self.count as Float,
+                _ => self.value,
+            };
+            writeln!(out, "{},{},{:.3}", start, self.name, value)
+                .map_err(|e| format!("Could not write to output file: {}", e))?;
+        }
+        self.start = None;
+        self.value = 0.;
+        self.count = 0;
+        Ok(())
+    }
+}
+
+/// Writes the results of the [`Report`](model::Report)s requested in a
+/// [`Model`], in long format: one row per report and time interval, with
+/// the date at which the interval starts, the name of the report and
+/// its value.
+#[derive(Debug, Clone)]
+pub struct ReportWriter {
+    /// The reports being written
+    reports: Vec<ReportAccumulator>,
+}
+
+impl ReportWriter {
+    /// Resolves the reports in a `Model` against the elements in the
+    /// [`SimulationStateHeader`], returning an error if any of them
+    /// asks for an output that is not in the simulation state.
+    pub fn new(model: &Model, state_header: &SimulationStateHeader) -> Result<Self, String> {
+        let full_header: Vec<String> = state_header
+            .elements
+            .iter()
+            .map(|x| x.stringify(model))
+            .collect();
+
+        let mut reports = Vec::with_capacity(model.reports.len());
+        for report in model.reports.iter() {
+            let output = serde_json::to_string(&report.output).map_err(|e| e.to_string())?;
+            let index = full_header
+                .iter()
+                .position(|x| x == &output)
+                .ok_or_else(|| {
+                    format!(
+                        "Report asks for output {}, which is not in the simulation state",
+                        output
+                    )
+                })?;
+            let frequency = report.frequency().copied().unwrap_or_default();
+            let aggregation = report.aggregation().copied().unwrap_or_default();
+            reports.push(ReportAccumulator {
+                name: format!("{} [{:?} {:?}]", output, frequency, aggregation),
+                index,
+                multiplier: model.state_element_multiplier(&state_header.elements[index]),
+                frequency,
+                aggregation,
+                start: None,
+                value: 0.,
+                count: 0,
+            });
+        }
+        Ok(Self { reports })
+    }
+
+    /// Writes the header of the results
+    pub fn write_header<T: std::io::Write>(&self, out: &mut T) -> Result<(), String> {
+        writeln!(out, "Date,Report,Value")
+            .map_err(|e| format!("Could not write to output file (header): {}", e))
+    }
+
+    /// Records the state at the end of a timestep of `dt` seconds,
+    /// writing the intervals that have ended
+    pub fn record<T: std::io::Write>(
+        &mut self,
+        date: Date,
+        dt: Float,
+        state: &SimulationState,
+        out: &mut T,
+    ) -> Result<(), String> {
+        for report in self.reports.iter_mut() {
+            let start = report.interval_start(date, dt);
+            if report.start != Some(start) {
+                report.flush(out)?;
+                report.start = Some(start);
+            }
+            report.add(state[report.index] * report.multiplier, dt);
+        }
+        Ok(())
+    }
+
+    /// Writes the intervals that are still open, at the end of the simulation
+    pub fn finish<T: std::io::Write>(&mut self, out: &mut T) -> Result<(), String> {
+        for report in self.reports.iter_mut() {
+            report.flush(out)?;
+        }
+        Ok(())
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use calendar::Period;
+    use model::{Output, Report, SimulationStateElement, Space};
+
+    fn get_writer(
+        frequency: ReportFrequency,
+        aggregation: ReportAggregation,
+    ) -> Result<(ReportWriter, SimulationState), String> {
+        let mut model = Model::default();
+        let space = model.add_space(Space::new("Kitchen"));
+        let mut header = SimulationStateHeader::new();
+        let i = header.push(SimulationStateElement::SpaceDryBulbTemperature(0), 0.)?;
+        space.set_dry_bulb_temperature_index(i)?;
+
+        let mut report = Report::new(Output::SpaceDryBulbTemperature("Kitchen".into()));
+        report.set_frequency(frequency).set_aggregation(aggregation);
+        model.reports.push(report);
+
+        let writer = ReportWriter::new(&model, &header)?;
+        let state = header.take_values().ok_or("Could not take values")?;
+        Ok((writer, state))
+    }
+
+    fn simulate(
+        frequency: ReportFrequency,
+        aggregation: ReportAggregation,
+    ) -> Result<Vec<String>, String> {
+        let (mut writer, mut state) = get_writer(frequency, aggregation)?;
+        let start = Date {
+            month: 1,
+            day: 1,
+            hour: 0.,
+            year: None,
+        };
+        // Dates are at the end of each timestep
+        let end = Date {
+            month: 1,
+            day: 3,
+            hour: 0.,
+            year: None,
+        };
+        let dt = 1800.;
+        let mut out: Vec<u8> = Vec::new();
+        for (i, date) in Period::new(start, end, dt).enumerate() {
+            // One degree per timestep
+            state[0] = i as Float;
+            writer.record(date, dt, &state, &mut out)?;
+        }
+        writer.finish(&mut out)?;
+        let out = String::from_utf8(out).map_err(|e| e.to_string())?;
+        Ok(out.lines().map(|s| s.to_string()).collect())
+    }
+
+    #[test]
+    fn test_hourly_average() -> Result<(), String> {
+        let rows = simulate(ReportFrequency::Hourly, ReportAggregation::Average)?;
+        assert_eq!(rows.len(), 48);
+        assert!(rows[0].ends_with("[Hourly Average],0.500"));
+        assert!(rows[1].ends_with(",2.500"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_daily_sum_min_max() -> Result<(), String> {
+        // 0 + 1 + ... + 47 = 1128 degrees, over half-hour timesteps
+        let rows = simulate(ReportFrequency::Daily, ReportAggregation::Sum)?;
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].ends_with(",564.000"));
+
+        let rows = simulate(ReportFrequency::Daily, ReportAggregation::Maximum)?;
+        assert!(rows[0].ends_with(",47.000"));
+        assert!(rows[1].ends_with(",95.000"));
+
+        let rows = simulate(ReportFrequency::RunPeriod, ReportAggregation::Minimum)?;
+        assert_eq!(rows.len(), 1);
+        assert!(rows[0].ends_with(",0.000"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_output() -> Result<(), String> {
+        let mut model = Model::default();
+        let report = Report::new(Output::SpaceDryBulbTemperature("Bedroom".into()));
+        model.reports.push(report);
+        let header = SimulationStateHeader::new();
+        assert!(ReportWriter::new(&model, &header).is_err());
+        Ok(())
+    }
+}
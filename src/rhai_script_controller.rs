@@ -18,11 +18,12 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 
-use model::rhai_api::register_control_api;
+use model::rhai_api::{register_control_api, register_environment_api};
 use model::{Model, SimulationState};
 use rhai::{Engine, AST};
 use std::fs::{self};
 use std::sync::{Arc, Mutex};
+use weather::CurrentWeather;
 
 /// A controller that adapts the state of the building based on a user-defined
 /// script written in [Rhai](https://rhai.rs) programming language.
@@ -32,6 +33,9 @@ use std::sync::{Arc, Mutex};
 pub struct RhaiControlScript {
     ast: AST,
     engine: Engine,
+
+    /// The current date and weather, readable from the script
+    weather: Arc<Mutex<CurrentWeather>>,
 }
 
 impl RhaiControlScript {
@@ -62,12 +66,32 @@ impl RhaiControlScript {
         let state = Arc::new(Mutex::new(state));
         let model = Arc::new(model);
         register_control_api(&mut engine, &model, &state, research_mode);
+        let weather = Arc::new(Mutex::new(CurrentWeather::default()));
+        register_environment_api(&mut engine, &weather);
         let ast = match engine.compile(control_script) {
             Ok(v) => v,
             Err(e) => return Err(format!("Rhai {}", e)),
         };
 
-        Ok((Self { ast, engine }, state))
+        Ok((
+            Self {
+                ast,
+                engine,
+                weather,
+            },
+            state,
+        ))
+    }
+
+    /// Sets the date and weather that the script sees when
+    /// calling `date()` and `weather()`
+    pub fn set_weather(&self, weather: CurrentWeather) -> Result<(), String> {
+        let mut w = self
+            .weather
+            .lock()
+            .map_err(|_| "Could not lock the weather of the control script".to_string())?;
+        *w = weather;
+        Ok(())
     }
 
     /// Runs a control script
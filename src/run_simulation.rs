@@ -20,83 +20,443 @@ SOFTWARE.
 use crate::control_trait::SimpleControl;
 use crate::Float;
 use crate::RhaiControlScript;
-use calendar::Period;
+use calendar::{Date, Period};
+#[cfg(feature = "cli")]
 use clap::Parser;
+use comfort::AdaptiveComfort;
 use communication::{MetaOptions, SimulationModel};
-use model::{Model, SimulationStateHeader};
+use light::{AnnualDaylight, SensorGrid};
+use model::{Model, SimpleError, SimulationState, SimulationStateHeader};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use serde_json;
 use std::borrow::Borrow;
 
+use crate::energy_summary::EnergySummary;
 use crate::multiphysics_model::MultiphysicsModel;
+use crate::peak_loads::PeakLoads;
+use crate::progress::{CancellationToken, ProgressReporter, ProgressTracker, StderrProgress};
+use crate::recorder::Recorder;
+use crate::reporting::ReportWriter;
+use crate::simulation_results::SimulationResults;
+use crate::stepper::march;
 use std::fs::{self};
-use weather::{EPWWeather, Weather};
+use std::ops::Range;
+use weather::{CurrentWeather, EPWWeather, RadiationInterpolation, Weather, WeatherTrait};
 
 /// SIMPLE aims to be a modern building simulation system. (c) Wise House | Germán Molina
-#[derive(Debug, Parser, Default)]
-#[clap(author, version, about, long_about = None)]
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "cli", derive(Parser))]
+#[cfg_attr(feature = "cli", clap(author, version, about, long_about = None, subcommand_negates_reqs = true))]
 pub struct SimOptions {
+    /// A command to run instead of a simulation
+    #[cfg_attr(feature = "cli", command(subcommand))]
+    pub command: Option<SimCommand>,
+
     /// Does not simulate, just reads the model to see whether
     /// it can be parsed appropriately
-    #[arg(long)]
+    #[cfg_attr(feature = "cli", arg(long))]
     pub check: bool,
 
     /// Translates the model from .JSON to .SPL, or the other way around.
-    #[arg(short, long)]
+    #[cfg_attr(feature = "cli", arg(short, long))]
     pub translate: Option<String>,
 
     /// The input simple file
-    #[arg(short, long)]
+    #[cfg_attr(feature = "cli", arg(short, long))]
     pub input_file: String,
 
-    /// The EPW weather file
-    #[arg(short, long)]
+    /// The weather file. It can be an EPW file (`.epw`), a TMY3 or
+    /// simple CSV file (`.csv`, see [`weather::csv_weather`]) or a
+    /// serialized [`Weather`] (`.sw`)
+    #[cfg_attr(feature = "cli", arg(short, long))]
     pub weather_file: Option<String>,
 
-    /// The control script
-    #[arg(short, long)]
+    /// The control script. Use `people` or `daylight` for the built-in
+    /// [`OccupantBehaviour`](crate::OccupantBehaviour) and
+    /// [`DaylightDimming`](crate::DaylightDimming) routines.
+    #[cfg_attr(feature = "cli", arg(short, long))]
     pub control_file: Option<String>,
 
     /// Specifies the path to which to write the results.
     /// If none is given, STDOUT is used
-    #[arg(short, long)]
+    #[cfg_attr(feature = "cli", arg(short, long))]
     pub output: Option<String>,
 
     /// Enable research mode, allowing some unrealistic
     /// but very powerful functions in the API
-    #[arg(short, long)]
+    #[cfg_attr(feature = "cli", arg(short, long))]
     pub research_mode: bool,
 
-    // /// The starting date
-    // #[clap(short = 's')]
-    // pub start: Date,
+    /// Does not report the progress of the simulation
+    #[cfg_attr(feature = "cli", arg(short, long))]
+    pub quiet: bool,
+
+    /// The date at which the simulation starts, in `MM/DD` format (e.g.,
+    /// `01/15`), or in `YYYY/MM/DD` format for weather data with years.
+    /// ISO 8601 dates (e.g., `2024-01-15` or `--01-15`) are accepted too.
+    /// If none is given, the simulation starts at the beginning of the
+    /// weather file.
+    #[cfg_attr(feature = "cli", arg(long, value_parser = parse_date))]
+    pub start_date: Option<Date>,
+
+    /// The date at which the simulation ends (exclusive), in `MM/DD` format.
+    /// For instance, `--start-date 01/15 --end-date 01/22` simulates a whole
+    /// week. If none is given, the simulation runs until the end of the
+    /// weather file. Periods that go through the new year are allowed.
+    #[cfg_attr(feature = "cli", arg(long, value_parser = parse_date))]
+    pub end_date: Option<Date>,
+
+    /// The maximum number of times that the first day of the simulation is
+    /// simulated before recording any results, so that these do not depend on
+    /// the initial conditions. The warm-up stops earlier if the temperatures
+    /// of all the spaces at the end of two consecutive days differ by less
+    /// than `warmup_tolerance`. Defaults to 0 (i.e., no warm-up).
+    #[cfg_attr(feature = "cli", arg(long, default_value_t = 0))]
+    pub warmup_days: usize,
+
+    /// The maximum difference (in C) in the temperature of the spaces between
+    /// two consecutive warm-up days for the warm-up to be considered
+    /// converged. If none is given, a value of 0.1 is used.
+    #[cfg_attr(feature = "cli", arg(long))]
+    pub warmup_tolerance: Option<Float>,
+
+    /// Specifies the path to which to write a checkpoint of the simulation
+    /// at the end of every simulated day and at the end of the simulation,
+    /// so that it can be resumed later (see `resume`).
+    #[cfg_attr(feature = "cli", arg(long))]
+    pub checkpoint: Option<String>,
+
+    /// Resumes the simulation from a checkpoint written by a previous run
+    /// of the same model, starting from the timestep after the one in which
+    /// the checkpoint was saved. No warm-up is performed.
+    #[cfg_attr(feature = "cli", arg(long))]
+    pub resume: Option<String>,
 
-    // /// The final date
-    // #[clap(short = 'e')]
-    // pub end: Date,
     /// The number of timesteps per hour in the simulation
-    #[arg(short, long, default_value_t = 1)]
+    #[cfg_attr(feature = "cli", arg(short, long, default_value_t = 1))]
     pub n: usize,
+
+    /// Reports the results in local clock time (i.e., accounting for the
+    /// daylight saving period declared in the weather file) instead of in
+    /// standard time. The simulation itself always runs in standard time.
+    #[cfg_attr(feature = "cli", arg(long))]
+    pub clock_time: bool,
+
+    /// Redistributes the (usually hourly) solar radiation of the weather
+    /// file over the timesteps of the simulation according to the position
+    /// of the sun, instead of interpolating it linearly. Recommended when
+    /// simulating several timesteps per hour.
+    #[cfg_attr(feature = "cli", arg(long))]
+    pub solar_interpolation: bool,
+
+    /// Specifies the path to which to write the adaptive comfort
+    /// statistics (ASHRAE-55 and EN-16798) of each space, at the end
+    /// of the simulation. If none is given, they are not calculated.
+    #[cfg_attr(feature = "cli", arg(long))]
+    pub adaptive_comfort: Option<String>,
+
+    /// Specifies the path to which to write a summary of the heating, cooling,
+    /// lighting and equipment energy of each space, per month (in kWh and
+    /// kWh/m2), at the end of the simulation. It is written as an HTML page
+    /// if the path ends in `.html`, and in JSON format otherwise.
+    #[cfg_attr(feature = "cli", arg(long))]
+    pub energy_summary: Option<String>,
+
+    /// Specifies the path to which to write the peak heating and cooling
+    /// loads of each space—and the time at which they happen—at the end of
+    /// the simulation, for sizing the HVAC systems. Use a design-day weather
+    /// file to simulate design conditions.
+    #[cfg_attr(feature = "cli", arg(long))]
+    pub peak_loads: Option<String>,
+
+    /// Specifies the path to which to write the annual daylight metrics
+    /// (DA, UDI and sDA) of the sensors in the `sensor_grid` files and in the
+    /// `SensorGrid`s of the model, at the end of the simulation. If none is
+    /// given, they are not calculated.
+    #[cfg_attr(feature = "cli", arg(long))]
+    pub daylight_metrics: Option<String>,
+
+    /// A file containing a grid of sensors for calculating the daylight
+    /// metrics, in Radiance's format (i.e., one 'x y z dx dy dz' sensor per line).
+    /// Can be used more than once.
+    #[cfg_attr(feature = "cli", arg(long))]
+    pub sensor_grid: Vec<String>,
+}
+
+/// Parses a date in `MM/DD` or `YYYY/MM/DD` format, at midnight, or
+/// in ISO 8601 format
+#[cfg(feature = "cli")]
+fn parse_date(s: &str) -> Result<Date, String> {
+    if s.contains('-') {
+        return Date::parse_iso8601(s);
+    }
+    let err = || {
+        format!(
+            "Invalid date '{}'... expecting MM/DD or YYYY/MM/DD format",
+            s
+        )
+    };
+    let s = s.trim();
+    let (year, month_day) = match s.splitn(3, '/').count() {
+        3 => {
+            let (year, month_day) = s.split_once('/').ok_or_else(err)?;
+            (Some(year.parse::<i32>().map_err(|_| err())?), month_day)
+        }
+        _ => (None, s),
+    };
+    let (month, day) = month_day.split_once('/').ok_or_else(err)?;
+    let month: u8 = month.parse().map_err(|_| err())?;
+    let day: u8 = day.parse().map_err(|_| err())?;
+    if !(1..=12).contains(&month) {
+        return Err(err());
+    }
+    let date = Date {
+        month,
+        day,
+        hour: 0.,
+        year,
+    };
+    if day == 0 || day > date.days_in_month() {
+        return Err(err());
+    }
+    Ok(date)
+}
+
+/// The commands that can be run instead of a simulation
+#[derive(Debug)]
+#[cfg_attr(feature = "cli", derive(clap::Subcommand))]
+pub enum SimCommand {
+    /// Loads and validates a model, and prints a summary of it
+    /// (see [`ModelSummary`](crate::ModelSummary)) without simulating it.
+    /// Exits with an error if any problem is found.
+    Check {
+        /// The input simple file
+        input_file: String,
+    },
+}
+
+/// The default value of the `warmup_tolerance` in [`SimOptions`], in C
+const DEFAULT_WARMUP_TOLERANCE: Float = 0.1;
+
+/// Repeatedly simulates the first day of the simulation—i.e., the
+/// `period`—by calling `march` on each of its timesteps, until the
+/// temperatures of the spaces at the end of the day converge or
+/// `options.warmup_days` are simulated. Returns the number of days simulated.
+///
+/// `march` simulates a timestep and returns the temperatures of the
+/// spaces after it.
+fn warm_up<F>(options: &SimOptions, period: Period, mut march: F) -> Result<usize, String>
+where
+    F: FnMut(Date) -> Result<Vec<Float>, String>,
+{
+    let tolerance = options.warmup_tolerance.unwrap_or(DEFAULT_WARMUP_TOLERANCE);
+    let mut previous: Option<Vec<Float>> = None;
+    for day in 1..=options.warmup_days {
+        let mut temperatures = Vec::new();
+        for date in period {
+            temperatures = march(date)?;
+        }
+        if let Some(previous) = &previous {
+            let converged = previous
+                .iter()
+                .zip(temperatures.iter())
+                .all(|(a, b)| (a - b).abs() < tolerance);
+            if converged {
+                if !options.quiet {
+                    eprintln!("  ... Warm-up converged after {} days", day);
+                }
+                return Ok(day);
+            }
+        }
+        previous = Some(temperatures);
+    }
+    if options.warmup_days > 0 {
+        crate::print_warning(
+            "Warm-up",
+            format!(
+                "Temperatures did not converge after {} warm-up days",
+                options.warmup_days
+            ),
+        );
+    }
+    Ok(options.warmup_days)
+}
+
+/// Writes the results of a simulation, timestep by timestep: either
+/// the [`Report`](model::Report)s of the model or—if there are none—the
+/// reported elements of the state, one column each.
+struct ResultsWriter<'a> {
+    /// The reports, which replace the timestep-by-timestep columns
+    reports: Option<ReportWriter>,
+
+    /// The data of the simulation, which contains the reported columns
+    data: &'a PreProcessData,
+
+    /// The number of outputs requested, which determines where
+    /// the commas go
+    len: usize,
+}
+
+impl<'a> ResultsWriter<'a> {
+    fn new(
+        model: &Model,
+        state_header: &SimulationStateHeader,
+        data: &'a PreProcessData,
+    ) -> Result<Self, String> {
+        let reports = if model.reports.is_empty() {
+            None
+        } else {
+            Some(ReportWriter::new(model, state_header)?)
+        };
+        let len = if model.outputs.is_empty() {
+            state_header.elements.len()
+        } else {
+            model.outputs.len()
+        };
+        Ok(Self { reports, data, len })
+    }
+
+    /// Writes the header of the results
+    fn write_header<T: std::io::Write>(&self, out: &mut T) -> Result<(), String> {
+        if let Some(reports) = &self.reports {
+            return reports.write_header(out);
+        }
+        let err = |e: std::io::Error| format!("Could not write to output file (header): {}", e);
+        write!(out, "Date,").map_err(err)?;
+        for (index, i) in self.data.report_indexes.iter().enumerate() {
+            let s = &self.data.full_header[*i];
+            if index < self.len - 1 {
+                write!(out, "{},", s).map_err(err)?;
+            } else {
+                write!(out, "{}", s).map_err(err)?;
+            }
+        }
+        writeln!(out).map_err(err)
+    }
+
+    /// Writes the state at the end of the timestep that is reported at `date`
+    fn record<T: std::io::Write>(
+        &mut self,
+        date: Date,
+        state: &SimulationState,
+        out: &mut T,
+    ) -> Result<(), String> {
+        if let Some(reports) = &mut self.reports {
+            return reports.record(date, self.data.dt, state, out);
+        }
+        let err = |e: std::io::Error| format!("Could not write to output file ({}): {}", date, e);
+        write!(out, "{},", date).map_err(err)?;
+        for (index, i) in self.data.report_indexes.iter().enumerate() {
+            let value = state[*i] * self.data.report_multipliers[index];
+            if index < self.len - 1 {
+                write!(out, "{:.3},", value).map_err(err)?;
+            } else {
+                write!(out, "{:.3}", value).map_err(err)?;
+            }
+        }
+        writeln!(out).map_err(err)
+    }
+
+    /// Writes whatever is pending at the end of the simulation
+    fn finish<T: std::io::Write>(&mut self, out: &mut T) -> Result<(), String> {
+        match &mut self.reports {
+            Some(reports) => reports.finish(out),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Saves a checkpoint of the simulation, if the options ask for it. Unless
+/// `force`, this is only done on the last timestep of each day.
+fn save_checkpoint(
+    options: &SimOptions,
+    data: &PreProcessData,
+    model: &Model,
+    state_header: &SimulationStateHeader,
+    date: Date,
+    state: &SimulationState,
+    force: bool,
+) -> Result<(), String> {
+    if let Some(path) = &options.checkpoint {
+        let mut next = date;
+        next.add_seconds(data.dt);
+        if force || !next.same_day(date) {
+            data.model
+                .save_checkpoint(path, date, model, state_header, state)?;
+        }
+    }
+    Ok(())
 }
 
 struct PreProcessData {
+    meta_options: MetaOptions,
+    resumed: bool,
     sim_period: Period,
+    warmup_period: Period,
+    temperature_indexes: Vec<usize>,
+    dt: Float,
     report_indexes: Vec<usize>,
+    report_multipliers: Vec<Float>,
     full_header: Vec<String>,
     model: MultiphysicsModel,
     weather: Weather,
 }
 
+/// Reads a weather file, whose format is identified by its extension:
+/// EPW files (`.epw`), TMY3 or simple CSV files (`.csv`, see
+/// [`weather::csv_weather`]) or serialized [`Weather`]s (`.sw`)
+pub fn load_weather(file: &str) -> Result<Weather, SimpleError> {
+    let format = match file.rsplit_once('.') {
+        Some((_, extension)) if ["epw", "csv", "sw"].contains(&extension) => extension,
+        _ => {
+            return Err(SimpleError::Parse(format!(
+                "Unsupported weather format in file '{}'",
+                file
+            )))
+        }
+    };
+    let bytes = fs::read(file)
+        .map_err(|_| SimpleError::Io(format!("Could not read weather file '{}'", file)))?;
+    weather_from_bytes(&bytes, format)
+}
+
+/// Parses the contents of a weather file in a certain `format`, given
+/// by the extension the file would have (i.e., `"epw"`, `"csv"` or `"sw"`).
+/// See [`load_weather`].
+pub fn weather_from_bytes(bytes: &[u8], format: &str) -> Result<Weather, SimpleError> {
+    let weather = match format {
+        "epw" => EPWWeather::from_bytes(bytes)?.into(),
+        "csv" => {
+            let s = std::str::from_utf8(bytes).map_err(|e| SimpleError::Parse(e.to_string()))?;
+            if weather::tmy3::is_tmy3(s) {
+                weather::tmy3::parse(s)?
+            } else {
+                weather::csv_weather::parse(s)?
+            }
+        }
+        "sw" => serde_json::from_slice(bytes).map_err(|e| SimpleError::Parse(e.to_string()))?,
+        _ => {
+            return Err(SimpleError::Parse(format!(
+                "Unsupported weather format '{}'",
+                format
+            )))
+        }
+    };
+    Ok(weather)
+}
+
 fn pre_process(
     model: &Model,
     options: &SimOptions,
     state_header: &mut SimulationStateHeader,
-) -> Result<PreProcessData, String> {
+) -> Result<PreProcessData, SimpleError> {
     const MAX_N: usize = 60;
     let n = if options.n > MAX_N {
         eprintln!("The maximum allowed value for -n param is {}... n has been automatically limited to that value", MAX_N);
         MAX_N
     } else if options.n == 0 {
-        return Err("Parameter 'n' should be larger than 0".to_string());
+        return Err("Parameter 'n' should be larger than 0".into());
     } else {
         options.n
     };
@@ -104,43 +464,59 @@ fn pre_process(
     let dt = 60. * 60. / n as Float;
 
     // Load weather
-    let mut weather: Weather = match &options.weather_file {
+    let mut weather = match &options.weather_file {
         None => {
-            return Err("No weather file specified".to_string());
-        }
-        Some(file) => {
-            if file.ends_with(".epw") {
-                EPWWeather::from_file(file.to_string())?.into()
-            } else if file.ends_with(".sw") {
-                let s = match fs::read_to_string(file) {
-                    Ok(v) => v,
-                    Err(_) => return Err(format!("Could not read JSON file '{}'", file)),
-                };
-                serde_json::from_str(&s).map_err(|e| format!("{}", e))?
-            } else {
-                return Err(format!("Unsupported weather format in file '{}'", file));
-            }
+            return Err("No weather file specified".into());
         }
+        Some(file) => load_weather(file)?,
     };
 
-    let start = weather.data[0].date;
-    let mut end = weather.data[weather.data.len() - 1].date;
-    end.hour -= 0.1;
-
-    let sim_period = Period::new(start, end, dt);
-
     weather.sort_data();
+    if options.solar_interpolation {
+        weather.radiation_interpolation = RadiationInterpolation::SolarGeometry;
+    }
 
     let meta_options = MetaOptions {
         latitude: weather.location.latitude,
         longitude: weather.location.longitude,
         standard_meridian: (weather.location.timezone as Float * 15.).to_radians(),
         elevation: weather.location.elevation,
+        daylight_saving: if options.clock_time {
+            weather.daylight_saving
+        } else {
+            None
+        },
     };
 
     // Create physics model
     let physics_model = MultiphysicsModel::new(&meta_options, (), model, state_header, n)?;
 
+    let mut start = options.start_date.unwrap_or(weather.data[0].date);
+    let end = match options.end_date {
+        // Stop just before the end date
+        Some(end) => Date::from_year_and_day(end.year, end.day_of_year() - 0.1 / 24.),
+        None => {
+            let mut end = weather.data[weather.data.len() - 1].date;
+            end.hour -= 0.1;
+            end
+        }
+    };
+
+    // Continue from the timestep after the checkpoint
+    if let Some(path) = &options.resume {
+        let (date, state) = physics_model.resume(path, model, state_header)?;
+        state_header.default_values = Some(state);
+        start = date;
+        start.add_seconds(dt);
+    }
+
+    let sim_period = Period::new(start, end, dt);
+
+    // The first day of the simulation, repeated during the warm-up
+    let mut warmup_end = start;
+    warmup_end.add_hours(23.9);
+    let warmup_period = Period::new(start, warmup_end, dt);
+
     // Collect variables we need to report
     let full_header: Vec<String> = state_header
         .elements
@@ -148,17 +524,13 @@ fn pre_process(
         .map(|x| x.stringify(model))
         .collect();
 
-    let report_indexes: Vec<usize> = model
-        .borrow()
-        .outputs
-        .iter()
-        .filter_map(|item| {
-            full_header.iter().position(|x| {
-                x == &serde_json::to_string(item)
-                    .expect("There was an error interpreting the inputs")
-            })
-        })
-        .collect();
+    let mut report_indexes: Vec<usize> = Vec::with_capacity(model.outputs.len());
+    for item in model.outputs.iter() {
+        let item = serde_json::to_string(item).map_err(|e| e.to_string())?;
+        if let Some(i) = full_header.iter().position(|x| x == &item) {
+            report_indexes.push(i);
+        }
+    }
 
     let report_indexes = if report_indexes.is_empty() {
         (0..full_header.len()).collect()
@@ -166,120 +538,609 @@ fn pre_process(
         report_indexes
     };
 
+    // The temperatures checked for convergence during the warm-up
+    let temperature_indexes: Vec<usize> = model
+        .spaces
+        .iter()
+        .filter_map(|s| s.dry_bulb_temperature_index())
+        .collect();
+
+    // Power consumptions are reported for all the copies of a space
+    let report_multipliers: Vec<Float> = report_indexes
+        .iter()
+        .map(|i| model.state_element_multiplier(&state_header.elements[*i]))
+        .collect();
+
     Ok(PreProcessData {
+        meta_options,
+        resumed: options.resume.is_some(),
         sim_period,
+        warmup_period,
+        temperature_indexes,
+        dt,
         report_indexes,
+        report_multipliers,
         full_header,
         weather,
         model: physics_model,
     })
 }
 
+/// The weather at a certain date, as seen by control scripts
+fn current_weather(weather: &Weather, date: Date) -> CurrentWeather {
+    CurrentWeather {
+        date,
+        ..weather.get_weather_data(date)
+    }
+}
+
+/// Creates the [`AdaptiveComfort`] evaluation, if the options ask for it
+fn adaptive_comfort(
+    model: &Model,
+    options: &SimOptions,
+    weather: &Weather,
+) -> Result<Option<AdaptiveComfort>, String> {
+    match &options.adaptive_comfort {
+        Some(_) => Ok(Some(AdaptiveComfort::new(model, &weather.data)?)),
+        None => Ok(None),
+    }
+}
+
+/// Writes the statistics of the [`AdaptiveComfort`] evaluation, if any
+fn write_adaptive_comfort(
+    options: &SimOptions,
+    adaptive: Option<AdaptiveComfort>,
+) -> Result<(), String> {
+    if let (Some(filename), Some(adaptive)) = (&options.adaptive_comfort, adaptive) {
+        let file = fs::File::create(filename)
+            .map_err(|e| format!("Could not create file '{}': {}", filename, e))?;
+        adaptive
+            .write_report(file)
+            .map_err(|e| format!("Could not write adaptive comfort to '{}': {}", filename, e))?;
+    }
+    Ok(())
+}
+
+/// Creates the [`EnergySummary`], if the options ask for it
+fn energy_summary(
+    model: &Model,
+    options: &SimOptions,
+    state_header: &SimulationStateHeader,
+) -> Option<EnergySummary> {
+    options
+        .energy_summary
+        .as_ref()
+        .map(|_| EnergySummary::new(model, state_header))
+}
+
+/// Writes the [`EnergySummary`], if any
+fn write_energy_summary(
+    options: &SimOptions,
+    summary: Option<EnergySummary>,
+) -> Result<(), String> {
+    if let (Some(filename), Some(summary)) = (&options.energy_summary, summary) {
+        let file = fs::File::create(filename)
+            .map_err(|e| format!("Could not create file '{}': {}", filename, e))?;
+        if filename.ends_with(".html") {
+            summary
+                .write_html(file)
+                .map_err(|e| format!("Could not write energy summary to '{}': {}", filename, e))?;
+        } else {
+            summary
+                .write_json(file)
+                .map_err(|e| format!("Could not write energy summary to '{}': {}", filename, e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Creates the [`PeakLoads`] evaluation, if the options ask for it
+fn peak_loads(
+    model: &Model,
+    options: &SimOptions,
+    state_header: &SimulationStateHeader,
+) -> Option<PeakLoads> {
+    options
+        .peak_loads
+        .as_ref()
+        .map(|_| PeakLoads::new(model, state_header))
+}
+
+/// Writes the [`PeakLoads`], if any
+fn write_peak_loads(options: &SimOptions, peaks: Option<PeakLoads>) -> Result<(), String> {
+    if let (Some(filename), Some(peaks)) = (&options.peak_loads, peaks) {
+        let file = fs::File::create(filename)
+            .map_err(|e| format!("Could not create file '{}': {}", filename, e))?;
+        peaks
+            .write_report(file)
+            .map_err(|e| format!("Could not write peak loads to '{}': {}", filename, e))?;
+    }
+    Ok(())
+}
+
+/// Creates the [`AnnualDaylight`] evaluation, if the options ask for it
+fn annual_daylight(
+    model: &Model,
+    options: &SimOptions,
+    meta_options: &MetaOptions,
+) -> Result<Option<AnnualDaylight>, String> {
+    if options.daylight_metrics.is_none() {
+        return Ok(None);
+    }
+    let mut grids = options
+        .sensor_grid
+        .iter()
+        .map(|f| SensorGrid::from_file(f))
+        .collect::<Result<Vec<SensorGrid>, String>>()?;
+    for grid in model.sensor_grids.iter() {
+        grids.push(SensorGrid {
+            name: grid.name().clone(),
+            sensors: grid.sensors()?,
+        });
+    }
+    if grids.is_empty() {
+        return Err("Daylight metrics were requested, but no sensor grid was given".to_string());
+    }
+    Ok(Some(AnnualDaylight::new(meta_options, model, grids)?))
+}
+
+/// Writes the metrics of the [`AnnualDaylight`] evaluation, if any
+fn write_annual_daylight(
+    options: &SimOptions,
+    daylight: Option<AnnualDaylight>,
+) -> Result<(), String> {
+    if let (Some(filename), Some(daylight)) = (&options.daylight_metrics, daylight) {
+        let file = fs::File::create(filename)
+            .map_err(|e| format!("Could not create file '{}': {}", filename, e))?;
+        daylight
+            .write_report(file)
+            .map_err(|e| format!("Could not write daylight metrics to '{}': {}", filename, e))?;
+    }
+    Ok(())
+}
+
 /// This function drives the simulation, after having parsed and built
 /// the Building, State and Peoeple.
 pub fn run<T, C, M>(
     model: M,
     state_header: &mut SimulationStateHeader,
     options: &SimOptions,
-    mut out: T,
+    out: T,
     controller: C,
-) -> Result<(), String>
+) -> Result<(), SimpleError>
 where
     T: std::io::Write,
     C: SimpleControl,
     M: Borrow<Model>,
 {
-    let pre_process_data = pre_process(model.borrow(), options, state_header)?;
+    let mut reporter = StderrProgress::new(options.quiet);
+    run_with_progress(
+        model,
+        state_header,
+        options,
+        out,
+        controller,
+        &mut reporter,
+        &CancellationToken::default(),
+    )
+}
+
+/// Like [`run`], but reporting the progress of the simulation to a
+/// [`ProgressReporter`] and aborting it—returning an error—as soon as
+/// the `cancel` token is cancelled.
+pub fn run_with_progress<T, C, M, P>(
+    model: M,
+    state_header: &mut SimulationStateHeader,
+    options: &SimOptions,
+    out: T,
+    controller: C,
+    reporter: &mut P,
+    cancel: &CancellationToken,
+) -> Result<(), SimpleError>
+where
+    T: std::io::Write,
+    C: SimpleControl,
+    M: Borrow<Model>,
+    P: ProgressReporter + ?Sized,
+{
+    simulate(
+        model,
+        state_header,
+        options,
+        out,
+        controller,
+        reporter,
+        cancel,
+        None,
+    )
+}
 
+/// Like [`run`], but accumulating the results in a [`Recorder`] instead
+/// of writing them, so that they can be used programmatically. Any data
+/// previously held by the `recorder` is removed.
+pub fn run_with_recorder<C, M>(
+    model: M,
+    state_header: &mut SimulationStateHeader,
+    options: &SimOptions,
+    controller: C,
+    recorder: &mut Recorder,
+) -> Result<(), SimpleError>
+where
+    C: SimpleControl,
+    M: Borrow<Model>,
+{
+    let mut reporter = StderrProgress::new(options.quiet);
+    simulate(
+        model,
+        state_header,
+        options,
+        std::io::sink(),
+        controller,
+        &mut reporter,
+        &CancellationToken::default(),
+        Some(recorder),
+    )
+}
+
+/// Like [`run`], but returning the whole [`SimulationState`](model::SimulationState)
+/// at every timestep as [`SimulationResults`], which can be queried by
+/// what the results represent instead of by their position.
+pub fn run_collect<C, M>(
+    model: M,
+    state_header: &mut SimulationStateHeader,
+    options: &SimOptions,
+    controller: C,
+) -> Result<SimulationResults, SimpleError>
+where
+    C: SimpleControl,
+    M: Borrow<Model>,
+{
+    let mut recorder = Recorder::whole_state();
+    run_with_recorder(model, state_header, options, controller, &mut recorder)?;
+    Ok(SimulationResults::new(recorder))
+}
+
+/// Like [`run_collect`], but splitting the simulation period into (at most)
+/// `shards` chunks of whole days that are simulated in parallel—when the
+/// `parallel` feature is enabled—and stitched together afterwards.
+///
+/// All chunks start from the state reached after a single, shared warm-up.
+/// Every chunk but the first also simulates the day before it starts—without
+/// recording it—so that it does not begin from the conditions of the first
+/// day of the simulation. The state is therefore not exactly continuous
+/// across the boundaries of the chunks, which is acceptable when what
+/// matters is the overall behaviour (e.g., in parametric studies, or when
+/// only the daylight is of interest) rather than every timestep.
+///
+/// Each chunk simulates its own copy of the model (see
+/// [`Model::deep_clone`]) and builds its own physics, so models whose
+/// physics are expensive to build (e.g., those with a lot of solar
+/// calculations) benefit less from sharding. No checkpoints or reports are
+/// written.
+pub fn run_sharded<C>(
+    model: &Model,
+    state_header: &mut SimulationStateHeader,
+    options: &SimOptions,
+    controller: C,
+    shards: usize,
+) -> Result<SimulationResults, SimpleError>
+where
+    C: SimpleControl + Sync,
+{
+    if shards == 0 {
+        return Err("The number of shards should be larger than 0".into());
+    }
+    // The copies simulated by the chunks are taken from this one, before
+    // the physics of the warm-up assign the indexes of the original
+    let pristine = (model.deep_clone()?, state_header.clone());
+    let pre_process_data = pre_process(model, options, state_header)?;
     let mut state = state_header
         .take_values()
         .ok_or("Could not take values from SimulationStateHeader")?;
+    let mut memory = pre_process_data.model.allocate_memory(&state)?;
 
-    let report_len = if model.borrow().outputs.is_empty() {
-        state_header.elements.len()
-    } else {
-        model.borrow().outputs.len()
+    if !pre_process_data.resumed {
+        warm_up(options, pre_process_data.warmup_period, |date| {
+            controller.control(model, &pre_process_data.model, &mut state)?;
+            march(
+                model,
+                &pre_process_data.model,
+                &pre_process_data.weather,
+                date,
+                pre_process_data.dt,
+                &mut state,
+                &mut memory,
+            )?;
+            Ok(pre_process_data
+                .temperature_indexes
+                .iter()
+                .map(|i| state[*i])
+                .collect())
+        })?;
+    }
+
+    let dt = pre_process_data.dt;
+    let n = (60. * 60. / dt).round() as usize;
+    let dates: Vec<Date> = pre_process_data.sim_period.collect();
+    let ranges = shard_ranges(dates.len(), shards, 24 * n);
+
+    let meta_options = &pre_process_data.meta_options;
+    let weather = &pre_process_data.weather;
+    let controller = &controller;
+    let state = &state;
+    let dates = &dates;
+    let pristine = &pristine;
+
+    #[cfg(feature = "parallel")]
+    let ranges = ranges.into_par_iter();
+    #[cfg(not(feature = "parallel"))]
+    let ranges = ranges.into_iter();
+
+    let recorders = ranges
+        .map(|(lead_in, range)| {
+            simulate_shard(
+                &pristine.0,
+                &pristine.1,
+                state,
+                meta_options,
+                weather,
+                n,
+                controller,
+                &dates[lead_in],
+                &dates[range],
+            )
+        })
+        .collect::<Result<Vec<Recorder>, SimpleError>>()?;
+
+    let mut recorders = recorders.into_iter();
+    let mut recorder = match recorders.next() {
+        Some(r) => r,
+        None => {
+            let mut r = Recorder::whole_state();
+            r.start(model, state_header)?;
+            r
+        }
     };
+    for r in recorders {
+        recorder.append(r)?;
+    }
+    Ok(SimulationResults::new(recorder))
+}
 
-    let mut memory = pre_process_data.model.allocate_memory(&state)?;
+/// Splits `n` timesteps into (at most) `shards` contiguous chunks whose
+/// length is a multiple of `day` timesteps. Returns the range of each
+/// chunk, preceded by the range of the (up to) `day` timesteps before it.
+fn shard_ranges(n: usize, shards: usize, day: usize) -> Vec<(Range<usize>, Range<usize>)> {
+    let day = day.max(1);
+    let len = n.div_ceil(shards.max(1)).div_ceil(day).max(1) * day;
+    (0..n)
+        .step_by(len)
+        .map(|start| {
+            (
+                start.saturating_sub(day)..start,
+                start..(start + len).min(n),
+            )
+        })
+        .collect()
+}
 
-    // Write header
-    let _u = out
-        .write(b"Date,")
-        .expect("Could not write to output file (header => 'Date').");
-    for (index, i) in pre_process_data.report_indexes.iter().enumerate() {
-        let s: &String = &pre_process_data.full_header[*i];
-        if index < report_len - 1 {
-            let s = format!("{s},");
-            let _u = out
-                .write(s.as_bytes())
-                .expect("Could not write to output file (header).");
-        } else {
-            let _u = out
-                .write(s.as_bytes())
-                .expect("Could not write to output file (header).");
+/// Simulates one of the chunks of [`run_sharded`] on its own copy of the
+/// `model`—whose physics have not been built—starting from the warmed-up
+/// `initial_state`. The `lead_in` timesteps are simulated before the
+/// `dates` of the chunk, but not recorded.
+#[allow(clippy::too_many_arguments)]
+fn simulate_shard<C: SimpleControl>(
+    model: &Model,
+    state_header: &SimulationStateHeader,
+    initial_state: &SimulationState,
+    meta_options: &MetaOptions,
+    weather: &Weather,
+    n: usize,
+    controller: &C,
+    lead_in: &[Date],
+    dates: &[Date],
+) -> Result<Recorder, SimpleError> {
+    let model = model.deep_clone()?;
+    let mut state_header = state_header.clone();
+    let physics = MultiphysicsModel::new(meta_options, (), &model, &mut state_header, n)?;
+    if state_header.elements.len() != initial_state.len() {
+        return Err(SimpleError::Other(format!(
+            "The state of a shard has {} elements, but the warmed-up state has {}",
+            state_header.elements.len(),
+            initial_state.len()
+        )));
+    }
+    let mut recorder = Recorder::whole_state();
+    recorder.start(&model, &state_header)?;
+
+    let dt = 60. * 60. / n as Float;
+    let mut state = initial_state.clone();
+    let mut memory = physics.allocate_memory(&state)?;
+    for (i, date) in lead_in.iter().chain(dates.iter()).enumerate() {
+        controller.control(&model, &physics, &mut state)?;
+        march(
+            &model,
+            &physics,
+            weather,
+            *date,
+            dt,
+            &mut state,
+            &mut memory,
+        )?;
+        if i >= lead_in.len() {
+            recorder.record(meta_options.report_date(*date), dt, &state);
         }
     }
-    let _u = out
-        .write(b"\n")
-        .expect("Could not write to output file (header newline).");
+    Ok(recorder)
+}
+
+/// Drives the simulation, writing the results to `out` and—if
+/// given—recording them in a [`Recorder`]
+#[allow(clippy::too_many_arguments)]
+fn simulate<T, C, M, P>(
+    model: M,
+    state_header: &mut SimulationStateHeader,
+    options: &SimOptions,
+    mut out: T,
+    controller: C,
+    reporter: &mut P,
+    cancel: &CancellationToken,
+    mut recorder: Option<&mut Recorder>,
+) -> Result<(), SimpleError>
+where
+    T: std::io::Write,
+    C: SimpleControl,
+    M: Borrow<Model>,
+    P: ProgressReporter + ?Sized,
+{
+    let pre_process_data = pre_process(model.borrow(), options, state_header)?;
+    if let Some(recorder) = &mut recorder {
+        recorder.start(model.borrow(), state_header)?;
+    }
+
+    let mut state = state_header
+        .take_values()
+        .ok_or("Could not take values from SimulationStateHeader")?;
+
+    let mut writer = ResultsWriter::new(model.borrow(), state_header, &pre_process_data)?;
+    let mut memory = pre_process_data.model.allocate_memory(&state)?;
+
+    if !pre_process_data.resumed {
+        warm_up(options, pre_process_data.warmup_period, |date| {
+            cancel.check()?;
+            controller.control(model.borrow(), &pre_process_data.model, &mut state)?;
+            march(
+                model.borrow(),
+                &pre_process_data.model,
+                &pre_process_data.weather,
+                date,
+                pre_process_data.dt,
+                &mut state,
+                &mut memory,
+            )?;
+            Ok(pre_process_data
+                .temperature_indexes
+                .iter()
+                .map(|i| state[*i])
+                .collect())
+        })?;
+    }
+
+    let mut adaptive = adaptive_comfort(model.borrow(), options, &pre_process_data.weather)?;
+    let mut daylight = annual_daylight(model.borrow(), options, &pre_process_data.meta_options)?;
+    let mut energy = energy_summary(model.borrow(), options, state_header);
+    let mut peaks = peak_loads(model.borrow(), options, state_header);
+
+    writer.write_header(&mut out)?;
 
     /* ************************************ */
     /* SIMULATE THE WHOLE SIMULATION PERIOD */
     /* ************************************ */
-    let mut last_reported_month: u8 = u8::MAX;
+    let mut progress = ProgressTracker::new(pre_process_data.sim_period, reporter);
+    let mut last_date = None;
     for date in pre_process_data.sim_period {
-        if date.month != last_reported_month {
-            last_reported_month = date.month;
-            eprintln!("  ... Simulating month {}", last_reported_month);
-        }
-
+        cancel.check()?;
         controller.control(model.borrow(), &pre_process_data.model, &mut state)?;
-
-        // Physics
-        pre_process_data.model.march(
-            date,
-            &pre_process_data.weather,
+        march(
             model.borrow(),
+            &pre_process_data.model,
+            &pre_process_data.weather,
+            date,
+            pre_process_data.dt,
             &mut state,
             &mut memory,
         )?;
-
-        // Print all the values in the state
-        let ds = format!("{},", date);
-        let _u = out
-            .write(ds.as_bytes())
-            .unwrap_or_else(|_| panic!("Could not write to output file (Date '{}')", date));
-
-        for (index, i) in pre_process_data.report_indexes.iter().enumerate() {
-            let st = if index < report_len - 1 {
-                format!("{:.3},", state[*i])
-            } else {
-                format!("{:.3}", state[*i])
-            };
-            let _u = out
-                .write(st.as_bytes())
-                .expect("Could not write to output file (data)");
+        if let Some(adaptive) = &mut adaptive {
+            adaptive.record(date, pre_process_data.dt, &state)?;
         }
-        let _u = out
-            .write(b"\n")
-            .expect("Could not write to output file (newline)");
+        if let Some(daylight) = &mut daylight {
+            let weather_data = pre_process_data.weather.get_weather_data(date);
+            daylight.record(date, pre_process_data.dt, weather_data)?;
+        }
+        if let Some(energy) = &mut energy {
+            energy.record(date, pre_process_data.dt, &state);
+        }
+        let report_date = pre_process_data.meta_options.report_date(date);
+        if let Some(peaks) = &mut peaks {
+            peaks.record(report_date, &state);
+        }
+        if let Some(recorder) = &mut recorder {
+            recorder.record(report_date, pre_process_data.dt, &state);
+        }
+
+        writer.record(report_date, &state, &mut out)?;
+        progress.step(date);
+        save_checkpoint(
+            options,
+            &pre_process_data,
+            model.borrow(),
+            state_header,
+            date,
+            &state,
+            false,
+        )?;
+        last_date = Some(date);
+    }
+    progress.finish();
+    writer.finish(&mut out)?;
+    if let Some(date) = last_date {
+        save_checkpoint(
+            options,
+            &pre_process_data,
+            model.borrow(),
+            state_header,
+            date,
+            &state,
+            true,
+        )?;
     }
 
+    write_adaptive_comfort(options, adaptive)?;
+    write_energy_summary(options, energy)?;
+    write_peak_loads(options, peaks)?;
+    write_annual_daylight(options, daylight)?;
     Ok(())
 }
 
 /// This function drives the simulation, after having parsed and built
 /// the Building, State and Peoeple.
 pub fn run_rhai<T>(
+    model: Model,
+    state_header: &mut SimulationStateHeader,
+    options: &SimOptions,
+    control_file: &String,
+    out: T,
+) -> Result<(), SimpleError>
+where
+    T: std::io::Write,
+{
+    let mut reporter = StderrProgress::new(options.quiet);
+    run_rhai_with_progress(
+        model,
+        state_header,
+        options,
+        control_file,
+        out,
+        &mut reporter,
+        &CancellationToken::default(),
+    )
+}
+
+/// Like [`run_rhai`], but reporting the progress of the simulation to a
+/// [`ProgressReporter`] and aborting it—returning an error—as soon as
+/// the `cancel` token is cancelled.
+pub fn run_rhai_with_progress<T, P>(
     model: Model,
     state_header: &mut SimulationStateHeader,
     options: &SimOptions,
     control_file: &String,
     mut out: T,
-) -> Result<(), String>
+    reporter: &mut P,
+    cancel: &CancellationToken,
+) -> Result<(), SimpleError>
 where
     T: std::io::Write,
+    P: ProgressReporter + ?Sized,
 {
     let model = std::sync::Arc::new(model);
 
@@ -289,87 +1150,296 @@ where
         .take_values()
         .ok_or("Could not take values from SimulationStateHeader")?;
     let mut memory = pre_process_data.model.allocate_memory(&state)?;
+    let mut adaptive = adaptive_comfort(&model, options, &pre_process_data.weather)?;
+    let mut daylight = annual_daylight(&model, options, &pre_process_data.meta_options)?;
+    let mut energy = energy_summary(&model, options, state_header);
+    let mut peaks = peak_loads(&model, options, state_header);
 
     let (controller, state) =
         RhaiControlScript::from_file(&model, state, control_file, options.research_mode)?;
 
-    let report_len = if model.outputs.is_empty() {
-        state_header.elements.len()
-    } else {
-        model.outputs.len()
-    };
+    if !pre_process_data.resumed {
+        warm_up(options, pre_process_data.warmup_period, |date| {
+            cancel.check()?;
+            controller.set_weather(current_weather(&pre_process_data.weather, date))?;
+            controller.control()?;
 
-    // Write header
-    let _u = out
-        .write(b"Date,")
-        .expect("Could not write to output file (header => 'Date').");
-    for (index, i) in pre_process_data.report_indexes.iter().enumerate() {
-        let s: &String = &pre_process_data.full_header[*i];
-        if index < report_len - 1 {
-            let s = format!("{s},");
-            let _u = out
-                .write(s.as_bytes())
-                .expect("Could not write to output file (header).");
-        } else {
-            let _u = out
-                .write(s.as_bytes())
-                .expect("Could not write to output file (header).");
-        }
+            let mut state_lock = (*state).lock().unwrap();
+            march(
+                &model,
+                &pre_process_data.model,
+                &pre_process_data.weather,
+                date,
+                pre_process_data.dt,
+                &mut state_lock,
+                &mut memory,
+            )?;
+            Ok(pre_process_data
+                .temperature_indexes
+                .iter()
+                .map(|i| state_lock[*i])
+                .collect())
+        })?;
     }
-    let _u = out
-        .write(b"\n")
-        .expect("Could not write to output file (header newline).");
+
+    let mut writer = ResultsWriter::new(&model, state_header, &pre_process_data)?;
+
+    writer.write_header(&mut out)?;
 
     /* ************************************ */
     /* SIMULATE THE WHOLE SIMULATION PERIOD */
     /* ************************************ */
-    let mut last_reported_month: u8 = 99;
-
+    let mut progress = ProgressTracker::new(pre_process_data.sim_period, reporter);
+    let mut last_date = None;
     for date in pre_process_data.sim_period {
-        if date.month != last_reported_month {
-            last_reported_month = date.month;
-            eprintln!("  ... Simulating month {}", last_reported_month);
-        }
-
+        cancel.check()?;
+        controller.set_weather(current_weather(&pre_process_data.weather, date))?;
         controller.control()?;
 
         let mut state_lock = (*state).lock().unwrap();
-        // Physics
-        // let model = model.as_ref();
-        // let mut state = (*state).borrow_mut();
-        pre_process_data.model.march(
-            date,
+        march(
+            &model,
+            &pre_process_data.model,
             &pre_process_data.weather,
-            model.borrow(),
+            date,
+            pre_process_data.dt,
             &mut state_lock,
             &mut memory,
         )?;
-
-        // Print all the values in the state
-        let ds = format!("{},", date);
-        let _u = out
-            .write(ds.as_bytes())
-            .unwrap_or_else(|_| panic!("Could not write to output file (Date '{}')", date));
-
-        for (index, i) in pre_process_data.report_indexes.iter().enumerate() {
-            let st = if index < report_len - 1 {
-                format!("{:.3},", state_lock[*i])
-            } else {
-                format!("{:.3}", state_lock[*i])
-            };
-            let _u = out
-                .write(st.as_bytes())
-                .expect("Could not write to output file (data)");
+        if let Some(adaptive) = &mut adaptive {
+            adaptive.record(date, pre_process_data.dt, &state_lock)?;
+        }
+        if let Some(daylight) = &mut daylight {
+            let weather_data = pre_process_data.weather.get_weather_data(date);
+            daylight.record(date, pre_process_data.dt, weather_data)?;
+        }
+        if let Some(energy) = &mut energy {
+            energy.record(date, pre_process_data.dt, &state_lock);
         }
+        let report_date = pre_process_data.meta_options.report_date(date);
+        if let Some(peaks) = &mut peaks {
+            peaks.record(report_date, &state_lock);
+        }
+
+        writer.record(report_date, &state_lock, &mut out)?;
+        save_checkpoint(
+            options,
+            &pre_process_data,
+            &model,
+            state_header,
+            date,
+            &state_lock,
+            false,
+        )?;
         std::mem::drop(state_lock); // drop mutex
-        let _u = out
-            .write(b"\n")
-            .expect("Could not write to output file (newline)");
+        progress.step(date);
+        last_date = Some(date);
+    }
+    progress.finish();
+    writer.finish(&mut out)?;
+    if let Some(date) = last_date {
+        let state_lock = (*state).lock().unwrap();
+        save_checkpoint(
+            options,
+            &pre_process_data,
+            &model,
+            state_header,
+            date,
+            &state_lock,
+            true,
+        )?;
     }
 
+    write_adaptive_comfort(options, adaptive)?;
+    write_energy_summary(options, energy)?;
+    write_peak_loads(options, peaks)?;
+    write_annual_daylight(options, daylight)?;
     Ok(())
 }
 
 /***********/
 /* TESTING */
 /***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use crate::VoidControl;
+
+    #[test]
+    fn test_warm_up() -> Result<(), String> {
+        // A day of 6-hour timesteps
+        let start = Date {
+            month: 1,
+            day: 1,
+            hour: 0.,
+            year: None,
+        };
+        let mut end = start;
+        end.add_hours(23.9);
+        let period = Period::new(start, end, 6. * 60. * 60.);
+        let steps = period.count();
+
+        // Temperatures halve their distance to 20C every day
+        let mut options = SimOptions {
+            warmup_days: 30,
+            quiet: true,
+            ..SimOptions::default()
+        };
+        let mut t = 10.;
+        let mut n = 0;
+        let days = warm_up(&options, period, |_| {
+            n += 1;
+            // Only the temperatures at the end of the day are compared
+            if n % steps == 0 {
+                t = 20. - (20. - t) / 2.;
+            }
+            Ok(vec![t, 2. * t])
+        })?;
+        // The 2nd space changes by 5, 2.5, 1.25 ... 0.078 < 0.1
+        assert_eq!(days, 8);
+        assert_eq!(n, steps * 8);
+
+        // Converges as soon as two days can be compared
+        options.warmup_days = 3;
+        let days = warm_up(&options, period, |_| Ok(vec![0.]))?;
+        assert_eq!(days, 2);
+
+        // Never converges
+        let mut t = 0.;
+        let days = warm_up(&options, period, |_| {
+            t += 1.;
+            Ok(vec![t])
+        })?;
+        assert_eq!(days, 3);
+
+        // Errors are propagated
+        assert!(warm_up(&options, period, |_| Err("Failed".to_string())).is_err());
+
+        // No warm-up at all
+        options.warmup_days = 0;
+        let days = warm_up(
+            &options,
+            period,
+            |_| Err("Should not be called".to_string()),
+        )?;
+        assert_eq!(days, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_sharded() -> Result<(), String> {
+        let load = || Model::from_file("./tests/box/box.spl");
+        let date = |day| Date {
+            month: 1,
+            day,
+            hour: 0.,
+            year: None,
+        };
+        let options = SimOptions {
+            weather_file: Some("./tests/wellington.epw".into()),
+            start_date: Some(date(1)),
+            end_date: Some(date(5)),
+            n: 1,
+            warmup_days: 2,
+            quiet: true,
+            ..SimOptions::default()
+        };
+
+        let (model, mut header) = load()?;
+        let exp = run_collect(&model, &mut header, &options, VoidControl {})?;
+
+        // Chunks of two days, simulated on copies of the model (the
+        // warm-up is simulated on the original one)
+        let (model, mut header) = load()?;
+        let found = run_sharded(&model, &mut header, &options, VoidControl {}, 2)?;
+        assert!(model.surfaces[0].first_node_temperature_index().is_some());
+        assert_eq!(exp.header(), found.header());
+        assert!(exp.len() > 48);
+        assert_eq!(exp.len(), found.len());
+
+        for (a, b) in exp.dates().zip(found.dates()) {
+            assert_eq!(a, b);
+        }
+        let space = model.spaces[0].name();
+        let temperatures = found.space_temperature(space).ok_or("No temperature")?;
+        assert!(temperatures.iter().all(|t| t.is_finite()));
+
+        assert!(run_sharded(&model, &mut header, &options, VoidControl {}, 0).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_weather() -> Result<(), SimpleError> {
+        let weather = load_weather("./tests/wellington.epw")?;
+        assert!(!weather.data.is_empty());
+
+        assert!(matches!(
+            load_weather("./tests/wellington.pdf"),
+            Err(SimpleError::Parse(_))
+        ));
+        assert!(matches!(
+            load_weather("./tests/nowhere.epw"),
+            Err(SimpleError::Io(_))
+        ));
+        assert!(matches!(
+            weather_from_bytes(b"{", "sw"),
+            Err(SimpleError::Parse(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_shard_ranges() {
+        // 10 timesteps, days of 2 timesteps
+        assert_eq!(
+            shard_ranges(10, 3, 2),
+            vec![(0..0, 0..4), (2..4, 4..8), (6..8, 8..10)]
+        );
+        // Chunks are made of whole days
+        assert_eq!(shard_ranges(10, 3, 3), vec![(0..0, 0..6), (3..6, 6..10)]);
+        // More shards than days
+        assert_eq!(shard_ranges(4, 10, 2), vec![(0..0, 0..2), (0..2, 2..4)]);
+        // A single shard
+        assert_eq!(shard_ranges(5, 1, 2), vec![(0..0, 0..5)]);
+        // Nothing to simulate
+        assert!(shard_ranges(0, 4, 24).is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "cli")]
+    fn test_parse_date() -> Result<(), String> {
+        let date = parse_date("01/15")?;
+        assert_eq!(date.month, 1);
+        assert_eq!(date.day, 15);
+        assert_eq!(date.hour, 0.);
+
+        let date = parse_date("12/31")?;
+        assert_eq!(date.month, 12);
+        assert_eq!(date.day, 31);
+        assert!(date.year.is_none());
+
+        let date = parse_date("2020/03/01")?;
+        assert_eq!(date.year, Some(2020));
+        assert_eq!(date.month, 3);
+        assert_eq!(date.day, 1);
+        assert!(parse_date("20a0/03/01").is_err());
+
+        // Leap years
+        assert_eq!(parse_date("2020/02/29")?.day, 29);
+        assert!(parse_date("2021/02/29").is_err());
+
+        assert!(parse_date("02/29").is_err());
+        assert!(parse_date("13/01").is_err());
+        assert!(parse_date("01/00").is_err());
+        assert!(parse_date("0115").is_err());
+        assert!(parse_date("Jan/15").is_err());
+
+        // ISO 8601
+        let date = parse_date("2020-03-01")?;
+        assert_eq!((date.year, date.month, date.day), (Some(2020), 3, 1));
+        assert_eq!(parse_date("--01-15")?.year, None);
+        assert!(parse_date("2020-3-1").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,363 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Sampling and sensitivity analysis utilities, built on top of
+//! [`ParametricStudy`].
+//!
+//! The parameters of the study are treated as continuous factors, whose
+//! range goes from the smallest to the largest of their values. Samples
+//! are simulated through [`ParametricStudy::run_samples`], and the
+//! sensitivity of each metric to each factor is calculated out of the
+//! resulting [`ParametricResults`].
+//!
+//! ```no_run
+//! use simple::parametrics::{Metric, Parameter, ParametricStudy};
+//! use simple::run_simulation::SimOptions;
+//! use simple::sensitivity::{latin_hypercube, correlations, MorrisDesign};
+//! use simple::Model;
+//!
+//! let (base, _) = Model::from_file("./tests/box/box.spl").unwrap();
+//! let metric = Metric::mean_space_temperature("Bedroom");
+//! let name = metric.name().to_string();
+//! let study = ParametricStudy::new(&base)
+//!     .unwrap()
+//!     .parameter(Parameter::material_thickness("R13LAYER", vec![0.02, 0.2]))
+//!     .parameter(Parameter::orientation(vec![0., 360.]))
+//!     .metric(metric);
+//!
+//! let options = SimOptions {
+//!     weather_file: Some("./tests/wellington.epw".into()),
+//!     quiet: true,
+//!     ..SimOptions::default()
+//! };
+//!
+//! // Uncertainty analysis
+//! let samples = latin_hypercube(&study, 50, 0).unwrap();
+//! let results = study.run_samples(samples, &options).unwrap();
+//! println!("{:?}", correlations(&results, &name).unwrap());
+//!
+//! // Screening
+//! let design = MorrisDesign::new(&study, 10, 4, 0).unwrap();
+//! let results = study.run_samples(design.points().to_vec(), &options).unwrap();
+//! for effects in design.elementary_effects(&results, &name).unwrap() {
+//!     println!("{:?}", effects);
+//! }
+//! ```
+
+use crate::parametrics::{ParametricResults, ParametricStudy};
+use crate::Float;
+use rendering::rand::{RandGen, Rng, SeedableRng, SliceRandom};
+
+/// The range of each of the parameters of a study (i.e., their
+/// smallest and largest values)
+fn ranges(study: &ParametricStudy) -> Result<Vec<(Float, Float)>, String> {
+    study
+        .parameters()
+        .iter()
+        .map(|p| {
+            let values = p.values();
+            if values.is_empty() {
+                return Err(format!("Parameter '{}' has no values", p.name()));
+            }
+            let min = values.iter().copied().fold(Float::INFINITY, Float::min);
+            let max = values.iter().copied().fold(Float::NEG_INFINITY, Float::max);
+            Ok((min, max))
+        })
+        .collect()
+}
+
+/// The values of a metric for each of the rows of some results
+fn outputs(results: &ParametricResults, metric: &str) -> Result<Vec<Float>, String> {
+    results
+        .metric(metric)
+        .ok_or_else(|| format!("There are no results for metric '{}'", metric))
+}
+
+/// Draws a number of `samples` of the parameters of a study through Latin
+/// Hypercube Sampling: the range of each parameter is divided into as many
+/// intervals as samples, and each interval is sampled exactly once. The
+/// samples are reproducible through a `seed`.
+pub fn latin_hypercube(
+    study: &ParametricStudy,
+    samples: usize,
+    seed: u64,
+) -> Result<Vec<Vec<Float>>, String> {
+    let ranges = ranges(study)?;
+    let mut rng = RandGen::seed_from_u64(seed);
+    let columns: Vec<Vec<Float>> = ranges
+        .iter()
+        .map(|(min, max)| {
+            let mut intervals: Vec<usize> = (0..samples).collect();
+            intervals.shuffle(&mut rng);
+            intervals
+                .iter()
+                .map(|i| min + (max - min) * (*i as Float + rng.gen::<Float>()) / samples as Float)
+                .collect()
+        })
+        .collect();
+    Ok((0..samples)
+        .map(|i| columns.iter().map(|c| c[i]).collect())
+        .collect())
+}
+
+/// The Pearson correlation coefficient between two series, or zero
+/// if either of them is constant
+fn pearson(x: &[Float], y: &[Float]) -> Float {
+    let n = x.len().min(y.len()) as Float;
+    if n == 0. {
+        return 0.;
+    }
+    let mean_x = x.iter().sum::<Float>() / n;
+    let mean_y = y.iter().sum::<Float>() / n;
+    let (mut sxy, mut sxx, mut syy) = (0., 0., 0.);
+    for (a, b) in x.iter().zip(y.iter()) {
+        sxy += (a - mean_x) * (b - mean_y);
+        sxx += (a - mean_x).powi(2);
+        syy += (b - mean_y).powi(2);
+    }
+    if sxx <= 0. || syy <= 0. {
+        0.
+    } else {
+        sxy / (sxx * syy).sqrt()
+    }
+}
+
+/// The Pearson correlation coefficient between each of the parameters and
+/// a metric, over some results (usually, of a [`latin_hypercube`] sample)
+pub fn correlations(results: &ParametricResults, metric: &str) -> Result<Vec<Float>, String> {
+    let y = outputs(results, metric)?;
+    let n_parameters = results.rows().first().map_or(0, |r| r.parameters.len());
+    Ok((0..n_parameters)
+        .map(|i| {
+            let x: Vec<Float> = results.rows().iter().map(|r| r.parameters[i]).collect();
+            pearson(&x, &y)
+        })
+        .collect())
+}
+
+/// The statistics of the elementary effects of a parameter, as calculated
+/// by the Morris method. Effects are given in units of the metric per
+/// whole range of the parameter.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ElementaryEffects {
+    /// The mean of the elementary effects
+    pub mu: Float,
+
+    /// The mean of the absolute value of the elementary effects, which
+    /// measures the overall influence of the parameter
+    pub mu_star: Float,
+
+    /// The standard deviation of the elementary effects, which measures
+    /// non-linearities and interactions with other parameters
+    pub sigma: Float,
+}
+
+/// A sample of the parameters of a study for screening them through the
+/// Morris method (i.e., elementary effects).
+///
+/// The sample is made of a number of trajectories, each of which starts
+/// at a random point of a grid and then changes one parameter at a time,
+/// in random order, by a fixed step.
+#[derive(Debug, Clone)]
+pub struct MorrisDesign {
+    /// The values of the parameters at each point of each trajectory
+    points: Vec<Vec<Float>>,
+
+    /// The parameter changed at each step of each trajectory, and the
+    /// change, as a fraction of its range
+    steps: Vec<(usize, Float)>,
+}
+
+impl MorrisDesign {
+    /// Creates a number of `trajectories` over a grid of (an even number
+    /// of) `levels` per parameter, reproducible through a `seed`
+    pub fn new(
+        study: &ParametricStudy,
+        trajectories: usize,
+        levels: usize,
+        seed: u64,
+    ) -> Result<Self, String> {
+        if levels < 2 || levels % 2 != 0 {
+            return Err(format!(
+                "The Morris method needs an even number of levels... found {}",
+                levels
+            ));
+        }
+        let ranges = ranges(study)?;
+        let mut rng = RandGen::seed_from_u64(seed);
+        let delta = levels as Float / (2. * (levels - 1) as Float);
+        let to_values = |x: &[Float]| -> Vec<Float> {
+            x.iter()
+                .zip(ranges.iter())
+                .map(|(x, (min, max))| min + x * (max - min))
+                .collect()
+        };
+
+        let mut points = Vec::with_capacity(trajectories * (ranges.len() + 1));
+        let mut steps = Vec::with_capacity(trajectories * ranges.len());
+        for _ in 0..trajectories {
+            let mut x: Vec<Float> = ranges
+                .iter()
+                .map(|_| rng.gen_range(0..levels) as Float / (levels - 1) as Float)
+                .collect();
+            points.push(to_values(&x));
+            let mut order: Vec<usize> = (0..ranges.len()).collect();
+            order.shuffle(&mut rng);
+            for i in order {
+                // Levels in the lower half of the grid go up, the others go down
+                let step = if x[i] + delta <= 1. + 1e-6 {
+                    delta
+                } else {
+                    -delta
+                };
+                x[i] += step;
+                points.push(to_values(&x));
+                steps.push((i, step));
+            }
+        }
+        Ok(Self { points, steps })
+    }
+
+    /// The values of the parameters to simulate, in order (see
+    /// [`ParametricStudy::run_samples`])
+    pub fn points(&self) -> &[Vec<Float>] {
+        &self.points
+    }
+
+    /// Calculates the statistics of the elementary effects of each
+    /// parameter on some `outputs`, one per point of the design
+    fn effects(&self, outputs: &[Float]) -> Result<Vec<ElementaryEffects>, String> {
+        if outputs.len() != self.points.len() {
+            return Err(format!(
+                "The Morris design has {} points, but {} outputs were given",
+                self.points.len(),
+                outputs.len()
+            ));
+        }
+        let n_parameters = self.points.first().map_or(0, |p| p.len());
+        let mut effects: Vec<Vec<Float>> = vec![Vec::new(); n_parameters];
+        for (t, trajectory) in outputs.chunks(n_parameters + 1).enumerate() {
+            for (j, pair) in trajectory.windows(2).enumerate() {
+                let (i, step) = self.steps[t * n_parameters + j];
+                effects[i].push((pair[1] - pair[0]) / step);
+            }
+        }
+        Ok(effects
+            .iter()
+            .map(|e| {
+                let n = e.len() as Float;
+                if e.is_empty() {
+                    return ElementaryEffects {
+                        mu: 0.,
+                        mu_star: 0.,
+                        sigma: 0.,
+                    };
+                }
+                let mu = e.iter().sum::<Float>() / n;
+                let mu_star = e.iter().map(|v| v.abs()).sum::<Float>() / n;
+                let sigma = if e.len() > 1 {
+                    (e.iter().map(|v| (v - mu).powi(2)).sum::<Float>() / (n - 1.)).sqrt()
+                } else {
+                    0.
+                };
+                ElementaryEffects { mu, mu_star, sigma }
+            })
+            .collect())
+    }
+
+    /// Calculates the statistics of the elementary effects of each parameter
+    /// on a metric, out of the results of simulating the [`points`](Self::points)
+    /// of the design
+    pub fn elementary_effects(
+        &self,
+        results: &ParametricResults,
+        metric: &str,
+    ) -> Result<Vec<ElementaryEffects>, String> {
+        self.effects(&outputs(results, metric)?)
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use crate::parametrics::Parameter;
+    use model::Model;
+    use validate::assert_close;
+
+    fn study() -> Result<ParametricStudy, String> {
+        Ok(ParametricStudy::new(&Model::default())?
+            .parameter(Parameter::new("a", vec![0., 2.], |_, _| Ok(())))
+            .parameter(Parameter::new("b", vec![10., 5., 20.], |_, _| Ok(())))
+            .parameter(Parameter::new("c", vec![-1., 1.], |_, _| Ok(()))))
+    }
+
+    #[test]
+    fn test_latin_hypercube() -> Result<(), String> {
+        let samples = latin_hypercube(&study()?, 10, 0)?;
+        assert_eq!(samples.len(), 10);
+
+        // Every interval of every parameter is sampled once
+        let ranges = [(0., 2.), (5., 20.), (-1., 1.)];
+        for (i, (min, max)) in ranges.iter().enumerate() {
+            let mut intervals: Vec<usize> = samples
+                .iter()
+                .map(|s| ((s[i] - min) / (max - min) * 10.) as usize)
+                .collect();
+            intervals.sort();
+            assert_eq!(intervals, (0..10).collect::<Vec<usize>>());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_pearson() {
+        assert_close!(pearson(&[1., 2., 3.], &[2., 4., 6.]), 1.);
+        assert_close!(pearson(&[1., 2., 3.], &[3., 2., 1.]), -1.);
+        assert_close!(pearson(&[1., 2., 3.], &[1., 1., 1.]), 0.);
+    }
+
+    #[test]
+    fn test_morris() -> Result<(), String> {
+        assert!(MorrisDesign::new(&study()?, 5, 3, 0).is_err());
+
+        let design = MorrisDesign::new(&study()?, 6, 4, 1)?;
+        assert_eq!(design.points().len(), 6 * 4);
+
+        // Linear in 'a', not affected by 'b', and quadratic in 'c'
+        let outputs: Vec<Float> = design
+            .points()
+            .iter()
+            .map(|p| 3. * p[0] + p[2] * p[2])
+            .collect();
+        let effects = design.effects(&outputs)?;
+        assert_close!(effects[0].mu, 6.);
+        assert_close!(effects[0].mu_star, 6.);
+        assert_close!(effects[0].sigma, 0.);
+        assert_close!(effects[1].mu_star, 0.);
+        assert!(effects[2].mu_star > 0.);
+
+        assert!(design.effects(&outputs[1..]).is_err());
+        Ok(())
+    }
+}
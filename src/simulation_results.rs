@@ -0,0 +1,259 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::recorder::{RecordedTimestep, Recorder};
+use crate::Float;
+use calendar::Date;
+use model::Output;
+
+/// The results of a simulation, as returned by
+/// [`run_collect`](crate::run_simulation::run_collect).
+///
+/// The results are queried by what they represent (e.g., the temperature
+/// of a space, by its name) rather than by their position in the results,
+/// so code analysing them does not depend on the ordering of the columns.
+///
+/// ```no_run
+/// use simple::run_simulation::{run_collect, SimOptions};
+/// use simple::{Model, VoidControl};
+///
+/// let options = SimOptions {
+///     input_file: "./tests/box/box.spl".into(),
+///     weather_file: Some("./tests/wellington.epw".into()),
+///     n: 1,
+///     quiet: true,
+///     ..SimOptions::default()
+/// };
+/// let (model, mut header) = Model::from_file(&options.input_file).unwrap();
+/// let results = run_collect(&model, &mut header, &options, VoidControl {}).unwrap();
+///
+/// // Whole time series
+/// let temperatures = results.space_temperature("Bedroom").unwrap();
+/// assert_eq!(temperatures.len(), results.len());
+///
+/// // Timestep by timestep
+/// let warm_hours = results
+///     .timesteps()
+///     .filter(|t| t.space_temperature("Bedroom").unwrap() > 26.)
+///     .count();
+/// ```
+#[derive(Debug, Clone)]
+pub struct SimulationResults {
+    /// The recorded values
+    recorder: Recorder,
+}
+
+/// The results of a single timestep of a simulation
+#[derive(Debug, Clone, Copy)]
+pub struct TimestepResults<'a> {
+    /// The results this timestep belongs to
+    results: &'a SimulationResults,
+
+    /// The recorded timestep
+    timestep: &'a RecordedTimestep,
+}
+
+impl<'a> TimestepResults<'a> {
+    /// The date of the timestep, as it would be reported in the results
+    pub fn date(&self) -> Date {
+        self.timestep.date
+    }
+
+    /// The duration of the timestep, in seconds
+    pub fn dt(&self) -> Float {
+        self.timestep.dt
+    }
+
+    /// The value of an [`Output`] at this timestep, if it was recorded
+    pub fn output(&self, output: &Output) -> Option<Float> {
+        self.results.column(output).map(|i| self.timestep.values[i])
+    }
+
+    /// The dry bulb temperature of a space, in C
+    pub fn space_temperature(&self, space: &str) -> Option<Float> {
+        self.output(&Output::SpaceDryBulbTemperature(space.into()))
+    }
+
+    /// The power delivered by an HVAC system, in W (positive when
+    /// heating and negative when cooling)
+    pub fn hvac_power(&self, hvac: &str) -> Option<Float> {
+        self.output(&Output::HeatingCoolingPowerConsumption(hvac.into()))
+    }
+}
+
+impl SimulationResults {
+    /// Creates a new `SimulationResults` from the data held by a [`Recorder`]
+    pub fn new(recorder: Recorder) -> Self {
+        Self { recorder }
+    }
+
+    /// The [`Recorder`] holding the raw results
+    pub fn recorder(&self) -> &Recorder {
+        &self.recorder
+    }
+
+    /// The number of timesteps
+    pub fn len(&self) -> usize {
+        self.recorder.timesteps().len()
+    }
+
+    /// Checks whether no timesteps were recorded
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The name of each of the recorded elements, as they would be
+    /// written in the header of a CSV file
+    pub fn header(&self) -> &[String] {
+        self.recorder.header()
+    }
+
+    /// The dates of the timesteps
+    pub fn dates(&self) -> impl Iterator<Item = Date> + '_ {
+        self.recorder.timesteps().iter().map(|t| t.date)
+    }
+
+    /// Iterates over the results of each timestep
+    pub fn timesteps(&self) -> impl Iterator<Item = TimestepResults<'_>> {
+        self.recorder
+            .timesteps()
+            .iter()
+            .map(move |timestep| TimestepResults {
+                results: self,
+                timestep,
+            })
+    }
+
+    /// The position of an [`Output`] in the [`header`](Self::header), if
+    /// it was recorded
+    pub fn column(&self, output: &Output) -> Option<usize> {
+        let output = serde_json::to_string(output).ok()?;
+        self.header().iter().position(|x| x == &output)
+    }
+
+    /// The time series of an [`Output`], if it was recorded
+    pub fn output(&self, output: &Output) -> Option<Vec<Float>> {
+        self.recorder.series(self.column(output)?)
+    }
+
+    /// Integrates an [`Output`] representing a power (in W) over the
+    /// whole simulation, returning the energy in kWh
+    pub fn energy(&self, output: &Output) -> Option<Float> {
+        let i = self.column(output)?;
+        let joules: Float = self
+            .recorder
+            .timesteps()
+            .iter()
+            .map(|t| t.values[i] * t.dt)
+            .sum();
+        Some(joules / 3600. / 1000.)
+    }
+
+    /// The dry bulb temperature of a space, in C
+    pub fn space_temperature(&self, space: &str) -> Option<Vec<Float>> {
+        self.output(&Output::SpaceDryBulbTemperature(space.into()))
+    }
+
+    /// The power delivered by an HVAC system, in W (positive when
+    /// heating and negative when cooling)
+    pub fn hvac_power(&self, hvac: &str) -> Option<Vec<Float>> {
+        self.output(&Output::HeatingCoolingPowerConsumption(hvac.into()))
+    }
+
+    /// The net energy delivered by an HVAC system over the whole
+    /// simulation, in kWh (heating counts as positive and cooling
+    /// as negative)
+    pub fn hvac_energy(&self, hvac: &str) -> Option<Float> {
+        self.energy(&Output::HeatingCoolingPowerConsumption(hvac.into()))
+    }
+
+    /// The energy consumed by a luminaire over the whole simulation, in kWh
+    pub fn luminaire_energy(&self, luminaire: &str) -> Option<Float> {
+        self.energy(&Output::LuminairePowerConsumption(luminaire.into()))
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use model::hvac::IdealHeaterCooler;
+    use model::{Model, SimulationStateElement, Space, HVAC};
+    use validate::assert_close;
+
+    #[test]
+    fn test_queries() -> Result<(), String> {
+        let mut model = Model::default();
+        let hvac = model.add_hvac(IdealHeaterCooler::new("Heater").wrap())?;
+        let mut header = model.take_state().ok_or("No state")?;
+        let space = model.add_space(Space::new("Kitchen"));
+        let i = header.push(SimulationStateElement::SpaceDryBulbTemperature(0), 0.)?;
+        space.set_dry_bulb_temperature_index(i)?;
+
+        let mut recorder = Recorder::whole_state();
+        recorder.start(&model, &header)?;
+        let mut state = header.take_values().ok_or("Could not take values")?;
+        let mut date = Date {
+            month: 1,
+            day: 1,
+            hour: 0.,
+            year: None,
+        };
+        for i in 0..4 {
+            if let HVAC::IdealHeaterCooler(h) = &hvac {
+                h.set_heating_cooling_consumption(&mut state, 1000. * i as Float)?;
+            }
+            space.set_dry_bulb_temperature(&mut state, 20. + i as Float)?;
+            recorder.record(date, 1800., &state);
+            date.add_hours(0.5);
+        }
+
+        let results = SimulationResults::new(recorder);
+        assert_eq!(results.len(), 4);
+        assert_eq!(
+            results.space_temperature("Kitchen"),
+            Some(vec![20., 21., 22., 23.])
+        );
+        assert!(results.space_temperature("Bedroom").is_none());
+        assert_eq!(
+            results.hvac_power("Heater"),
+            Some(vec![0., 1000., 2000., 3000.])
+        );
+
+        // 6000 W during half an hour
+        assert_close!(results.hvac_energy("Heater").ok_or("No energy")?, 3.);
+        assert!(results.luminaire_energy("Heater").is_none());
+
+        // Timestep by timestep
+        let warm: Vec<Date> = results
+            .timesteps()
+            .filter(|t| t.space_temperature("Kitchen").unwrap_or(0.) > 21.5)
+            .map(|t| t.date())
+            .collect();
+        assert_eq!(warm.len(), 2);
+        assert_close!(warm[0].hour, 1.);
+        assert!(results.timesteps().all(|t| t.dt() == 1800.));
+        assert_eq!(results.dates().count(), 4);
+        Ok(())
+    }
+}
@@ -0,0 +1,342 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::control_trait::{Forecast, PredictionContext, PredictiveControl};
+use crate::multiphysics_model::MultiphysicsModelMemory;
+use crate::run_simulation::weather_from_bytes;
+use crate::{Float, MultiphysicsModel};
+use calendar::{Date, Period};
+use communication::{MetaOptions, SimulationModel};
+use model::{Model, SimulationState, SimulationStateHeader};
+use weather::{CurrentWeather, Weather, WeatherTrait};
+
+/// Simulates a timestep: applies the thermostats and schedules of
+/// the model, and marches the physics
+pub(crate) fn march<W: WeatherTrait>(
+    model: &Model,
+    physics: &MultiphysicsModel,
+    weather: &W,
+    date: Date,
+    dt: Float,
+    state: &mut SimulationState,
+    memory: &mut MultiphysicsModelMemory,
+) -> Result<(), String> {
+    model.apply_thermostats(date, dt, state)?;
+    model.apply_schedules(date, state)?;
+    physics.march(date, weather, model, state, memory)
+}
+
+/// A simulation that is advanced one timestep at a time by the caller,
+/// which can read and modify the [`SimulationState`] in between (e.g., for
+/// embedding `SIMPLE` in interactive tools or co-simulation environments).
+///
+/// Unlike [`run`](crate::run_simulation::run), a `Stepper` does not read
+/// or write any file, so it can be used where there is no file system
+/// (e.g., when compiling to WebAssembly).
+///
+/// ```no_run
+/// use simple::run_simulation::load_weather;
+/// use simple::{Model, Stepper};
+///
+/// let (model, header) = Model::from_file("./tests/box/box.spl").unwrap();
+/// let weather = load_weather("./tests/wellington.epw").unwrap();
+/// let mut stepper = Stepper::new(model, header, weather, 4).unwrap();
+///
+/// let i = stepper.index_of("{\"SpaceDryBulbTemperature\":\"ZONE ONE\"}").unwrap();
+/// while let Some(date) = stepper.step().unwrap() {
+///     println!("{} => {}", date, stepper.state()[i]);
+/// }
+/// ```
+pub struct Stepper {
+    /// The model being simulated
+    model: Model,
+
+    /// The physics of the model
+    physics: MultiphysicsModel,
+
+    /// The memory used by the physics model
+    memory: MultiphysicsModelMemory,
+
+    /// The weather driving the simulation
+    weather: Weather,
+
+    /// The current state of the simulation
+    state: SimulationState,
+
+    /// The name of each element of the state
+    header: Vec<String>,
+
+    /// The timesteps that have not been simulated yet
+    period: Period,
+
+    /// The last date that was simulated
+    date: Option<Date>,
+
+    /// The duration of each timestep, in seconds
+    dt: Float,
+}
+
+impl Stepper {
+    /// Prepares the simulation of a `model` through the whole period
+    /// covered by the `weather` data, with `n` timesteps per hour
+    pub fn new(
+        model: Model,
+        mut state_header: SimulationStateHeader,
+        mut weather: Weather,
+        n: usize,
+    ) -> Result<Self, String> {
+        if n == 0 {
+            return Err("Parameter 'n' should be larger than 0".to_string());
+        }
+        if weather.data.is_empty() {
+            return Err("The weather has no data".to_string());
+        }
+        weather.sort_data();
+        let dt = 60. * 60. / n as Float;
+
+        let meta_options = MetaOptions {
+            latitude: weather.location.latitude,
+            longitude: weather.location.longitude,
+            standard_meridian: (weather.location.timezone as Float * 15.).to_radians(),
+            elevation: weather.location.elevation,
+            daylight_saving: None,
+        };
+        let physics = MultiphysicsModel::new(&meta_options, (), &model, &mut state_header, n)?;
+
+        let start = weather.data[0].date;
+        let mut end = weather.data[weather.data.len() - 1].date;
+        end.hour -= 0.1;
+        let period = Period::new(start, end, dt);
+
+        let header = state_header
+            .elements
+            .iter()
+            .map(|x| x.stringify(&model))
+            .collect();
+        let state = state_header
+            .take_values()
+            .ok_or("Could not take values from SimulationStateHeader")?;
+        let memory = physics.allocate_memory(&state)?;
+
+        Ok(Self {
+            model,
+            physics,
+            memory,
+            weather,
+            state,
+            header,
+            period,
+            date: None,
+            dt,
+        })
+    }
+
+    /// Like [`Stepper::new`], but parsing the model (in `.spl` format)
+    /// and the weather (in a `weather_format` accepted by
+    /// [`weather_from_bytes`]) from their contents
+    pub fn from_bytes(
+        model: &[u8],
+        weather: &[u8],
+        weather_format: &str,
+        n: usize,
+    ) -> Result<Self, String> {
+        let (model, state_header) = Model::from_bytes(model)?;
+        let weather = weather_from_bytes(weather, weather_format)?;
+        Self::new(model, state_header, weather, n)
+    }
+
+    /// Simulates the next timestep—applying the thermostats and schedules
+    /// of the model first—and returns its date, or `None` if the whole
+    /// period has already been simulated
+    pub fn step(&mut self) -> Result<Option<Date>, String> {
+        let date = match self.period.next() {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+        march(
+            &self.model,
+            &self.physics,
+            &self.weather,
+            date,
+            self.dt,
+            &mut self.state,
+            &mut self.memory,
+        )?;
+        self.date = Some(date);
+        Ok(Some(date))
+    }
+
+    /// Like [`Stepper::step`], but the timestep is driven by another
+    /// `weather` (e.g., one whose values come from a co-simulation)
+    /// instead of by the weather given when creating the `Stepper`
+    pub fn step_with_weather<W: WeatherTrait>(
+        &mut self,
+        weather: &W,
+    ) -> Result<Option<Date>, String> {
+        let date = match self.period.next() {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+        march(
+            &self.model,
+            &self.physics,
+            weather,
+            date,
+            self.dt,
+            &mut self.state,
+            &mut self.memory,
+        )?;
+        self.date = Some(date);
+        Ok(Some(date))
+    }
+
+    /// Like [`Stepper::step`], but letting a [`PredictiveControl`] set
+    /// the actuators first. The controller is given a snapshot of the
+    /// current state and the weather over its horizon (and nothing
+    /// beyond it), so it can simulate forward before deciding.
+    pub fn step_with_controller<P: PredictiveControl>(
+        &mut self,
+        controller: &P,
+    ) -> Result<Option<Date>, String> {
+        let dates: Vec<Date> = self.period.take(controller.horizon().max(1)).collect();
+        if dates.is_empty() {
+            return Ok(None);
+        }
+        let mut state = self.state.clone();
+        let context = PredictionContext {
+            date: dates[0],
+            dt: self.dt,
+            model: &self.model,
+            physics: &self.physics,
+            state: &self.state,
+            forecast: Forecast::new(&self.weather, dates),
+        };
+        controller.control(&context, &mut state)?;
+        self.state = state;
+        self.step()
+    }
+
+    /// The last date that was simulated, or `None` if nothing
+    /// has been simulated yet
+    pub fn date(&self) -> Option<Date> {
+        self.date
+    }
+
+    /// The duration of each timestep, in seconds
+    pub fn dt(&self) -> Float {
+        self.dt
+    }
+
+    /// The model being simulated
+    pub fn model(&self) -> &Model {
+        &self.model
+    }
+
+    /// The weather at the last date that was simulated (or at the
+    /// beginning of the simulation, if nothing has been simulated yet)
+    pub fn current_weather(&self) -> CurrentWeather {
+        let date = self.date.unwrap_or(self.weather.data[0].date);
+        CurrentWeather {
+            date,
+            ..self.weather.get_weather_data(date)
+        }
+    }
+
+    /// The name of each element of the state, as written in the
+    /// header of the results
+    pub fn header(&self) -> &[String] {
+        &self.header
+    }
+
+    /// The position within the state of the element called `name`
+    /// in the [`header`](Stepper::header)
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.header.iter().position(|h| h == name)
+    }
+
+    /// The current state of the simulation
+    pub fn state(&self) -> &SimulationState {
+        &self.state
+    }
+
+    /// The current state of the simulation, which can be modified
+    /// before simulating the next timestep (e.g., for controlling
+    /// the HVAC systems)
+    pub fn state_mut(&mut self) -> &mut SimulationState {
+        &mut self.state
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use crate::run_simulation::load_weather;
+
+    #[test]
+    fn test_step() -> Result<(), String> {
+        let (model, header) = Model::from_file("./tests/box/box.spl")?;
+        let weather = load_weather("./tests/wellington.epw")?;
+        let start = Date {
+            month: 1,
+            day: 1,
+            hour: 0.,
+            year: None,
+        };
+        let end = Date {
+            month: 1,
+            day: 2,
+            hour: 0.,
+            year: None,
+        };
+        let weather = weather.extract_days(start, end);
+        let mut stepper = Stepper::new(model, header, weather, 2)?;
+        assert!(stepper.date().is_none());
+        assert_eq!(stepper.header().len(), stepper.state().len());
+
+        let i = stepper
+            .index_of("{\"SpaceDryBulbTemperature\":\"ZONE ONE\"}")
+            .ok_or("No temperature")?;
+        let mut n = 0;
+        while let Some(date) = stepper.step()? {
+            n += 1;
+            assert_eq!(date.month, 1);
+            assert!(stepper.state()[i].is_finite());
+        }
+        assert!(n > 0);
+        assert!(stepper.date().is_some());
+
+        // Nothing else to simulate
+        assert!(stepper.step()?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors() -> Result<(), String> {
+        let (model, header) = Model::from_file("./tests/box/box.spl")?;
+        let weather = load_weather("./tests/wellington.epw")?;
+        assert!(Stepper::new(model, header, weather, 0).is_err());
+        assert!(Stepper::from_bytes(b"", b"", "pdf", 1).is_err());
+        Ok(())
+    }
+}
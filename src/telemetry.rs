@@ -0,0 +1,349 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::{Float, Stepper};
+use rumqttc::{Client, Event, MqttOptions, Packet, QoS};
+use serde::{Deserialize, Serialize};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// How long to wait for the broker to accept the connection
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A link between an MQTT topic and an element of the simulation state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetryChannel {
+    /// The MQTT topic
+    pub topic: String,
+
+    /// The name of the element of the state, as in the
+    /// [`header`](Stepper::header) of the results (e.g.,
+    /// `{"SpaceDryBulbTemperature":"Bedroom"}`)
+    pub element: String,
+}
+
+/// The options of a [`TelemetryBridge`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetryOptions {
+    /// The host of the MQTT broker
+    pub host: String,
+
+    /// The port of the MQTT broker. Usually 1883.
+    pub port: u16,
+
+    /// The ID of this client
+    pub client_id: String,
+
+    /// The elements of the state that are published after each timestep
+    pub outputs: Vec<TelemetryChannel>,
+
+    /// The elements of the state that are set by the messages received
+    /// (e.g., setpoints sent by a real controller)
+    pub inputs: Vec<TelemetryChannel>,
+
+    /// If given, the simulation is paced so that each timestep takes
+    /// `dt / real_time` seconds of wall-clock time (i.e., `1.0` means
+    /// real time and `60.0` means a simulated minute every second).
+    /// Otherwise, it runs as fast as possible.
+    #[serde(default)]
+    pub real_time: Option<Float>,
+}
+
+/// What the thread polling the connection forwards to the bridge
+enum Message {
+    /// The broker accepted the connection
+    Connected,
+
+    /// A message was received on a topic
+    Command(String, Vec<u8>),
+
+    /// The connection failed
+    Error(String),
+}
+
+/// The value of an element of the state, as published
+fn format_value(v: Float) -> String {
+    format!("{}", v)
+}
+
+/// Parses the value of a command, sent as text (e.g., `"21.5"`)
+fn parse_value(payload: &[u8]) -> Result<Float, String> {
+    let s = std::str::from_utf8(payload).map_err(|e| e.to_string())?;
+    s.trim()
+        .parse()
+        .map_err(|_| format!("Invalid value '{}'", s))
+}
+
+/// Finds the position of the element of each channel within the state
+fn resolve(
+    stepper: &Stepper,
+    channels: &[TelemetryChannel],
+) -> Result<Vec<(String, usize)>, String> {
+    channels
+        .iter()
+        .map(|c| match stepper.index_of(&c.element) {
+            Some(i) => Ok((c.topic.clone(), i)),
+            None => Err(format!(
+                "Element '{}' (topic '{}') is not in the simulation state",
+                c.element, c.topic
+            )),
+        })
+        .collect()
+}
+
+/// Connects a [`Stepper`] with the outside world through MQTT, enabling
+/// hardware-in-the-loop and digital-twin experiments: after each timestep,
+/// some elements of the state are published; and the last value received
+/// on each input topic is written into the state before each timestep.
+///
+/// Values are sent and received as text (e.g., `"21.5"`).
+///
+/// ```no_run
+/// use simple::run_simulation::load_weather;
+/// use simple::telemetry::{TelemetryBridge, TelemetryChannel, TelemetryOptions};
+/// use simple::{Model, Stepper};
+///
+/// let (model, header) = Model::from_file("./tests/box/box.spl").unwrap();
+/// let weather = load_weather("./tests/wellington.epw").unwrap();
+/// let mut stepper = Stepper::new(model, header, weather, 4).unwrap();
+///
+/// let options = TelemetryOptions {
+///     host: "localhost".to_string(),
+///     port: 1883,
+///     client_id: "simple".to_string(),
+///     outputs: vec![TelemetryChannel {
+///         topic: "box/temperature".to_string(),
+///         element: "{\"SpaceDryBulbTemperature\":\"ZONE ONE\"}".to_string(),
+///     }],
+///     inputs: vec![],
+///     real_time: Some(60.),
+/// };
+/// let mut bridge = TelemetryBridge::connect(options, &stepper).unwrap();
+/// bridge.run(&mut stepper).unwrap();
+/// ```
+pub struct TelemetryBridge {
+    /// The MQTT client
+    client: Client,
+
+    /// Receives the messages forwarded by the thread polling the connection
+    messages: Receiver<Message>,
+
+    /// The topic and position within the state of each output
+    outputs: Vec<(String, usize)>,
+
+    /// The topic and position within the state of each input
+    inputs: Vec<(String, usize)>,
+
+    /// The last value received for each input, if any
+    commands: Vec<Option<Float>>,
+
+    /// The wall-clock duration of each timestep, if paced
+    pacing: Option<Duration>,
+
+    /// When the last timestep finished
+    last_step: Option<Instant>,
+}
+
+impl TelemetryBridge {
+    /// Connects to the broker and subscribes to the input topics
+    pub fn connect(options: TelemetryOptions, stepper: &Stepper) -> Result<Self, String> {
+        let outputs = resolve(stepper, &options.outputs)?;
+        let inputs = resolve(stepper, &options.inputs)?;
+        let pacing = match options.real_time {
+            Some(r) if r > 0. => Some(Duration::from_secs_f64((stepper.dt() / r) as f64)),
+            Some(r) => {
+                return Err(format!(
+                    "Parameter 'real_time' must be positive... found {}",
+                    r
+                ))
+            }
+            None => None,
+        };
+
+        let mut mqtt_options = MqttOptions::new(options.client_id, options.host, options.port);
+        mqtt_options.set_keep_alive(Duration::from_secs(30));
+        let (client, mut connection) = Client::new(mqtt_options, 64);
+
+        let (tx, messages) = mpsc::channel();
+        thread::spawn(move || {
+            for notification in connection.iter() {
+                let msg = match notification {
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => Message::Connected,
+                    Ok(Event::Incoming(Packet::Publish(p))) => {
+                        Message::Command(p.topic, p.payload.to_vec())
+                    }
+                    Ok(_) => continue,
+                    Err(e) => Message::Error(e.to_string()),
+                };
+                let failed = matches!(msg, Message::Error(_));
+                // The bridge was dropped
+                if tx.send(msg).is_err() || failed {
+                    break;
+                }
+            }
+        });
+
+        // Wait for the connection
+        loop {
+            match messages.recv_timeout(CONNECTION_TIMEOUT) {
+                Ok(Message::Connected) => break,
+                Ok(Message::Command(..)) => continue,
+                Ok(Message::Error(e)) => return Err(format!("Could not connect to broker: {}", e)),
+                Err(RecvTimeoutError::Timeout) => {
+                    return Err("Timed out while connecting to broker".to_string())
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err("Lost connection to broker".to_string())
+                }
+            }
+        }
+
+        for (topic, _) in inputs.iter() {
+            client
+                .subscribe(topic, QoS::AtLeastOnce)
+                .map_err(|e| e.to_string())?;
+        }
+
+        Ok(Self {
+            client,
+            messages,
+            commands: vec![None; inputs.len()],
+            outputs,
+            inputs,
+            pacing,
+            last_step: None,
+        })
+    }
+
+    /// Stores the commands received since the last call
+    fn receive(&mut self) -> Result<(), String> {
+        loop {
+            match self.messages.try_recv() {
+                Ok(Message::Command(topic, payload)) => {
+                    let v = parse_value(&payload).map_err(|e| {
+                        format!("Received an invalid command on '{}': {}", topic, e)
+                    })?;
+                    for ((t, _), command) in self.inputs.iter().zip(self.commands.iter_mut()) {
+                        if *t == topic {
+                            *command = Some(v);
+                        }
+                    }
+                }
+                Ok(Message::Connected) => {}
+                Ok(Message::Error(e)) => return Err(format!("Lost connection to broker: {}", e)),
+                Err(TryRecvError::Empty) => return Ok(()),
+                Err(TryRecvError::Disconnected) => {
+                    return Err("Lost connection to broker".to_string())
+                }
+            }
+        }
+    }
+
+    /// Simulates the next timestep: applies the commands received, steps,
+    /// publishes the outputs and—if paced—waits until the timestep is due.
+    /// Returns the date simulated, or `None` when the simulation is over.
+    pub fn step(&mut self, stepper: &mut Stepper) -> Result<Option<crate::Date>, String> {
+        self.receive()?;
+        let state = stepper.state_mut();
+        for ((_, i), command) in self.inputs.iter().zip(self.commands.iter()) {
+            if let Some(v) = command {
+                state[*i] = *v;
+            }
+        }
+
+        let date = match stepper.step()? {
+            Some(d) => d,
+            None => return Ok(None),
+        };
+
+        for (topic, i) in self.outputs.iter() {
+            let payload = format_value(stepper.state()[*i]);
+            self.client
+                .publish(topic, QoS::AtMostOnce, false, payload)
+                .map_err(|e| e.to_string())?;
+        }
+
+        if let Some(pacing) = self.pacing {
+            if let Some(last) = self.last_step {
+                let elapsed = last.elapsed();
+                if elapsed < pacing {
+                    thread::sleep(pacing - elapsed);
+                }
+            }
+            self.last_step = Some(Instant::now());
+        }
+        Ok(Some(date))
+    }
+
+    /// Simulates the whole period
+    pub fn run(&mut self, stepper: &mut Stepper) -> Result<(), String> {
+        while self.step(stepper)?.is_some() {}
+        Ok(())
+    }
+}
+
+impl Drop for TelemetryBridge {
+    fn drop(&mut self) {
+        // Nothing to do if it fails, as we are leaving anyway
+        let _ = self.client.disconnect();
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use crate::run_simulation::load_weather;
+    use model::Model;
+
+    #[test]
+    fn test_values() -> Result<(), String> {
+        assert_eq!(parse_value(b" 21.5\n")?, 21.5);
+        assert!(parse_value(b"hot").is_err());
+        assert_eq!(parse_value(format_value(-3.25).as_bytes())?, -3.25);
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve() -> Result<(), String> {
+        let (model, header) = Model::from_file("./tests/box/box.spl")?;
+        let weather = load_weather("./tests/wellington.epw")?;
+        let stepper = Stepper::new(model, header, weather, 1)?;
+        let element = "{\"SpaceDryBulbTemperature\":\"ZONE ONE\"}";
+        let channels = vec![TelemetryChannel {
+            topic: "box/temperature".to_string(),
+            element: element.to_string(),
+        }];
+        let resolved = resolve(&stepper, &channels)?;
+        assert_eq!(resolved[0].0, "box/temperature");
+        assert_eq!(Some(resolved[0].1), stepper.index_of(element));
+
+        let channels = vec![TelemetryChannel {
+            topic: "nothing".to_string(),
+            element: "{\"SpaceDryBulbTemperature\":\"NOWHERE\"}".to_string(),
+        }];
+        assert!(resolve(&stepper, &channels).is_err());
+        Ok(())
+    }
+}
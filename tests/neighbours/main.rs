@@ -1,8 +1,8 @@
-use simple::{run_simulation::*, Model};
+use simple::{run_simulation::*, Model, SimpleError};
 
 #[test]
 #[ignore]
-fn neighbours_sim() -> Result<(), String> {
+fn neighbours_sim() -> Result<(), SimpleError> {
     // cargo test --features parallel --release --package simple --test neighbours -- neighbours_sim --exact --nocapture --ignored
 
     let options = SimOptions {
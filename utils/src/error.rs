@@ -0,0 +1,110 @@
+/// The kinds of errors produced while building and simulating a model.
+///
+/// These are returned when reading models and weather files, and by the
+/// functions that run whole simulations (e.g., `simple::run_simulation::run`).
+/// The rest of the library (e.g., the physics and the controllers) still
+/// reports errors as `String`, so `SimpleError` converts from and into
+/// `String` (i.e., the `?` operator works in both directions). Errors
+/// coming from a `String` become [`SimpleError::Other`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SimpleError {
+    /// A file or text could not be parsed (e.g., a model or a weather file)
+    Parse(String),
+
+    /// A file could not be read or written
+    Io(String),
+
+    /// An object refers to another one that does not exist
+    MissingReference {
+        /// The kind of object that was being searched for (e.g., `"Space"`)
+        kind: String,
+        /// The name of the object that was not found
+        name: String,
+    },
+
+    /// Any other error
+    Other(String),
+}
+
+impl SimpleError {
+    /// Creates a [`SimpleError::MissingReference`]
+    pub fn missing_reference<K: Into<String>, N: Into<String>>(kind: K, name: N) -> Self {
+        Self::MissingReference {
+            kind: kind.into(),
+            name: name.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for SimpleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Parse(msg) | Self::Io(msg) | Self::Other(msg) => {
+                write!(f, "{}", msg)
+            }
+            Self::MissingReference { kind, name } => {
+                write!(f, "Could not find {} '{}' in model", kind, name)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SimpleError {}
+
+impl From<String> for SimpleError {
+    fn from(msg: String) -> Self {
+        Self::Other(msg)
+    }
+}
+
+impl From<&str> for SimpleError {
+    fn from(msg: &str) -> Self {
+        Self::Other(msg.to_string())
+    }
+}
+
+impl From<std::io::Error> for SimpleError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e.to_string())
+    }
+}
+
+impl From<SimpleError> for String {
+    fn from(e: SimpleError) -> Self {
+        e.to_string()
+    }
+}
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+
+    fn string_error() -> Result<(), String> {
+        Err("some error".to_string())
+    }
+
+    fn simple_error() -> Result<(), SimpleError> {
+        Err(SimpleError::missing_reference("Space", "Kitchen"))
+    }
+
+    #[test]
+    fn test_conversions() {
+        let from_string = || -> Result<(), SimpleError> {
+            string_error()?;
+            Ok(())
+        };
+        assert_eq!(
+            from_string(),
+            Err(SimpleError::Other("some error".to_string()))
+        );
+
+        let into_string = || -> Result<(), String> {
+            simple_error()?;
+            Ok(())
+        };
+        assert_eq!(
+            into_string(),
+            Err("Could not find Space 'Kitchen' in model".to_string())
+        );
+    }
+}
@@ -1,2 +1,5 @@
 pub mod progress_bar;
 pub use progress_bar::ProgressBar;
+
+pub mod error;
+pub use error::SimpleError;
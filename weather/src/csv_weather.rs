@@ -0,0 +1,296 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+//! Reads weather data from a simple CSV format, for regions in which
+//! EPW files are not distributed.
+//!
+//! # Format
+//!
+//! The file may start with some lines beginning with `#`, containing the
+//! details of the location as `key: value` pairs. The supported keys are
+//! `city`, `state`, `country`, `source`, `latitude` and `longitude` (in
+//! degrees, with South and West being negative), `timezone` (in hours from
+//! GMT) and `elevation` (in m).
+//!
+//! The first line that does not start with `#` is a header with the name of
+//! the columns, which can be in any order. Each of the following lines
+//! contains the data of one hour.
+//!
+//! | Column | Units | Required | Default |
+//! |--------|-------|----------|---------|
+//! | `month` | 1 to 12 | Yes | |
+//! | `day` | 1 to 31 | Yes | |
+//! | `hour` | 1 to 24, at the end of the hour (as in EPW files) | Yes | |
+//! | `dry_bulb_temperature` | C | Yes | |
+//! | `dew_point_temperature` | C | Yes | |
+//! | `relative_humidity` | % | No | 50 |
+//! | `pressure` | Pa | No | 101325 |
+//! | `global_horizontal_radiation` | Wh/m2 | No | 0 |
+//! | `direct_normal_radiation` | Wh/m2 | No | 0 |
+//! | `diffuse_horizontal_radiation` | Wh/m2 | No | 0 |
+//! | `horizontal_infrared_radiation` | Wh/m2 | No | derived from the sky cover |
+//! | `wind_speed` | m/s | No | 0 |
+//! | `wind_direction` | degrees (North is 0, East is 90) | No | 0 |
+//! | `opaque_sky_cover` | tenths (0 to 10) | No | 0 |
+//!
+//! ```
+//! let src = "# city: Wellington
+//! ## latitude: -41.3
+//! ## longitude: 174.8
+//! ## timezone: 12
+//! month,day,hour,dry_bulb_temperature,dew_point_temperature,wind_speed
+//! 1,1,1,14.2,10.1,3.5
+//! 1,1,2,13.9,10.0,3.1
+//! ";
+//! let weather = weather::csv_weather::parse(src).unwrap();
+//! assert_eq!(weather.data.len(), 2);
+//! assert_eq!(weather.location.city, "Wellington");
+//! ```
+
+use crate::{CurrentWeather, Float, Location, RadiationInterpolation, Weather};
+use calendar::Date;
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use utils::SimpleError;
+
+/// Splits a line of a CSV file into its fields, removing the quotes
+/// around them (if any)
+pub(crate) fn split_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    for c in line.chars() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    fields.push(current);
+    fields.iter().map(|f| f.trim().to_string()).collect()
+}
+
+/// Parses a number in a CSV file, reporting the line and column if it fails
+pub(crate) fn parse_number(
+    fields: &[String],
+    column: usize,
+    ln: usize,
+    name: &str,
+) -> Result<Float, SimpleError> {
+    let field = fields
+        .get(column)
+        .ok_or_else(|| SimpleError::Parse(format!("Line {}: missing value for '{}'", ln, name)))?;
+    field.parse().map_err(|_| {
+        SimpleError::Parse(format!(
+            "Line {}: invalid value '{}' for '{}'",
+            ln, field, name
+        ))
+    })
+}
+
+/// Reads a weather file in the simple CSV format
+pub fn from_file<P: AsRef<Path> + Display>(filename: P) -> Result<Weather, SimpleError> {
+    let src = fs::read_to_string(&filename)
+        .map_err(|_| SimpleError::Io(format!("Could not read weather file '{}'", filename)))?;
+    parse(&src)
+}
+
+/// Parses weather data in the simple CSV format
+pub fn parse(src: &str) -> Result<Weather, SimpleError> {
+    let mut location = Location {
+        source: "CSV".to_string(),
+        ..Location::default()
+    };
+
+    let mut lines = src
+        .lines()
+        .enumerate()
+        .map(|(i, l)| (i + 1, l.trim()))
+        .filter(|(_, l)| !l.is_empty());
+
+    // Location and header
+    let header = loop {
+        let (ln, line) = lines
+            .next()
+            .ok_or_else(|| SimpleError::Parse("The weather file has no header".to_string()))?;
+        let line = match line.strip_prefix('#') {
+            Some(l) => l,
+            None => break split_line(line),
+        };
+        let (key, value) = match line.split_once(':') {
+            Some((k, v)) => (k.trim(), v.trim()),
+            None => continue, // just a comment
+        };
+        let number = || -> Result<Float, SimpleError> {
+            value.parse().map_err(|_| {
+                SimpleError::Parse(format!("Line {}: invalid {} '{}'", ln, key, value))
+            })
+        };
+        match key {
+            "city" => location.city = value.to_string(),
+            "state" => location.state = value.to_string(),
+            "country" => location.country = value.to_string(),
+            "source" => location.source = value.to_string(),
+            "latitude" => location.latitude = number()?.to_radians(),
+            "longitude" => location.longitude = number()?.to_radians(),
+            "timezone" => location.timezone = number()? as i8,
+            "elevation" => location.elevation = number()?,
+            _ => {}
+        }
+    };
+
+    let column = |name: &str| header.iter().position(|h| h == name);
+    let required = |name: &str| {
+        column(name)
+            .ok_or_else(|| SimpleError::Parse(format!("The weather file has no '{}' column", name)))
+    };
+    let month = required("month")?;
+    let day = required("day")?;
+    let hour = required("hour")?;
+    let dry_bulb = required("dry_bulb_temperature")?;
+    let dew_point = required("dew_point_temperature")?;
+    let optional = [
+        ("relative_humidity", 50.),
+        ("pressure", 101325.),
+        ("global_horizontal_radiation", 0.),
+        ("direct_normal_radiation", 0.),
+        ("diffuse_horizontal_radiation", 0.),
+        ("wind_speed", 0.),
+        ("wind_direction", 0.),
+        ("opaque_sky_cover", 0.),
+    ]
+    .map(|(name, default)| (name, column(name), default));
+    let ir = column("horizontal_infrared_radiation");
+
+    let mut data = Vec::new();
+    for (ln, line) in lines {
+        if line.starts_with('#') {
+            continue;
+        }
+        let fields = split_line(line);
+        let get = |i: usize, name: &str| parse_number(&fields, i, ln, name);
+        let mut values = [0.; 8];
+        for (v, (name, col, default)) in values.iter_mut().zip(optional.iter()) {
+            *v = match col {
+                Some(i) => get(*i, name)?,
+                None => *default,
+            };
+        }
+        let [relative_humidity, pressure, global, direct, diffuse, wind_speed, wind_direction, sky_cover] =
+            values;
+
+        data.push(CurrentWeather {
+            date: Date {
+                month: get(month, "month")? as u8,
+                day: get(day, "day")? as u8,
+                // Same convention as EPW files
+                hour: get(hour, "hour")? - 0.000001,
+                year: None,
+            },
+            dry_bulb_temperature: get(dry_bulb, "dry_bulb_temperature")?,
+            dew_point_temperature: get(dew_point, "dew_point_temperature")?,
+            global_horizontal_radiation: global,
+            direct_normal_radiation: direct,
+            diffuse_horizontal_radiation: diffuse,
+            wind_speed,
+            wind_direction: wind_direction.to_radians(),
+            horizontal_infrared_radiation_intensity: match ir {
+                Some(i) => Some(get(i, "horizontal_infrared_radiation")?),
+                None => None,
+            },
+            opaque_sky_cover: sky_cover / 10.,
+            relative_humidity: relative_humidity / 100.,
+            pressure,
+        });
+    }
+
+    if data.is_empty() {
+        return Err(SimpleError::Parse(
+            "The weather file has no data".to_string(),
+        ));
+    }
+
+    Ok(Weather {
+        data,
+        location,
+        ground_temperature: Vec::new(),
+        typical_periods: Vec::new(),
+        daylight_saving: None,
+        radiation_interpolation: RadiationInterpolation::default(),
+    })
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use validate::assert_close;
+
+    #[test]
+    fn test_split_line() {
+        assert_eq!(split_line("a, b,c"), vec!["a", "b", "c"]);
+        assert_eq!(
+            split_line("690150,\"TWENTYNINE PALMS, CA\",CA"),
+            vec!["690150", "TWENTYNINE PALMS, CA", "CA"]
+        );
+    }
+
+    #[test]
+    fn test_parse() -> Result<(), String> {
+        let src = "# A comment
+# latitude: -41.3
+# elevation: 6
+
+hour,month,day,dry_bulb_temperature,dew_point_temperature,relative_humidity,opaque_sky_cover
+1,7,21,4.5,2.0,84,10
+2,7,21,4.0,1.9,86,5
+";
+        let weather = parse(src)?;
+        assert_close!(weather.location.latitude, (-41.3 as Float).to_radians());
+        assert_close!(weather.location.elevation, 6.);
+        assert_eq!(weather.data.len(), 2);
+
+        let w = &weather.data[1];
+        assert_eq!(w.date.month, 7);
+        assert_eq!(w.date.day, 21);
+        assert_close!(w.date.hour, 2., 1e-5);
+        assert_close!(w.dry_bulb_temperature, 4.);
+        assert_close!(w.relative_humidity, 0.86);
+        assert_close!(w.opaque_sky_cover, 0.5);
+        assert_close!(w.pressure, 101325.);
+        assert!(w.horizontal_infrared_radiation_intensity.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_errors() {
+        // Missing column
+        assert!(parse("month,day,hour,dry_bulb_temperature\n1,1,1,20").is_err());
+        // Invalid number
+        let src = "month,day,hour,dry_bulb_temperature,dew_point_temperature\n1,1,1,hot,10";
+        assert!(parse(src).is_err());
+        // No data
+        assert!(parse("month,day,hour,dry_bulb_temperature,dew_point_temperature").is_err());
+    }
+}
@@ -18,10 +18,14 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 
+use crate::psychrometrics;
 use crate::Float;
 use calendar::Date;
 use serde::{Deserialize, Serialize};
 
+/// The Stefan-Boltzmann constant, in $`W/m^2K^4`$
+const SIGMA: Float = 5.670374419e-8;
+
 /// A structure containing weather data necessary to simulate the performance
 /// of buildings.
 #[derive(Default, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -79,7 +83,7 @@ impl CurrentWeather {
     /// ```
     ///
     /// Where $`\epsilon_{sky}`$ can be calculated from the clear-sky emmisivity ($`\epsilon_{sky, clear}`$)
-    /// based on the opaque sky cover $`N`$ as follows
+    /// based on the opaque sky cover $`N`$ (in tenths) as follows
     ///
     /// ```math
     /// \epsilon_{sky} = \epsilon_{sky, clear} \left( 1 + 0.0224 N - 0.0035 N^2 + 0.00028 N^3 \right)
@@ -114,7 +118,7 @@ impl CurrentWeather {
     ///     // This method returns an error if this info is not available.
     ///     dry_bulb_temperature: 13.625,
     ///     dew_point_temperature: 8.325,
-    ///     opaque_sky_cover: 5.,
+    ///     opaque_sky_cover: 0.5,
     ///     .. CurrentWeather::default()
     /// };
     ///
@@ -123,8 +127,8 @@ impl CurrentWeather {
     /// assert!( (expected - found).abs() < 0.1, "expected = {} | found = {}", expected, found );
     /// ```
     pub fn derive_horizontal_ir(&self) -> Float {
-        pub const SIGMA: Float = 5.670374419e-8;
-        let n = self.opaque_sky_cover;
+        // The correlation uses tenths
+        let n = 10. * self.opaque_sky_cover;
 
         let dp = self.dew_point_temperature + 273.15;
 
@@ -136,6 +140,69 @@ impl CurrentWeather {
         SIGMA * e_sky * (temp).powi(4)
     }
 
+    /// The horizontal infrared radiation intensity from the sky, in $`W/m^2`$.
+    /// This is the `horizontal_infrared_radiation_intensity` reported by the
+    /// weather or, when it is missing, the one calculated through
+    /// [`derive_horizontal_ir`](Self::derive_horizontal_ir).
+    pub fn horizontal_ir(&self) -> Float {
+        match self.horizontal_infrared_radiation_intensity {
+            Some(v) => v,
+            None => self.derive_horizontal_ir(),
+        }
+    }
+
+    /// The effective temperature of the sky (in C), i.e., the temperature
+    /// of a black body emitting the [`horizontal_ir`](Self::horizontal_ir)
+    ///
+    /// ```math
+    /// T_{sky} = \left( \frac{IR_h}{\sigma} \right)^{1/4}
+    /// ```
+    ///
+    /// ```rust
+    /// # use weather::current_weather::CurrentWeather;
+    /// let cw = CurrentWeather {
+    ///     horizontal_infrared_radiation_intensity: Some(300.),
+    ///     .. CurrentWeather::default()
+    /// };
+    /// assert!((cw.sky_temperature() + 3.45).abs() < 0.01);
+    /// ```
+    pub fn sky_temperature(&self) -> Float {
+        (self.horizontal_ir() / SIGMA).powf(0.25) - 273.15
+    }
+
+    /// The `pressure`, or the [`STANDARD_PRESSURE`](psychrometrics::STANDARD_PRESSURE)
+    /// if the weather does not report one
+    pub fn pressure_or_standard(&self) -> Float {
+        if self.pressure > 0. {
+            self.pressure
+        } else {
+            psychrometrics::STANDARD_PRESSURE
+        }
+    }
+
+    /// The humidity ratio of the exterior air, in kg of water per kg of dry air
+    pub fn humidity_ratio(&self) -> Float {
+        psychrometrics::humidity_ratio(
+            self.dry_bulb_temperature,
+            self.relative_humidity,
+            self.pressure_or_standard(),
+        )
+    }
+
+    /// The wet bulb temperature of the exterior air, in C
+    pub fn wet_bulb_temperature(&self) -> Float {
+        psychrometrics::wet_bulb_temperature(
+            self.dry_bulb_temperature,
+            self.relative_humidity,
+            self.pressure_or_standard(),
+        )
+    }
+
+    /// The specific enthalpy of the exterior air, in J per kg of dry air
+    pub fn enthalpy(&self) -> Float {
+        psychrometrics::enthalpy(self.dry_bulb_temperature, self.humidity_ratio())
+    }
+
     /// Interpolates the data between to WeatherLines
     pub fn interpolate(&self, other: &Self, x: Float) -> Self {
         let interp_opt = |a, b| {
@@ -0,0 +1,335 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::psychrometrics::{self, saturation_pressure};
+use crate::{CurrentWeather, Float, Location, RadiationInterpolation, Weather};
+use calendar::Date;
+use serde::{Deserialize, Serialize};
+
+/// The fraction of the hours of the year that are colder than the
+/// heating design temperature (i.e., the 99.6% heating condition)
+const HEATING_PERCENTILE: Float = 0.004;
+
+/// The fraction of the hours of the year that are warmer than the
+/// cooling design temperature (i.e., the 0.4% cooling condition)
+const COOLING_PERCENTILE: Float = 0.004;
+
+/// The day of the month used for design days
+const DESIGN_DAY: u8 = 21;
+
+/// The fraction of the daily range by which the temperature is below
+/// the maximum at each hour of the day (from 0 to 23), as given by
+/// ASHRAE Fundamentals (2009), Chapter 14, Table 6.
+const DAILY_RANGE_FRACTIONS: [Float; 24] = [
+    0.82, 0.88, 0.92, 0.95, 0.98, 1.00, 0.98, 0.91, 0.74, 0.55, 0.38, 0.23, 0.13, 0.05, 0.00, 0.00,
+    0.06, 0.14, 0.24, 0.39, 0.50, 0.59, 0.68, 0.75,
+];
+
+/// A day with extreme weather conditions, used for sizing the heating and
+/// cooling systems of a building, in the spirit of ASHRAE's design days.
+///
+/// ```no_run
+/// use weather::design_day::DesignDay;
+/// use weather::{EPWWeather, Weather};
+///
+/// let weather: Weather = EPWWeather::from_file("./test_data/wellington.epw").unwrap().into();
+/// let heating = DesignDay::heating(&weather).unwrap();
+/// let cooling = DesignDay::cooling(&weather).unwrap();
+///
+/// // A Weather that can be used in a sizing simulation
+/// let design_weather = cooling.to_weather(&weather.location);
+/// assert_eq!(design_weather.data.len(), 24);
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DesignDay {
+    /// The month of the design day
+    pub month: u8,
+
+    /// The day of the month of the design day
+    pub day: u8,
+
+    /// The maximum dry bulb temperature of the day, in C
+    pub max_dry_bulb_temperature: Float,
+
+    /// The difference between the maximum and minimum dry bulb
+    /// temperatures of the day, in K
+    pub daily_range: Float,
+
+    /// The dew point temperature, constant throughout the day, in C
+    pub dew_point_temperature: Float,
+
+    /// The wind speed, constant throughout the day, in m/s
+    pub wind_speed: Float,
+
+    /// Whether the sky is clear (i.e., with sun) or overcast (i.e., without sun)
+    pub clear_sky: bool,
+}
+
+/// The value of `data` below which there is a certain `fraction` of the values
+fn percentile(mut data: Vec<Float>, fraction: Float) -> Float {
+    data.sort_by(|a, b| a.total_cmp(b));
+    let i = ((data.len() as Float * fraction) as usize).min(data.len() - 1);
+    data[i]
+}
+
+/// The average of some values, or `None` if there are none
+fn mean<I: Iterator<Item = Float>>(values: I) -> Option<Float> {
+    let (sum, n) = values.fold((0., 0), |(s, n), v| (s + v, n + 1));
+    if n > 0 {
+        Some(sum / n as Float)
+    } else {
+        None
+    }
+}
+
+/// The month (from 1 to 12) with the lowest (if `coldest`) or highest
+/// average dry bulb temperature
+fn extreme_month(weather: &Weather, coldest: bool) -> u8 {
+    let mut ret = 1;
+    let mut extreme: Option<Float> = None;
+    for month in 1..=12 {
+        let m = mean(
+            weather
+                .data
+                .iter()
+                .filter(|w| w.date.month == month)
+                .map(|w| w.dry_bulb_temperature),
+        );
+        if let Some(m) = m {
+            let is_extreme = match extreme {
+                None => true,
+                Some(e) => (coldest && m < e) || (!coldest && m > e),
+            };
+            if is_extreme {
+                extreme = Some(m);
+                ret = month;
+            }
+        }
+    }
+    ret
+}
+
+impl DesignDay {
+    /// Derives the heating design day from a year of weather data: a day in
+    /// the coldest month with a constant temperature equal to the 99.6%
+    /// heating dry bulb temperature (i.e., only 0.4% of the hours of the
+    /// year are colder), saturated air, no sun and the mean wind speed
+    /// coincident with such temperatures.
+    pub fn heating(weather: &Weather) -> Result<Self, String> {
+        if weather.data.is_empty() {
+            return Err("Cannot derive a heating design day from an empty weather".to_string());
+        }
+        let temperatures = weather
+            .data
+            .iter()
+            .map(|w| w.dry_bulb_temperature)
+            .collect();
+        let design_temperature = percentile(temperatures, HEATING_PERCENTILE);
+        let wind_speed = mean(
+            weather
+                .data
+                .iter()
+                .filter(|w| w.dry_bulb_temperature <= design_temperature)
+                .map(|w| w.wind_speed),
+        )
+        .unwrap_or(0.);
+
+        Ok(Self {
+            month: extreme_month(weather, true),
+            day: DESIGN_DAY,
+            max_dry_bulb_temperature: design_temperature,
+            daily_range: 0.,
+            dew_point_temperature: design_temperature,
+            wind_speed,
+            clear_sky: false,
+        })
+    }
+
+    /// Derives the cooling design day from a year of weather data: a clear
+    /// day in the warmest month whose maximum temperature is the 0.4%
+    /// cooling dry bulb temperature (i.e., only 0.4% of the hours of the
+    /// year are warmer), with the mean daily range of that month, and with
+    /// the mean dew point temperature coincident with such temperatures.
+    pub fn cooling(weather: &Weather) -> Result<Self, String> {
+        if weather.data.is_empty() {
+            return Err("Cannot derive a cooling design day from an empty weather".to_string());
+        }
+        let temperatures = weather
+            .data
+            .iter()
+            .map(|w| w.dry_bulb_temperature)
+            .collect();
+        let design_temperature = percentile(temperatures, 1. - COOLING_PERCENTILE);
+        let dew_point_temperature = mean(
+            weather
+                .data
+                .iter()
+                .filter(|w| w.dry_bulb_temperature >= design_temperature)
+                .map(|w| w.dew_point_temperature),
+        )
+        .unwrap_or(design_temperature);
+
+        let month = extreme_month(weather, false);
+        let month_data: Vec<&CurrentWeather> = weather
+            .data
+            .iter()
+            .filter(|w| w.date.month == month)
+            .collect();
+
+        // Mean of the daily ranges in the month
+        let mut ranges: Vec<(u8, Float, Float)> = Vec::new();
+        for w in month_data.iter() {
+            let t = w.dry_bulb_temperature;
+            match ranges.last_mut() {
+                Some((day, min, max)) if *day == w.date.day => {
+                    *min = min.min(t);
+                    *max = max.max(t);
+                }
+                _ => ranges.push((w.date.day, t, t)),
+            }
+        }
+        let daily_range = mean(ranges.iter().map(|(_, min, max)| max - min)).unwrap_or(0.);
+        let wind_speed = mean(month_data.iter().map(|w| w.wind_speed)).unwrap_or(0.);
+
+        Ok(Self {
+            month,
+            day: DESIGN_DAY,
+            max_dry_bulb_temperature: design_temperature,
+            daily_range,
+            dew_point_temperature: dew_point_temperature.min(design_temperature),
+            wind_speed,
+            clear_sky: true,
+        })
+    }
+
+    /// The dry bulb temperature at a certain hour of the day (from 0 to 23)
+    pub fn dry_bulb_temperature(&self, hour: usize) -> Float {
+        self.max_dry_bulb_temperature - DAILY_RANGE_FRACTIONS[hour % 24] * self.daily_range
+    }
+
+    /// Produces a [`Weather`] with the 24 hours of the design day at a
+    /// certain [`Location`], which can be used in sizing simulations. The
+    /// solar radiation of clear days is calculated from the position of
+    /// the sun; overcast days have no solar radiation.
+    pub fn to_weather(&self, location: &Location) -> Weather {
+        let pressure = psychrometrics::pressure_at_elevation(location.elevation);
+
+        let data = (0..24)
+            .map(|hour| {
+                let dry_bulb_temperature = self.dry_bulb_temperature(hour);
+                let dew_point_temperature = self.dew_point_temperature.min(dry_bulb_temperature);
+                CurrentWeather {
+                    date: Date {
+                        month: self.month,
+                        day: self.day,
+                        hour: hour as Float,
+                        year: None,
+                    },
+                    dry_bulb_temperature,
+                    dew_point_temperature,
+                    relative_humidity: saturation_pressure(dew_point_temperature)
+                        / saturation_pressure(dry_bulb_temperature),
+                    wind_speed: self.wind_speed,
+                    opaque_sky_cover: if self.clear_sky { 0. } else { 1. },
+                    pressure,
+                    ..CurrentWeather::default()
+                }
+            })
+            .collect();
+
+        let mut weather = Weather {
+            data,
+            location: location.clone(),
+            ground_temperature: Vec::new(),
+            typical_periods: Vec::new(),
+            daylight_saving: None,
+            radiation_interpolation: RadiationInterpolation::default(),
+        };
+        if self.clear_sky {
+            // This only fails if there is no data
+            weather
+                .fill_solar_radiation_data()
+                .expect("Could not calculate the solar radiation of the design day");
+        }
+        weather
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use crate::EPWWeather;
+    use validate::assert_close;
+
+    #[test]
+    fn test_percentile() {
+        let data: Vec<Float> = (0..1000).map(|i| i as Float).collect();
+        assert_close!(percentile(data.clone(), 0.004), 4.);
+        assert_close!(percentile(data, 0.996), 996.);
+    }
+
+    #[test]
+    fn test_daily_profile() {
+        let day = DesignDay {
+            month: 1,
+            day: 21,
+            max_dry_bulb_temperature: 30.,
+            daily_range: 10.,
+            dew_point_temperature: 15.,
+            wind_speed: 2.,
+            clear_sky: true,
+        };
+        assert_close!(day.dry_bulb_temperature(5), 20.);
+        assert_close!(day.dry_bulb_temperature(14), 30.);
+    }
+
+    #[test]
+    fn test_wellington() -> Result<(), String> {
+        let weather: Weather = EPWWeather::from_file("./test_data/wellington.epw")?.into();
+        let heating = DesignDay::heating(&weather)?;
+        let cooling = DesignDay::cooling(&weather)?;
+
+        // Southern hemisphere
+        assert_eq!(heating.month, 7);
+        assert!([1, 2].contains(&cooling.month));
+        assert!(heating.max_dry_bulb_temperature < cooling.max_dry_bulb_temperature);
+        assert!(cooling.daily_range > 0.);
+
+        // Heating design days are constant and dark
+        let w = heating.to_weather(&weather.location);
+        assert_eq!(w.data.len(), 24);
+        assert!(w.data.iter().all(|d| d.global_horizontal_radiation == 0.));
+        assert!(w
+            .data
+            .iter()
+            .all(|d| (d.dry_bulb_temperature - heating.max_dry_bulb_temperature).abs() < 1e-9));
+
+        // Cooling design days have sun at noon, but not at midnight
+        let w = cooling.to_weather(&weather.location);
+        assert!(w.data[12].global_horizontal_radiation > 0.);
+        assert_close!(w.data[0].global_horizontal_radiation, 0.);
+        assert!(w.data.iter().all(|d| d.relative_humidity <= 1.));
+        Ok(())
+    }
+}
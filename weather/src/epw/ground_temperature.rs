@@ -18,6 +18,7 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 use crate::Float;
+use calendar::Date;
 use serde::{Deserialize, Serialize};
 
 /// Ground Temperature information gotten from from an EPW file
@@ -38,3 +39,94 @@ pub struct EPWGroundTemperature {
     /// The averate temperature of the soil at this specific `depth`
     pub average_monthly_temperature: [Float; 12],
 }
+
+impl EPWGroundTemperature {
+    /// Interpolates the `average_monthly_temperature` linearly in time, assuming
+    /// that each monthly average corresponds to the middle of its month.
+    pub fn temperature(&self, date: Date) -> Float {
+        // Day of the year at which each month is centered
+        const MID_MONTH: [Float; 12] = [
+            15.5, 45., 74.5, 105., 135.5, 166., 196.5, 227.5, 258., 288.5, 319., 349.5,
+        ];
+
+        let n = date.day_of_year();
+        let (before, after, x) = if n < MID_MONTH[0] {
+            // Between mid-December of last year and mid-January
+            (
+                11,
+                0,
+                (n + 365. - MID_MONTH[11]) / (MID_MONTH[0] + 365. - MID_MONTH[11]),
+            )
+        } else if n >= MID_MONTH[11] {
+            // Between mid-December and mid-January of next year
+            (
+                11,
+                0,
+                (n - MID_MONTH[11]) / (MID_MONTH[0] + 365. - MID_MONTH[11]),
+            )
+        } else {
+            let after = MID_MONTH.iter().position(|mid| *mid > n).unwrap(); // we know it is there
+            let before = after - 1;
+            (
+                before,
+                after,
+                (n - MID_MONTH[before]) / (MID_MONTH[after] - MID_MONTH[before]),
+            )
+        };
+
+        let t_before = self.average_monthly_temperature[before];
+        let t_after = self.average_monthly_temperature[after];
+        t_before + x * (t_after - t_before)
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use validate::assert_close;
+
+    #[test]
+    fn test_temperature() {
+        let g = EPWGroundTemperature {
+            depth: 0.5,
+            average_monthly_temperature: [1., 2., 3., 4., 5., 6., 7., 8., 9., 10., 11., 12.],
+            ..EPWGroundTemperature::default()
+        };
+
+        // Middle of the months
+        let jan = Date {
+            month: 1,
+            day: 16,
+            hour: 12.,
+            year: None,
+        };
+        assert_close!(g.temperature(jan), 1.);
+        let jun = Date {
+            month: 6,
+            day: 16,
+            hour: 0.,
+            year: None,
+        };
+        assert_close!(g.temperature(jun), 6.);
+
+        // Between December and January
+        let new_year = Date {
+            month: 1,
+            day: 1,
+            hour: 0.,
+            year: None,
+        };
+        assert_close!(g.temperature(new_year), 6.5);
+        let christmas = Date {
+            month: 12,
+            day: 31,
+            hour: 0.,
+            year: None,
+        };
+        assert_close!(g.temperature(christmas), 12. - 11. * 14.5 / 31.);
+    }
+}
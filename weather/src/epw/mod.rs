@@ -2,6 +2,8 @@
 pub mod ground_temperature;
 /// Module for scanning a file
 pub(crate) mod scanner;
+/// The typical and extreme periods reported in EPW files
+pub mod typical_period;
 /// The EPW File itself.
 pub mod weather;
 /// A line within the EPW file
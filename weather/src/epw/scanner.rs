@@ -18,13 +18,16 @@ OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
 SOFTWARE.
 */
 use crate::Float;
+use calendar::{Date, DaylightSaving};
 
 use super::ground_temperature::EPWGroundTemperature;
+use super::typical_period::{EPWPeriodKind, EPWTypicalPeriod};
 use super::weather::EPWWeather;
 use super::weather_line::EPWWeatherLine;
 use std::fmt::Display;
 use std::fs;
 use std::path::Path;
+use utils::SimpleError;
 
 pub(crate) struct EPWScanner<'a> {
     /// Indicates the position of current character being
@@ -58,24 +61,29 @@ impl<'a> EPWScanner<'a> {
     }
 
     /// Builds a weather file starting from a file name
-    pub fn from_file<P: AsRef<Path> + Display>(filename: P) -> Result<EPWWeather, String> {
+    pub fn from_file<P: AsRef<Path> + Display>(filename: P) -> Result<EPWWeather, SimpleError> {
         let src = match fs::read(&filename) {
             Ok(v) => v,
-            Err(_) => return Err(format!("Could not read epw file '{}'", filename)),
+            Err(_) => {
+                return Err(SimpleError::Io(format!(
+                    "Could not read epw file '{}'",
+                    filename
+                )))
+            }
         };
 
         EPWScanner::build_weather_file(&src)
     }
 
     /// Parses the EPW file and builds a proper EPWWeather
-    pub fn build_weather_file(src: &'a Vec<u8>) -> Result<EPWWeather, String> {
+    pub fn build_weather_file(src: &'a Vec<u8>) -> Result<EPWWeather, SimpleError> {
         // build a scaner
         let mut scanner = EPWScanner::new(src);
 
         // create an empty .EPW
         let mut epw = EPWWeather::default();
 
-        scanner.parse_file(&mut epw)?;
+        scanner.parse_file(&mut epw).map_err(SimpleError::Parse)?;
 
         Ok(epw)
     }
@@ -153,14 +161,8 @@ impl<'a> EPWScanner<'a> {
     /// Scans an element and transforms it into a number
     fn scan_number(slice: Option<&[u8]>) -> Result<Float, String> {
         if let Ok(v) = EPWScanner::scan_string(slice) {
-            let the_v = match v.parse::<Float>() {
-                Ok(v) => v,
-                Err(msg) => {
-                    panic!("{}", msg);
-                }
-            };
-
-            Ok(the_v)
+            v.parse::<Float>()
+                .map_err(|e| format!("Could not parse '{}' as a number: {}", v, e))
         } else {
             Err("Could not scan number... scan_string() return error".to_string())
         }
@@ -206,6 +208,14 @@ impl<'a> EPWScanner<'a> {
                 self.parse_ground_temperature(epw)?;
             }
 
+            if keyword == *"TYPICAL/EXTREME PERIODS" {
+                self.parse_typical_periods(epw)?;
+            }
+
+            if keyword == *"HOLIDAYS/DAYLIGHT SAVINGS" {
+                self.parse_daylight_saving(epw)?;
+            }
+
             // Parse location, when found
             if keyword == *"LOCATION" {
                 self.parse_location(epw)?;
@@ -309,6 +319,67 @@ impl<'a> EPWScanner<'a> {
         Ok(())
     }
 
+    /// Parses a date in `month/day` format (e.g., `"12/ 8"`), at hour 0
+    fn scan_month_day(slice: Option<&[u8]>) -> Result<Date, String> {
+        let s = EPWScanner::scan_string(slice)?;
+        let (month, day) = s
+            .split_once('/')
+            .ok_or_else(|| format!("Expecting a date in month/day format... found '{}'", s))?;
+        let month = month.trim().parse::<u8>().map_err(|e| e.to_string())?;
+        let day = day.trim().parse::<u8>().map_err(|e| e.to_string())?;
+        Ok(Date {
+            month,
+            day,
+            hour: 0.,
+            year: None,
+        })
+    }
+
+    /// Parses the typical and extreme periods... assumes that the
+    /// TYPICAL/EXTREME PERIODS keyword has been consumed already
+    fn parse_typical_periods(&mut self, epw: &mut EPWWeather) -> Result<(), String> {
+        let n_periods = EPWScanner::scan_number(self.scan_element())? as usize;
+        for _ in 0..n_periods {
+            let name = EPWScanner::scan_string(self.scan_element())?;
+            let kind = if EPWScanner::scan_string(self.scan_element())?.trim() == "Extreme" {
+                EPWPeriodKind::Extreme
+            } else {
+                EPWPeriodKind::Typical
+            };
+            let start = EPWScanner::scan_month_day(self.scan_element())?;
+            let end = EPWScanner::scan_month_day(self.scan_element())?;
+            epw.typical_periods.push(EPWTypicalPeriod {
+                name,
+                kind,
+                start,
+                end,
+            });
+        }
+        Ok(())
+    }
+
+    /// Parses the daylight saving period... assumes that the
+    /// HOLIDAYS/DAYLIGHT SAVINGS keyword has been consumed already.
+    ///
+    /// Only periods given as `month/day` are supported; a `0` means that
+    /// there is no daylight saving time, and rules such as `Last Sunday in
+    /// October` (which depend on the year) are ignored. Holidays are
+    /// ignored as well.
+    fn parse_daylight_saving(&mut self, epw: &mut EPWWeather) -> Result<(), String> {
+        // Leap year observed
+        EPWScanner::skip_string(self.scan_element())?;
+        let start = self.scan_element().map(|s| s.to_vec());
+        let end = self.scan_element().map(|s| s.to_vec());
+        let is_month_day = |s: &Option<Vec<u8>>| s.as_ref().is_some_and(|s| s.contains(&b'/'));
+        if is_month_day(&start) && is_month_day(&end) {
+            epw.daylight_saving = Some(DaylightSaving {
+                start: EPWScanner::scan_month_day(start.as_deref())?,
+                end: EPWScanner::scan_month_day(end.as_deref())?,
+            });
+        }
+        Ok(())
+    }
+
     /// Parses the ground temperature        
     #[allow(clippy::field_reassign_with_default)]
     fn parse_ground_temperature(&mut self, epw: &mut EPWWeather) -> Result<(), String> {
@@ -462,6 +533,30 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_daylight_saving() -> Result<(), String> {
+        let raw_source =
+            "HOLIDAYS/DAYLIGHT SAVINGS,No,9/24,4/ 2,1,New Year,1/ 1\nCOMMENTS 1,\"Some comments\""
+                .to_string();
+        let v = raw_source.into_bytes();
+        let mut scanner = EPWScanner::new(&v);
+        let mut epw = EPWWeather::default();
+        scanner.parse_file(&mut epw)?;
+        let dst = epw.daylight_saving.ok_or("No daylight saving")?;
+        assert_eq!((dst.start.month, dst.start.day), (9, 24));
+        assert_eq!((dst.end.month, dst.end.day), (4, 2));
+
+        // Rules that depend on the year are ignored
+        let raw_source =
+            "HOLIDAYS/DAYLIGHT SAVINGS,No,2nd Sunday in March,1st Sunday in November,0".to_string();
+        let v = raw_source.into_bytes();
+        let mut scanner = EPWScanner::new(&v);
+        let mut epw = EPWWeather::default();
+        scanner.parse_file(&mut epw)?;
+        assert!(epw.daylight_saving.is_none());
+        Ok(())
+    }
+
     #[test]
     fn test_data_period() -> Result<(), String> {
         let raw_source = "DATA PERIODS,1,1,Data,Sunday, 1/ 1,12/31\n1987,1,1,1,60,C9C9C9C9*0?9?9?9?9?9?9?9A7A7B8B8A7*0*0E8*0*0,16.7,9.6,63,95600,0,1415,326,0,0,0,0,0,0,0,150,1.5,0,0,9.9,77777,9,999999999,0,0.2680,0,88,0.000,0.0,0.0\n1987,1,1,2,60,C9C9C9C9*0?9?9?9?9?9?9?9A7A7A7A7A7A7*0E8*0*0,15.1,8.4,64,95700,0,1415,317,0,0,0,0,0,0,0,0,0.0,0,0,15.0,22000,9,999999999,0,0.2680,0,88,0.000,0.0,0.0\n1987,1,1,3,60,C9C9C9C9*0?9?9?9?9?9?9?9A7A7B8B8A7*0*0E8*0*0,13.8,7.6,66,95700,0,1415,311,0,0,0,0,0,0,0,0,0.0,0,0,9.9,22000,9,999999999,0,0.2680,0,88,0.000,0.0,0.0\n1987,1,1,4,60,C9C9C9C9*0?9?9?9?9?9?9?9A7A7B8B8A7*0*0E8*0*0,12.7,7.3,70,95700,0,1415,306,0,0,0,0,0,0,0,0,0.0,0,0,9.9,22000,9,999999999,0,0.2680,0,88,0.000,0.0,0.0".to_string();
@@ -480,6 +575,19 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_error_kinds() {
+        assert!(matches!(
+            EPWScanner::from_file("./tests/this_does_not_exist.epw"),
+            Err(SimpleError::Io(_))
+        ));
+        let source = b"LOCATION,SANTIAGO,-,CHL,IWEC Data,855740,not a number".to_vec();
+        assert!(matches!(
+            EPWScanner::build_weather_file(&source),
+            Err(SimpleError::Parse(_))
+        ));
+    }
+
     #[test]
     fn test_parse_file() -> Result<(), String> {
         let raw_source = "LOCATION,SANTIAGO,-,CHL,IWEC Data,855740,-33.38,-70.78,-4.0,476.0\nDESIGN CONDITIONS,1,Climate Design Data 2009 ASHRAE Handbook,,Heating,7,-1.1,0,-2.7,3.2,4.1,-1.4,3.6,4.4,8.3,9.6,6.5,10.7,0.9,30,Cooling,1,17.2,31.8,18,30.7,17.8,29.7,17.5,19.5,29,18.8,28.4,18.3,27.9,5.7,200,15.8,11.9,23.8,14.9,11.2,23,14.1,10.6,22,57.5,29.2,55.3,28.4,53.3,28,1149,Extremes,8.4,7.4,6.5,27.1,-3.5,34.5,1.3,1.1,-4.4,35.3,-5.2,35.9,-5.9,36.6,-6.8,37.4\nTYPICAL/EXTREME PERIODS,6,Summer - Week Nearest Max Temperature For Period,Extreme,1/20,1/26,Summer - Week Nearest Average Temperature For Period,Typical,12/ 8,12/14,Winter - Week Nearest Min Temperature For Period,Extreme,7/27,8/ 2,Winter - Week Nearest Average Temperature For Period,Typical,8/10,8/16,Autumn - Week Nearest Average Temperature For Period,Typical,4/12,4/18,Spring - Week Nearest Average Temperature For Period,Typical,10/27,11/ 2\nGROUND TEMPERATURES,3,.5,,,,18.03,20.05,20.54,19.99,17.11,13.95,11.03,8.95,8.41,9.49,11.96,15.03,2,,,,16.15,18.06,18.93,18.92,17.37,15.20,12.89,10.95,9.98,10.23,11.65,13.77,4,,,,14.90,16.39,17.29,17.55,16.95,15.67,14.11,12.60,11.61,11.40,12.03,13.28\nHOLIDAYS/DAYLIGHT SAVINGS,No,0,0,0\nCOMMENTS 1,\"IWEC- WMO#855740 - South America -- Original Source Data (c) 2001 American Society of Heating, Refrigerating and Air-Conditioning Engineers (ASHRAE), Inc., Atlanta, GA, USA.  www.ashrae.org  All rights reserved as noted in the License Agreement and Additional Conditions. DISCLAIMER OF WARRANTIES: The data is provided 'as is' without warranty of any kind, either expressed or implied. The entire risk as to the quality and performance of the data is with you. In no event will ASHRAE or its contractors be liable to you for any damages, including without limitation any lost profits, lost savings, or other incidental or consequential damages arising out of the use or inability to use this data.\"\nCOMMENTS 2, -- Ground temps produced with a standard soil diffusivity of 2.3225760E-03 {m**2/day}\nDATA PERIODS,1,1,Data,Sunday, 1/ 1,12/31\n1987,1,1,1,60,C9C9C9C9*0?9?9?9?9?9?9?9A7A7B8B8A7*0*0E8*0*0,16.7,9.6,63,95600,0,1415,326,0,0,0,0,0,0,0,150,1.5,0,0,9.9,77777,9,999999999,0,0.2680,0,88,0.000,0.0,0.0\n1987,1,1,2,60,C9C9C9C9*0?9?9?9?9?9?9?9A7A7A7A7A7A7*0E8*0*0,15.1,8.4,64,95700,0,1415,317,0,0,0,0,0,0,0,0,0.0,0,0,15.0,22000,9,999999999,0,0.2680,0,88,0.000,0.0,0.0\n1987,1,1,3,60,C9C9C9C9*0?9?9?9?9?9?9?9A7A7B8B8A7*0*0E8*0*0,13.8,7.6,66,95700,0,1415,311,0,0,0,0,0,0,0,0,0.0,0,0,9.9,22000,9,999999999,0,0.2680,0,88,0.000,0.0,0.0\n1987,1,1,4,60,C9C9C9C9*0?9?9?9?9?9?9?9A7A7B8B8A7*0*0E8*0*0,12.7,7.3,70,95700,0,1415,306,0,0,0,0,0,0,0,0,0.0,0,0,9.9,22000,9,999999999,0,0.2680,0,88,0.000,0.0,0.0".to_string();
@@ -505,6 +613,25 @@ mod tests {
         // Data
         assert_eq!(epw.data.len(), 4);
 
+        // typical and extreme periods
+        assert_eq!(epw.typical_periods.len(), 6);
+        let hot = &epw.typical_periods[0];
+        assert!(hot.is_extreme_hot());
+        assert_eq!((hot.start.month, hot.start.day), (1, 20));
+        assert_eq!((hot.end.month, hot.end.day), (1, 26));
+        assert_eq!(epw.typical_periods[1].kind, EPWPeriodKind::Typical);
+        assert_eq!(
+            (
+                epw.typical_periods[1].start.month,
+                epw.typical_periods[1].start.day
+            ),
+            (12, 8)
+        );
+        assert!(epw.typical_periods[2].is_extreme_cold());
+
+        // No daylight saving time
+        assert!(epw.daylight_saving.is_none());
+
         // ground temperature
         assert_eq!(epw.ground_temperature.len(), 3);
         assert_eq!(epw.ground_temperature[0].depth, 0.5);
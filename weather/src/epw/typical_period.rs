@@ -0,0 +1,64 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use calendar::Date;
+use serde::{Deserialize, Serialize};
+
+/// The kind of period reported in the `TYPICAL/EXTREME PERIODS`
+/// header of an EPW file
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EPWPeriodKind {
+    /// A period with conditions close to the average of its season
+    Typical,
+
+    /// A period with the most extreme conditions of its season
+    Extreme,
+}
+
+/// A typical or extreme period (usually a week), as
+/// reported in the header of an EPW file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EPWTypicalPeriod {
+    /// The description of the period (e.g., `"Summer - Week
+    /// Nearest Max Temperature For Period"`)
+    pub name: String,
+
+    /// Whether the period is typical or extreme
+    pub kind: EPWPeriodKind,
+
+    /// The first day of the period (at hour 0)
+    pub start: Date,
+
+    /// The last day of the period (at hour 0), which may be
+    /// earlier in the year than `start` if the period goes
+    /// through New Year
+    pub end: Date,
+}
+
+impl EPWTypicalPeriod {
+    /// Whether this is the week with the lowest temperatures of its season
+    pub fn is_extreme_cold(&self) -> bool {
+        self.kind == EPWPeriodKind::Extreme && self.name.to_lowercase().contains("min")
+    }
+
+    /// Whether this is the week with the highest temperatures of its season
+    pub fn is_extreme_hot(&self) -> bool {
+        self.kind == EPWPeriodKind::Extreme && self.name.to_lowercase().contains("max")
+    }
+}
@@ -20,12 +20,15 @@ SOFTWARE.
 
 use super::ground_temperature::EPWGroundTemperature;
 use super::scanner::EPWScanner;
+use super::typical_period::EPWTypicalPeriod;
 use super::weather_line::EPWWeatherLine;
 use crate::location::Location;
-use crate::Weather;
+use crate::{CurrentWeather, RadiationInterpolation, Weather};
+use calendar::DaylightSaving;
 
 use serde::{Deserialize, Serialize};
 use std::{fmt::Display, path::Path};
+use utils::SimpleError;
 
 /// A structure representing an EPW file
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +39,13 @@ pub struct EPWWeather {
     /// The [`EPWGroundTemperature`] of the file
     pub ground_temperature: Vec<EPWGroundTemperature>,
 
+    /// The [`EPWTypicalPeriod`]s reported in the file
+    #[serde(default)]
+    pub typical_periods: Vec<EPWTypicalPeriod>,
+
+    /// The daylight saving period reported in the file, if any
+    pub daylight_saving: Option<DaylightSaving>,
+
     /// The weather data
     pub data: Vec<EPWWeatherLine>,
 }
@@ -46,24 +56,43 @@ impl std::default::Default for EPWWeather {
             location: Location::default(),
             data: Vec::with_capacity(8670),
             ground_temperature: Vec::with_capacity(1),
+            typical_periods: Vec::with_capacity(6),
+            daylight_saving: None,
         }
     }
 }
 
 impl EPWWeather {
     /// Creates an `EPWWeather` from a file
-    pub fn from_file<P: AsRef<Path> + Display>(filename: P) -> Result<Self, String> {
+    pub fn from_file<P: AsRef<Path> + Display>(filename: P) -> Result<Self, SimpleError> {
         EPWScanner::from_file(filename)
     }
+
+    /// Parses an `EPWWeather` from the contents of an EPW file
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, SimpleError> {
+        EPWScanner::build_weather_file(&bytes.to_vec())
+    }
 }
 
 impl std::convert::From<EPWWeather> for Weather {
+    /// Files with data for February 29 (i.e., actual weather of a leap
+    /// year) keep the years of their data, so that they are represented
+    /// faithfully. Other files are year-agnostic.
     fn from(epw: EPWWeather) -> Weather {
-        let data = epw.data.iter().map(|ln| ln.into()).collect();
+        let mut data: Vec<CurrentWeather> = epw.data.iter().map(|ln| ln.into()).collect();
+        if epw.data.iter().any(|ln| ln.month == 2 && ln.day == 29) {
+            for (w, ln) in data.iter_mut().zip(epw.data.iter()) {
+                w.date.year = Some(ln.year as i32);
+            }
+        }
 
         Weather {
             data,
             location: epw.location,
+            ground_temperature: epw.ground_temperature,
+            typical_periods: epw.typical_periods,
+            daylight_saving: epw.daylight_saving,
+            radiation_interpolation: RadiationInterpolation::default(),
         }
     }
 }
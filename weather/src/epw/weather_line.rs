@@ -162,8 +162,13 @@ impl std::convert::From<&EPWWeatherLine> for CurrentWeather {
             month: epw.month,
             day: epw.day,
             hour: epw.hour - 0.000001,
+            year: None,
         };
 
+        // Missing values are reported as 9999
+        let horizontal_infrared_radiation_intensity =
+            Some(epw.horizontal_infrared_radiation_intensity).filter(|v| *v < 9999.);
+
         CurrentWeather {
             date,
             dry_bulb_temperature: epw.dry_bulb_temperature,
@@ -173,9 +178,7 @@ impl std::convert::From<&EPWWeatherLine> for CurrentWeather {
             diffuse_horizontal_radiation: epw.diffuse_horizontal_radiation,
             wind_speed: epw.wind_speed,
             wind_direction: epw.wind_direction.to_radians(),
-            horizontal_infrared_radiation_intensity: Some(
-                epw.horizontal_infrared_radiation_intensity,
-            ),
+            horizontal_infrared_radiation_intensity,
             opaque_sky_cover: epw.opaque_sky_cover / 10.0, // convert units
             relative_humidity: epw.relative_humidity / 100.0, // convert units
             pressure: epw.atmospheric_station_pressure,
@@ -192,6 +195,7 @@ impl EPWWeatherLine {
             // We count hours from 0 to 23.9999, EPW files include
             // hours from 1 to 24.99
             hour: self.hour, // - 1.0,
+            year: None,
         }
     }
 }
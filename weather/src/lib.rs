@@ -54,7 +54,9 @@ const PI: Float = std::f64::consts::PI;
 ///
 /// North points in the Y direction. East points in the X direction. Up points in Z.
 pub mod solar;
-pub use self::solar::{PerezSky, ReinhartSky, SkyUnits, Solar, Time};
+pub use self::solar::{
+    DiffuseSkyModel, PerezSky, ReinhartSky, SkyUnits, Solar, TiltedIrradiance, Time,
+};
 
 /// Data associated to a specific Location
 pub mod location;
@@ -67,22 +69,62 @@ pub use crate::current_weather::CurrentWeather;
 /// For handling EPW Files
 pub mod epw;
 pub use epw::{
-    ground_temperature::EPWGroundTemperature, weather::EPWWeather, weather_line::EPWWeatherLine,
+    ground_temperature::EPWGroundTemperature,
+    typical_period::{EPWPeriodKind, EPWTypicalPeriod},
+    weather::EPWWeather,
+    weather_line::EPWWeatherLine,
 };
+
+/// Psychrometric functions, for calculating the properties of moist air
+pub mod psychrometrics;
+
+/// Options for calculating the solar radiation in between the
+/// instants given in the weather data
+pub mod radiation_interpolation;
+pub use crate::radiation_interpolation::RadiationInterpolation;
+
+/// For reading weather data in NREL's TMY3 format
+pub mod tmy3;
+
+/// For reading weather data in a simple, documented, CSV format
+pub mod csv_weather;
+
 use serde::{Deserialize, Serialize};
+pub use utils::SimpleError;
 
 /// Allows creating weathers that can be used for highly-specific
 /// simulation. E.g., Having a sinusoidal exterior temperature with no
 /// sun.
 pub mod synthetic_weather;
 pub use crate::synthetic_weather::SyntheticWeather;
-pub use calendar::Date;
+
+/// Weather coming from a live feed, for real-time applications
+pub mod streaming_weather;
+pub use crate::streaming_weather::StreamingWeather;
+
+/// Design days for sizing simulations, derived from the
+/// statistics of a year of weather data
+pub mod design_day;
+pub use crate::design_day::DesignDay;
+pub use calendar::{Date, DaylightSaving};
 
 /// The basic trait defining a Weather that can be used in
 /// Building Simulation
 pub trait WeatherTrait: Sync {
     /// Retreives a [`CurrentWeather`] object based on the date.
     fn get_weather_data(&self, date: Date) -> CurrentWeather;
+
+    /// Retrieves the undisturbed ground temperature at a certain `depth` (in $`m`$),
+    /// if the weather has such information. Returns `None` by default.
+    fn get_ground_temperature(&self, _date: Date, _depth: Float) -> Option<Float> {
+        None
+    }
+
+    /// Retrieves the effective temperature of the sky (in C) used for
+    /// calculating long-wave radiation exchange. See [`CurrentWeather::sky_temperature`]
+    fn get_sky_temperature(&self, date: Date) -> Float {
+        self.get_weather_data(date).sky_temperature()
+    }
 }
 
 /// A structure containing weather data
@@ -95,13 +137,52 @@ pub struct Weather {
     ///
     /// This is based on EPW Files information
     pub location: Location,
+
+    /// The monthly ground temperatures at different depths, as
+    /// reported in EPW Files
+    #[serde(default)]
+    pub ground_temperature: Vec<EPWGroundTemperature>,
+
+    /// The typical and extreme periods (usually weeks) of the
+    /// year, as reported in EPW Files
+    #[serde(default)]
+    pub typical_periods: Vec<EPWTypicalPeriod>,
+
+    /// The period of the year in which clocks are one hour ahead
+    /// of standard time, as reported in EPW Files
+    #[serde(default)]
+    pub daylight_saving: Option<DaylightSaving>,
+
+    /// How the solar radiation is calculated in between the
+    /// instants given in the `data`. Defaults to `Linear`.
+    #[serde(default)]
+    pub radiation_interpolation: RadiationInterpolation,
 }
 
 impl Weather {
     /// Gets a weather line corresponding to a specific [`Date`].
     ///
-    /// It interpolates if necessary    
+    /// It interpolates if necessary. If the data has years (see
+    /// [`with_years`](Self::with_years)), dates without a year are assumed
+    /// to be in the first year of the data, and dates outside of the data
+    /// get the closest line; otherwise, the data wraps around the year.
+    ///
+    /// The solar radiation is calculated as established by the
+    /// [`radiation_interpolation`](Self::radiation_interpolation).
     pub fn find_weather_line(&self, date: Date) -> CurrentWeather {
+        let mut w = self.interpolate_weather_line(date);
+        if self.radiation_interpolation == RadiationInterpolation::SolarGeometry {
+            self.redistribute_radiation(date, &mut w);
+        }
+        w
+    }
+
+    /// Interpolates all the variables of the weather data linearly
+    fn interpolate_weather_line(&self, mut date: Date) -> CurrentWeather {
+        let first_year = self.data.first().and_then(|w| w.date.year);
+        if date.year.is_none() {
+            date.year = first_year;
+        }
         match self.data.binary_search_by(|x| x.date.cmp(&date)) {
             Ok(i) => {
                 // Exact match.
@@ -109,7 +190,10 @@ impl Weather {
             }
             Err(i) => {
                 let n = self.data.len();
-                if i == 0 || i == n {
+                if (i == 0 || i == n) && first_year.is_some() {
+                    // Multi-year data does not wrap around
+                    self.data[i.min(n - 1)]
+                } else if i == 0 || i == n {
                     // Border condition: Date is between the last and the first dates found in the data.
                     // This means we need to interpolate with element 0.
                     let last_date = self.data[n - 1].date;
@@ -124,17 +208,97 @@ impl Weather {
                     let before = self.data[i - 1].date;
                     let after = self.data[i].date;
 
-                    let before_n = before.day_of_year();
-                    let after_n = after.day_of_year();
-                    let date_n = date.day_of_year();
-
-                    let x = (date_n - before_n) / (after_n - before_n);
+                    let x = (date - before) / (after - before);
                     self.data[i - 1].interpolate(&self.data[i], x)
                 }
             }
         }
     }
 
+    /// Redistributes the radiation of the line covering `date` (i.e.,
+    /// the first one at or after it) according to the position of the
+    /// sun. See [`RadiationInterpolation::SolarGeometry`]
+    fn redistribute_radiation(&self, mut date: Date, w: &mut CurrentWeather) {
+        let n = self.data.len();
+        let first_year = self.data.first().and_then(|w| w.date.year);
+        if n < 2 {
+            return;
+        }
+        if date.year.is_none() {
+            date.year = first_year;
+        }
+        let i = match self.data.binary_search_by(|x| x.date.cmp(&date)) {
+            Ok(i) | Err(i) => i,
+        };
+        let (prev, i) = if i > 0 && i < n {
+            (i - 1, i)
+        } else if first_year.is_none() {
+            // Between the last and the first lines
+            (n - 1, 0)
+        } else {
+            // Multi-year data does not wrap around
+            return;
+        };
+
+        let start = self.data[prev].date.day_of_year();
+        let mut end = self.data[i].date.day_of_year();
+        let mut t = date.day_of_year();
+        if end < start {
+            // The interval goes through New Year
+            end += 365.;
+            if t < start {
+                t += 365.;
+            }
+        }
+        if end - start > 1. {
+            // Not an interval, but a gap in the data
+            return;
+        }
+        let solar = self.location.get_solar();
+        radiation_interpolation::redistribute(&solar, &self.data[i], start, end, t, w);
+    }
+
+    /// Assigns years to the data, which is assumed to be in chronological
+    /// order: the first lines are in `first_year`, and the year advances
+    /// every time the data goes through New Year.
+    ///
+    /// This allows concatenating multi-year datasets (e.g., actual
+    /// meteorological years) and simulating them continuously, instead
+    /// of wrapping around a single year.
+    ///
+    /// ```
+    /// use weather::{CurrentWeather, Date, Weather};
+    ///
+    /// let line = |month, day| CurrentWeather {
+    ///     date: Date { month, day, hour: 12., year: None },
+    ///     ..CurrentWeather::default()
+    /// };
+    /// let mut weather = Weather {
+    ///     data: vec![line(12, 31)],
+    ///     ..Weather::default()
+    /// }
+    /// .with_years(2019);
+    /// weather += Weather {
+    ///     data: vec![line(1, 1)],
+    ///     ..Weather::default()
+    /// }
+    /// .with_years(2020);
+    /// assert_eq!(weather.data[1].date.year, Some(2020));
+    /// ```
+    pub fn with_years(mut self, first_year: i32) -> Self {
+        let mut year = first_year;
+        let mut last: Option<Date> = None;
+        for w in self.data.iter_mut() {
+            w.date.year = None;
+            if matches!(last, Some(last) if w.date < last) {
+                year += 1;
+            }
+            last = Some(w.date);
+            w.date.year = Some(year);
+        }
+        self
+    }
+
     /// Sorts the data by date
     pub fn sort_data(&mut self) {
         self.data.sort_by(|a, b| a.date.cmp(&b.date));
@@ -198,6 +362,133 @@ impl Weather {
 
         Ok(())
     }
+
+    /// Extracts the data between the days of `start` and `end` (both
+    /// included, ignoring their hours) into a new `Weather`. If `end` is
+    /// earlier in the year than `start`, the period goes through New Year.
+    pub fn extract_days(&self, start: Date, end: Date) -> Weather {
+        let day = |d: Date| Date { hour: 0., ..d }.day_of_year();
+        let (start, end) = (day(start), day(end));
+        let data = self
+            .data
+            .iter()
+            .filter(|w| {
+                let n = day(w.date);
+                if start <= end {
+                    n >= start && n <= end
+                } else {
+                    n >= start || n <= end
+                }
+            })
+            .copied()
+            .collect();
+
+        Weather {
+            data,
+            location: self.location.clone(),
+            ground_temperature: self.ground_temperature.clone(),
+            typical_periods: Vec::new(),
+            daylight_saving: self.daylight_saving,
+            radiation_interpolation: self.radiation_interpolation,
+        }
+    }
+
+    /// The average dry bulb temperature of each day in the data, in order
+    fn daily_mean_temperatures(&self) -> Vec<(Date, Float)> {
+        let mut days: Vec<(Date, Float, usize)> = Vec::new();
+        for w in self.data.iter() {
+            match days.last_mut() {
+                Some((d, sum, n)) if d.month == w.date.month && d.day == w.date.day => {
+                    *sum += w.dry_bulb_temperature;
+                    *n += 1;
+                }
+                _ => days.push((w.date, w.dry_bulb_temperature, 1)),
+            }
+        }
+        days.into_iter()
+            .map(|(d, sum, n)| (d, sum / n as Float))
+            .collect()
+    }
+
+    /// Finds the week (i.e., seven consecutive days in the data) whose
+    /// average temperature minimizes `score`
+    fn find_week<F: Fn(Float) -> Float>(&self, score: F) -> Result<Weather, String> {
+        const DAYS: usize = 7;
+        let days = self.daily_mean_temperatures();
+        if days.len() < DAYS {
+            return Err(format!(
+                "Cannot find a week in a weather with only {} days of data",
+                days.len()
+            ));
+        }
+        let (first, _) = days
+            .windows(DAYS)
+            .enumerate()
+            .map(|(i, w)| {
+                let mean = w.iter().map(|(_, t)| t).sum::<Float>() / DAYS as Float;
+                (i, score(mean))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap(); // we know there is at least one week
+        Ok(self.extract_days(days[first].0, days[first + DAYS - 1].0))
+    }
+
+    /// The average dry bulb temperature of the data
+    fn mean_temperature(&self) -> Float {
+        self.data
+            .iter()
+            .map(|w| w.dry_bulb_temperature)
+            .sum::<Float>()
+            / self.data.len() as Float
+    }
+
+    /// Extracts the coldest week of the year into a new `Weather`. This
+    /// is the extreme cold period reported in the EPW file (i.e., the
+    /// Winter week nearest the minimum temperature) or, if there is none,
+    /// the seven consecutive days with the lowest average temperature.
+    pub fn extreme_cold_week(&self) -> Result<Weather, String> {
+        match self.typical_periods.iter().find(|p| p.is_extreme_cold()) {
+            Some(p) => Ok(self.extract_days(p.start, p.end)),
+            None => self.find_week(|t| t),
+        }
+    }
+
+    /// Extracts the hottest week of the year into a new `Weather`. This
+    /// is the extreme hot period reported in the EPW file (i.e., the
+    /// Summer week nearest the maximum temperature) or, if there is none,
+    /// the seven consecutive days with the highest average temperature.
+    pub fn extreme_hot_week(&self) -> Result<Weather, String> {
+        match self.typical_periods.iter().find(|p| p.is_extreme_hot()) {
+            Some(p) => Ok(self.extract_days(p.start, p.end)),
+            None => self.find_week(|t| -t),
+        }
+    }
+
+    /// Extracts a typical week into a new `Weather`. Of the typical periods
+    /// reported in the EPW file, this is the one whose average temperature is
+    /// closest to that of the whole year. If there are none, it is the seven
+    /// consecutive days whose average temperature is closest to that of the year.
+    pub fn typical_week(&self) -> Result<Weather, String> {
+        if self.data.is_empty() {
+            return Err("Cannot find a typical week in an empty weather".to_string());
+        }
+        let annual_mean = self.mean_temperature();
+        let typical = self
+            .typical_periods
+            .iter()
+            .filter(|p| p.kind == EPWPeriodKind::Typical)
+            .map(|p| self.extract_days(p.start, p.end))
+            .filter(|w| !w.data.is_empty())
+            .min_by(|a, b| {
+                let a = (a.mean_temperature() - annual_mean).abs();
+                let b = (b.mean_temperature() - annual_mean).abs();
+                a.total_cmp(&b)
+            });
+        match typical {
+            Some(w) => Ok(w),
+            None => self.find_week(|t| (t - annual_mean).abs()),
+        }
+    }
 }
 
 impl std::ops::AddAssign<Self> for Weather {
@@ -214,6 +505,28 @@ impl WeatherTrait for Weather {
     fn get_weather_data(&self, date: Date) -> CurrentWeather {
         self.find_weather_line(date)
     }
+
+    /// Interpolates the ground temperatures linearly in depth. Depths
+    /// outside of the reported range take the value of the closest one.
+    fn get_ground_temperature(&self, date: Date, depth: Float) -> Option<Float> {
+        let mut grounds: Vec<&EPWGroundTemperature> = self.ground_temperature.iter().collect();
+        grounds.sort_by(|a, b| a.depth.total_cmp(&b.depth));
+
+        let first = grounds.first()?;
+        if depth <= first.depth {
+            return Some(first.temperature(date));
+        }
+        for pair in grounds.windows(2) {
+            let (above, below) = (pair[0], pair[1]);
+            if depth <= below.depth {
+                let x = (depth - above.depth) / (below.depth - above.depth);
+                let t_above = above.temperature(date);
+                let t_below = below.temperature(date);
+                return Some(t_above + x * (t_below - t_above));
+            }
+        }
+        grounds.last().map(|g| g.temperature(date))
+    }
 }
 
 #[cfg(test)]
@@ -235,6 +548,7 @@ mod tests {
             month: 1,
             day: 1,
             hour: 1.,
+            year: None,
         });
 
         assert_close!(
@@ -252,6 +566,7 @@ mod tests {
             month: 1,
             day: 1,
             hour: 2.,
+            year: None,
         });
 
         assert_close!(
@@ -269,6 +584,7 @@ mod tests {
             month: 1,
             day: 1,
             hour: 1.5,
+            year: None,
         });
 
         assert_close!(
@@ -290,11 +606,13 @@ mod tests {
                 month: 1,
                 day: 1,
                 hour: 0.,
+                year: None,
             },
             Date {
                 month: 1,
                 day: 25,
                 hour: 0.,
+                year: None,
             },
             60. * 60. / 2., // half an hour
         );
@@ -323,11 +641,13 @@ mod tests {
         let mut first = Weather {
             location: Location::default(),
             data: first_dates,
+            ..Weather::default()
         };
 
         let second = Weather {
             location: Location::default(),
             data: second_dates,
+            ..Weather::default()
         };
 
         first += second;
@@ -346,6 +666,7 @@ mod tests {
                 month: 1,
                 day: 1,
                 hour: 0.0,
+                year: None,
             },
             dry_bulb_temperature: 22.,
             ..CurrentWeather::default()
@@ -356,6 +677,7 @@ mod tests {
                 month: 1,
                 day: 1,
                 hour: 0.0,
+                year: None,
             },
             dry_bulb_temperature: 33.,
             ..CurrentWeather::default()
@@ -374,6 +696,7 @@ mod tests {
                         month: 12,
                         day: 1,
                         hour: 0.0,
+                        year: None,
                     },
                     ..CurrentWeather::default()
                 },
@@ -382,6 +705,7 @@ mod tests {
                         month: 1,
                         day: 1,
                         hour: 0.0,
+                        year: None,
                     },
                     ..CurrentWeather::default()
                 },
@@ -390,10 +714,12 @@ mod tests {
                         month: 10,
                         day: 1,
                         hour: 0.0,
+                        year: None,
                     },
                     ..CurrentWeather::default()
                 },
             ],
+            ..Weather::default()
         };
 
         let mut wclone = w.clone();
@@ -404,4 +730,248 @@ mod tests {
         assert_eq!(w.data[1].date, wclone.data[0].date);
         assert_eq!(w.data[2].date, wclone.data[1].date);
     }
+
+    /// A year of hourly data with a sinusoidal temperature, coldest at
+    /// noon of January 21 and hottest half a year later
+    fn sinusoidal_year() -> Weather {
+        let data = (0..365 * 24)
+            .map(|i| {
+                let n = i as Float / 24.;
+                let mut date = Date::from_day_of_year(n);
+                date.hour += 0.5;
+                CurrentWeather {
+                    date,
+                    dry_bulb_temperature: 15. - 10. * (2. * PI * (n - 20.5) / 365.).cos(),
+                    ..CurrentWeather::default()
+                }
+            })
+            .collect();
+        Weather {
+            data,
+            ..Weather::default()
+        }
+    }
+
+    #[test]
+    fn test_extract_days() {
+        let w = sinusoidal_year();
+        let date = |month, day| Date {
+            month,
+            day,
+            hour: 0.,
+            year: None,
+        };
+
+        let week = w.extract_days(date(3, 1), date(3, 7));
+        assert_eq!(week.data.len(), 7 * 24);
+        assert_eq!(week.data[0].date.month, 3);
+        assert_eq!(week.data[0].date.day, 1);
+
+        // Through New Year... still sorted
+        let week = w.extract_days(date(12, 29), date(1, 4));
+        assert_eq!(week.data.len(), 7 * 24);
+        assert_eq!(week.data[0].date.month, 1);
+        assert_eq!(week.data.last().unwrap().date.month, 12);
+    }
+
+    #[test]
+    fn test_recomputed_weeks() -> Result<(), String> {
+        let w = sinusoidal_year();
+
+        // Coldest week is centred on January 21
+        let cold = w.extreme_cold_week()?;
+        assert_eq!(cold.data.len(), 7 * 24);
+        assert_eq!(cold.data[0].date.month, 1);
+        assert_eq!(cold.data[0].date.day, 18);
+
+        // Hottest week is centred on the night of July 22
+        let hot = w.extreme_hot_week()?;
+        assert_eq!(hot.data.len(), 7 * 24);
+        assert_eq!(hot.data[0].date.month, 7);
+        assert!([19, 20].contains(&hot.data[0].date.day));
+
+        let typical = w.typical_week()?;
+        assert_eq!(typical.data.len(), 7 * 24);
+        assert_close!(typical.mean_temperature(), 15., 0.5);
+
+        // Not enough data
+        let short = w.extract_days(cold.data[0].date, cold.data[0].date);
+        assert!(short.extreme_cold_week().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_epw_weeks() -> Result<(), String> {
+        let w: Weather = EPWWeather::from_file("./test_data/wellington.epw")?.into();
+        assert_eq!(w.typical_periods.len(), 6);
+
+        // Winter - Week Nearest Min Temperature For Period, 7/20 - 7/26
+        let cold = w.extreme_cold_week()?;
+        assert_eq!(cold.data.len(), 7 * 24);
+        assert_eq!((cold.data[0].date.month, cold.data[0].date.day), (7, 20));
+
+        // Summer - Week Nearest Max Temperature For Period, 2/10 - 2/16
+        let hot = w.extreme_hot_week()?;
+        assert_eq!((hot.data[0].date.month, hot.data[0].date.day), (2, 10));
+        assert!(hot.mean_temperature() > cold.mean_temperature());
+
+        // One of the typical weeks
+        let typical = w.typical_week()?;
+        assert_eq!(typical.data.len(), 7 * 24);
+        assert!(typical.typical_periods.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_multi_year() {
+        let day = |month, day, temperature| {
+            (0..24).map(move |h| CurrentWeather {
+                date: Date {
+                    month,
+                    day,
+                    hour: h as Float + 0.5,
+                    year: None,
+                },
+                dry_bulb_temperature: temperature,
+                ..CurrentWeather::default()
+            })
+        };
+        let mut weather = Weather {
+            data: day(12, 31, 10.).collect(),
+            ..Weather::default()
+        }
+        .with_years(2019);
+        weather += Weather {
+            data: day(1, 1, 20.).chain(day(1, 2, 30.)).collect(),
+            ..Weather::default()
+        }
+        .with_years(2020);
+        assert_eq!(weather.data[0].date.year, Some(2019));
+        assert_eq!(weather.data[24].date.year, Some(2020));
+
+        // Interpolates through New Year
+        let date = |month, day, hour, year| Date {
+            month,
+            day,
+            hour,
+            year,
+        };
+        let w = weather.find_weather_line(date(1, 1, 0., Some(2020)));
+        assert_close!(w.dry_bulb_temperature, 15.);
+
+        // Does not wrap around
+        let w = weather.find_weather_line(date(1, 3, 12., Some(2020)));
+        assert_close!(w.dry_bulb_temperature, 30.);
+
+        // Dates without a year are in the first year
+        let w = weather.find_weather_line(date(12, 31, 12.5, None));
+        assert_close!(w.dry_bulb_temperature, 10.);
+    }
+
+    #[test]
+    fn test_leap_year_epw() {
+        let mut raw_source = "LOCATION,SANTIAGO,-,CHL,IWEC Data,855740,-33.38,-70.78,-4.0,476.0\nDATA PERIODS,1,1,Data,Saturday, 2/28, 3/ 1".to_string();
+        for (month, day, temperature) in [(2, 28, 10.), (2, 29, 20.), (3, 1, 30.)] {
+            for hour in 1..=24 {
+                raw_source += &format!("\n2020,{month},{day},{hour},60,C9C9C9C9*0?9?9?9?9?9?9?9A7A7B8B8A7*0*0E8*0*0,{temperature},9.6,63,95600,0,1415,326,0,0,0,0,0,0,0,150,1.5,0,0,9.9,77777,9,999999999,0,0.2680,0,88,0.000,0.0,0.0");
+            }
+        }
+        let source: Vec<u8> = raw_source.into_bytes();
+        let weather: Weather = EPWScanner::build_weather_file(&source).unwrap().into();
+
+        assert_eq!(weather.data.len(), 3 * 24);
+        assert_eq!(weather.data[0].date.year, Some(2020));
+
+        // February 29 is not confused with March 1
+        let date = |month, day, hour| Date {
+            month,
+            day,
+            hour,
+            year: None,
+        };
+        let w = weather.find_weather_line(date(2, 29, 12.));
+        assert_close!(w.dry_bulb_temperature, 20.);
+        let w = weather.find_weather_line(date(3, 1, 12.));
+        assert_close!(w.dry_bulb_temperature, 30.);
+    }
+
+    #[test]
+    fn test_get_ground_temperature() {
+        let date = Date {
+            month: 3,
+            day: 1,
+            hour: 12.,
+            year: None,
+        };
+
+        // No information
+        let mut w = Weather::default();
+        assert!(w.get_ground_temperature(date, 1.).is_none());
+
+        w.ground_temperature = vec![
+            EPWGroundTemperature {
+                depth: 2.,
+                average_monthly_temperature: [10.; 12],
+                ..EPWGroundTemperature::default()
+            },
+            EPWGroundTemperature {
+                depth: 0.5,
+                average_monthly_temperature: [16.; 12],
+                ..EPWGroundTemperature::default()
+            },
+        ];
+
+        // Clamped
+        assert_close!(w.get_ground_temperature(date, 0.).unwrap(), 16.);
+        assert_close!(w.get_ground_temperature(date, 4.).unwrap(), 10.);
+        // Interpolated
+        assert_close!(w.get_ground_temperature(date, 1.).unwrap(), 14.);
+    }
+
+    #[test]
+    fn test_radiation_interpolation() -> Result<(), String> {
+        let mut weather: Weather = EPWWeather::from_file("./test_data/wellington.epw")?.into();
+        let date = |day: u8, hour: Float| Date {
+            month: 1,
+            day,
+            hour,
+            year: None,
+        };
+
+        // The average of the linear interpolation is not that of the hour
+        let mean_global = |weather: &Weather, day: u8, hour: Float| {
+            (0..60)
+                .map(|m| {
+                    let w = weather.find_weather_line(date(day, hour + (m as Float + 0.5) / 60.));
+                    w.global_horizontal_radiation
+                })
+                .sum::<Float>()
+                / 60.
+        };
+        // January 15, from 12:00 to 13:00
+        let line = weather.data[14 * 24 + 12];
+        assert_eq!((line.date.day, line.date.hour.round()), (15, 13.));
+        weather.radiation_interpolation = RadiationInterpolation::SolarGeometry;
+        assert!(line.global_horizontal_radiation > 100.);
+        let mean = mean_global(&weather, 15, 12.);
+        assert!(
+            (mean - line.global_horizontal_radiation).abs()
+                < 0.01 * line.global_horizontal_radiation,
+            "mean = {}, expected {}",
+            mean,
+            line.global_horizontal_radiation
+        );
+
+        // Radiation grows throughout the morning, within the hour
+        let early = weather.find_weather_line(date(15, 9.1));
+        let late = weather.find_weather_line(date(15, 9.9));
+        assert!(early.global_horizontal_radiation < late.global_horizontal_radiation);
+        assert_close!(early.direct_normal_radiation, late.direct_normal_radiation);
+
+        // Nothing at night
+        let night = weather.find_weather_line(date(15, 1.5));
+        assert_close!(night.global_horizontal_radiation, 0.);
+        assert_close!(night.direct_normal_radiation, 0.);
+        Ok(())
+    }
 }
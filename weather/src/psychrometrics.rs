@@ -0,0 +1,195 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+//! Psychrometric functions (i.e., the properties of moist air).
+//!
+//! Temperatures are in C, pressures in Pa, relative humidities go
+//! from 0 to 1 and humidity ratios are in kg of water per kg of dry air.
+
+use crate::Float;
+
+/// The standard atmospheric pressure at sea level, in Pa. Used when
+/// the weather does not report one.
+pub const STANDARD_PRESSURE: Float = 101325.;
+
+/// The ratio between the molecular masses of water and dry air
+const MOLECULAR_MASS_RATIO: Float = 0.621945;
+
+/// Constants of the Magnus formula
+const MAGNUS_A: Float = 610.94;
+const MAGNUS_B: Float = 17.625;
+const MAGNUS_C: Float = 243.04;
+
+/// Calculates the saturation pressure of water vapour (in Pa) at a
+/// certain temperature (in C), using the Magnus formula
+pub fn saturation_pressure(temperature: Float) -> Float {
+    MAGNUS_A * (MAGNUS_B * temperature / (temperature + MAGNUS_C)).exp()
+}
+
+/// Calculates the partial pressure of water vapour (in Pa) in air with a
+/// certain humidity ratio and (total) pressure
+pub fn vapour_pressure(humidity_ratio: Float, pressure: Float) -> Float {
+    pressure * humidity_ratio / (MOLECULAR_MASS_RATIO + humidity_ratio)
+}
+
+/// Calculates the humidity ratio of air at a certain temperature,
+/// relative humidity and pressure
+pub fn humidity_ratio(temperature: Float, relative_humidity: Float, pressure: Float) -> Float {
+    let pw = relative_humidity * saturation_pressure(temperature);
+    MOLECULAR_MASS_RATIO * pw / (pressure - pw)
+}
+
+/// Calculates the relative humidity of air at a certain temperature,
+/// humidity ratio and pressure
+pub fn relative_humidity(temperature: Float, humidity_ratio: Float, pressure: Float) -> Float {
+    vapour_pressure(humidity_ratio, pressure) / saturation_pressure(temperature)
+}
+
+/// Calculates the dew point temperature of air at a certain temperature
+/// and relative humidity, inverting the Magnus formula
+pub fn dew_point_temperature(temperature: Float, relative_humidity: Float) -> Float {
+    let pw = relative_humidity.max(1e-6) * saturation_pressure(temperature);
+    let gamma = (pw / MAGNUS_A).ln();
+    MAGNUS_C * gamma / (MAGNUS_B - gamma)
+}
+
+/// Calculates the specific enthalpy of moist air (in J per kg of dry air),
+/// taking 0C as the reference
+pub fn enthalpy(temperature: Float, humidity_ratio: Float) -> Float {
+    1006. * temperature + humidity_ratio * (2.501e6 + 1860. * temperature)
+}
+
+/// Calculates the (thermodynamic) wet bulb temperature of air at a certain
+/// temperature, relative humidity and pressure.
+///
+/// This is the temperature at which the humidity ratio found through
+/// ASHRAE's psychrometric equation (Fundamentals 2017, Chapter 1, Eq. 33)
+/// equals the humidity ratio of the air, found by bisection between the dew
+/// point and the dry bulb temperatures.
+pub fn wet_bulb_temperature(
+    temperature: Float,
+    relative_humidity: Float,
+    pressure: Float,
+) -> Float {
+    let w = humidity_ratio(temperature, relative_humidity, pressure);
+    // The humidity ratio that corresponds to a certain wet bulb temperature
+    let w_at = |twb: Float| {
+        let ws = humidity_ratio(twb, 1., pressure);
+        ((2501. - 2.326 * twb) * ws - 1.006 * (temperature - twb))
+            / (2501. + 1.86 * temperature - 4.186 * twb)
+    };
+
+    let mut low = dew_point_temperature(temperature, relative_humidity).min(temperature);
+    let mut high = temperature;
+    for _ in 0..50 {
+        let mid = (low + high) / 2.;
+        if w_at(mid) > w {
+            high = mid;
+        } else {
+            low = mid;
+        }
+        if high - low < 1e-4 {
+            break;
+        }
+    }
+    (low + high) / 2.
+}
+
+/// Calculates the atmospheric pressure (in Pa) at a certain elevation (in m)
+/// above sea level, according to the standard atmosphere
+pub fn pressure_at_elevation(elevation: Float) -> Float {
+    STANDARD_PRESSURE * (1. - 2.25577e-5 * elevation).powf(5.2559)
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use crate::CurrentWeather;
+    use validate::assert_close;
+
+    #[test]
+    fn test_saturation() {
+        // ASHRAE Fundamentals, Table 3
+        assert_close!(saturation_pressure(0.), 611., 1.);
+        assert_close!(saturation_pressure(20.), 2339., 10.);
+        assert_close!(dew_point_temperature(20., 1.), 20., 1e-6);
+    }
+
+    #[test]
+    fn test_humidity_ratio() {
+        let w = humidity_ratio(20., 0.5, STANDARD_PRESSURE);
+        assert_close!(w, 0.00726, 1e-4);
+        assert_close!(relative_humidity(20., w, STANDARD_PRESSURE), 0.5, 1e-9);
+        assert_close!(
+            vapour_pressure(w, STANDARD_PRESSURE),
+            0.5 * saturation_pressure(20.),
+            1e-6
+        );
+    }
+
+    #[test]
+    fn test_dew_point() {
+        assert_close!(dew_point_temperature(20., 0.5), 9.3, 0.1);
+        assert_close!(dew_point_temperature(30., 0.3), 10.5, 0.1);
+    }
+
+    #[test]
+    fn test_enthalpy() {
+        assert_close!(enthalpy(0., 0.), 0.);
+        // Psychrometric chart: 20C, 50% is roughly 38.5 kJ/kg
+        let w = humidity_ratio(20., 0.5, STANDARD_PRESSURE);
+        assert_close!(enthalpy(20., w), 38.5e3, 0.5e3);
+    }
+
+    #[test]
+    fn test_wet_bulb() {
+        // Saturated air
+        assert_close!(wet_bulb_temperature(20., 1., STANDARD_PRESSURE), 20., 1e-3);
+        // Psychrometric chart
+        assert_close!(wet_bulb_temperature(25., 0.5, STANDARD_PRESSURE), 18., 0.2);
+        assert_close!(wet_bulb_temperature(35., 0.2, STANDARD_PRESSURE), 18.9, 0.3);
+    }
+
+    #[test]
+    fn test_current_weather() {
+        let w = CurrentWeather {
+            dry_bulb_temperature: 25.,
+            relative_humidity: 0.5,
+            ..CurrentWeather::default()
+        };
+        // No pressure reported
+        assert_close!(w.pressure_or_standard(), STANDARD_PRESSURE);
+        assert_close!(
+            w.humidity_ratio(),
+            humidity_ratio(25., 0.5, STANDARD_PRESSURE)
+        );
+        assert_close!(w.wet_bulb_temperature(), 17.9, 0.1);
+        assert_close!(w.enthalpy(), enthalpy(25., w.humidity_ratio()));
+    }
+
+    #[test]
+    fn test_pressure_at_elevation() {
+        assert_close!(pressure_at_elevation(0.), STANDARD_PRESSURE);
+        assert_close!(pressure_at_elevation(1500.), 84556., 100.);
+    }
+}
@@ -0,0 +1,96 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use crate::{CurrentWeather, Float, Solar, Time};
+use serde::{Deserialize, Serialize};
+
+/// The number of instants in which the position of the sun is sampled
+/// within each interval of the weather data
+const N_SAMPLES: usize = 20;
+
+/// The way in which the solar radiation of the weather data is
+/// calculated in between the instants that are explicitly given
+/// (e.g., for simulations with several timesteps per hour).
+///
+/// Other variables (e.g., temperatures) are always interpolated linearly.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RadiationInterpolation {
+    /// The radiation is interpolated linearly, like every other variable
+    #[default]
+    Linear,
+
+    /// Each value of the weather data is considered to be the average
+    /// radiation over the interval ending at its date (e.g., the preceding
+    /// hour, as in EPW files), and it is redistributed within such interval
+    /// according to the position of the sun. Horizontal radiation is
+    /// proportional to the cosine of the solar zenith, and direct normal
+    /// radiation is constant while the sun is up. This preserves the energy
+    /// of each interval, and there is no radiation before sunrise or after
+    /// sunset.
+    SolarGeometry,
+}
+
+/// Redistributes the radiation of `line`—the average over the interval
+/// from day `start` to day `end` (days of the year, in standard time)—to
+/// the instant `n` within that interval, according to the position of
+/// the sun.
+///
+/// If the sun is never up during the interval (i.e., the data and the
+/// solar geometry do not agree), the average values are kept.
+pub(crate) fn redistribute(
+    solar: &Solar,
+    line: &CurrentWeather,
+    start: Float,
+    end: Float,
+    n: Float,
+    w: &mut CurrentWeather,
+) {
+    let cos_zenith = |n: Float| {
+        solar
+            .sun_position(Time::Standard(n))
+            .map_or(0., |sun| sun.z.max(0.))
+    };
+    let dt = (end - start) / N_SAMPLES as Float;
+    let (sum, sunlit) = (0..N_SAMPLES)
+        .map(|i| cos_zenith(start + (i as Float + 0.5) * dt))
+        .fold((0., 0), |(sum, sunlit), c| {
+            (sum + c, if c > 0. { sunlit + 1 } else { sunlit })
+        });
+
+    if sunlit == 0 {
+        w.global_horizontal_radiation = line.global_horizontal_radiation;
+        w.diffuse_horizontal_radiation = line.diffuse_horizontal_radiation;
+        w.direct_normal_radiation = line.direct_normal_radiation;
+        return;
+    }
+
+    let mean_cos_zenith = sum / N_SAMPLES as Float;
+    let sunlit_fraction = sunlit as Float / N_SAMPLES as Float;
+    let c = cos_zenith(n);
+    if c > 0. {
+        let k = c / mean_cos_zenith;
+        w.global_horizontal_radiation = line.global_horizontal_radiation * k;
+        w.diffuse_horizontal_radiation = line.diffuse_horizontal_radiation * k;
+        w.direct_normal_radiation = line.direct_normal_radiation / sunlit_fraction;
+    } else {
+        w.global_horizontal_radiation = 0.;
+        w.diffuse_horizontal_radiation = 0.;
+        w.direct_normal_radiation = 0.;
+    }
+}
@@ -27,6 +27,8 @@ use super::CurrentWeather;
 use super::{Float, PI};
 use calendar::Date;
 pub use perez::{PerezSky, SkyUnits};
+mod tilted_surface;
+pub use tilted_surface::{DiffuseSkyModel, TiltedIrradiance};
 
 /// Calculates the Air-mass .PerezSky
 ///
@@ -1174,6 +1176,7 @@ mod tests {
             month: 8,
             day: 22,
             hour: 11.5,
+            year: None,
         };
 
         let n = date.day_of_year();
@@ -1228,6 +1231,7 @@ mod tests {
             month: 2,
             day: 3,
             hour: 10.5,
+            year: None,
         };
         let standard_n = standard_time.day_of_year();
 
@@ -1249,6 +1253,7 @@ mod tests {
                 month: month,
                 day: day,
                 hour: 0.,
+                year: None,
             };
             let n = date.day_of_year();
             assert_eq!(n, expected_n - 1.);
@@ -1290,6 +1295,7 @@ mod tests {
             month: 2,
             day: 13,
             hour: 10.5,
+            year: None,
         }
         .day_of_year();
 
@@ -1302,6 +1308,7 @@ mod tests {
             month: 2,
             day: 13,
             hour: 12.0,
+            year: None,
         }
         .day_of_year();
         let w = solar.hour_angle(Time::Solar(n)).to_degrees();
@@ -1312,6 +1319,7 @@ mod tests {
             month: 2,
             day: 13,
             hour: 13.0,
+            year: None,
         }
         .day_of_year();
         let w = solar.hour_angle(Time::Solar(n)).to_degrees();
@@ -1333,6 +1341,7 @@ mod tests {
             month: 2,
             day: 13,
             hour: 9.5,
+            year: None,
         }
         .day_of_year();
         let dir = solar
@@ -1360,6 +1369,7 @@ mod tests {
             month: 7,
             day: 1,
             hour: 18.5,
+            year: None,
         }
         .day_of_year();
         let dir = solar
@@ -1400,6 +1410,7 @@ mod tests {
             month: 2,
             day: 13,
             hour: 10.5,
+            year: None,
         }
         .day_of_year();
         let solar_dir = solar
@@ -1448,6 +1459,7 @@ mod tests {
             month: 3,
             day: 16,
             hour: 16.,
+            year: None,
         };
         let n = date.day_of_year();
         let n_midday = n.floor() + 0.5;
@@ -1732,11 +1744,13 @@ mod tests {
             month: 1,
             day: 1,
             hour: 0.0,
+            year: None,
         };
         let end = Date {
             month: 1,
             day: 3,
             hour: 0.0,
+            year: None,
         };
 
         let dates = Period::new(start, end, 3600.0);
@@ -281,7 +281,7 @@ impl PerezSky {
         dew_point: Float,
         diffuse_horizontal_irrad: Float,
         direct_normal_irrad: Float,
-    ) -> Box<dyn Fn(Vector3D) -> Float + Sync> {
+    ) -> Box<dyn Fn(Vector3D) -> Float + Send + Sync> {
         // Convert local into solar time
         let day = Time::Standard(date.day_of_year());
         let sun_position = solar.sun_position(day).unwrap();
@@ -410,6 +410,36 @@ impl PerezSky {
         Ok(vec)
     }
 
+    /// Creates a matrix with the radiance/luminance of the direct sun
+    /// only—distributed over the patches of the sky, see
+    /// [`ReinhartSky::sun_patches`]—with one column per timestep (i.e., the
+    /// equivalent of Radiance's `genskymtx -d`). The sun is placed at the
+    /// `date` of each element of `weather_data`.
+    ///
+    /// This allows calculating the contribution of the direct sun
+    /// separately from that of the diffuse sky, as in the
+    /// 3- and 5-phase methods.
+    pub fn gen_sun_mtx(
+        mf: usize,
+        solar: &Solar,
+        weather_data: &[CurrentWeather],
+        units: SkyUnits,
+    ) -> Result<Matrix, String> {
+        let r = ReinhartSky::new(mf);
+        let mut mtx = Matrix::new(0.0, r.n_bins, weather_data.len());
+        let mut vec = Matrix::new(0.0, r.n_bins, 1);
+        for (col, w) in weather_data.iter().enumerate() {
+            for bin in 0..r.n_bins {
+                vec.set(bin, 0, 0.0)?;
+            }
+            Self::update_sky_vec(&mut vec, mf, solar, w.date, *w, units, 0.0, false, true)?;
+            for bin in 0..r.n_bins {
+                mtx.set(bin, col, vec.get(bin, 0)?)?;
+            }
+        }
+        Ok(mtx)
+    }
+
     /// Updates a vector of values representing the luminance/radiance of each
     /// patch in the sky. This is meant to be used with the Daylight Coefficient approach.
     #[allow(clippy::too_many_arguments)]
@@ -527,44 +557,9 @@ impl PerezSky {
         }
 
         if add_sun && direct_normal_irrad > 1e-4 {
-            const N_SUNS: usize = 4;
-
-            /* IDENTIFY CLOSESTS PATCHES */
-            // Store the closest bin and the dot product between
-            // the sun position and the bin position
-            let mut closests: [(usize, Float); N_SUNS] = [(0, -1.); N_SUNS];
-            for bin in 0..r.n_bins {
-                let dir = r.bin_dir(bin);
-                let dot = dir * sun_position;
-                for sun_index in 0..N_SUNS {
-                    // A greated dot product implies being more close
-                    if dot > closests[sun_index].1 {
-                        // Shift vector
-                        for k in (sun_index + 1..N_SUNS).rev() {
-                            closests[k] = closests[k - 1];
-                        }
-                        closests[sun_index] = (bin, dot);
-                        break;
-                    }
-                }
-            }
-            /* SET WEIGHTS */
-            let mut weights: [Float; N_SUNS] = [-1.; N_SUNS];
-            let mut tot_weight: Float = 0.0;
-            for k in (0..N_SUNS).rev() {
-                let this_w = 1. / (1.002 - closests[k].1);
-                weights[k] = this_w;
-                tot_weight += this_w;
-            }
-
-            for k in (0..N_SUNS).rev() {
-                let mut val_add = weights[k] * dir_illum / (WHTEFFICACY * tot_weight);
-                let bin = closests[k].0;
+            for (bin, weight) in r.sun_patches(sun_position) {
                 let solid_angle = r.bin_solid_angle(bin);
-                val_add /= solid_angle;
-                // Add
-                // let old = vec.get(bin, 0).unwrap();
-                // vec.set(bin, 0, old + val_add).unwrap();
+                let val_add = weight * dir_illum / (WHTEFFICACY * solid_angle);
                 if !val_add.is_nan() {
                     vec.add_to_element(bin, 0, val_add)?;
                 }
@@ -760,7 +755,12 @@ mod tests {
         let month = 1;
         let day = 1;
         let hour = 5.5;
-        let date = Date { month, day, hour };
+        let date = Date {
+            month,
+            day,
+            hour,
+            year: None,
+        };
         let solar = Solar::new(lat, lon, std_mer);
         let albedo = 0.2;
 
@@ -835,7 +835,12 @@ mod tests {
         let month = 1;
         let day = 1;
         let hour = 5.5;
-        let date = Date { month, day, hour };
+        let date = Date {
+            month,
+            day,
+            hour,
+            year: None,
+        };
         let solar = Solar::new(lat, lon, std_mer);
         let albedo = 0.2;
 
@@ -901,6 +906,52 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_gen_sun_mtx() -> Result<(), String> {
+        let solar = Solar::new(-41.41 * PI / 180., -174.87 * PI / 180., -180. * PI / 180.);
+        let day = CurrentWeather {
+            date: Date {
+                month: 1,
+                day: 1,
+                hour: 10.5,
+                year: None,
+            },
+            dew_point_temperature: 11.,
+            direct_normal_radiation: 538.,
+            diffuse_horizontal_radiation: 25.,
+            ..CurrentWeather::default()
+        };
+        let mut night = day;
+        night.date.hour = 0.5;
+
+        for mf in [1, 2] {
+            let mtx = PerezSky::gen_sun_mtx(mf, &solar, &[night, day], SkyUnits::Solar)?;
+            let r = ReinhartSky::new(mf);
+            assert_eq!(mtx.size(), (r.n_bins, 2));
+
+            // Same as a sky vector with only the sun
+            let exp = PerezSky::gen_sky_vec(
+                mf,
+                &solar,
+                day.date,
+                day,
+                SkyUnits::Solar,
+                0.0,
+                false,
+                true,
+            )?;
+            let mut direct = 0.0;
+            for bin in 0..r.n_bins {
+                assert_eq!(mtx.get(bin, 0)?, 0.0);
+                assert_eq!(mtx.get(bin, 1)?, exp.get(bin, 0)?);
+                direct += mtx.get(bin, 1)? * r.bin_solid_angle(bin);
+            }
+            // All the direct normal radiation is there
+            assert!((direct - 538.).abs() < 1e-6);
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_gen_sky_vec_solar_with_sun() -> Result<(), String> {
         let mf = 1;
@@ -910,7 +961,12 @@ mod tests {
         let month = 1;
         let day = 1;
         let hour = 5.5;
-        let date = Date { month, day, hour };
+        let date = Date {
+            month,
+            day,
+            hour,
+            year: None,
+        };
         let solar = Solar::new(lat, lon, std_mer);
         let albedo = 0.2;
 
@@ -985,7 +1041,12 @@ mod tests {
         let month = 1;
         let day = 1;
         let hour = 5.5;
-        let date = Date { month, day, hour };
+        let date = Date {
+            month,
+            day,
+            hour,
+            year: None,
+        };
         let solar = Solar::new(lat, lon, std_mer);
         let albedo = 0.2;
 
@@ -1059,7 +1120,12 @@ mod tests {
         let month = 1;
         let day = 1;
         let hour = 5.5;
-        let date = Date { month, day, hour };
+        let date = Date {
+            month,
+            day,
+            hour,
+            year: None,
+        };
         let solar = Solar::new(lat, lon, std_mer);
         let albedo = 0.2;
 
@@ -1134,7 +1200,12 @@ mod tests {
         let month = 1;
         let day = 1;
         let hour = 5.5;
-        let date = Date { month, day, hour };
+        let date = Date {
+            month,
+            day,
+            hour,
+            year: None,
+        };
         let solar = Solar::new(lat, lon, std_mer);
         let albedo = 0.2;
 
@@ -1211,6 +1282,7 @@ mod tests {
             month: 2,
             day: 2,
             hour: 12.0,
+            year: None,
         };
         let dew_point = 11.0;
         let direct_normal_irrad = 500.0;
@@ -1248,6 +1320,7 @@ mod tests {
             month: 2,
             day: 2,
             hour: 12.0,
+            year: None,
         };
         let dew_point = 11.0;
         let direct_normal_irrad = 500.0;
@@ -1281,6 +1354,7 @@ mod tests {
             month: 2,
             day: 2,
             hour: 12.0,
+            year: None,
         };
         let dew_point = 11.0;
         let direct_normal_irrad = 500.0;
@@ -1314,6 +1388,7 @@ mod tests {
             month: 4,
             day: 5,
             hour: 10.0,
+            year: None,
         };
         let dew_point = 11.0;
         let direct_normal_irrad = 600.0;
@@ -1351,6 +1426,7 @@ mod tests {
             month: 4,
             day: 5,
             hour: 10.0,
+            year: None,
         };
         let dew_point = 11.0;
         let direct_normal_irrad = 600.0;
@@ -1384,6 +1460,7 @@ mod tests {
             month: 4,
             day: 5,
             hour: 10.0,
+            year: None,
         };
         let dew_point = 11.0;
         let direct_normal_irrad = 600.0;
@@ -1413,6 +1490,7 @@ mod tests {
             month: 11,
             day: 7,
             hour: 16.0,
+            year: None,
         };
         let dew_point = 11.0;
         let direct_normal_irrad = 900.0;
@@ -1446,6 +1524,7 @@ mod tests {
             month: 11,
             day: 7,
             hour: 16.0,
+            year: None,
         };
         let dew_point = 11.0;
         let direct_normal_irrad = 900.0;
@@ -1475,6 +1554,7 @@ mod tests {
             month: 11,
             day: 7,
             hour: 16.0,
+            year: None,
         };
         let dew_point = 11.0;
         let direct_normal_irrad = 900.0;
@@ -1504,6 +1584,7 @@ mod tests {
             month: 1,
             day: 1,
             hour: 13.0,
+            year: None,
         };
         let dew_point = 11.0;
         let direct_normal_irrad = 300.0;
@@ -1537,6 +1618,7 @@ mod tests {
             month: 1,
             day: 1,
             hour: 13.0,
+            year: None,
         };
         let dew_point = 11.0;
         let direct_normal_irrad = 300.0;
@@ -1566,6 +1648,7 @@ mod tests {
             month: 1,
             day: 1,
             hour: 13.0,
+            year: None,
         };
         let dew_point = 11.0;
         let direct_normal_irrad = 300.0;
@@ -1602,6 +1685,7 @@ mod tests {
             month: 2,
             day: 2,
             hour: 12.0,
+            year: None,
         };
         let dew_point = 11.0;
         let direct_normal_irrad = 500.0;
@@ -1639,6 +1723,7 @@ mod tests {
             month: 2,
             day: 2,
             hour: 12.0,
+            year: None,
         };
         let dew_point = 11.0;
         let direct_normal_irrad = 500.0;
@@ -1672,6 +1757,7 @@ mod tests {
             month: 2,
             day: 2,
             hour: 12.0,
+            year: None,
         };
         let dew_point = 11.0;
         let direct_normal_irrad = 500.0;
@@ -1705,6 +1791,7 @@ mod tests {
             month: 4,
             day: 5,
             hour: 10.0,
+            year: None,
         };
         let dew_point = 11.0;
         let direct_normal_irrad = 600.0;
@@ -1742,6 +1829,7 @@ mod tests {
             month: 4,
             day: 5,
             hour: 10.0,
+            year: None,
         };
         let dew_point = 11.0;
         let direct_normal_irrad = 600.0;
@@ -1775,6 +1863,7 @@ mod tests {
             month: 4,
             day: 5,
             hour: 10.0,
+            year: None,
         };
         let dew_point = 11.0;
         let direct_normal_irrad = 600.0;
@@ -1804,6 +1893,7 @@ mod tests {
             month: 11,
             day: 7,
             hour: 16.0,
+            year: None,
         };
         let dew_point = 11.0;
         let direct_normal_irrad = 900.0;
@@ -1837,6 +1927,7 @@ mod tests {
             month: 11,
             day: 7,
             hour: 16.0,
+            year: None,
         };
         let dew_point = 11.0;
         let direct_normal_irrad = 900.0;
@@ -1866,6 +1957,7 @@ mod tests {
             month: 11,
             day: 7,
             hour: 16.0,
+            year: None,
         };
         let dew_point = 11.0;
         let direct_normal_irrad = 900.0;
@@ -1895,6 +1987,7 @@ mod tests {
             month: 1,
             day: 1,
             hour: 13.0,
+            year: None,
         };
         let dew_point = 11.0;
         let direct_normal_irrad = 300.0;
@@ -1928,6 +2021,7 @@ mod tests {
             month: 1,
             day: 1,
             hour: 13.0,
+            year: None,
         };
         let dew_point = 11.0;
         let direct_normal_irrad = 300.0;
@@ -1957,6 +2051,7 @@ mod tests {
             month: 1,
             day: 1,
             hour: 13.0,
+            year: None,
         };
         let dew_point = 11.0;
         let direct_normal_irrad = 300.0;
@@ -97,6 +97,10 @@ pub struct ReinhartSky {
 }
 
 impl ReinhartSky {
+    /// The number of patches over which the direct sun is distributed
+    /// (as in Radiance's `genskymtx`)
+    pub const SUN_PATCHES: usize = 4;
+
     /// Calculates the number of total bins in a Reinhart's discretization
     /// including the ground
     pub fn n_bins(mf: usize) -> usize {
@@ -302,6 +306,45 @@ impl ReinhartSky {
         Vector3D::new(dx, dy, dz)
     }
 
+    /// Maps the solar disc—located at `sun_position`—onto the patches of
+    /// the sky, as done by Radiance's `genskymtx`. Returns the
+    /// [`Self::SUN_PATCHES`] patches closest to the sun, each with a weight
+    /// that grows as the patch gets closer to the sun. The weights add up to 1.
+    ///
+    /// With finer subdivisions (i.e., `mf > 1`), the sun is spread over a
+    /// smaller portion of the sky, so its position is represented more
+    /// accurately.
+    pub fn sun_patches(&self, sun_position: Vector3D) -> [(usize, Float); Self::SUN_PATCHES] {
+        // Store the closest bin and the dot product between
+        // the sun position and the bin position
+        let mut closests = [(0, -1.); Self::SUN_PATCHES];
+        for bin in 0..self.n_bins {
+            let dot = self.bin_dir(bin) * sun_position;
+            for sun_index in 0..Self::SUN_PATCHES {
+                // A greated dot product implies being more close
+                if dot > closests[sun_index].1 {
+                    // Shift vector
+                    for k in (sun_index + 1..Self::SUN_PATCHES).rev() {
+                        closests[k] = closests[k - 1];
+                    }
+                    closests[sun_index] = (bin, dot);
+                    break;
+                }
+            }
+        }
+
+        // Set weights
+        let mut tot_weight = 0.0;
+        for (_, w) in closests.iter_mut() {
+            *w = 1. / (1.002 - *w);
+            tot_weight += *w;
+        }
+        for (_, w) in closests.iter_mut() {
+            *w /= tot_weight;
+        }
+        closests
+    }
+
     /// Creates  a new Reinhart sky discretization
     pub fn new(mf: usize) -> Self {
         if mf == 0 {
@@ -399,6 +442,53 @@ mod tests {
         assert_eq!(bin, 0);
     }
 
+    #[test]
+    fn test_sun_patches() {
+        for mf in [1, 2, 4] {
+            let r = ReinhartSky::new(mf);
+            let sun = Vector3D::new(0.3, -0.5, 0.6).get_normalized();
+            let patches = r.sun_patches(sun);
+
+            // Weights add up to one, and the closest patch weighs the most
+            let total: Float = patches.iter().map(|(_, w)| w).sum();
+            assert!((total - 1.).abs() < 1e-9);
+            assert!(patches.windows(2).all(|p| p[0].1 >= p[1].1));
+
+            // None of them is the ground
+            assert!(patches.iter().all(|(bin, _)| *bin != 0));
+        }
+
+        // A sun at the centre of a patch: as in genskymtx, each of the closest
+        // patches weighs 1/(1.002 - cos), where cos is the cosine of the angle
+        // between the sun and the centre of the patch
+        let r = ReinhartSky::new(2);
+        let sun = r.bin_dir(10);
+        let patches = r.sun_patches(sun);
+        let raw: Vec<Float> = patches
+            .iter()
+            .map(|(bin, _)| 1. / (1.002 - r.bin_dir(*bin) * sun))
+            .collect();
+        let total: Float = raw.iter().sum();
+        for ((_, w), raw) in patches.iter().zip(raw.iter()) {
+            assert!((w - raw / total).abs() < 1e-12);
+        }
+
+        // The patch itself, its two neighbours in the same row, and the
+        // closest one in the row above
+        let exp = [
+            (10, 0.5585242157724338),
+            (9, 0.14969729532001647),
+            (11, 0.14969729532001647),
+            (70, 0.1420811935875332),
+        ];
+        for ((bin, w), (exp_bin, exp_w)) in patches.iter().zip(exp.iter()) {
+            assert_eq!(bin, exp_bin);
+            assert!((w - exp_w).abs() < 1e-9, "{} vs {}", w, exp_w);
+        }
+        let total: Float = patches.iter().map(|(_, w)| w).sum();
+        assert!((total - 1.).abs() < 1e-12);
+    }
+
     #[test]
     fn test_row_altitude() -> Result<(), String> {
         fn check(mf: usize) -> Result<(), String> {
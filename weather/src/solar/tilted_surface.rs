@@ -0,0 +1,287 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use super::{air_mass, PerezSky, Solar, Time};
+use crate::{CurrentWeather, Float};
+use geometry::Vector3D;
+
+/// The brightening coefficients of Perez et al.'s (1990) irradiance
+/// model (Table 6 of the paper), for each sky clearness category:
+/// `[f11, f12, f13, f21, f22, f23]`
+const PEREZ_COEFFICIENTS: [[Float; 6]; 8] = [
+    [
+        -0.0083117, 0.5877285, -0.0620636, -0.0596012, 0.0721249, -0.0220216,
+    ],
+    [
+        0.1299457, 0.6825954, -0.1513752, -0.0189325, 0.0659650, -0.0288748,
+    ],
+    [
+        0.3296958, 0.4868735, -0.2210958, 0.0554140, -0.0639588, -0.0260542,
+    ],
+    [
+        0.5682053, 0.1874525, -0.2951290, 0.1088631, -0.1519229, -0.0139754,
+    ],
+    [
+        0.8730280, -0.3920403, -0.3616149, 0.2255647, -0.4620442, 0.0012448,
+    ],
+    [
+        1.1326077, -1.2367284, -0.4118494, 0.2877813, -0.8230357, 0.0558651,
+    ],
+    [
+        1.0601591, -1.5999137, -0.3589221, 0.2642124, -1.1272340, 0.1310694,
+    ],
+    [
+        0.6777470, -0.3272588, -0.2504286, 0.1561313, -1.3765031, 0.2506212,
+    ],
+];
+
+/// The model used for calculating the diffuse radiation that a
+/// tilted surface receives from the sky
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DiffuseSkyModel {
+    /// The sky has the same radiance in all directions
+    Isotropic,
+
+    /// Perez et al.'s (1990) model, which accounts for the brightening
+    /// of the sky around the sun and close to the horizon
+    #[default]
+    Perez,
+}
+
+/// The solar irradiance (in W/m2) received by a tilted surface, split
+/// into its components
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TiltedIrradiance {
+    /// The irradiance coming directly from the sun
+    pub beam: Float,
+
+    /// The diffuse irradiance coming from the sky
+    pub sky_diffuse: Float,
+
+    /// The irradiance reflected by the ground
+    pub ground_reflected: Float,
+}
+
+impl TiltedIrradiance {
+    /// The total irradiance received by the surface
+    pub fn total(&self) -> Float {
+        self.beam + self.sky_diffuse + self.ground_reflected
+    }
+}
+
+impl Solar {
+    /// Calculates the angle of incidence (in radians) of the sun on a
+    /// surface facing `surface_normal` (Z is up, Y is North and X is East),
+    /// at a certain time. Returns `None` if the sun is below the horizon.
+    ///
+    /// Angles larger than $`\pi/2`$ mean that the sun is behind the surface.
+    pub fn incidence_angle(&self, surface_normal: Vector3D, n: Time) -> Option<Float> {
+        let sun = self.sun_position(n)?;
+        let cos_theta = sun * surface_normal.get_normalized();
+        Some(cos_theta.clamp(-1., 1.).acos())
+    }
+
+    /// Calculates the solar irradiance received by a surface facing
+    /// `surface_normal` at a certain time, based on the direct normal,
+    /// diffuse horizontal and global horizontal radiation in the `weather`.
+    /// The ground has a reflectance of `albedo`.
+    ///
+    /// The diffuse radiation coming from the sky is calculated according
+    /// to the `sky_model`, while the ground is assumed to reflect
+    /// isotropically.
+    ///
+    /// ```
+    /// use weather::{CurrentWeather, DiffuseSkyModel, Solar, Time};
+    /// use geometry::Vector3D;
+    ///
+    /// let solar = Solar::new(0.7, 0., 0.);
+    /// let weather = CurrentWeather {
+    ///     direct_normal_radiation: 800.,
+    ///     diffuse_horizontal_radiation: 100.,
+    ///     global_horizontal_radiation: 700.,
+    ///     ..CurrentWeather::default()
+    /// };
+    /// // Noon, at the end of June
+    /// let n = Time::Solar(180.5);
+    /// let south = Vector3D::new(0., -1., 0.);
+    /// let irradiance = solar.tilted_irradiance(south, n, &weather, 0.2, DiffuseSkyModel::Perez);
+    /// assert!(irradiance.beam > 0.);
+    /// assert!(irradiance.total() < 700.);
+    /// ```
+    pub fn tilted_irradiance(
+        &self,
+        surface_normal: Vector3D,
+        n: Time,
+        weather: &CurrentWeather,
+        albedo: Float,
+        sky_model: DiffuseSkyModel,
+    ) -> TiltedIrradiance {
+        let normal = surface_normal.get_normalized();
+        let cos_tilt = normal.z.clamp(-1., 1.);
+        let sin_tilt = (1. - cos_tilt * cos_tilt).sqrt();
+
+        let global = weather.global_horizontal_radiation.max(0.);
+        let ground_reflected = global * albedo * (1. - cos_tilt) / 2.;
+
+        let sun = match self.sun_position(n) {
+            Some(sun) => sun,
+            None => {
+                return TiltedIrradiance {
+                    ground_reflected,
+                    ..TiltedIrradiance::default()
+                }
+            }
+        };
+        let direct = weather.direct_normal_radiation.max(0.);
+        let diffuse = weather.diffuse_horizontal_radiation.max(0.);
+        let cos_theta = (sun * normal).max(0.);
+        let beam = direct * cos_theta;
+
+        let isotropic = (1. + cos_tilt) / 2.;
+        let sky_diffuse = match sky_model {
+            DiffuseSkyModel::Isotropic => diffuse * isotropic,
+            DiffuseSkyModel::Perez if diffuse <= 0. => 0.,
+            DiffuseSkyModel::Perez => {
+                let zenith = sun.z.clamp(-1., 1.).acos();
+                let epsilon = PerezSky::sky_clearness(diffuse, direct, zenith);
+                let day = self.unwrap_solar_time(n);
+                let delta =
+                    diffuse * air_mass(zenith) / self.normal_extraterrestrial_radiation(day);
+                let [f11, f12, f13, f21, f22, f23] =
+                    PEREZ_COEFFICIENTS[PerezSky::clearness_category(epsilon)];
+                let f1 = (f11 + f12 * delta + f13 * zenith).max(0.);
+                let f2 = f21 + f22 * delta + f23 * zenith;
+                // Ratio between the circumsolar radiation on the
+                // surface and on the horizontal
+                let a = cos_theta;
+                let b = sun.z.max((85. as Float).to_radians().cos());
+                let r = diffuse * ((1. - f1) * isotropic + f1 * a / b + f2 * sin_tilt);
+                r.max(0.)
+            }
+        };
+
+        TiltedIrradiance {
+            beam,
+            sky_diffuse,
+            ground_reflected,
+        }
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use calendar::Date;
+    use validate::assert_close;
+
+    fn weather() -> CurrentWeather {
+        CurrentWeather {
+            direct_normal_radiation: 600.,
+            diffuse_horizontal_radiation: 150.,
+            global_horizontal_radiation: 550.,
+            ..CurrentWeather::default()
+        }
+    }
+
+    #[test]
+    fn test_incidence_angle() -> Result<(), String> {
+        // Example 1.6.1 of Duffie and Beckman: Madison, Wisconsin, at 10:30
+        // (solar time) on February 13, surface tilted 45° pointing 15° west of south
+        let solar = Solar::new((43. as Float).to_radians(), 0., 0.);
+        let day = Date {
+            month: 2,
+            day: 13,
+            hour: 10.5,
+            year: None,
+        }
+        .day_of_year();
+        let n = Time::Solar(day);
+        let (beta, gamma) = ((45. as Float).to_radians(), (15. as Float).to_radians());
+        let normal = Vector3D::new(
+            -gamma.sin() * beta.sin(),
+            -gamma.cos() * beta.sin(),
+            beta.cos(),
+        );
+        let angle = solar.incidence_angle(normal, n).ok_or("No sun")?;
+        assert_close!(angle.to_degrees(), 35., 0.2);
+
+        // Not normalized... same thing
+        let angle = solar.incidence_angle(normal * 3., n).ok_or("No sun")?;
+        assert_close!(angle.to_degrees(), 35., 0.2);
+
+        // At night
+        assert!(solar
+            .incidence_angle(normal, Time::Solar(day.floor() + 1. / 24.))
+            .is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_horizontal() {
+        // A horizontal surface gets the global horizontal radiation
+        let solar = Solar::new(-0.6, 0., 0.);
+        let up = Vector3D::new(0., 0., 1.);
+        let n = Time::Solar(10.5);
+        let sun = solar.sun_position(n).unwrap();
+        let mut w = weather();
+        w.global_horizontal_radiation = w.direct_normal_radiation * sun.z + 150.;
+        for model in [DiffuseSkyModel::Isotropic, DiffuseSkyModel::Perez] {
+            let irradiance = solar.tilted_irradiance(up, n, &w, 0.2, model);
+            assert_close!(irradiance.ground_reflected, 0.);
+            assert_close!(irradiance.total(), w.global_horizontal_radiation, 1.);
+        }
+    }
+
+    #[test]
+    fn test_tilted() -> Result<(), String> {
+        // Southern hemisphere summer: the sun is to the North at noon
+        let solar = Solar::new(-0.6, 0., 0.);
+        let n = Time::Solar(10.5);
+        let w = weather();
+        let sun = solar.sun_position(n).ok_or("No sun")?;
+
+        // Facing the sun
+        let iso = solar.tilted_irradiance(sun, n, &w, 0.2, DiffuseSkyModel::Isotropic);
+        let perez = solar.tilted_irradiance(sun, n, &w, 0.2, DiffuseSkyModel::Perez);
+        assert_close!(iso.beam, 600.);
+        assert_close!(perez.beam, 600.);
+        // Circumsolar brightening
+        assert!(perez.sky_diffuse > iso.sky_diffuse);
+
+        // Facing South... no sun
+        let south = Vector3D::new(0., -1., 0.);
+        let iso = solar.tilted_irradiance(south, n, &w, 0.2, DiffuseSkyModel::Isotropic);
+        assert_close!(iso.beam, 0.);
+        assert_close!(iso.sky_diffuse, 75.);
+        assert_close!(iso.ground_reflected, 55.);
+        let away = solar.tilted_irradiance(south, n, &w, 0.2, DiffuseSkyModel::Perez);
+        assert_close!(away.beam, 0.);
+        assert!(away.sky_diffuse < perez.sky_diffuse);
+
+        // At night, only reflected radiation
+        let night =
+            solar.tilted_irradiance(south, Time::Solar(10.), &w, 0.2, DiffuseSkyModel::Perez);
+        assert_close!(night.total(), 55.);
+        Ok(())
+    }
+}
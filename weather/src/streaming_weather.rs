@@ -0,0 +1,175 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+use crate::current_weather::CurrentWeather;
+use crate::WeatherTrait;
+use calendar::Date;
+use std::sync::mpsc::Receiver;
+use std::sync::Mutex;
+
+/// The source of the data of a [`StreamingWeather`]
+enum Source {
+    /// A function called every time weather data is needed
+    Callback(Box<dyn Fn(Date) -> CurrentWeather + Send + Sync>),
+
+    /// A channel through which weather readings arrive, and
+    /// the latest reading received
+    Channel(Mutex<(Receiver<CurrentWeather>, Option<CurrentWeather>)>),
+}
+
+/// A weather whose data comes from a live feed (e.g., a weather station,
+/// a forecast service or a hardware-in-the-loop setup) rather than from a
+/// file, for real-time and model-predictive-control applications.
+///
+/// ```
+/// use std::sync::mpsc::channel;
+/// use weather::{CurrentWeather, Date, StreamingWeather, WeatherTrait};
+///
+/// let (sender, receiver) = channel();
+/// let weather = StreamingWeather::from_receiver(receiver);
+///
+/// // e.g., from a thread reading a sensor
+/// sender.send(CurrentWeather {
+///     dry_bulb_temperature: 12.5,
+///     ..CurrentWeather::default()
+/// }).unwrap();
+///
+/// let date = Date { month: 6, day: 1, hour: 10., year: None };
+/// assert_eq!(weather.get_weather_data(date).dry_bulb_temperature, 12.5);
+/// ```
+pub struct StreamingWeather {
+    source: Source,
+}
+
+impl StreamingWeather {
+    /// Creates a `StreamingWeather` that calls `callback` with the
+    /// [`Date`] every time the simulation needs weather data
+    pub fn from_callback<F>(callback: F) -> Self
+    where
+        F: Fn(Date) -> CurrentWeather + Send + Sync + 'static,
+    {
+        Self {
+            source: Source::Callback(Box::new(callback)),
+        }
+    }
+
+    /// Creates a `StreamingWeather` that reads [`CurrentWeather`]s from a
+    /// channel.
+    ///
+    /// Every time the simulation needs weather data, the readings waiting
+    /// in the channel are consumed and the most recent one is used. If no
+    /// reading has arrived yet, this blocks until the first one does;
+    /// afterwards, the latest reading is reused until a new one arrives.
+    /// The `date` of the returned data is the one requested by the simulation.
+    ///
+    /// # Panics
+    /// Asking for weather data panics if the sender is dropped before
+    /// sending any reading.
+    pub fn from_receiver(receiver: Receiver<CurrentWeather>) -> Self {
+        Self {
+            source: Source::Channel(Mutex::new((receiver, None))),
+        }
+    }
+}
+
+impl WeatherTrait for StreamingWeather {
+    fn get_weather_data(&self, date: Date) -> CurrentWeather {
+        let mut data = match &self.source {
+            Source::Callback(callback) => callback(date),
+            Source::Channel(channel) => {
+                let mut channel = channel.lock().unwrap_or_else(|e| e.into_inner());
+                let (receiver, latest) = &mut *channel;
+                while let Ok(reading) = receiver.try_recv() {
+                    *latest = Some(reading);
+                }
+                match latest {
+                    Some(reading) => *reading,
+                    None => {
+                        let reading = receiver
+                            .recv()
+                            .expect("The weather feed was closed before sending any data");
+                        *latest = Some(reading);
+                        reading
+                    }
+                }
+            }
+        };
+        data.date = date;
+        data
+    }
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use crate::Float;
+    use std::sync::mpsc::channel;
+    use validate::assert_close;
+
+    const DATE: Date = Date {
+        month: 3,
+        day: 10,
+        hour: 8.,
+        year: None,
+    };
+
+    #[test]
+    fn test_callback() {
+        let weather = StreamingWeather::from_callback(|date: Date| CurrentWeather {
+            dry_bulb_temperature: date.hour as Float,
+            ..CurrentWeather::default()
+        });
+        let data = weather.get_weather_data(DATE);
+        assert_close!(data.dry_bulb_temperature, 8.);
+        assert_eq!(data.date, DATE);
+    }
+
+    #[test]
+    fn test_channel() {
+        let (sender, receiver) = channel();
+        let weather = StreamingWeather::from_receiver(receiver);
+        let reading = |t: Float| CurrentWeather {
+            dry_bulb_temperature: t,
+            ..CurrentWeather::default()
+        };
+
+        // Blocks until the first reading arrives
+        let handle = std::thread::spawn(move || {
+            sender.send(reading(10.)).unwrap();
+            sender
+        });
+        assert_close!(weather.get_weather_data(DATE).dry_bulb_temperature, 10.);
+        let sender = handle.join().unwrap();
+
+        // Uses the latest of the pending readings
+        sender.send(reading(11.)).unwrap();
+        sender.send(reading(12.)).unwrap();
+        let data = weather.get_weather_data(DATE);
+        assert_close!(data.dry_bulb_temperature, 12.);
+        assert_eq!(data.date, DATE);
+
+        // Reuses the latest reading, even if the feed is closed
+        drop(sender);
+        assert_close!(weather.get_weather_data(DATE).dry_bulb_temperature, 12.);
+    }
+}
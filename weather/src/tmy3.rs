@@ -0,0 +1,198 @@
+/*
+MIT License
+Copyright (c)  Germán Molina
+Permission is hereby granted, free of charge, to any person obtaining a copy
+of this software and associated documentation files (the "Software"), to deal
+in the Software without restriction, including without limitation the rights
+to use, copy, modify, merge, publish, distribute, sublicense, and/or sell
+copies of the Software, and to permit persons to whom the Software is
+furnished to do so, subject to the following conditions:
+The above copyright notice and this permission notice shall be included in all
+copies or substantial portions of the Software.
+THE SOFTWARE IS PROVIDED "AS IS", WITHOUT WARRANTY OF ANY KIND, EXPRESS OR
+IMPLIED, INCLUDING BUT NOT LIMITED TO THE WARRANTIES OF MERCHANTABILITY,
+FITNESS FOR A PARTICULAR PURPOSE AND NONINFRINGEMENT. IN NO EVENT SHALL THE
+AUTHORS OR COPYRIGHT HOLDERS BE LIABLE FOR ANY CLAIM, DAMAGES OR OTHER
+LIABILITY, WHETHER IN AN ACTION OF CONTRACT, TORT OR OTHERWISE, ARISING FROM,
+OUT OF OR IN CONNECTION WITH THE SOFTWARE OR THE USE OR OTHER DEALINGS IN THE
+SOFTWARE.
+*/
+
+use crate::csv_weather::{parse_number, split_line};
+use crate::{CurrentWeather, Location, RadiationInterpolation, Weather};
+use calendar::Date;
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use utils::SimpleError;
+
+/// The name of the column with the date, in the header of TMY3 files
+const DATE_COLUMN: &str = "Date (MM/DD/YYYY)";
+
+/// Checks whether some data seems to be in NREL's TMY3 format (i.e.,
+/// the second line is the header of the data)
+pub fn is_tmy3(src: &str) -> bool {
+    src.lines()
+        .nth(1)
+        .map(|l| l.trim_start().starts_with(DATE_COLUMN))
+        .unwrap_or(false)
+}
+
+/// Reads a weather file in NREL's TMY3 format
+pub fn from_file<P: AsRef<Path> + Display>(filename: P) -> Result<Weather, SimpleError> {
+    let src = fs::read_to_string(&filename)
+        .map_err(|_| SimpleError::Io(format!("Could not read TMY3 file '{}'", filename)))?;
+    parse(&src)
+}
+
+/// Parses weather data in NREL's TMY3 format.
+///
+/// The first line of these files contains the station number, city, state,
+/// timezone, latitude, longitude and elevation; the second one contains the
+/// name of the columns, and every following line contains the data of one
+/// hour. Dates and hours are given as `MM/DD/YYYY` and `HH:MM`, where hours
+/// go from `01:00` to `24:00` (i.e., at the end of each hour, like in EPW files).
+pub fn parse(src: &str) -> Result<Weather, SimpleError> {
+    let mut lines = src.lines().enumerate().map(|(i, l)| (i + 1, l.trim()));
+    let parse_err = |msg: &str| SimpleError::Parse(format!("Invalid TMY3 data: {}", msg));
+
+    // Location
+    let (ln, line) = lines.next().ok_or_else(|| parse_err("empty file"))?;
+    let fields = split_line(line);
+    let location = Location {
+        wmo: fields[0].clone(),
+        city: fields.get(1).cloned().unwrap_or_default(),
+        state: fields.get(2).cloned().unwrap_or_default(),
+        country: "USA".to_string(),
+        source: "TMY3".to_string(),
+        timezone: parse_number(&fields, 3, ln, "timezone")? as i8,
+        latitude: parse_number(&fields, 4, ln, "latitude")?.to_radians(),
+        longitude: parse_number(&fields, 5, ln, "longitude")?.to_radians(),
+        elevation: parse_number(&fields, 6, ln, "elevation")?,
+    };
+
+    // Header
+    let (_, line) = lines.next().ok_or_else(|| parse_err("no header"))?;
+    let header = split_line(line);
+    let column = |name: &str| {
+        header
+            .iter()
+            .position(|h| h == name)
+            .ok_or_else(|| parse_err(&format!("no '{}' column", name)))
+    };
+    let date = column(DATE_COLUMN)?;
+    let time = column("Time (HH:MM)")?;
+    let global = column("GHI (W/m^2)")?;
+    let direct = column("DNI (W/m^2)")?;
+    let diffuse = column("DHI (W/m^2)")?;
+    let sky_cover = column("OpqCld (tenths)")?;
+    let dry_bulb = column("Dry-bulb (C)")?;
+    let dew_point = column("Dew-point (C)")?;
+    let relative_humidity = column("RHum (%)")?;
+    let pressure = column("Pressure (mbar)")?;
+    let wind_direction = column("Wdir (degrees)")?;
+    let wind_speed = column("Wspd (m/s)")?;
+
+    let mut data = Vec::new();
+    for (ln, line) in lines.filter(|(_, l)| !l.is_empty()) {
+        let fields = split_line(line);
+        let get = |i: usize, name: &str| parse_number(&fields, i, ln, name);
+
+        let field = |i: usize| fields.get(i).map(|s| s.as_str()).unwrap_or("");
+        let invalid =
+            |what: &str| SimpleError::Parse(format!("Line {}: invalid {} '{}'", ln, what, line));
+        let mut mmdd = field(date).split('/');
+        let month: u8 = mmdd
+            .next()
+            .and_then(|m| m.parse().ok())
+            .ok_or_else(|| invalid("date"))?;
+        let day: u8 = mmdd
+            .next()
+            .and_then(|d| d.parse().ok())
+            .ok_or_else(|| invalid("date"))?;
+        let hour: crate::Float = field(time)
+            .split_once(':')
+            .and_then(|(h, _)| h.parse().ok())
+            .ok_or_else(|| invalid("time"))?;
+
+        data.push(CurrentWeather {
+            date: Date {
+                month,
+                day,
+                // Same convention as EPW files
+                hour: hour - 0.000001,
+                year: None,
+            },
+            dry_bulb_temperature: get(dry_bulb, "Dry-bulb")?,
+            dew_point_temperature: get(dew_point, "Dew-point")?,
+            global_horizontal_radiation: get(global, "GHI")?,
+            direct_normal_radiation: get(direct, "DNI")?,
+            diffuse_horizontal_radiation: get(diffuse, "DHI")?,
+            wind_speed: get(wind_speed, "Wspd")?,
+            wind_direction: get(wind_direction, "Wdir")?.to_radians(),
+            horizontal_infrared_radiation_intensity: None,
+            opaque_sky_cover: get(sky_cover, "OpqCld")? / 10.,
+            relative_humidity: get(relative_humidity, "RHum")? / 100.,
+            pressure: get(pressure, "Pressure")? * 100.,
+        });
+    }
+
+    if data.is_empty() {
+        return Err(parse_err("no data"));
+    }
+
+    Ok(Weather {
+        data,
+        location,
+        ground_temperature: Vec::new(),
+        typical_periods: Vec::new(),
+        daylight_saving: None,
+        radiation_interpolation: RadiationInterpolation::default(),
+    })
+}
+
+/***********/
+/* TESTING */
+/***********/
+
+#[cfg(test)]
+mod testing {
+    use super::*;
+    use crate::Float;
+    use validate::assert_close;
+
+    const SRC: &str = "690150,\"TWENTYNINE PALMS\",CA,-8.0,34.300,-116.167,626
+Date (MM/DD/YYYY),Time (HH:MM),ETR (W/m^2),ETRN (W/m^2),GHI (W/m^2),GHI source,GHI uncert (%),DNI (W/m^2),DNI source,DNI uncert (%),DHI (W/m^2),DHI source,DHI uncert (%),GH illum (lx),GH illum source,Global illum uncert (%),DN illum (lx),DN illum source,DN illum uncert (%),DH illum (lx),DH illum source,DH illum uncert (%),Zenith lum (cd/m^2),Zenith lum source,Zenith lum uncert (%),TotCld (tenths),TotCld source,TotCld uncert (code),OpqCld (tenths),OpqCld source,OpqCld uncert (code),Dry-bulb (C),Dry-bulb source,Dry-bulb uncert (code),Dew-point (C),Dew-point source,Dew-point uncert (code),RHum (%),RHum source,RHum uncert (code),Pressure (mbar),Pressure source,Pressure uncert (code),Wdir (degrees),Wdir source,Wdir uncert (code),Wspd (m/s),Wspd source,Wspd uncert (code),Hvis (m),Hvis source,Hvis uncert (code),CeilHgt (m),CeilHgt source,CeilHgt uncert (code),Pwat (cm),Pwat source,Pwat uncert (code),AOD (unitless),AOD source,AOD uncert (code),Alb (unitless),Alb source,Alb uncert (code),Lprecip depth (mm),Lprecip quantity (hr),Lprecip source,Lprecip uncert (code)
+01/01/1988,01:00,0,0,0,1,0,0,1,0,0,1,0,0,1,0,0,1,0,0,1,0,0,1,0,0,A,7,0,A,7,3.0,A,7,-5.0,A,7,56,A,7,940,A,7,0,A,7,0.0,A,7,-9900,?,0,77777,A,7,0.6,E,8,0.047,F,8,0.18,F,8,-9900,-9900,?,0
+01/01/1988,12:00,1044,1415,557,1,8,847,1,14,89,1,10,59400,1,8,89000,1,15,13100,1,9,4300,1,8,2,A,7,1,A,7,15.0,A,7,-6.0,A,7,24,A,7,950,A,7,330,A,7,4.1,A,7,-9900,?,0,77777,A,7,0.6,E,8,0.047,F,8,0.18,F,8,-9900,-9900,?,0
+";
+
+    #[test]
+    fn test_parse() -> Result<(), String> {
+        assert!(is_tmy3(SRC));
+        assert!(!is_tmy3("month,day,hour\n1,1,1"));
+
+        let weather = parse(SRC)?;
+        assert_eq!(weather.location.city, "TWENTYNINE PALMS");
+        assert_eq!(weather.location.timezone, -8);
+        assert_close!(weather.location.latitude, (34.3 as Float).to_radians());
+        assert_close!(weather.location.elevation, 626.);
+
+        assert_eq!(weather.data.len(), 2);
+        let w = &weather.data[1];
+        assert_eq!(w.date.month, 1);
+        assert_eq!(w.date.day, 1);
+        assert_close!(w.date.hour, 12., 1e-5);
+        assert_close!(w.global_horizontal_radiation, 557.);
+        assert_close!(w.direct_normal_radiation, 847.);
+        assert_close!(w.diffuse_horizontal_radiation, 89.);
+        assert_close!(w.dry_bulb_temperature, 15.);
+        assert_close!(w.dew_point_temperature, -6.);
+        assert_close!(w.relative_humidity, 0.24);
+        assert_close!(w.pressure, 95000.);
+        assert_close!(w.wind_direction, (330. as Float).to_radians());
+        assert_close!(w.wind_speed, 4.1);
+        assert_close!(w.opaque_sky_cover, 0.1);
+        Ok(())
+    }
+}
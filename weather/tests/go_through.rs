@@ -18,12 +18,14 @@ fn test_go_through() -> Result<(), String> {
             day: 1,
             month: 1,
             hour: 0.0,
+            year: None,
         };
 
         let end = Date {
             day: 31,
             month: 12,
             hour: 23.99999999,
+            year: None,
         };
 
         let dt = 60. * 60. / 20.;